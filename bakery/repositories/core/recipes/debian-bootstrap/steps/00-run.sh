@@ -20,6 +20,11 @@ case "${RUGIX_ARCH}" in
         exit 1
 esac
 
+if [ -n "${RUGIX_APT_PROXY:-}" ]; then
+    # Download packages through the proxy without persisting it in the system.
+    export http_proxy="${RUGIX_APT_PROXY}"
+fi
+
 if [ "${RECIPE_PARAM_SNAPSHOT}" != "" ]; then 
     mmdebstrap \
         --skip=check/qemu \
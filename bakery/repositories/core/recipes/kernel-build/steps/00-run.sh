@@ -0,0 +1,108 @@
+#!/bin/bash
+
+set -euo pipefail
+
+case "${RUGIX_ARCH}" in
+    "amd64")
+        KERNEL_ARCH="x86_64"
+        CROSS_COMPILE="x86_64-linux-gnu-"
+        KERNEL_IMAGE="arch/x86/boot/bzImage"
+        ;;
+    "arm64")
+        KERNEL_ARCH="arm64"
+        CROSS_COMPILE="aarch64-linux-gnu-"
+        KERNEL_IMAGE="arch/arm64/boot/Image"
+        ;;
+    "armv7" | "armhf" | "arm")
+        KERNEL_ARCH="arm"
+        CROSS_COMPILE="arm-linux-gnueabihf-"
+        KERNEL_IMAGE="arch/arm/boot/zImage"
+        ;;
+    *)
+        echo "Unsupported architecture '${RUGIX_ARCH}'."
+        exit 1
+esac
+
+KERNEL_CACHE_DIR="${RUGIX_CACHE_DIR}/kernel"
+SOURCE_ID=$(echo "${RECIPE_PARAM_SOURCE}#${RECIPE_PARAM_TAG}" | sha1sum | cut -d' ' -f1)
+SOURCE_DIR="${KERNEL_CACHE_DIR}/sources/${SOURCE_ID}"
+BUILD_DIR="${KERNEL_CACHE_DIR}/builds/${SOURCE_ID}-${RUGIX_ARCH}"
+
+# Fetch the kernel sources, if they are not cached already.
+if [ ! -d "${SOURCE_DIR}/.git" ]; then
+    rm -rf "${SOURCE_DIR}"
+    mkdir -p "$(dirname "${SOURCE_DIR}")"
+    git clone --depth 1 --branch "${RECIPE_PARAM_TAG}" "${RECIPE_PARAM_SOURCE}" "${SOURCE_DIR}"
+fi
+
+# Apply patches on a fresh copy of the sources.
+WORK_DIR=$(mktemp -d)
+trap 'rm -rf "${WORK_DIR}"' EXIT
+git -C "${SOURCE_DIR}" worktree prune
+git -C "${SOURCE_DIR}" worktree add --detach "${WORK_DIR}/linux" HEAD
+KERNEL_DIR="${WORK_DIR}/linux"
+trap 'git -C "${SOURCE_DIR}" worktree remove --force "${KERNEL_DIR}"; rm -rf "${WORK_DIR}"' EXIT
+
+for pattern in ${RECIPE_PARAM_PATCHES}; do
+    for patch in "${RUGIX_PROJECT_DIR}"/${pattern}; do
+        echo "Applying patch '${patch}'..."
+        git -C "${KERNEL_DIR}" apply "${patch}"
+        echo "${patch#"${RUGIX_PROJECT_DIR}/"}" >> "${LAYER_REBUILD_IF_CHANGED}"
+    done
+done
+
+export ARCH="${KERNEL_ARCH}"
+export CROSS_COMPILE
+export CCACHE_DIR="${RUGIX_CACHE_DIR}/ccache"
+# Keep the build reproducible.
+export KBUILD_BUILD_TIMESTAMP="@${SOURCE_DATE_EPOCH}"
+export KBUILD_BUILD_USER="rugix"
+export KBUILD_BUILD_HOST="rugix-bakery"
+
+MAKE_ARGS=(-C "${KERNEL_DIR}" "O=${BUILD_DIR}" "CC=ccache ${CROSS_COMPILE}gcc" "-j$(nproc)")
+
+mkdir -p "${BUILD_DIR}"
+make "${MAKE_ARGS[@]}" "${RECIPE_PARAM_DEFCONFIG}"
+
+FRAGMENTS=()
+for pattern in ${RECIPE_PARAM_FRAGMENTS}; do
+    for fragment in "${RUGIX_PROJECT_DIR}"/${pattern}; do
+        FRAGMENTS+=("${fragment}")
+        echo "${fragment#"${RUGIX_PROJECT_DIR}/"}" >> "${LAYER_REBUILD_IF_CHANGED}"
+    done
+done
+if [ ${#FRAGMENTS[@]} -gt 0 ]; then
+    "${KERNEL_DIR}/scripts/kconfig/merge_config.sh" -m -O "${BUILD_DIR}" \
+        "${BUILD_DIR}/.config" "${FRAGMENTS[@]}"
+    make "${MAKE_ARGS[@]}" olddefconfig
+fi
+
+make "${MAKE_ARGS[@]}"
+
+KERNEL_RELEASE=$(make -s "${MAKE_ARGS[@]}" kernelrelease)
+echo "Installing kernel ${KERNEL_RELEASE}..."
+
+mkdir -p "${RUGIX_ROOT_DIR}/boot"
+cp "${BUILD_DIR}/${KERNEL_IMAGE}" "${RUGIX_ROOT_DIR}/boot/vmlinuz-${KERNEL_RELEASE}"
+cp "${BUILD_DIR}/.config" "${RUGIX_ROOT_DIR}/boot/config-${KERNEL_RELEASE}"
+cp "${BUILD_DIR}/System.map" "${RUGIX_ROOT_DIR}/boot/System.map-${KERNEL_RELEASE}"
+
+make "${MAKE_ARGS[@]}" modules_install INSTALL_MOD_PATH="${RUGIX_ROOT_DIR}" INSTALL_MOD_STRIP=1
+
+DTBS_DIR="${RUGIX_ROOT_DIR}/usr/lib/linux-image-${KERNEL_RELEASE}"
+if [ "${KERNEL_ARCH}" != "x86_64" ]; then
+    make "${MAKE_ARGS[@]}" dtbs_install INSTALL_DTBS_PATH="${DTBS_DIR}"
+fi
+
+if [ "${RECIPE_PARAM_INSTALL_BOOT}" = "true" ]; then
+    BOOT_DIR="${RUGIX_LAYER_DIR}/roots/boot"
+    mkdir -p "${BOOT_DIR}"
+    cp "${BUILD_DIR}/${KERNEL_IMAGE}" "${BOOT_DIR}/$(basename "${KERNEL_IMAGE}")"
+    if [ -d "${DTBS_DIR}" ]; then
+        find "${DTBS_DIR}" -name "*.dtb" -exec cp {} "${BOOT_DIR}" \;
+        if find "${DTBS_DIR}" -name "*.dtbo" | grep -q .; then
+            mkdir -p "${BOOT_DIR}/overlays"
+            find "${DTBS_DIR}" -name "*.dtbo" -exec cp {} "${BOOT_DIR}/overlays" \;
+        fi
+    fi
+fi
@@ -15,6 +15,18 @@ if [ "${RECIPE_PARAM_RUGIX_ADMIN}" = "true" ]; then
     systemctl enable rugix-admin
 fi
 
+if command -v systemctl >/dev/null 2>&1; then
+    install -D -m 644 "${RECIPE_DIR}/files/rugix-first-boot.service" -t /usr/lib/systemd/system/
+
+    systemctl enable rugix-first-boot
+
+    if [ "${RECIPE_PARAM_USAGE_MONITOR}" = "true" ]; then
+        install -D -m 644 "${RECIPE_DIR}/files/rugix-usage-monitor.service" -t /usr/lib/systemd/system/
+
+        systemctl enable rugix-usage-monitor
+    fi
+fi
+
 
 prog_exists() {
     prog="$1";
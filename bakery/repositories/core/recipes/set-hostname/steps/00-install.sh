@@ -2,6 +2,8 @@
 
 set -euo pipefail
 
+echo "warning: \`core/set-hostname\` is deprecated, use \`core/system-settings\` instead" >&2
+
 echo "${RECIPE_PARAM_HOSTNAME}" > /etc/hostname
 
 cat > /etc/hosts << EOF
@@ -0,0 +1,83 @@
+#!/bin/sh
+
+set -eu
+
+HOSTNAME_PATTERN="localhost"
+
+if [ -f /etc/rugix/hostname.conf ]; then
+    . /etc/rugix/hostname.conf
+fi
+
+serial() {
+    if [ -r /proc/device-tree/serial-number ]; then
+        tr -d '\0' < /proc/device-tree/serial-number
+    elif [ -r /sys/class/dmi/id/product_serial ]; then
+        cat /sys/class/dmi/id/product_serial
+    else
+        machine_id
+    fi
+}
+
+mac() {
+    for interface in $(ls /sys/class/net 2>/dev/null | sort); do
+        if [ "$interface" != "lo" ] && [ -r "/sys/class/net/$interface/address" ]; then
+            tr -d ':' < "/sys/class/net/$interface/address"
+            return
+        fi
+    done
+}
+
+machine_id() {
+    # The machine id may not have been initialized yet, e.g., on the first boot.
+    if [ -r /etc/machine-id ]; then
+        cat /etc/machine-id
+    fi
+}
+
+# Reduce a value to lowercase letters, digits, and single dashes.
+sanitize() {
+    printf '%s' "$1" \
+        | tr 'A-Z' 'a-z' \
+        | tr -c 'a-z0-9-' '-' \
+        | tr -s '-' \
+        | sed -e 's/^-*//' -e 's/-*$//'
+}
+
+hostname=""
+pattern="${HOSTNAME_PATTERN}"
+while :; do
+    case "$pattern" in
+        *"{"*)
+            hostname="${hostname}${pattern%%\{*}"
+            rest="${pattern#*\{}"
+            placeholder="${rest%%\}*}"
+            pattern="${rest#*\}}"
+            case "${placeholder%%:*}" in
+                serial) value="$(serial)" ;;
+                mac) value="$(mac)" ;;
+                machine-id) value="$(machine_id)" ;;
+                *) value="" ;;
+            esac
+            value="$(sanitize "$value")"
+            case "$placeholder" in
+                *:*) value="$(printf '%s' "$value" | tail -c "${placeholder#*:}")" ;;
+            esac
+            hostname="${hostname}${value}"
+            ;;
+        *)
+            hostname="${hostname}${pattern}"
+            break
+            ;;
+    esac
+done
+
+# Hostnames are limited to 63 characters.
+hostname="$(sanitize "$(sanitize "$hostname" | cut -c 1-63)")"
+if [ -z "$hostname" ]; then
+    hostname="localhost"
+fi
+
+echo "$hostname" > /etc/hostname
+sed -i '/^127\.0\.1\.1[[:space:]]/d' /etc/hosts
+echo "127.0.1.1       $hostname" >> /etc/hosts
+hostname "$hostname"
@@ -0,0 +1,114 @@
+#!/bin/bash
+
+set -euo pipefail
+
+write_hostname() {
+    echo "$1" > /etc/hostname
+    if [ -f /etc/hosts ]; then
+        sed -i '/^127\.0\.1\.1[[:space:]]/d' /etc/hosts
+    else
+        echo "127.0.0.1       localhost" > /etc/hosts
+    fi
+    echo "127.0.1.1       $1" >> /etc/hosts
+}
+
+if [ -n "${RECIPE_PARAM_HOSTNAME}" ]; then
+    HOSTNAME_PATTERN="${RECIPE_PARAM_HOSTNAME}"
+    if ! [[ "${HOSTNAME_PATTERN}" =~ ^([A-Za-z0-9-]|\{(serial|mac|machine-id)(:[0-9]+)?\})+$ ]]; then
+        echo "invalid hostname pattern ${HOSTNAME_PATTERN@Q}" >&2
+        exit 1
+    fi
+    if [[ "${HOSTNAME_PATTERN}" != *"{"* ]]; then
+        if [ "${#HOSTNAME_PATTERN}" -gt 63 ]; then
+            echo "hostname ${HOSTNAME_PATTERN@Q} is longer than 63 characters" >&2
+            exit 1
+        fi
+        echo "setting hostname to ${HOSTNAME_PATTERN@Q}"
+        write_hostname "$(printf '%s' "${HOSTNAME_PATTERN}" | tr 'A-Z' 'a-z')"
+    else
+        # The placeholders can only be resolved on the device.
+        echo "installing service setting the hostname from ${HOSTNAME_PATTERN@Q}"
+        mkdir -p /etc/rugix
+        echo "HOSTNAME_PATTERN='${HOSTNAME_PATTERN}'" > /etc/rugix/hostname.conf
+        install -D -m 744 "${RECIPE_DIR}/files/set-hostname.sh" -t /usr/lib/rugix/scripts/
+        if command -v systemctl; then
+            install -D -m 644 "${RECIPE_DIR}/files/systemd/rugix-hostname.service" -t /usr/lib/systemd/system/
+            systemctl enable rugix-hostname
+        fi
+        if command -v rc-update; then
+            install -D -m 744 "${RECIPE_DIR}/files/openrc/rugix-hostname" -t /etc/init.d/
+            rc-update add rugix-hostname boot
+        fi
+        # Use the literal part of the pattern until the service runs.
+        prefix="$(printf '%s' "${HOSTNAME_PATTERN%%\{*}" | tr 'A-Z' 'a-z' | sed 's/-*$//')"
+        write_hostname "${prefix:-localhost}"
+    fi
+fi
+
+if [ -n "${RECIPE_PARAM_TIMEZONE}" ]; then
+    TIMEZONE="${RECIPE_PARAM_TIMEZONE}"
+    if [[ "/${TIMEZONE}/" == *"/../"* ]] || [[ "/${TIMEZONE}/" == *"//"* ]]; then
+        echo "invalid timezone ${TIMEZONE@Q}" >&2
+        exit 1
+    fi
+    if [ ! -f "/usr/share/zoneinfo/${TIMEZONE}" ]; then
+        echo "timezone ${TIMEZONE@Q} does not exist, is \`tzdata\` installed?" >&2
+        exit 1
+    fi
+    echo "setting timezone to ${TIMEZONE@Q}"
+    ln -sf "/usr/share/zoneinfo/${TIMEZONE}" /etc/localtime
+    echo "${TIMEZONE}" > /etc/timezone
+fi
+
+if [ -n "${RECIPE_PARAM_LOCALE}" ]; then
+    LOCALE="${RECIPE_PARAM_LOCALE}"
+    if [[ "${LOCALE}" == *[$'\n'\"/]* ]]; then
+        echo "invalid locale ${LOCALE@Q}" >&2
+        exit 1
+    fi
+    # Locale directories use a normalized codeset, e.g., `en_US.utf8`.
+    NORMALIZED="${LOCALE}"
+    if [[ "${LOCALE}" == *.* ]]; then
+        CODESET="${LOCALE#*.}"
+        CODESET="${CODESET,,}"
+        NORMALIZED="${LOCALE%%.*}.${CODESET//-/}"
+    fi
+    case "${LOCALE}" in
+        C|POSIX|C.UTF-8|C.utf8) ;;
+        *)
+            # We cannot look into the archive, so we assume that it contains the locale.
+            if [ ! -d "/usr/lib/locale/${NORMALIZED}" ] && [ ! -d "/usr/lib/locale/${LOCALE}" ] \
+                && [ ! -f /usr/lib/locale/locale-archive ]; then
+                echo "warning: locale ${LOCALE@Q} does not seem to be available in the system" >&2
+            fi
+            ;;
+    esac
+    echo "setting locale to ${LOCALE@Q}"
+    # Debian uses `/etc/default/locale`, systemd and other distributions use
+    # `/etc/locale.conf`.
+    if [ -d /etc/default ]; then
+        echo "LANG=${LOCALE}" > /etc/default/locale
+    fi
+    echo "LANG=${LOCALE}" > /etc/locale.conf
+fi
+
+if [ -n "${RECIPE_PARAM_KEYBOARD_LAYOUT}" ]; then
+    LAYOUT="${RECIPE_PARAM_KEYBOARD_LAYOUT}"
+    if ! [[ "${LAYOUT}" =~ ^[A-Za-z0-9_-]+$ ]]; then
+        echo "invalid keyboard layout ${LAYOUT@Q}" >&2
+        exit 1
+    fi
+    echo "setting keyboard layout to ${LAYOUT@Q}"
+    # Debian's `keyboard-configuration` uses `/etc/default/keyboard`, systemd uses
+    # `/etc/vconsole.conf`.
+    if [ -d /etc/default ]; then
+        cat > /etc/default/keyboard <<KEYBOARD
+XKBMODEL="pc105"
+XKBLAYOUT="${LAYOUT}"
+XKBVARIANT=""
+XKBOPTIONS=""
+BACKSPACE="guess"
+KEYBOARD
+    fi
+    echo "KEYMAP=${LAYOUT}" > /etc/vconsole.conf
+fi
@@ -0,0 +1,202 @@
+#!/bin/bash
+
+set -euo pipefail
+
+ROOTS_DIR="${RUGIX_LAYER_DIR}/roots"
+BOOT_DIR="${ROOTS_DIR}/boot"
+
+case "${RUGIX_ARCH}" in
+    "arm64")
+        FIT_ARCH="arm64"
+        ;;
+    "armv7" | "armhf" | "arm")
+        FIT_ARCH="arm"
+        ;;
+    "amd64")
+        FIT_ARCH="x86_64"
+        ;;
+    *)
+        echo "Unsupported architecture '${RUGIX_ARCH}'."
+        exit 1
+esac
+
+# Find the kernel, if not explicitly specified.
+if [ -n "${RECIPE_PARAM_KERNEL}" ]; then
+    KERNEL="${RUGIX_ROOT_DIR}/${RECIPE_PARAM_KERNEL}"
+else
+    KERNEL=""
+    for candidate in "${BOOT_DIR}/Image" "${BOOT_DIR}/zImage" "${RUGIX_ROOT_DIR}/boot/Image" \
+        "${RUGIX_ROOT_DIR}/boot/zImage" "${RUGIX_ROOT_DIR}"/boot/vmlinuz-*; do
+        if [ -f "${candidate}" ]; then
+            KERNEL="${candidate}"
+            break
+        fi
+    done
+fi
+if [ -z "${KERNEL}" ] || [ ! -f "${KERNEL}" ]; then
+    echo "Error: Unable to find kernel for FIT image."
+    exit 1
+fi
+
+INITRAMFS=""
+if [ -n "${RECIPE_PARAM_INITRAMFS}" ]; then
+    INITRAMFS="${RUGIX_ROOT_DIR}/${RECIPE_PARAM_INITRAMFS}"
+fi
+
+DTBS=()
+if [ -n "${RECIPE_PARAM_DTBS}" ]; then
+    for pattern in ${RECIPE_PARAM_DTBS}; do
+        for dtb in "${RUGIX_ROOT_DIR}"/${pattern}; do
+            if [ -f "${dtb}" ]; then
+                DTBS+=("${dtb}")
+            fi
+        done
+    done
+    if [ ${#DTBS[@]} -eq 0 ]; then
+        echo "Error: No device trees found matching '${RECIPE_PARAM_DTBS}'."
+        exit 1
+    fi
+fi
+
+if [ -n "${RECIPE_PARAM_KERNEL_LOAD}" ]; then
+    KERNEL_TYPE="kernel"
+    KERNEL_LOAD="${RECIPE_PARAM_KERNEL_LOAD}"
+    KERNEL_ENTRY="${RECIPE_PARAM_KERNEL_ENTRY:-${RECIPE_PARAM_KERNEL_LOAD}}"
+else
+    # The kernel is executed in place.
+    KERNEL_TYPE="kernel_noload"
+    KERNEL_LOAD="0x0"
+    KERNEL_ENTRY="0x0"
+fi
+
+SIGN=""
+if [ -n "${RECIPE_PARAM_KEY_DIR}" ]; then
+    KEY_DIR="${RUGIX_PROJECT_DIR}/${RECIPE_PARAM_KEY_DIR}"
+    if [ ! -f "${KEY_DIR}/${RECIPE_PARAM_KEY_NAME}.key" ]; then
+        echo "Error: Signing key '${KEY_DIR}/${RECIPE_PARAM_KEY_NAME}.key' does not exist."
+        exit 1
+    fi
+    SIGN="true"
+fi
+
+WORK_DIR=$(mktemp -d)
+trap 'rm -rf "${WORK_DIR}"' EXIT
+
+ITS="${WORK_DIR}/image.its"
+
+hash_node() {
+    cat << EOT
+            hash-1 {
+                algo = "sha256";
+            };
+EOT
+}
+
+{
+    cat << EOT
+/dts-v1/;
+
+/ {
+    description = "Rugix FIT image";
+    #address-cells = <1>;
+
+    images {
+        kernel-1 {
+            description = "Linux kernel";
+            data = /incbin/("${KERNEL}");
+            type = "${KERNEL_TYPE}";
+            arch = "${FIT_ARCH}";
+            os = "linux";
+            compression = "none";
+            load = <${KERNEL_LOAD}>;
+            entry = <${KERNEL_ENTRY}>;
+$(hash_node)
+        };
+EOT
+    if [ -n "${INITRAMFS}" ]; then
+        cat << EOT
+        ramdisk-1 {
+            description = "Initramfs";
+            data = /incbin/("${INITRAMFS}");
+            type = "ramdisk";
+            arch = "${FIT_ARCH}";
+            os = "linux";
+            compression = "none";
+$(hash_node)
+        };
+EOT
+    fi
+    idx=1
+    for dtb in "${DTBS[@]}"; do
+        cat << EOT
+        fdt-${idx} {
+            description = "$(basename "${dtb}")";
+            data = /incbin/("${dtb}");
+            type = "flat_dt";
+            arch = "${FIT_ARCH}";
+            compression = "none";
+$(hash_node)
+        };
+EOT
+        idx=$((idx + 1))
+    done
+    cat << EOT
+    };
+
+    configurations {
+        default = "conf-1";
+EOT
+    count=${#DTBS[@]}
+    if [ "${count}" -eq 0 ]; then
+        count=1
+    fi
+    for idx in $(seq 1 "${count}"); do
+        REGIONS="\"kernel\""
+        echo "        conf-${idx} {"
+        if [ ${#DTBS[@]} -gt 0 ]; then
+            echo "            description = \"$(basename "${DTBS[$((idx - 1))]}")\";"
+            echo "            fdt = \"fdt-${idx}\";"
+            REGIONS="${REGIONS}, \"fdt\""
+        fi
+        echo "            kernel = \"kernel-1\";"
+        if [ -n "${INITRAMFS}" ]; then
+            echo "            ramdisk = \"ramdisk-1\";"
+            REGIONS="${REGIONS}, \"ramdisk\""
+        fi
+        if [ -n "${SIGN}" ]; then
+            cat << EOT
+            signature-1 {
+                algo = "${RECIPE_PARAM_ALGO}";
+                key-name-hint = "${RECIPE_PARAM_KEY_NAME}";
+                sign-images = ${REGIONS};
+            };
+EOT
+        fi
+        echo "        };"
+    done
+    cat << EOT
+    };
+};
+EOT
+} > "${ITS}"
+
+OUTPUT="${ROOTS_DIR}/${RECIPE_PARAM_OUTPUT}"
+mkdir -p "$(dirname "${OUTPUT}")"
+
+MKIMAGE_ARGS=(-f "${ITS}")
+if [ -n "${SIGN}" ]; then
+    MKIMAGE_ARGS+=(-k "${KEY_DIR}" -r)
+    if [ -n "${RECIPE_PARAM_UBOOT_DTB}" ]; then
+        # Embed the public key into a copy of U-Boot's control device tree.
+        UBOOT_DTB="${RUGIX_ARTIFACTS_DIR}/u-boot-pubkey.dtb"
+        mkdir -p "${RUGIX_ARTIFACTS_DIR}"
+        cp "${RUGIX_PROJECT_DIR}/${RECIPE_PARAM_UBOOT_DTB}" "${UBOOT_DTB}"
+        MKIMAGE_ARGS+=(-K "${UBOOT_DTB}")
+    fi
+fi
+
+mkimage "${MKIMAGE_ARGS[@]}" "${OUTPUT}"
+
+if [ -n "${SIGN}" ] && [ -n "${RECIPE_PARAM_UBOOT_DTB}" ]; then
+    fit_check_sign -f "${OUTPUT}" -k "${UBOOT_DTB}"
+fi
@@ -0,0 +1,41 @@
+#!/bin/sh
+
+set -eu
+
+ZRAM_SIZE="50%"
+ZRAM_ALGORITHM="zstd"
+ZRAM_PRIORITY="100"
+
+if [ -f /etc/rugix/zram.conf ]; then
+    . /etc/rugix/zram.conf
+fi
+
+case "${1:-start}" in
+    start)
+        modprobe zram num_devices=1 2>/dev/null || true
+        if [ ! -e /sys/block/zram0 ]; then
+            echo "zram is not supported by the kernel" >&2
+            exit 1
+        fi
+        case "${ZRAM_SIZE}" in
+            *%)
+                # Size relative to the total memory.
+                MEM_KIB=$(awk '/^MemTotal:/ { print $2 }' /proc/meminfo)
+                SIZE="$((MEM_KIB * ${ZRAM_SIZE%\%} / 100))K"
+                ;;
+            *)
+                SIZE="${ZRAM_SIZE}"
+                ;;
+        esac
+        # Not all kernels support all algorithms, hence, we fall back to the default.
+        echo "${ZRAM_ALGORITHM}" >/sys/block/zram0/comp_algorithm 2>/dev/null \
+            || echo "compression algorithm ${ZRAM_ALGORITHM} is not supported" >&2
+        echo "${SIZE}" >/sys/block/zram0/disksize
+        mkswap /dev/zram0 >/dev/null
+        swapon -p "${ZRAM_PRIORITY}" /dev/zram0
+        ;;
+    stop)
+        swapoff /dev/zram0 2>/dev/null || true
+        echo 1 >/sys/block/zram0/reset
+        ;;
+esac
@@ -0,0 +1,22 @@
+#!/bin/bash
+
+set -euo pipefail
+
+install -D -m 744 "${RECIPE_DIR}/files/setup-zram.sh" -t /usr/lib/rugix/scripts/
+
+mkdir -p /etc/rugix
+cat >/etc/rugix/zram.conf <<ZRAM
+ZRAM_SIZE="${RECIPE_PARAM_SIZE}"
+ZRAM_ALGORITHM="${RECIPE_PARAM_ALGORITHM}"
+ZRAM_PRIORITY="${RECIPE_PARAM_SWAP_PRIORITY}"
+ZRAM
+
+if command -v systemctl; then
+    install -D -m 644 "${RECIPE_DIR}/files/systemd/rugix-zram.service" -t /usr/lib/systemd/system/
+    systemctl enable rugix-zram
+fi
+
+if command -v rc-update; then
+    install -D -m 744 "${RECIPE_DIR}/files/openrc/rugix-zram" -t /etc/init.d/
+    rc-update add rugix-zram boot
+fi
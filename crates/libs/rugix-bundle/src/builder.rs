@@ -61,6 +61,16 @@ pub fn pack(path: &Path, dst: &Path) -> BundleResult<()> {
             } else {
                 None
             },
+            type_metadata: if let manifest::DeliveryConfig::Metadata(metadata_config) =
+                &payload.delivery
+            {
+                Some(format::MetadataPayloadType {
+                    name: metadata_config.name.clone(),
+                    slot: metadata_config.slot.clone(),
+                })
+            } else {
+                None
+            },
             header_hash: Bytes {
                 raw: hash_algorithm
                     .hash::<Box<[u8]>>(&payload_header)
@@ -84,6 +94,7 @@ pub fn pack(path: &Path, dst: &Path) -> BundleResult<()> {
                         .collect(),
                     original_hash: encoding.original_hash.clone(),
                 }),
+            variant: payload.variant.clone(),
         });
         prepared_payloads.push(PreparedPayload {
             payload_header,
@@ -0,0 +1,69 @@
+//! CMS signatures of bundles.
+//!
+//! CMS signatures sign the encoded [`SignedMetadata`][crate::format::SignedMetadata] of a
+//! bundle. Verification is delegated to `openssl cms` and never loads the default
+//! certificates of the operating system.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use reportify::ResultExt;
+
+use crate::format::decode::decode_slice;
+use crate::format::SignedMetadata;
+use crate::reader::BundleReader;
+use crate::source::BundleSource;
+use crate::BundleResult;
+
+/// Verify a CMS signature of the bundle against the given root certificates.
+///
+/// Root certificates may be given as files or as directories of hashed certificates.
+/// Returns the PEM-encoded certificate of the signer if the signature is valid and the
+/// reason for rejecting it otherwise.
+pub fn verify<S: BundleSource>(
+    reader: &BundleReader<S>,
+    signature: &[u8],
+    root_certs: &[PathBuf],
+) -> BundleResult<Result<Vec<u8>, String>> {
+    let tempdir = tempfile::tempdir().whatever("unable to create temporary directory")?;
+    let signed_metadata_raw = tempdir.path().join("signed-metadata.raw");
+    let signed_metadata_cms = tempdir.path().join("signed-metadata.cms");
+    let signer_pem = tempdir.path().join("signer.pem");
+    std::fs::write(&signed_metadata_cms, signature).whatever("unable to write CMS signature")?;
+    let mut command = Command::new("openssl");
+    command
+        .args(["cms", "-verify", "-inform", "DER", "-in"])
+        .arg(&signed_metadata_cms)
+        .arg("-out")
+        .arg(&signed_metadata_raw)
+        .arg("-signer")
+        .arg(&signer_pem)
+        // Do not load OS default certificates.
+        .args(["-no-CAfile", "-no-CApath", "-no-CAstore"])
+        // Non-zero exit code on verification failure.
+        .arg("-verify_retcode");
+    for cert in root_certs {
+        command.arg(if cert.is_dir() { "-CApath" } else { "-CAfile" });
+        command.arg(cert);
+    }
+    let output = command.output().whatever("unable to run `openssl`")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("verification failed");
+        return Ok(Err(reason.trim().to_owned()));
+    }
+    let signed_metadata =
+        std::fs::read(&signed_metadata_raw).whatever("unable to read signed metadata")?;
+    let Ok(signed_metadata) = decode_slice::<SignedMetadata>(&signed_metadata) else {
+        return Ok(Err("invalid signed metadata".to_owned()));
+    };
+    if signed_metadata.header_hash != reader.header_hash(signed_metadata.header_hash.algorithm()) {
+        return Ok(Err("signature is for a different bundle header".to_owned()));
+    }
+    Ok(Ok(
+        std::fs::read(&signer_pem).whatever("unable to read signer certificate")?
+    ))
+}
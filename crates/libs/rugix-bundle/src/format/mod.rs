@@ -47,12 +47,16 @@ define_struct! {
         /// Slot where the payload should be installed to.
         pub type_slot[PAYLOAD_ENTRY_TYPE_SLOT]: Option<SlotPayloadType>,
         pub type_execute[PAYLOAD_ENTRY_TYPE_EXECUTE]: Option<ExecutePayloadType>,
+        /// Metadata about the contents of a slot.
+        pub type_metadata[PAYLOAD_ENTRY_TYPE_METADATA]: Option<MetadataPayloadType>,
         /// Hash of the payload header.
         pub header_hash[PAYLOAD_ENTRY_HEADER_HASH]: Bytes,
         /// Hash of the payload file.
         pub file_hash[PAYLOAD_ENTRY_FILE_HASH]: Bytes,
         /// Delta encoding.
         pub delta_encoding[PAYLOAD_ENTRY_DELTA_ENCODING]: Option<DeltaEncoding>,
+        /// Variant of the system the payload is for.
+        pub variant[PAYLOAD_ENTRY_VARIANT]: Option<String>,
     }
 }
 
@@ -98,6 +102,16 @@ define_struct! {
     }
 }
 
+define_struct! {
+    /// Metadata about the contents of a slot.
+    pub struct MetadataPayloadType {
+        /// Name of the metadata.
+        pub name[PAYLOAD_TYPE_METADATA_NAME]: String,
+        /// Slot the metadata is about.
+        pub slot[PAYLOAD_TYPE_METADATA_SLOT]: String,
+    }
+}
+
 define_struct! {
     /// Header of a payload.
     pub struct PayloadHeader {
@@ -111,6 +125,8 @@ define_struct! {
     pub struct Signatures {
         /// Embedded CMS signatures.
         pub cms_signatures[SIGNATURES_CMS_SIGNATURE]: Vec<Bytes>,
+        /// Embedded Sigstore bundles (JSON-encoded).
+        pub sigstore_bundles[SIGNATURES_SIGSTORE_BUNDLE]: Vec<Bytes>,
     }
 }
 
@@ -118,6 +134,7 @@ impl Default for Signatures {
     fn default() -> Self {
         Self {
             cms_signatures: Default::default(),
+            sigstore_bundles: Default::default(),
         }
     }
 }
@@ -147,17 +147,30 @@ define_tags! {
     /// Slot where the payload should be installed to.
     PAYLOAD_ENTRY_TYPE_SLOT = 0x45ca7e7e,
     PAYLOAD_ENTRY_TYPE_EXECUTE = 0x3adf32f5,
+    /// Metadata about the contents of a slot.
+    ///
+    /// Optional, as older readers can safely ignore metadata.
+    PAYLOAD_ENTRY_TYPE_METADATA = 0xcd050e74?,
     /// Hash of the payload's header.
     PAYLOAD_ENTRY_HEADER_HASH = 0x5f6a60b1,
     /// Hash of the payload's file.
     PAYLOAD_ENTRY_FILE_HASH = 0x0c8d1fd0,
     /// Payload entry delta encoding.
     PAYLOAD_ENTRY_DELTA_ENCODING = 0x272cdf9f,
+    /// Variant of the system the payload is for.
+    ///
+    /// Required, as older readers must not install payloads of other variants.
+    PAYLOAD_ENTRY_VARIANT = 0x2d41c7a9,
 
     PAYLOAD_TYPE_SLOT_SLOT = 0x1b231de7,
 
     PAYLOAD_TYPE_EXECUTE_HANDLER = 0x4b3836a2,
 
+    /// Name of the metadata.
+    PAYLOAD_TYPE_METADATA_NAME = 0x66d28a77,
+    /// Slot the metadata is about.
+    PAYLOAD_TYPE_METADATA_SLOT = 0x66d7d9a7,
+
     BLOCK_INDEX = 0x1ae50c8e,
 
     BUNDLE_HEADER_IS_INCREMENTAL = 0x20f3d16b,
@@ -173,6 +186,9 @@ define_tags! {
     /// CMS signature.
     SIGNATURES_CMS_SIGNATURE = 0x9795498f?,
 
+    /// Sigstore bundle with signature, certificate, and transparency log proof.
+    SIGNATURES_SIGSTORE_BUNDLE = 0xd834190d?,
+
     /// Payloads segment of the bundle.
     PAYLOADS = 0x1f38fba,
 
@@ -3,15 +3,17 @@
 //! Implementation of Rugix Ctrl's update bundle format.
 
 use std::io::{BufReader, BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use byte_calc::NumBytes;
 use format::decode::decode_slice;
 use format::BundleHeader;
-use reader::{expect_start, read_into_vec};
-use reportify::{Report, ResultExt};
+use reader::{expect_start, read_into_vec, BundleReader};
+use reportify::{bail, Report, ResultExt};
 use si_crypto_hashes::HashDigest;
-use source::FileSource;
+use sigstore::SigstoreTrust;
+use source::{BundleSource, FileSource};
+use tracing::{info, warn};
 
 use crate::format::encode::Encode;
 use crate::format::stlv::write_segment_start;
@@ -20,9 +22,11 @@ use crate::reader::read_optional_metadata;
 
 pub mod block_encoding;
 pub mod builder;
+pub mod cms;
 pub mod format;
 pub mod manifest;
 pub mod reader;
+pub mod sigstore;
 pub mod source;
 pub mod xdelta;
 
@@ -68,14 +72,87 @@ pub fn bundle_hash(bundle: &Path) -> BundleResult<HashDigest> {
 
 pub fn signed_metadata(bundle: &Path) -> BundleResult<Vec<u8>> {
     let hash = bundle_hash(&bundle).unwrap();
-    let metadata = SignedMetadata { header_hash: hash };
-    Ok(format::encode::to_vec(
-        &metadata,
-        format::tags::SIGNED_METADATA,
-    ))
+    Ok(encode_signed_metadata(hash))
+}
+
+/// Encode the metadata to sign for a bundle with the given header hash.
+pub fn encode_signed_metadata(header_hash: HashDigest) -> Vec<u8> {
+    let metadata = SignedMetadata { header_hash };
+    format::encode::to_vec(&metadata, format::tags::SIGNED_METADATA)
 }
 
 pub fn add_bundle_signature(bundle: &Path, signature: Vec<u8>, out: &Path) -> BundleResult<()> {
+    update_bundle_signatures(bundle, out, |signatures| {
+        signatures.cms_signatures.push(Bytes { raw: signature });
+    })
+}
+
+/// Add a Sigstore bundle with a signature of the signed metadata to a bundle.
+pub fn add_bundle_sigstore_bundle(
+    bundle: &Path,
+    sigstore_bundle: Vec<u8>,
+    out: &Path,
+) -> BundleResult<()> {
+    update_bundle_signatures(bundle, out, |signatures| {
+        signatures.sigstore_bundles.push(Bytes {
+            raw: sigstore_bundle,
+        });
+    })
+}
+
+/// Verify that the bundle has a valid CMS or Sigstore signature.
+///
+/// CMS signatures are verified against the given root certificates and Sigstore
+/// signatures against the given trust configuration.
+pub fn verify_signatures<S: BundleSource>(
+    reader: &BundleReader<S>,
+    root_certs: &[PathBuf],
+    sigstore_trust: &SigstoreTrust,
+) -> BundleResult<()> {
+    let Some(signatures) = reader.signatures() else {
+        bail!("no signatures found in bundle");
+    };
+    if root_certs.is_empty() && !sigstore_trust.is_configured() {
+        bail!("no root certificates or Sigstore trust provided for signature verification");
+    }
+    if root_certs.len() > 1 {
+        bail!("multiple root certificates are not yet supported");
+    }
+    info!("checking bundle signatures");
+    // CMS signatures can only be verified with a root certificate.
+    if !root_certs.is_empty() {
+        for signature in &signatures.cms_signatures {
+            match cms::verify(reader, &signature.raw, root_certs)? {
+                Ok(_) => {
+                    info!("found valid signature");
+                    return Ok(());
+                }
+                Err(reason) => warn!("rejecting CMS signature: {reason}"),
+            }
+        }
+    }
+    if sigstore_trust.is_configured() {
+        let signed_metadata =
+            encode_signed_metadata(reader.header_hash(reader.header().hash_algorithm));
+        for sigstore_bundle in &signatures.sigstore_bundles {
+            match sigstore::verify(sigstore_trust, &signed_metadata, &sigstore_bundle.raw) {
+                Ok(()) => {
+                    info!("found valid Sigstore signature");
+                    return Ok(());
+                }
+                Err(error) => warn!("rejecting Sigstore signature: {error}"),
+            }
+        }
+    }
+    bail!("no valid signature found");
+}
+
+/// Copy a bundle while updating its signatures.
+fn update_bundle_signatures(
+    bundle: &Path,
+    out: &Path,
+    update: impl FnOnce(&mut format::Signatures),
+) -> BundleResult<()> {
     let bundle_file =
         BufReader::new(std::fs::File::open(bundle).whatever("unable to open bundle file")?);
     let mut source = FileSource::new(bundle_file);
@@ -96,7 +173,7 @@ pub fn add_bundle_signature(bundle: &Path, signature: Vec<u8>, out: &Path) -> Bu
     bundle_file.write_all(&header_bytes).unwrap();
     // Read existing signatures.
     let mut signatures = read_optional_metadata(&mut source)?.unwrap_or_default();
-    signatures.cms_signatures.push(Bytes { raw: signature });
+    update(&mut signatures);
     // Write signature section.
     signatures
         .encode(&mut bundle_file, format::tags::SIGNATURES)
@@ -23,6 +23,8 @@ pub mod manifest {
         pub update_type: UpdateType,
         #[doc = ""]
         pub hash_algorithm: ::std::option::Option<HashAlgorithm>,
+        #[doc = "Release version of the system contained in the bundle.\n\nAs the manifest is part of the bundle header, the version is covered by the\nsignatures of the bundle.\n"]
+        pub version: ::std::option::Option<::std::string::String>,
         #[doc = ""]
         pub payloads: ::std::vec::Vec<Payload>,
     }
@@ -33,6 +35,7 @@ pub mod manifest {
                 update_type,
                 payloads,
                 hash_algorithm: ::std::default::Default::default(),
+                version: ::std::default::Default::default(),
             }
         }
         #[doc = "Sets the value of `update_type`."]
@@ -61,6 +64,22 @@ pub mod manifest {
             self.hash_algorithm = hash_algorithm;
             self
         }
+        #[doc = "Sets the value of `version`."]
+        pub fn set_version(
+            &mut self,
+            version: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.version = version;
+            self
+        }
+        #[doc = "Sets the value of `version`."]
+        pub fn with_version(
+            mut self,
+            version: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.version = version;
+            self
+        }
         #[doc = "Sets the value of `payloads`."]
         pub fn set_payloads(&mut self, payloads: ::std::vec::Vec<Payload>) -> &mut Self {
             self.payloads = payloads;
@@ -79,12 +98,16 @@ pub mod manifest {
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "BundleManifest", 3usize)?;
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "BundleManifest", 4usize)?;
             __record.serialize_field("update-type", &self.update_type)?;
             __record.serialize_optional_field(
                 "hash-algorithm",
                 ::core::option::Option::as_ref(&self.hash_algorithm),
             )?;
+            __record.serialize_optional_field(
+                "version",
+                ::core::option::Option::as_ref(&self.version),
+            )?;
             __record.serialize_field("payloads", &self.payloads)?;
             __record.end()
         }
@@ -121,7 +144,7 @@ pub mod manifest {
                                 return ::core::result::Result::Err(
                                     __serde::de::Error::invalid_length(
                                         0usize,
-                                        &"record with 3 fields",
+                                        &"record with 4 fields",
                                     ),
                                 );
                             }
@@ -133,25 +156,37 @@ pub mod manifest {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 3 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
                             );
                         }
                     };
                     let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<
                         ::std::vec::Vec<Payload>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 3 fields"),
+                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
                             );
                         }
                     };
                     ::core::result::Result::Ok(BundleManifest {
                         update_type: __field0,
                         hash_algorithm: __field1,
-                        payloads: __field2,
+                        version: __field2,
+                        payloads: __field3,
                     })
                 }
                 #[inline]
@@ -164,16 +199,16 @@ pub mod manifest {
                 {
                     #[doc(hidden)]
                     const __IDENTIFIERS: &'static [&'static str] =
-                        &["update-type", "hash-algorithm", "payloads"];
+                        &["update-type", "hash-algorithm", "version", "payloads"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"update-type\", \"hash-algorithm\", \"payloads\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"update-type\", \"hash-algorithm\", \"version\", \"payloads\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
                         __Identifier2,
+                        __Identifier3,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -197,6 +232,7 @@ pub mod manifest {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -214,9 +250,12 @@ pub mod manifest {
                                 "hash-algorithm" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
-                                "payloads" => {
+                                "version" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier2)
                                 }
+                                "payloads" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -234,9 +273,12 @@ pub mod manifest {
                                 b"hash-algorithm" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
-                                b"payloads" => {
+                                b"version" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier2)
                                 }
+                                b"payloads" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -259,7 +301,10 @@ pub mod manifest {
                         ::core::option::Option::None;
                     let mut __field1: ::core::option::Option<::std::option::Option<HashAlgorithm>> =
                         ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<::std::vec::Vec<Payload>> =
+                    let mut __field2: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<::std::vec::Vec<Payload>> =
                         ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -295,11 +340,25 @@ pub mod manifest {
                                 if ::core::option::Option::is_some(&__field2) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "payloads",
+                                            "version",
                                         ),
                                     );
                                 }
                                 __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "payloads",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<::std::vec::Vec<Payload>>(
                                         &mut __map,
                                     )?,
@@ -325,6 +384,10 @@ pub mod manifest {
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
                     let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field3 = match __field3 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
@@ -335,13 +398,14 @@ pub mod manifest {
                     ::core::result::Result::Ok(BundleManifest {
                         update_type: __field0,
                         hash_algorithm: __field1,
-                        payloads: __field2,
+                        version: __field2,
+                        payloads: __field3,
                     })
                 }
             }
             #[doc(hidden)]
             const __FIELDS: &'static [&'static str] =
-                &["update-type", "hash-algorithm", "payloads"];
+                &["update-type", "hash-algorithm", "version", "payloads"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
                 "BundleManifest",
@@ -531,6 +595,10 @@ pub mod manifest {
         pub block_encoding: ::std::option::Option<BlockEncoding>,
         #[doc = "Payload file has been delta encoded.\n\nSpecifies the delta encoding used to produce the payload file.\n"]
         pub delta_encoding: ::std::option::Option<DeltaEncoding>,
+        #[doc = "Payload contains a dm-verity hash tree.\n"]
+        pub verity: ::std::option::Option<PayloadVerity>,
+        #[doc = "Variant of the system the payload is for.\n\nPayloads without a variant are installed on all systems.\n"]
+        pub variant: ::std::option::Option<::std::string::String>,
     }
     impl Payload {
         #[doc = "Creates a new [`Payload`]."]
@@ -540,6 +608,8 @@ pub mod manifest {
                 filename,
                 block_encoding: ::std::default::Default::default(),
                 delta_encoding: ::std::default::Default::default(),
+                verity: ::std::default::Default::default(),
+                variant: ::std::default::Default::default(),
             }
         }
         #[doc = "Sets the value of `delivery`."]
@@ -594,6 +664,32 @@ pub mod manifest {
             self.delta_encoding = delta_encoding;
             self
         }
+        #[doc = "Sets the value of `verity`."]
+        pub fn set_verity(&mut self, verity: ::std::option::Option<PayloadVerity>) -> &mut Self {
+            self.verity = verity;
+            self
+        }
+        #[doc = "Sets the value of `verity`."]
+        pub fn with_verity(mut self, verity: ::std::option::Option<PayloadVerity>) -> Self {
+            self.verity = verity;
+            self
+        }
+        #[doc = "Sets the value of `variant`."]
+        pub fn set_variant(
+            &mut self,
+            variant: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.variant = variant;
+            self
+        }
+        #[doc = "Sets the value of `variant`."]
+        pub fn with_variant(
+            mut self,
+            variant: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.variant = variant;
+            self
+        }
     }
     #[automatically_derived]
     impl __serde::Serialize for Payload {
@@ -602,7 +698,7 @@ pub mod manifest {
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "Payload", 4usize)?;
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "Payload", 6usize)?;
             __record.serialize_field("delivery", &self.delivery)?;
             __record.serialize_field("filename", &self.filename)?;
             __record.serialize_optional_field(
@@ -613,6 +709,12 @@ pub mod manifest {
                 "delta-encoding",
                 ::core::option::Option::as_ref(&self.delta_encoding),
             )?;
+            __record
+                .serialize_optional_field("verity", ::core::option::Option::as_ref(&self.verity))?;
+            __record.serialize_optional_field(
+                "variant",
+                ::core::option::Option::as_ref(&self.variant),
+            )?;
             __record.end()
         }
     }
@@ -648,7 +750,7 @@ pub mod manifest {
                                 return ::core::result::Result::Err(
                                     __serde::de::Error::invalid_length(
                                         0usize,
-                                        &"record with 4 fields",
+                                        &"record with 6 fields",
                                     ),
                                 );
                             }
@@ -660,7 +762,7 @@ pub mod manifest {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 6 fields"),
                             );
                         }
                     };
@@ -671,7 +773,7 @@ pub mod manifest {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(2usize, &"record with 6 fields"),
                             );
                         }
                     };
@@ -682,7 +784,29 @@ pub mod manifest {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(3usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<PayloadVerity>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(5usize, &"record with 6 fields"),
                             );
                         }
                     };
@@ -691,6 +815,8 @@ pub mod manifest {
                         filename: __field1,
                         block_encoding: __field2,
                         delta_encoding: __field3,
+                        verity: __field4,
+                        variant: __field5,
                     })
                 }
                 #[inline]
@@ -702,10 +828,16 @@ pub mod manifest {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] =
-                        &["delivery", "filename", "block-encoding", "delta-encoding"];
+                    const __IDENTIFIERS: &'static [&'static str] = &[
+                        "delivery",
+                        "filename",
+                        "block-encoding",
+                        "delta-encoding",
+                        "verity",
+                        "variant",
+                    ];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"delivery\", \"filename\", \"block-encoding\", \"delta-encoding\"]" ;
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"delivery\", \"filename\", \"block-encoding\", \"delta-encoding\", \"verity\", \"variant\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -713,6 +845,8 @@ pub mod manifest {
                         __Identifier1,
                         __Identifier2,
                         __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -737,6 +871,8 @@ pub mod manifest {
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -760,6 +896,10 @@ pub mod manifest {
                                 "delta-encoding" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
+                                "verity" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                "variant" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -783,6 +923,12 @@ pub mod manifest {
                                 b"delta-encoding" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
+                                b"verity" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                b"variant" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -809,6 +955,11 @@ pub mod manifest {
                         ::core::option::Option::None;
                     let mut __field3: ::core::option::Option<::std::option::Option<DeltaEncoding>> =
                         ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<::std::option::Option<PayloadVerity>> =
+                        ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -869,51 +1020,658 @@ pub mod manifest {
                                     >(&mut __map)?,
                                 );
                             }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "verity",
+                                        ),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<PayloadVerity>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "variant",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
                             _ => {
                                 __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
                                     &mut __map,
                                 )?;
                             }
                         }
-                    }
-                    let __field0 = match __field0 {
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("delivery"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("filename"),
+                            );
+                        }
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field4 = match __field4 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(Payload {
+                        delivery: __field0,
+                        filename: __field1,
+                        block_encoding: __field2,
+                        delta_encoding: __field3,
+                        verity: __field4,
+                        variant: __field5,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &[
+                "delivery",
+                "filename",
+                "block-encoding",
+                "delta-encoding",
+                "verity",
+                "variant",
+            ];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "Payload",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Parameters of a dm-verity hash tree appended to a payload.\n"]
+    #[derive(Clone, Debug)]
+    pub struct PayloadVerity {
+        #[doc = "Root hash of the hash tree (hex).\n"]
+        pub root_hash: ::std::string::String,
+        #[doc = "Salt used for hashing (hex).\n"]
+        pub salt: ::std::string::String,
+        #[doc = "Hash algorithm used for the hash tree.\n"]
+        pub hash_algorithm: ::std::string::String,
+        #[doc = "Size of data blocks in bytes.\n"]
+        pub data_block_size: u32,
+        #[doc = "Size of hash blocks in bytes.\n"]
+        pub hash_block_size: u32,
+        #[doc = "Number of data blocks.\n"]
+        pub data_blocks: u64,
+        #[doc = "Offset of the hash tree in bytes.\n"]
+        pub hash_offset: u64,
+    }
+    impl PayloadVerity {
+        #[doc = "Creates a new [`PayloadVerity`]."]
+        pub fn new(
+            root_hash: ::std::string::String,
+            salt: ::std::string::String,
+            hash_algorithm: ::std::string::String,
+            data_block_size: u32,
+            hash_block_size: u32,
+            data_blocks: u64,
+            hash_offset: u64,
+        ) -> Self {
+            Self {
+                root_hash,
+                salt,
+                hash_algorithm,
+                data_block_size,
+                hash_block_size,
+                data_blocks,
+                hash_offset,
+            }
+        }
+        #[doc = "Sets the value of `root_hash`."]
+        pub fn set_root_hash(&mut self, root_hash: ::std::string::String) -> &mut Self {
+            self.root_hash = root_hash;
+            self
+        }
+        #[doc = "Sets the value of `root_hash`."]
+        pub fn with_root_hash(mut self, root_hash: ::std::string::String) -> Self {
+            self.root_hash = root_hash;
+            self
+        }
+        #[doc = "Sets the value of `salt`."]
+        pub fn set_salt(&mut self, salt: ::std::string::String) -> &mut Self {
+            self.salt = salt;
+            self
+        }
+        #[doc = "Sets the value of `salt`."]
+        pub fn with_salt(mut self, salt: ::std::string::String) -> Self {
+            self.salt = salt;
+            self
+        }
+        #[doc = "Sets the value of `hash_algorithm`."]
+        pub fn set_hash_algorithm(&mut self, hash_algorithm: ::std::string::String) -> &mut Self {
+            self.hash_algorithm = hash_algorithm;
+            self
+        }
+        #[doc = "Sets the value of `hash_algorithm`."]
+        pub fn with_hash_algorithm(mut self, hash_algorithm: ::std::string::String) -> Self {
+            self.hash_algorithm = hash_algorithm;
+            self
+        }
+        #[doc = "Sets the value of `data_block_size`."]
+        pub fn set_data_block_size(&mut self, data_block_size: u32) -> &mut Self {
+            self.data_block_size = data_block_size;
+            self
+        }
+        #[doc = "Sets the value of `data_block_size`."]
+        pub fn with_data_block_size(mut self, data_block_size: u32) -> Self {
+            self.data_block_size = data_block_size;
+            self
+        }
+        #[doc = "Sets the value of `hash_block_size`."]
+        pub fn set_hash_block_size(&mut self, hash_block_size: u32) -> &mut Self {
+            self.hash_block_size = hash_block_size;
+            self
+        }
+        #[doc = "Sets the value of `hash_block_size`."]
+        pub fn with_hash_block_size(mut self, hash_block_size: u32) -> Self {
+            self.hash_block_size = hash_block_size;
+            self
+        }
+        #[doc = "Sets the value of `data_blocks`."]
+        pub fn set_data_blocks(&mut self, data_blocks: u64) -> &mut Self {
+            self.data_blocks = data_blocks;
+            self
+        }
+        #[doc = "Sets the value of `data_blocks`."]
+        pub fn with_data_blocks(mut self, data_blocks: u64) -> Self {
+            self.data_blocks = data_blocks;
+            self
+        }
+        #[doc = "Sets the value of `hash_offset`."]
+        pub fn set_hash_offset(&mut self, hash_offset: u64) -> &mut Self {
+            self.hash_offset = hash_offset;
+            self
+        }
+        #[doc = "Sets the value of `hash_offset`."]
+        pub fn with_hash_offset(mut self, hash_offset: u64) -> Self {
+            self.hash_offset = hash_offset;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for PayloadVerity {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "PayloadVerity", 7usize)?;
+            __record.serialize_field("root-hash", &self.root_hash)?;
+            __record.serialize_field("salt", &self.salt)?;
+            __record.serialize_field("hash-algorithm", &self.hash_algorithm)?;
+            __record.serialize_field("data-block-size", &self.data_block_size)?;
+            __record.serialize_field("hash-block-size", &self.hash_block_size)?;
+            __record.serialize_field("data-blocks", &self.data_blocks)?;
+            __record.serialize_field("hash-offset", &self.hash_offset)?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for PayloadVerity {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = PayloadVerity;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record PayloadVerity")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<u32>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<u32>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<u64>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(5usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field6 = match __serde::de::SeqAccess::next_element::<u64>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(6usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PayloadVerity {
+                        root_hash: __field0,
+                        salt: __field1,
+                        hash_algorithm: __field2,
+                        data_block_size: __field3,
+                        hash_block_size: __field4,
+                        data_blocks: __field5,
+                        hash_offset: __field6,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &[
+                        "root-hash",
+                        "salt",
+                        "hash-algorithm",
+                        "data-block-size",
+                        "hash-block-size",
+                        "data-blocks",
+                        "hash-offset",
+                    ];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"root-hash\", \"salt\", \"hash-algorithm\", \"data-block-size\", \"hash-block-size\", \"data-blocks\", \"hash-offset\"]" ;
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Identifier2,
+                        __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
+                        __Identifier6,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                6u64 => ::core::result::Result::Ok(__Identifier::__Identifier6),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "root-hash" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "salt" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "hash-algorithm" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                "data-block-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
+                                "hash-block-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                "data-blocks" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                "hash-offset" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"root-hash" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"salt" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                b"hash-algorithm" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                b"data-block-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
+                                b"hash-block-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                b"data-blocks" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                b"hash-offset" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<u32> = ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<u32> = ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<u64> = ::core::option::Option::None;
+                    let mut __field6: ::core::option::Option<u64> = ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "root-hash",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("salt"),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "hash-algorithm",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "data-block-size",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u32>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "hash-block-size",
+                                        ),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u32>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "data-blocks",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u64>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier6 => {
+                                if ::core::option::Option::is_some(&__field6) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "hash-offset",
+                                        ),
+                                    );
+                                }
+                                __field6 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u64>(&mut __map)?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("root-hash"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("salt"),
+                            );
+                        }
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("hash-algorithm"),
+                            );
+                        }
+                    };
+                    let __field3 = match __field3 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("delivery"),
+                                <__A::Error as __serde::de::Error>::missing_field(
+                                    "data-block-size",
+                                ),
                             );
                         }
                     };
-                    let __field1 = match __field1 {
+                    let __field4 = match __field4 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("filename"),
+                                <__A::Error as __serde::de::Error>::missing_field(
+                                    "hash-block-size",
+                                ),
                             );
                         }
                     };
-                    let __field2 = match __field2 {
+                    let __field5 = match __field5 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("data-blocks"),
+                            );
+                        }
                     };
-                    let __field3 = match __field3 {
+                    let __field6 = match __field6 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("hash-offset"),
+                            );
+                        }
                     };
-                    ::core::result::Result::Ok(Payload {
-                        delivery: __field0,
-                        filename: __field1,
-                        block_encoding: __field2,
-                        delta_encoding: __field3,
+                    ::core::result::Result::Ok(PayloadVerity {
+                        root_hash: __field0,
+                        salt: __field1,
+                        hash_algorithm: __field2,
+                        data_block_size: __field3,
+                        hash_block_size: __field4,
+                        data_blocks: __field5,
+                        hash_offset: __field6,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] =
-                &["delivery", "filename", "block-encoding", "delta-encoding"];
+            const __FIELDS: &'static [&'static str] = &[
+                "root-hash",
+                "salt",
+                "hash-algorithm",
+                "data-block-size",
+                "hash-block-size",
+                "data-blocks",
+                "hash-offset",
+            ];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "Payload",
+                "PayloadVerity",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -1618,6 +2376,8 @@ pub mod manifest {
         Slot(SlotDeliveryConfig),
         #[doc = ""]
         Execute(ExecuteDeliveryConfig),
+        #[doc = ""]
+        Metadata(MetadataDeliveryConfig),
     }
     #[automatically_derived]
     impl __serde::Serialize for DeliveryConfig {
@@ -1634,6 +2394,9 @@ pub mod manifest {
                 Self::Execute(__value) => {
                     __serializer.serialize_internally_tagged("type", "execute", 1u32, __value)
                 }
+                Self::Metadata(__value) => {
+                    __serializer.serialize_internally_tagged("type", "metadata", 2u32, __value)
+                }
             }
         }
     }
@@ -1643,15 +2406,16 @@ pub mod manifest {
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
             #[doc(hidden)]
-            const __IDENTIFIERS: &'static [&'static str] = &["slot", "execute"];
+            const __IDENTIFIERS: &'static [&'static str] = &["slot", "execute", "metadata"];
             #[doc(hidden)]
             const __EXPECTING_IDENTIFIERS: &'static str =
-                "an identifier in [\"slot\", \"execute\"]";
+                "an identifier in [\"slot\", \"execute\", \"metadata\"]";
             #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
             #[doc(hidden)]
             enum __Identifier {
                 __Identifier0,
                 __Identifier1,
+                __Identifier2,
             }
             #[doc(hidden)]
             struct __IdentifierVisitor;
@@ -1670,6 +2434,7 @@ pub mod manifest {
                     match __value {
                         0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                         1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
                         __variant => {
                             ::core::result::Result::Err(__serde::de::Error::invalid_value(
                                 __serde::de::Unexpected::Unsigned(__variant),
@@ -1685,6 +2450,7 @@ pub mod manifest {
                     match __value {
                         "slot" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                         "execute" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        "metadata" => ::core::result::Result::Ok(__Identifier::__Identifier2),
                         __variant => ::core::result::Result::Err(
                             __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
                         ),
@@ -1700,6 +2466,7 @@ pub mod manifest {
                     match __value {
                         b"slot" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                         b"execute" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        b"metadata" => ::core::result::Result::Ok(__Identifier::__Identifier2),
                         __variant => {
                             ::core::result::Result::Err(__serde::de::Error::invalid_value(
                                 __serde::de::Unexpected::Bytes(__variant),
@@ -1722,28 +2489,17 @@ pub mod manifest {
                 }
             }
             #[doc(hidden)]
-            const __VARIANTS: &'static [&'static str] = &["slot", "execute"];
+            const __VARIANTS: &'static [&'static str] = &["slot", "execute", "metadata"];
             if __serde::Deserializer::is_human_readable(&__deserializer) {
                 let __tagged = __sidex_serde::de::tagged::deserialize_tagged_variant::<
                     __Identifier,
                     __D,
                 >(__deserializer, "type")?;
                 match __tagged.tag {
-                    __Identifier::__Identifier0 => {
-                        ::core::result::Result::Ok(DeliveryConfig::Slot(
-                            __tagged
-                                .deserialize_internally_tagged::<SlotDeliveryConfig, __D::Error>(
-                                )?,
-                        ))
-                    }
-                    __Identifier::__Identifier1 => {
-                        ::core::result::Result::Ok(DeliveryConfig::Execute(
-                            __tagged
-                                .deserialize_internally_tagged::<ExecuteDeliveryConfig, __D::Error>(
-                                )?,
-                        ))
-                    }
-                }
+__Identifier::__Identifier0 => ::core::result::Result::Ok(DeliveryConfig::Slot(__tagged.deserialize_internally_tagged::<SlotDeliveryConfig, __D::Error>()?)),
+__Identifier::__Identifier1 => ::core::result::Result::Ok(DeliveryConfig::Execute(__tagged.deserialize_internally_tagged::<ExecuteDeliveryConfig, __D::Error>()?)),
+__Identifier::__Identifier2 => ::core::result::Result::Ok(DeliveryConfig::Metadata(__tagged.deserialize_internally_tagged::<MetadataDeliveryConfig, __D::Error>()?)),
+}
             } else {
                 #[doc(hidden)]
                 struct __Visitor {
@@ -1795,6 +2551,12 @@ pub mod manifest {
                                 >(__variant)?;
                                 ::core::result::Result::Ok(DeliveryConfig::Execute(__value))
                             }
+                            (__Identifier::__Identifier2, __variant) => {
+                                let __value = __serde::de::VariantAccess::newtype_variant::<
+                                    MetadataDeliveryConfig,
+                                >(__variant)?;
+                                ::core::result::Result::Ok(DeliveryConfig::Metadata(__value))
+                            }
                         }
                     }
                 }
@@ -2223,6 +2985,265 @@ pub mod manifest {
             )
         }
     }
+    #[doc = "Metadata about the contents of a slot, e.g., an SBOM.\n\nMetadata payloads are stored alongside the state of the slot and must be placed\nafter the payload installing the slot.\n"]
+    #[derive(Clone, Debug)]
+    pub struct MetadataDeliveryConfig {
+        #[doc = "Name of the metadata, e.g., `sbom`.\n"]
+        pub name: ::std::string::String,
+        #[doc = "Slot the metadata is about.\n"]
+        pub slot: ::std::string::String,
+    }
+    impl MetadataDeliveryConfig {
+        #[doc = "Creates a new [`MetadataDeliveryConfig`]."]
+        pub fn new(name: ::std::string::String, slot: ::std::string::String) -> Self {
+            Self { name, slot }
+        }
+        #[doc = "Sets the value of `name`."]
+        pub fn set_name(&mut self, name: ::std::string::String) -> &mut Self {
+            self.name = name;
+            self
+        }
+        #[doc = "Sets the value of `name`."]
+        pub fn with_name(mut self, name: ::std::string::String) -> Self {
+            self.name = name;
+            self
+        }
+        #[doc = "Sets the value of `slot`."]
+        pub fn set_slot(&mut self, slot: ::std::string::String) -> &mut Self {
+            self.slot = slot;
+            self
+        }
+        #[doc = "Sets the value of `slot`."]
+        pub fn with_slot(mut self, slot: ::std::string::String) -> Self {
+            self.slot = slot;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for MetadataDeliveryConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "MetadataDeliveryConfig",
+                2usize,
+            )?;
+            __record.serialize_field("name", &self.name)?;
+            __record.serialize_field("slot", &self.slot)?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for MetadataDeliveryConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = MetadataDeliveryConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record MetadataDeliveryConfig")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(MetadataDeliveryConfig {
+                        name: __field0,
+                        slot: __field1,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["name", "slot"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"name\", \"slot\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "name" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "slot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"name" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                b"slot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("name"),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("slot"),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("name"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("slot"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(MetadataDeliveryConfig {
+                        name: __field0,
+                        slot: __field1,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["name", "slot"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "MetadataDeliveryConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
     #[doc = ""]
     #[derive(Clone, Debug)]
     pub struct BlockEncoding {
@@ -68,6 +68,11 @@ impl<S: BundleSource> BundleReader<S> {
         algorithm.hash(&self.header_raw)
     }
 
+    /// Source the bundle is read from.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
     pub fn signatures(&self) -> Option<&Signatures> {
         self.signatures.as_ref()
     }
@@ -155,6 +160,11 @@ impl<'r, S: BundleSource> PayloadReader<'r, S> {
         &self.reader.header().payload_index[self.idx]
     }
 
+    /// Size of the payload data remaining to be read.
+    pub fn remaining_data(&self) -> NumBytes {
+        self.remaining_data
+    }
+
     pub fn skip(self) -> BundleResult<()> {
         self.reader.source.skip(self.remaining_data)?;
         skip_until_end(&mut self.reader.source, tags::PAYLOAD)?;
@@ -0,0 +1,113 @@
+//! Sigstore signatures of bundles.
+//!
+//! Just like CMS signatures, Sigstore signatures sign the encoded
+//! [`SignedMetadata`][crate::format::SignedMetadata] of a bundle. They are embedded as
+//! Sigstore bundles containing the signature, the signing certificate or public key, and
+//! the proof of inclusion in the Rekor transparency log. This allows verifying them
+//! offline on devices. Signing and verification are delegated to `cosign`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use reportify::{bail, ResultExt};
+
+use crate::BundleResult;
+
+/// Trust configuration for verifying Sigstore signatures.
+#[derive(Debug, Clone, Default)]
+pub struct SigstoreTrust {
+    /// Public key for verifying key-based signatures.
+    pub key: Option<PathBuf>,
+    /// Expected identity of the signer of keyless signatures, e.g., an email address.
+    pub certificate_identity: Option<String>,
+    /// Expected OIDC issuer of the identity of the signer of keyless signatures.
+    pub certificate_oidc_issuer: Option<String>,
+    /// Sigstore trusted root with the certificate authorities and transparency logs.
+    ///
+    /// Required for keyless signatures, as the signing certificate cannot be verified
+    /// offline otherwise.
+    pub trusted_root: Option<PathBuf>,
+}
+
+impl SigstoreTrust {
+    /// Returns whether Sigstore signatures should be verified.
+    pub fn is_configured(&self) -> bool {
+        self.key.is_some() || self.certificate_identity.is_some()
+    }
+}
+
+/// Sign the given signed metadata with `cosign` and return the Sigstore bundle.
+///
+/// Without a key, `cosign` performs keyless signing based on an OIDC identity.
+pub fn sign(signed_metadata: &[u8], key: Option<&Path>) -> BundleResult<Vec<u8>> {
+    let tempdir = tempfile::tempdir().whatever("unable to create temporary directory")?;
+    let signed_metadata_raw = tempdir.path().join("signed-metadata.raw");
+    let sigstore_bundle = tempdir.path().join("sigstore-bundle.json");
+    std::fs::write(&signed_metadata_raw, signed_metadata)
+        .whatever("unable to write signed metadata")?;
+    let mut command = Command::new("cosign");
+    command
+        .args(["sign-blob", "--yes", "--bundle"])
+        .arg(&sigstore_bundle);
+    if let Some(key) = key {
+        command.arg("--key").arg(key);
+    }
+    command.arg(&signed_metadata_raw);
+    let status = command.status().whatever("unable to run `cosign`")?;
+    if !status.success() {
+        bail!("unable to sign bundle with `cosign`");
+    }
+    std::fs::read(&sigstore_bundle).whatever("unable to read Sigstore bundle")
+}
+
+/// Verify a Sigstore bundle for the given signed metadata offline with `cosign`.
+pub fn verify(
+    trust: &SigstoreTrust,
+    signed_metadata: &[u8],
+    sigstore_bundle: &[u8],
+) -> BundleResult<()> {
+    let tempdir = tempfile::tempdir().whatever("unable to create temporary directory")?;
+    let signed_metadata_raw = tempdir.path().join("signed-metadata.raw");
+    let sigstore_bundle_json = tempdir.path().join("sigstore-bundle.json");
+    std::fs::write(&signed_metadata_raw, signed_metadata)
+        .whatever("unable to write signed metadata")?;
+    std::fs::write(&sigstore_bundle_json, sigstore_bundle)
+        .whatever("unable to write Sigstore bundle")?;
+    let mut command = Command::new("cosign");
+    command
+        .args(["verify-blob", "--offline", "--bundle"])
+        .arg(&sigstore_bundle_json);
+    match (
+        &trust.key,
+        &trust.certificate_identity,
+        &trust.certificate_oidc_issuer,
+    ) {
+        (Some(key), None, None) => {
+            command.arg("--key").arg(key);
+        }
+        (None, Some(identity), Some(issuer)) => {
+            if trust.trusted_root.is_none() {
+                bail!("keyless verification requires a Sigstore trusted root");
+            }
+            command
+                .args(["--certificate-identity", identity])
+                .args(["--certificate-oidc-issuer", issuer]);
+        }
+        (None, Some(_), None) => {
+            bail!("keyless verification requires the OIDC issuer of the signer");
+        }
+        _ => {
+            bail!("either a public key or the identity of the signer must be given, but not both");
+        }
+    }
+    if let Some(trusted_root) = &trust.trusted_root {
+        command.arg("--trusted-root").arg(trusted_root);
+    }
+    command.arg(&signed_metadata_raw);
+    let output = command.output().whatever("unable to run `cosign`")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("invalid Sigstore signature: {}", stderr.trim());
+    }
+    Ok(())
+}
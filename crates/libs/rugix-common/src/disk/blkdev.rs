@@ -82,8 +82,12 @@ impl BlockDevice {
     }
 
     /// Check whether the device is a partition and return its number, if it is.
+    ///
+    /// Partitions of device-mapper devices, e.g., created by `kpartx` for multipath
+    /// devices, are device-mapper devices themselves and are also considered partitions.
     pub fn is_partition(&self) -> io::Result<Option<u32>> {
-        let partition = self.sysfs_path()?.join("partition");
+        let sysfs_path = self.sysfs_path()?;
+        let partition = sysfs_path.join("partition");
         if partition.exists() {
             Ok(Some(
                 fs::read_to_string(&partition)?
@@ -92,10 +96,23 @@ impl BlockDevice {
                     .expect("partition attribute must be a number"),
             ))
         } else {
-            Ok(None)
+            Ok(read_dm_uuid(&sysfs_path).and_then(|uuid| parse_dm_partition_uuid(&uuid)))
         }
     }
 
+    /// Check whether the device is a device-mapper device.
+    pub fn is_device_mapper(&self) -> bool {
+        self.name().starts_with("dm-")
+    }
+
+    /// Check whether the device is a boot partition of an eMMC.
+    ///
+    /// The boot partitions of an eMMC are separate hardware partitions and appear as
+    /// whole disks named `mmcblk<N>boot<M>`. By default, they are read-only.
+    pub fn is_emmc_boot_partition(&self) -> bool {
+        is_emmc_boot_partition_name(self.name())
+    }
+
     /// Check whether the device is a whole disk, i.e., not a partition.
     pub fn is_whole_disk(&self) -> io::Result<bool> {
         self.is_partition().map(|partition| partition.is_none())
@@ -134,8 +151,22 @@ impl BlockDevice {
         // hierarchy for a parent device with a matching subsystem as the Kernel is free
         // to insert devices at any point in the hierarchy.
         let sysfs_path = self.sysfs_path()?;
+        if self.is_device_mapper() {
+            // Device-mapper devices are not nested in the hierarchy. The parent of a
+            // partition is the single device it is mapped onto.
+            if self.is_partition()?.is_none() {
+                return Ok(None);
+            }
+            let mut slaves = self.find_slaves()?;
+            return Ok(if slaves.len() == 1 {
+                slaves.pop()
+            } else {
+                None
+            });
+        }
         let mut path = sysfs_path.parent();
         while let Some(parent) = path {
+            path = parent.parent();
             let Ok(subsystem) = fs::read_link(parent.join("subsystem")) else {
                 continue;
             };
@@ -145,16 +176,74 @@ impl BlockDevice {
             if subsystem == OsStr::new("block") {
                 return Self::from_sysfs_path(parent).map(Some);
             }
-            path = parent.parent();
         }
         Ok(None)
     }
 
+    /// Find the devices a device-mapper device is mapped onto.
+    pub fn find_slaves(&self) -> io::Result<Vec<Self>> {
+        self.read_sysfs_devices("slaves")
+    }
+
+    /// Find the device underlying stacked device-mapper devices.
+    ///
+    /// For instance, for an encrypted or dm-verity protected partition, this returns the
+    /// actual partition. Partitions of device-mapper devices and devices mapped onto
+    /// multiple devices are returned as is.
+    pub fn resolve_device_mapper(&self) -> io::Result<Self> {
+        let mut device = self.clone();
+        while device.is_device_mapper() && device.is_partition()?.is_none() {
+            let mut slaves = device.find_slaves()?;
+            if slaves.len() != 1 {
+                break;
+            }
+            device = slaves.pop().unwrap();
+        }
+        Ok(device)
+    }
+
+    /// Check whether the device is read-only due to `force_ro`.
+    pub fn is_force_read_only(&self) -> io::Result<bool> {
+        Ok(fs::read_to_string(self.sysfs_path()?.join("force_ro"))?.trim() == "1")
+    }
+
+    /// Make the device writable or read-only by setting `force_ro`.
+    ///
+    /// This is required to write to eMMC boot partitions.
+    pub fn set_force_read_only(&self, read_only: bool) -> io::Result<()> {
+        fs::write(
+            self.sysfs_path()?.join("force_ro"),
+            if read_only { "1" } else { "0" },
+        )
+    }
+
     /// Get a block device for the given partition of the device, if it exits.
     pub fn get_partition(&self, partition: u32) -> io::Result<Option<Self>> {
-        // We take a simple approach and directly construct a path for the partition in
-        // `/dev`. There may be some edge cases where this does not work. An alternative
-        // would scan the device directory in `/sys` for partitions.
+        // We scan the device directory in `/sys` for partitions such that we do not
+        // depend on the naming of the partitions, e.g., `nvme0n1p3` vs. `sda3`.
+        let sysfs_path = self.sysfs_path()?;
+        if self.is_device_mapper() {
+            // Partitions of device-mapper devices are holders of the device.
+            for holder in self.read_sysfs_devices("holders")? {
+                let holder_path = holder.sysfs_path()?;
+                let holder_partition =
+                    read_dm_uuid(&holder_path).and_then(|uuid| parse_dm_partition_uuid(&uuid));
+                if holder_partition == Some(partition) {
+                    return Ok(Some(holder));
+                }
+            }
+            return Ok(None);
+        }
+        for entry in fs::read_dir(&sysfs_path)? {
+            let entry_path = entry?.path();
+            let Ok(number) = fs::read_to_string(entry_path.join("partition")) else {
+                continue;
+            };
+            if number.trim().parse::<u32>().ok() == Some(partition) {
+                return Self::from_sysfs_path(entry_path).map(Some);
+            }
+        }
+        // Fall back to constructing a path for the partition in `/dev`.
         let mut path = self.path.clone();
         if path.ends_with(|c: char| c.is_ascii_digit()) {
             path.push('p');
@@ -168,10 +257,38 @@ impl BlockDevice {
         }
     }
 
+    /// Get a block device for the given boot partition of an eMMC, if it exists.
+    pub fn get_emmc_boot_partition(&self, boot_partition: u32) -> io::Result<Option<Self>> {
+        // The boot partitions are siblings of the device in `/sys`.
+        let sysfs_path = self.sysfs_path()?;
+        let Some(parent) = sysfs_path.parent() else {
+            return Ok(None);
+        };
+        let path = parent.join(format!("{}boot{boot_partition}", self.name()));
+        if path.exists() {
+            Self::from_sysfs_path(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Canonical path of the block device in `/sys`.
     fn sysfs_path(&self) -> io::Result<PathBuf> {
         sysfs_device_number_to_path(self.dev).canonicalize()
     }
+
+    /// Read the devices listed in the given directory of the device in `/sys`.
+    fn read_sysfs_devices(&self, dir: &str) -> io::Result<Vec<Self>> {
+        let path = self.sysfs_path()?.join(dir);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let mut devices = Vec::new();
+        for entry in fs::read_dir(path)? {
+            devices.push(Self::from_sysfs_path(entry?.path())?);
+        }
+        Ok(devices)
+    }
 }
 
 impl PartialEq for BlockDevice {
@@ -233,6 +350,44 @@ pub fn find_block_device<P: AsRef<Path>>(path: P) -> io::Result<Option<BlockDevi
     inner(path.as_ref())
 }
 
+/// List the whole disks of the system.
+///
+/// Disks without a device node in `/dev` are skipped.
+pub fn list_disks() -> io::Result<Vec<BlockDevice>> {
+    let mut disks = Vec::new();
+    for entry in fs::read_dir("/sys/block")? {
+        if let Ok(device) = BlockDevice::from_sysfs_path(entry?.path()) {
+            disks.push(device);
+        }
+    }
+    Ok(disks)
+}
+
+/// Read the UUID of a device-mapper device from its path in `/sys`.
+fn read_dm_uuid(sysfs_path: &Path) -> Option<String> {
+    fs::read_to_string(sysfs_path.join("dm/uuid"))
+        .ok()
+        .map(|uuid| uuid.trim().to_owned())
+}
+
+/// Parse the partition number from the UUID of a device-mapper device.
+///
+/// By convention, partitions of device-mapper devices created by `kpartx` and
+/// `multipath` have UUIDs of the form `part<N>-<UUID of the parent>`.
+fn parse_dm_partition_uuid(uuid: &str) -> Option<u32> {
+    let (prefix, _) = uuid.split_once('-')?;
+    prefix.strip_prefix("part")?.parse().ok()
+}
+
+/// Check whether the given device name is the name of an eMMC boot partition.
+fn is_emmc_boot_partition_name(name: &str) -> bool {
+    let Some((disk, boot_partition)) = name.split_once("boot") else {
+        return false;
+    };
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    disk.strip_prefix("mmcblk").is_some_and(is_number) && is_number(boot_partition)
+}
+
 /// Convert the device number to a block device path in `/sys`.
 ///
 /// Path has the form `/sys/dev/block/{major}:{minor}`.
@@ -269,3 +424,34 @@ fn sysfs_path_to_name(path: &Path) -> io::Result<String> {
         .expect("device name should be valid UTF-8")
         .to_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dm_partition_uuid() {
+        assert_eq!(
+            parse_dm_partition_uuid("part3-mpath-360014380056efd3a0000600000a40000"),
+            Some(3)
+        );
+        assert_eq!(
+            parse_dm_partition_uuid("mpath-360014380056efd3a0000600000a40000"),
+            None
+        );
+        assert_eq!(
+            parse_dm_partition_uuid("CRYPT-LUKS2-5bd3d2ae2e6c4cd5a4f2a9c5d1e0f0d3-data"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_emmc_boot_partition_name() {
+        assert!(is_emmc_boot_partition_name("mmcblk0boot0"));
+        assert!(is_emmc_boot_partition_name("mmcblk1boot1"));
+        assert!(!is_emmc_boot_partition_name("mmcblk0"));
+        assert!(!is_emmc_boot_partition_name("mmcblk0p1"));
+        assert!(!is_emmc_boot_partition_name("mmcblk0rpmb"));
+        assert!(!is_emmc_boot_partition_name("nvme0n1p1"));
+    }
+}
@@ -1,10 +1,15 @@
 //! Utilities for working with GUID partition tables.
 
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
 use std::ops::Deref;
+use std::path::Path;
 
+use reportify::{bail, Report, ResultExt};
 use thiserror::Error;
 
-use super::NumBlocks;
+use super::{sfdisk, NumBlocks, PartitionTable};
+use crate::partitions::DiskError;
 use crate::utils::ascii_numbers::{self, byte_to_ascii_hex, parse_ascii_hex_byte, Case};
 use crate::utils::const_helpers::const_for;
 
@@ -136,6 +141,211 @@ pub mod gpt_types {
     pub const LINUX: PartitionType = PartitionType::Gpt(const_unwrap_result!(Guid::from_hex_str(
         "0FC63DAF-8483-4772-8E79-3D69D8477DE4"
     )));
+    /// Linux swap GPT partition type.
+    pub const LINUX_SWAP: PartitionType = PartitionType::Gpt(const_unwrap_result!(
+        Guid::from_hex_str("0657FD6D-A4AB-43C4-84E5-0933C84B4F4F")
+    ));
+    /// BIOS boot GPT partition type (used by Grub for legacy BIOS booting).
+    pub const BIOS_BOOT: PartitionType = PartitionType::Gpt(const_unwrap_result!(
+        Guid::from_hex_str("21686148-6449-6E6F-744E-656564454649")
+    ));
+    /// Extended boot loader GPT partition type (`XBOOTLDR`).
+    pub const XBOOTLDR: PartitionType = PartitionType::Gpt(const_unwrap_result!(
+        Guid::from_hex_str("BC13C2FF-59E6-4262-A352-B275FD6F7172")
+    ));
+    /// Linux root GPT partition type for x86-64 (Discoverable Partitions Specification).
+    pub const LINUX_ROOT_X86_64: PartitionType = PartitionType::Gpt(const_unwrap_result!(
+        Guid::from_hex_str("4F68BCE3-E8CD-4DB1-96E7-FBCAF984B709")
+    ));
+    /// Linux root GPT partition type for AArch64 (Discoverable Partitions Specification).
+    pub const LINUX_ROOT_ARM64: PartitionType = PartitionType::Gpt(const_unwrap_result!(
+        Guid::from_hex_str("B921B045-1DF0-41C3-AF44-4C6F280D3FAE")
+    ));
+}
+
+/// Attribute flags of a GPT partition.
+///
+/// The bits 0 to 2 are defined by the UEFI standard while the bits 48 to 63 are specific
+/// to the type of the partition. The textual representation matches the one of `sfdisk`,
+/// e.g., `RequiredPartition,LegacyBIOSBootable,GUID:60`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GptAttributes(u64);
+
+impl GptAttributes {
+    /// No attributes.
+    pub const NONE: Self = Self(0);
+    /// Partition is required for the platform to function.
+    pub const REQUIRED_PARTITION: Self = Self(1 << 0);
+    /// Firmware must not provide the EFI block IO protocol for the partition.
+    pub const NO_BLOCK_IO_PROTOCOL: Self = Self(1 << 1);
+    /// Partition is bootable by legacy BIOS firmware.
+    pub const LEGACY_BIOS_BOOTABLE: Self = Self(1 << 2);
+
+    /// Names of the attributes defined by the UEFI standard.
+    const NAMES: [(Self, &'static str); 3] = [
+        (Self::REQUIRED_PARTITION, "RequiredPartition"),
+        (Self::NO_BLOCK_IO_PROTOCOL, "NoBlockIOProtocol"),
+        (Self::LEGACY_BIOS_BOOTABLE, "LegacyBIOSBootable"),
+    ];
+
+    /// Create attributes from their raw bits.
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    /// Raw bits of the attributes.
+    pub const fn into_raw(self) -> u64 {
+        self.0
+    }
+
+    /// Type-specific attribute with the given bit (48 to 63).
+    pub const fn type_specific(bit: u8) -> Option<Self> {
+        if matches!(bit, 48..=63) {
+            Some(Self(1 << bit))
+        } else {
+            None
+        }
+    }
+
+    /// Indicates whether no attributes are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Indicates whether all the given attributes are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Parse a single attribute, either by its name or by its bit number.
+    fn parse_single(name: &str) -> Option<Self> {
+        if let Some((attr, _)) = Self::NAMES
+            .iter()
+            .find(|(_, attr_name)| attr_name.eq_ignore_ascii_case(name))
+        {
+            return Some(*attr);
+        }
+        let bit = name
+            .strip_prefix("GUID:")
+            .or_else(|| name.strip_prefix("guid:"))
+            .unwrap_or(name)
+            .parse::<u8>()
+            .ok()?;
+        match bit {
+            0..=2 => Some(Self(1 << bit)),
+            _ => Self::type_specific(bit),
+        }
+    }
+}
+
+impl std::ops::BitOr for GptAttributes {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for GptAttributes {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+impl std::fmt::Display for GptAttributes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for bit in 0..64 {
+            if self.0 & (1 << bit) == 0 {
+                continue;
+            }
+            if !first {
+                f.write_str(",")?;
+            }
+            first = false;
+            match Self::NAMES.iter().find(|(attr, _)| attr.0 == 1 << bit) {
+                Some((_, name)) => f.write_str(name)?,
+                None => write!(f, "GUID:{bit}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for GptAttributes {
+    type Err = InvalidGptAttributes;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut attributes = Self::NONE;
+        for name in s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+        {
+            attributes |= Self::parse_single(name).ok_or_else(|| InvalidGptAttributes {
+                attribute: name.to_owned(),
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+impl serde::Serialize for GptAttributes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for GptAttributes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = GptAttributes;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("GPT partition attributes")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut attributes = GptAttributes::NONE;
+                while let Some(name) = seq.next_element::<String>()? {
+                    attributes |= GptAttributes::parse_single(&name).ok_or_else(|| {
+                        <A::Error as serde::de::Error>::invalid_value(
+                            serde::de::Unexpected::Str(&name),
+                            &self,
+                        )
+                    })?;
+                }
+                Ok(attributes)
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Error indicating an invalid GPT partition attribute.
+#[derive(Debug, Clone, Error)]
+#[error("invalid GPT partition attribute {attribute:?}")]
+pub struct InvalidGptAttributes {
+    attribute: String,
 }
 
 /// GUID string representation.
@@ -181,11 +391,164 @@ pub enum InvalidGuid {
     InvalidByte { pos: usize },
 }
 
+/// Signature of a GPT header.
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+
+/// Minimal size of a GPT header.
+const GPT_HEADER_MIN_SIZE: usize = 92;
+
+/// Status of the backup GPT header of a disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GptBackupStatus {
+    /// The backup header and partition entries are intact.
+    Intact,
+    /// The backup header is not at the end of the disk.
+    ///
+    /// This typically happens when an image is written to a larger disk.
+    Misplaced,
+    /// The backup header or partition entries are damaged or missing.
+    Damaged,
+}
+
+impl GptBackupStatus {
+    /// Indicates whether the backup header needs to be repaired.
+    pub fn needs_repair(self) -> bool {
+        !matches!(self, Self::Intact)
+    }
+}
+
+impl std::fmt::Display for GptBackupStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GptBackupStatus::Intact => "intact",
+            GptBackupStatus::Misplaced => "misplaced",
+            GptBackupStatus::Damaged => "damaged",
+        })
+    }
+}
+
+/// Fields of a GPT header relevant for checking the backup header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GptHeader {
+    /// LBA of the header itself.
+    my_lba: u64,
+    /// LBA of the other header.
+    alternate_lba: u64,
+    /// LBA of the partition entries.
+    entries_lba: u64,
+    /// Number of partition entries.
+    num_entries: u32,
+    /// Size of a partition entry.
+    entry_size: u32,
+    /// CRC32 of the partition entries.
+    entries_crc: u32,
+}
+
+impl GptHeader {
+    /// Parse a GPT header from the given block.
+    ///
+    /// Returns `None` if the block does not contain a valid header.
+    fn parse(block: &[u8]) -> Option<Self> {
+        if block.len() < GPT_HEADER_MIN_SIZE || &block[0..8] != GPT_SIGNATURE {
+            return None;
+        }
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(block[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(block[offset..offset + 8].try_into().unwrap());
+        let header_size = u32_at(12) as usize;
+        if !(GPT_HEADER_MIN_SIZE..=block.len()).contains(&header_size) {
+            return None;
+        }
+        // The CRC32 of the header is computed with the CRC32 field set to zero.
+        let mut header = block[..header_size].to_vec();
+        header[16..20].fill(0);
+        if crc32fast::hash(&header) != u32_at(16) {
+            return None;
+        }
+        Some(Self {
+            my_lba: u64_at(24),
+            alternate_lba: u64_at(32),
+            entries_lba: u64_at(72),
+            num_entries: u32_at(80),
+            entry_size: u32_at(84),
+            entries_crc: u32_at(88),
+        })
+    }
+
+    /// Size of the partition entries in bytes.
+    fn entries_size(&self) -> u64 {
+        u64::from(self.num_entries) * u64::from(self.entry_size)
+    }
+}
+
+/// Read the block with the given LBA.
+fn read_block(file: &mut File, lba: u64, block_size: u64) -> io::Result<Vec<u8>> {
+    let mut block = vec![0; block_size as usize];
+    file.seek(SeekFrom::Start(lba * block_size))?;
+    file.read_exact(&mut block)?;
+    Ok(block)
+}
+
+/// Check whether the partition entries of the given header are intact.
+fn check_entries(file: &mut File, header: &GptHeader, block_size: u64) -> io::Result<bool> {
+    let mut entries = vec![0; header.entries_size() as usize];
+    file.seek(SeekFrom::Start(header.entries_lba * block_size))?;
+    file.read_exact(&mut entries)?;
+    Ok(crc32fast::hash(&entries) == header.entries_crc)
+}
+
+/// Check the backup header of the GPT of the given device or image.
+pub fn check_backup_header(dev: impl AsRef<Path>) -> Result<GptBackupStatus, Report<DiskError>> {
+    let dev = dev.as_ref();
+    let table = PartitionTable::read(dev)?;
+    if !table.is_gpt() {
+        bail!("{dev:?} does not have a GPT");
+    }
+    let block_size = table.block_size.into_raw();
+    let last_lba = table.disk_size.into_raw() - 1;
+    let mut file = File::open(dev).whatever("unable to open device")?;
+    let Some(primary) = GptHeader::parse(
+        &read_block(&mut file, 1, block_size).whatever("unable to read primary GPT header")?,
+    ) else {
+        bail!("primary GPT header of {dev:?} is damaged");
+    };
+    if primary.alternate_lba != last_lba {
+        return Ok(GptBackupStatus::Misplaced);
+    }
+    let backup =
+        read_block(&mut file, last_lba, block_size).whatever("unable to read backup GPT header")?;
+    let Some(backup) = GptHeader::parse(&backup) else {
+        return Ok(GptBackupStatus::Damaged);
+    };
+    if backup.my_lba != last_lba
+        || backup.alternate_lba != 1
+        || backup.entries_crc != primary.entries_crc
+        || backup.entries_size() != primary.entries_size()
+        || backup.entries_lba >= last_lba
+    {
+        return Ok(GptBackupStatus::Damaged);
+    }
+    if !check_entries(&mut file, &backup, block_size)
+        .whatever("unable to read backup GPT partition entries")?
+    {
+        return Ok(GptBackupStatus::Damaged);
+    }
+    Ok(GptBackupStatus::Intact)
+}
+
+/// Rewrite the backup header and partition entries of the GPT of the given device.
+///
+/// The backup is written to the end of the device based on the primary header.
+pub fn repair_backup_header(dev: impl AsRef<Path>) -> Result<(), Report<DiskError>> {
+    sfdisk::sfdisk_relocate_gpt_backup(dev.as_ref())
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::ops::Deref;
 
-    use crate::disk::gpt::Guid;
+    use crate::disk::gpt::{GptAttributes, GptHeader, Guid, GPT_SIGNATURE};
     use crate::utils::ascii_numbers;
 
     #[test]
@@ -207,4 +570,48 @@ pub mod tests {
             LINUX
         );
     }
+
+    #[test]
+    pub fn test_gpt_attributes_roundtrip() {
+        let attributes = "RequiredPartition,LegacyBIOSBootable,GUID:60"
+            .parse::<GptAttributes>()
+            .unwrap();
+        assert_eq!(attributes.into_raw(), 1 | (1 << 2) | (1 << 60));
+        assert_eq!(
+            attributes.to_string(),
+            "RequiredPartition,LegacyBIOSBootable,GUID:60"
+        );
+        // `sfdisk` separates attributes with spaces in its JSON output.
+        assert_eq!(
+            "legacybiosbootable GUID:63"
+                .parse::<GptAttributes>()
+                .unwrap(),
+            GptAttributes::LEGACY_BIOS_BOOTABLE | GptAttributes::type_specific(63).unwrap()
+        );
+        assert!("GUID:10".parse::<GptAttributes>().is_err());
+        assert!("".parse::<GptAttributes>().unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_gpt_header_parse() {
+        let mut block = vec![0; 512];
+        block[0..8].copy_from_slice(GPT_SIGNATURE);
+        block[12..16].copy_from_slice(&92u32.to_le_bytes());
+        block[24..32].copy_from_slice(&1u64.to_le_bytes());
+        block[32..40].copy_from_slice(&2047u64.to_le_bytes());
+        block[72..80].copy_from_slice(&2u64.to_le_bytes());
+        block[80..84].copy_from_slice(&128u32.to_le_bytes());
+        block[84..88].copy_from_slice(&128u32.to_le_bytes());
+        let crc = crc32fast::hash(&block[..92]);
+        block[16..20].copy_from_slice(&crc.to_le_bytes());
+        let header = GptHeader::parse(&block).unwrap();
+        assert_eq!(header.my_lba, 1);
+        assert_eq!(header.alternate_lba, 2047);
+        assert_eq!(header.entries_lba, 2);
+        assert_eq!(header.entries_size(), 128 * 128);
+        // Any modification of the header must invalidate its CRC32.
+        block[32] = 0xFF;
+        assert!(GptHeader::parse(&block).is_none());
+        assert!(GptHeader::parse(&[0; 512]).is_none());
+    }
 }
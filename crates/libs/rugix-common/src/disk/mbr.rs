@@ -1,5 +1,14 @@
 //! Utilities for working with MBR partition tables.
 
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use reportify::{bail, Report, ResultExt};
+
+use super::PartitionTable;
+use crate::partitions::DiskError;
+
 /// MBR disk id.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MbrId {
@@ -45,4 +54,212 @@ pub mod mbr_types {
 
     /// Linux filesystem.
     pub const LINUX: PartitionType = PartitionType::Mbr(0x83);
+
+    /// Linux swap space.
+    pub const LINUX_SWAP: PartitionType = PartitionType::Mbr(0x82);
+}
+
+/// Entry of a hybrid MBR mirroring a GPT partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HybridMbrEntry {
+    /// Number of the GPT partition.
+    pub number: u8,
+    /// MBR partition type.
+    pub ty: u8,
+    /// Indicates whether the partition should be marked as bootable.
+    pub bootable: bool,
+}
+
+/// Write a hybrid MBR for the given GPT partition table to a device or image.
+///
+/// Each mirrored partition is placed in the MBR slot matching its GPT partition number
+/// such that partition numbers are consistent between both tables. The protective
+/// `0xEE` partition covering the GPT structures is placed in the first free slot.
+pub fn write_hybrid_mbr(
+    dev: &Path,
+    table: &PartitionTable,
+    entries: &[HybridMbrEntry],
+) -> Result<(), Report<DiskError>> {
+    let mbr_entries = hybrid_mbr_entries(table, entries)?;
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .open(dev)
+        .whatever("unable to open device")?;
+    let mut mbr = [0; 512];
+    file.read_exact(&mut mbr)
+        .whatever("unable to read MBR from device")?;
+    for (idx, entry) in mbr_entries.iter().enumerate() {
+        let offset = MBR_PARTITIONS_OFFSET + idx * 16;
+        mbr[offset..offset + 16].copy_from_slice(entry);
+    }
+    mbr[510] = 0x55;
+    mbr[511] = 0xAA;
+    file.seek(SeekFrom::Start(0))
+        .whatever("unable to seek to MBR")?;
+    file.write_all(&mbr).whatever("unable to write MBR")?;
+    file.flush().whatever("unable to flush MBR")?;
+    Ok(())
+}
+
+/// Read the mirrored partitions of a hybrid MBR from a device or image.
+///
+/// Returns no entries, if the MBR is a plain protective MBR or not part of a GPT.
+pub fn read_hybrid_mbr(dev: &Path) -> Result<Vec<HybridMbrEntry>, Report<DiskError>> {
+    let mut file = File::open(dev).whatever("unable to open device")?;
+    let mut mbr = [0; 512];
+    file.read_exact(&mut mbr)
+        .whatever("unable to read MBR from device")?;
+    Ok(parse_hybrid_mbr_entries(&mbr))
+}
+
+/// Extract the mirrored partitions from a raw hybrid MBR.
+fn parse_hybrid_mbr_entries(mbr: &[u8; 512]) -> Vec<HybridMbrEntry> {
+    let raw_entries = (0..4)
+        .map(|idx| &mbr[MBR_PARTITIONS_OFFSET + idx * 16..MBR_PARTITIONS_OFFSET + (idx + 1) * 16])
+        .collect::<Vec<_>>();
+    if mbr[510..512] != [0x55, 0xAA] || !raw_entries.iter().any(|entry| entry[4] == 0xEE) {
+        return Vec::new();
+    }
+    raw_entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !matches!(entry[4], 0x00 | 0xEE))
+        .map(|(idx, entry)| HybridMbrEntry {
+            number: idx as u8 + 1,
+            ty: entry[4],
+            bootable: entry[0] == 0x80,
+        })
+        .collect()
+}
+
+/// Offset of the partition entries in the MBR.
+const MBR_PARTITIONS_OFFSET: usize = 446;
+
+/// Compute the raw partition entries of a hybrid MBR.
+fn hybrid_mbr_entries(
+    table: &PartitionTable,
+    entries: &[HybridMbrEntry],
+) -> Result<[[u8; 16]; 4], Report<DiskError>> {
+    if !table.is_gpt() {
+        bail!("hybrid MBRs require a GPT partition table");
+    }
+    let mut slots = [None; 4];
+    for entry in entries {
+        let Some(slot) = slots.get_mut(usize::from(entry.number).wrapping_sub(1)) else {
+            bail!(
+                "partition {} cannot be included in hybrid MBR (only partitions 1 to 4)",
+                entry.number
+            );
+        };
+        let Some(partition) = table.partitions.iter().find(|p| p.number == entry.number) else {
+            bail!("partition {} does not exist", entry.number);
+        };
+        let (Ok(start), Ok(size)) = (
+            u32::try_from(partition.start.into_raw()),
+            u32::try_from(partition.size.into_raw()),
+        ) else {
+            bail!("partition {} is not addressable with an MBR", entry.number);
+        };
+        if slot.is_some() {
+            bail!("partition {} is included twice in hybrid MBR", entry.number);
+        }
+        *slot = Some(raw_mbr_entry(entry.bootable, entry.ty, start, size));
+    }
+    let Some(protective) = slots.iter().position(Option::is_none) else {
+        bail!("hybrid MBR needs a free slot for the protective partition");
+    };
+    let first_start = entries
+        .iter()
+        .filter_map(|entry| table.partitions.iter().find(|p| p.number == entry.number))
+        .map(|partition| partition.start.into_raw())
+        .min()
+        .unwrap_or(table.disk_size.into_raw());
+    let protective_size = u32::try_from(first_start.saturating_sub(1)).unwrap_or(u32::MAX);
+    slots[protective] = Some(raw_mbr_entry(false, 0xEE, 1, protective_size));
+    Ok(slots.map(Option::unwrap_or_default))
+}
+
+/// Encode a raw MBR partition entry using LBA addressing.
+fn raw_mbr_entry(bootable: bool, ty: u8, start: u32, size: u32) -> [u8; 16] {
+    let mut entry = [0; 16];
+    entry[0] = if bootable { 0x80 } else { 0x00 };
+    // We do not use CHS addressing and set the CHS addresses to their maximum.
+    entry[1..4].copy_from_slice(&[0xFE, 0xFF, 0xFF]);
+    entry[4] = ty;
+    entry[5..8].copy_from_slice(&[0xFE, 0xFF, 0xFF]);
+    entry[8..12].copy_from_slice(&start.to_le_bytes());
+    entry[12..16].copy_from_slice(&size.to_le_bytes());
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::gpt::{gpt_types, GptAttributes, Guid};
+    use crate::disk::{DiskId, NumBlocks, Partition};
+
+    fn gpt_partition(number: u8, start: u64, size: u64) -> Partition {
+        Partition {
+            number,
+            start: NumBlocks::from_raw(start),
+            size: NumBlocks::from_raw(size),
+            ty: gpt_types::LINUX,
+            name: None,
+            gpt_id: None,
+            attributes: GptAttributes::NONE,
+        }
+    }
+
+    #[test]
+    pub fn test_hybrid_mbr_entries() {
+        let mut table = PartitionTable::new(
+            DiskId::Gpt(Guid::from_bytes([1; 16])),
+            NumBlocks::from_raw(1 << 20),
+        );
+        table.partitions = vec![
+            gpt_partition(1, 2048, 2048),
+            gpt_partition(2, 4096, 4096),
+            gpt_partition(3, 8192, 4096),
+        ];
+        let entries = hybrid_mbr_entries(
+            &table,
+            &[HybridMbrEntry {
+                number: 2,
+                ty: 0x0C,
+                bootable: true,
+            }],
+        )
+        .unwrap();
+        assert_eq!(entries[0], raw_mbr_entry(false, 0xEE, 1, 4095));
+        assert_eq!(entries[1], raw_mbr_entry(true, 0x0C, 4096, 4096));
+        assert_eq!(entries[2], [0; 16]);
+        assert_eq!(entries[3], [0; 16]);
+        let mut mbr = [0; 512];
+        for (idx, entry) in entries.iter().enumerate() {
+            let offset = MBR_PARTITIONS_OFFSET + idx * 16;
+            mbr[offset..offset + 16].copy_from_slice(entry);
+        }
+        mbr[510] = 0x55;
+        mbr[511] = 0xAA;
+        assert_eq!(
+            parse_hybrid_mbr_entries(&mbr),
+            vec![HybridMbrEntry {
+                number: 2,
+                ty: 0x0C,
+                bootable: true,
+            }]
+        );
+        mbr[MBR_PARTITIONS_OFFSET + 4] = 0x83;
+        assert!(parse_hybrid_mbr_entries(&mbr).is_empty());
+        assert!(hybrid_mbr_entries(
+            &table,
+            &[HybridMbrEntry {
+                number: 5,
+                ty: 0x0C,
+                bootable: false,
+            }]
+        )
+        .is_err());
+    }
 }
@@ -1,13 +1,14 @@
 //! Utilities for working with disks, disk images, and disk streams.
 
+use std::io::Read;
 use std::path::Path;
 
 use rand::Rng;
-use reportify::{bail, Report};
+use reportify::{bail, Report, ResultExt};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use self::gpt::{Guid, GPT_TABLE_BLOCKS, GUID_STRING_LENGTH};
+use self::gpt::{GptAttributes, Guid, GPT_TABLE_BLOCKS, GUID_STRING_LENGTH};
 use crate::partitions::DiskError;
 use crate::utils::ascii_numbers::{parse_ascii_decimal_digit, Case};
 use crate::utils::units::{NumBytes, Quantity, Unit};
@@ -85,6 +86,18 @@ impl PartitionTable {
         sfdisk::sfdisk_read(dev.as_ref())
     }
 
+    /// Read the partition table of the given device, if it has one.
+    ///
+    /// Returns `None` only if the device does not have a partition table at all. Any
+    /// other error, e.g., an I/O error or an unsupported partition table, is returned.
+    pub fn read_optional(dev: impl AsRef<Path>) -> Result<Option<Self>, Report<DiskError>> {
+        let dev = dev.as_ref();
+        if !has_partition_table(dev).whatever("unable to probe for partition table")? {
+            return Ok(None);
+        }
+        Self::read(dev).map(Some)
+    }
+
     /// The size of the disk in bytes.
     pub fn size(&self) -> NumBytes {
         NumBytes::from_raw(self.block_size.into_raw() * self.disk_size.into_raw())
@@ -209,10 +222,15 @@ pub struct Partition {
     pub size: NumBlocks,
     /// Type of the partition.
     pub ty: PartitionType,
-    /// Optional name of the partition.
+    /// Optional name of the partition (GPT only).
     pub name: Option<String>,
     /// Optional unique identifier of the partition.
     pub gpt_id: Option<gpt::Guid>,
+    /// Attribute flags of the partition.
+    ///
+    /// For MBR partitions, only [`GptAttributes::LEGACY_BIOS_BOOTABLE`] is supported and
+    /// corresponds to the bootable flag.
+    pub attributes: GptAttributes,
 }
 
 impl Partition {
@@ -427,6 +445,28 @@ impl<'de> serde::Deserialize<'de> for NumBytes {
     }
 }
 
+/// Check whether the device has a partition table.
+///
+/// Every MBR partition table carries a boot signature and every GPT starts with a
+/// protective MBR followed by a header signature in the next logical block.
+fn has_partition_table(dev: &Path) -> std::io::Result<bool> {
+    let mut file = std::fs::File::open(dev)?;
+    let mut buffer = vec![0; 4096 + 512];
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    let buffer = &buffer[..filled];
+    let has_mbr_signature = buffer.get(510..512) == Some(&[0x55, 0xAA]);
+    let has_gpt_header = [512, 4096]
+        .iter()
+        .any(|offset| buffer.get(*offset..*offset + 8) == Some(b"EFI PART"));
+    Ok(has_mbr_signature || has_gpt_header)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -458,4 +498,21 @@ mod tests {
             NumBlocks::from_raw(2048)
         )
     }
+
+    #[test]
+    pub fn test_has_partition_table() {
+        let mut image = tempfile::NamedTempFile::new().unwrap();
+        let mut data = vec![0; 1 << 16];
+        std::io::Write::write_all(&mut image, &data).unwrap();
+        assert!(!has_partition_table(image.path()).unwrap());
+        data[510] = 0x55;
+        data[511] = 0xAA;
+        std::fs::write(image.path(), &data).unwrap();
+        assert!(has_partition_table(image.path()).unwrap());
+        data[510] = 0;
+        data[512..520].copy_from_slice(b"EFI PART");
+        std::fs::write(image.path(), &data).unwrap();
+        assert!(has_partition_table(image.path()).unwrap());
+        assert!(PartitionTable::read_optional("/nonexistent/disk").is_err());
+    }
 }
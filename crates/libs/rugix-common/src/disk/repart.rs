@@ -4,7 +4,7 @@ use reportify::{bail, Report};
 use serde::Deserialize;
 
 use super::{parse_size, PartitionTable, PartitionTableType};
-use crate::disk::gpt::gpt_types;
+use crate::disk::gpt::{gpt_types, GptAttributes};
 use crate::disk::mbr::mbr_types;
 use crate::disk::{NumBlocks, Partition, PartitionType};
 use crate::partitions::DiskError;
@@ -15,6 +15,9 @@ use crate::utils::units::NumBytes;
 pub struct PartitionSchema {
     #[serde(rename = "type")]
     pub ty: PartitionTableType,
+    /// Alignment of the start of new partitions (defaults to 1MiB).
+    #[serde(default)]
+    pub alignment: Option<NumBytes>,
     pub partitions: Vec<SchemaPartition>,
 }
 
@@ -26,6 +29,13 @@ pub struct SchemaPartition {
     pub size: Option<NumBytes>,
     #[serde(rename = "type")]
     pub ty: Option<PartitionType>,
+    /// Attribute flags of the partition.
+    pub attributes: Option<GptAttributes>,
+    /// Grow the partition into all the available space.
+    ///
+    /// The available space extends to the next existing partition or the end of the disk.
+    #[serde(default)]
+    pub grow: bool,
 }
 
 /// Repartition the given table based on the provided schema.
@@ -47,13 +57,27 @@ pub fn repart(
         PartitionTableType::Gpt => gpt_types::LINUX,
         PartitionTableType::Mbr => mbr_types::LINUX,
     };
-    let align = NumBlocks::from_raw(2048);
+    let align = match schema.alignment {
+        Some(alignment) => old_table.bytes_to_blocks(alignment),
+        None => NumBlocks::from_raw(2048),
+    };
+    if align.into_raw() == 0 {
+        bail!("partition alignment must not be zero");
+    }
     let mut new_table = old_table.clone();
     let mut next_start = old_table.first_usable_block().ceil_align_to(align);
     let mut last_usable = old_table.last_usable_block();
     let mut in_extended = false;
     let mut has_changed = false;
+    // Logical MBR partitions are numbered starting with 5, independently of the number
+    // of primary partitions preceding the extended partition.
+    let mut next_number = 1;
     for (idx, partition) in schema.partitions.iter().enumerate() {
+        let number = next_number;
+        next_number += 1;
+        if schema.ty == PartitionTableType::Mbr && number > 4 && !in_extended {
+            bail!("MBR supports at most four primary partitions, use an extended partition");
+        }
         eprintln!(
             "Partition: {}, Next Start: {next_start}, Last Useable: {last_usable}",
             idx + 1
@@ -100,7 +124,11 @@ pub fn repart(
             // We need to add one block as the last block is still usable.
             last_usable - start + NumBlocks::ONE
         };
-        let mut size = size.unwrap_or(available).min(available);
+        let mut size = if partition.grow {
+            available
+        } else {
+            size.unwrap_or(available).min(available)
+        };
         eprintln!(
             "  Start: {start}, End: {}, Available: {available}, Size: {size}",
             start + size - NumBlocks::ONE,
@@ -114,18 +142,23 @@ pub fn repart(
         } else {
             has_changed = true;
             new_table.partitions.push(Partition {
-                number: (idx + 1) as u8,
+                number,
                 start,
                 size,
                 ty,
-                name: None,
+                name: partition.name.clone(),
                 gpt_id: None,
+                attributes: partition.attributes.unwrap_or_default(),
             })
         }
         if ty.is_extended() {
+            if in_extended {
+                bail!("nested extended partitions are not allowed");
+            }
             last_usable = start + size - NumBlocks::ONE;
             in_extended = true;
             next_start = start;
+            next_number = 5;
         } else {
             next_start = start + size;
         }
@@ -178,104 +211,239 @@ fn check_new_table(
     Ok(())
 }
 
-pub fn generic_mbr_partition_schema(system_size: NumBytes) -> PartitionSchema {
+/// Mismatch between a partition table and a partition schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutMismatch {
+    /// The type of the partition table differs.
+    TableType {
+        expected: PartitionTableType,
+        actual: PartitionTableType,
+    },
+    /// A partition of the schema does not exist.
+    MissingPartition { number: u8 },
+    /// The type of a partition differs.
+    PartitionType {
+        number: u8,
+        expected: PartitionType,
+        actual: PartitionType,
+    },
+    /// A partition is smaller than required by the schema.
+    PartitionSize {
+        number: u8,
+        expected: NumBlocks,
+        actual: NumBlocks,
+    },
+    /// The name of a partition differs.
+    PartitionName {
+        number: u8,
+        expected: String,
+        actual: Option<String>,
+    },
+    /// A partition exists which is not part of the schema.
+    ExtraPartition { number: u8 },
+}
+
+impl std::fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutMismatch::TableType { expected, actual } => {
+                write!(f, "partition table is {actual} but {expected} is expected")
+            }
+            LayoutMismatch::MissingPartition { number } => {
+                write!(f, "partition {number} does not exist")
+            }
+            LayoutMismatch::PartitionType {
+                number,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "partition {number} has type {actual} but {expected} is expected"
+            ),
+            LayoutMismatch::PartitionSize {
+                number,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "partition {number} has {actual} blocks but at least {expected} are expected"
+            ),
+            LayoutMismatch::PartitionName {
+                number,
+                expected,
+                actual,
+            } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "partition {number} is named {actual:?} but {expected:?} is expected"
+                ),
+                None => write!(
+                    f,
+                    "partition {number} has no name but {expected:?} is expected"
+                ),
+            },
+            LayoutMismatch::ExtraPartition { number } => {
+                write!(f, "partition {number} is not part of the layout")
+            }
+        }
+    }
+}
+
+/// Compare the given table with the layout expected by the provided schema.
+///
+/// Partitions are matched up by their index, just as [`repart`] does. Partitions must
+/// be at least as large as required by the schema, as they may have been grown.
+pub fn check_layout(table: &PartitionTable, schema: &PartitionSchema) -> Vec<LayoutMismatch> {
+    if table.ty() != schema.ty {
+        return vec![LayoutMismatch::TableType {
+            expected: schema.ty,
+            actual: table.ty(),
+        }];
+    }
+    let default_partition_ty = match schema.ty {
+        PartitionTableType::Gpt => gpt_types::LINUX,
+        PartitionTableType::Mbr => mbr_types::LINUX,
+    };
+    let mut mismatches = Vec::new();
+    let mut next_number = 1;
+    for (idx, partition) in schema.partitions.iter().enumerate() {
+        let ty = partition.ty.unwrap_or(default_partition_ty);
+        let Some(actual) = table.partitions.get(idx) else {
+            mismatches.push(LayoutMismatch::MissingPartition {
+                number: next_number,
+            });
+            next_number = if ty.is_extended() { 5 } else { next_number + 1 };
+            continue;
+        };
+        next_number = if ty.is_extended() {
+            5
+        } else {
+            actual.number + 1
+        };
+        if actual.ty != ty {
+            mismatches.push(LayoutMismatch::PartitionType {
+                number: actual.number,
+                expected: ty,
+                actual: actual.ty,
+            });
+        }
+        if let Some(size) = partition.size.filter(|_| !partition.grow) {
+            let expected = table.bytes_to_blocks(size);
+            if actual.size < expected {
+                mismatches.push(LayoutMismatch::PartitionSize {
+                    number: actual.number,
+                    expected,
+                    actual: actual.size,
+                });
+            }
+        }
+        if schema.ty == PartitionTableType::Gpt {
+            if let Some(name) = &partition.name {
+                if actual.name.as_ref() != Some(name) {
+                    mismatches.push(LayoutMismatch::PartitionName {
+                        number: actual.number,
+                        expected: name.clone(),
+                        actual: actual.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for extra in table.partitions.iter().skip(schema.partitions.len()) {
+        mismatches.push(LayoutMismatch::ExtraPartition {
+            number: extra.number,
+        });
+    }
+    mismatches
+}
+
+/// Parameters of the generic default layouts.
+#[derive(Debug, Clone, Copy)]
+pub struct DefaultLayout {
+    /// Size of the config partition (defaults to 256MiB).
+    pub config_size: Option<NumBytes>,
+    /// Size of the boot partitions (defaults to 128MiB for MBR and 256MiB for GPT).
+    pub boot_size: Option<NumBytes>,
+    /// Size of the system partitions.
+    pub system_size: NumBytes,
+    /// Alignment of the start of the partitions (defaults to 1MiB).
+    pub alignment: Option<NumBytes>,
+}
+
+impl DefaultLayout {
+    /// Create the parameters of a default layout with the given system size.
+    pub fn new(system_size: NumBytes) -> Self {
+        Self {
+            config_size: None,
+            boot_size: None,
+            system_size,
+            alignment: None,
+        }
+    }
+
+    /// Size of the config partition.
+    fn config_size(&self) -> NumBytes {
+        self.config_size
+            .unwrap_or_else(|| parse_size("256M").unwrap())
+    }
+}
+
+/// Schema partition with a fixed size and type.
+fn fixed_partition(size: Option<NumBytes>, ty: PartitionType) -> SchemaPartition {
+    SchemaPartition {
+        number: None,
+        name: None,
+        size,
+        ty: Some(ty),
+        attributes: None,
+        grow: false,
+    }
+}
+
+pub fn generic_mbr_partition_schema(layout: &DefaultLayout) -> PartitionSchema {
+    let boot_size = layout
+        .boot_size
+        .unwrap_or_else(|| parse_size("128M").unwrap());
     PartitionSchema {
         ty: PartitionTableType::Mbr,
+        alignment: layout.alignment,
         partitions: vec![
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("256M").unwrap()),
-                ty: Some(mbr_types::FAT32_LBA),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("128M").unwrap()),
-                ty: Some(mbr_types::FAT32_LBA),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("128M").unwrap()),
-                ty: Some(mbr_types::FAT32_LBA),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: None,
-                ty: Some(mbr_types::EXTENDED),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(system_size),
-                ty: Some(mbr_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(system_size),
-                ty: Some(mbr_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: None,
-                ty: Some(mbr_types::LINUX),
-            },
+            fixed_partition(Some(layout.config_size()), mbr_types::FAT32_LBA),
+            fixed_partition(Some(boot_size), mbr_types::FAT32_LBA),
+            fixed_partition(Some(boot_size), mbr_types::FAT32_LBA),
+            fixed_partition(None, mbr_types::EXTENDED),
+            fixed_partition(Some(layout.system_size), mbr_types::LINUX),
+            fixed_partition(Some(layout.system_size), mbr_types::LINUX),
+            fixed_partition(None, mbr_types::LINUX),
         ],
     }
 }
 
-pub fn generic_efi_partition_schema(system_size: NumBytes) -> PartitionSchema {
+pub fn generic_efi_partition_schema(layout: &DefaultLayout) -> PartitionSchema {
+    let boot_size = layout
+        .boot_size
+        .unwrap_or_else(|| parse_size("256M").unwrap());
     PartitionSchema {
         ty: PartitionTableType::Gpt,
+        alignment: layout.alignment,
         partitions: vec![
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("256M").unwrap()),
-                ty: Some(gpt_types::EFI),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("256M").unwrap()),
-                ty: Some(gpt_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(parse_size("256M").unwrap()),
-                ty: Some(gpt_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(system_size),
-                ty: Some(gpt_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: Some(system_size),
-                ty: Some(gpt_types::LINUX),
-            },
-            SchemaPartition {
-                number: None,
-                name: None,
-                size: None,
-                ty: Some(gpt_types::LINUX),
-            },
+            fixed_partition(Some(layout.config_size()), gpt_types::EFI),
+            fixed_partition(Some(boot_size), gpt_types::LINUX),
+            fixed_partition(Some(boot_size), gpt_types::LINUX),
+            fixed_partition(Some(layout.system_size), gpt_types::LINUX),
+            fixed_partition(Some(layout.system_size), gpt_types::LINUX),
+            fixed_partition(None, gpt_types::LINUX),
         ],
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{generic_efi_partition_schema, generic_mbr_partition_schema, repart};
-    use crate::disk::gpt::{gpt_types, Guid};
+    use super::{
+        check_layout, generic_efi_partition_schema, generic_mbr_partition_schema, repart,
+        DefaultLayout, LayoutMismatch, PartitionSchema, PartitionTableType, SchemaPartition,
+    };
+    use crate::disk::gpt::{gpt_types, GptAttributes, Guid};
     use crate::disk::mbr::{mbr_types, MbrId};
     use crate::disk::{parse_size, DiskId, NumBlocks, Partition, PartitionTable};
 
@@ -296,6 +464,7 @@ mod tests {
                 ty: mbr_types::FAT32_LBA,
                 name: None,
                 gpt_id: None,
+                attributes: GptAttributes::NONE,
             })
         }
         old_table.partitions.push(Partition {
@@ -305,6 +474,7 @@ mod tests {
             ty: mbr_types::EXTENDED,
             name: None,
             gpt_id: None,
+            attributes: GptAttributes::NONE,
         });
         old_table.partitions.push(Partition {
             number: 5,
@@ -313,15 +483,84 @@ mod tests {
             ty: mbr_types::LINUX,
             name: None,
             gpt_id: None,
+            attributes: GptAttributes::NONE,
         });
         old_table.validate().unwrap();
         repart(
             &old_table,
-            &generic_mbr_partition_schema(parse_size("4G").unwrap()),
+            &generic_mbr_partition_schema(&DefaultLayout::new(parse_size("4G").unwrap())),
         )
         .unwrap();
     }
 
+    #[test]
+    fn test_repart_mbr_logical() {
+        let mut old_table = PartitionTable::new(
+            DiskId::Mbr(MbrId::new(0x123456)),
+            NumBlocks::from_raw(1 << 22),
+        );
+        old_table.partitions.push(Partition {
+            number: 1,
+            start: 2048.into(),
+            size: 2048.into(),
+            ty: mbr_types::FAT32_LBA,
+            name: None,
+            gpt_id: None,
+            attributes: GptAttributes::NONE,
+        });
+        old_table.validate().unwrap();
+        let partition = |size: Option<&str>, ty| SchemaPartition {
+            number: None,
+            name: None,
+            size: size.map(|size| parse_size(size).unwrap()),
+            ty: Some(ty),
+            attributes: None,
+            grow: false,
+        };
+        let schema = PartitionSchema {
+            ty: PartitionTableType::Mbr,
+            alignment: None,
+            partitions: vec![
+                partition(Some("1M"), mbr_types::FAT32_LBA),
+                partition(None, mbr_types::EXTENDED),
+                partition(Some("64M"), mbr_types::LINUX),
+                partition(None, mbr_types::LINUX),
+            ],
+        };
+        let new_table = repart(&old_table, &schema).unwrap().unwrap();
+        let numbers = new_table
+            .partitions
+            .iter()
+            .map(|partition| partition.number)
+            .collect::<Vec<_>>();
+        // The extended partition is the second one, logical partitions start with 5.
+        assert_eq!(numbers, [1, 2, 5, 6]);
+        // The last logical partition ends with the extended partition.
+        assert_eq!(new_table.partitions[3].end(), new_table.partitions[1].end());
+    }
+
+    #[test]
+    fn test_repart_too_many_primary_partitions() {
+        let old_table = PartitionTable::new(
+            DiskId::Mbr(MbrId::new(0x123456)),
+            NumBlocks::from_raw(1 << 22),
+        );
+        let partition = SchemaPartition {
+            number: None,
+            name: None,
+            size: Some(parse_size("1M").unwrap()),
+            ty: None,
+            attributes: None,
+            grow: false,
+        };
+        let schema = PartitionSchema {
+            ty: PartitionTableType::Mbr,
+            alignment: None,
+            partitions: vec![partition; 5],
+        };
+        assert!(repart(&old_table, &schema).is_err());
+    }
+
     #[test]
     fn test_repart_gpt() {
         let mut old_table = PartitionTable::new(
@@ -343,6 +582,7 @@ mod tests {
                 },
                 name: None,
                 gpt_id: None,
+                attributes: GptAttributes::NONE,
             })
         }
         old_table.partitions.push(Partition {
@@ -352,12 +592,133 @@ mod tests {
             ty: gpt_types::LINUX,
             name: None,
             gpt_id: None,
+            attributes: GptAttributes::NONE,
         });
         old_table.validate().unwrap();
         repart(
             &old_table,
-            &generic_efi_partition_schema(parse_size("4G").unwrap()),
+            &generic_efi_partition_schema(&DefaultLayout::new(parse_size("4G").unwrap())),
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_repart_grow() {
+        let mut old_table = PartitionTable::new(
+            DiskId::Gpt(Guid::from_random_bytes([0x42; 16])),
+            NumBlocks::from_raw(1 << 22),
+        );
+        for (number, start) in [(1, 2048), (2, 1 << 20)] {
+            old_table.partitions.push(Partition {
+                number,
+                start: NumBlocks::from_raw(start),
+                size: NumBlocks::from_raw(2048),
+                ty: gpt_types::LINUX,
+                name: None,
+                gpt_id: None,
+                attributes: GptAttributes::NONE,
+            });
+        }
+        old_table.validate().unwrap();
+        let partition = |size: Option<&str>, grow| SchemaPartition {
+            number: None,
+            name: None,
+            size: size.map(|size| parse_size(size).unwrap()),
+            ty: None,
+            attributes: None,
+            grow,
+        };
+        let schema = PartitionSchema {
+            ty: PartitionTableType::Gpt,
+            alignment: None,
+            partitions: vec![partition(Some("1M"), true), partition(Some("1M"), true)],
+        };
+        let new_table = repart(&old_table, &schema).unwrap().unwrap();
+        // The first partition grows up to the second one.
+        assert_eq!(
+            new_table.partitions[0].size,
+            NumBlocks::from_raw((1 << 20) - 2048)
+        );
+        // The second partition grows up to the end of the disk.
+        assert_eq!(
+            new_table.partitions[1].end(),
+            old_table.last_usable_block() + NumBlocks::ONE
+        );
+    }
+
+    #[test]
+    fn test_generic_schema_parameters() {
+        let old_table = PartitionTable::new(
+            DiskId::Gpt(Guid::from_random_bytes([0x42; 16])),
+            NumBlocks::from_raw(1 << 26),
+        );
+        let layout = DefaultLayout {
+            config_size: Some(parse_size("64M").unwrap()),
+            boot_size: Some(parse_size("512M").unwrap()),
+            system_size: parse_size("4G").unwrap(),
+            alignment: Some(parse_size("4M").unwrap()),
+        };
+        let schema = generic_efi_partition_schema(&layout);
+        let new_table = repart(&old_table, &schema).unwrap().unwrap();
+        let sizes = new_table
+            .partitions
+            .iter()
+            .take(5)
+            .map(|partition| partition.size)
+            .collect::<Vec<_>>();
+        let blocks = |size| old_table.bytes_to_blocks(parse_size(size).unwrap());
+        assert_eq!(
+            sizes,
+            [
+                blocks("64M"),
+                blocks("512M"),
+                blocks("512M"),
+                blocks("4G"),
+                blocks("4G")
+            ]
+        );
+        for partition in &new_table.partitions {
+            assert_eq!(partition.start.into_raw() % blocks("4M").into_raw(), 0);
+        }
+        assert!(check_layout(&new_table, &schema).is_empty());
+        // The default sizes of the boot partitions do not match.
+        let default = generic_efi_partition_schema(&DefaultLayout::new(layout.system_size));
+        assert!(!check_layout(&new_table, &default).is_empty());
+    }
+
+    #[test]
+    fn test_check_layout() {
+        let old_table = PartitionTable::new(
+            DiskId::Gpt(Guid::from_random_bytes([0x42; 16])),
+            NumBlocks::from_raw(1 << 26),
+        );
+        let schema = generic_efi_partition_schema(&DefaultLayout::new(parse_size("4G").unwrap()));
+        let new_table = repart(&old_table, &schema).unwrap().unwrap();
+        assert!(check_layout(&new_table, &schema).is_empty());
+        // A table created with smaller system partitions does not match.
+        let larger = generic_efi_partition_schema(&DefaultLayout::new(parse_size("8G").unwrap()));
+        let mismatches = check_layout(&new_table, &larger);
+        assert!(matches!(
+            mismatches[..],
+            [
+                LayoutMismatch::PartitionSize { number: 4, .. },
+                LayoutMismatch::PartitionSize { number: 5, .. }
+            ]
+        ));
+        // Partitions which have not been created yet are reported as missing.
+        assert_eq!(
+            check_layout(&old_table, &schema),
+            (1..=6)
+                .map(|number| LayoutMismatch::MissingPartition { number })
+                .collect::<Vec<_>>()
+        );
+        // The partition table type must match.
+        assert!(matches!(
+            check_layout(
+                &new_table,
+                &generic_mbr_partition_schema(&DefaultLayout::new(parse_size("4G").unwrap()))
+            )[..],
+            [LayoutMismatch::TableType { .. }]
+        ));
+    }
 }
@@ -8,7 +8,7 @@ use serde::Deserialize;
 use xscript::{read_str, run, Run};
 
 use super::blkdev::BlockDevice;
-use super::gpt::Guid;
+use super::gpt::{GptAttributes, Guid};
 use super::{mbr, DiskId, NumBlocks, Partition, PartitionTable, PartitionType};
 use crate::partitions::DiskError;
 use crate::utils::units::NumBytes;
@@ -90,13 +90,24 @@ pub(crate) fn sfdisk_read(dev: &Path) -> Result<PartitionTable, Report<DiskError
                     })
                 })
                 .transpose()?;
+            let mut attributes = partition
+                .attrs
+                .as_deref()
+                .map(GptAttributes::from_str)
+                .transpose()
+                .whatever("unable to parse partition attributes from `sfdisk` output")?
+                .unwrap_or_default();
+            if partition.bootable {
+                attributes |= GptAttributes::LEGACY_BIOS_BOOTABLE;
+            }
             Ok(Partition {
                 number,
                 start: NumBlocks::from_raw(partition.start),
                 size: NumBlocks::from_raw(partition.size),
                 ty,
-                name: Some(partition.node),
+                name: partition.name,
                 gpt_id,
+                attributes,
             })
         })
         .collect::<Result<Vec<_>, Report<DiskError>>>()?;
@@ -109,6 +120,15 @@ pub(crate) fn sfdisk_read(dev: &Path) -> Result<PartitionTable, Report<DiskError
     })
 }
 
+/// Move the backup GPT header and partition entries to the end of the device.
+///
+/// This also rewrites a damaged backup based on the primary header.
+pub(crate) fn sfdisk_relocate_gpt_backup(dev: &Path) -> Result<(), Report<DiskError>> {
+    run!([SFDISK, "--relocate", "gpt-bak-std", dev])
+        .whatever("unable to rewrite backup GPT header")?;
+    Ok(())
+}
+
 pub(crate) fn sfdisk_write(table: &PartitionTable, dev: &Path) -> Result<(), Report<DiskError>> {
     let mut script = String::new();
     match table.disk_id {
@@ -129,6 +149,24 @@ pub(crate) fn sfdisk_write(table: &PartitionTable, dev: &Path) -> Result<(), Rep
         if let Some(gpt_id) = partition.gpt_id {
             write!(&mut script, ",uuid={}", gpt_id).unwrap();
         }
+        match table.disk_id {
+            DiskId::Mbr(_) => {
+                if partition
+                    .attributes
+                    .contains(GptAttributes::LEGACY_BIOS_BOOTABLE)
+                {
+                    script.push_str(",bootable");
+                }
+            }
+            DiskId::Gpt(_) => {
+                if let Some(name) = &partition.name {
+                    write!(&mut script, ",name=\"{}\"", name.replace('"', "")).unwrap();
+                }
+                if !partition.attributes.is_empty() {
+                    write!(&mut script, ",attrs=\"{}\"", partition.attributes).unwrap();
+                }
+            }
+        }
         script.push('\n');
     }
 
@@ -177,4 +215,8 @@ pub struct SfdiskJsonPartition {
     #[serde(rename = "type")]
     ty: String,
     uuid: Option<String>,
+    name: Option<String>,
+    attrs: Option<String>,
+    #[serde(default)]
+    bootable: bool,
 }
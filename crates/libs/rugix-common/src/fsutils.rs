@@ -1,21 +1,26 @@
 //! Utilities for working with the filesystem.
 
+use std::borrow::Borrow;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{self, Write};
 use std::os::fd::{AsRawFd, RawFd};
-use std::path::Path;
+use std::os::unix::fs::{FileExt, FileTypeExt};
+use std::path::{Component, Path, PathBuf};
 
 use nix::errno::Errno;
 use nix::fcntl::FallocateFlags;
 use nix::libc::off64_t;
 use nix::unistd::{lseek64, Whence};
-use reportify::{Report, ResultExt};
+use reportify::{bail, Report, ResultExt};
 use xscript::{run, Run};
 
 reportify::new_whatever_type! {
     FsError
 }
 
+/// Maximal number of symbolic links followed when resolving a path.
+const MAX_SYMLINKS: usize = 40;
+
 pub fn copy_recursive(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> Result<(), Report<FsError>> {
     let dst = dst.as_ref();
     let src = src.as_ref();
@@ -51,6 +56,187 @@ pub fn punch_hole(fd: RawFd, offset: off64_t, size: off64_t) -> Result<(), Repor
     Ok(())
 }
 
+/// Size of the blocks which are checked for zeros when writing sparsely.
+const SPARSE_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Create a sparse file of the given size.
+pub fn create_sparse_file(path: &Path, size: u64) -> Result<File, Report<FsError>> {
+    let file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .whatever("unable to create file")?;
+    file.set_len(size).whatever("unable to set file size")?;
+    Ok(file)
+}
+
+/// Writer skipping blocks of zeros.
+///
+/// Instead of writing blocks of zeros, holes are punched into regular files and block
+/// devices are asked to zero the respective ranges (`BLKZEROOUT`), which is much faster
+/// for mostly-empty images. If neither is supported, zeros are written as usual.
+#[derive(Debug)]
+pub struct SparseWriter<F = File> {
+    file: F,
+    is_block_device: bool,
+    /// Offset of the next write.
+    offset: u64,
+    /// Size of the range of zeros before the offset which has not been written yet.
+    pending_zeros: u64,
+}
+
+impl<F: Borrow<File>> SparseWriter<F> {
+    /// Create a writer starting at the given offset.
+    pub fn new(file: F, offset: u64) -> Result<Self, Report<FsError>> {
+        let is_block_device = file
+            .borrow()
+            .metadata()
+            .whatever("unable to read file metadata")?
+            .file_type()
+            .is_block_device();
+        Ok(Self {
+            file,
+            is_block_device,
+            offset,
+            pending_zeros: 0,
+        })
+    }
+
+    /// Offset of the next write.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Underlying file.
+    ///
+    /// Note that pending zeros may not have been written yet, see [`Write::flush`].
+    pub fn file(&self) -> &File {
+        self.file.borrow()
+    }
+
+    /// Write the given bytes skipping blocks of zeros.
+    pub fn write(&mut self, mut bytes: &[u8]) -> Result<(), Report<FsError>> {
+        while !bytes.is_empty() {
+            // Align the blocks such that zeros are skipped in whole blocks.
+            let block_size = SPARSE_BLOCK_SIZE
+                - usize::try_from(self.offset % SPARSE_BLOCK_SIZE as u64).unwrap();
+            let (block, rest) = bytes.split_at(block_size.min(bytes.len()));
+            if block.iter().all(|byte| *byte == 0) {
+                self.skip_zeros(block.len() as u64);
+            } else {
+                self.flush_zeros()?;
+                self.file
+                    .borrow()
+                    .write_all_at(block, self.offset)
+                    .whatever("unable to write block")?;
+                self.offset += block.len() as u64;
+            }
+            bytes = rest;
+        }
+        Ok(())
+    }
+
+    /// Skip the given number of bytes which must be zero.
+    pub fn skip_zeros(&mut self, size: u64) {
+        self.pending_zeros += size;
+        self.offset += size;
+    }
+
+    /// Read bytes which have already been written.
+    pub fn read_exact_at(&mut self, buffer: &mut [u8], offset: u64) -> Result<(), Report<FsError>> {
+        self.flush_zeros()?;
+        self.file
+            .borrow()
+            .read_exact_at(buffer, offset)
+            .whatever("unable to read block")
+    }
+
+    /// Zero the pending range of zeros.
+    fn flush_zeros(&mut self) -> Result<(), Report<FsError>> {
+        if self.pending_zeros == 0 {
+            return Ok(());
+        }
+        let size = self.pending_zeros;
+        let offset = self.offset - size;
+        self.pending_zeros = 0;
+        let file = self.file.borrow();
+        let is_zeroed = if self.is_block_device {
+            // The kernel requires the range to be aligned to sectors.
+            offset.is_multiple_of(512)
+                && size.is_multiple_of(512)
+                && zero_out(file, offset, size).is_ok()
+        } else {
+            punch_hole(
+                file.as_raw_fd(),
+                off64_t::try_from(offset).unwrap(),
+                off64_t::try_from(size).unwrap(),
+            )
+            .is_ok()
+        };
+        if !is_zeroed {
+            let zeros = vec![0; SPARSE_BLOCK_SIZE];
+            let mut remaining = size;
+            while remaining > 0 {
+                let chunk = remaining.min(SPARSE_BLOCK_SIZE as u64);
+                file.write_all_at(&zeros[..chunk as usize], offset + size - remaining)
+                    .whatever("unable to write zeros")?;
+                remaining -= chunk;
+            }
+        }
+        Ok(())
+    }
+
+    /// Zero any pending range of zeros and return the underlying file.
+    ///
+    /// Regular files are extended such that they include trailing zeros.
+    pub fn finish(mut self) -> Result<F, Report<FsError>> {
+        self.flush_zeros()?;
+        if !self.is_block_device {
+            let file = self.file.borrow();
+            let len = file
+                .metadata()
+                .whatever("unable to read file metadata")?
+                .len();
+            if len < self.offset {
+                file.set_len(self.offset)
+                    .whatever("unable to set file size")?;
+            }
+        }
+        Ok(self.file)
+    }
+}
+
+impl<F: Borrow<File>> Write for SparseWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        SparseWriter::write(self, buf).map_err(|error| io::Error::other(error.to_string()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flush_zeros()
+            .map_err(|error| io::Error::other(error.to_string()))
+    }
+}
+
+/// Zero a range of a block device.
+fn zero_out(file: &File, offset: u64, size: u64) -> nix::Result<()> {
+    nix::ioctl_write_ptr_bad! {
+        /// Zero a range of a block device (`BLKZEROOUT`).
+        ioctl_zero_out, nix::request_code_none!(0x12, 127), [u64; 2]
+    }
+
+    unsafe {
+        // SAFETY: The file points to a block device and the range is a `[u64; 2]`.
+        ioctl_zero_out(file.as_raw_fd(), &[offset, size])?;
+    }
+    Ok(())
+}
+
+/// Copy a range of bytes from one file into another preserving holes.
+///
+/// Holes of the source as well as blocks of zeros are not written, see [`SparseWriter`].
 pub fn copy_sparse(
     src: &mut File,
     dst: &mut File,
@@ -58,57 +244,161 @@ pub fn copy_sparse(
     dst_offset: u64,
     size: u64,
 ) -> Result<(), Report<FsError>> {
-    let mut src_offset = off64_t::try_from(src_offset).unwrap();
-    let mut dst_offset = off64_t::try_from(dst_offset).unwrap();
     let src_raw_fd = src.as_raw_fd();
-    let dst_raw_fd = dst.as_raw_fd();
-    lseek64(src_raw_fd, src_offset, Whence::SeekSet)
-        .whatever_with(|_| format!("unable to seek to {src_offset}"))?;
-    lseek64(dst_raw_fd, dst_offset, Whence::SeekSet)
-        .whatever_with(|_| format!("unable to seek to {dst_offset}"))?;
-    let mut total_remaining = usize::try_from(size).unwrap();
-    let mut buffer = vec![0; 8192];
-    while total_remaining > 0 {
-        // If there is no hole, then `next_hole` points to the end of the file as there
-        // always is an implicit hole at the end of any file.
-        let next_hole = lseek64(src_raw_fd, src_offset, Whence::SeekHole)
+    let mut writer = SparseWriter::new(&*dst, dst_offset)?;
+    let end = src_offset + size;
+    let mut offset = src_offset;
+    let mut buffer = vec![0; SPARSE_BLOCK_SIZE];
+    while offset < end {
+        let data_start = match lseek64(src_raw_fd, offset as off64_t, Whence::SeekData) {
+            Ok(data_start) => (data_start as u64).min(end),
+            // There is no more data, only a hole until the end of the file.
+            Err(Errno::ENXIO) => end,
+            error => error.whatever("unable to seek to next data")? as u64,
+        };
+        writer.skip_zeros(data_start - offset);
+        offset = data_start;
+        if offset >= end {
+            break;
+        }
+        // If there is no hole, then the next hole is the end of the file as there always
+        // is an implicit hole at the end of any file.
+        let data_end = lseek64(src_raw_fd, offset as off64_t, Whence::SeekHole)
             .whatever("unable to seek to next hole")?;
-        lseek64(src.as_raw_fd(), src_offset, Whence::SeekSet)
-            .whatever("unable to set source offset")?;
-        let chunk_size = usize::try_from(next_hole - src_offset).unwrap();
-        let mut chunk_remaining = chunk_size;
-        while chunk_remaining > 0 && total_remaining > 0 {
-            let chunk_read = buffer.len().min(chunk_remaining).min(total_remaining);
-            src.read_exact(&mut buffer[..chunk_read])
+        let data_end = (data_end as u64).min(end);
+        while offset < data_end {
+            let chunk = buffer.len().min((data_end - offset) as usize);
+            src.read_exact_at(&mut buffer[..chunk], offset)
                 .whatever("unable to read src bytes into buffer")?;
-            dst.write_all(&buffer[..chunk_read])
-                .whatever("unable to write buffer into dst")?;
-            chunk_remaining -= chunk_read;
-            total_remaining -= chunk_read;
-            dst_offset += chunk_read as i64;
+            writer.write(&buffer[..chunk])?;
+            offset += chunk as u64;
         }
-        if total_remaining > 0 {
-            src_offset = match lseek64(src_raw_fd, next_hole, Whence::SeekData) {
-                Ok(src_offset) => src_offset,
-                Err(Errno::ENXIO) => {
-                    lseek64(
-                        dst_raw_fd,
-                        total_remaining.try_into().unwrap(),
-                        Whence::SeekCur,
-                    )
-                    .whatever("unable to seek in dst")?;
-                    break;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Copy a file preserving holes.
+pub fn copy_file_sparse(src: &Path, dst: &Path) -> Result<(), Report<FsError>> {
+    let mut src = File::open(src).whatever_with(|_| format!("unable to open {src:?}"))?;
+    let size = src
+        .metadata()
+        .whatever("unable to read file metadata")?
+        .len();
+    let mut dst = create_sparse_file(dst, size)?;
+    copy_sparse(&mut src, &mut dst, 0, 0, size)
+}
+
+/// Resolve a path within the given root directory.
+///
+/// Symbolic links are followed component by component. Absolute link targets are
+/// interpreted relative to the root and `..` never leaves the root, mirroring path
+/// resolution in a `chroot`.
+pub fn resolve_in_root(root: &Path, path: &Path) -> Result<PathBuf, Report<FsError>> {
+    let mut pending = path
+        .components()
+        .rev()
+        .map(|component| component.as_os_str().to_owned())
+        .collect::<Vec<_>>();
+    let mut resolved = Vec::new();
+    let mut symlinks = 0;
+    while let Some(name) = pending.pop() {
+        match Path::new(&name).components().next() {
+            Some(Component::Normal(name)) => {
+                let mut candidate = root.to_path_buf();
+                candidate.extend(&resolved);
+                candidate.push(name);
+                let Ok(target) = fs::read_link(&candidate) else {
+                    // The component is not a symbolic link or does not exist.
+                    resolved.push(name.to_owned());
+                    continue;
+                };
+                symlinks += 1;
+                if symlinks > MAX_SYMLINKS {
+                    bail!("too many levels of symbolic links resolving {path:?}");
+                }
+                if target.is_absolute() {
+                    resolved.clear();
                 }
-                error => error.whatever("unable to seek in src")?,
-            };
-            let hole_size = src_offset - next_hole;
-            // TODO: Punch a hole in the destination file. This requires us to consider
-            // the block size of dst's filesystem.
-            // punch_hole(dst_raw_fd, dst_offset, hole_size)?;
-            dst_offset += hole_size;
-            lseek64(dst_raw_fd, hole_size, Whence::SeekCur).whatever("unable to seek in dst")?;
-            total_remaining -= usize::try_from(hole_size).unwrap();
+                pending.extend(
+                    target
+                        .components()
+                        .rev()
+                        .map(|component| component.as_os_str().to_owned()),
+                );
+            }
+            Some(Component::ParentDir) => {
+                resolved.pop();
+            }
+            Some(Component::RootDir | Component::CurDir) | None => { /* nothing to do */ }
+            Some(Component::Prefix(_)) => bail!("invalid path {path:?}"),
         }
     }
-    Ok(())
+    let mut path = root.to_path_buf();
+    path.extend(resolved);
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+
+    #[test]
+    fn test_copy_file_sparse() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.img");
+        let dst = dir.path().join("dst.img");
+        let mut data = vec![0; 5 * SPARSE_BLOCK_SIZE + 17];
+        data[SPARSE_BLOCK_SIZE + 3] = 1;
+        data[3 * SPARSE_BLOCK_SIZE - 1] = 2;
+        fs::write(&src, &data).unwrap();
+        // Existing contents of the destination must be overwritten.
+        fs::write(&dst, vec![0xFF; 6 * SPARSE_BLOCK_SIZE]).unwrap();
+        copy_file_sparse(&src, &dst).unwrap();
+        assert_eq!(fs::read(&dst).unwrap(), data);
+    }
+
+    #[test]
+    fn test_sparse_writer_trailing_zeros() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.img");
+        let file = File::create(&path).unwrap();
+        let mut writer = SparseWriter::new(file, 0).unwrap();
+        writer.write(&[1, 2, 3]).unwrap();
+        writer.write(&vec![0; 2 * SPARSE_BLOCK_SIZE]).unwrap();
+        let mut buffer = [0; 3];
+        writer.read_exact_at(&mut buffer, 0).unwrap();
+        assert_eq!(buffer, [1, 2, 3]);
+        writer.finish().unwrap();
+        let written = fs::read(&path).unwrap();
+        assert_eq!(written.len(), 2 * SPARSE_BLOCK_SIZE + 3);
+        assert!(written[3..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_resolve_in_root() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("usr/lib")).unwrap();
+        fs::create_dir_all(root.join("etc")).unwrap();
+        symlink("/usr/lib/os-release", root.join("etc/os-release")).unwrap();
+        symlink("../../../..", root.join("usr/lib/escape")).unwrap();
+        symlink("loop", root.join("loop")).unwrap();
+        assert_eq!(
+            resolve_in_root(root, Path::new("/etc/os-release")).unwrap(),
+            root.join("usr/lib/os-release")
+        );
+        assert_eq!(
+            resolve_in_root(root, Path::new("usr/lib/escape/etc")).unwrap(),
+            root.join("etc")
+        );
+        assert_eq!(
+            resolve_in_root(root, Path::new("../etc/./os-release")).unwrap(),
+            root.join("usr/lib/os-release")
+        );
+        assert!(resolve_in_root(root, Path::new("loop")).is_err());
+    }
 }
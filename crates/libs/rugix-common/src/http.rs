@@ -0,0 +1,48 @@
+//! Utilities for serving files over HTTP.
+
+/// Parse a `Range` header with a single byte range into a half-open interval.
+///
+/// Returns [`None`] if the range is invalid or not satisfiable for a file of the given
+/// size. Ranges exceeding the size of the file are truncated.
+pub fn parse_range(range: &str, size: u64) -> Option<(u64, u64)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (size.saturating_sub(suffix.parse().ok()?), size),
+        (start, "") => (start.parse().ok()?, size),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.saturating_add(1),
+        ),
+    };
+    let end = end.min(size);
+    (start < end).then_some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 100)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 1000)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 1000)));
+        // Ranges exceeding the file are truncated.
+        assert_eq!(parse_range("bytes=900-2000", 1000), Some((900, 1000)));
+        assert_eq!(parse_range("bytes=-2000", 1000), Some((0, 1000)));
+        assert_eq!(
+            parse_range("bytes=0-18446744073709551615", 10),
+            Some((0, 10))
+        );
+        // Unsatisfiable ranges.
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=100-99", 1000), None);
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+        // Malformed ranges.
+        assert_eq!(parse_range("bytes=0", 1000), None);
+        assert_eq!(parse_range("bytes=a-b", 1000), None);
+        assert_eq!(parse_range("bytes=--1", 1000), None);
+        assert_eq!(parse_range("items=0-99", 1000), None);
+    }
+}
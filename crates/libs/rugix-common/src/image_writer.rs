@@ -0,0 +1,313 @@
+//! Streaming writer for images and payloads.
+//!
+//! The [`ImageWriter`] writes a stream of bytes to a file or block device, skipping
+//! blocks of zeros (see [`SparseWriter`]). It reports its progress and throughput,
+//! optionally syncs the written data to disk in regular intervals, and optionally
+//! verifies the written data by reading it back after writing.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+use byte_calc::NumBytes;
+use nix::fcntl::{posix_fadvise, PosixFadviseAdvice};
+use reportify::{bail, Report, ResultExt};
+use si_crypto_hashes::{HashAlgorithm, HashDigest, Hasher};
+use tracing::debug;
+
+use crate::fsutils::SparseWriter;
+
+reportify::new_whatever_type! {
+    ImageWriterError
+}
+
+/// Minimal interval between progress reports.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Hash algorithm used to verify the written data.
+const VERIFICATION_ALGORITHM: HashAlgorithm = HashAlgorithm::Sha512_256;
+
+/// Progress of an [`ImageWriter`].
+#[derive(Debug, Clone, Copy)]
+pub struct WriteProgress {
+    /// Number of bytes written so far.
+    pub written: NumBytes,
+    /// Total number of bytes to write, if known.
+    pub total: Option<NumBytes>,
+    /// Time elapsed since writing started.
+    pub elapsed: Duration,
+}
+
+impl WriteProgress {
+    /// Average throughput in bytes per second.
+    pub fn throughput(&self) -> NumBytes {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            NumBytes::new((self.written.raw as f64 / seconds) as u64)
+        } else {
+            NumBytes::ZERO
+        }
+    }
+
+    /// Progress in percent, if the total number of bytes is known.
+    pub fn percentage(&self) -> Option<f64> {
+        self.total
+            .filter(|total| total.raw > 0)
+            .map(|total| (self.written.raw as f64) / (total.raw as f64) * 100.0)
+    }
+}
+
+/// Summary of a finished write.
+#[derive(Debug, Clone)]
+pub struct WriteSummary {
+    /// Number of bytes written.
+    pub size: NumBytes,
+    /// Time it took to write and, if enabled, verify the data.
+    pub elapsed: Duration,
+    /// Hash of the written data, if it has been verified.
+    pub verified_hash: Option<HashDigest>,
+}
+
+/// Streaming writer for images and payloads.
+pub struct ImageWriter<'p> {
+    writer: SparseWriter,
+    /// Hasher for the written data, if verification is enabled.
+    hasher: Option<Hasher>,
+    /// Number of bytes after which the written data is synced to disk.
+    sync_interval: Option<NumBytes>,
+    /// Number of bytes written since the last sync.
+    unsynced: NumBytes,
+    written: NumBytes,
+    total: Option<NumBytes>,
+    started: Instant,
+    last_report: Option<Instant>,
+    progress: Option<Box<dyn FnMut(&WriteProgress) + 'p>>,
+}
+
+impl<'p> ImageWriter<'p> {
+    /// Create a writer writing to the given file from its beginning.
+    pub fn new(file: File) -> Result<Self, Report<ImageWriterError>> {
+        Ok(Self {
+            writer: SparseWriter::new(file, 0).whatever("unable to create writer")?,
+            hasher: None,
+            sync_interval: None,
+            unsynced: NumBytes::ZERO,
+            written: NumBytes::ZERO,
+            total: None,
+            started: Instant::now(),
+            last_report: None,
+            progress: None,
+        })
+    }
+
+    /// Set the total number of bytes to write, if known.
+    pub fn with_total(mut self, total: Option<NumBytes>) -> Self {
+        self.total = total;
+        self
+    }
+
+    /// Sync the written data to disk after the given number of bytes.
+    pub fn with_sync_interval(mut self, interval: Option<NumBytes>) -> Self {
+        self.sync_interval = interval.filter(|interval| interval.raw > 0);
+        self
+    }
+
+    /// Verify the written data by reading it back after writing.
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.hasher = verify.then(|| VERIFICATION_ALGORITHM.hasher());
+        self
+    }
+
+    /// Report the progress to the given callback.
+    ///
+    /// The callback is invoked at most every 500 milliseconds and once writing is done.
+    pub fn with_progress(mut self, progress: impl FnMut(&WriteProgress) + 'p) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Number of bytes written so far.
+    pub fn written(&self) -> NumBytes {
+        self.written
+    }
+
+    /// Write the given bytes.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), Report<ImageWriterError>> {
+        self.writer
+            .write(bytes)
+            .whatever("unable to write to target")?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(bytes);
+        }
+        let size = NumBytes::from_usize(bytes.len());
+        self.written += size;
+        if let Some(sync_interval) = self.sync_interval {
+            self.unsynced += size;
+            if self.unsynced >= sync_interval {
+                self.sync()?;
+            }
+        }
+        let now = Instant::now();
+        if self
+            .last_report
+            .is_none_or(|last_report| now - last_report >= PROGRESS_INTERVAL)
+        {
+            self.last_report = Some(now);
+            self.report_progress();
+        }
+        Ok(())
+    }
+
+    /// Read bytes which have already been written.
+    pub fn read_exact_at(
+        &mut self,
+        buffer: &mut [u8],
+        offset: u64,
+    ) -> Result<(), Report<ImageWriterError>> {
+        self.writer
+            .read_exact_at(buffer, offset)
+            .whatever("unable to read from target")
+    }
+
+    /// Finish writing, sync the data to disk, and verify it, if enabled.
+    pub fn finish(mut self) -> Result<WriteSummary, Report<ImageWriterError>> {
+        self.report_progress();
+        let mut file = self.writer.finish().whatever("unable to write to target")?;
+        file.sync_all().whatever("unable to sync target")?;
+        let verified_hash = match self.hasher {
+            Some(hasher) => {
+                let expected = hasher.finalize();
+                debug!("verifying {} written bytes", self.written);
+                let found = hash_written(&mut file, self.written)
+                    .whatever("unable to read back written data")?;
+                if found != expected {
+                    bail!(
+                        "verification of written data failed: expected {expected}, found {found}"
+                    );
+                }
+                Some(found)
+            }
+            None => None,
+        };
+        Ok(WriteSummary {
+            size: self.written,
+            elapsed: self.started.elapsed(),
+            verified_hash,
+        })
+    }
+
+    /// Sync the written data to disk.
+    fn sync(&mut self) -> Result<(), Report<ImageWriterError>> {
+        // Pending zeros must be written before syncing.
+        self.writer.flush().whatever("unable to write to target")?;
+        self.writer
+            .file()
+            .sync_data()
+            .whatever("unable to sync target")?;
+        self.unsynced = NumBytes::ZERO;
+        Ok(())
+    }
+
+    fn report_progress(&mut self) {
+        if let Some(progress) = &mut self.progress {
+            progress(&WriteProgress {
+                written: self.written,
+                total: self.total,
+                elapsed: self.started.elapsed(),
+            });
+        }
+    }
+}
+
+impl Write for ImageWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        ImageWriter::write(self, buf).map_err(|error| io::Error::other(error.to_string()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Hash the given number of bytes from the beginning of the file.
+///
+/// The page cache is dropped beforehand such that the data is actually read from disk.
+fn hash_written(file: &mut File, size: NumBytes) -> io::Result<HashDigest> {
+    posix_fadvise(
+        file.as_raw_fd(),
+        0,
+        0,
+        PosixFadviseAdvice::POSIX_FADV_DONTNEED,
+    )?;
+    file.seek(SeekFrom::Start(0))?;
+    let mut hasher = VERIFICATION_ALGORITHM.hasher();
+    let mut reader = file.take(size.raw);
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    if reader.limit() > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "target is smaller than the written data",
+        ));
+    }
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::os::unix::fs::FileExt;
+
+    use super::*;
+
+    fn open_target(path: &std::path::Path) -> File {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("target.img");
+        let data = (0..256 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut writer = ImageWriter::new(open_target(&path))
+            .unwrap()
+            .with_verification(true);
+        writer.write(&data).unwrap();
+        let summary = writer.finish().unwrap();
+        assert_eq!(summary.size, NumBytes::from_usize(data.len()));
+        assert!(summary.verified_hash.is_some());
+        assert_eq!(fs::read(&path).unwrap(), data);
+    }
+
+    #[test]
+    fn test_verification_detects_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("target.img");
+        let data = (0..256 * 1024).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut writer = ImageWriter::new(open_target(&path))
+            .unwrap()
+            .with_verification(true);
+        writer.write(&data).unwrap();
+        Write::flush(&mut writer).unwrap();
+        // Corrupt the written data behind the writer's back.
+        let corrupt = OpenOptions::new().write(true).open(&path).unwrap();
+        corrupt.write_all_at(&[0xFF; 16], 1000).unwrap();
+        corrupt.sync_all().unwrap();
+        let error = writer.finish().unwrap_err();
+        assert!(format!("{error:?}").contains("verification of written data failed"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Inspecting built images.
+//!
+//! An image is attached read-only to a loop device and its filesystems are mounted
+//! read-only on demand. Everything is unmounted and detached again when the
+//! [`InspectedImage`] is dropped.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, Report, ResultExt};
+use tempfile::TempDir;
+
+use crate::disk::blkdev::BlockDevice;
+use crate::fsutils::resolve_in_root;
+use crate::loop_dev::LoopDevice;
+use crate::mount::Mounted;
+use crate::partitions::filesystem_type;
+
+reportify::new_whatever_type! {
+    InspectError
+}
+
+/// Paths of the `os-release` file relative to the root filesystem.
+const OS_RELEASE_PATHS: &[&str] = &["etc/os-release", "usr/lib/os-release"];
+
+/// Partition of an inspected image.
+#[derive(Debug, Clone)]
+pub struct ImagePartition {
+    /// Number of the partition.
+    pub number: u32,
+    /// Device of the partition.
+    pub device: PathBuf,
+    /// Size of the partition in bytes.
+    pub size: u64,
+    /// Type of the filesystem on the partition (e.g., `ext4`), if any.
+    pub filesystem: Option<String>,
+}
+
+/// Usage of a filesystem.
+#[derive(Debug, Clone, Copy)]
+pub struct FilesystemUsage {
+    /// Size of the filesystem in bytes.
+    pub size: u64,
+    /// Used bytes of the filesystem.
+    pub used: u64,
+}
+
+/// Image attached read-only to a loop device.
+#[derive(Debug)]
+pub struct InspectedImage {
+    // The fields are dropped in order, so filesystems are unmounted before the image
+    // is detached and the mount points are removed.
+    /// Mounted filesystems by their partition number.
+    mounts: HashMap<u32, Mounted>,
+    /// Partitions of the image.
+    partitions: Vec<ImagePartition>,
+    /// Loop device the image is attached to.
+    loop_device: LoopDevice,
+    /// Directory with the mount points of the filesystems.
+    mount_dir: TempDir,
+}
+
+impl InspectedImage {
+    /// Attach the image read-only and scan it for partitions.
+    pub fn open(image: impl AsRef<Path>) -> Result<Self, Report<InspectError>> {
+        let image = image.as_ref();
+        let loop_device = LoopDevice::attach_read_only(image).whatever("unable to attach image")?;
+        let mut partitions = Vec::new();
+        for (number, device) in loop_device
+            .partitions()
+            .whatever("unable to read partitions")?
+        {
+            let size = BlockDevice::new(&device)
+                .and_then(|device| device.size())
+                .whatever("unable to determine size of partition")?;
+            let filesystem = filesystem_type(&device)
+                .ok()
+                .filter(|filesystem| !filesystem.is_empty());
+            partitions.push(ImagePartition {
+                number,
+                device,
+                size,
+                filesystem,
+            });
+        }
+        let mount_dir = TempDir::new().whatever("unable to create temporary directory")?;
+        Ok(Self {
+            mounts: HashMap::new(),
+            partitions,
+            loop_device,
+            mount_dir,
+        })
+    }
+
+    /// Path of the loop device the image is attached to.
+    pub fn device(&self) -> &Path {
+        self.loop_device.path()
+    }
+
+    /// Partitions of the image ordered by their number.
+    pub fn partitions(&self) -> &[ImagePartition] {
+        &self.partitions
+    }
+
+    /// Mount the filesystem of the given partition read-only and return its path.
+    ///
+    /// Filesystems are only mounted once and stay mounted until the image is dropped.
+    pub fn mount(&mut self, number: u32) -> Result<PathBuf, Report<InspectError>> {
+        if let Some(mounted) = self.mounts.get(&number) {
+            return Ok(mounted.path().to_path_buf());
+        }
+        let Some(partition) = self
+            .partitions
+            .iter()
+            .find(|partition| partition.number == number)
+        else {
+            bail!("partition {number} does not exist");
+        };
+        if partition.filesystem.is_none() {
+            bail!("partition {number} does not contain a filesystem");
+        }
+        let mount_point = self.mount_dir.path().join(format!("partition-{number}"));
+        fs::create_dir_all(&mount_point).whatever("unable to create mount point")?;
+        let mounted = Mounted::mount_read_only(&partition.device, &mount_point)
+            .whatever_with(|_| format!("unable to mount partition {number}"))?;
+        self.mounts.insert(number, mounted);
+        Ok(mount_point)
+    }
+
+    /// Resolve a path on the filesystem of the given partition.
+    ///
+    /// The path is interpreted relative to the root of the filesystem. Symbolic links
+    /// are resolved within the filesystem, i.e., absolute link targets are relative to
+    /// its root and links cannot escape it.
+    pub fn resolve(
+        &mut self,
+        number: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<PathBuf, Report<InspectError>> {
+        let root = self.mount(number)?;
+        resolve_in_root(&root, path.as_ref()).whatever("unable to resolve path")
+    }
+
+    /// List the entries of a directory on the filesystem of the given partition.
+    pub fn list(
+        &mut self,
+        number: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<String>, Report<InspectError>> {
+        let dir = self.resolve(number, path)?;
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&dir).whatever("unable to read directory")? {
+            let entry = entry.whatever("unable to read directory")?;
+            entries.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// Read a file from the filesystem of the given partition.
+    pub fn read(
+        &mut self,
+        number: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<u8>, Report<InspectError>> {
+        let path = self.resolve(number, path)?;
+        fs::read(&path).whatever_with(|_| format!("unable to read {path:?}"))
+    }
+
+    /// Usage of the filesystem of the given partition.
+    pub fn usage(&mut self, number: u32) -> Result<FilesystemUsage, Report<InspectError>> {
+        let path = self.mount(number)?;
+        let stat = nix::sys::statvfs::statvfs(&path).whatever("unable to query filesystem")?;
+        let fragment_size = stat.fragment_size() as u64;
+        Ok(FilesystemUsage {
+            size: stat.blocks() as u64 * fragment_size,
+            used: (stat.blocks() - stat.blocks_free()) as u64 * fragment_size,
+        })
+    }
+
+    /// Find the partition with the root filesystem of the system.
+    ///
+    /// This is the first partition with an `os-release` file.
+    pub fn system_partition(&mut self) -> Result<Option<u32>, Report<InspectError>> {
+        Ok(self.os_release()?.map(|(number, _)| number))
+    }
+
+    /// Read the `os-release` file of the system and return its partition and values.
+    pub fn os_release(
+        &mut self,
+    ) -> Result<Option<(u32, BTreeMap<String, String>)>, Report<InspectError>> {
+        let numbers = self
+            .partitions
+            .iter()
+            .filter(|partition| partition.filesystem.is_some())
+            .map(|partition| partition.number)
+            .collect::<Vec<_>>();
+        for number in numbers {
+            // Partitions which cannot be mounted, e.g., swap, are skipped.
+            let Ok(root) = self.mount(number) else {
+                continue;
+            };
+            for path in OS_RELEASE_PATHS {
+                let Ok(path) = resolve_in_root(&root, Path::new(path)) else {
+                    continue;
+                };
+                if let Ok(os_release) = fs::read_to_string(path) {
+                    return Ok(Some((number, parse_os_release(&os_release))));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Parse the contents of an `os-release` file.
+pub fn parse_os_release(os_release: &str) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    for line in os_release.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = match value.strip_prefix(['"', '\'']) {
+            Some(quoted) => quoted
+                .strip_suffix(&value[..1])
+                .unwrap_or(quoted)
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+            None => value.to_owned(),
+        };
+        values.insert(key.trim().to_owned(), value);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_os_release;
+
+    #[test]
+    fn test_parse_os_release() {
+        let values = parse_os_release(
+            "# Comment\nNAME=\"Debian GNU/Linux\"\nVERSION_ID='12'\nID=debian\n\n\
+            PRETTY_NAME=\"Debian \\\"Bookworm\\\"\"\n",
+        );
+        assert_eq!(values["NAME"], "Debian GNU/Linux");
+        assert_eq!(values["VERSION_ID"], "12");
+        assert_eq!(values["ID"], "debian");
+        assert_eq!(values["PRETTY_NAME"], "Debian \"Bookworm\"");
+        assert_eq!(values.len(), 4);
+    }
+}
@@ -15,6 +15,11 @@ pub mod devices;
 pub mod disk;
 #[cfg(target_os = "linux")]
 pub mod fsutils;
+pub mod http;
+#[cfg(target_os = "linux")]
+pub mod image_writer;
+#[cfg(target_os = "linux")]
+pub mod inspect;
 pub mod loop_dev;
 pub mod maybe_compressed;
 #[cfg(target_os = "linux")]
@@ -1,42 +1,270 @@
+//! Management of Linux loop devices.
+//!
+//! Images are attached to loop devices directly via the `ioctl` interface of the kernel
+//! instead of shelling out to `losetup`. This avoids races when looking for a free loop
+//! device and allows enumerating the partitions of an attached image.
+
+use std::fs::{self, File};
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use nix::errno::Errno;
+use nix::libc::c_int;
+use nix::sys::stat::{makedev, mknod, Mode, SFlag};
+use reportify::{bail, Report, ResultExt};
+use tracing::debug;
 
-use reportify::{Report, ResultExt};
-use xscript::{read_str, run, Run};
+use crate::disk::blkdev::BlockDevice;
 
 reportify::new_whatever_type! {
     LoopDeviceError
 }
 
+/// Path of the loop control device.
+const LOOP_CONTROL: &str = "/dev/loop-control";
+
+/// Number of attempts to find and bind a free loop device.
+const ATTACH_ATTEMPTS: usize = 10;
+
+/// Number of attempts to wait for a device node to appear.
+const DEVICE_NODE_ATTEMPTS: usize = 10;
+
+/// Automatically detach the loop device when it is no longer in use.
+const LO_FLAGS_AUTOCLEAR: u32 = 4;
+/// Scan the attached image for partitions.
+const LO_FLAGS_PARTSCAN: u32 = 8;
+
+/// Status of a loop device (`struct loop_info64`).
+#[repr(C)]
+#[allow(dead_code, reason = "fields are read by the kernel")]
+struct LoopInfo64 {
+    lo_device: u64,
+    lo_inode: u64,
+    lo_rdevice: u64,
+    lo_offset: u64,
+    lo_sizelimit: u64,
+    lo_number: u32,
+    lo_encrypt_type: u32,
+    lo_encrypt_key_size: u32,
+    lo_flags: u32,
+    lo_file_name: [u8; 64],
+    lo_crypt_name: [u8; 64],
+    lo_encrypt_key: [u8; 32],
+    lo_init: [u64; 2],
+}
+
+nix::ioctl_none_bad! {
+    /// Get the number of a free loop device (`LOOP_CTL_GET_FREE`).
+    ioctl_get_free, 0x4C82
+}
+
+nix::ioctl_write_int_bad! {
+    /// Bind a file to a loop device (`LOOP_SET_FD`).
+    ioctl_set_fd, 0x4C00
+}
+
+nix::ioctl_none_bad! {
+    /// Unbind the file from a loop device (`LOOP_CLR_FD`).
+    ioctl_clr_fd, 0x4C01
+}
+
+nix::ioctl_write_ptr_bad! {
+    /// Set the status of a loop device (`LOOP_SET_STATUS64`).
+    ioctl_set_status64, 0x4C04, LoopInfo64
+}
+
 /// A loop device with an attached image.
+///
+/// The image is detached when the loop device is dropped. As the loop device is marked
+/// for automatic detaching, it is only detached by the kernel once all its partitions
+/// have been unmounted.
 #[derive(Debug)]
 pub struct LoopDevice {
     path: PathBuf,
+    /// Open file of the loop device.
+    file: File,
 }
 
 impl LoopDevice {
-    /// Attaches an image to the next free loop device.
+    /// Attaches an image to the next free loop device and scans it for partitions.
     pub fn attach(image: impl AsRef<Path>) -> Result<Self, Report<LoopDeviceError>> {
-        let image = image.as_ref();
-        let path = read_str!(["losetup", "-f"]).whatever("failed to find free loop device")?;
-        run!(["losetup", "-P", &path, image]).whatever("failed to bind image to loop device")?;
-        Ok(LoopDevice { path: path.into() })
+        Self::attach_with(image.as_ref(), false)
+    }
+
+    /// Attaches an image read-only to the next free loop device.
+    pub fn attach_read_only(image: impl AsRef<Path>) -> Result<Self, Report<LoopDeviceError>> {
+        Self::attach_with(image.as_ref(), true)
     }
 
-    /// Path to the partition device.
-    pub fn partition(&self, part: usize) -> PathBuf {
-        let mut path = self.path.as_os_str().to_owned();
-        path.push(&format!("p{}", part));
-        path.into()
+    fn attach_with(image: &Path, read_only: bool) -> Result<Self, Report<LoopDeviceError>> {
+        let image_file = File::options()
+            .read(true)
+            .write(!read_only)
+            .open(image)
+            .whatever_with(|_| format!("unable to open image {image:?}"))?;
+        let control = File::options()
+            .read(true)
+            .write(true)
+            .open(LOOP_CONTROL)
+            .whatever("unable to open loop control device")?;
+        for _ in 0..ATTACH_ATTEMPTS {
+            let number = unsafe {
+                // SAFETY: The file is the loop control device.
+                ioctl_get_free(control.as_raw_fd())
+            }
+            .whatever("unable to find free loop device")?;
+            let path = ensure_device_node(&format!("loop{number}"))?;
+            let file = File::options()
+                .read(true)
+                .write(!read_only)
+                .open(&path)
+                .whatever_with(|_| format!("unable to open loop device {path:?}"))?;
+            let result = unsafe {
+                // SAFETY: The file is a loop device and the argument a file descriptor.
+                ioctl_set_fd(file.as_raw_fd(), image_file.as_raw_fd() as c_int)
+            };
+            match result {
+                Ok(_) => {}
+                // Another process has grabbed the loop device in the meantime.
+                Err(Errno::EBUSY) => continue,
+                Err(error) => {
+                    return Err(error).whatever("unable to bind image to loop device");
+                }
+            }
+            let loop_device = LoopDevice { path, file };
+            // The image is attached read-only if the loop device has been opened read-only.
+            let mut info = LoopInfo64 {
+                lo_device: 0,
+                lo_inode: 0,
+                lo_rdevice: 0,
+                lo_offset: 0,
+                lo_sizelimit: 0,
+                lo_number: 0,
+                lo_encrypt_type: 0,
+                lo_encrypt_key_size: 0,
+                lo_flags: LO_FLAGS_AUTOCLEAR | LO_FLAGS_PARTSCAN,
+                lo_file_name: [0; 64],
+                lo_crypt_name: [0; 64],
+                lo_encrypt_key: [0; 32],
+                lo_init: [0; 2],
+            };
+            // The name is informational only and shown by `losetup`.
+            let name = image.as_os_str().as_bytes();
+            let name_len = name.len().min(info.lo_file_name.len() - 1);
+            info.lo_file_name[..name_len].copy_from_slice(&name[..name_len]);
+            unsafe {
+                // SAFETY: The file is a loop device and the argument a `loop_info64`.
+                ioctl_set_status64(loop_device.file.as_raw_fd(), &info)
+            }
+            .whatever("unable to set status of loop device")?;
+            debug!("attached {image:?} to {:?}", loop_device.path);
+            return Ok(loop_device);
+        }
+        bail!("unable to find free loop device after {ATTACH_ATTEMPTS} attempts");
     }
 
+    /// Path of the loop device.
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Block device of the loop device.
+    pub fn device(&self) -> Result<BlockDevice, Report<LoopDeviceError>> {
+        BlockDevice::new(&self.path).whatever("loop device is not a block device")
+    }
+
+    /// Partitions of the attached image ordered by their number.
+    pub fn partitions(&self) -> Result<Vec<(u32, PathBuf)>, Report<LoopDeviceError>> {
+        let name = self.name();
+        let sysfs_path = Path::new("/sys/class/block").join(name);
+        let mut partitions = Vec::new();
+        for entry in fs::read_dir(&sysfs_path).whatever("unable to read partitions")? {
+            let entry = entry.whatever("unable to read partitions")?;
+            let Ok(number) = fs::read_to_string(entry.path().join("partition")) else {
+                continue;
+            };
+            let number = number
+                .trim()
+                .parse::<u32>()
+                .whatever("invalid partition number")?;
+            let partition_name = entry.file_name();
+            let Some(partition_name) = partition_name.to_str() else {
+                continue;
+            };
+            partitions.push((number, ensure_device_node(partition_name)?));
+        }
+        partitions.sort();
+        Ok(partitions)
+    }
+
+    /// Path to the device of the given partition.
+    pub fn partition(&self, number: u32) -> Result<PathBuf, Report<LoopDeviceError>> {
+        match self
+            .partitions()?
+            .into_iter()
+            .find(|(partition, _)| *partition == number)
+        {
+            Some((_, path)) => Ok(path),
+            None => bail!("partition {number} of {:?} does not exist", self.path),
+        }
+    }
+
+    /// Name of the loop device.
+    fn name(&self) -> &str {
+        self.path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("loop device path must have a name")
+    }
 }
 
 impl Drop for LoopDevice {
     fn drop(&mut self) {
-        // Detach the loop device and ignore any errors.
-        run!(["losetup", "-d", &self.path]).ok();
+        // Detach the loop device and ignore any errors. If the device is still in use,
+        // e.g., because a partition is mounted, it will be detached automatically.
+        let _ = unsafe {
+            // SAFETY: The file is a loop device.
+            ioctl_clr_fd(self.file.as_raw_fd())
+        };
+    }
+}
+
+/// Make sure that a device node for the block device with the given name exists.
+///
+/// In containers, `/dev` is often not managed by the kernel such that device nodes of
+/// new loop devices and their partitions do not appear automatically.
+fn ensure_device_node(name: &str) -> Result<PathBuf, Report<LoopDeviceError>> {
+    let path = Path::new("/dev").join(name);
+    let dev_file = Path::new("/sys/class/block").join(name).join("dev");
+    // Device nodes may be created asynchronously, so we wait a bit for them.
+    for _ in 0..DEVICE_NODE_ATTEMPTS {
+        if path.exists() {
+            return Ok(path);
+        }
+        thread::sleep(Duration::from_millis(50));
     }
+    let dev = fs::read_to_string(&dev_file)
+        .whatever_with(|_| format!("unable to read device number of {name:?}"))?;
+    let Some((major, minor)) = dev.trim().split_once(':') else {
+        bail!("invalid device number {dev:?} of {name:?}");
+    };
+    let major = major.parse().whatever("invalid major device number")?;
+    let minor = minor.parse().whatever("invalid minor device number")?;
+    debug!("creating device node {path:?}");
+    mknod(
+        &path,
+        SFlag::S_IFBLK,
+        Mode::from_bits_truncate(0o660),
+        makedev(major, minor),
+    )
+    .or_else(|error| match error {
+        // The device node has been created in the meantime.
+        Errno::EEXIST => Ok(()),
+        error => Err(error),
+    })
+    .whatever_with(|_| format!("unable to create device node {path:?}"))?;
+    Ok(path)
 }
@@ -25,6 +25,11 @@ impl<R: Read> MaybeCompressed<R> {
         }
     }
 
+    /// Indicates whether the stream is compressed.
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.inner, MaybeCompressedInner::Xz(_))
+    }
+
     pub fn into_inner(self) -> R {
         match self.inner {
             MaybeCompressedInner::Uncompressed(reader) => reader.reader,
@@ -27,6 +27,21 @@ impl Mounted {
         Ok(Mounted { path: dst.into() })
     }
 
+    /// Mount the filesystem on the given device read-only.
+    pub fn mount_read_only(
+        dev: impl AsRef<Path>,
+        dst: impl AsRef<Path>,
+    ) -> Result<Self, Report<MountError>> {
+        let dst = dst.as_ref();
+        let dev = dev.as_ref();
+        debug!("Mounting {dev:?} read-only to {dst:?}.");
+        run!(["/usr/bin/mount", "-o", "ro", dev, dst])
+            .whatever("unable to mount filesystem")
+            .with_info(|_| format!("dev: {dev:?}"))
+            .with_info(|_| format!("dst: {dst:?}"))?;
+        Ok(Mounted { path: dst.into() })
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
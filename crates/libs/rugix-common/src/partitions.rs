@@ -1,8 +1,10 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use reportify::{Report, ResultExt};
+use reportify::{bail, Report, ResultExt};
 use xscript::{read_str, run, Run};
 
+use crate::mount::Mounted;
+
 pub fn is_dir(path: impl AsRef<Path>) -> bool {
     path.as_ref().is_dir()
 }
@@ -33,6 +35,22 @@ pub fn get_disk_id(path: impl AsRef<Path>) -> Result<String, Report<DiskError>>
 const MKFS_ETX4: &str = "/usr/sbin/mkfs.ext4";
 /// The `mkfs.vfat` executable.
 const MKFS_VFAT: &str = "/usr/sbin/mkfs.vfat";
+/// The `mkfs.f2fs` executable.
+const MKFS_F2FS: &str = "/usr/sbin/mkfs.f2fs";
+/// The `resize2fs` executable.
+const RESIZE2FS: &str = "/usr/sbin/resize2fs";
+/// The `resize.f2fs` executable.
+const RESIZE_F2FS: &str = "/usr/sbin/resize.f2fs";
+/// The `mkfs.btrfs` executable.
+const MKFS_BTRFS: &str = "/usr/sbin/mkfs.btrfs";
+/// The `btrfs` executable.
+const BTRFS: &str = "/usr/bin/btrfs";
+/// The `blkid` executable.
+const BLKID: &str = "/usr/sbin/blkid";
+/// Mount point used for growing filesystems which must be mounted for that.
+const RESIZE_MOUNT_POINT: &str = "/run/rugix/mounts/resize";
+/// The `cryptsetup` executable.
+const CRYPTSETUP: &str = "/usr/sbin/cryptsetup";
 
 /// Formats a boot partition with FAT32.
 pub fn mkfs_vfat(dev: impl AsRef<Path>, label: impl AsRef<str>) -> Result<(), Report<DiskError>> {
@@ -47,3 +65,156 @@ pub fn mkfs_ext4(dev: impl AsRef<Path>, label: impl AsRef<str>) -> Result<(), Re
         .whatever("unable to create ETX4 filesystem")?;
     Ok(())
 }
+
+/// Formats a data partition with F2FS.
+pub fn mkfs_f2fs(dev: impl AsRef<Path>, label: impl AsRef<str>) -> Result<(), Report<DiskError>> {
+    run!([MKFS_F2FS, "-f", "-l", label.as_ref(), dev.as_ref()])
+        .whatever("unable to create F2FS filesystem")?;
+    Ok(())
+}
+
+/// Formats a data partition with Btrfs.
+pub fn mkfs_btrfs(dev: impl AsRef<Path>, label: impl AsRef<str>) -> Result<(), Report<DiskError>> {
+    run!([MKFS_BTRFS, "-f", "-L", label.as_ref(), dev.as_ref()])
+        .whatever("unable to create Btrfs filesystem")?;
+    Ok(())
+}
+
+/// Grows an EXT4 filesystem to the size of its partition.
+pub fn resize_ext4(dev: impl AsRef<Path>) -> Result<(), Report<DiskError>> {
+    run!([RESIZE2FS, dev.as_ref()]).whatever("unable to resize EXT4 filesystem")?;
+    Ok(())
+}
+
+/// Grows an F2FS filesystem to the size of its partition.
+pub fn resize_f2fs(dev: impl AsRef<Path>) -> Result<(), Report<DiskError>> {
+    run!([RESIZE_F2FS, dev.as_ref()]).whatever("unable to resize F2FS filesystem")?;
+    Ok(())
+}
+
+/// Grows a Btrfs filesystem to the size of its partition.
+///
+/// Btrfs filesystems can only be grown while mounted, hence, the filesystem is
+/// temporarily mounted.
+pub fn resize_btrfs(dev: impl AsRef<Path>) -> Result<(), Report<DiskError>> {
+    std::fs::create_dir_all(RESIZE_MOUNT_POINT).whatever("unable to create mount point")?;
+    let mounted =
+        Mounted::mount(dev.as_ref(), RESIZE_MOUNT_POINT).whatever("unable to mount filesystem")?;
+    run!([BTRFS, "filesystem", "resize", "max", mounted.path()])
+        .whatever("unable to resize Btrfs filesystem")?;
+    Ok(())
+}
+
+/// Returns the type of the filesystem on the given device (e.g., `ext4`).
+pub fn filesystem_type(dev: impl AsRef<Path>) -> Result<String, Report<DiskError>> {
+    let ty = read_str!([BLKID, "-o", "value", "-s", "TYPE", dev.as_ref()])
+        .whatever("unable to determine filesystem type")
+        .with_info(|_| format!("device: {:?}", dev.as_ref()))?;
+    Ok(ty.trim().to_owned())
+}
+
+/// Returns the devices with a filesystem with the given tag (e.g., `LABEL` or `UUID`).
+pub fn find_filesystem(tag: &str, value: &str) -> Vec<PathBuf> {
+    find_devices(tag, value)
+}
+
+/// Returns the partitions with the given tag (e.g., `PARTLABEL` or `PARTUUID`).
+///
+/// In contrast to [`find_filesystem`], this also finds partitions without a filesystem.
+pub fn find_partition(tag: &str, value: &str) -> Vec<PathBuf> {
+    // `blkid` reports GPT partition UUIDs in lowercase.
+    let value = if tag == "PARTUUID" {
+        value.to_ascii_lowercase()
+    } else {
+        value.to_owned()
+    };
+    find_devices(tag, &value)
+}
+
+/// Returns the devices with the given tag as reported by `blkid`.
+///
+/// Tags are not necessarily unique, e.g., when a disk with a copy of the same image is
+/// attached, hence, all matching devices are returned.
+fn find_devices(tag: &str, value: &str) -> Vec<PathBuf> {
+    // Bypass the cache of `blkid` as it may be stale after flashing an image.
+    let Ok(devices) = read_str!([
+        BLKID,
+        "-c",
+        "/dev/null",
+        "-o",
+        "device",
+        "-t",
+        format!("{tag}={value}")
+    ]) else {
+        // `blkid` exits with a non-zero status, if no filesystem matches.
+        return Vec::new();
+    };
+    devices
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Grows the filesystem of the given type to the size of its partition.
+pub fn resize_filesystem(dev: impl AsRef<Path>, ty: &str) -> Result<(), Report<DiskError>> {
+    match ty {
+        "ext2" | "ext3" | "ext4" => resize_ext4(dev),
+        "f2fs" => resize_f2fs(dev),
+        "btrfs" => resize_btrfs(dev),
+        _ => bail!("growing {ty:?} filesystems is not supported"),
+    }
+}
+
+/// Formats a partition as a LUKS2 container with an initial keyslot for the given key
+/// file.
+pub fn luks_format(
+    dev: impl AsRef<Path>,
+    key_file: impl AsRef<Path>,
+) -> Result<(), Report<DiskError>> {
+    run!([
+        CRYPTSETUP,
+        "luksFormat",
+        "--batch-mode",
+        "--type",
+        "luks2",
+        "--key-file",
+        key_file.as_ref(),
+        dev.as_ref()
+    ])
+    .whatever("unable to format LUKS container")?;
+    Ok(())
+}
+
+/// Unlocks a LUKS container and returns the path of the unlocked device.
+pub fn luks_open(
+    dev: impl AsRef<Path>,
+    name: &str,
+    key_file: impl AsRef<Path>,
+) -> Result<PathBuf, Report<DiskError>> {
+    run!([
+        CRYPTSETUP,
+        "open",
+        "--key-file",
+        key_file.as_ref(),
+        dev.as_ref(),
+        name
+    ])
+    .whatever("unable to unlock LUKS container")
+    .with_info(|_| format!("device: {:?}", dev.as_ref()))?;
+    Ok(Path::new("/dev/mapper").join(name))
+}
+
+/// Locks an unlocked LUKS container.
+pub fn luks_close(name: &str) -> Result<(), Report<DiskError>> {
+    run!([CRYPTSETUP, "close", name]).whatever("unable to lock LUKS container")?;
+    Ok(())
+}
+
+/// Grows an unlocked LUKS container to the size of its partition.
+pub fn luks_resize(name: &str, key_file: impl AsRef<Path>) -> Result<(), Report<DiskError>> {
+    run!([CRYPTSETUP, "resize", "--key-file", key_file.as_ref(), name])
+        .whatever("unable to resize LUKS container")?;
+    Ok(())
+}
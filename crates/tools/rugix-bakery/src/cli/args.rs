@@ -2,9 +2,11 @@
 
 use std::path::PathBuf;
 
+use byte_calc::NumBytes;
 use clap::Parser;
 
-use crate::config::systems::Architecture;
+use crate::config::systems::{Architecture, ImageFormat, VulnerabilitySeverity};
+use crate::oven::export::LayerExportFormat;
 use crate::oven::system::ReleaseInfo;
 use crate::oven::BundleOpts;
 
@@ -15,6 +17,13 @@ pub struct Args {
     /// Path to the `rugix-bakery.toml` configuration file.
     #[clap(long)]
     pub config: Option<PathBuf>,
+    /// Forbid network access, failing on anything which has not been prefetched.
+    #[clap(long)]
+    pub offline: bool,
+    /// Override a recipe parameter (`recipes.<recipe>.<parameter>=<value>`) or a value of
+    /// a system configuration (`systems.<system>.<path>=<value>`).
+    #[clap(short = 'D', long = "define", value_name = "KEY=VALUE", global = true)]
+    pub defines: Vec<String>,
     /// The command to execute.
     #[clap(subcommand)]
     pub cmd: Command,
@@ -34,11 +43,23 @@ pub enum Command {
     #[clap(subcommand)]
     List(ListCommand),
     /// Pull in external repositories.
-    Pull,
+    Pull(PullCommand),
+    /// Prefetch base layers, repositories, and toolchains for offline builds.
+    Fetch(FetchCommand),
+    /// Search the configured registries for repositories.
+    Search(SearchCommand),
     /// Initialize the project from a template.
     Init(InitCommand),
-    /// Spawn a shell in the Rugix Bakery Docker container.
-    Shell,
+    /// Check the build environment for common problems.
+    Doctor,
+    /// Compare the root filesystems of two built systems.
+    Diff(DiffCommand),
+    /// Scan a built system for known vulnerabilities.
+    Scan(ScanCommand),
+    /// Inspect the partitions and filesystems of a built image.
+    InspectImage(InspectImageCommand),
+    /// Spawn a shell or run a command in the Rugix Bakery Docker container or a layer.
+    Shell(ShellCommand),
     /// Control the cache of Rugix Bakery.
     #[clap(subcommand)]
     Cache(CacheCommand),
@@ -59,6 +80,7 @@ impl ReleaseInfoArgs {
         ReleaseInfo {
             system_id: self.release_id.clone(),
             system_version: self.release_version.clone(),
+            commit: None,
         }
     }
 }
@@ -67,7 +89,48 @@ impl ReleaseInfoArgs {
 #[derive(Debug, Parser)]
 pub enum ListCommand {
     /// List available images.
-    Systems,
+    Systems {
+        #[clap(flatten)]
+        opts: ListOpts,
+        /// Only list systems for the given architecture.
+        #[clap(long)]
+        arch: Option<Architecture>,
+    },
+    /// List available layers.
+    Layers {
+        #[clap(flatten)]
+        opts: ListOpts,
+        /// Only list layers of the given repository.
+        #[clap(long)]
+        repository: Option<String>,
+        /// Only list layers with a configuration for the given architecture.
+        #[clap(long)]
+        arch: Option<Architecture>,
+    },
+    /// List available recipes.
+    Recipes {
+        #[clap(flatten)]
+        opts: ListOpts,
+        /// Only list recipes of the given repository.
+        #[clap(long)]
+        repository: Option<String>,
+    },
+    /// List the repositories used by the project.
+    Repositories {
+        #[clap(flatten)]
+        opts: ListOpts,
+    },
+}
+
+/// Options shared by the subcommands of the `list` command.
+#[derive(Debug, clap::Args)]
+pub struct ListOpts {
+    /// Output the results as JSON.
+    #[clap(long)]
+    pub json: bool,
+    /// Only list entries whose name or description contains the given text.
+    #[clap(long)]
+    pub filter: Option<String>,
 }
 
 /// The `bake` command.
@@ -79,10 +142,25 @@ pub enum BakeCommand {
         system: String,
         /// The output path for the resulting files.
         output: Option<PathBuf>,
+        /// Format of the output image (inferred from the output path by default).
+        #[clap(long)]
+        format: Option<ImageFormat>,
+        /// Build profile of the system (e.g., `dev`).
+        #[clap(long)]
+        profile: Option<String>,
+        /// Assemble the image from a layer exported with `bake layer --export layer`.
+        #[clap(long)]
+        from_layer: Option<PathBuf>,
         #[clap(flatten)]
         release: ReleaseInfoArgs,
         #[clap(long)]
         source_date: Option<jiff::Timestamp>,
+        /// Write a build report with timing information.
+        #[clap(long)]
+        report: bool,
+        /// Print which layers would be rebuilt and why without baking anything.
+        #[clap(long)]
+        plan: bool,
     },
     /// Bake a layer.
     Layer {
@@ -93,6 +171,18 @@ pub enum BakeCommand {
         layer: String,
         #[clap(long)]
         source_date: Option<jiff::Timestamp>,
+        /// Export the root filesystem of the layer in the given format.
+        #[clap(long)]
+        export: Option<LayerExportFormat>,
+        /// The output path of the exported root filesystem.
+        #[clap(long, requires = "export")]
+        output: Option<PathBuf>,
+        /// Write a build report with timing information.
+        #[clap(long)]
+        report: bool,
+        /// Print which layers would be rebuilt and why without baking anything.
+        #[clap(long)]
+        plan: bool,
     },
     /// Bake a bundle.
     Bundle {
@@ -101,8 +191,19 @@ pub enum BakeCommand {
         /// Disable compression of the bundle.
         #[clap(flatten)]
         opts: BundleOpts,
+        /// Assemble the system from a layer exported with `bake layer --export layer`.
+        #[clap(long)]
+        from_layer: Option<PathBuf>,
         #[clap(flatten)]
         release: ReleaseInfoArgs,
+        #[clap(long)]
+        source_date: Option<jiff::Timestamp>,
+        /// Write a build report with timing information.
+        #[clap(long)]
+        report: bool,
+        /// Print which layers would be rebuilt and why without baking anything.
+        #[clap(long)]
+        plan: bool,
     },
 }
 
@@ -110,6 +211,9 @@ pub enum BakeCommand {
 #[derive(Debug, Parser)]
 pub struct TestCommand {
     pub workflows: Vec<String>,
+    /// Maximal number of tests to run in parallel.
+    #[clap(long, short, default_value_t = 1)]
+    pub jobs: usize,
 }
 
 /// The `cache` command.
@@ -117,6 +221,68 @@ pub struct TestCommand {
 pub enum CacheCommand {
     /// Clean the cache.
     Clean,
+    /// Remove cache entries which have not been used recently or exceed a size limit.
+    Prune {
+        /// Remove entries which have not been used within the given time span (e.g.,
+        /// `30d`).
+        #[clap(long)]
+        older_than: Option<jiff::Span>,
+        /// Remove the least recently used entries until the cache fits into the given
+        /// size.
+        #[clap(long)]
+        max_size: Option<NumBytes>,
+    },
+    /// Show the entries of the cache with their sizes and last use.
+    Stats,
+    /// Export cached layers into an archive.
+    Export {
+        /// Path of the archive (compressed based on the extension, e.g., `.tar.zst`).
+        archive: PathBuf,
+        /// Layers to export (defaults to all cached layers).
+        #[clap(long = "layer")]
+        layers: Vec<String>,
+    },
+    /// Import cached layers from an archive.
+    Import {
+        /// Path of the archive.
+        archive: PathBuf,
+    },
+}
+
+/// The `shell` command.
+#[derive(Debug, Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct ShellCommand {
+    /// Layer to spawn the shell in the customization environment of.
+    pub layer: Option<String>,
+    /// The architecture of the layer.
+    #[clap(long, requires = "layer")]
+    pub arch: Option<Architecture>,
+    /// Recipe before which to spawn the shell.
+    #[clap(long, requires = "layer")]
+    pub recipe: Option<String>,
+    #[clap(subcommand)]
+    pub cmd: Option<ShellSubcommand>,
+}
+
+/// Subcommands of the `shell` command.
+#[derive(Debug, Parser)]
+pub enum ShellSubcommand {
+    /// Run a command non-interactively and exit with its exit code.
+    Exec {
+        /// Layer to run the command in the customization environment of.
+        #[clap(long)]
+        layer: Option<String>,
+        /// The architecture of the layer.
+        #[clap(long, requires = "layer")]
+        arch: Option<Architecture>,
+        /// Recipe before which to run the command.
+        #[clap(long, requires = "layer")]
+        recipe: Option<String>,
+        /// Command to run and its arguments.
+        #[clap(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
 }
 
 /// The `run` command.
@@ -140,8 +306,101 @@ pub enum InternalCommand {
 /// The `init` command.
 #[derive(Debug, Parser)]
 pub struct InitCommand {
-    /// Template to use.
+    /// Template to use (name of a builtin template or `<git-url>[#<ref>]`).
+    #[clap(conflicts_with = "interactive")]
     pub template: Option<String>,
+    /// Interactively generate a project for a device.
+    #[clap(long, short)]
+    pub interactive: bool,
+    /// Set a variable of the template (`<name>=<value>`).
+    #[clap(long = "set")]
+    pub variables: Vec<String>,
+}
+
+/// The `pull` command.
+#[derive(Debug, Parser)]
+pub struct PullCommand {
+    /// Require the lock file to pin all repositories and to be up to date.
+    #[clap(long, conflicts_with = "update")]
+    pub locked: bool,
+    /// Ignore the lock file and pin the latest commits of all repositories.
+    #[clap(long)]
+    pub update: bool,
+}
+
+/// The `fetch` command.
+#[derive(Debug, Parser)]
+pub struct FetchCommand {
+    /// Systems to prefetch the sources of (defaults to all systems).
+    pub systems: Vec<String>,
+}
+
+/// The `diff` command.
+#[derive(Debug, Parser)]
+pub struct DiffCommand {
+    /// Build directory of the old system (e.g., `build/<system>`).
+    pub old: PathBuf,
+    /// Build directory of the new system.
+    pub new: PathBuf,
+    /// Output the differences as JSON.
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// The `scan` command.
+#[derive(Debug, Parser)]
+pub struct ScanCommand {
+    /// The name of the system to scan.
+    pub system: String,
+    /// Fail if there are vulnerabilities with at least the given severity.
+    #[clap(long)]
+    pub fail_on: Option<VulnerabilitySeverity>,
+}
+
+/// The `search` command.
+#[derive(Debug, Parser)]
+pub struct SearchCommand {
+    /// Text to search for in the names and descriptions of repositories.
+    pub query: Option<String>,
+}
+
+/// The `inspect-image` command.
+#[derive(Debug, Parser)]
+pub struct InspectImageCommand {
+    /// Path of the image (e.g., `build/<system>/system.img`).
+    pub image: PathBuf,
+    /// Output the results as JSON.
+    #[clap(long)]
+    pub json: bool,
+    /// What to inspect (defaults to the partitions).
+    #[clap(subcommand)]
+    pub cmd: Option<InspectImageSubcommand>,
+}
+
+/// What to inspect with the `inspect-image` command.
+#[derive(Debug, Parser)]
+pub enum InspectImageSubcommand {
+    /// List the partitions and filesystems of the image.
+    Partitions,
+    /// List the entries of a directory.
+    Ls {
+        /// Partition number (defaults to the partition of the root filesystem).
+        #[clap(long, short)]
+        partition: Option<u32>,
+        /// Path of the directory.
+        #[clap(default_value = "/")]
+        path: PathBuf,
+    },
+    /// Print the contents of a file.
+    Cat {
+        /// Partition number (defaults to the partition of the root filesystem).
+        #[clap(long, short)]
+        partition: Option<u32>,
+        /// Path of the file.
+        path: PathBuf,
+    },
+    /// Print the `os-release` information of the system.
+    OsRelease,
 }
 
 /// The `bundler` command.
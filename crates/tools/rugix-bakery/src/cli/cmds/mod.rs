@@ -1,9 +1,15 @@
 pub mod run_bake;
 pub mod run_bundler;
 pub mod run_cache;
+pub mod run_diff;
+pub mod run_doctor;
+pub mod run_fetch;
 pub mod run_init;
+pub mod run_inspect_image;
 pub mod run_list;
 pub mod run_pull;
 pub mod run_run;
+pub mod run_scan;
+pub mod run_search;
 pub mod run_shell;
 pub mod run_test;
@@ -1,73 +1,331 @@
 //! The `bake` command.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use reportify::ResultExt;
+use colored::Colorize;
+use reportify::{bail, ResultExt};
+use rugix_common::fsutils::copy_file_sparse;
 
 use crate::cli::{args, load_project};
-use crate::oven::LayerBakery;
+use crate::config::systems::ImageFormat;
+use crate::oven::plan::{LayerPlan, PlanAction};
+use crate::oven::provenance::BakeInfo;
+use crate::oven::{report, LayerBakery};
+use crate::project::ProjectRef;
 use crate::{oven, BakeryResult};
 
 /// Run the `bake` command.
 pub fn run(args: &args::Args, cmd: &args::BakeCommand) -> BakeryResult<()> {
+    let started_on = jiff::Timestamp::now();
     let project = load_project(args)?;
     match cmd {
         args::BakeCommand::Image {
             system,
             output,
+            format,
+            profile,
+            from_layer,
             release,
             source_date,
+            report,
+            plan,
         } => {
-            let system_path = Path::new("build").join(system);
+            if *plan && from_layer.is_some() {
+                println!(
+                    "{} system `{system}` (assembled from layer artifact)",
+                    "build".yellow()
+                );
+                return Ok(());
+            }
+            if *plan {
+                return print_system_plan(&project, system, profile.as_deref());
+            }
+            if *report {
+                report::enable();
+            }
+            // Profiles are built separately such that they do not replace the system.
+            let system_path = match profile {
+                Some(profile) => Path::new("build").join(format!("{system}-{profile}")),
+                None => Path::new("build").join(system),
+            };
             let source_date_epoch =
                 source_date.unwrap_or_else(jiff::Timestamp::now).as_second() as u64;
-            oven::bake_system(
-                &project,
-                &release.release_info(),
-                system,
-                &system_path,
-                source_date_epoch,
-            )?;
+            let release_info = release.release_info();
+            match from_layer {
+                Some(layer) => oven::bake_system_from_layer(
+                    &project,
+                    &release_info,
+                    system,
+                    profile.as_deref(),
+                    layer,
+                    &system_path,
+                    source_date_epoch,
+                )?,
+                None => oven::bake_system(
+                    &project,
+                    &release_info,
+                    system,
+                    profile.as_deref(),
+                    &system_path,
+                    source_date_epoch,
+                )?,
+            }
+            let system_config = project.config().resolve_system_config(system)?;
+            let system_image_path = system_path.join("system.img");
+            if let Some(formats) = system_config
+                .image
+                .as_ref()
+                .and_then(|image| image.formats.as_ref())
+            {
+                for format in formats {
+                    if *format == ImageFormat::Raw {
+                        continue;
+                    }
+                    report::phase(&format!("conversion to {}", format.as_str()), || {
+                        oven::convert::convert_image(
+                            system_config,
+                            system,
+                            &system_image_path,
+                            *format,
+                            &system_path.join(format!("system.{}", format.extension())),
+                        )
+                    })?;
+                }
+            }
+            let compression = system_config
+                .image
+                .as_ref()
+                .and_then(|image| image.compression.as_ref());
+            if let Some(compression) = compression {
+                report::phase("compression", || {
+                    oven::compression::compress_image(&system_image_path, compression)
+                })?;
+            }
+            // Artifacts written outside of the build directory.
+            let mut outputs = Vec::new();
             if let Some(output) = output {
                 if let Some(parent) = output.parent() {
                     std::fs::create_dir_all(parent).ok();
                 }
-                let system_image_path = system_path
-                    .join("system.img")
-                    .canonicalize()
-                    .whatever("unable to canonicalize system image path")?;
-                let output_image_path = output
-                    .canonicalize()
-                    .whatever("unable to canonicalize output image path")?;
-                if system_image_path != output_image_path {
-                    std::fs::copy(system_image_path, output_image_path)
-                        .whatever("error copying image")?;
+                let format = format
+                    .or_else(|| ImageFormat::from_path(output))
+                    .unwrap_or(ImageFormat::Raw);
+                if format != ImageFormat::Raw {
+                    oven::convert::convert_image(
+                        system_config,
+                        system,
+                        &system_image_path,
+                        format,
+                        output,
+                    )?;
+                } else {
+                    let system_image_path = system_image_path
+                        .canonicalize()
+                        .whatever("unable to canonicalize system image path")?;
+                    let output_image_path = output
+                        .canonicalize()
+                        .whatever("unable to canonicalize output image path")?;
+                    if system_image_path != output_image_path {
+                        copy_file_sparse(&system_image_path, &output_image_path)
+                            .whatever("error copying image")?;
+                    }
+                }
+                if let Some(compression) = compression {
+                    // The system image itself has already been compressed above.
+                    if output.canonicalize().ok() != system_image_path.canonicalize().ok() {
+                        outputs.push(oven::compression::compress_image(output, compression)?);
+                    }
                 }
             }
+            outputs.extend(output.clone());
+            let outputs = outputs.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+            report::phase("manifest and provenance", || {
+                let manifest = oven::artifacts::write_manifest(
+                    system,
+                    &system_path,
+                    &outputs,
+                    source_date_epoch,
+                )?;
+                let bake = BakeInfo {
+                    command: "bake image",
+                    system,
+                    release: &release_info,
+                    source_date_epoch,
+                    started_on,
+                };
+                oven::provenance::write_provenance(&project, &bake, &system_path, &manifest)
+            })?;
+            report::write_report("bake image", system, &system_path, "")?;
         }
         args::BakeCommand::Layer {
             layer,
             arch,
             source_date,
+            export,
+            output,
+            report,
+            plan,
         } => {
+            if *plan {
+                print_plan(&LayerBakery::new(&project, *arch).plan_root(layer)?);
+                return Ok(());
+            }
+            if *report {
+                report::enable();
+            }
             let source_date_epoch =
                 source_date.unwrap_or_else(jiff::Timestamp::now).as_second() as u64;
-            LayerBakery::new(&project, *arch).bake_root(layer, source_date_epoch)?;
+            let system_tar =
+                LayerBakery::new(&project, *arch).bake_root(layer, source_date_epoch)?;
+            if let Some(format) = export {
+                let output = output.clone().unwrap_or_else(|| {
+                    Path::new("build/layers").join(format!("{layer}-{arch}.{}", format.extension()))
+                });
+                report::phase("export", || {
+                    oven::export::export_layer(
+                        &system_tar,
+                        layer,
+                        *arch,
+                        *format,
+                        &output,
+                        source_date_epoch,
+                    )
+                })?;
+            }
+            report::write_report(
+                "bake layer",
+                layer,
+                Path::new("build/layers"),
+                &format!("{layer}-{arch}."),
+            )?;
         }
         args::BakeCommand::Bundle {
             system,
             output,
             opts,
+            from_layer,
             release,
+            source_date,
+            report,
+            plan,
         } => {
+            let variants = oven::bundle_variants(&project, system)?;
+            if from_layer.is_some() && !variants.is_empty() {
+                bail!("`--from-layer` is not supported for bundles with variants");
+            }
+            if *plan && from_layer.is_some() {
+                println!(
+                    "{} system `{system}` (assembled from layer artifact)",
+                    "build".yellow()
+                );
+                return Ok(());
+            }
+            if *plan {
+                return print_system_plan(&project, system, None);
+            }
+            if *report {
+                report::enable();
+            }
             let system_path = Path::new("build").join(system);
-            let now = jiff::Timestamp::now().as_second() as u64;
-            oven::bake_system(&project, &release.release_info(), system, &system_path, now)?;
+            let source_date_epoch =
+                source_date.unwrap_or_else(jiff::Timestamp::now).as_second() as u64;
+            let release_info = release.release_info();
+            match from_layer {
+                Some(layer) => oven::bake_system_from_layer(
+                    &project,
+                    &release_info,
+                    system,
+                    None,
+                    layer,
+                    &system_path,
+                    source_date_epoch,
+                )?,
+                None => oven::bake_system(
+                    &project,
+                    &release_info,
+                    system,
+                    None,
+                    &system_path,
+                    source_date_epoch,
+                )?,
+            }
+            for variant in variants {
+                let variant_path = Path::new("build").join(&variant);
+                oven::bake_system(
+                    &project,
+                    &release_info,
+                    &variant,
+                    None,
+                    &variant_path,
+                    source_date_epoch,
+                )?;
+            }
             let output = output
                 .clone()
                 .unwrap_or_else(|| system_path.join("system.rugixb"));
-            oven::bake_bundle(&project, system, &system_path, &output, opts)?;
+            report::phase("bundle", || {
+                oven::bake_bundle(&project, system, &system_path, &output, opts)
+            })?;
+            report::phase("manifest and provenance", || {
+                let manifest = oven::artifacts::write_manifest(
+                    system,
+                    &system_path,
+                    &[&output],
+                    source_date_epoch,
+                )?;
+                let bake = BakeInfo {
+                    command: "bake bundle",
+                    system,
+                    release: &release_info,
+                    source_date_epoch,
+                    started_on,
+                };
+                oven::provenance::write_provenance(&project, &bake, &system_path, &manifest)
+            })?;
+            report::write_report("bake bundle", system, &system_path, "")?;
         }
     }
     Ok(())
 }
+
+/// Print the plan of baking the layers of the given system.
+fn print_system_plan(
+    project: &ProjectRef,
+    system: &str,
+    profile: Option<&str>,
+) -> BakeryResult<()> {
+    let system_config = project.config().resolve_system_config(system)?;
+    let profile = match profile {
+        Some(profile) => system_config.get_profile(profile)?,
+        None => None,
+    };
+    let layer = profile
+        .and_then(|profile| profile.layer.as_ref())
+        .unwrap_or(&system_config.layer);
+    let plans = LayerBakery::new(project, system_config.architecture).plan_root(layer)?;
+    print_plan(&plans);
+    println!(
+        "{} system `{system}` (always assembled from its layer)",
+        "build".yellow()
+    );
+    Ok(())
+}
+
+/// Print the plans of layers.
+fn print_plan(plans: &[LayerPlan]) {
+    for plan in plans {
+        let action = match plan.action {
+            PlanAction::Cached => plan.action.as_str().green(),
+            PlanAction::Download | PlanAction::Build => plan.action.as_str().yellow(),
+        };
+        println!("{action} layer `{}`", plan.name);
+        for reason in &plan.reasons {
+            println!("    because {reason}");
+        }
+        if plan.action == PlanAction::Build {
+            for recipe in &plan.recipes {
+                println!("    {} {recipe}", "recipe".blue());
+            }
+        }
+    }
+}
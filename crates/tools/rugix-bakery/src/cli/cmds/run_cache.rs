@@ -2,7 +2,18 @@
 
 use std::path::Path;
 
+use byte_calc::NumBytes;
+use reportify::{bail, ResultExt};
+use tracing::{info, warn};
+use xscript::{cmd, run, ParentEnv, Run};
+
 use crate::cli::{args, load_project};
+use crate::config::systems::Architecture;
+use crate::oven::customize::{used_files_unchanged, CACHE_KEY_FILE};
+use crate::oven::LayerBakery;
+use crate::project::ProjectRef;
+use crate::utils::build_env;
+use crate::utils::caching::{cache_entries, layer_arch, CacheEntryKind};
 use crate::BakeryResult;
 
 /// Run the `list` command.
@@ -10,12 +21,171 @@ pub fn run(args: &args::Args, cmd: &args::CacheCommand) -> BakeryResult<()> {
     let project = load_project(args)?;
     match cmd {
         args::CacheCommand::Clean => {
-            std::fs::remove_dir_all("/project/.rugix").ok();
-            std::fs::remove_dir_all(
-                Path::new("/run/rugix/bakery/cache").join(project.local_id().as_str()),
-            )
-            .ok();
+            std::fs::remove_dir_all(".rugix").ok();
+            std::fs::remove_dir_all(build_env::cache_dir().join(project.local_id().as_str())).ok();
+        }
+        args::CacheCommand::Prune {
+            older_than,
+            max_size,
+        } => {
+            let mut entries = cache_entries()?;
+            // Prune the least recently used entries first.
+            entries.sort_by_key(|entry| entry.last_used);
+            let mut remaining = Vec::new();
+            if let Some(older_than) = older_than {
+                let cutoff = jiff::Zoned::now()
+                    .checked_sub(*older_than)
+                    .whatever("invalid time span")?
+                    .timestamp()
+                    .as_second() as u64;
+                for entry in entries {
+                    if entry.last_used < cutoff {
+                        info!("removing {:?}", entry.path);
+                        entry.remove()?;
+                    } else {
+                        remaining.push(entry);
+                    }
+                }
+            } else {
+                remaining = entries;
+            }
+            if let Some(max_size) = max_size {
+                let mut total_size = remaining.iter().map(|entry| entry.size).sum::<u64>();
+                for entry in &remaining {
+                    if total_size <= max_size.raw {
+                        break;
+                    }
+                    info!("removing {:?}", entry.path);
+                    entry.remove()?;
+                    total_size -= entry.size;
+                }
+            }
+        }
+        args::CacheCommand::Stats => {
+            let mut entries = cache_entries()?;
+            // Show the most recently used entries first.
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+            let total_size = entries.iter().map(|entry| entry.size).sum::<u64>();
+            rugix_cli::suspend(|| {
+                eprintln!("{:<48} {:>12}  {}", "Entry", "Size", "Last Use");
+                for entry in &entries {
+                    let name = match &entry.kind {
+                        CacheEntryKind::Layer { name, arch } => format!(
+                            "layer {} ({})",
+                            name.as_deref().unwrap_or("<unknown>"),
+                            arch.as_deref().unwrap_or("?")
+                        ),
+                        CacheEntryKind::Download => format!(
+                            "download {}",
+                            entry.path.file_name().unwrap_or_default().to_string_lossy()
+                        ),
+                    };
+                    let last_used = jiff::Timestamp::from_second(entry.last_used as i64)
+                        .map(|timestamp| timestamp.strftime("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    eprintln!(
+                        "{name:<48} {:>12}  {last_used}",
+                        NumBytes::new(entry.size).to_string()
+                    );
+                }
+                eprintln!(
+                    "{} entries, {} in total",
+                    entries.len(),
+                    NumBytes::new(total_size)
+                );
+            });
+        }
+        args::CacheCommand::Export { archive, layers } => {
+            let mut cmd = cmd!(
+                "tar",
+                "-c",
+                "--auto-compress",
+                "-f",
+                archive,
+                "-C",
+                ".rugix"
+            );
+            let mut exported = 0;
+            for entry in cache_entries()? {
+                let CacheEntryKind::Layer { name, .. } = &entry.kind else {
+                    continue;
+                };
+                if !layers.is_empty() && !name.as_ref().is_some_and(|name| layers.contains(name)) {
+                    continue;
+                }
+                let layer_id = entry.path.file_name().unwrap();
+                if !is_current_layer(&project, &entry.path)? {
+                    warn!("skipping outdated layer {layer_id:?}");
+                    continue;
+                }
+                // Cached layers are keyed by their layer id and include their cache key.
+                cmd.add_arg(Path::new("layers").join(layer_id));
+                exported += 1;
+            }
+            if exported == 0 {
+                bail!("no cached layers to export");
+            }
+            info!("exporting {exported} cached layers to {archive:?}");
+            ParentEnv
+                .run(cmd)
+                .whatever("unable to create cache archive")?;
+        }
+        args::CacheCommand::Import { archive } => {
+            let layers_dir = Path::new(".rugix/layers");
+            std::fs::create_dir_all(layers_dir).whatever("unable to create layers directory")?;
+            let import_dir =
+                tempfile::tempdir_in(".rugix").whatever("unable to create temporary directory")?;
+            info!("importing cached layers from {archive:?}");
+            // Extracted files get the current time as their modification time, such that
+            // layers are not considered outdated because of a fresh checkout.
+            run!([
+                "tar",
+                "-x",
+                "--touch",
+                "-f",
+                archive,
+                "-C",
+                import_dir.path(),
+                "layers"
+            ])
+            .whatever("unable to extract cache archive")?;
+            let mut imported = 0;
+            for entry in std::fs::read_dir(import_dir.path().join("layers"))
+                .whatever("unable to read cache archive")?
+            {
+                let path = entry.whatever("unable to read cache archive")?.path();
+                let layer_id = path.file_name().unwrap();
+                // As their modification times are not meaningful, only layers built from
+                // the current inputs of the project must be imported.
+                if !is_current_layer(&project, &path)? {
+                    warn!("skipping outdated layer {layer_id:?}");
+                    continue;
+                }
+                let target = layers_dir.join(layer_id);
+                if target.exists() {
+                    std::fs::remove_dir_all(&target).whatever("unable to remove cached layer")?;
+                }
+                std::fs::rename(&path, &target).whatever("unable to import cached layer")?;
+                imported += 1;
+            }
+            info!("imported {imported} cached layers");
         }
     }
     Ok(())
 }
+
+/// Check whether the cached layer in the given directory has been built from the current
+/// inputs of the project, i.e., whether its recorded cache key is up to date and the
+/// contents of the files used by its recipes are unchanged.
+fn is_current_layer(project: &ProjectRef, layer_dir: &Path) -> BakeryResult<bool> {
+    let Ok(recorded) = std::fs::read_to_string(layer_dir.join(CACHE_KEY_FILE)) else {
+        return Ok(false);
+    };
+    let Some(arch) = layer_arch(layer_dir).and_then(|arch| arch.parse::<Architecture>().ok())
+    else {
+        return Ok(false);
+    };
+    let layer_id = layer_dir.file_name().unwrap().to_string_lossy();
+    let expected = LayerBakery::new(project, arch).cache_key_by_id(&layer_id)?;
+    Ok(expected.as_deref() == Some(recorded.trim()) && used_files_unchanged(project, layer_dir))
+}
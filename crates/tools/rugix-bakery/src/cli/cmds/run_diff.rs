@@ -0,0 +1,84 @@
+//! The `diff` command.
+
+use byte_calc::NumBytes;
+use colored::Colorize;
+
+use crate::cli::args;
+use crate::oven::budget::format_growth;
+use crate::oven::index::{self, FileChange};
+use crate::BakeryResult;
+
+/// Run the `diff` command.
+pub fn run(cmd: &args::DiffCommand) -> BakeryResult<()> {
+    let old = index::load_index(&cmd.old)?;
+    let new = index::load_index(&cmd.new)?;
+    let diff = index::diff(&old, &new);
+    rugix_cli::suspend(|| {
+        if cmd.json {
+            println!("{}", serde_json::to_string_pretty(&diff).unwrap());
+            return;
+        }
+        for (root, partition) in &diff.partitions {
+            println!(
+                "Partition `{}`: {} -> {} ({})",
+                root.bold(),
+                NumBytes::new(partition.old_size),
+                NumBytes::new(partition.new_size),
+                format_growth(partition.new_size as i64 - partition.old_size as i64)
+            );
+            if partition.is_empty() {
+                println!("  {}", "no changes".bright_black());
+                continue;
+            }
+            if !partition.packages.is_empty() {
+                println!("  Packages:");
+                for package in &partition.packages {
+                    match (&package.old_version, &package.new_version) {
+                        (None, Some(new)) => {
+                            println!("    {} {} {new}", "+".green(), package.name)
+                        }
+                        (Some(old), None) => {
+                            println!("    {} {} {old}", "-".red(), package.name)
+                        }
+                        (Some(old), Some(new)) => {
+                            println!("    {} {} {old} -> {new}", "~".yellow(), package.name)
+                        }
+                        (None, None) => unreachable!("package must exist in one of the systems"),
+                    }
+                }
+            }
+            println!(
+                "  Files: {} added, {} removed, {} changed",
+                partition.added.len(),
+                partition.removed.len(),
+                partition.changed.len()
+            );
+            let mut changes = partition
+                .added
+                .iter()
+                .map(|change| ("+".green(), change))
+                .chain(partition.removed.iter().map(|change| ("-".red(), change)))
+                .chain(
+                    partition
+                        .changed
+                        .iter()
+                        .map(|change| ("~".yellow(), change)),
+                )
+                .collect::<Vec<(_, &FileChange)>>();
+            changes.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+            for (marker, change) in changes {
+                let delta = change.size_delta();
+                if delta == 0 {
+                    println!("    {marker} {}", change.path);
+                } else {
+                    println!(
+                        "    {marker} {} {}",
+                        change.path,
+                        format!("({})", format_growth(delta)).bright_black()
+                    );
+                }
+            }
+        }
+    });
+    Ok(())
+}
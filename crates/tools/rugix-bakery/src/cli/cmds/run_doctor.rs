@@ -0,0 +1,318 @@
+//! The `doctor` command.
+
+use std::fs;
+use std::path::Path;
+
+use byte_calc::NumBytes;
+use colored::Colorize;
+use reportify::bail;
+use xscript::{read_str, Run};
+
+use crate::config::systems::Architecture;
+use crate::oven::emulation::{emulation_status, EmulationStatus};
+use crate::utils::build_env::{self, Isolation};
+use crate::BakeryResult;
+
+/// Free disk space below which builds are likely to fail.
+const MIN_DISK_SPACE: NumBytes = NumBytes::gibibytes(4);
+
+/// Free disk space recommended for building images.
+const RECOMMENDED_DISK_SPACE: NumBytes = NumBytes::gibibytes(16);
+
+/// Memory limit of the container below which builds are likely to get OOM-killed.
+const MIN_MEMORY: NumBytes = NumBytes::gibibytes(2);
+
+/// Linux capability required for mounting filesystems.
+const CAP_SYS_ADMIN: u32 = 21;
+
+/// Command to configure emulation on the host.
+const BINFMT_FIX: &str = "configure emulation on the host with \
+    `docker run --privileged --rm tonistiigi/binfmt --install all`";
+
+/// Severity of the outcome of a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Outcome of a check.
+#[derive(Debug)]
+struct Check {
+    name: String,
+    severity: Severity,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            severity: Severity::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(
+        name: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(name: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            severity: Severity::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run the `doctor` command.
+pub fn run() -> BakeryResult<()> {
+    let isolation = Isolation::from_env()?;
+    let rootless = is_rootless();
+    let mut checks = Vec::new();
+    match isolation {
+        Isolation::Chroot => {
+            checks.push(check_container_runtime());
+            checks.push(check_privileges(rootless));
+            checks.push(check_resource_limits());
+        }
+        Isolation::Bwrap => {
+            checks.push(check_bubblewrap());
+        }
+    }
+    checks.push(check_loop_devices(isolation, rootless));
+    for arch in [
+        Architecture::Amd64,
+        Architecture::Arm64,
+        Architecture::Armv7,
+    ] {
+        checks.push(check_emulation(arch, isolation, rootless));
+    }
+    checks.push(check_disk_space("project directory", Path::new(".")));
+    checks.push(check_disk_space("cache directory", &build_env::cache_dir()));
+
+    rugix_cli::suspend(|| {
+        for check in &checks {
+            let label = match check.severity {
+                Severity::Ok => "ok".green(),
+                Severity::Warning => "warning".yellow(),
+                Severity::Error => "error".red(),
+            };
+            eprintln!("[{label}] {}: {}", check.name.bold(), check.message);
+            if let Some(fix) = &check.fix {
+                eprintln!("    {} {fix}", "fix:".blue());
+            }
+        }
+    });
+    let errors = checks
+        .iter()
+        .filter(|check| check.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!("environment check found {errors} problem(s)");
+    }
+    Ok(())
+}
+
+/// Check whether we are running in a user namespace not mapping the host's root.
+fn is_rootless() -> bool {
+    fs::read_to_string("/proc/self/uid_map")
+        .map(|uid_map| {
+            !uid_map
+                .lines()
+                .any(|line| line.split_whitespace().collect::<Vec<_>>() == ["0", "0", "4294967295"])
+        })
+        .unwrap_or(false)
+}
+
+/// Read a field of `/proc/self/status`.
+fn proc_status(field: &str) -> Option<String> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status.lines().find_map(|line| {
+        line.strip_prefix(field)
+            .and_then(|value| value.strip_prefix(':'))
+            .map(|value| value.trim().to_owned())
+    })
+}
+
+fn check_container_runtime() -> Check {
+    const NAME: &str = "container runtime";
+    // The version is provided by `run-bakery`, as the runtime is not available inside
+    // of the container.
+    match std::env::var("RUGIX_CONTAINER_RUNTIME") {
+        Ok(version) if !version.trim().is_empty() => Check::ok(NAME, version.trim()),
+        _ => Check::warning(
+            NAME,
+            "unable to determine the container runtime",
+            "run Rugix Bakery with the latest `run-bakery` script and Docker or Podman",
+        ),
+    }
+}
+
+fn check_privileges(rootless: bool) -> Check {
+    const NAME: &str = "privileges";
+    let is_root = proc_status("Uid")
+        .and_then(|uids| uids.split_whitespace().nth(1).map(|euid| euid == "0"))
+        .unwrap_or(false);
+    let has_sys_admin = proc_status("CapEff")
+        .and_then(|caps| u64::from_str_radix(&caps, 16).ok())
+        .is_some_and(|caps| caps & (1 << CAP_SYS_ADMIN) != 0);
+    if !is_root || !has_sys_admin {
+        return Check::error(
+            NAME,
+            "not running as root with `CAP_SYS_ADMIN`, `chroot` and mounts are unavailable",
+            "run the container with `--privileged`, as done by `run-bakery`",
+        );
+    }
+    if rootless {
+        Check::ok(NAME, "running in a rootless container")
+    } else {
+        Check::ok(NAME, "running in a privileged container")
+    }
+}
+
+fn check_bubblewrap() -> Check {
+    const NAME: &str = "bubblewrap";
+    let Ok(version) = read_str!(["bwrap", "--version"]) else {
+        return Check::error(
+            NAME,
+            "`bwrap` is not installed",
+            "install Bubblewrap, e.g., with `apt install bubblewrap`",
+        );
+    };
+    let userns_disabled = fs::read_to_string("/proc/sys/kernel/unprivileged_userns_clone")
+        .is_ok_and(|value| value.trim() == "0")
+        || fs::read_to_string("/proc/sys/user/max_user_namespaces")
+            .is_ok_and(|value| value.trim() == "0");
+    if userns_disabled {
+        return Check::error(
+            NAME,
+            "unprivileged user namespaces are disabled",
+            "enable them with `sysctl kernel.unprivileged_userns_clone=1` \
+            and `sysctl user.max_user_namespaces=15000`",
+        );
+    }
+    Check::ok(NAME, version.trim())
+}
+
+fn check_resource_limits() -> Check {
+    const NAME: &str = "resource limits";
+    let fix = "raise the limits in the `container` section of `rugix-bakery.toml`";
+    // With cgroups v2, the limits of the container are exposed at the root of its
+    // cgroup namespace.
+    let memory = fs::read_to_string("/sys/fs/cgroup/memory.max")
+        .ok()
+        .and_then(|limit| limit.trim().parse().ok())
+        .map(NumBytes::new);
+    let cpus = fs::read_to_string("/sys/fs/cgroup/cpu.max")
+        .ok()
+        .and_then(|limit| {
+            let (quota, period) = limit.trim().split_once(' ')?;
+            Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+        });
+    let mut limits = Vec::new();
+    if let Some(memory) = memory {
+        limits.push(format!("{memory} of memory"));
+    }
+    if let Some(cpus) = cpus {
+        limits.push(format!("{cpus} CPUs"));
+    }
+    if limits.is_empty() {
+        return Check::ok(NAME, "no limits");
+    }
+    let message = limits.join(", ");
+    if memory.is_some_and(|memory| memory < MIN_MEMORY) {
+        Check::warning(
+            NAME,
+            format!("{message}, at least {MIN_MEMORY} of memory are recommended"),
+            fix,
+        )
+    } else {
+        Check::ok(NAME, message)
+    }
+}
+
+fn check_loop_devices(isolation: Isolation, rootless: bool) -> Check {
+    const NAME: &str = "loop devices";
+    let fix = "layers based on existing images require loop devices, \
+        run `modprobe loop` on the host and use a rootful container runtime";
+    if !Path::new("/dev/loop-control").exists() {
+        return Check::warning(NAME, "`/dev/loop-control` does not exist", fix);
+    }
+    if isolation == Isolation::Bwrap || rootless {
+        return Check::warning(NAME, "loop devices require root privileges", fix);
+    }
+    Check::ok(NAME, "loop devices are available")
+}
+
+fn check_emulation(arch: Architecture, isolation: Isolation, rootless: bool) -> Check {
+    let name = format!("emulation ({arch})");
+    match emulation_status(arch) {
+        EmulationStatus::Native => Check::ok(name, "supported natively"),
+        EmulationStatus::Registered { fix_binary: true } => {
+            Check::ok(name, "emulator registered with `binfmt_misc`")
+        }
+        EmulationStatus::Registered { fix_binary: false } => {
+            Check::warning(name, "emulator registered without the `F` flag", BINFMT_FIX)
+        }
+        EmulationStatus::Disabled { entry_path } => Check::error(
+            name,
+            format!("emulator registered but disabled (see {entry_path:?})"),
+            format!("enable it with `echo 1 > {}`", entry_path.display()),
+        ),
+        EmulationStatus::Unregistered { interpreter } => {
+            if interpreter.is_some() && isolation == Isolation::Chroot && !rootless {
+                Check::ok(name, "emulator will be registered when needed")
+            } else {
+                Check::error(name, "emulation is not set up", BINFMT_FIX)
+            }
+        }
+    }
+}
+
+fn check_disk_space(location: &str, path: &Path) -> Check {
+    let name = format!("disk space ({location})");
+    let fix = "free up disk space, e.g., with `rugix-bakery cache prune` or `docker system prune`";
+    let available = read_str!(["df", "--output=avail", "-B1", path])
+        .ok()
+        .and_then(|output| {
+            output
+                .lines()
+                .last()
+                .and_then(|line| line.trim().parse().ok())
+        })
+        .map(NumBytes::new);
+    let Some(available) = available else {
+        return Check::warning(
+            name,
+            format!("unable to determine free space of {path:?}"),
+            fix,
+        );
+    };
+    let message = format!("{available} available");
+    if available < MIN_DISK_SPACE {
+        Check::error(name, message, fix)
+    } else if available < RECOMMENDED_DISK_SPACE {
+        Check::warning(
+            name,
+            format!("{message}, at least {RECOMMENDED_DISK_SPACE} are recommended"),
+            fix,
+        )
+    } else {
+        Check::ok(name, message)
+    }
+}
@@ -0,0 +1,86 @@
+//! The `fetch` command.
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+use url::Url;
+
+use crate::cli::{args, load_project};
+use crate::config::recipes::ToolchainKind;
+use crate::oven::customize::recipe_schedule;
+use crate::oven::{rust, scan, toolchain};
+use crate::project::recipes::StepKind;
+use crate::utils::caching::download;
+use crate::BakeryResult;
+
+/// Run the `fetch` command.
+pub fn run(args: &args::Args, cmd: &args::FetchCommand) -> BakeryResult<()> {
+    // Loading the project checks out the repositories.
+    let project = load_project(args)?;
+    let library = project.library()?;
+    let systems = if cmd.systems.is_empty() {
+        let mut systems = project
+            .config()
+            .systems
+            .as_ref()
+            .map(|systems| systems.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        systems.sort();
+        systems
+    } else {
+        cmd.systems.clone()
+    };
+    let scans = systems.iter().any(|system| {
+        project
+            .config()
+            .get_system_config(system)
+            .is_some_and(|config| config.scan.is_some())
+    });
+    if scans {
+        scan::update_database()?;
+    }
+    for system in &systems {
+        info!("prefetching sources of system `{system}`");
+        let system_config = project.config().resolve_system_config(system)?;
+        let arch = system_config.architecture;
+        let root_repository = library.repositories.root_repository;
+        let Some(mut layer_idx) = library.lookup_layer(root_repository, &system_config.layer)
+        else {
+            bail!("unable to find layer `{}`", system_config.layer);
+        };
+        loop {
+            let layer = &library.layers[layer_idx];
+            let Some(config) = layer.config(arch) else {
+                bail!("no layer configuration for architecture `{arch}`");
+            };
+            if let Some(url) = &config.url {
+                let url = Url::parse(url).whatever("unable to parse layer URL")?;
+                if url.scheme() != "file" {
+                    download(&url)?;
+                }
+            }
+            for job in recipe_schedule(layer.repo, config, &library)? {
+                for step in &job.recipe.steps {
+                    match &step.kind {
+                        StepKind::Toolchain { config } => {
+                            if matches!(config.kind, ToolchainKind::Zig) {
+                                toolchain::provision_zig(config)?;
+                            }
+                        }
+                        StepKind::Rust { config } => {
+                            rust::prefetch(config, arch, &job.recipe.path)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            let Some(parent) = &config.parent else {
+                break;
+            };
+            let Some(parent_idx) = library.lookup_layer(layer.repo, parent) else {
+                bail!("unable to find layer `{parent}`");
+            };
+            layer_idx = parent_idx;
+        }
+    }
+    Ok(())
+}
@@ -2,10 +2,14 @@
 
 use std::collections::HashMap;
 use std::fs;
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 
 use colored::Colorize;
 use serde::Deserialize;
+use tempfile::tempdir;
+use tracing::info;
+use xscript::{run, LocalEnv, Run};
 
 use reportify::{bail, ResultExt};
 
@@ -16,6 +20,12 @@ use crate::BakeryResult;
 
 /// Run the `init` command.
 pub fn run(cmd: &args::InitCommand) -> BakeryResult<()> {
+    if cmd.interactive {
+        if Path::new("rugix-bakery.toml").exists() {
+            bail!("Project has already been initialized.");
+        }
+        return run_wizard();
+    }
     let Some(template_name) = &cmd.template else {
         let templates: HashMap<String, TemplateInfo> = toml::from_str(
             &fs::read_to_string(Path::new(TEMPLATE_PATH).join("templates.toml"))
@@ -42,14 +52,438 @@ pub fn run(cmd: &args::InitCommand) -> BakeryResult<()> {
         bail!("Project has already been initialized.");
     }
     let cwd = current_dir()?;
+    if let Some((url, rev)) = parse_git_template(template_name) {
+        let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+        let template_dir = temp_dir.path().join("template");
+        info!("fetching template from {url}");
+        run!(["git", "clone", "--quiet", url, &template_dir])
+            .whatever("unable to clone template repository")?;
+        if let Some(rev) = rev {
+            let env = LocalEnv::new(&template_dir);
+            // Only the default branch has a local branch after cloning, other branches
+            // must be resolved via their remote-tracking branches.
+            let remote_rev = format!("origin/{rev}");
+            let Some(commit) = [rev, remote_rev.as_str()].into_iter().find(|candidate| {
+                run!(env, ["git", "cat-file", "-e", "{candidate}^{{commit}}"]).is_ok()
+            }) else {
+                bail!("unable to find `{rev}` in template repository");
+            };
+            run!(env, ["git", "checkout", "--quiet", "--detach", commit])
+                .whatever_with(|_| format!("unable to check out `{rev}` of template repository"))?;
+        }
+        fs::remove_dir_all(template_dir.join(".git"))
+            .whatever("unable to remove `.git` directory of template")?;
+        let manifest_path = template_dir.join(TEMPLATE_MANIFEST);
+        let manifest = if manifest_path.exists() {
+            let manifest: TemplateManifest = toml::from_str(
+                &fs::read_to_string(&manifest_path).whatever("error reading template manifest")?,
+            )
+            .whatever("error parsing template manifest")?;
+            fs::remove_file(&manifest_path).whatever("unable to remove template manifest")?;
+            manifest
+        } else {
+            TemplateManifest::default()
+        };
+        let values = template_values(&manifest, &cmd.variables)?;
+        substitute_variables(&template_dir, &values)?;
+        copy_recursive(template_dir, cwd)
+            .whatever("error copying template to project directory")?;
+        return Ok(());
+    }
+    if !cmd.variables.is_empty() {
+        bail!("builtin templates do not have any variables");
+    }
     let template_dir = Path::new(TEMPLATE_PATH).join(template_name);
     copy_recursive(template_dir, cwd).whatever("error copying template to project directory")?;
     Ok(())
 }
 
+/// Split a template into the URL of a Git repository and an optional ref.
+///
+/// Returns [`None`] if the template is not a Git URL but the name of a builtin template.
+fn parse_git_template(template: &str) -> Option<(&str, Option<&str>)> {
+    if !template.contains("://") && !template.starts_with("git@") {
+        return None;
+    }
+    Some(match template.split_once('#') {
+        Some((url, rev)) => (url, Some(rev)),
+        None => (template, None),
+    })
+}
+
+/// Determine the values of the variables of a template.
+fn template_values(
+    manifest: &TemplateManifest,
+    assignments: &[String],
+) -> BakeryResult<HashMap<String, String>> {
+    let mut values = HashMap::new();
+    for (name, variable) in &manifest.variables {
+        if let Some(default) = &variable.default {
+            values.insert(name.clone(), default.clone());
+        }
+    }
+    for assignment in assignments {
+        let Some((name, value)) = assignment.split_once('=') else {
+            bail!("invalid variable assignment {assignment:?}, expected `<name>=<value>`");
+        };
+        if !manifest.variables.contains_key(name) {
+            bail!("template does not have a variable `{name}`");
+        }
+        values.insert(name.to_owned(), value.to_owned());
+    }
+    for (name, variable) in &manifest.variables {
+        if !values.contains_key(name) {
+            match &variable.description {
+                Some(description) => bail!(
+                    "missing value for variable `{name}` ({}), use `--set {name}=<value>`",
+                    description.trim()
+                ),
+                None => bail!("missing value for variable `{name}`, use `--set {name}=<value>`"),
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Substitute `{{ <name> }}` with the values of the variables in all text files.
+fn substitute_variables(dir: &Path, values: &HashMap<String, String>) -> BakeryResult<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).whatever("unable to read template directory")? {
+        let entry = entry.whatever("unable to read template directory entry")?;
+        let file_type = entry
+            .file_type()
+            .whatever("unable to determine file type")?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            substitute_variables(&path, values)?;
+        } else if file_type.is_file() {
+            // Binary files are copied as they are.
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut substituted = content.clone();
+            for (name, value) in values {
+                substituted = substituted
+                    .replace(&format!("{{{{ {name} }}}}"), value)
+                    .replace(&format!("{{{{{name}}}}}"), value);
+            }
+            if substituted != content {
+                fs::write(&path, substituted)
+                    .whatever_with(|_| format!("unable to write {path:?}"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Template path.
 const TEMPLATE_PATH: &str = "/usr/share/rugix/templates";
 
+/// Interactively ask for the device and features and generate a project.
+fn run_wizard() -> BakeryResult<()> {
+    let device = Device::ALL[prompt_select(
+        "Which device do you want to build images for?",
+        &Device::ALL.map(Device::description),
+    )?];
+    let architectures = device.architectures();
+    let architecture = architectures[prompt_select("Which architecture?", architectures)?];
+    let distributions = device.distributions();
+    let distribution = distributions[prompt_select(
+        "Which base distribution?",
+        &distributions
+            .iter()
+            .map(|d| d.description())
+            .collect::<Vec<_>>(),
+    )?];
+    // Debian and Alpine do not ship firmware updates for the Raspberry Pi, hence, the
+    // firmware required for the tryboot mechanism must be included in the image.
+    let firmware_model = if device == Device::RpiTryboot && distribution != Distribution::Raspios {
+        let models = ["pi4", "pi5"];
+        Some(
+            models[prompt_select(
+                "Which Raspberry Pi model?",
+                &["Raspberry Pi 4 or CM4", "Raspberry Pi 5"],
+            )?],
+        )
+    } else {
+        None
+    };
+    let ab_updates = prompt_bool("Use Rugix Ctrl's A/B update boot flow?", true)?;
+    let persist_root_home = prompt_bool(
+        "Persist the home directory of `root` (state management)?",
+        true,
+    )?;
+    let ssh = prompt_bool("Enable SSH?", true)?;
+    let ssh_key = if ssh {
+        prompt_string("Public SSH key to authorize for `root` (leave empty to set later):")?
+    } else {
+        String::new()
+    };
+
+    let mut recipes = Vec::new();
+    if firmware_model.is_some() {
+        recipes.push((
+            "core/rpi-include-firmware",
+            "Include the firmware update for the Raspberry Pi.",
+        ));
+    }
+    if ab_updates {
+        recipes.push((
+            distribution.setup_recipe(device),
+            "Prepare the system for Rugix Ctrl.",
+        ));
+        if device == Device::RpiUboot {
+            recipes.push((
+                "core/rpi-uboot-setup",
+                "Include U-Boot second stage boot script.",
+            ));
+        }
+    }
+    if persist_root_home {
+        recipes.push((
+            "core/persist-root-home",
+            "Persist the home directory of the root user.",
+        ));
+    }
+    if ssh {
+        recipes.push(("core/ssh", "Configure SSH."));
+    }
+    let mut layer = format!(
+        "parent = \"{}\"\n\nrecipes = [\n",
+        distribution.parent_layer(device)
+    );
+    for (recipe, comment) in &recipes {
+        layer.push_str(&format!("    # {comment}\n    \"{recipe}\",\n"));
+    }
+    layer.push_str("]\n");
+    if let Some(model) = firmware_model {
+        layer.push_str(&format!(
+            "\n[parameters.\"core/rpi-include-firmware\"]\nmodel = \"{model}\"\n"
+        ));
+    }
+    if ssh {
+        let ssh_key = if ssh_key.is_empty() {
+            "<INSERT YOUR PUBLIC SSH KEY HERE>"
+        } else {
+            ssh_key.as_str()
+        };
+        layer.push_str(&format!(
+            "\n[parameters.\"core/ssh\"]\nroot_authorized_keys = \"\"\"\n{ssh_key}\n\"\"\"\n"
+        ));
+    }
+
+    let target = if ab_updates {
+        device.target()
+    } else {
+        "unknown"
+    };
+    let mut project = String::new();
+    if !ab_updates {
+        project.push_str("# The boot flow of the device must be set up by a custom recipe.\n");
+    }
+    project.push_str(&format!(
+        "[systems.customized-{architecture}]\nlayer = \"customized\"\narchitecture = \"{architecture}\"\ntarget = \"{target}\"\n"
+    ));
+
+    fs::create_dir_all("layers").whatever("unable to create `layers` directory")?;
+    fs::create_dir_all("recipes").whatever("unable to create `recipes` directory")?;
+    if distribution.parent_layer(device) == ALPINE_ROOT_LAYER {
+        fs::write(
+            format!("layers/{ALPINE_ROOT_LAYER}.toml"),
+            "root = true\n\nrecipes = [\n    \"core/alpine-bootstrap\"\n]\n\n[parameters.\"core/alpine-bootstrap\"]\nversion = \"3.20\"\n",
+        )
+        .whatever("unable to write layer configuration")?;
+    }
+    fs::write("layers/customized.toml", layer).whatever("unable to write layer configuration")?;
+    fs::write("rugix-bakery.toml", project).whatever("unable to write project configuration")?;
+    fs::write(".gitignore", "/build\n/.rugix\n").whatever("unable to write `.gitignore`")?;
+    rugix_cli::suspend(|| {
+        eprintln!(
+            "\n{} Build an image with `./run-bakery bake image customized-{architecture}`.",
+            "Project has been initialized.".bold()
+        );
+    });
+    Ok(())
+}
+
+/// Root layer bootstrapping Alpine Linux for the Raspberry Pi.
+const ALPINE_ROOT_LAYER: &str = "alpine-root";
+
+/// Device supported by the wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Device {
+    RpiTryboot,
+    RpiUboot,
+    GenericEfi,
+}
+
+impl Device {
+    const ALL: [Device; 3] = [Device::RpiTryboot, Device::RpiUboot, Device::GenericEfi];
+
+    fn description(self) -> &'static str {
+        match self {
+            Device::RpiTryboot => "Raspberry Pi 4, 5, or CM4",
+            Device::RpiUboot => "Raspberry Pi 1, 2, 3, CM3, or Zero",
+            Device::GenericEfi => "EFI-compatible system (e.g., x86 PCs or VMs)",
+        }
+    }
+
+    fn target(self) -> &'static str {
+        match self {
+            Device::RpiTryboot => "rpi-tryboot",
+            Device::RpiUboot => "rpi-uboot",
+            Device::GenericEfi => "generic-grub-efi",
+        }
+    }
+
+    fn architectures(self) -> &'static [&'static str] {
+        match self {
+            Device::RpiTryboot => &["arm64"],
+            Device::RpiUboot => &["arm64", "armhf"],
+            Device::GenericEfi => &["amd64", "arm64"],
+        }
+    }
+
+    fn distributions(self) -> &'static [Distribution] {
+        match self {
+            Device::RpiTryboot => &[
+                Distribution::Raspios,
+                Distribution::Debian,
+                Distribution::Alpine,
+            ],
+            Device::RpiUboot => &[Distribution::Raspios],
+            Device::GenericEfi => &[Distribution::Debian, Distribution::Alpine],
+        }
+    }
+}
+
+/// Base distribution supported by the wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Distribution {
+    Debian,
+    Alpine,
+    Raspios,
+}
+
+impl Distribution {
+    fn description(self) -> &'static str {
+        match self {
+            Distribution::Debian => "Debian Bookworm",
+            Distribution::Alpine => "Alpine Linux 3.20",
+            Distribution::Raspios => "Raspberry Pi OS Bookworm",
+        }
+    }
+
+    fn parent_layer(self, device: Device) -> &'static str {
+        match (self, device) {
+            (Distribution::Debian, _) => "core/debian-bookworm",
+            (Distribution::Alpine, Device::GenericEfi) => "core/alpine-3-20",
+            // On the Raspberry Pi, Alpine Linux is bootstrapped by a root layer of the project.
+            (Distribution::Alpine, _) => ALPINE_ROOT_LAYER,
+            (Distribution::Raspios, _) => "core/raspios-bookworm",
+        }
+    }
+
+    fn setup_recipe(self, device: Device) -> &'static str {
+        match (self, device) {
+            (Distribution::Debian, Device::GenericEfi) => "core/debian-grub-setup",
+            (Distribution::Alpine, Device::GenericEfi) => "core/alpine-grub-setup",
+            (Distribution::Debian, _) => "core/rpi-debian-setup",
+            (Distribution::Alpine, _) => "core/rpi-alpine-setup",
+            (Distribution::Raspios, _) => "core/rpi-raspios-setup",
+        }
+    }
+}
+
+/// Read a line from the standard input.
+fn read_answer() -> BakeryResult<String> {
+    let mut answer = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .whatever("unable to read answer")?;
+    if read == 0 {
+        bail!("unexpected end of input");
+    }
+    Ok(answer.trim().to_owned())
+}
+
+/// Ask the user to select one of the given options.
+fn prompt_select<T: AsRef<str>>(question: &str, options: &[T]) -> BakeryResult<usize> {
+    if options.len() == 1 {
+        return Ok(0);
+    }
+    loop {
+        rugix_cli::suspend(|| {
+            eprintln!("{}", question.bold());
+            for (idx, option) in options.iter().enumerate() {
+                eprintln!("  {}) {}", idx + 1, option.as_ref());
+            }
+            eprint!("Selection [1]: ");
+            io::stderr().flush().ok();
+        });
+        let answer = read_answer()?;
+        if answer.is_empty() {
+            return Ok(0);
+        }
+        match answer.parse::<usize>() {
+            Ok(selection) if (1..=options.len()).contains(&selection) => return Ok(selection - 1),
+            _ => rugix_cli::suspend(|| eprintln!("{}", "Invalid selection.".red())),
+        }
+    }
+}
+
+/// Ask the user a yes/no question.
+fn prompt_bool(question: &str, default: bool) -> BakeryResult<bool> {
+    loop {
+        rugix_cli::suspend(|| {
+            eprint!(
+                "{} [{}]: ",
+                question.bold(),
+                if default { "Y/n" } else { "y/N" }
+            );
+            io::stderr().flush().ok();
+        });
+        match read_answer()?.to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => rugix_cli::suspend(|| eprintln!("{}", "Please answer `y` or `n`.".red())),
+        }
+    }
+}
+
+/// Ask the user for a string.
+fn prompt_string(question: &str) -> BakeryResult<String> {
+    rugix_cli::suspend(|| {
+        eprint!("{} ", question.bold());
+        io::stderr().flush().ok();
+    });
+    read_answer()
+}
+
+/// Name of the manifest file of templates fetched from Git repositories.
+const TEMPLATE_MANIFEST: &str = "rugix-template.toml";
+
+/// Manifest of a template fetched from a Git repository.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TemplateManifest {
+    /// Variables of the template.
+    #[serde(default)]
+    pub variables: HashMap<String, TemplateVariable>,
+}
+
+/// Variable of a template.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateVariable {
+    /// Description of the variable.
+    pub description: Option<String>,
+    /// Default value of the variable.
+    pub default: Option<String>,
+}
+
 /// Template information.
 #[derive(Debug, Clone, Deserialize)]
 pub struct TemplateInfo {
@@ -0,0 +1,127 @@
+//! The `inspect-image` command.
+
+use std::io::Write;
+
+use byte_calc::NumBytes;
+use colored::Colorize;
+use reportify::{bail, ResultExt};
+use rugix_common::inspect::InspectedImage;
+use serde::Serialize;
+
+use crate::cli::args::{self, InspectImageSubcommand};
+use crate::BakeryResult;
+
+/// Partition of an inspected image.
+#[derive(Debug, Serialize)]
+struct PartitionInfo {
+    number: u32,
+    size: u64,
+    filesystem: Option<String>,
+    used: Option<u64>,
+    system: bool,
+}
+
+/// Run the `inspect-image` command.
+pub fn run(cmd: &args::InspectImageCommand) -> BakeryResult<()> {
+    if !cmd.image.is_file() {
+        bail!("image {:?} does not exist", cmd.image);
+    }
+    let mut image = InspectedImage::open(&cmd.image).whatever("unable to inspect image")?;
+    match cmd
+        .cmd
+        .as_ref()
+        .unwrap_or(&InspectImageSubcommand::Partitions)
+    {
+        InspectImageSubcommand::Partitions => {
+            let system_partition = image
+                .system_partition()
+                .whatever("unable to inspect image")?;
+            let mut partitions = Vec::new();
+            for partition in image.partitions().to_vec() {
+                let used = match partition.filesystem {
+                    // Filesystems which cannot be mounted, e.g., swap, have no usage.
+                    Some(_) => image.usage(partition.number).ok().map(|usage| usage.used),
+                    None => None,
+                };
+                partitions.push(PartitionInfo {
+                    number: partition.number,
+                    size: partition.size,
+                    filesystem: partition.filesystem,
+                    used,
+                    system: system_partition == Some(partition.number),
+                });
+            }
+            rugix_cli::suspend(|| {
+                if cmd.json {
+                    println!("{}", serde_json::to_string_pretty(&partitions).unwrap());
+                    return;
+                }
+                for partition in &partitions {
+                    let mut line = format!(
+                        "Partition {}: {} {}",
+                        partition.number.to_string().bold(),
+                        NumBytes::new(partition.size),
+                        partition.filesystem.as_deref().unwrap_or("no filesystem")
+                    );
+                    if let Some(used) = partition.used {
+                        line.push_str(&format!(" ({} used)", NumBytes::new(used)));
+                    }
+                    if partition.system {
+                        line.push_str(&format!(" {}", "[system]".green()));
+                    }
+                    println!("{line}");
+                }
+            });
+        }
+        InspectImageSubcommand::Ls { partition, path } => {
+            let number = resolve_partition(&mut image, *partition)?;
+            let entries = image
+                .list(number, path)
+                .whatever("unable to list directory")?;
+            rugix_cli::suspend(|| {
+                if cmd.json {
+                    println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+                    return;
+                }
+                for entry in &entries {
+                    println!("{entry}");
+                }
+            });
+        }
+        InspectImageSubcommand::Cat { partition, path } => {
+            let number = resolve_partition(&mut image, *partition)?;
+            let contents = image.read(number, path).whatever("unable to read file")?;
+            rugix_cli::suspend(|| std::io::stdout().write_all(&contents))
+                .whatever("unable to write file to stdout")?;
+        }
+        InspectImageSubcommand::OsRelease => {
+            let Some((_, values)) = image.os_release().whatever("unable to inspect image")? else {
+                bail!("image does not contain an `os-release` file");
+            };
+            rugix_cli::suspend(|| {
+                if cmd.json {
+                    println!("{}", serde_json::to_string_pretty(&values).unwrap());
+                    return;
+                }
+                for (key, value) in &values {
+                    println!("{key}={value}");
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Determine the partition to inspect, defaulting to the partition of the system.
+fn resolve_partition(image: &mut InspectedImage, partition: Option<u32>) -> BakeryResult<u32> {
+    if let Some(partition) = partition {
+        return Ok(partition);
+    }
+    match image
+        .system_partition()
+        .whatever("unable to inspect image")?
+    {
+        Some(partition) => Ok(partition),
+        None => bail!("unable to find root filesystem, please specify a partition"),
+    }
+}
@@ -1,24 +1,285 @@
 //! The `list` command.
 
+use std::collections::{BTreeMap, HashMap};
+
+use colored::Colorize;
+use serde::Serialize;
+
 use crate::cli::{args, load_project};
+use crate::config::recipes::ParameterValue;
+use crate::config::repositories::SourceConfig;
+use crate::config::systems::{Architecture, Target};
+use crate::project::repositories::{ProjectRepositories, RepositoryIdx};
 use crate::BakeryResult;
 
+/// System of the project.
+#[derive(Debug, Serialize)]
+struct SystemInfo {
+    name: String,
+    layer: String,
+    architecture: Architecture,
+    target: Option<Target>,
+    profiles: Vec<String>,
+}
+
+/// Layer of the project or one of its repositories.
+#[derive(Debug, Serialize)]
+struct LayerInfo {
+    name: String,
+    repository: Option<String>,
+    description: Option<String>,
+    parent: Option<String>,
+    /// Architectures with a configuration, `None` if the layer supports all.
+    architectures: Option<Vec<Architecture>>,
+    recipes: Vec<String>,
+}
+
+/// Recipe of the project or one of its repositories.
+#[derive(Debug, Serialize)]
+struct RecipeInfo {
+    name: String,
+    repository: Option<String>,
+    description: Option<String>,
+    dependencies: Vec<String>,
+    /// Parameters with their default values, if any.
+    parameters: BTreeMap<String, Option<ParameterValue>>,
+    license: Option<String>,
+}
+
+/// Repository used by the project.
+#[derive(Debug, Serialize)]
+struct RepositoryInfo {
+    name: Option<String>,
+    description: Option<String>,
+    source: String,
+    /// Tag, branch, or revision of a Git repository.
+    version: Option<String>,
+    commit: Option<String>,
+}
+
 /// Run the `list` command.
 pub fn run(args: &args::Args, cmd: &args::ListCommand) -> BakeryResult<()> {
     let project = load_project(args)?;
     match cmd {
-        args::ListCommand::Systems => {
-            rugix_cli::suspend(|| {
-                if let Some(systems) = &project.config().systems {
-                    eprintln!("Available Systems:");
-                    for name in systems.keys() {
-                        eprintln!("  {name}");
-                    }
+        args::ListCommand::Systems { opts, arch } => {
+            let mut systems = Vec::new();
+            for (name, config) in project.config().systems.iter().flatten() {
+                if arch.is_some_and(|arch| arch != config.architecture)
+                    || !matches_filter(opts, name, None)
+                {
+                    continue;
+                }
+                let mut profiles = config
+                    .profiles
+                    .iter()
+                    .flatten()
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>();
+                profiles.sort();
+                systems.push(SystemInfo {
+                    name: name.clone(),
+                    layer: config.layer.clone(),
+                    architecture: config.architecture,
+                    target: config.target.clone(),
+                    profiles,
+                });
+            }
+            systems.sort_by(|x, y| x.name.cmp(&y.name));
+            print(opts, &systems, |system| {
+                println!(
+                    "{} {}",
+                    system.name.blue(),
+                    format!("({}, layer `{}`)", system.architecture, system.layer).bright_black()
+                );
+            });
+        }
+        args::ListCommand::Layers {
+            opts,
+            repository,
+            arch,
+        } => {
+            let library = project.library()?;
+            let names = repository_names(&library.repositories);
+            let mut layers = Vec::new();
+            for (_, layer) in library.layers.iter() {
+                let repository_name = names.get(&layer.repo).cloned();
+                if repository.is_some() && repository_name != *repository {
+                    continue;
+                }
+                if arch.is_some_and(|arch| layer.config(arch).is_none()) {
+                    continue;
+                }
+                let Some(config) = layer
+                    .default_config
+                    .as_ref()
+                    .or_else(|| layer.arch_configs.values().next())
+                else {
+                    continue;
+                };
+                if !matches_filter(opts, &layer.name, config.description.as_deref()) {
+                    continue;
+                }
+                let architectures = if layer.default_config.is_some() {
+                    None
                 } else {
-                    eprintln!("No systems available.");
+                    let mut architectures = layer.arch_configs.keys().copied().collect::<Vec<_>>();
+                    architectures.sort_by_key(|arch| arch.as_str());
+                    Some(architectures)
+                };
+                layers.push(LayerInfo {
+                    name: qualified_name(repository_name.as_deref(), &layer.name),
+                    repository: repository_name,
+                    description: config.description.clone(),
+                    parent: config.parent.clone(),
+                    architectures,
+                    recipes: config.recipes.clone().unwrap_or_default(),
+                });
+            }
+            layers.sort_by(|x, y| x.name.cmp(&y.name));
+            print(opts, &layers, |layer| {
+                println!("{}", layer.name.blue());
+                if let Some(description) = &layer.description {
+                    println!("  {}", description.trim().bright_black());
+                }
+            });
+        }
+        args::ListCommand::Recipes { opts, repository } => {
+            let library = project.library()?;
+            let names = repository_names(&library.repositories);
+            let mut recipes = Vec::new();
+            for (_, recipe) in library.recipes.iter() {
+                let repository_name = names.get(&recipe.repository).cloned();
+                if repository.is_some() && repository_name != *repository {
+                    continue;
+                }
+                let config = &recipe.config;
+                if !matches_filter(opts, &recipe.name, config.description.as_deref()) {
+                    continue;
+                }
+                recipes.push(RecipeInfo {
+                    name: qualified_name(repository_name.as_deref(), &recipe.name),
+                    repository: repository_name,
+                    description: config.description.clone(),
+                    dependencies: config.dependencies.clone().unwrap_or_default(),
+                    parameters: config
+                        .parameters
+                        .iter()
+                        .flatten()
+                        .map(|(name, def)| (name.clone(), def.default.clone()))
+                        .collect(),
+                    license: config.license.clone(),
+                });
+            }
+            recipes.sort_by(|x, y| x.name.cmp(&y.name));
+            print(opts, &recipes, |recipe| {
+                println!("{}", recipe.name.blue());
+                if let Some(description) = &recipe.description {
+                    println!("  {}", description.trim().bright_black());
+                }
+            });
+        }
+        args::ListCommand::Repositories { opts } => {
+            let repositories = project.repositories()?;
+            let names = repository_names(&repositories);
+            let mut infos = Vec::new();
+            for (idx, repository) in repositories.iter() {
+                let name = names.get(&idx).cloned();
+                if !matches_filter(
+                    opts,
+                    name.as_deref().unwrap_or_default(),
+                    repository.config.description.as_deref(),
+                ) {
+                    continue;
+                }
+                let (source, version) = match &repository.source.config {
+                    SourceConfig::Git(git) => (
+                        git.url.clone(),
+                        git.tag
+                            .clone()
+                            .or_else(|| git.branch.clone())
+                            .or_else(|| git.rev.clone()),
+                    ),
+                    SourceConfig::Path(path) => (path.path.clone(), None),
+                    SourceConfig::Registry(registry) => {
+                        (registry.repository.clone(), registry.version.clone())
+                    }
+                };
+                infos.push(RepositoryInfo {
+                    name,
+                    description: repository.config.description.clone(),
+                    source,
+                    version,
+                    commit: repository.source.commit.clone(),
+                });
+            }
+            print(opts, &infos, |repository| {
+                let name = repository.name.as_deref().unwrap_or("(project)");
+                let version = repository
+                    .version
+                    .as_deref()
+                    .or(repository.commit.as_deref())
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {}",
+                    name.blue(),
+                    version,
+                    format!("({})", repository.source).bright_black()
+                );
+                if let Some(description) = &repository.description {
+                    println!("  {}", description.trim().bright_black());
                 }
             });
         }
     }
     Ok(())
 }
+
+/// Check whether the name or description of an entry contains the filter text.
+fn matches_filter(opts: &args::ListOpts, name: &str, description: Option<&str>) -> bool {
+    let Some(filter) = &opts.filter else {
+        return true;
+    };
+    let filter = filter.to_lowercase();
+    name.to_lowercase().contains(&filter)
+        || description.is_some_and(|description| description.to_lowercase().contains(&filter))
+}
+
+/// Print the entries as JSON or with the given function.
+fn print<T: Serialize>(opts: &args::ListOpts, entries: &[T], print_entry: impl Fn(&T)) {
+    rugix_cli::suspend(|| {
+        if opts.json {
+            println!("{}", serde_json::to_string_pretty(entries).unwrap());
+        } else if entries.is_empty() {
+            eprintln!("Nothing found.");
+        } else {
+            entries.iter().for_each(print_entry);
+        }
+    });
+}
+
+/// Names by which the project refers to its repositories.
+///
+/// The repository of the project itself has no name. Repositories which are only used
+/// by other repositories are named as in the first repository using them.
+fn repository_names(repositories: &ProjectRepositories) -> HashMap<RepositoryIdx, String> {
+    let mut names = HashMap::new();
+    names.insert(repositories.core_repository, "core".to_owned());
+    let root = &repositories[repositories.root_repository];
+    let others = repositories.iter().map(|(_, repository)| repository);
+    for repository in std::iter::once(root).chain(others) {
+        for (name, dependency) in &repository.repositories {
+            if *dependency != repositories.root_repository {
+                names.entry(*dependency).or_insert_with(|| name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// Name of a layer or recipe as used in the configuration of the project.
+fn qualified_name(repository: Option<&str>, name: &str) -> String {
+    match repository {
+        Some(repository) => format!("{repository}/{name}"),
+        None => name.to_owned(),
+    }
+}
@@ -2,13 +2,21 @@
 
 use colored::Colorize;
 
-use crate::cli::{args, load_project};
+use crate::cli::{args, project_loader};
 use crate::config::repositories::SourceConfig;
+use crate::project::repositories::LockMode;
 use crate::BakeryResult;
 
 /// Run the `pull` command.
-pub fn run(args: &args::Args) -> BakeryResult<()> {
-    let project = load_project(args)?;
+pub fn run(args: &args::Args, cmd: &args::PullCommand) -> BakeryResult<()> {
+    let lock_mode = if cmd.locked {
+        LockMode::Locked
+    } else if cmd.update {
+        LockMode::Update
+    } else {
+        LockMode::Default
+    };
+    let project = project_loader(args)?.with_lock_mode(lock_mode).load()?;
     for (_, repository) in project.repositories()?.iter() {
         rugix_cli::suspend(|| {
             println!(
@@ -32,9 +40,22 @@ pub fn run(args: &args::Args) -> BakeryResult<()> {
                 }
                 SourceConfig::Git(config) => {
                     println!(
-                        "  {}{}",
+                        "  {}{} {}",
                         "source git ".bright_black(),
-                        config.url.bright_black()
+                        config.url.bright_black(),
+                        repository
+                            .source
+                            .commit
+                            .as_deref()
+                            .unwrap_or_default()
+                            .bright_black(),
+                    );
+                }
+                SourceConfig::Registry(config) => {
+                    println!(
+                        "  {}{}",
+                        "source registry ".bright_black(),
+                        config.repository.bright_black()
                     );
                 }
             }
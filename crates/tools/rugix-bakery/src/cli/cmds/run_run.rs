@@ -1,9 +1,9 @@
 //! The `run` command.
 
 use std::path::Path;
-use std::time::Duration;
 
 use reportify::ResultExt;
+use rugix_common::fsutils::copy_file_sparse;
 use rugix_tasks::block_on;
 use tempfile::TempDir;
 use tracing::info;
@@ -24,6 +24,7 @@ pub fn run(args: &args::Args, cmd: &args::RunCommand) -> BakeryResult<()> {
         &project,
         &cmd.release.release_info(),
         &cmd.system,
+        None,
         &output,
         now,
     )
@@ -36,7 +37,17 @@ pub fn run(args: &args::Args, cmd: &args::RunCommand) -> BakeryResult<()> {
     let temp_img = tempdir.path().join("system.img");
 
     // We copy the image such that new builds do not corrupt the VM.
-    std::fs::copy(&image_path, &temp_img).whatever("unable to copy image")?;
+    copy_file_sparse(&image_path, &temp_img).whatever("unable to copy image")?;
+    let disks_dir = output.join("disks");
+    if disks_dir.exists() {
+        let temp_disks_dir = tempdir.path().join("disks");
+        std::fs::create_dir_all(&temp_disks_dir).whatever("unable to create disks directory")?;
+        for entry in std::fs::read_dir(&disks_dir).whatever("unable to read disks directory")? {
+            let entry = entry.whatever("unable to read disks directory")?;
+            copy_file_sparse(&entry.path(), &temp_disks_dir.join(entry.file_name()))
+                .whatever("unable to copy disk image")?;
+        }
+    }
 
     let image_path = temp_img;
 
@@ -45,24 +56,28 @@ pub fn run(args: &args::Args, cmd: &args::RunCommand) -> BakeryResult<()> {
         system: cmd.system.clone(),
         disk_size: None,
         ssh: None,
+        device: None,
+        qemu: None,
+        snapshot: None,
     };
 
-    block_on(async {
-        let _vm = qemu::start(
-            image_config.architecture,
-            &image_path.to_string_lossy(),
-            &system,
-        )
-        .await?;
-
-        info!("VM started");
-
-        loop {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-        }
-
-        #[expect(unreachable_code)]
-        BakeryResult::Ok(())
+    let mut vm = block_on(qemu::start(
+        image_config.architecture,
+        &image_path.to_string_lossy(),
+        &system,
+        tempdir.path(),
+        qemu::VmOptions {
+            interactive: true,
+            ..qemu::VmOptions::new(qemu::SSH_PORT)
+        },
+    ))?;
+
+    info!("VM started");
+
+    // The serial console of the VM is attached to the terminal until Qemu exits.
+    rugix_cli::suspend(|| {
+        eprintln!("Press Ctrl-A X to quit the VM or Ctrl-A C to switch to the Qemu monitor.");
+        block_on(vm.wait())
     })?;
 
     Ok(())
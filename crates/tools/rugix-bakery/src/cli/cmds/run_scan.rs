@@ -0,0 +1,59 @@
+//! The `scan` command.
+
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::cli::{args, load_project};
+use crate::config::systems::VulnerabilitySeverity;
+use crate::oven::scan;
+use crate::BakeryResult;
+
+/// Run the `scan` command.
+pub fn run(args: &args::Args, cmd: &args::ScanCommand) -> BakeryResult<()> {
+    let project = load_project(args)?;
+    let system_config = project.config().resolve_system_config(&cmd.system)?;
+    let scan_config = system_config.scan.as_ref();
+    let system_path = Path::new("build").join(&cmd.system);
+    let vulnerabilities = scan::scan_system(scan_config, &system_path)?;
+    rugix_cli::suspend(|| {
+        for vulnerability in &vulnerabilities {
+            let severity = match vulnerability.severity {
+                Some(VulnerabilitySeverity::Critical) => "critical".red().bold(),
+                Some(VulnerabilitySeverity::High) => "high".red(),
+                Some(VulnerabilitySeverity::Medium) => "medium".yellow(),
+                Some(VulnerabilitySeverity::Low) => "low".normal(),
+                Some(VulnerabilitySeverity::Negligible) => "negligible".bright_black(),
+                None => "unknown".bright_black(),
+            };
+            let mut line = format!(
+                "[{severity}] {} {} {}",
+                vulnerability.id.bold(),
+                vulnerability.package,
+                vulnerability.version
+            );
+            if !vulnerability.fixed_in.is_empty() {
+                line.push_str(&format!(
+                    " {}",
+                    format!("(fixed in {})", vulnerability.fixed_in.join(", ")).green()
+                ));
+            }
+            if vulnerability.ignored {
+                line.push_str(&format!(" {}", "(ignored)".bright_black()));
+            }
+            println!("{line}");
+        }
+        println!(
+            "Found {} vulnerabilities, see {:?}.",
+            vulnerabilities.len(),
+            system_path.join(scan::VULNERABILITIES_FILE)
+        );
+    });
+    if let Some(fail_on) = cmd
+        .fail_on
+        .or_else(|| scan_config.and_then(|config| config.fail_on))
+    {
+        scan::check_severity(&vulnerabilities, fail_on)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! The `search` command.
+
+use colored::Colorize;
+use reportify::bail;
+
+use crate::cli::{args, load_project};
+use crate::project::registry::RegistryIndex;
+use crate::BakeryResult;
+
+/// Run the `search` command.
+pub fn run(args: &args::Args, cmd: &args::SearchCommand) -> BakeryResult<()> {
+    let project = load_project(args)?;
+    let Some(registries) = project
+        .config()
+        .registries
+        .as_ref()
+        .filter(|registries| !registries.is_empty())
+    else {
+        bail!("no registries configured");
+    };
+    let query = cmd.query.as_deref().unwrap_or_default().to_lowercase();
+    let mut names = registries.keys().collect::<Vec<_>>();
+    names.sort();
+    for registry in names {
+        let index = RegistryIndex::fetch(&registries[registry])?;
+        let mut repositories = index
+            .repositories
+            .iter()
+            .filter(|(name, repository)| {
+                name.to_lowercase().contains(&query)
+                    || repository
+                        .description
+                        .as_deref()
+                        .is_some_and(|description| description.to_lowercase().contains(&query))
+            })
+            .collect::<Vec<_>>();
+        repositories.sort_by_key(|(name, _)| *name);
+        rugix_cli::suspend(|| {
+            for (name, repository) in repositories {
+                let version = repository
+                    .latest_version()
+                    .map(|version| version.to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {}",
+                    name.blue(),
+                    version,
+                    format!("({registry})").bright_black()
+                );
+                if let Some(description) = &repository.description {
+                    println!("  {}", description.trim().bright_black());
+                }
+            }
+        });
+    }
+    Ok(())
+}
@@ -1,12 +1,83 @@
 //! The `shell` command.
 
-use reportify::ResultExt;
+use std::ffi::CString;
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
 
+use reportify::{bail, ResultExt};
+
+use crate::cli::{args, load_project};
+use crate::config::systems::Architecture;
+use crate::oven::LayerBakery;
 use crate::BakeryResult;
 
 /// Run the `shell` command.
-pub fn run() -> BakeryResult<()> {
-    // Replace ourselves with a shell. This is primarily intended for debugging.
-    nix::unistd::execv::<&std::ffi::CStr>(c"/bin/zsh", &[]).whatever("error executing shell")?;
+pub fn run(args: &args::Args, cmd: &args::ShellCommand) -> BakeryResult<()> {
+    if let Some(args::ShellSubcommand::Exec {
+        layer,
+        arch,
+        recipe,
+        command,
+    }) = &cmd.cmd
+    {
+        return exec(args, layer.as_deref(), *arch, recipe.as_deref(), command);
+    }
+    let Some(layer) = &cmd.layer else {
+        // Replace ourselves with a shell. This is primarily intended for debugging.
+        nix::unistd::execv::<&std::ffi::CStr>(c"/bin/zsh", &[])
+            .whatever("error executing shell")?;
+        return Ok(());
+    };
+    let Some(arch) = cmd.arch else {
+        bail!("the architecture of the layer must be specified with `--arch`");
+    };
+    let project = load_project(args)?;
+    let source_date_epoch = jiff::Timestamp::now().as_second() as u64;
+    LayerBakery::new(&project, arch).shell(
+        layer,
+        cmd.recipe.as_deref(),
+        None,
+        source_date_epoch,
+    )?;
+    Ok(())
+}
+
+/// Run a command in the Docker container or a layer and exit with its exit code.
+fn exec(
+    args: &args::Args,
+    layer: Option<&str>,
+    arch: Option<Architecture>,
+    recipe: Option<&str>,
+    command: &[String],
+) -> BakeryResult<()> {
+    let Some(layer) = layer else {
+        // Replace ourselves with the command such that its exit code is propagated.
+        let argv = command
+            .iter()
+            .map(|arg| CString::new(arg.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+            .whatever("command must not contain null bytes")?;
+        nix::unistd::execvp(&argv[0], &argv)
+            .whatever_with(|_| format!("error executing command {:?}", command[0]))?;
+        return Ok(());
+    };
+    let Some(arch) = arch else {
+        bail!("the architecture of the layer must be specified with `--arch`");
+    };
+    let project = load_project(args)?;
+    let source_date_epoch = jiff::Timestamp::now().as_second() as u64;
+    let status =
+        LayerBakery::new(&project, arch).shell(layer, recipe, Some(command), source_date_epoch)?;
+    if !status.success() {
+        // The customization environment has been torn down already.
+        std::process::exit(exit_code(status));
+    }
     Ok(())
 }
+
+/// Exit code of a process, following the shell's convention for signals.
+fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .unwrap_or_else(|| 128 + status.signal().unwrap_or_default())
+}
@@ -33,9 +33,7 @@ pub fn run(args: &args::Args, cmd: &args::TestCommand) -> BakeryResult<()> {
             );
         }
     };
-    for workflow in &workflows {
-        tester::main(&project, &workflow)?;
-        rugix_cli::force_redraw();
-    }
+    tester::main(&project, &workflows, cmd.jobs)?;
+    rugix_cli::force_redraw();
     Ok(())
 }
@@ -5,7 +5,9 @@ use std::path::{Path, PathBuf};
 use reportify::ResultExt;
 use tracing::info;
 
+use crate::config::overrides::Overrides;
 use crate::project::{ProjectLoader, ProjectRef};
+use crate::utils::build_env;
 use crate::BakeryResult;
 
 mod cmds;
@@ -16,14 +18,23 @@ pub(crate) mod status;
 
 /// Run Rugix Bakery with the provided command line arguments.
 pub fn run(args: args::Args) -> BakeryResult<()> {
+    if args.offline {
+        build_env::set_offline();
+    }
     match &args.cmd {
         args::Command::Bake(cmd) => cmds::run_bake::run(&args, cmd),
         args::Command::Test(cmd) => cmds::run_test::run(&args, cmd),
         args::Command::Run(cmd) => cmds::run_run::run(&args, cmd),
         args::Command::List(cmd) => cmds::run_list::run(&args, cmd),
-        args::Command::Pull => cmds::run_pull::run(&args),
+        args::Command::Pull(cmd) => cmds::run_pull::run(&args, cmd),
+        args::Command::Fetch(cmd) => cmds::run_fetch::run(&args, cmd),
+        args::Command::Search(cmd) => cmds::run_search::run(&args, cmd),
         args::Command::Init(cmd) => cmds::run_init::run(cmd),
-        args::Command::Shell => cmds::run_shell::run(),
+        args::Command::Doctor => cmds::run_doctor::run(),
+        args::Command::Diff(cmd) => cmds::run_diff::run(cmd),
+        args::Command::Scan(cmd) => cmds::run_scan::run(&args, cmd),
+        args::Command::InspectImage(cmd) => cmds::run_inspect_image::run(cmd),
+        args::Command::Shell(cmd) => cmds::run_shell::run(&args, cmd),
         args::Command::Bundler(cmd) => cmds::run_bundler::run(cmd),
         args::Command::Cache(cmd) => cmds::run_cache::run(&args, cmd),
     }
@@ -36,28 +47,33 @@ fn current_dir() -> BakeryResult<PathBuf> {
 
 /// Load the project from the current working directory.
 fn load_project(args: &args::Args) -> BakeryResult<ProjectRef> {
+    project_loader(args)?.load()
+}
+
+/// Prepare loading the project from the current working directory.
+fn project_loader(args: &args::Args) -> BakeryResult<ProjectLoader> {
     let dev_mode = std::env::var("RUGIX_DEV")
         .map(|dev| dev != "false")
         .unwrap_or(false);
     let bakery_image =
         std::env::var("RUGIX_BAKERY_IMAGE").whatever("unable to determine Docker image")?;
-    let image_tag = Path::new("/project/.rugix/docker-image");
+    let image_tag = Path::new(".rugix/docker-image");
     if !dev_mode {
         if image_tag.exists() {
             let cache_image =
                 std::fs::read_to_string(image_tag).whatever("unable to read image tag")?;
             if cache_image.trim() != bakery_image.trim() {
                 info!("cache is based on older Docker image, deleting `.rugix` directory");
-                std::fs::remove_dir_all("/project/.rugix").ok();
+                std::fs::remove_dir_all(".rugix").ok();
             }
         }
     }
-    std::fs::create_dir_all("/project/.rugix").whatever("unable to create `.rugix` directory")?;
+    std::fs::create_dir_all(".rugix").whatever("unable to create `.rugix` directory")?;
     std::fs::write(image_tag, bakery_image).whatever("unable to write Docker image tag")?;
     let project_identity =
         std::env::var("RUGIX_HOST_PROJECT_DIR").whatever("unable to determine host directory")?;
-    ProjectLoader::current_dir()?
+    Ok(ProjectLoader::current_dir()?
         .with_config_file(args.config.as_deref())
-        .with_local_id(project_identity.as_bytes())
-        .load()
+        .with_overrides(Overrides::parse(&args.defines)?)
+        .with_local_id(project_identity.as_bytes()))
 }
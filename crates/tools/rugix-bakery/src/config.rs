@@ -5,18 +5,23 @@ use std::str::FromStr;
 use std::{fmt, fs};
 
 use images::{Filesystem, PartitionTableType};
+use overrides::Overrides;
 use projects::ProjectConfig;
 use rugix_tasks::check_canceled;
 use serde::de::DeserializeOwned;
 
-use reportify::{whatever, ResultExt};
+use reportify::{bail, whatever, ResultExt};
 
 use crate::BakeryResult;
 
 use self::recipes::ParameterValue;
-use self::systems::{Architecture, SystemConfig};
+use self::systems::{
+    Architecture, ImageFormat, SystemConfig, SystemProfileConfig, VulnerabilitySeverity,
+};
 
 mod generated;
+pub mod overrides;
+pub mod partition_size;
 // Re-export the generated data structures.
 pub use generated::*;
 
@@ -28,6 +33,18 @@ pub mod errors {
     #[derive(Debug, Error)]
     #[error("invalid architecture")]
     pub struct InvalidArchitectureError;
+
+    #[derive(Debug, Error)]
+    #[error("invalid image format")]
+    pub struct InvalidImageFormatError;
+
+    #[derive(Debug, Error)]
+    #[error("invalid vulnerability severity")]
+    pub struct InvalidSeverityError;
+
+    #[derive(Debug, Error)]
+    #[error("invalid partition size")]
+    pub struct InvalidPartitionSizeError;
 }
 
 impl Architecture {
@@ -63,6 +80,82 @@ impl FromStr for Architecture {
     }
 }
 
+impl VulnerabilitySeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VulnerabilitySeverity::Negligible => "negligible",
+            VulnerabilitySeverity::Low => "low",
+            VulnerabilitySeverity::Medium => "medium",
+            VulnerabilitySeverity::High => "high",
+            VulnerabilitySeverity::Critical => "critical",
+        }
+    }
+}
+
+impl FromStr for VulnerabilitySeverity {
+    type Err = errors::InvalidSeverityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "negligible" => Ok(Self::Negligible),
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            _ => Err(errors::InvalidSeverityError),
+        }
+    }
+}
+
+impl ImageFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageFormat::Raw => "raw",
+            ImageFormat::Qcow2 => "qcow2",
+            ImageFormat::Vhdx => "vhdx",
+            ImageFormat::Vmdk => "vmdk",
+            ImageFormat::Ova => "ova",
+        }
+    }
+
+    /// File extension of images in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Raw => "img",
+            _ => self.as_str(),
+        }
+    }
+
+    /// Infer the image format from the extension of the given path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "img" | "raw" => Some(ImageFormat::Raw),
+            extension => extension.parse().ok(),
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = errors::InvalidImageFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Self::Raw),
+            "qcow2" => Ok(Self::Qcow2),
+            "vhdx" => Ok(Self::Vhdx),
+            "vmdk" => Ok(Self::Vmdk),
+            "ova" => Ok(Self::Ova),
+            _ => Err(errors::InvalidImageFormatError),
+        }
+    }
+}
+
 impl fmt::Display for ParameterValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -87,6 +180,24 @@ impl ProjectConfig {
     }
 }
 
+impl SystemConfig {
+    /// Retrieve the build profile with the provided name.
+    ///
+    /// Returns `None` for the `release` profile, unless the system defines it, as the
+    /// release profile is the configuration of the system itself.
+    pub fn get_profile(&self, name: &str) -> BakeryResult<Option<&SystemProfileConfig>> {
+        match self
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+        {
+            Some(profile) => Ok(Some(profile)),
+            None if name == "release" => Ok(None),
+            None => bail!("system does not have a profile {name:?}"),
+        }
+    }
+}
+
 impl Filesystem {
     /// Name of the filesystem.
     pub fn name(&self) -> &'static str {
@@ -94,6 +205,8 @@ impl Filesystem {
             Filesystem::Ext4(_) => "ext4",
             Filesystem::Fat32 => "fat32",
             Filesystem::Squashfs(_) => "squashfs",
+            Filesystem::F2fs(_) => "f2fs",
+            Filesystem::Swap => "swap",
         }
     }
 }
@@ -129,6 +242,81 @@ where
     .with_info(|_| format!("loading configuration from {path:?}"))
 }
 
+/// Load the project configuration from the provided path.
+///
+/// Before parsing, the overrides are applied and the configurations of systems are merged
+/// with the configurations of the systems they extend.
+pub fn load_project_config(path: &Path, overrides: &Overrides) -> BakeryResult<ProjectConfig> {
+    check_canceled();
+    let config = fs::read_to_string(path)
+        .whatever_with(|_| format!("unable to read configuration file {path:?}"))?;
+    let mut config = toml::from_str::<toml::Table>(&config)
+        .whatever("unable to parse configuration file")
+        .with_info(|_| format!("loading configuration from {path:?}"))?;
+    overrides.apply(&mut config)?;
+    if let Some(toml::Value::Table(systems)) = config.get_mut("systems") {
+        let mut resolved = toml::Table::new();
+        for name in systems.keys() {
+            resolved.insert(
+                name.clone(),
+                toml::Value::Table(resolve_system(systems, name, &mut Vec::new())?),
+            );
+        }
+        *systems = resolved;
+    }
+    toml::Value::Table(config)
+        .try_into()
+        .whatever("unable to parse configuration file")
+        .with_info(|_| format!("loading configuration from {path:?}"))
+}
+
+/// Resolve the configuration of a system by merging it with the system it extends.
+///
+/// The `stack` contains the systems whose configurations are currently being resolved.
+fn resolve_system(
+    systems: &toml::Table,
+    name: &str,
+    stack: &mut Vec<String>,
+) -> BakeryResult<toml::Table> {
+    if stack.iter().any(|other| other == name) {
+        bail!("cyclic `extends` of system {name:?}");
+    }
+    let Some(toml::Value::Table(system)) = systems.get(name) else {
+        bail!("configuration of system {name:?} must be a table");
+    };
+    let Some(base) = system.get("extends") else {
+        return Ok(system.clone());
+    };
+    let Some(base) = base.as_str() else {
+        bail!("`extends` of system {name:?} must be a string");
+    };
+    if !systems.contains_key(base) {
+        bail!("system {name:?} extends unknown system {base:?}");
+    }
+    stack.push(name.to_owned());
+    let mut resolved = resolve_system(systems, base, stack)?;
+    stack.pop();
+    merge_tables(&mut resolved, system.clone());
+    Ok(resolved)
+}
+
+/// Merge the overrides into the given table.
+///
+/// Nested tables are merged recursively while all other values, including arrays, are
+/// replaced.
+fn merge_tables(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(overrides)) => {
+                merge_tables(table, overrides);
+            }
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
 /// Load JSON file of type `T` from the provided path.
 pub fn load_json<T>(path: &Path) -> BakeryResult<T>
 where
@@ -0,0 +1,85 @@
+//! Overrides of configuration values provided on the command line.
+
+use std::collections::HashMap;
+
+use reportify::bail;
+
+use crate::BakeryResult;
+
+/// Overrides of configuration values.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    /// Overrides of values of the project configuration by their path.
+    pub config: Vec<(Vec<String>, toml::Value)>,
+    /// Overrides of recipe parameters by recipe and parameter name.
+    pub parameters: HashMap<String, HashMap<String, String>>,
+}
+
+impl Overrides {
+    /// Parse overrides of the form `KEY=VALUE`.
+    ///
+    /// Keys of the form `recipes.<recipe>.<parameter>` override recipe parameters and
+    /// keys of the form `systems.<system>.<path>` override values of system
+    /// configurations.
+    pub fn parse<S: AsRef<str>>(defines: &[S]) -> BakeryResult<Self> {
+        let mut overrides = Self::default();
+        for define in defines {
+            let define = define.as_ref();
+            let Some((key, value)) = define.split_once('=') else {
+                bail!("invalid override {define:?}, expected `KEY=VALUE`");
+            };
+            if let Some(parameter) = key.strip_prefix("recipes.") {
+                let Some((recipe, parameter)) = parameter.rsplit_once('.') else {
+                    bail!("invalid key {key:?}, expected `recipes.<recipe>.<parameter>`");
+                };
+                overrides
+                    .parameters
+                    .entry(recipe.to_owned())
+                    .or_default()
+                    .insert(parameter.to_owned(), value.to_owned());
+            } else if key.starts_with("systems.") {
+                let path = key.split('.').map(str::to_owned).collect::<Vec<_>>();
+                if path.len() < 3 || path.iter().any(String::is_empty) {
+                    bail!("invalid key {key:?}, expected `systems.<system>.<path>`");
+                }
+                overrides.config.push((path, parse_value(value)));
+            } else {
+                bail!("unable to override {key:?}, only `recipes.*` and `systems.*` are supported");
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Apply the overrides of configuration values to the given configuration.
+    pub fn apply(&self, config: &mut toml::Table) -> BakeryResult<()> {
+        for (path, value) in &self.config {
+            let (key, tables) = path.split_last().unwrap();
+            let mut table = &mut *config;
+            for name in tables {
+                let entry = table
+                    .entry(name.clone())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+                let toml::Value::Table(inner) = entry else {
+                    bail!(
+                        "unable to override {:?}, {name:?} is not a table",
+                        path.join(".")
+                    );
+                };
+                table = inner;
+            }
+            table.insert(key.clone(), value.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Parse a value as TOML, falling back to a string.
+///
+/// For instance, `true` and `42` are parsed as boolean and integer while `1.4.2` and
+/// `8GiB` are strings.
+fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_owned()))
+}
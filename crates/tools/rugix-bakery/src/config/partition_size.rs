@@ -0,0 +1,177 @@
+//! Size expressions for partitions of image layouts.
+//!
+//! A size expression is either a fixed size (e.g., `256MiB` or a number of bytes), a
+//! percentage of the image size (e.g., `25%`), or `rest` for the remaining space of the
+//! image. Optionally, the resulting size can be aligned to a multiple of a given size,
+//! e.g., `25% align 4MiB`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use byte_calc::NumBytes;
+
+use super::errors;
+
+/// Size of a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionSize {
+    /// Base size of the partition.
+    pub base: PartitionSizeBase,
+    /// Optional alignment of the size.
+    pub align: Option<NumBytes>,
+}
+
+impl PartitionSize {
+    /// Indicates whether the partition takes up the remaining space.
+    pub fn is_rest(&self) -> bool {
+        matches!(self.base, PartitionSizeBase::Rest)
+    }
+
+    /// Resolve the size given the size of the image.
+    ///
+    /// Returns [`None`] if a percentage is used but the image size is not known. For
+    /// `rest`, the provided remaining space is used and aligned downwards.
+    pub fn resolve(&self, image_size: Option<NumBytes>, remaining: NumBytes) -> Option<NumBytes> {
+        let size = match self.base {
+            PartitionSizeBase::Fixed(size) => size,
+            PartitionSizeBase::Percent(percent) => {
+                NumBytes::new(image_size?.raw / 100 * percent as u64)
+            }
+            PartitionSizeBase::Rest => {
+                return Some(match self.align {
+                    Some(align) if align.raw > 0 => {
+                        NumBytes::new(remaining.raw / align.raw * align.raw)
+                    }
+                    _ => remaining,
+                });
+            }
+        };
+        Some(match self.align {
+            Some(align) if align.raw > 0 => NumBytes::new(size.raw.div_ceil(align.raw) * align.raw),
+            _ => size,
+        })
+    }
+}
+
+impl From<NumBytes> for PartitionSize {
+    fn from(value: NumBytes) -> Self {
+        Self {
+            base: PartitionSizeBase::Fixed(value),
+            align: None,
+        }
+    }
+}
+
+/// Base size of a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionSizeBase {
+    /// Fixed size.
+    Fixed(NumBytes),
+    /// Percentage of the image size.
+    Percent(u8),
+    /// Remaining space of the image.
+    Rest,
+}
+
+impl fmt::Display for PartitionSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.base {
+            PartitionSizeBase::Fixed(size) => size.fmt(f)?,
+            PartitionSizeBase::Percent(percent) => write!(f, "{percent}%")?,
+            PartitionSizeBase::Rest => f.write_str("rest")?,
+        }
+        if let Some(align) = self.align {
+            write!(f, " align {align}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PartitionSize {
+    type Err = errors::InvalidPartitionSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, align) = match s.split_once(" align ") {
+            Some((base, align)) => (
+                base.trim(),
+                Some(
+                    NumBytes::parse_str(align.trim())
+                        .map_err(|_| errors::InvalidPartitionSizeError)?,
+                ),
+            ),
+            None => (s.trim(), None),
+        };
+        let base = if base == "rest" {
+            PartitionSizeBase::Rest
+        } else if let Some(percent) = base.strip_suffix('%') {
+            let percent = percent
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| errors::InvalidPartitionSizeError)?;
+            if percent > 100 {
+                return Err(errors::InvalidPartitionSizeError);
+            }
+            PartitionSizeBase::Percent(percent)
+        } else {
+            PartitionSizeBase::Fixed(
+                NumBytes::parse_str(base).map_err(|_| errors::InvalidPartitionSizeError)?,
+            )
+        };
+        Ok(Self { base, align })
+    }
+}
+
+impl serde::Serialize for PartitionSize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match (self.base, self.align) {
+            (PartitionSizeBase::Fixed(size), None) => serializer.serialize_u64(size.raw),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PartitionSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = PartitionSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("partition size")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &"partition size"))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(NumBytes::new(v).into())
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                u64::try_from(v)
+                    .map(|v| NumBytes::new(v).into())
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
@@ -0,0 +1,154 @@
+//! Proxying and caching of APT downloads while baking layers.
+
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fs, io};
+
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use reportify::{bail, ResultExt};
+use tracing::{info, warn};
+use xscript::{run, Run};
+
+use crate::project::ProjectRef;
+use crate::utils::build_env;
+use crate::BakeryResult;
+
+/// APT configuration file with the proxy in the root filesystem.
+const PROXY_CONF_FILE: &str = "etc/apt/apt.conf.d/99rugix-bakery-proxy";
+
+/// Port of the local `apt-cacher-ng` instance.
+const CACHE_PORT: u16 = 3142;
+
+/// APT proxy used while baking a layer.
+pub struct AptProxy {
+    url: String,
+    /// Local cache started for the proxy, if any.
+    _cache: Option<AptCache>,
+}
+
+impl AptProxy {
+    /// URL of the proxy.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+/// Determine the APT proxy of the project, starting the local cache if necessary.
+pub fn apt_proxy(project: &ProjectRef) -> BakeryResult<Option<AptProxy>> {
+    let Some(config) = &project.config().apt else {
+        return Ok(None);
+    };
+    if let Some(proxy) = &config.proxy {
+        return Ok(Some(AptProxy {
+            url: proxy.clone(),
+            _cache: None,
+        }));
+    }
+    if config.cache.unwrap_or(false) {
+        return Ok(Some(AptProxy {
+            url: format!("http://127.0.0.1:{CACHE_PORT}"),
+            _cache: start_cache()?,
+        }));
+    }
+    Ok(None)
+}
+
+/// Local `apt-cacher-ng` instance, which is stopped when dropped.
+struct AptCache {
+    pid_file: PathBuf,
+}
+
+impl Drop for AptCache {
+    fn drop(&mut self) {
+        info!("stopping `apt-cacher-ng`");
+        let pid = match fs::read_to_string(&self.pid_file) {
+            Ok(pid) => pid,
+            Err(error) => {
+                warn!("unable to read PID of `apt-cacher-ng`: {error}");
+                return;
+            }
+        };
+        let Ok(pid) = pid.trim().parse() else {
+            warn!("invalid PID of `apt-cacher-ng`: {pid:?}");
+            return;
+        };
+        if let Err(error) = kill(Pid::from_raw(pid), Signal::SIGTERM) {
+            warn!("unable to stop `apt-cacher-ng`: {error}");
+        }
+    }
+}
+
+/// Start the local `apt-cacher-ng` instance, if it is not running yet.
+///
+/// Returns `None`, if the instance has not been started by us.
+fn start_cache() -> BakeryResult<Option<AptCache>> {
+    if is_cache_running() {
+        return Ok(None);
+    }
+    let cache_dir = build_env::cache_dir().join("apt-cacher-ng");
+    let log_dir = cache_dir.join("log");
+    let pid_file = cache_dir.join("apt-cacher-ng.pid");
+    fs::create_dir_all(&log_dir).whatever("unable to create APT cache directory")?;
+    info!("starting `apt-cacher-ng` with cache in {cache_dir:?}");
+    // When offline, packages are only served from the cache.
+    let offline_mode = if build_env::is_offline() { "1" } else { "0" };
+    run!([
+        "apt-cacher-ng",
+        format!("CacheDir={}", cache_dir.display()),
+        format!("LogDir={}", log_dir.display()),
+        format!("Port={CACHE_PORT}"),
+        format!("Offlinemode={offline_mode}"),
+        format!("PidFile={}", pid_file.display()),
+        "BindAddress=127.0.0.1",
+        "ForeGround=0"
+    ])
+    .whatever("unable to start `apt-cacher-ng`")?;
+    let cache = AptCache { pid_file };
+    for _ in 0..50 {
+        if is_cache_running() {
+            return Ok(Some(cache));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    bail!("`apt-cacher-ng` did not start, see logs in {log_dir:?}")
+}
+
+/// Check whether the local `apt-cacher-ng` instance accepts connections.
+fn is_cache_running() -> bool {
+    TcpStream::connect(("127.0.0.1", CACHE_PORT)).is_ok()
+}
+
+/// Proxy configuration of APT in a root filesystem.
+///
+/// The configuration is removed when dropped such that it does not end up in the
+/// layer.
+pub struct ProxyConf {
+    path: PathBuf,
+}
+
+impl ProxyConf {
+    /// Configure APT in the given root filesystem to use the proxy.
+    ///
+    /// Returns `None`, if the system does not use APT.
+    pub fn install(root_dir: &Path, proxy: &str) -> BakeryResult<Option<Self>> {
+        let path = root_dir.join(PROXY_CONF_FILE);
+        if !path.parent().unwrap().is_dir() {
+            return Ok(None);
+        }
+        fs::write(&path, format!("Acquire::http::Proxy \"{proxy}\";\n"))
+            .whatever("unable to write APT proxy configuration")?;
+        Ok(Some(Self { path }))
+    }
+}
+
+impl Drop for ProxyConf {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.path) {
+            if error.kind() != io::ErrorKind::NotFound {
+                warn!("unable to remove APT proxy configuration: {error}");
+            }
+        }
+    }
+}
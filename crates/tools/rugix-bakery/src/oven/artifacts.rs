@@ -0,0 +1,185 @@
+//! Machine-readable manifest of the artifacts produced for a system.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use reportify::ResultExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::oven::budget::SIZES_FILE;
+use crate::oven::index::INDEX_FILE;
+use crate::oven::licenses::LICENSES_FILE;
+use crate::oven::provenance::PROVENANCE_FILE;
+use crate::oven::report::{REPORT_HTML_FILE, REPORT_JSON_FILE};
+use crate::oven::scan::VULNERABILITIES_FILE;
+use crate::oven::system::SystemInfo;
+use crate::BakeryResult;
+
+/// Name of the manifest file in the build directory of a system.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Files of the build directory which describe the build rather than being artifacts.
+const METADATA_FILES: &[&str] = &[
+    MANIFEST_FILE,
+    PROVENANCE_FILE,
+    REPORT_JSON_FILE,
+    REPORT_HTML_FILE,
+    SIZES_FILE,
+    INDEX_FILE,
+    LICENSES_FILE,
+    VULNERABILITIES_FILE,
+];
+
+/// Directories of the build directory containing intermediate files.
+const INTERMEDIATE_DIRS: &[&str] = &["filesystems"];
+
+/// Manifest of the artifacts produced for a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactManifest {
+    /// Name of the system.
+    pub system: String,
+    /// Release version of the system.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Git commit the system has been built from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Timestamp of the build (RFC 3339).
+    pub built_at: String,
+    /// Artifacts produced for the system.
+    pub artifacts: Vec<Artifact>,
+}
+
+/// Artifact produced for a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    /// Path of the artifact relative to the build directory of the system.
+    ///
+    /// For artifacts written to an explicit output path, this is the output path.
+    pub path: PathBuf,
+    /// Size of the artifact in bytes.
+    pub size: u64,
+    /// Hex-encoded SHA-256 hash of the artifact.
+    pub sha256: String,
+    /// Header hash of the artifact, if it is an update bundle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_hash: Option<String>,
+}
+
+/// Write the artifact manifest for the given system.
+///
+/// All files in the build directory of the system, except for intermediate files, and
+/// the given additional outputs are included in the manifest. The build time is the
+/// source date of the bake, such that the manifest is reproducible.
+pub fn write_manifest(
+    system: &str,
+    system_path: &Path,
+    outputs: &[&Path],
+    source_date_epoch: u64,
+) -> BakeryResult<ArtifactManifest> {
+    info!("writing artifact manifest");
+    let mut paths = Vec::new();
+    collect_files(system_path, system_path, &mut paths).whatever("unable to collect artifacts")?;
+    paths.sort();
+    // Pairs of the path in the manifest and the actual path of the artifact.
+    let mut files = paths
+        .into_iter()
+        .map(|path| (path.clone(), system_path.join(path)))
+        .collect::<Vec<_>>();
+    let system_dir = system_path.canonicalize().ok();
+    for output in outputs {
+        let is_inside = output
+            .canonicalize()
+            .ok()
+            .zip(system_dir.as_ref())
+            .is_some_and(|(output, system_dir)| output.starts_with(system_dir));
+        if !is_inside {
+            files.push((output.to_path_buf(), output.to_path_buf()));
+        }
+    }
+    let mut artifacts = Vec::new();
+    for (path, full_path) in files {
+        let size = fs::metadata(&full_path)
+            .whatever_with(|_| format!("unable to read metadata of {full_path:?}"))?
+            .len();
+        let sha256 =
+            sha256_file(&full_path).whatever_with(|_| format!("unable to hash {full_path:?}"))?;
+        let bundle_hash = if full_path.extension().is_some_and(|ext| ext == "rugixb") {
+            Some(
+                rugix_bundle::bundle_hash(&full_path)
+                    .whatever("unable to compute bundle hash")?
+                    .to_string(),
+            )
+        } else {
+            None
+        };
+        artifacts.push(Artifact {
+            path,
+            size,
+            sha256,
+            bundle_hash,
+        });
+    }
+    let release = fs::read_to_string(system_path.join("system-build-info.json"))
+        .ok()
+        .and_then(|info| serde_json::from_str::<SystemInfo>(&info).ok())
+        .map(|info| info.release);
+    let manifest = ArtifactManifest {
+        system: system.to_owned(),
+        version: release.as_ref().map(|release| release.version.clone()),
+        commit: release.and_then(|release| release.commit),
+        built_at: jiff::Timestamp::from_second(source_date_epoch as i64)
+            .whatever("invalid source date")?
+            .to_string(),
+        artifacts,
+    };
+    fs::write(
+        system_path.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .whatever("unable to write artifact manifest")?;
+    Ok(manifest)
+}
+
+/// Recursively collect the files in the given directory relative to the base directory.
+fn collect_files(base: &Path, dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path.strip_prefix(base).unwrap().to_path_buf();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if !INTERMEDIATE_DIRS
+                .iter()
+                .any(|dir| relative == Path::new(dir))
+            {
+                collect_files(base, &path, paths)?;
+            }
+        } else if file_type.is_file()
+            && !METADATA_FILES
+                .iter()
+                .any(|file| relative == Path::new(file))
+        {
+            paths.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Compute the hex-encoded SHA-256 hash of a file.
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break Ok(hex::encode(hasher.finalize()));
+        }
+        hasher.update(buffer);
+        let consumed = buffer.len();
+        reader.consume(consumed);
+    }
+}
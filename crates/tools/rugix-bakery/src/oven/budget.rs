@@ -0,0 +1,305 @@
+//! Size budgets of partitions and update bundles.
+//!
+//! When creating a system image, the sizes of the contents of its partitions are
+//! measured, including the largest paths and packages and how much they grew since the
+//! previous build. The budgets of a system are checked against these sizes and, if
+//! exceeded, the largest contributors to the growth are printed. Only sizes within the
+//! budgets are written to [`SIZES_FILE`] in the build directory, such that the growth
+//! is always reported relative to the last build within the budgets.
+
+use std::collections::{BTreeMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use byte_calc::NumBytes;
+use reportify::{bail, ResultExt};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::images::ImageLayout;
+use crate::config::load_json;
+use crate::config::systems::SystemConfig;
+use crate::oven::packages;
+use crate::BakeryResult;
+
+/// Name of the file with the sizes of the partitions in the build directory.
+pub const SIZES_FILE: &str = "sizes.json";
+
+/// Depth up to which the sizes of paths are recorded.
+const PATH_DEPTH: usize = 3;
+
+/// Minimal size of paths and packages to be recorded.
+const MIN_ENTRY_SIZE: u64 = 1024 * 1024;
+
+/// Number of contributors to print when a budget is exceeded.
+const TOP_CONTRIBUTORS: usize = 10;
+
+/// Sizes of the contents of the partitions of a system by their root directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemSizes {
+    /// Sizes of the partitions by their root directory.
+    pub partitions: BTreeMap<String, PartitionSizes>,
+}
+
+/// Sizes of the contents of a partition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionSizes {
+    /// Total size of the files in bytes.
+    pub size: u64,
+    /// Total size of the files in the previous build, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_size: Option<u64>,
+    /// Largest paths.
+    pub paths: Vec<SizeEntry>,
+    /// Largest packages installed with the package manager.
+    pub packages: Vec<SizeEntry>,
+}
+
+/// Size of a path or package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeEntry {
+    /// Path or name of the package.
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Growth in bytes since the previous build.
+    pub growth: i64,
+}
+
+/// Load the sizes recorded in the given build directory, if there are any.
+pub fn load_sizes(system_path: &Path) -> Option<SystemSizes> {
+    let path = system_path.join(SIZES_FILE);
+    if !path.exists() {
+        return None;
+    }
+    load_json(&path).ok()
+}
+
+/// Measure the contents of the partitions.
+pub fn measure_partitions(
+    layout: &ImageLayout,
+    roots_dir: &Path,
+    previous: Option<&SystemSizes>,
+) -> BakeryResult<SystemSizes> {
+    info!("measuring sizes of partitions");
+    let mut sizes = SystemSizes::default();
+    for partition in layout.partitions.iter().flatten() {
+        let Some(root) = &partition.root else {
+            continue;
+        };
+        let previous = previous.and_then(|previous| previous.partitions.get(root));
+        let root_dir = roots_dir.join(root);
+        let (size, paths) = measure_paths(&root_dir)
+            .whatever_with(|_| format!("unable to measure size of {root_dir:?}"))?;
+        let packages = packages::installed_packages(&root_dir)
+            .into_iter()
+            .map(|(name, package)| (name, package.size))
+            .collect();
+        sizes.partitions.insert(
+            root.clone(),
+            PartitionSizes {
+                size,
+                previous_size: previous.map(|previous| previous.size),
+                paths: size_entries(paths, previous.map(|previous| &previous.paths)),
+                packages: size_entries(packages, previous.map(|previous| &previous.packages)),
+            },
+        );
+    }
+    Ok(sizes)
+}
+
+/// Write the sizes of the partitions to the build directory.
+pub fn write_sizes(sizes: &SystemSizes, out: &Path) -> BakeryResult<()> {
+    fs::write(
+        out.join(SIZES_FILE),
+        serde_json::to_string_pretty(sizes).unwrap(),
+    )
+    .whatever("unable to write partition sizes")
+}
+
+/// Check the sizes of the partitions in the given build directory against the budgets.
+pub fn check_partitions(config: &SystemConfig, system_path: &Path) -> BakeryResult<()> {
+    if config
+        .budget
+        .as_ref()
+        .and_then(|budget| budget.partitions.as_ref())
+        .is_none()
+    {
+        return Ok(());
+    }
+    let Some(sizes) = load_sizes(system_path) else {
+        warn!("no partition sizes found, skipping budget check");
+        return Ok(());
+    };
+    check_sizes(config, &sizes)
+}
+
+/// Check the given sizes of the partitions against the budgets.
+pub fn check_sizes(config: &SystemConfig, sizes: &SystemSizes) -> BakeryResult<()> {
+    let Some(budgets) = config
+        .budget
+        .as_ref()
+        .and_then(|budget| budget.partitions.as_ref())
+    else {
+        return Ok(());
+    };
+    let mut exceeded = Vec::new();
+    for (root, budget) in budgets {
+        let Some(partition) = sizes.partitions.get(root) else {
+            bail!("budget for unknown partition root `{root}`");
+        };
+        if partition.size > budget.raw {
+            exceeded.push(format!(
+                "partition `{root}` has {} exceeding its budget of {budget}",
+                NumBytes::new(partition.size)
+            ));
+            print_breakdown(root, partition);
+        }
+    }
+    if !exceeded.is_empty() {
+        bail!("size budget exceeded: {}", exceeded.join(", "));
+    }
+    Ok(())
+}
+
+/// Check the size of the given update bundle against the budget.
+pub fn check_bundle(config: &SystemConfig, system_path: &Path, bundle: &Path) -> BakeryResult<()> {
+    let Some(budget) = config.budget.as_ref().and_then(|budget| budget.bundle) else {
+        return Ok(());
+    };
+    let size = fs::metadata(bundle)
+        .whatever("unable to read metadata of bundle")?
+        .len();
+    if size > budget.raw {
+        for (root, partition) in load_sizes(system_path).unwrap_or_default().partitions {
+            print_breakdown(&root, &partition);
+        }
+        bail!(
+            "size budget exceeded: bundle has {} exceeding its budget of {budget}",
+            NumBytes::new(size)
+        );
+    }
+    Ok(())
+}
+
+/// Print the largest contributors to the size of a partition.
+fn print_breakdown(root: &str, partition: &PartitionSizes) {
+    // Without a previous build, contributors are sorted by size instead of growth.
+    let has_previous = partition.previous_size.is_some();
+    rugix_cli::suspend(|| {
+        eprint!("Partition `{root}`: {}", NumBytes::new(partition.size));
+        if let Some(previous_size) = partition.previous_size {
+            eprint!(
+                " ({})",
+                format_growth(partition.size as i64 - previous_size as i64)
+            );
+        }
+        eprintln!();
+        for (kind, entries) in [
+            ("paths", &partition.paths),
+            ("packages", &partition.packages),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let mut entries = entries.iter().collect::<Vec<_>>();
+            if has_previous {
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.growth));
+                entries.retain(|entry| entry.growth > 0);
+                eprintln!("  Largest growth of {kind}:");
+            } else {
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.size));
+                eprintln!("  Largest {kind}:");
+            }
+            for entry in entries.iter().take(TOP_CONTRIBUTORS) {
+                eprintln!(
+                    "    {:>12}  {:>12}  {}",
+                    NumBytes::new(entry.size).to_string(),
+                    format_growth(entry.growth),
+                    entry.name
+                );
+            }
+        }
+    });
+}
+
+/// Format a growth in bytes with its sign.
+pub fn format_growth(growth: i64) -> String {
+    let bytes = NumBytes::new(growth.unsigned_abs());
+    if growth < 0 {
+        format!("-{bytes}")
+    } else {
+        format!("+{bytes}")
+    }
+}
+
+/// Turn the given sizes into entries, computing the growth based on previous entries.
+fn size_entries(sizes: BTreeMap<String, u64>, previous: Option<&Vec<SizeEntry>>) -> Vec<SizeEntry> {
+    let previous = previous
+        .into_iter()
+        .flatten()
+        .map(|entry| (entry.name.as_str(), entry.size))
+        .collect::<BTreeMap<_, _>>();
+    sizes
+        .into_iter()
+        .filter(|(_, size)| *size >= MIN_ENTRY_SIZE)
+        .map(|(name, size)| {
+            let growth = size as i64 - previous.get(name.as_str()).copied().unwrap_or(0) as i64;
+            SizeEntry { name, size, growth }
+        })
+        .collect()
+}
+
+/// Measure the total size of the files in a directory.
+pub(crate) fn measure_size(root: &Path) -> io::Result<u64> {
+    measure_paths(root).map(|(total, _)| total)
+}
+
+/// Measure the total size of the files in a directory and the sizes of its paths.
+///
+/// Files with multiple hard links are only counted once.
+fn measure_paths(root: &Path) -> io::Result<(u64, BTreeMap<String, u64>)> {
+    fn walk(
+        root: &Path,
+        dir: &Path,
+        seen: &mut HashSet<(u64, u64)>,
+        total: &mut u64,
+        paths: &mut BTreeMap<String, u64>,
+    ) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                walk(root, &entry.path(), seen, total, paths)?;
+                continue;
+            }
+            if metadata.nlink() > 1 && !seen.insert((metadata.dev(), metadata.ino())) {
+                continue;
+            }
+            let size = metadata.len();
+            *total += size;
+            // Attribute the size to all ancestors up to the maximal depth.
+            let path = entry.path();
+            let mut prefix = PathBuf::from("/");
+            for component in path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .take(PATH_DEPTH)
+            {
+                prefix.push(component);
+                *paths
+                    .entry(prefix.to_string_lossy().into_owned())
+                    .or_default() += size;
+            }
+        }
+        Ok(())
+    }
+    let mut total = 0;
+    let mut paths = BTreeMap::new();
+    if root.exists() {
+        walk(root, root, &mut HashSet::new(), &mut total, &mut paths)?;
+    }
+    Ok((total, paths))
+}
@@ -0,0 +1,85 @@
+//! Compilation caching with ccache for recipes enabling it.
+//!
+//! The cache lives in the global cache directory of the project, such that it persists
+//! across builds and is shared by all recipes of the project. Compilers are wrapped in
+//! two ways: via the compiler symlinks in `/usr/lib/ccache`, which are put first on the
+//! `PATH` and thereby also cover build systems hardcoding compiler names (e.g., the
+//! Linux kernel), and by prefixing the `CC` and `CXX` variables of toolchains.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use reportify::ResultExt;
+use tracing::warn;
+
+use crate::project::ProjectRef;
+use crate::utils::build_env;
+use crate::BakeryResult;
+
+/// Directory with the compiler symlinks of ccache.
+const CCACHE_LIB_DIR: &str = "/usr/lib/ccache";
+
+/// Default `PATH` of steps executed in the chroot.
+const CHROOT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Environment variables enabling ccache for steps executed in the build environment.
+pub fn host_vars(project: &ProjectRef) -> BakeryResult<Vec<(&'static str, OsString)>> {
+    let cache_dir = build_env::cache_dir()
+        .join(project.local_id().as_str())
+        .join("ccache");
+    fs::create_dir_all(&cache_dir).whatever("unable to create ccache directory")?;
+    let mut path = OsString::from(CCACHE_LIB_DIR);
+    if let Some(host_path) = std::env::var_os("PATH") {
+        path.push(":");
+        path.push(host_path);
+    }
+    Ok(vec![
+        ("CCACHE_DIR", cache_dir.into_os_string()),
+        // Paths within the project are hashed relative to it.
+        ("CCACHE_BASEDIR", project.dir().as_os_str().to_owned()),
+        ("PATH", path),
+    ])
+}
+
+/// Environment variables enabling ccache for steps executed in the chroot.
+///
+/// Returns no variables if ccache is not installed in the root directory.
+pub fn chroot_vars(
+    project: &ProjectRef,
+    root_dir: &Path,
+) -> BakeryResult<Vec<(&'static str, OsString)>> {
+    if !root_dir.join("usr/bin/ccache").exists() {
+        warn!("ccache is not installed in the system, install it with a `packages` step");
+        return Ok(Vec::new());
+    }
+    // The global cache directory is mounted into the chroot.
+    fs::create_dir_all(
+        build_env::cache_dir()
+            .join(project.local_id().as_str())
+            .join("ccache"),
+    )
+    .whatever("unable to create ccache directory")?;
+    Ok(vec![
+        (
+            "CCACHE_DIR",
+            Path::new("/run/rugix/bakery/build-env/cache")
+                .join(project.local_id().as_str())
+                .join("ccache")
+                .into_os_string(),
+        ),
+        ("CCACHE_BASEDIR", "/run/rugix/bakery/project".into()),
+        ("PATH", format!("{CCACHE_LIB_DIR}:{CHROOT_PATH}").into()),
+    ])
+}
+
+/// Wrap the compilers of the given toolchain variables with ccache.
+pub fn wrap_compilers(toolchain_vars: &mut [(&'static str, OsString)]) {
+    for (name, value) in toolchain_vars {
+        if matches!(*name, "CC" | "CXX") {
+            let mut wrapped = OsString::from("ccache ");
+            wrapped.push(&*value);
+            *value = wrapped;
+        }
+    }
+}
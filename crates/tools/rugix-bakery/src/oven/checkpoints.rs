@@ -0,0 +1,110 @@
+//! Checkpoints of layer builds after each recipe.
+//!
+//! After applying a recipe, the layer's build directory is saved as a checkpoint. The
+//! checkpoint is identified by a key chaining the parent layer with all recipes applied
+//! so far, including their parameters and modification times. Hence, when the layer
+//! needs to be rebuilt, the build resumes after the last recipe whose key is unchanged.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reportify::ResultExt;
+use tracing::info;
+use xscript::{run, Run};
+
+use crate::oven::customize::RecipeJob;
+use crate::project::ProjectRef;
+use crate::utils::caching::{mtime, Hasher, ModificationTime};
+use crate::BakeryResult;
+
+/// Directory of the checkpoints in the output directory of a layer.
+const CHECKPOINTS_DIR: &str = "checkpoints";
+
+/// Checkpoints of the recipes applied to a layer.
+#[derive(Debug)]
+pub struct Checkpoints {
+    /// Directory with the checkpoints.
+    dir: PathBuf,
+    /// Keys of the checkpoints after each recipe.
+    keys: Vec<String>,
+}
+
+impl Checkpoints {
+    /// Compute the checkpoints of the given recipe jobs.
+    ///
+    /// The modification time of the parent layer is given by `src_modified`.
+    pub fn new(
+        project: &ProjectRef,
+        layer_path: &Path,
+        src_modified: Option<ModificationTime>,
+        jobs: &[RecipeJob],
+    ) -> Self {
+        let mut hasher = Hasher::new();
+        hasher.push("parent", format!("{src_modified:?}"));
+        let mut key = hasher.finalize();
+        let mut keys = Vec::new();
+        for job in jobs {
+            let mut hasher = Hasher::new();
+            hasher.push("previous", &key);
+            hasher.push("recipe", job.recipe.name.as_bytes());
+            hasher.push("modified", format!("{:?}", job.recipe.modified));
+            let mut parameters = job.parameters.iter().collect::<Vec<_>>();
+            parameters.sort();
+            for (name, value) in parameters {
+                hasher.push("parameter", format!("{name}={value}"));
+            }
+            key = hasher.finalize();
+            keys.push(key.clone());
+        }
+        Self {
+            dir: project.dir().join(layer_path).join(CHECKPOINTS_DIR),
+            keys,
+        }
+    }
+
+    /// Find the latest valid checkpoint.
+    ///
+    /// Checkpoints saved before any of the files used by the recipes last changed, as
+    /// given by `used_modified`, are invalid. Returns the number of recipes applied in
+    /// the checkpoint and its path.
+    pub fn latest(&self, used_modified: Option<ModificationTime>) -> Option<(usize, PathBuf)> {
+        (0..self.keys.len())
+            .rev()
+            .map(|idx| (idx + 1, self.path(idx)))
+            .find(|(_, path)| match mtime(path) {
+                Ok(modified) => !used_modified.is_some_and(|used| used >= modified),
+                Err(_) => false,
+            })
+    }
+
+    /// Save the build directory as checkpoint after applying the recipe with the index.
+    pub fn save(&self, idx: usize, build_dir: &Path) -> BakeryResult<()> {
+        fs::create_dir_all(&self.dir).whatever("unable to create checkpoints directory")?;
+        let path = self.path(idx);
+        let partial = path.with_extension("partial");
+        info!("saving checkpoint");
+        run!(["tar", "-c", "-f", &partial, "-C", build_dir, "."])
+            .whatever("unable to save checkpoint")?;
+        fs::rename(&partial, &path).whatever("unable to save checkpoint")
+    }
+
+    /// Remove all checkpoints which are no longer valid.
+    pub fn prune(&self) -> BakeryResult<()> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(());
+        };
+        for entry in entries {
+            let entry = entry.whatever("unable to read checkpoints directory")?;
+            let is_valid = (0..self.keys.len()).any(|idx| self.path(idx) == entry.path());
+            if !is_valid {
+                fs::remove_file(entry.path()).whatever("unable to remove checkpoint")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the checkpoint after applying the recipe with the given index.
+    fn path(&self, idx: usize) -> PathBuf {
+        self.dir.join(format!("{}.tar", self.keys[idx]))
+    }
+}
@@ -0,0 +1,290 @@
+//! Cleanup of the root filesystem of a layer after applying its recipes.
+//!
+//! The cleanup removes files which are not needed at runtime, e.g., documentation,
+//! translations, and caches. The space saved by each action is measured and written to
+//! [`CLEANUP_FILE`] in the output directory of the layer.
+
+use std::fs;
+use std::path::{Component, Path};
+
+use byte_calc::NumBytes;
+use reportify::{bail, ResultExt};
+use serde::Serialize;
+use tracing::info;
+
+use crate::config::layers::{CleanupConfig, CleanupProfile};
+use crate::oven::budget;
+use crate::BakeryResult;
+
+/// Name of the cleanup report in the output directory of a layer.
+pub const CLEANUP_FILE: &str = "cleanup.json";
+
+/// Directories with documentation.
+const DOCS_DIRS: &[&str] = &["usr/share/doc", "usr/share/man", "usr/share/info"];
+
+/// Directory with translations.
+const LOCALES_DIR: &str = "usr/share/locale";
+
+/// Directory with the package lists of APT.
+const APT_LISTS_DIR: &str = "var/lib/apt/lists";
+
+/// Caches which are rebuilt on demand.
+const CACHE_PATHS: &[&str] = &[
+    "var/cache/apt/pkgcache.bin",
+    "var/cache/apt/srcpkgcache.bin",
+    "var/cache/debconf/config.dat-old",
+    "var/cache/debconf/templates.dat-old",
+    "var/cache/ldconfig/aux-cache",
+    "var/cache/man",
+    "root/.cache",
+];
+
+/// Directories with temporary files.
+const TEMP_DIRS: &[&str] = &["tmp", "var/tmp"];
+
+/// Directory with log files.
+const LOGS_DIR: &str = "var/log";
+
+/// Cleanup options with the defaults of the profile applied.
+#[derive(Debug, Clone)]
+pub struct CleanupOptions {
+    pub docs: bool,
+    pub locales: bool,
+    pub keep_locales: Vec<String>,
+    pub apt_lists: bool,
+    pub caches: bool,
+    pub logs: bool,
+    pub remove_packages: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+impl CleanupOptions {
+    /// Resolve the options of the given configuration.
+    pub fn from_config(config: &CleanupConfig) -> Self {
+        let (minimal, production) = match config.profile {
+            None => (false, false),
+            Some(CleanupProfile::Minimal) => (true, false),
+            Some(CleanupProfile::Production) => (true, true),
+        };
+        Self {
+            docs: config.docs.unwrap_or(production),
+            locales: config.locales.unwrap_or(production),
+            keep_locales: config
+                .keep_locales
+                .clone()
+                .unwrap_or_else(|| vec!["en".to_owned()]),
+            apt_lists: config.apt_lists.unwrap_or(minimal),
+            caches: config.caches.unwrap_or(minimal),
+            logs: config.logs.unwrap_or(production),
+            remove_packages: config.remove_packages.clone().unwrap_or_default(),
+            paths: config.paths.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Report of the space saved by the cleanup of a layer.
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    /// Size of the root filesystem before the cleanup in bytes.
+    pub size_before: u64,
+    /// Size of the root filesystem after the cleanup in bytes.
+    pub size_after: u64,
+    /// Actions in the order in which they have been applied.
+    pub actions: Vec<CleanupActionReport>,
+}
+
+/// Space saved by a cleanup action.
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupActionReport {
+    /// Name of the action.
+    pub name: String,
+    /// Saved space in bytes.
+    pub saved: u64,
+}
+
+impl CleanupReport {
+    /// Start a report by measuring the size of the root filesystem.
+    pub fn new(root_dir: &Path) -> BakeryResult<Self> {
+        let size = measure(root_dir)?;
+        Ok(Self {
+            size_before: size,
+            size_after: size,
+            actions: Vec::new(),
+        })
+    }
+
+    /// Record the space saved by the action which has just been applied.
+    pub fn record(&mut self, name: &str, root_dir: &Path) -> BakeryResult<()> {
+        let size = measure(root_dir)?;
+        let saved = self.size_after.saturating_sub(size);
+        info!("cleanup: {name} saved {}", NumBytes::new(saved));
+        self.actions.push(CleanupActionReport {
+            name: name.to_owned(),
+            saved,
+        });
+        self.size_after = size;
+        Ok(())
+    }
+
+    /// Total space saved by the cleanup.
+    pub fn saved(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+
+    /// Write the report to the output directory of the layer.
+    pub fn write(&self, output_dir: &Path) -> BakeryResult<()> {
+        info!(
+            "cleanup saved {} in total, from {} to {}",
+            NumBytes::new(self.saved()),
+            NumBytes::new(self.size_before),
+            NumBytes::new(self.size_after),
+        );
+        fs::write(
+            output_dir.join(CLEANUP_FILE),
+            serde_json::to_string_pretty(self).unwrap(),
+        )
+        .whatever("unable to write cleanup report")
+    }
+}
+
+/// Measure the size of the root filesystem.
+fn measure(root_dir: &Path) -> BakeryResult<u64> {
+    budget::measure_size(root_dir).whatever("unable to measure size of root filesystem")
+}
+
+/// Remove documentation, keeping the copyright files of packages.
+pub fn remove_docs(root_dir: &Path) -> BakeryResult<()> {
+    for dir in DOCS_DIRS {
+        let dir = root_dir.join(dir);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry.whatever("unable to read directory entry")?.path();
+            if path.join("copyright").is_file() {
+                // Copyright files are required by licenses and the license report.
+                for entry in fs::read_dir(&path).whatever("unable to read directory")? {
+                    let path = entry.whatever("unable to read directory entry")?.path();
+                    if path.file_name().is_some_and(|name| name != "copyright") {
+                        remove_path(&path)?;
+                    }
+                }
+            } else {
+                remove_path(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove translations except for the locales to keep.
+pub fn remove_locales(root_dir: &Path, keep: &[String]) -> BakeryResult<()> {
+    let Ok(entries) = fs::read_dir(root_dir.join(LOCALES_DIR)) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry.whatever("unable to read directory entry")?;
+        if !entry.file_type().is_ok_and(|ty| ty.is_dir()) {
+            // Keep files like `locale.alias`.
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !keep.iter().any(|locale| is_locale_kept(&name, locale)) {
+            remove_path(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Check whether a locale directory belongs to a locale to keep.
+///
+/// Keeping `en` keeps, e.g., `en_US`, `en_GB`, and `en@quot`.
+fn is_locale_kept(name: &str, keep: &str) -> bool {
+    name.strip_prefix(keep)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['_', '.', '@']))
+}
+
+/// Remove the package lists of APT.
+pub fn remove_apt_lists(root_dir: &Path) -> BakeryResult<()> {
+    let Ok(entries) = fs::read_dir(root_dir.join(APT_LISTS_DIR)) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry.whatever("unable to read directory entry")?;
+        if entry.file_type().is_ok_and(|ty| ty.is_file()) && entry.file_name() != "lock" {
+            remove_path(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove caches and temporary files.
+pub fn remove_caches(root_dir: &Path) -> BakeryResult<()> {
+    for path in CACHE_PATHS {
+        remove_path(&root_dir.join(path))?;
+    }
+    for dir in TEMP_DIRS {
+        remove_contents(&root_dir.join(dir))?;
+    }
+    Ok(())
+}
+
+/// Remove log files, keeping the directory structure for the services writing them.
+pub fn remove_logs(root_dir: &Path) -> BakeryResult<()> {
+    fn walk(dir: &Path) -> BakeryResult<()> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(());
+        };
+        for entry in entries {
+            let entry = entry.whatever("unable to read directory entry")?;
+            if entry.file_type().is_ok_and(|ty| ty.is_dir()) {
+                walk(&entry.path())?;
+            } else {
+                remove_path(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+    walk(&root_dir.join(LOGS_DIR))
+}
+
+/// Remove additional paths given relative to the root filesystem.
+pub fn remove_paths(root_dir: &Path, paths: &[String]) -> BakeryResult<()> {
+    for path in paths {
+        let relative = Path::new(path.trim_start_matches('/'));
+        if relative.as_os_str().is_empty()
+            || relative
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            bail!("invalid cleanup path {path:?}");
+        }
+        remove_path(&root_dir.join(relative))?;
+    }
+    Ok(())
+}
+
+/// Remove the entries of a directory, keeping the directory itself.
+fn remove_contents(dir: &Path) -> BakeryResult<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        remove_path(&entry.whatever("unable to read directory entry")?.path())?;
+    }
+    Ok(())
+}
+
+/// Remove a file or directory, if it exists.
+fn remove_path(path: &Path) -> BakeryResult<()> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .whatever_with(|_| format!("unable to remove {path:?}"))
+}
@@ -0,0 +1,144 @@
+//! Embedding of cloud-init NoCloud seeds into images.
+
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+
+use crate::config::systems::CloudInitConfig;
+use crate::project::ProjectRef;
+use crate::BakeryResult;
+
+/// Directory of the seed on the config partition.
+const SEED_DIR: &str = "cidata";
+
+/// Configuration of cloud-init pointing the NoCloud datasource to the seed.
+const NOCLOUD_CONFIG: &str = r#"# Generated by Rugix Bakery.
+datasource_list: [ NoCloud, None ]
+datasource:
+  NoCloud:
+    seedfrom: file:///run/rugix/mounts/config/cidata/
+"#;
+
+/// Write the NoCloud seed to the config partition and configure cloud-init to use it.
+///
+/// The seed is placed on the config partition such that it can be edited after
+/// flashing the image, e.g., to personalize a generic image.
+pub fn write_seed(
+    project: &ProjectRef,
+    config: &CloudInitConfig,
+    vars: &[(&str, &str)],
+    config_dir: &Path,
+    system_dir: &Path,
+) -> BakeryResult<()> {
+    info!("writing cloud-init NoCloud seed");
+    let seed_dir = config_dir.join(SEED_DIR);
+    fs::create_dir_all(&seed_dir).whatever("unable to create seed directory")?;
+    let template = config.template.unwrap_or(false);
+    let mut vars = vars.to_vec();
+    for (name, value) in config.variables.iter().flatten() {
+        vars.push((name.as_str(), value.as_str()));
+    }
+    for (name, path, default) in [
+        ("user-data", &config.user_data, Some("#cloud-config\n")),
+        ("meta-data", &config.meta_data, None),
+        ("network-config", &config.network_config, None),
+    ] {
+        let content = match path {
+            Some(path) => {
+                let content = fs::read_to_string(project.dir().join(path))
+                    .whatever_with(|_| format!("unable to read cloud-init {name} {path:?}"))?;
+                if template {
+                    substitute(&content, &vars)
+                        .whatever_with(|_| format!("unable to substitute cloud-init {name}"))?
+                } else {
+                    content
+                }
+            }
+            None if name == "meta-data" => {
+                // The `instance-id` is required by cloud-init.
+                let instance_id = vars
+                    .iter()
+                    .find(|(var, _)| *var == "RUGIX_RELEASE_ID")
+                    .map(|(_, value)| *value)
+                    .unwrap_or("rugix");
+                format!("instance-id: {instance_id}\n")
+            }
+            None => match default {
+                Some(default) => default.to_owned(),
+                None => continue,
+            },
+        };
+        fs::write(seed_dir.join(name), content)
+            .whatever_with(|_| format!("unable to write cloud-init {name}"))?;
+    }
+    let cloud_cfg_dir = system_dir.join("etc/cloud/cloud.cfg.d");
+    fs::create_dir_all(&cloud_cfg_dir).whatever("unable to create cloud-init config directory")?;
+    fs::write(cloud_cfg_dir.join("99-rugix-nocloud.cfg"), NOCLOUD_CONFIG)
+        .whatever("unable to write cloud-init config")?;
+    Ok(())
+}
+
+/// Substitute `${VAR}` placeholders with the given variables.
+///
+/// A placeholder can be escaped as `$${VAR}`, which is replaced by a literal `${VAR}`.
+/// Undefined variables are an error.
+fn substitute(template: &str, vars: &[(&str, &str)]) -> BakeryResult<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            bail!("unterminated placeholder in cloud-init template");
+        };
+        let name = &rest[start + 2..start + end];
+        let Some((_, value)) = vars.iter().find(|(var, _)| *var == name) else {
+            bail!("undefined variable `{name}` in cloud-init template");
+        };
+        output.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARS: &[(&str, &str)] = &[("RUGIX_SYSTEM_NAME", "customized"), ("HOST", "device")];
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(
+            substitute("hostname: ${HOST}-${RUGIX_SYSTEM_NAME}\n", VARS).unwrap(),
+            "hostname: device-customized\n"
+        );
+        assert_eq!(
+            substitute("no placeholders", VARS).unwrap(),
+            "no placeholders"
+        );
+    }
+
+    #[test]
+    fn test_substitute_escape() {
+        assert_eq!(
+            substitute("echo $${HOME} ${HOST}", VARS).unwrap(),
+            "echo ${HOME} device"
+        );
+    }
+
+    #[test]
+    fn test_substitute_errors() {
+        // Variables of the environment must not leak into the seed.
+        assert!(substitute("${PATH}", VARS).is_err());
+        assert!(substitute("${HOST", VARS).is_err());
+    }
+}
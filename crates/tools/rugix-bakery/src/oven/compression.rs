@@ -0,0 +1,81 @@
+//! Compression of final images.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+use xscript::{cmd_os, read_str, LocalEnv, ParentEnv, Run};
+
+use crate::config::systems::{CompressionFormat, ImageCompression};
+use crate::BakeryResult;
+
+impl CompressionFormat {
+    /// File extension of files compressed with this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            CompressionFormat::Xz => "xz",
+            CompressionFormat::Zstd => "zst",
+            CompressionFormat::Gzip => "gz",
+        }
+    }
+}
+
+/// Compress the given image and write a SHA256 checksum file next to it.
+///
+/// The original image is kept. Returns the path of the compressed image.
+pub fn compress_image(image: &Path, compression: &ImageCompression) -> BakeryResult<PathBuf> {
+    let format = compression.format;
+    let mut compressed = image.as_os_str().to_owned();
+    compressed.push(".");
+    compressed.push(format.extension());
+    let compressed = PathBuf::from(compressed);
+    info!("compressing image {image:?} with {}", format.extension());
+    let mut cmd = match format {
+        CompressionFormat::Xz => cmd_os!("xz", "-k", "-f"),
+        CompressionFormat::Zstd => cmd_os!("zstd", "-k", "-f", "-q"),
+        CompressionFormat::Gzip => cmd_os!("gzip", "-k", "-f"),
+    };
+    if let Some(level) = compression.level {
+        if format == CompressionFormat::Zstd && level > 19 {
+            cmd.add_arg("--ultra");
+        }
+        cmd.add_arg(format!("-{level}"));
+    }
+    if let Some(threads) = compression.threads {
+        match format {
+            CompressionFormat::Xz | CompressionFormat::Zstd => {
+                cmd.add_arg(format!("-T{threads}"));
+            }
+            // Gzip does not support multi-threaded compression.
+            CompressionFormat::Gzip => {}
+        }
+    }
+    cmd.add_arg(image);
+    if format == CompressionFormat::Zstd {
+        cmd.add_arg("-o");
+        cmd.add_arg(&compressed);
+    }
+    ParentEnv.run(cmd).whatever("unable to compress image")?;
+    write_checksum(&compressed)?;
+    Ok(compressed)
+}
+
+/// Write a SHA256 checksum file in the format of `sha256sum` next to the given file.
+fn write_checksum(path: &Path) -> BakeryResult<()> {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        bail!("invalid path {path:?}");
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let env = LocalEnv::new(dir);
+    let checksum =
+        read_str!(env, ["sha256sum", file_name]).whatever("unable to compute checksum of image")?;
+    let mut checksum_path = path.as_os_str().to_owned();
+    checksum_path.push(".sha256");
+    fs::write(checksum_path, format!("{checksum}\n")).whatever("unable to write checksum file")?;
+    Ok(())
+}
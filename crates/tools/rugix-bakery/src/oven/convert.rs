@@ -0,0 +1,208 @@
+//! Conversion of raw system images into other image formats.
+
+use std::fs;
+use std::path::Path;
+
+use reportify::ResultExt;
+use rugix_common::fsutils::copy_file_sparse;
+use tempfile::tempdir;
+use tracing::info;
+use xscript::{run, Run};
+
+use crate::config::systems::{ImageFormat, SystemConfig, Target};
+use crate::BakeryResult;
+
+/// Convert the raw image at the given path into the given format.
+pub fn convert_image(
+    config: &SystemConfig,
+    system_name: &str,
+    image: &Path,
+    format: ImageFormat,
+    output: &Path,
+) -> BakeryResult<()> {
+    info!("converting image to {format}");
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    match format {
+        ImageFormat::Raw => {
+            copy_file_sparse(image, output).whatever("error copying image")?;
+        }
+        ImageFormat::Qcow2 => {
+            run!(["qemu-img", "convert", "-f", "raw", "-O", "qcow2", image, output])
+                .whatever("unable to convert image to qcow2")?;
+        }
+        ImageFormat::Vhdx => {
+            run!([
+                "qemu-img",
+                "convert",
+                "-f",
+                "raw",
+                "-O",
+                "vhdx",
+                "-o",
+                "subformat=dynamic",
+                image,
+                output
+            ])
+            .whatever("unable to convert image to VHDX")?;
+        }
+        ImageFormat::Vmdk => {
+            run!([
+                "qemu-img",
+                "convert",
+                "-f",
+                "raw",
+                "-O",
+                "vmdk",
+                "-o",
+                "subformat=streamOptimized",
+                image,
+                output
+            ])
+            .whatever("unable to convert image to VMDK")?;
+        }
+        ImageFormat::Ova => {
+            let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+            let temp_dir_path = temp_dir.path();
+            let disk_name = format!("{system_name}-disk1.vmdk");
+            let disk_path = temp_dir_path.join(&disk_name);
+            convert_image(config, system_name, image, ImageFormat::Vmdk, &disk_path)?;
+            let capacity = fs::metadata(image)
+                .whatever("unable to read image metadata")?
+                .len();
+            let disk_size = fs::metadata(&disk_path)
+                .whatever("unable to read disk metadata")?
+                .len();
+            let ovf_name = format!("{system_name}.ovf");
+            fs::write(
+                temp_dir_path.join(&ovf_name),
+                ovf_descriptor(config, system_name, &disk_name, disk_size, capacity),
+            )
+            .whatever("unable to write OVF descriptor")?;
+            // The OVF descriptor must be the first file in the archive.
+            run!([
+                "tar",
+                "--format=ustar",
+                "-cf",
+                output,
+                "-C",
+                temp_dir_path,
+                ovf_name,
+                disk_name
+            ])
+            .whatever("unable to create OVA archive")?;
+        }
+    }
+    Ok(())
+}
+
+/// Generate a minimal OVF descriptor for a virtual machine with a single disk.
+fn ovf_descriptor(
+    config: &SystemConfig,
+    system_name: &str,
+    disk_name: &str,
+    disk_size: u64,
+    capacity: u64,
+) -> String {
+    let firmware = match config.target {
+        Some(Target::GenericGrubEfi) => "efi",
+        _ => "bios",
+    };
+    let system_name = escape_xml(system_name);
+    let disk_name = escape_xml(disk_name);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Envelope xmlns="http://schemas.dmtf.org/ovf/envelope/1"
+          xmlns:ovf="http://schemas.dmtf.org/ovf/envelope/1"
+          xmlns:rasd="http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_ResourceAllocationSettingData"
+          xmlns:vssd="http://schemas.dmtf.org/wbem/wscim/1/cim-schema/2/CIM_VirtualSystemSettingData"
+          xmlns:vmw="http://www.vmware.com/schema/ovf">
+  <References>
+    <File ovf:id="file1" ovf:href="{disk_name}" ovf:size="{disk_size}"/>
+  </References>
+  <DiskSection>
+    <Info>Virtual disks</Info>
+    <Disk ovf:diskId="vmdisk1" ovf:fileRef="file1" ovf:capacity="{capacity}" ovf:format="http://www.vmware.com/interfaces/specifications/vmdk.html#streamOptimized"/>
+  </DiskSection>
+  <NetworkSection>
+    <Info>Logical networks</Info>
+    <Network ovf:name="nat">
+      <Description>NAT network</Description>
+    </Network>
+  </NetworkSection>
+  <VirtualSystem ovf:id="{system_name}">
+    <Info>Rugix system {system_name}</Info>
+    <Name>{system_name}</Name>
+    <OperatingSystemSection ovf:id="101">
+      <Info>Guest operating system</Info>
+      <Description>Linux</Description>
+    </OperatingSystemSection>
+    <VirtualHardwareSection>
+      <Info>Virtual hardware requirements</Info>
+      <System>
+        <vssd:ElementName>Virtual Hardware Family</vssd:ElementName>
+        <vssd:InstanceID>0</vssd:InstanceID>
+        <vssd:VirtualSystemIdentifier>{system_name}</vssd:VirtualSystemIdentifier>
+        <vssd:VirtualSystemType>vmx-14</vssd:VirtualSystemType>
+      </System>
+      <Item>
+        <rasd:AllocationUnits>hertz * 10^6</rasd:AllocationUnits>
+        <rasd:ElementName>2 virtual CPUs</rasd:ElementName>
+        <rasd:InstanceID>1</rasd:InstanceID>
+        <rasd:ResourceType>3</rasd:ResourceType>
+        <rasd:VirtualQuantity>2</rasd:VirtualQuantity>
+      </Item>
+      <Item>
+        <rasd:AllocationUnits>byte * 2^20</rasd:AllocationUnits>
+        <rasd:ElementName>2048MB of memory</rasd:ElementName>
+        <rasd:InstanceID>2</rasd:InstanceID>
+        <rasd:ResourceType>4</rasd:ResourceType>
+        <rasd:VirtualQuantity>2048</rasd:VirtualQuantity>
+      </Item>
+      <Item>
+        <rasd:Address>0</rasd:Address>
+        <rasd:ElementName>SATA Controller</rasd:ElementName>
+        <rasd:InstanceID>3</rasd:InstanceID>
+        <rasd:ResourceSubType>vmware.sata.ahci</rasd:ResourceSubType>
+        <rasd:ResourceType>20</rasd:ResourceType>
+      </Item>
+      <Item>
+        <rasd:AddressOnParent>0</rasd:AddressOnParent>
+        <rasd:ElementName>Hard Disk 1</rasd:ElementName>
+        <rasd:HostResource>ovf:/disk/vmdisk1</rasd:HostResource>
+        <rasd:InstanceID>4</rasd:InstanceID>
+        <rasd:Parent>3</rasd:Parent>
+        <rasd:ResourceType>17</rasd:ResourceType>
+      </Item>
+      <Item>
+        <rasd:AutomaticAllocation>true</rasd:AutomaticAllocation>
+        <rasd:Connection>nat</rasd:Connection>
+        <rasd:ElementName>Ethernet 1</rasd:ElementName>
+        <rasd:InstanceID>5</rasd:InstanceID>
+        <rasd:ResourceSubType>E1000</rasd:ResourceSubType>
+        <rasd:ResourceType>10</rasd:ResourceType>
+      </Item>
+      <vmw:Config ovf:required="false" vmw:key="firmware" vmw:value="{firmware}"/>
+    </VirtualHardwareSection>
+  </VirtualSystem>
+</Envelope>
+"#
+    )
+}
+
+/// Escape a string for use in XML text and attribute values.
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
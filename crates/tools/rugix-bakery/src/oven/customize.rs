@@ -1,33 +1,64 @@
 //! Applies a set of recipes to a system.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsString;
 use std::fs;
 use std::io::{Read, Write};
 use std::ops::Deref;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use reportify::{bail, ResultExt};
 use rugix_cli::{cli_msg, StatusSegmentRef};
 use rugix_common::mount::{MountStack, Mounted};
-use tempfile::tempdir;
-use tracing::{error, info};
-use xscript::{cmd, run, vars, Cmd, ParentEnv, Run};
+use tempfile::{tempdir, TempDir};
+use tracing::{error, info, warn};
+use xscript::{cmd_os, run, vars, Cmd, ParentEnv, Run, Vars};
 
 use crate::cli::status::CliLog;
-use crate::config::layers::LayerConfig;
+use crate::config::layers::{CleanupConfig, LayerConfig};
 use crate::config::systems::Architecture;
+use crate::oven::apt::{self, ProxyConf};
+use crate::oven::checkpoints::Checkpoints;
+use crate::oven::cleanup::{self, CleanupOptions, CleanupReport};
+use crate::oven::emulation::ensure_emulation;
 use crate::oven::layer::LayerContext;
+use crate::oven::layer_cache::{self, CachedLayer};
+use crate::oven::report::{RecipeReport, StepReport};
+use crate::oven::templates::TemplateContext;
+use crate::oven::{ccache, files, network, rust, templates, toolchain, users};
 use crate::project::layers::Layer;
 use crate::project::library::Library;
 use crate::project::recipes::{PackageManager, Recipe, StepKind};
 use crate::project::repositories::RepositoryIdx;
 use crate::project::ProjectRef;
-use crate::utils::caching::{mtime, mtime_recursive};
+use crate::utils::build_env::{self, Isolation};
+use crate::utils::caching::{mtime, mtime_recursive, Hasher, ModificationTime};
 use crate::BakeryResult;
 
+/// File recording the recipe parameters a layer has been built with.
+const PARAMETERS_FILE: &str = "parameters.json";
+
+/// File recording the inputs of templates a layer has been built with.
+const TEMPLATE_INPUTS_FILE: &str = "template-inputs.json";
+
+/// File recording the key in the shared layer cache a layer has been built with.
+///
+/// As the key is derived from the inputs of the layer, it identifies the contents of
+/// the layer independently of any modification times.
+pub(crate) const CACHE_KEY_FILE: &str = "cache-key";
+
+/// File recording the hash of the contents of the files used by the recipes of a layer.
+///
+/// In contrast to modification times, the hash is meaningful on other machines.
+const USED_FILES_KEY_FILE: &str = "used-files-key";
+
+/// Directory of the first-boot scripts executed by Rugix Ctrl.
+const FIRST_BOOT_SCRIPTS_DIR: &str = "usr/lib/rugix/first-boot";
+
 struct Logger {
     cli_log: StatusSegmentRef<CliLog>,
     state: Mutex<LoggerState>,
@@ -39,9 +70,8 @@ struct LoggerState {
 }
 
 impl Logger {
-    pub fn new(layer_name: &str, layer_path: &Path) -> BakeryResult<Self> {
-        let log_file = fs::File::create(layer_path.join("build.log"))
-            .whatever("error creating layer log file")?;
+    pub fn new(layer_name: &str, log_path: &Path) -> BakeryResult<Self> {
+        let log_file = fs::File::create(log_path).whatever("error creating layer log file")?;
         Ok(Self {
             cli_log: rugix_cli::add_status(CliLog::new(format!("Layer: {layer_name}"))),
             state: Mutex::new(LoggerState {
@@ -76,6 +106,10 @@ impl Logger {
     }
 }
 
+/// Customize a layer by applying its recipes.
+///
+/// Returns the reports of the applied recipes or `None`, if the layer is up-to-date.
+#[allow(clippy::too_many_arguments)]
 pub fn customize(
     project: &ProjectRef,
     arch: Architecture,
@@ -83,8 +117,10 @@ pub fn customize(
     src: Option<&Path>,
     target: &Path,
     layer_path: &Path,
+    cache_key: &str,
+    cached: Option<&CachedLayer>,
     source_date_epoch: u64,
-) -> BakeryResult<()> {
+) -> BakeryResult<Option<Vec<RecipeReport>>> {
     let library = project.library()?;
     // Collect the recipes to apply.
     let config = layer.config(arch).unwrap();
@@ -92,63 +128,71 @@ pub fn customize(
     if jobs.is_empty() {
         bail!("layer must have recipes")
     }
-    let mut last_modified = jobs
-        .iter()
-        .map(|job| job.recipe.modified)
-        .max()
-        .unwrap()
-        .max(layer.modified);
-    if let Some(src) = src {
-        last_modified = last_modified.max(mtime(src).whatever("unable to determine mtime")?);
+    let src_modified = src
+        .map(|src| mtime(src).whatever("unable to determine mtime"))
+        .transpose()?;
+    let reasons = rebuild_reasons(project, layer, &jobs, src_modified, target, layer_path)?;
+    if reasons.is_empty() {
+        return Ok(None);
     }
-    let mut force_run = false;
-    let used_files = project
-        .dir()
-        .join(layer_path.join("rebuild-if-changed.txt"));
-    if used_files.exists() {
-        for line in std::fs::read_to_string(used_files)
-            .whatever("unable to read used files")?
-            .lines()
-        {
-            if let Ok(modified) = mtime_recursive(&project.dir().join(line)) {
-                last_modified = last_modified.max(modified)
-            } else {
-                error!("error determining modification time for {line}");
-                force_run = true;
-            }
-        }
+    for reason in &reasons {
+        info!("rebuilding layer `{}`: {reason}", layer.name);
     }
-    if target.exists()
-        && last_modified < mtime(target).whatever("unable to read `mtime` of target")?
-        && !force_run
-    {
-        return Ok(());
+    if let Some(cached) = cached {
+        if cached.fetch(project, target, layer_path) {
+            info!("retrieved layer `{}` from cache", layer.name);
+            write_parameters(project, layer_path, &jobs, cache_key)?;
+            return Ok(None);
+        }
     }
-    let bundle_dir = tempdir().whatever("unable to create temporary directory")?;
+    ensure_emulation(arch)?;
+    let aggregate_packages = config.aggregate_packages.unwrap_or(false);
+    // Packages of all recipes are installed upfront when aggregating them. Hence,
+    // checkpoints would not capture the state after the individual recipes.
+    let checkpoints = (config.checkpoints.unwrap_or(false) && !aggregate_packages)
+        .then(|| Checkpoints::new(project, layer_path, src_modified, &jobs));
+    let checkpoint = match &checkpoints {
+        Some(checkpoints) => latest_checkpoint(project, layer_path, checkpoints)?,
+        None => None,
+    };
+    let (applied, bundle_dir) = match checkpoint {
+        Some((applied, checkpoint)) => {
+            info!(
+                "resuming layer `{}` after recipe `{}`",
+                layer.name,
+                jobs[applied - 1].recipe.name
+            );
+            (applied, unpack_layer(Some(&checkpoint))?)
+        }
+        None => (0, unpack_layer(src)?),
+    };
     let bundle_dir = bundle_dir.path();
-    if let Some(src) = src {
-        info!("Extracting layer.");
-        run!(["tar", "-x", "-f", &src, "-C", bundle_dir]).whatever("unable to extract layer")?;
-    } else {
-        info!("Creating empty layer.");
-        std::fs::create_dir_all(&bundle_dir).whatever("unable ot create layer directory")?;
-    }
     let layer_ctx = LayerContext {
         project: project.clone(),
+        layer_name: layer.name.clone(),
+        aggregate_packages,
         build_dir: bundle_dir.to_path_buf(),
         output_dir: layer_path.to_path_buf(),
     };
     let root_dir = bundle_dir.join("roots/system");
     std::fs::create_dir_all(&root_dir).ok();
-    let logger = Logger::new(&layer.name, layer_path)?;
+    let logger = Logger::new(&layer.name, &layer_path.join("build.log"))?;
+    let mut recipe_reports = Vec::new();
+    let mut save_checkpoint = checkpoints
+        .as_ref()
+        .map(|checkpoints| move |idx: usize| checkpoints.save(applied + idx, bundle_dir));
     if let Err(error) = apply_recipes(
         &layer_ctx,
         &logger,
         project,
         arch,
-        &jobs,
+        &jobs[applied..],
         &root_dir,
         source_date_epoch,
+        &mut recipe_reports,
+        save_checkpoint
+            .as_mut()
+            .map(|save| save as &mut dyn FnMut(usize) -> BakeryResult<()>),
     ) {
         let last_lines = logger.current_lines();
 
@@ -159,30 +203,316 @@ pub fn customize(
 
         return Err(error);
     }
+    if let Some(cleanup) = &config.cleanup {
+        cleanup_layer(&layer_ctx, project, cleanup, &root_dir)?;
+    }
     info!("packing system files");
-    run!([
+    let mut tar = cmd_os!(
         "tar",
         "--sort=name",
         "--pax-option=exthdr.name=%d/PaxHeaders/%f,delete=atime,delete=ctime",
         "--clamp-mtime",
         format!("--mtime=@{source_date_epoch}"),
-        "-c",
-        "-f",
-        &target,
-        "-C",
-        bundle_dir,
-        "."
-    ])
-    .whatever("unable to package system files")?;
-    Ok(())
+    );
+    if Isolation::from_env()? == Isolation::Bwrap {
+        // The files belong to the invoking user on the host, who is root in the layer.
+        tar.extend_args(["--owner=0", "--group=0", "--numeric-owner"]);
+    }
+    tar.add_arg("-c")
+        .add_arg("-f")
+        .add_arg(target)
+        .add_arg("-C")
+        .add_arg(bundle_dir)
+        .add_arg(".");
+    ParentEnv
+        .run(tar)
+        .whatever("unable to package system files")?;
+    write_parameters(project, layer_path, &jobs, cache_key)?;
+    if let Some(checkpoints) = &checkpoints {
+        checkpoints.prune()?;
+    }
+    if let Some(cached) = cached {
+        cached.push(project, target, layer_path);
+    }
+    Ok(Some(recipe_reports))
+}
+
+/// Record the parameters of the recipes applied to a layer and its cache key.
+fn write_parameters(
+    project: &ProjectRef,
+    layer_path: &Path,
+    jobs: &[RecipeJob],
+    cache_key: &str,
+) -> BakeryResult<()> {
+    fs::write(layer_path.join(CACHE_KEY_FILE), cache_key).whatever("unable to record cache key")?;
+    fs::write(
+        layer_path.join(USED_FILES_KEY_FILE),
+        used_files_key(project, layer_path)?,
+    )
+    .whatever("unable to record key of used files")?;
+    fs::write(
+        layer_path.join(PARAMETERS_FILE),
+        serde_json::to_string_pretty(&job_parameters(jobs)).unwrap(),
+    )
+    .whatever("unable to record recipe parameters")?;
+    fs::write(
+        layer_path.join(TEMPLATE_INPUTS_FILE),
+        serde_json::to_string_pretty(&templates::template_inputs(project, jobs)?).unwrap(),
+    )
+    .whatever("unable to record template inputs")
+}
+
+/// Find the latest valid checkpoint of a layer.
+///
+/// Returns the number of recipes applied in the checkpoint and its path.
+fn latest_checkpoint(
+    project: &ProjectRef,
+    layer_path: &Path,
+    checkpoints: &Checkpoints,
+) -> BakeryResult<Option<(usize, PathBuf)>> {
+    let mut used_modified = None;
+    for line in used_files(project, layer_path)? {
+        match mtime_recursive(&project.dir().join(&line)) {
+            Ok(modified) => used_modified = used_modified.max(Some(modified)),
+            Err(_) => {
+                error!("error determining modification time for {line}");
+                return Ok(None);
+            }
+        }
+    }
+    Ok(checkpoints.latest(used_modified))
+}
+
+/// Parameters of the given recipe jobs by recipe and parameter name.
+fn job_parameters(jobs: &[RecipeJob]) -> BTreeMap<String, BTreeMap<String, String>> {
+    jobs.iter()
+        .map(|job| {
+            let parameters = job
+                .parameters
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            (job.recipe.name.to_string(), parameters)
+        })
+        .collect()
+}
+
+/// Spawn an interactive shell or run a command in the customization environment of a
+/// layer.
+///
+/// If a recipe is given, only the recipes scheduled before it are applied and the
+/// shell gets the environment of the recipe's `install` steps. Otherwise, all recipes
+/// are applied.
+pub fn shell(
+    project: &ProjectRef,
+    arch: Architecture,
+    layer: &Layer,
+    src: Option<&Path>,
+    layer_path: &Path,
+    recipe: Option<&str>,
+    command: Option<&[String]>,
+    source_date_epoch: u64,
+) -> BakeryResult<ExitStatus> {
+    let library = project.library()?;
+    let config = layer.config(arch).unwrap();
+    let jobs = recipe_schedule(layer.repo, config, &library)?;
+    let checkpoint = match recipe {
+        Some(recipe) => {
+            let Some(position) = jobs.iter().position(|job| job.recipe.name == recipe) else {
+                bail!("recipe `{recipe}` is not part of layer `{}`", layer.name);
+            };
+            Some(position)
+        }
+        None => None,
+    };
+    ensure_emulation(arch)?;
+    let bundle_dir = unpack_layer(src)?;
+    let bundle_dir = bundle_dir.path();
+    let layer_ctx = LayerContext {
+        project: project.clone(),
+        layer_name: layer.name.clone(),
+        aggregate_packages: config.aggregate_packages.unwrap_or(false),
+        build_dir: bundle_dir.to_path_buf(),
+        output_dir: layer_path.to_path_buf(),
+    };
+    let root_dir = bundle_dir.join("roots/system");
+    std::fs::create_dir_all(&root_dir).ok();
+    let logger = Logger::new(&layer.name, &layer_path.join("shell.log"))?;
+    apply_recipes(
+        &layer_ctx,
+        &logger,
+        project,
+        arch,
+        &jobs[..checkpoint.unwrap_or(jobs.len())],
+        &root_dir,
+        source_date_epoch,
+        &mut Vec::new(),
+        None,
+    )?;
+    let mut root_env = RootEnv::new(&root_dir)?;
+    let job = checkpoint.map(|position| &jobs[position]);
+    let (prefix, _mounted) = root_env.enter(
+        project,
+        &layer_ctx,
+        job.map(|job| job.recipe.path.as_path()),
+    )?;
+    let vars = chroot_vars(&layer_ctx, project, arch, job, source_date_epoch);
+    let mut process = Command::new(prefix.prog());
+    process.args(prefix.args());
+    match command {
+        Some(command) => {
+            process.args(command);
+        }
+        None => {
+            process.args([
+                "/bin/sh",
+                "-c",
+                "if command -v bash >/dev/null 2>&1; then exec bash; else exec sh; fi",
+            ]);
+        }
+    }
+    for (name, value) in vars.values() {
+        if let Some(value) = value {
+            process.env(name, value);
+        }
+    }
+    rugix_cli::suspend(|| {
+        if command.is_none() {
+            eprintln!("Exit the shell to discard the customization environment.");
+        }
+        process.status()
+    })
+    .whatever("unable to spawn process")
+}
+
+/// Unpack the given layer into a temporary directory or create an empty layer.
+fn unpack_layer(src: Option<&Path>) -> BakeryResult<TempDir> {
+    let bundle_dir = tempdir().whatever("unable to create temporary directory")?;
+    if let Some(src) = src {
+        info!("Extracting layer.");
+        run!(["tar", "-x", "-f", &src, "-C", bundle_dir.path()])
+            .whatever("unable to extract layer")?;
+    } else {
+        info!("Creating empty layer.");
+        std::fs::create_dir_all(bundle_dir.path()).whatever("unable ot create layer directory")?;
+    }
+    Ok(bundle_dir)
 }
 
-struct RecipeJob {
-    recipe: Arc<Recipe>,
-    parameters: HashMap<String, String>,
+/// Determine why a layer needs to be rebuilt.
+///
+/// Returns no reasons, if the layer is up-to-date. The modification time of the parent
+/// layer is given by `src_modified`.
+pub(crate) fn rebuild_reasons(
+    project: &ProjectRef,
+    layer: &Layer,
+    jobs: &[RecipeJob],
+    src_modified: Option<ModificationTime>,
+    target: &Path,
+    layer_path: &Path,
+) -> BakeryResult<Vec<String>> {
+    if !target.exists() {
+        return Ok(vec!["layer has not been built yet".to_owned()]);
+    }
+    let target_modified = mtime(target).whatever("unable to read `mtime` of target")?;
+    let mut reasons = Vec::new();
+    if layer.modified >= target_modified {
+        reasons.push("layer configuration changed".to_owned());
+    }
+    for job in jobs {
+        if job.recipe.modified >= target_modified {
+            reasons.push(format!("recipe `{}` changed", job.recipe.name));
+        }
+    }
+    if src_modified.is_some_and(|modified| modified >= target_modified) {
+        reasons.push("parent layer changed".to_owned());
+    }
+    // Parameters may change without any file changing, e.g., when overridden with `-D`.
+    let parameters_file = project.dir().join(layer_path.join(PARAMETERS_FILE));
+    if let Ok(previous) = fs::read_to_string(&parameters_file) {
+        let previous =
+            serde_json::from_str::<BTreeMap<String, BTreeMap<String, String>>>(&previous)
+                .unwrap_or_default();
+        for (recipe, parameters) in job_parameters(jobs) {
+            if previous.get(&recipe) != Some(&parameters) {
+                reasons.push(format!("parameters of recipe `{recipe}` changed"));
+            }
+        }
+    }
+    let template_inputs = templates::template_inputs(project, jobs)?;
+    let previous_inputs =
+        fs::read_to_string(project.dir().join(layer_path.join(TEMPLATE_INPUTS_FILE)))
+            .ok()
+            .and_then(|previous| serde_json::from_str::<BTreeMap<String, String>>(&previous).ok())
+            .unwrap_or_default();
+    if previous_inputs != template_inputs {
+        reasons.push("inputs of templates changed".to_owned());
+    }
+    for line in used_files(project, layer_path)? {
+        match mtime_recursive(&project.dir().join(&line)) {
+            Ok(modified) if modified >= target_modified => {
+                reasons.push(format!("file `{line}` changed"));
+            }
+            Ok(_) => {}
+            Err(_) => {
+                error!("error determining modification time for {line}");
+                reasons.push(format!("unable to determine modification time of `{line}`"));
+            }
+        }
+    }
+    Ok(reasons)
+}
+
+/// Check whether the files used by the recipes of a layer are unchanged since the layer
+/// has been built, based on the recorded hash of their contents.
+pub(crate) fn used_files_unchanged(project: &ProjectRef, layer_path: &Path) -> bool {
+    let Ok(recorded) = fs::read_to_string(layer_path.join(USED_FILES_KEY_FILE)) else {
+        return false;
+    };
+    match used_files_key(project, layer_path) {
+        Ok(key) => key == recorded.trim(),
+        Err(error) => {
+            warn!("unable to hash files used by layer: {error:?}");
+            false
+        }
+    }
 }
 
-fn recipe_schedule(
+/// Compute the hash of the contents of the files used by the recipes of a layer.
+fn used_files_key(project: &ProjectRef, layer_path: &Path) -> BakeryResult<String> {
+    let used_files = used_files(project, layer_path)?;
+    let mut hasher = Hasher::new();
+    layer_cache::hash_used_files(
+        &mut hasher,
+        project.dir(),
+        used_files.iter().map(String::as_str),
+    )?;
+    Ok(hasher.finalize())
+}
+
+/// Files used by the recipes of a layer, as registered with `LAYER_REBUILD_IF_CHANGED`.
+fn used_files(project: &ProjectRef, layer_path: &Path) -> BakeryResult<Vec<String>> {
+    let used_files = project
+        .dir()
+        .join(layer_path.join("rebuild-if-changed.txt"));
+    if !used_files.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(std::fs::read_to_string(used_files)
+        .whatever("unable to read used files")?
+        .lines()
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Recipe scheduled to be applied to a layer.
+pub(crate) struct RecipeJob {
+    pub recipe: Arc<Recipe>,
+    pub parameters: HashMap<String, String>,
+}
+
+/// Compute the recipes to apply to a layer in the order in which they are applied.
+pub(crate) fn recipe_schedule(
     repo: RepositoryIdx,
     layer: &LayerConfig,
     library: &Library,
@@ -246,9 +576,14 @@ fn recipe_schedule(
                     }
                 }
             }
+            let overrides = library.parameter_overrides.get(&idx);
             let mut parameters = HashMap::new();
             if let Some(p) = &recipe.config.parameters {
                 for (name, def) in p {
+                    if let Some(value) = overrides.and_then(|overrides| overrides.get(name)) {
+                        parameters.insert(name.to_owned(), value.clone());
+                        continue;
+                    }
                     if let Some(params) = recipe_params {
                         if let Some(value) = params.get(name) {
                             parameters.insert(name.to_owned(), value.to_string());
@@ -265,7 +600,14 @@ fn recipe_schedule(
             Ok(RecipeJob { recipe, parameters })
         })
         .collect::<Result<Vec<_>, _>>()?;
-    recipes.sort_by_key(|job| -job.recipe.config.priority.unwrap_or_default());
+    // Recipes with the same priority are ordered by name such that the order is stable
+    // across builds, which is required for checkpoints.
+    recipes.sort_by_key(|job| {
+        (
+            -job.recipe.config.priority.unwrap_or_default(),
+            job.recipe.name.to_string(),
+        )
+    });
     Ok(recipes)
 }
 
@@ -317,6 +659,11 @@ fn run_cmd(logger: &Logger, cmd: Cmd<OsString>) -> BakeryResult<()> {
     Ok(())
 }
 
+/// Apply the given recipes to the root directory, recording their timings.
+///
+/// If provided, `on_applied` is called with the index of each recipe after applying it.
+/// At this point, nothing is mounted into the root directory.
+#[allow(clippy::too_many_arguments)]
 fn apply_recipes(
     layer_ctx: &LayerContext,
     logger: &Logger,
@@ -325,56 +672,63 @@ fn apply_recipes(
     jobs: &[RecipeJob],
     root_dir_path: &Path,
     source_date_epoch: u64,
+    reports: &mut Vec<RecipeReport>,
+    mut on_applied: Option<&mut dyn FnMut(usize) -> BakeryResult<()>>,
 ) -> BakeryResult<()> {
-    let mut mount_stack = MountStack::new();
-
-    fn mount_all(
-        project: &ProjectRef,
-        root_dir_path: &Path,
-        stack: &mut MountStack,
-    ) -> BakeryResult<()> {
-        stack.push(
-            Mounted::bind("/dev", root_dir_path.join("dev")).whatever("unable to mount /dev")?,
-        );
-        stack.push(
-            Mounted::bind("/dev/pts", root_dir_path.join("dev/pts"))
-                .whatever("unable to mount /dev/pts")?,
-        );
-        stack.push(
-            Mounted::bind("/sys", root_dir_path.join("sys")).whatever("unable to mount /sys")?,
-        );
-        stack.push(
-            Mounted::mount_fs("proc", "proc", root_dir_path.join("proc"))
-                .whatever("unable to mount /proc")?,
-        );
-        stack.push(
-            Mounted::mount_fs("tmpfs", "tmpfs", root_dir_path.join("run"))
-                .whatever("unable to mount /run")?,
-        );
-        stack.push(
-            Mounted::mount_fs("tmpfs", "tmpfs", root_dir_path.join("tmp"))
-                .whatever("unable to mount /tmp")?,
-        );
-
-        let project_dir = root_dir_path.join("run/rugix/bakery/project");
-        fs::create_dir_all(&project_dir).whatever("unable to create project directory")?;
+    let mut root_env = RootEnv::new(root_dir_path)?;
 
-        let resolved_resolv = root_dir_path.join("run/systemd/resolve/stub-resolv.conf");
-        fs::create_dir_all(resolved_resolv.parent().unwrap())
-            .whatever("unable to create `systemd/resolve` directory")?;
-        let resolv_conf =
-            fs::read("/etc/resolv.conf").whatever("unable to read `/etc/resolv.conf")?;
-        fs::write(resolved_resolv, resolv_conf).whatever("unable to write `resolv.conf`")?;
-
-        stack.push(
-            Mounted::bind(project.dir(), &project_dir)
-                .whatever("unable to bind mount project directory")?,
-        );
+    let project_dir = root_env.project_dir(project);
 
+    // The APT proxy is configured as soon as the system has APT.
+    let apt_proxy = apt::apt_proxy(project)?;
+    let mut proxy_conf = None;
+    let configure_proxy = |proxy_conf: &mut Option<ProxyConf>| -> BakeryResult<()> {
+        if let Some(proxy) = &apt_proxy {
+            if proxy_conf.is_none() {
+                *proxy_conf = ProxyConf::install(root_dir_path, proxy.url())?;
+            }
+        }
         Ok(())
-    }
+    };
 
-    let project_dir = root_dir_path.join("run/rugix/bakery/project");
+    let aggregated = layer_ctx.aggregate_packages;
+    if aggregated {
+        configure_proxy(&mut proxy_conf)?;
+        // Install the packages of all recipes in a single transaction.
+        let manager = chroot_package_manager(root_dir_path)?;
+        let mut packages = Vec::new();
+        let mut seen = HashSet::new();
+        for job in jobs {
+            for step in &job.recipe.steps {
+                if let StepKind::Packages {
+                    packages: step_packages,
+                    manager: step_manager,
+                } = &step.kind
+                {
+                    if step_manager.unwrap_or(manager) != manager {
+                        continue;
+                    }
+                    for package in step_packages {
+                        if seen.insert(package) {
+                            packages.push(package.clone());
+                        }
+                    }
+                }
+            }
+        }
+        if !packages.is_empty() {
+            info!("installing {} packages of all recipes", packages.len());
+            install_packages(
+                &mut root_env,
+                project,
+                layer_ctx,
+                manager,
+                &packages,
+                source_date_epoch,
+            )?;
+            clean_package_cache(&mut root_env, project, layer_ctx, manager)?;
+        }
+    }
 
     for (idx, job) in jobs.iter().enumerate() {
         let recipe = &job.recipe;
@@ -390,89 +744,50 @@ fn apply_recipes(
             &job.parameters,
         );
 
+        // Environment variables of the toolchain provisioned by the recipe.
+        let mut toolchain_vars = Vec::new();
+        let use_ccache = recipe.config.ccache.unwrap_or(false);
+        let ccache_vars = if use_ccache {
+            ccache::host_vars(project)?
+        } else {
+            Vec::new()
+        };
+
+        let recipe_started = Instant::now();
+        let mut step_reports = Vec::new();
         for step in &recipe.steps {
             info!("    - {}", step.filename);
+            let step_started = Instant::now();
+            configure_proxy(&mut proxy_conf)?;
             match &step.kind {
                 StepKind::Packages { packages, manager } => {
-                    if mount_stack.is_empty() {
-                        mount_all(project, root_dir_path, &mut mount_stack)?;
-                    }
-                    let chroot_manager = if root_dir_path.join("usr/bin/apt-get").exists() {
-                        PackageManager::Apt
-                    } else if root_dir_path.join("sbin/apk").exists() {
-                        PackageManager::Apk
-                    } else if root_dir_path.join("usr/bin/pacman").exists() {
-                        PackageManager::Pacman
-                    } else {
-                        bail!("unable to determine package manager")
-                    };
+                    let chroot_manager = chroot_package_manager(root_dir_path)?;
                     let manager = manager.unwrap_or(chroot_manager);
-                    if manager == chroot_manager {
-                        let mut cmd = match manager {
-                            PackageManager::Apt => {
-                                cmd!("chroot", root_dir_path, "apt-get", "install", "-y")
-                            }
-                            PackageManager::Apk => {
-                                cmd!("chroot", root_dir_path, "apk", "add", "--no-interactive")
-                            }
-                            PackageManager::Pacman => {
-                                cmd!("chroot", root_dir_path, "pacman", "-S", "--noconfirm")
-                            }
-                        };
-                        cmd.extend_args(packages);
-                        ParentEnv
-                            .run(cmd.with_vars(vars! {
-                                DEBIAN_FRONTEND = "noninteractive",
-                                SOURCE_DATE_EPOCH = source_date_epoch.to_string(),
-                            }))
-                            .whatever("unable to install packages")?;
+                    if manager == chroot_manager && !aggregated {
+                        install_packages(
+                            &mut root_env,
+                            project,
+                            layer_ctx,
+                            manager,
+                            packages,
+                            source_date_epoch,
+                        )?;
                     }
                 }
                 StepKind::Install => {
-                    if mount_stack.is_empty() {
-                        mount_all(project, root_dir_path, &mut mount_stack)?;
-                    }
-                    let bakery_recipe_path = root_dir_path.join("run/rugix/bakery/recipe");
-                    fs::create_dir_all(&bakery_recipe_path)
-                        .whatever("unable to create recipe directory")?;
-                    let _mounted_recipe = Mounted::bind(&recipe.path, &bakery_recipe_path)
-                        .whatever("unable to bind mount recipe")?;
-                    let chroot_layer_dir = root_dir_path.join("run/rugix/bakery/layer");
-                    fs::create_dir_all(&chroot_layer_dir)
-                        .whatever("unable to create layer bundle directory")?;
-                    let _mounted_layer_dir = Mounted::bind(&layer_ctx.build_dir, &chroot_layer_dir)
-                        .whatever("unable to bind mount layer")?;
-                    let build_env_path = root_dir_path.join("run/rugix/bakery/build-env");
-                    fs::create_dir_all(&build_env_path)
-                        .whatever("unable to create recipe directory")?;
-                    let _mounted_build_env = Mounted::bind("/run/rugix/bakery", &build_env_path)
-                        .whatever("unable to bind mount recipe")?;
+                    let (mut cmd, _mounted) =
+                        root_env.enter(project, layer_ctx, Some(&recipe.path))?;
                     let script = format!("/run/rugix/bakery/recipe/steps/{}", step.filename);
-                    let mut vars = vars! {
-                        DEBIAN_FRONTEND = "noninteractive",
-                        RUGIX_LAYER_DIR = "/run/rugix/bakery/layer",
-                        RUGIX_ARTIFACTS_DIR = "/run/rugix/bakery/layer/artifacts",
-                        RUGIX_CONTEXT_DIR = "/run/rugix/bakery/build-env/context",
-                        RUGIX_CACHE_DIR = Path::new("/run/rugix/bakery/build-env/cache").join(project.local_id().as_str()),
-                        RUGIX_ROOT_DIR = "/",
-                        RUGIX_PROJECT_DIR = "/run/rugix/bakery/project",
-                        RUGIX_ARCH = arch.as_str(),
-                        LAYER_REBUILD_IF_CHANGED = Path::new("/run/rugix/bakery/project").join(&layer_ctx.output_dir).join("rebuild-if-changed.txt"),
-                        RECIPE_DIR = "/run/rugix/bakery/recipe",
-                        RECIPE_STEP_PATH = &script,
-                        SOURCE_DATE_EPOCH = source_date_epoch.to_string(),
-                    };
-                    for (name, value) in &job.parameters {
-                        vars.set(format!("RECIPE_PARAM_{}", name.to_uppercase()), value);
+                    let mut vars =
+                        chroot_vars(layer_ctx, project, arch, Some(job), source_date_epoch);
+                    vars.set("RECIPE_STEP_PATH", &script);
+                    if use_ccache {
+                        for (name, value) in ccache::chroot_vars(project, root_dir_path)? {
+                            vars.set(name, value);
+                        }
                     }
-                    run_cmd(
-                        logger,
-                        Cmd::new("chroot")
-                            .add_arg(root_dir_path)
-                            .add_arg(&script)
-                            .clone()
-                            .with_vars(vars),
-                    )?;
+                    cmd.add_arg(&script);
+                    run_cmd(logger, cmd.with_vars(vars))?;
                 }
                 StepKind::Run => {
                     let script = recipe.path.join("steps").join(&step.filename);
@@ -481,8 +796,8 @@ fn apply_recipes(
                         RUGIX_LAYER_DIR = &layer_ctx.build_dir,
                         RUGIX_ARTIFACTS_DIR = layer_ctx.build_dir.join("artifacts"),
                         RUGIX_ROOT_DIR = root_dir_path,
-                        RUGIX_CONTEXT_DIR = "/run/rugix/bakery/context",
-                        RUGIX_CACHE_DIR = Path::new("/run/rugix/bakery/cache").join(project.local_id().as_str()),
+                        RUGIX_CONTEXT_DIR = build_env::context_dir(),
+                        RUGIX_CACHE_DIR = build_env::cache_dir().join(project.local_id().as_str()),
                         RUGIX_PROJECT_DIR = &project_dir,
                         RUGIX_ARCH = arch.as_str(),
                         LAYER_REBUILD_IF_CHANGED = project_dir.join(&layer_ctx.output_dir).join("rebuild-if-changed.txt"),
@@ -493,11 +808,583 @@ fn apply_recipes(
                     for (name, value) in &job.parameters {
                         vars.set(format!("RECIPE_PARAM_{}", name.to_uppercase()), value);
                     }
-                    run_cmd(logger, Cmd::new(&script).with_vars(vars))?;
+                    for (name, value) in ccache_vars.iter().chain(&toolchain_vars) {
+                        vars.set(*name, value);
+                    }
+                    if let Some(proxy) = &apt_proxy {
+                        vars.set("RUGIX_APT_PROXY", proxy.url());
+                    }
+                    let cmd = if build_env::is_offline() && apt_proxy.is_none() {
+                        // Without a network, steps accessing the network fail immediately
+                        // instead of timing out or succeeding only by chance.
+                        let mut cmd = cmd_os!("unshare");
+                        if root_env.isolation == Isolation::Bwrap {
+                            cmd.add_arg("--map-root-user");
+                        }
+                        cmd.extend_args(["--net", "--"]);
+                        cmd.add_arg(&script);
+                        cmd
+                    } else {
+                        Cmd::new(&script)
+                    };
+                    run_cmd(logger, cmd.with_vars(vars))?;
+                }
+                StepKind::Toolchain { config } => {
+                    toolchain_vars = toolchain::toolchain_vars(config, arch, root_dir_path)?;
+                    if use_ccache {
+                        ccache::wrap_compilers(&mut toolchain_vars);
+                    }
+                }
+                StepKind::Rust { config } => {
+                    rust::build_crate(
+                        project,
+                        config,
+                        arch,
+                        &recipe.path,
+                        root_dir_path,
+                        &toolchain_vars,
+                        source_date_epoch,
+                        |cmd| run_cmd(logger, cmd),
+                    )?;
+                }
+                StepKind::Templates { config } => {
+                    let ctx = TemplateContext {
+                        project,
+                        layer: &layer_ctx.layer_name,
+                        recipe: &recipe.name,
+                        parameters: &job.parameters,
+                        arch,
+                        source_date_epoch,
+                    };
+                    templates::render_templates(config, &recipe.path, root_dir_path, &ctx)?;
+                }
+                StepKind::Files { config } => {
+                    files::install_files(config, &recipe.path, root_dir_path)?;
+                }
+                StepKind::Users { config } => {
+                    users::provision_users(config, root_dir_path, source_date_epoch)?;
+                }
+                StepKind::Network { config } => {
+                    network::configure_network(config, root_dir_path)?;
+                }
+                StepKind::Overlays { sources } => {
+                    compile_overlays(
+                        logger,
+                        &recipe.path,
+                        sources,
+                        root_dir_path,
+                        &layer_ctx.build_dir.join("roots/boot"),
+                    )?;
+                }
+                StepKind::FirstBoot => {
+                    let scripts_dir = root_dir_path.join(FIRST_BOOT_SCRIPTS_DIR);
+                    fs::create_dir_all(&scripts_dir)
+                        .whatever("unable to create first-boot scripts directory")?;
+                    // Scripts are executed in the order of their names.
+                    let script = scripts_dir.join(format!("{:03}-{}", step.position, recipe.name));
+                    fs::copy(recipe.path.join("steps").join(&step.filename), &script)
+                        .whatever("unable to install first-boot script")?;
+                    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+                        .whatever("unable to make first-boot script executable")?;
                 }
             }
+            step_reports.push(StepReport {
+                filename: step.filename.clone(),
+                duration: step_started.elapsed().as_secs_f64(),
+            });
+        }
+        reports.push(RecipeReport {
+            name: recipe.name.to_string(),
+            duration: recipe_started.elapsed().as_secs_f64(),
+            steps: step_reports,
+        });
+        if let Some(on_applied) = on_applied.as_mut() {
+            // Remove the proxy configuration and the mounts, they are restored on demand.
+            proxy_conf = None;
+            root_env.leave();
+            on_applied(idx)?;
         }
     }
 
     Ok(())
 }
+
+/// Determine the package manager of the system in the given root directory.
+fn chroot_package_manager(root_dir: &Path) -> BakeryResult<PackageManager> {
+    if root_dir.join("usr/bin/apt-get").exists() {
+        Ok(PackageManager::Apt)
+    } else if root_dir.join("sbin/apk").exists() {
+        Ok(PackageManager::Apk)
+    } else if root_dir.join("usr/bin/pacman").exists() {
+        Ok(PackageManager::Pacman)
+    } else {
+        bail!("unable to determine package manager")
+    }
+}
+
+/// Install the given packages with the package manager of the system.
+fn install_packages(
+    root_env: &mut RootEnv,
+    project: &ProjectRef,
+    layer_ctx: &LayerContext,
+    manager: PackageManager,
+    packages: &[String],
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    // Offline, packages can only be installed from an APT proxy or cache.
+    let has_apt_proxy = project
+        .config()
+        .apt
+        .as_ref()
+        .is_some_and(|apt| apt.proxy.is_some() || apt.cache.unwrap_or(false));
+    if manager != PackageManager::Apt || !has_apt_proxy {
+        build_env::ensure_online("installing packages")?;
+    }
+    let (mut cmd, _mounted) = root_env.enter(project, layer_ctx, None)?;
+    match manager {
+        PackageManager::Apt => {
+            cmd.add_arg("apt-get");
+            if root_env.isolation == Isolation::Bwrap {
+                // APT cannot drop privileges with a single mapped user.
+                cmd.extend_args(["-o", "APT::Sandbox::User=root"]);
+            }
+            cmd.extend_args(["install", "-y"]);
+        }
+        PackageManager::Apk => {
+            cmd.extend_args(["apk", "add", "--no-interactive"]);
+        }
+        PackageManager::Pacman => {
+            cmd.extend_args(["pacman", "-S", "--noconfirm"]);
+        }
+    }
+    cmd.extend_args(packages);
+    ParentEnv
+        .run(cmd.with_vars(vars! {
+            DEBIAN_FRONTEND = "noninteractive",
+            SOURCE_DATE_EPOCH = source_date_epoch.to_string(),
+        }))
+        .whatever("unable to install packages")?;
+    Ok(())
+}
+
+/// Remove downloaded package files from the cache of the package manager.
+fn clean_package_cache(
+    root_env: &mut RootEnv,
+    project: &ProjectRef,
+    layer_ctx: &LayerContext,
+    manager: PackageManager,
+) -> BakeryResult<()> {
+    let (mut cmd, _mounted) = root_env.enter(project, layer_ctx, None)?;
+    match manager {
+        PackageManager::Apt => {
+            cmd.extend_args(["apt-get", "clean"]);
+        }
+        // APK does not cache packages unless a cache has been configured explicitly.
+        PackageManager::Apk => return Ok(()),
+        PackageManager::Pacman => {
+            cmd.extend_args(["pacman", "-Scc", "--noconfirm"]);
+        }
+    }
+    ParentEnv
+        .run(cmd)
+        .whatever("unable to clean package cache")?;
+    Ok(())
+}
+
+/// Remove the given packages and the packages installed automatically for them.
+fn remove_packages(
+    root_env: &mut RootEnv,
+    project: &ProjectRef,
+    layer_ctx: &LayerContext,
+    manager: PackageManager,
+    packages: &[String],
+) -> BakeryResult<()> {
+    let (mut cmd, _mounted) = root_env.enter(project, layer_ctx, None)?;
+    match manager {
+        PackageManager::Apt => {
+            cmd.add_arg("apt-get");
+            if root_env.isolation == Isolation::Bwrap {
+                // APT cannot drop privileges with a single mapped user.
+                cmd.extend_args(["-o", "APT::Sandbox::User=root"]);
+            }
+            cmd.extend_args(["purge", "-y", "--auto-remove"]);
+        }
+        PackageManager::Apk => {
+            cmd.extend_args(["apk", "del", "--no-interactive"]);
+        }
+        PackageManager::Pacman => {
+            cmd.extend_args(["pacman", "-Rns", "--noconfirm"]);
+        }
+    }
+    cmd.extend_args(packages);
+    ParentEnv
+        .run(cmd.with_vars(vars! {
+            DEBIAN_FRONTEND = "noninteractive",
+        }))
+        .whatever("unable to remove packages")?;
+    Ok(())
+}
+
+/// Clean up the root filesystem of a layer and report the space saved.
+fn cleanup_layer(
+    layer_ctx: &LayerContext,
+    project: &ProjectRef,
+    config: &CleanupConfig,
+    root_dir_path: &Path,
+) -> BakeryResult<()> {
+    info!("cleaning up layer `{}`", layer_ctx.layer_name);
+    let options = CleanupOptions::from_config(config);
+    let mut report = CleanupReport::new(root_dir_path)?;
+    let mut root_env = RootEnv::new(root_dir_path)?;
+    if !options.remove_packages.is_empty() {
+        let manager = chroot_package_manager(root_dir_path)?;
+        remove_packages(
+            &mut root_env,
+            project,
+            layer_ctx,
+            manager,
+            &options.remove_packages,
+        )?;
+        // Sizes must not be measured while pseudo filesystems are mounted.
+        root_env.leave();
+        report.record("packages", root_dir_path)?;
+    }
+    if options.caches {
+        if let Ok(manager) = chroot_package_manager(root_dir_path) {
+            clean_package_cache(&mut root_env, project, layer_ctx, manager)?;
+            root_env.leave();
+        }
+        cleanup::remove_caches(root_dir_path)?;
+        report.record("caches", root_dir_path)?;
+    }
+    if options.apt_lists {
+        cleanup::remove_apt_lists(root_dir_path)?;
+        report.record("apt-lists", root_dir_path)?;
+    }
+    if options.docs {
+        cleanup::remove_docs(root_dir_path)?;
+        report.record("docs", root_dir_path)?;
+    }
+    if options.locales {
+        cleanup::remove_locales(root_dir_path, &options.keep_locales)?;
+        report.record("locales", root_dir_path)?;
+    }
+    if options.logs {
+        cleanup::remove_logs(root_dir_path)?;
+        report.record("logs", root_dir_path)?;
+    }
+    if !options.paths.is_empty() {
+        cleanup::remove_paths(root_dir_path, &options.paths)?;
+        report.record("paths", root_dir_path)?;
+    }
+    report.write(&layer_ctx.output_dir)
+}
+
+/// Environment for executing programs in the root directory of a layer.
+struct RootEnv<'r> {
+    isolation: Isolation,
+    root_dir: &'r Path,
+    mount_stack: MountStack,
+}
+
+impl<'r> RootEnv<'r> {
+    fn new(root_dir: &'r Path) -> BakeryResult<Self> {
+        Ok(Self {
+            isolation: Isolation::from_env()?,
+            root_dir,
+            mount_stack: MountStack::new(),
+        })
+    }
+
+    /// Project directory as seen from the host.
+    fn project_dir(&self, project: &ProjectRef) -> PathBuf {
+        match self.isolation {
+            Isolation::Chroot => self.root_dir.join("run/rugix/bakery/project"),
+            Isolation::Bwrap => project.dir().to_path_buf(),
+        }
+    }
+
+    /// Unmount everything mounted into the root directory.
+    fn leave(&mut self) {
+        self.mount_stack.unmount_all();
+    }
+
+    /// Prepare executing a program in the root directory.
+    ///
+    /// Returns the command prefix for executing the program and the mounts which must
+    /// be held while it is running. If a recipe is given, it is made available at
+    /// `/run/rugix/bakery/recipe`.
+    fn enter(
+        &mut self,
+        project: &ProjectRef,
+        layer_ctx: &LayerContext,
+        recipe: Option<&Path>,
+    ) -> BakeryResult<(Cmd<OsString>, Vec<Mounted>)> {
+        match self.isolation {
+            Isolation::Chroot => {
+                if self.mount_stack.is_empty() {
+                    mount_all(project, self.root_dir, &mut self.mount_stack)?;
+                }
+                let mut mounted = Vec::new();
+                if let Some(recipe) = recipe {
+                    let bakery_recipe_path = self.root_dir.join("run/rugix/bakery/recipe");
+                    fs::create_dir_all(&bakery_recipe_path)
+                        .whatever("unable to create recipe directory")?;
+                    mounted.push(
+                        Mounted::bind(recipe, &bakery_recipe_path)
+                            .whatever("unable to bind mount recipe")?,
+                    );
+                }
+                mounted.extend(mount_build_env(layer_ctx, self.root_dir)?);
+                Ok((cmd_os!("chroot", self.root_dir), mounted))
+            }
+            Isolation::Bwrap => {
+                // The invoking user is mapped to root in a new user namespace. Hence,
+                // no privileges are required on the host.
+                let mut cmd = cmd_os!(
+                    "bwrap",
+                    "--unshare-user",
+                    "--uid",
+                    "0",
+                    "--gid",
+                    "0",
+                    "--unshare-pid",
+                    "--unshare-ipc",
+                    "--die-with-parent",
+                    "--bind",
+                    self.root_dir,
+                    "/",
+                    "--dev",
+                    "/dev",
+                    "--proc",
+                    "/proc",
+                    "--ro-bind",
+                    "/sys",
+                    "/sys",
+                    "--tmpfs",
+                    "/tmp",
+                    "--tmpfs",
+                    "/run",
+                    "--ro-bind",
+                    "/etc/resolv.conf",
+                    "/run/systemd/resolve/stub-resolv.conf",
+                    "--bind",
+                    project.dir(),
+                    "/run/rugix/bakery/project",
+                    "--bind",
+                    &layer_ctx.build_dir,
+                    "/run/rugix/bakery/layer",
+                    "--bind",
+                    build_env::run_dir(),
+                    "/run/rugix/bakery/build-env",
+                    "--bind-try",
+                    build_env::context_dir(),
+                    "/run/rugix/bakery/build-env/context"
+                );
+                if let Some(recipe) = recipe {
+                    cmd.add_arg("--ro-bind")
+                        .add_arg(recipe)
+                        .add_arg("/run/rugix/bakery/recipe");
+                }
+                Ok((cmd, Vec::new()))
+            }
+        }
+    }
+}
+
+/// Mount the pseudo filesystems and the project directory into the root directory.
+fn mount_all(
+    project: &ProjectRef,
+    root_dir_path: &Path,
+    stack: &mut MountStack,
+) -> BakeryResult<()> {
+    stack.push(Mounted::bind("/dev", root_dir_path.join("dev")).whatever("unable to mount /dev")?);
+    stack.push(
+        Mounted::bind("/dev/pts", root_dir_path.join("dev/pts"))
+            .whatever("unable to mount /dev/pts")?,
+    );
+    stack.push(Mounted::bind("/sys", root_dir_path.join("sys")).whatever("unable to mount /sys")?);
+    stack.push(
+        Mounted::mount_fs("proc", "proc", root_dir_path.join("proc"))
+            .whatever("unable to mount /proc")?,
+    );
+    stack.push(
+        Mounted::mount_fs("tmpfs", "tmpfs", root_dir_path.join("run"))
+            .whatever("unable to mount /run")?,
+    );
+    stack.push(
+        Mounted::mount_fs("tmpfs", "tmpfs", root_dir_path.join("tmp"))
+            .whatever("unable to mount /tmp")?,
+    );
+
+    let project_dir = root_dir_path.join("run/rugix/bakery/project");
+    fs::create_dir_all(&project_dir).whatever("unable to create project directory")?;
+
+    let resolved_resolv = root_dir_path.join("run/systemd/resolve/stub-resolv.conf");
+    fs::create_dir_all(resolved_resolv.parent().unwrap())
+        .whatever("unable to create `systemd/resolve` directory")?;
+    let resolv_conf = fs::read("/etc/resolv.conf").whatever("unable to read `/etc/resolv.conf")?;
+    fs::write(resolved_resolv, resolv_conf).whatever("unable to write `resolv.conf`")?;
+
+    stack.push(
+        Mounted::bind(project.dir(), &project_dir)
+            .whatever("unable to bind mount project directory")?,
+    );
+
+    Ok(())
+}
+
+/// Bind mount the layer directory and the build environment into the root directory.
+fn mount_build_env(layer_ctx: &LayerContext, root_dir_path: &Path) -> BakeryResult<Vec<Mounted>> {
+    let chroot_layer_dir = root_dir_path.join("run/rugix/bakery/layer");
+    fs::create_dir_all(&chroot_layer_dir).whatever("unable to create layer bundle directory")?;
+    let mounted_layer_dir = Mounted::bind(&layer_ctx.build_dir, &chroot_layer_dir)
+        .whatever("unable to bind mount layer")?;
+    let build_env_path = root_dir_path.join("run/rugix/bakery/build-env");
+    fs::create_dir_all(&build_env_path).whatever("unable to create recipe directory")?;
+    let mounted_build_env = Mounted::bind(build_env::run_dir(), &build_env_path)
+        .whatever("unable to bind mount recipe")?;
+    let mut mounted = vec![mounted_layer_dir, mounted_build_env];
+    let context_dir = build_env::context_dir();
+    if context_dir != build_env::run_dir().join("context") && context_dir.exists() {
+        let chroot_context_dir = build_env_path.join("context");
+        fs::create_dir_all(&chroot_context_dir).whatever("unable to create context directory")?;
+        mounted.push(
+            Mounted::bind(&context_dir, &chroot_context_dir)
+                .whatever("unable to bind mount context directory")?,
+        );
+    }
+    Ok(mounted)
+}
+
+/// Environment variables of steps executed in the chroot.
+fn chroot_vars(
+    layer_ctx: &LayerContext,
+    project: &ProjectRef,
+    arch: Architecture,
+    job: Option<&RecipeJob>,
+    source_date_epoch: u64,
+) -> Vars {
+    let mut vars = vars! {
+        DEBIAN_FRONTEND = "noninteractive",
+        RUGIX_LAYER_DIR = "/run/rugix/bakery/layer",
+        RUGIX_ARTIFACTS_DIR = "/run/rugix/bakery/layer/artifacts",
+        RUGIX_CONTEXT_DIR = "/run/rugix/bakery/build-env/context",
+        RUGIX_CACHE_DIR = Path::new("/run/rugix/bakery/build-env/cache").join(project.local_id().as_str()),
+        RUGIX_ROOT_DIR = "/",
+        RUGIX_PROJECT_DIR = "/run/rugix/bakery/project",
+        RUGIX_ARCH = arch.as_str(),
+        LAYER_REBUILD_IF_CHANGED = Path::new("/run/rugix/bakery/project").join(&layer_ctx.output_dir).join("rebuild-if-changed.txt"),
+        SOURCE_DATE_EPOCH = source_date_epoch.to_string(),
+    };
+    if let Some(job) = job {
+        vars.set("RECIPE_DIR", "/run/rugix/bakery/recipe");
+        for (name, value) in &job.parameters {
+            vars.set(format!("RECIPE_PARAM_{}", name.to_uppercase()), value);
+        }
+    }
+    vars
+}
+
+/// Compile device tree sources of a recipe and install them into the boot directory.
+///
+/// Overlays are installed into the `overlays` directory and checked against the device
+/// trees in the boot directory to ensure that their references can be resolved. If there
+/// are no device trees in the boot directory, a warning is emitted instead.
+fn compile_overlays(
+    logger: &Logger,
+    recipe_path: &Path,
+    sources: &[String],
+    root_dir_path: &Path,
+    boot_dir: &Path,
+) -> BakeryResult<()> {
+    let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+    let mut base_dtbs = Vec::new();
+    if boot_dir.is_dir() {
+        for entry in fs::read_dir(boot_dir).whatever("unable to read boot directory")? {
+            let path = entry.whatever("unable to read directory entry")?.path();
+            if path.extension().is_some_and(|ext| ext == "dtb") {
+                base_dtbs.push(path);
+            }
+        }
+    }
+    base_dtbs.sort();
+    for source in sources {
+        let source_path = recipe_path.join(source);
+        let Some(stem) = source_path.file_stem().and_then(|stem| stem.to_str()) else {
+            bail!("invalid device tree source {source:?}");
+        };
+        let content = fs::read_to_string(&source_path)
+            .whatever_with(|_| format!("unable to read device tree source {source:?}"))?;
+        let is_overlay = source_path.extension().is_some_and(|ext| ext == "dtso")
+            || content.contains("/plugin/;");
+        let output = if is_overlay {
+            let name = stem.strip_suffix("-overlay").unwrap_or(stem);
+            boot_dir.join("overlays").join(format!("{name}.dtbo"))
+        } else {
+            boot_dir.join(format!("{stem}.dtb"))
+        };
+        fs::create_dir_all(output.parent().unwrap())
+            .whatever("unable to create output directory")?;
+        let preprocessed = temp_dir.path().join(format!("{stem}.pp.dts"));
+        let mut cpp = Cmd::new("cpp");
+        cpp.extend_args([
+            "-nostdinc",
+            "-undef",
+            "-D__DTS__",
+            "-x",
+            "assembler-with-cpp",
+        ]);
+        cpp.add_arg("-I");
+        cpp.add_arg(source_path.parent().unwrap());
+        cpp.add_arg("-I");
+        cpp.add_arg(recipe_path);
+        cpp.add_arg("-I");
+        cpp.add_arg(root_dir_path.join("usr/include"));
+        cpp.add_arg("-o");
+        cpp.add_arg(&preprocessed);
+        cpp.add_arg(&source_path);
+        run_cmd(logger, cpp).whatever("unable to preprocess device tree source")?;
+        let mut dtc = Cmd::new("dtc");
+        dtc.extend_args(["-@", "-I", "dts", "-O", "dtb", "-o"]);
+        dtc.add_arg(&output);
+        dtc.add_arg(&preprocessed);
+        run_cmd(logger, dtc).whatever_with(|_| format!("unable to compile {source:?}"))?;
+        if !is_overlay {
+            continue;
+        }
+        if base_dtbs.is_empty() {
+            warn!("no device trees in the boot partition, unable to check overlay {source:?}");
+            continue;
+        }
+        let mut applies = false;
+        let mut errors = Vec::new();
+        for base in &base_dtbs {
+            let check = Command::new("fdtoverlay")
+                .arg("-i")
+                .arg(base)
+                .arg("-o")
+                .arg(temp_dir.path().join("check.dtb"))
+                .arg(&output)
+                .stdin(Stdio::null())
+                .output()
+                .whatever("unable to run `fdtoverlay`")?;
+            if check.status.success() {
+                applies = true;
+                break;
+            }
+            errors.push(format!(
+                "{}: {}",
+                base.file_name().unwrap().to_string_lossy(),
+                String::from_utf8_lossy(&check.stderr).trim()
+            ));
+        }
+        if !applies {
+            bail!(
+                "overlay {source:?} cannot be applied to any device tree in the boot partition:\n{}",
+                errors.join("\n")
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Static delta bundles against previous images.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use rugix_bundle::manifest::{
+    BlockEncoding, BundleManifest, ChunkerAlgorithm, Compression, DeliveryConfig, DeltaEncoding,
+    DeltaEncodingFormat, DeltaEncodingInput, XzCompression,
+};
+use rugix_bundle::xdelta::xdelta_compress;
+use rugix_common::disk::PartitionTable;
+use si_crypto_hashes::{HashAlgorithm, HashDigest};
+use tempfile::tempdir;
+use tracing::info;
+
+use crate::BakeryResult;
+
+/// Encode the payloads of a bundle as deltas against the partitions of an old image.
+///
+/// The new payloads are read from the `filesystems` directory of the system and the
+/// patches are written to the given payloads directory. As the boot partition is usually
+/// modified on the device, the delta for the `boot` slot is computed against the `system`
+/// partition of the old image.
+pub fn encode_payloads(
+    manifest: &mut BundleManifest,
+    filesystems_dir: &Path,
+    old_image: &Path,
+    payloads_dir: &Path,
+    compress: bool,
+) -> BakeryResult<()> {
+    let table =
+        PartitionTable::read(old_image).whatever("unable to read partition table of old image")?;
+    let hash_algorithm = manifest.hash_algorithm.unwrap_or(HashAlgorithm::Sha512_256);
+    let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+    let slot_filenames = manifest
+        .payloads
+        .iter()
+        .filter_map(|payload| match &payload.delivery {
+            DeliveryConfig::Slot(config) => Some((config.slot.clone(), payload.filename.clone())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for payload in &mut manifest.payloads {
+        let DeliveryConfig::Slot(config) = &payload.delivery else {
+            continue;
+        };
+        if payload.verity.is_some() {
+            bail!("delta bundles are not supported for slots with dm-verity");
+        }
+        let old_slot = if config.slot == "boot" {
+            "system"
+        } else {
+            config.slot.as_str()
+        };
+        let Some((_, old_filename)) = slot_filenames.iter().find(|(slot, _)| slot == old_slot)
+        else {
+            bail!("unable to find payload of slot `{old_slot}`");
+        };
+        let Some(partition) = old_filename
+            .strip_prefix("partition-")
+            .and_then(|name| name.strip_suffix(".img"))
+            .and_then(|number| number.parse::<usize>().ok())
+        else {
+            bail!("unable to determine partition of payload {old_filename:?}");
+        };
+        let Some(old_partition) = partition
+            .checked_sub(1)
+            .and_then(|idx| table.partitions.get(idx))
+        else {
+            bail!("old image does not have a partition {partition}");
+        };
+        info!(
+            "computing delta for slot `{}` against `{old_slot}`",
+            config.slot
+        );
+        let old_path = temp_dir.path().join(old_filename);
+        if !old_path.exists() {
+            extract_range(
+                old_image,
+                table.blocks_to_bytes(old_partition.start).into_raw(),
+                table.blocks_to_bytes(old_partition.size).into_raw(),
+                &old_path,
+            )
+            .whatever("unable to extract partition from old image")?;
+        }
+        let new_path = filesystems_dir.join(&payload.filename);
+        let patch_filename = format!("{}.xdelta", payload.filename);
+        xdelta_compress(&old_path, &new_path, &payloads_dir.join(&patch_filename))
+            .whatever("unable to compute delta")?;
+        let old_hash = hash_file(hash_algorithm, &old_path).whatever("unable to hash old slot")?;
+        let new_hash = hash_file(hash_algorithm, &new_path).whatever("unable to hash payload")?;
+        payload.filename = patch_filename;
+        payload.block_encoding = Some(
+            BlockEncoding::new(ChunkerAlgorithm::Fixed {
+                block_size_kib: 256,
+            })
+            .with_compression(compress.then(|| Compression::Xz(XzCompression::new()))),
+        );
+        payload.delta_encoding = Some(DeltaEncoding::new(
+            vec![DeltaEncodingInput {
+                hashes: vec![old_hash],
+            }],
+            DeltaEncodingFormat::Xdelta,
+            new_hash,
+        ));
+    }
+    Ok(())
+}
+
+/// Extract a range of bytes from a file.
+fn extract_range(src: &Path, offset: u64, length: u64, dst: &Path) -> io::Result<()> {
+    let mut src = fs::File::open(src)?;
+    src.seek(SeekFrom::Start(offset))?;
+    let mut dst = fs::File::create(dst)?;
+    io::copy(&mut src.take(length), &mut dst)?;
+    Ok(())
+}
+
+/// Compute the hash of a file.
+fn hash_file(algorithm: HashAlgorithm, path: &Path) -> io::Result<HashDigest> {
+    let mut hasher = algorithm.hasher();
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break Ok(hasher.finalize());
+        }
+        hasher.update(buffer);
+        let consumed = buffer.len();
+        reader.consume(consumed);
+    }
+}
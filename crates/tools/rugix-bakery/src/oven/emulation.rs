@@ -0,0 +1,187 @@
+//! Emulation of foreign architectures with QEMU user mode emulators.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, ResultExt};
+use tracing::{info, warn};
+use xscript::{run, Run};
+
+use crate::config::systems::Architecture;
+use crate::BakeryResult;
+
+/// Directory where the `binfmt_misc` filesystem is mounted.
+const BINFMT_MISC_DIR: &str = "/proc/sys/fs/binfmt_misc";
+
+/// QEMU user mode emulator of an architecture.
+struct Emulator {
+    /// Architecture name used by QEMU.
+    name: &'static str,
+    /// Magic bytes of ELF headers of the architecture.
+    magic: &'static str,
+    /// Mask applied to ELF headers before comparing them with the magic bytes.
+    mask: &'static str,
+}
+
+impl Emulator {
+    fn for_arch(arch: Architecture) -> Self {
+        // Magic bytes and masks are taken from QEMU's `qemu-binfmt-conf.sh`.
+        match arch {
+            Architecture::Amd64 => Self {
+                name: "x86_64",
+                magic: r"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\x3e\x00",
+                mask: r"\xff\xff\xff\xff\xff\xfe\xfe\x00\xff\xff\xff\xff\xff\xff\xff\xff\xfe\xff\xff\xff",
+            },
+            Architecture::Arm64 => Self {
+                name: "aarch64",
+                magic: r"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\xb7\x00",
+                mask: r"\xff\xff\xff\xff\xff\xff\xff\x00\xff\xff\xff\xff\xff\xff\xff\xff\xfe\xff\xff\xff",
+            },
+            Architecture::Armv7 | Architecture::Armhf | Architecture::Arm => Self {
+                name: "arm",
+                magic: r"\x7fELF\x01\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00\x28\x00",
+                mask: r"\xff\xff\xff\xff\xff\xff\xff\x00\xff\xff\xff\xff\xff\xff\xff\xff\xfe\xff\xff\xff",
+            },
+        }
+    }
+
+    /// Find the statically-linked emulator binary.
+    fn find_interpreter(&self) -> Option<PathBuf> {
+        [
+            format!("/usr/bin/qemu-{}-static", self.name),
+            format!("/usr/libexec/qemu-binfmt/{}-binfmt-P", self.name),
+            format!("/usr/local/bin/qemu-{}-static", self.name),
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+    }
+}
+
+/// Check whether the host executes binaries of the given architecture natively.
+///
+/// We assume that 64-bit ARM hosts are able to execute 32-bit ARM binaries.
+fn is_native(arch: Architecture) -> bool {
+    matches!(
+        (std::env::consts::ARCH, arch),
+        ("x86_64", Architecture::Amd64)
+            | (
+                "aarch64",
+                Architecture::Arm64 | Architecture::Armv7 | Architecture::Armhf | Architecture::Arm
+            )
+            | (
+                "arm",
+                Architecture::Armv7 | Architecture::Armhf | Architecture::Arm
+            )
+    )
+}
+
+/// State of the emulation of an architecture.
+pub enum EmulationStatus {
+    /// The host executes binaries of the architecture natively.
+    Native,
+    /// An emulator is registered with `binfmt_misc`.
+    Registered {
+        /// Indicates whether the emulator is registered with the `F` flag.
+        fix_binary: bool,
+    },
+    /// An emulator is registered with `binfmt_misc` but disabled.
+    Disabled {
+        /// Path of the `binfmt_misc` entry.
+        entry_path: PathBuf,
+    },
+    /// No emulator is registered with `binfmt_misc`.
+    Unregistered {
+        /// Emulator binary which can be registered, if it is installed.
+        interpreter: Option<PathBuf>,
+    },
+}
+
+/// Determine the state of the emulation of the given architecture.
+pub fn emulation_status(arch: Architecture) -> EmulationStatus {
+    if is_native(arch) {
+        return EmulationStatus::Native;
+    }
+    let emulator = Emulator::for_arch(arch);
+    let entry_path = Path::new(BINFMT_MISC_DIR).join(format!("qemu-{}", emulator.name));
+    let Ok(entry) = fs::read_to_string(&entry_path) else {
+        return EmulationStatus::Unregistered {
+            interpreter: emulator.find_interpreter(),
+        };
+    };
+    if !entry.lines().any(|line| line == "enabled") {
+        return EmulationStatus::Disabled { entry_path };
+    }
+    let fix_binary = entry
+        .lines()
+        .find_map(|line| line.strip_prefix("flags: "))
+        .is_some_and(|flags| flags.contains('F'));
+    EmulationStatus::Registered { fix_binary }
+}
+
+/// Ensure that binaries of the given architecture can be executed.
+///
+/// If emulation has not been set up on the host, the QEMU user mode emulator of the
+/// architecture is registered with `binfmt_misc`. Note that `binfmt_misc` is global,
+/// hence, the registration also affects the host.
+pub fn ensure_emulation(arch: Architecture) -> BakeryResult<()> {
+    if is_native(arch) {
+        return Ok(());
+    }
+    let emulator = Emulator::for_arch(arch);
+    let binfmt_dir = Path::new(BINFMT_MISC_DIR);
+    if !binfmt_dir.join("register").exists() {
+        info!("mounting `binfmt_misc` filesystem");
+        run!(["mount", "-t", "binfmt_misc", "binfmt_misc", BINFMT_MISC_DIR]).whatever_with(
+            |_| {
+                format!(
+                    "emulation for `{arch}` requires `binfmt_misc`, which is not available\n\n\
+                    Please configure emulation on the host, e.g., with:\n\n    \
+                    docker run --privileged --rm tonistiigi/binfmt --install {arch}"
+                )
+            },
+        )?;
+    }
+    let interpreter = match emulation_status(arch) {
+        EmulationStatus::Native => return Ok(()),
+        EmulationStatus::Registered { fix_binary } => {
+            if !fix_binary {
+                // Without the `F` flag, the emulator must exist in the root filesystem.
+                warn!(
+                    "emulation for `{arch}` is registered without the `F` flag, \
+                    executing binaries of the system may fail"
+                );
+            }
+            return Ok(());
+        }
+        EmulationStatus::Disabled { entry_path } => {
+            bail!("emulation for `{arch}` is registered but disabled (see {entry_path:?})");
+        }
+        EmulationStatus::Unregistered { interpreter: None } => {
+            bail!(
+                "emulation for `{arch}` is not set up and `qemu-{}-static` is not installed\n\n\
+                Please configure emulation on the host, e.g., with:\n\n    \
+                docker run --privileged --rm tonistiigi/binfmt --install {arch}",
+                emulator.name
+            );
+        }
+        EmulationStatus::Unregistered {
+            interpreter: Some(interpreter),
+        } => interpreter,
+    };
+    info!("registering {interpreter:?} for `{arch}` with `binfmt_misc`");
+    let rule = format!(
+        ":qemu-{}:M::{}:{}:{}:F",
+        emulator.name,
+        emulator.magic,
+        emulator.mask,
+        interpreter.display()
+    );
+    fs::write(binfmt_dir.join("register"), rule).whatever_with(|_| {
+        format!(
+            "unable to register emulation for `{arch}`, are you running Rugix Bakery \
+            with sufficient privileges?"
+        )
+    })?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Encryption of partitions with LUKS2.
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+use xscript::{cmd_os, ParentEnv, Run};
+
+use crate::config::images::PartitionEncryption;
+use crate::BakeryResult;
+
+/// Space reserved for the LUKS2 header.
+///
+/// `cryptsetup reencrypt --reduce-device-size` places the header at the start of the
+/// device and shifts the data towards the end by this amount. Hence, this space must be
+/// unused at the end of the filesystem. `cryptsetup` recommends twice the size of the
+/// default LUKS2 header.
+pub const LUKS_SIZE_REDUCTION: u64 = 32 * 1024 * 1024;
+
+/// Encrypt the filesystem image in place, turning it into a LUKS2 container.
+///
+/// The filesystem must leave [`LUKS_SIZE_REDUCTION`] bytes of the partition unused, which
+/// are taken up by the header at the start of the container after encrypting it. The
+/// image is extended to the size of the partition before encrypting it.
+pub fn encrypt_filesystem(
+    fs_image: &Path,
+    partition_size: u64,
+    encryption: &PartitionEncryption,
+) -> BakeryResult<()> {
+    info!("encrypting filesystem {fs_image:?}");
+    let key_file = Path::new(&encryption.key_file);
+    if !key_file.is_file() {
+        bail!("encryption key file {key_file:?} does not exist");
+    }
+    let fs_size = fs::metadata(fs_image)
+        .whatever("unable to read filesystem image metadata")?
+        .len();
+    if fs_size + LUKS_SIZE_REDUCTION > partition_size {
+        bail!("filesystem is too large for encrypted partition");
+    }
+    File::options()
+        .write(true)
+        .open(fs_image)
+        .and_then(|file| file.set_len(partition_size))
+        .whatever("unable to extend filesystem image")?;
+    let mut cmd = cmd_os!(
+        "cryptsetup",
+        "reencrypt",
+        "--encrypt",
+        "--batch-mode",
+        "--type",
+        "luks2",
+        "--reduce-device-size",
+        format!("{}M", LUKS_SIZE_REDUCTION / (1024 * 1024)),
+        "--key-file",
+        key_file
+    );
+    if let Some(pbkdf) = &encryption.pbkdf {
+        cmd.extend_args(["--pbkdf", pbkdf.as_str()]);
+    }
+    cmd.add_arg(fs_image);
+    ParentEnv
+        .run(cmd)
+        .whatever("unable to encrypt filesystem")?;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Exporting the root filesystem of layers as standalone artifacts.
+
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+use reportify::{bail, ResultExt};
+use serde::{Deserialize, Serialize};
+use tempfile::tempdir;
+use tracing::info;
+use xscript::{read_str, run, Run};
+
+use crate::config::systems::Architecture;
+use crate::BakeryResult;
+
+/// File in exported layers recording the layer and its architecture.
+const LAYER_INFO_FILE: &str = "./layer-info.json";
+
+/// Information about an exported layer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LayerInfo {
+    /// Name of the layer.
+    pub layer: String,
+    /// Architecture the layer has been baked for.
+    pub arch: String,
+}
+
+/// Read the information about a layer exported with the format `layer`.
+pub fn read_layer_info(artifact: &Path) -> BakeryResult<LayerInfo> {
+    let Ok(info) = read_str!(["tar", "-x", "-O", "-f", artifact, LAYER_INFO_FILE]) else {
+        bail!("layer artifact {artifact:?} has no layer information, export it again");
+    };
+    serde_json::from_str(&info).whatever("unable to parse layer information")
+}
+
+/// Format of exported layers.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LayerExportFormat {
+    /// Tarball (compressed based on the file extension of the output path).
+    Tar,
+    /// Squashfs image.
+    Squashfs,
+    /// Complete layer, including boot files and artifacts, for `bake image --from-layer`.
+    Layer,
+}
+
+impl LayerExportFormat {
+    /// File extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            LayerExportFormat::Tar => "tar",
+            LayerExportFormat::Squashfs => "squashfs",
+            LayerExportFormat::Layer => "layer.tar",
+        }
+    }
+}
+
+/// Export the root filesystem of a baked layer in the given format.
+///
+/// With the format `layer`, the layer and its architecture are recorded in the artifact,
+/// such that images are only assembled from artifacts of the layer of the system.
+pub fn export_layer(
+    system_tar: &Path,
+    layer: &str,
+    arch: Architecture,
+    format: LayerExportFormat,
+    output: &Path,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    if let LayerExportFormat::Layer = format {
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent).whatever("unable to create output directory")?;
+        }
+        info!("exporting layer to {output:?}");
+        fs::copy(system_tar, output).whatever("unable to copy layer")?;
+        let info_dir = tempdir().whatever("unable to create temporary directory")?;
+        let info = LayerInfo {
+            layer: layer.to_owned(),
+            arch: arch.as_str().to_owned(),
+        };
+        fs::write(
+            info_dir.path().join(LAYER_INFO_FILE),
+            serde_json::to_string_pretty(&info).unwrap(),
+        )
+        .whatever("unable to write layer information")?;
+        run!([
+            "tar",
+            "--append",
+            format!("--mtime=@{source_date_epoch}"),
+            "--owner=0",
+            "--group=0",
+            "--numeric-owner",
+            "-f",
+            output,
+            "-C",
+            info_dir.path(),
+            LAYER_INFO_FILE
+        ])
+        .whatever("unable to record layer information")?;
+        return Ok(());
+    }
+    let bundle_dir = tempdir().whatever("unable to create temporary directory")?;
+    let bundle_dir = bundle_dir.path();
+    info!("extracting layer");
+    run!([
+        "tar",
+        "-x",
+        "-f",
+        system_tar,
+        "-C",
+        bundle_dir,
+        "./roots/system"
+    ])
+    .whatever("unable to extract root filesystem of layer")?;
+    let root_dir = bundle_dir.join("roots/system");
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).whatever("unable to create output directory")?;
+    }
+    fs::remove_file(output).ok();
+    info!("exporting root filesystem to {output:?}");
+    match format {
+        LayerExportFormat::Tar => {
+            run!([
+                "tar",
+                "--sort=name",
+                "--pax-option=exthdr.name=%d/PaxHeaders/%f,delete=atime,delete=ctime",
+                "--clamp-mtime",
+                format!("--mtime=@{source_date_epoch}"),
+                "--numeric-owner",
+                "--auto-compress",
+                "-c",
+                "-f",
+                output,
+                "-C",
+                &root_dir,
+                "."
+            ])
+            .whatever("unable to create tarball")?;
+        }
+        LayerExportFormat::Squashfs => {
+            run!([
+                "mksquashfs",
+                &root_dir,
+                output,
+                "-noappend",
+                "-mkfs-time",
+                source_date_epoch.to_string()
+            ])
+            .whatever("unable to create squashfs image")?;
+        }
+        LayerExportFormat::Layer => unreachable!("layers are exported as is"),
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Installation of recipe files with explicit ownership and permissions.
+
+use std::fs;
+use std::os::unix::fs::{lchown, symlink, PermissionsExt};
+use std::path::Path;
+use std::sync::Once;
+
+use reportify::{bail, whatever, ResultExt};
+use rugix_common::fsutils::resolve_in_root;
+use tracing::{info, warn};
+
+use crate::config::recipes::FilesConfig;
+use crate::utils::build_env::Isolation;
+use crate::BakeryResult;
+
+/// Directory of the recipe copied into the root filesystem by default.
+const DEFAULT_SOURCE: &str = "root";
+
+/// Name of the user and group owning files by default.
+const DEFAULT_OWNER: &str = "root";
+
+/// Permissions of copied directories and executable files.
+const EXECUTABLE_MODE: u32 = 0o755;
+
+/// Permissions of copied regular files.
+const REGULAR_MODE: u32 = 0o644;
+
+/// Parse octal permissions.
+pub(crate) fn parse_mode(mode: &str) -> BakeryResult<u32> {
+    u32::from_str_radix(mode, 8).map_err(|_| whatever!("invalid mode {mode:?}"))
+}
+
+/// Resolve the name or numeric id of a user or group with the given database of the
+/// root filesystem, e.g., `etc/passwd`.
+fn resolve_id(root_dir: &Path, database: &str, name: &str) -> BakeryResult<u32> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+    let entries = fs::read_to_string(root_dir.join(database)).unwrap_or_default();
+    for line in entries.lines() {
+        let mut fields = line.split(':');
+        if fields.next() == Some(name) {
+            if let Some(id) = fields.nth(1).and_then(|id| id.parse().ok()) {
+                return Ok(id);
+            }
+        }
+    }
+    if name == DEFAULT_OWNER {
+        return Ok(0);
+    }
+    bail!("unable to find {name:?} in `/{database}` of the system")
+}
+
+/// Change the owner and group of a path without following symbolic links.
+///
+/// With Bubblewrap isolation, Rugix Bakery runs unprivileged and the files of a layer
+/// are packed as owned by root. Hence, other owners cannot be applied and are ignored.
+pub(crate) fn set_owner(path: &Path, uid: Option<u32>, gid: Option<u32>) -> BakeryResult<()> {
+    if Isolation::from_env()? == Isolation::Bwrap {
+        if uid.is_some_and(|uid| uid != 0) || gid.is_some_and(|gid| gid != 0) {
+            static WARNING: Once = Once::new();
+            WARNING.call_once(|| {
+                warn!("ignoring owners other than root, which require `chroot` isolation");
+            });
+        }
+        return Ok(());
+    }
+    lchown(path, uid, gid).whatever_with(|_| format!("unable to change owner of {path:?}"))
+}
+
+/// Resolve the owner and group of files in the root filesystem.
+pub(crate) fn resolve_ownership(
+    root_dir: &Path,
+    owner: Option<&str>,
+    group: Option<&str>,
+) -> BakeryResult<(Option<u32>, Option<u32>)> {
+    let uid = owner
+        .map(|owner| resolve_id(root_dir, "etc/passwd", owner))
+        .transpose()?;
+    let gid = group
+        .map(|group| resolve_id(root_dir, "etc/group", group))
+        .transpose()?;
+    Ok((uid, gid))
+}
+
+/// Copy a directory of a recipe into the root filesystem and apply the configured
+/// ownership and permissions.
+pub fn install_files(config: &FilesConfig, recipe_dir: &Path, root_dir: &Path) -> BakeryResult<()> {
+    let source = recipe_dir.join(config.source.as_deref().unwrap_or(DEFAULT_SOURCE));
+    if !source.is_dir() {
+        bail!("files directory {source:?} does not exist");
+    }
+    let (uid, gid) = resolve_ownership(
+        root_dir,
+        Some(config.owner.as_deref().unwrap_or(DEFAULT_OWNER)),
+        Some(config.group.as_deref().unwrap_or(DEFAULT_OWNER)),
+    )?;
+    info!("copying files from {source:?}");
+    copy_tree(&source, root_dir, uid, gid)?;
+    for attributes in config.paths.as_deref().unwrap_or_default() {
+        let path = root_dir.join(attributes.path.trim_start_matches('/'));
+        if fs::symlink_metadata(&path).is_err() {
+            bail!("path {:?} does not exist", attributes.path);
+        }
+        let (uid, gid) = resolve_ownership(
+            root_dir,
+            attributes.owner.as_deref(),
+            attributes.group.as_deref(),
+        )?;
+        if uid.is_some() || gid.is_some() {
+            set_owner(&path, uid, gid)?;
+        }
+        if let Some(mode) = &attributes.mode {
+            // Symbolic links do not have permissions, so they apply to the target, which
+            // must be resolved within the root filesystem instead of on the host.
+            let target = resolve_in_root(root_dir, Path::new(&attributes.path))
+                .whatever_with(|_| format!("unable to resolve {:?}", attributes.path))?;
+            fs::set_permissions(&target, fs::Permissions::from_mode(parse_mode(mode)?))
+                .whatever_with(|_| format!("unable to set permissions of {:?}", attributes.path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Recursively copy the given directory, owning the copied files by the given user and
+/// group.
+///
+/// Directories which already exist in the root filesystem are left untouched.
+pub(crate) fn copy_tree(
+    source: &Path,
+    target: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+) -> BakeryResult<()> {
+    let mut entries = fs::read_dir(source)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .whatever_with(|_| format!("unable to read directory {source:?}"))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let source_path = entry.path();
+        let target_path = target.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .whatever("unable to determine file type")?;
+        let existing = fs::symlink_metadata(&target_path).ok();
+        if file_type.is_dir() {
+            match &existing {
+                Some(metadata) if metadata.file_type().is_symlink() => {
+                    // Absolute symlinks would point outside of the root filesystem.
+                    let link = fs::read_link(&target_path).whatever("unable to read symlink")?;
+                    if link.is_absolute() {
+                        bail!("unable to copy into {target_path:?}, which is an absolute symlink");
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    fs::create_dir(&target_path)
+                        .whatever_with(|_| format!("unable to create {target_path:?}"))?;
+                    fs::set_permissions(&target_path, fs::Permissions::from_mode(EXECUTABLE_MODE))
+                        .whatever("unable to set permissions")?;
+                    set_owner(&target_path, uid, gid)?;
+                }
+            }
+            copy_tree(&source_path, &target_path, uid, gid)?;
+        } else {
+            if existing.is_some() {
+                fs::remove_file(&target_path)
+                    .whatever_with(|_| format!("unable to replace {target_path:?}"))?;
+            }
+            if file_type.is_symlink() {
+                let link = fs::read_link(&source_path).whatever("unable to read symlink")?;
+                symlink(link, &target_path)
+                    .whatever_with(|_| format!("unable to create symlink {target_path:?}"))?;
+            } else {
+                fs::copy(&source_path, &target_path)
+                    .whatever_with(|_| format!("unable to copy {source_path:?}"))?;
+                let source_mode = entry
+                    .metadata()
+                    .whatever("unable to read metadata")?
+                    .permissions()
+                    .mode();
+                let mode = if source_mode & 0o111 != 0 {
+                    EXECUTABLE_MODE
+                } else {
+                    REGULAR_MODE
+                };
+                fs::set_permissions(&target_path, fs::Permissions::from_mode(mode))
+                    .whatever("unable to set permissions")?;
+            }
+            set_owner(&target_path, uid, gid)?;
+        }
+    }
+    Ok(())
+}
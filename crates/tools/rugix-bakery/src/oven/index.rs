@@ -0,0 +1,281 @@
+//! Index of the files and packages of the partitions of a system.
+//!
+//! The index is written to [`INDEX_FILE`] in the build directory of a system and allows
+//! comparing built systems without their root filesystems.
+
+use std::collections::BTreeMap;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::{fs, io};
+
+use reportify::ResultExt;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::config::images::ImageLayout;
+use crate::config::load_json;
+use crate::oven::artifacts::sha256_file;
+use crate::oven::packages::{self, Package};
+use crate::BakeryResult;
+
+/// Name of the index file in the build directory of a system.
+pub const INDEX_FILE: &str = "index.json";
+
+/// Index of a system.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SystemIndex {
+    /// Indices of the partitions by their root directory.
+    pub partitions: BTreeMap<String, PartitionIndex>,
+}
+
+/// Index of the contents of a partition.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PartitionIndex {
+    /// Files by their absolute path.
+    pub files: BTreeMap<String, FileEntry>,
+    /// Installed packages by their name.
+    pub packages: BTreeMap<String, Package>,
+}
+
+/// Kind of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+/// Entry of a file in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub kind: FileKind,
+    /// Permission bits of the file.
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// Hex-encoded SHA-256 hash of regular files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Target of symbolic links.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Load the index of the system in the given build directory.
+pub fn load_index(system_path: &Path) -> BakeryResult<SystemIndex> {
+    load_json(&system_path.join(INDEX_FILE))
+}
+
+/// Index the contents of the partitions and write the index to the build directory.
+pub fn write_index(layout: &ImageLayout, roots_dir: &Path, out: &Path) -> BakeryResult<()> {
+    info!("indexing files of partitions");
+    let mut index = SystemIndex::default();
+    for partition in layout.partitions.iter().flatten() {
+        let Some(root) = &partition.root else {
+            continue;
+        };
+        let root_dir = roots_dir.join(root);
+        let mut files = BTreeMap::new();
+        if root_dir.exists() {
+            index_dir(&root_dir, &root_dir, &mut files)
+                .whatever_with(|_| format!("unable to index {root_dir:?}"))?;
+        }
+        index.partitions.insert(
+            root.clone(),
+            PartitionIndex {
+                files,
+                packages: packages::installed_packages(&root_dir),
+            },
+        );
+    }
+    fs::write(out.join(INDEX_FILE), serde_json::to_string(&index).unwrap())
+        .whatever("unable to write index")?;
+    Ok(())
+}
+
+/// Recursively index the files of a directory.
+fn index_dir(root: &Path, dir: &Path, files: &mut BTreeMap<String, FileEntry>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let metadata = entry.metadata()?;
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_file() {
+            FileKind::File
+        } else if file_type.is_dir() {
+            FileKind::Directory
+        } else if file_type.is_symlink() {
+            FileKind::Symlink
+        } else {
+            FileKind::Other
+        };
+        let relative = Path::new("/").join(path.strip_prefix(root).unwrap());
+        files.insert(
+            relative.to_string_lossy().into_owned(),
+            FileEntry {
+                kind,
+                mode: metadata.permissions().mode() & 0o7777,
+                uid: metadata.uid(),
+                gid: metadata.gid(),
+                size: if kind == FileKind::File {
+                    metadata.len()
+                } else {
+                    0
+                },
+                sha256: if kind == FileKind::File {
+                    Some(sha256_file(&path)?)
+                } else {
+                    None
+                },
+                target: if kind == FileKind::Symlink {
+                    Some(fs::read_link(&path)?.to_string_lossy().into_owned())
+                } else {
+                    None
+                },
+            },
+        );
+        if kind == FileKind::Directory {
+            index_dir(root, &path, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// Differences between two systems.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemDiff {
+    /// Differences of the partitions by their root directory.
+    pub partitions: BTreeMap<String, PartitionDiff>,
+}
+
+/// Differences between the contents of two partitions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PartitionDiff {
+    /// Total size of the files of the old partition.
+    pub old_size: u64,
+    /// Total size of the files of the new partition.
+    pub new_size: u64,
+    /// Files which have been added.
+    pub added: Vec<FileChange>,
+    /// Files which have been removed.
+    pub removed: Vec<FileChange>,
+    /// Files which have been changed.
+    pub changed: Vec<FileChange>,
+    /// Packages which have been added, removed, or changed their version.
+    pub packages: Vec<PackageChange>,
+}
+
+impl PartitionDiff {
+    /// Check whether the partitions are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.packages.is_empty()
+    }
+}
+
+/// Change of a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// Size of the old file, if it exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_size: Option<u64>,
+    /// Size of the new file, if it exists.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_size: Option<u64>,
+}
+
+impl FileChange {
+    /// Difference of the size in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// Change of a package.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageChange {
+    pub name: String,
+    /// Version of the old package, if it is installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_version: Option<String>,
+    /// Version of the new package, if it is installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_version: Option<String>,
+}
+
+/// Compute the differences between two systems.
+pub fn diff(old: &SystemIndex, new: &SystemIndex) -> SystemDiff {
+    let empty = PartitionIndex::default();
+    let mut diff = SystemDiff::default();
+    let roots = old.partitions.keys().chain(new.partitions.keys());
+    for root in roots {
+        if diff.partitions.contains_key(root) {
+            continue;
+        }
+        let old = old.partitions.get(root).unwrap_or(&empty);
+        let new = new.partitions.get(root).unwrap_or(&empty);
+        diff.partitions
+            .insert(root.clone(), diff_partitions(old, new));
+    }
+    diff
+}
+
+fn diff_partitions(old: &PartitionIndex, new: &PartitionIndex) -> PartitionDiff {
+    let mut diff = PartitionDiff {
+        old_size: old.files.values().map(|file| file.size).sum(),
+        new_size: new.files.values().map(|file| file.size).sum(),
+        ..PartitionDiff::default()
+    };
+    for (path, old_file) in &old.files {
+        match new.files.get(path) {
+            Some(new_file) if new_file == old_file => { /* unchanged */ }
+            Some(new_file) => diff.changed.push(FileChange {
+                path: path.clone(),
+                old_size: Some(old_file.size),
+                new_size: Some(new_file.size),
+            }),
+            None => diff.removed.push(FileChange {
+                path: path.clone(),
+                old_size: Some(old_file.size),
+                new_size: None,
+            }),
+        }
+    }
+    for (path, new_file) in &new.files {
+        if !old.files.contains_key(path) {
+            diff.added.push(FileChange {
+                path: path.clone(),
+                old_size: None,
+                new_size: Some(new_file.size),
+            });
+        }
+    }
+    for (name, old_package) in &old.packages {
+        let new_version = new.packages.get(name).map(|package| &package.version);
+        if new_version != Some(&old_package.version) {
+            diff.packages.push(PackageChange {
+                name: name.clone(),
+                old_version: Some(old_package.version.clone()),
+                new_version: new_version.cloned(),
+            });
+        }
+    }
+    for (name, new_package) in &new.packages {
+        if !old.packages.contains_key(name) {
+            diff.packages.push(PackageChange {
+                name: name.clone(),
+                old_version: None,
+                new_version: Some(new_package.version.clone()),
+            });
+        }
+    }
+    diff.packages.sort_by(|a, b| a.name.cmp(&b.name));
+    diff
+}
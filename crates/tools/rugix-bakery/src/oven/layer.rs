@@ -21,6 +21,10 @@ impl FrozenLayer {
         Self { name, path }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn last_modified(&self) -> BakeryResult<ModificationTime> {
         mtime(&self.path).whatever_with(|_| {
             format!(
@@ -66,6 +70,8 @@ impl AsRef<Path> for Layer {
 
 pub struct LayerContext {
     pub project: ProjectRef,
+    pub layer_name: String,
+    pub aggregate_packages: bool,
     pub build_dir: PathBuf,
     pub output_dir: PathBuf,
 }
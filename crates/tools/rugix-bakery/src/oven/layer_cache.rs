@@ -0,0 +1,314 @@
+//! Shared cache of layers.
+//!
+//! Layers are stored under a key derived from the contents of their recipes, their
+//! parameters, and the keys of their parent layers. As recipes may register further
+//! files with `LAYER_REBUILD_IF_CHANGED`, which are only known after building the layer,
+//! the files are stored as `<key>.files`. The layer itself is stored as `<key>.tar`
+//! where the key additionally covers the contents of these files. The SHA256 digest of
+//! the layer is stored as `<key>.sha256` and verified before the layer is used.
+
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use reqwest::blocking::{Body, Client, RequestBuilder};
+use reqwest::StatusCode;
+use tracing::{info, warn};
+
+use crate::config::projects::{LayerCacheConfig, ProjectConfig};
+use crate::oven::artifacts::sha256_file;
+use crate::oven::customize::RecipeJob;
+use crate::oven::provenance::hash_dir;
+use crate::project::ProjectRef;
+use crate::utils::build_env;
+use crate::utils::caching::Hasher;
+use crate::BakeryResult;
+
+/// Environment variable with the token for the cache.
+const TOKEN_VAR: &str = "RUGIX_LAYER_CACHE_TOKEN";
+
+/// Files used by the recipes of a layer, relative to the layer's output directory.
+const USED_FILES: &str = "rebuild-if-changed.txt";
+
+/// Client of the shared layer cache.
+#[derive(Debug)]
+pub struct LayerCache {
+    /// URL of the cache without trailing slash.
+    url: String,
+    /// Token sent as bearer token.
+    token: Option<String>,
+    /// Indicates whether layers built locally are uploaded.
+    push: bool,
+    /// HTTP client.
+    client: Client,
+}
+
+/// Layer in the shared cache.
+#[derive(Debug)]
+pub struct CachedLayer<'c> {
+    /// Cache the layer is stored in.
+    pub cache: &'c LayerCache,
+    /// Key of the layer.
+    pub key: String,
+}
+
+impl LayerCache {
+    /// Create a client for the cache configured in the project, if any.
+    ///
+    /// Returns `None` when baking offline.
+    pub fn from_project(project: &ProjectRef) -> BakeryResult<Option<Self>> {
+        let Some(config) = &project.config().layer_cache else {
+            return Ok(None);
+        };
+        if build_env::is_offline() {
+            return Ok(None);
+        }
+        Self::new(config).map(Some)
+    }
+
+    /// Create a client for the given cache.
+    pub fn new(config: &LayerCacheConfig) -> BakeryResult<Self> {
+        let client = Client::builder()
+            // Layers are large, so we must not time out while transferring them.
+            .timeout(None)
+            .build()
+            .whatever("unable to create HTTP client")?;
+        Ok(Self {
+            url: config.url.trim_end_matches('/').to_owned(),
+            token: std::env::var(TOKEN_VAR)
+                .ok()
+                .filter(|token| !token.is_empty()),
+            push: config.push.unwrap_or(true),
+            client,
+        })
+    }
+
+    fn get(&self, name: &str) -> RequestBuilder {
+        self.authorize(self.client.get(format!("{}/{name}", self.url)))
+    }
+
+    fn put(&self, name: &str) -> RequestBuilder {
+        self.authorize(self.client.put(format!("{}/{name}", self.url)))
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+/// Add the configuration of the project affecting how layers are built to the key of a
+/// layer.
+///
+/// The systems, the container, the cache, and the release do not affect layers.
+pub fn hash_project_config(hasher: &mut Hasher, config: &ProjectConfig) {
+    let mut repositories = config.repositories.iter().flatten().collect::<Vec<_>>();
+    repositories.sort_by_key(|(name, _)| *name);
+    for (name, source) in repositories {
+        hasher.push("repository", name);
+        hasher.push("source", serde_json::to_string(source).unwrap());
+    }
+    let mut registries = config.registries.iter().flatten().collect::<Vec<_>>();
+    registries.sort();
+    for (name, url) in registries {
+        hasher.push("registry", format!("{name}={url}"));
+    }
+    if let Some(apt) = &config.apt {
+        hasher.push("apt", serde_json::to_string(apt).unwrap());
+    }
+}
+
+/// Add the contents and parameters of the given recipes to the key of a layer.
+pub fn hash_recipes(hasher: &mut Hasher, jobs: &[RecipeJob]) -> BakeryResult<()> {
+    for job in jobs {
+        hasher.push("recipe", job.recipe.name.as_bytes());
+        hasher.push(
+            "contents",
+            hash_dir(&job.recipe.path).whatever("unable to hash recipe")?,
+        );
+        let mut parameters = job.parameters.iter().collect::<Vec<_>>();
+        parameters.sort();
+        for (name, value) in parameters {
+            hasher.push("parameter", format!("{name}={value}"));
+        }
+    }
+    Ok(())
+}
+
+impl CachedLayer<'_> {
+    /// Try to retrieve the layer from the cache.
+    ///
+    /// Returns `false` if the layer is not in the cache. Errors are reported as warnings
+    /// such that the layer is built locally instead.
+    pub fn fetch(&self, project: &ProjectRef, target: &Path, layer_path: &Path) -> bool {
+        match self.try_fetch(project, target, layer_path) {
+            Ok(found) => found,
+            Err(error) => {
+                warn!("unable to retrieve layer from cache: {error:?}");
+                false
+            }
+        }
+    }
+
+    fn try_fetch(
+        &self,
+        project: &ProjectRef,
+        target: &Path,
+        layer_path: &Path,
+    ) -> BakeryResult<bool> {
+        let response = self
+            .cache
+            .get(&format!("{}.files", self.key))
+            .send()
+            .whatever("unable to connect to layer cache")?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let used_files = response
+            .error_for_status()
+            .whatever("unable to retrieve used files")?
+            .text()
+            .whatever("unable to retrieve used files")?;
+        let layer_key = self.layer_key(project, &used_files)?;
+        let response = self
+            .cache
+            .get(&format!("{layer_key}.sha256"))
+            .send()
+            .whatever("unable to connect to layer cache")?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let digest = response
+            .error_for_status()
+            .whatever("unable to retrieve layer digest")?
+            .text()
+            .whatever("unable to retrieve layer digest")?;
+        let mut response = self
+            .cache
+            .get(&format!("{layer_key}.tar"))
+            .send()
+            .whatever("unable to connect to layer cache")?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let mut response = response
+            .error_for_status()
+            .whatever("unable to retrieve layer")?;
+        info!("downloading layer from cache");
+        let partial = target.with_extension("partial");
+        let mut file = fs::File::create(&partial).whatever("unable to create layer file")?;
+        response
+            .copy_to(&mut file)
+            .whatever("unable to download layer")?;
+        drop(file);
+        // Corrupted or truncated layers must not be unpacked.
+        if sha256_file(&partial).whatever("unable to hash layer")? != digest.trim() {
+            fs::remove_file(&partial).ok();
+            bail!("digest of downloaded layer does not match");
+        }
+        fs::write(project.dir().join(layer_path).join(USED_FILES), used_files)
+            .whatever("unable to record used files")?;
+        fs::rename(&partial, target).whatever("unable to store layer")?;
+        Ok(true)
+    }
+
+    /// Upload the layer to the cache, if pushing is enabled.
+    ///
+    /// Errors are reported as warnings as the layer has been built successfully.
+    pub fn push(&self, project: &ProjectRef, target: &Path, layer_path: &Path) {
+        if !self.cache.push {
+            return;
+        }
+        if let Err(error) = self.try_push(project, target, layer_path) {
+            warn!("unable to upload layer to cache: {error:?}");
+        }
+    }
+
+    fn try_push(&self, project: &ProjectRef, target: &Path, layer_path: &Path) -> BakeryResult<()> {
+        let used_files =
+            fs::read_to_string(project.dir().join(layer_path).join(USED_FILES)).unwrap_or_default();
+        let layer_key = self.layer_key(project, &used_files)?;
+        info!("uploading layer to cache");
+        let digest = sha256_file(target).whatever("unable to hash layer")?;
+        let file = fs::File::open(target).whatever("unable to open layer")?;
+        let size = file.metadata().whatever("unable to open layer")?.len();
+        self.cache
+            .put(&format!("{layer_key}.tar"))
+            .body(Body::sized(file, size))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .whatever("unable to upload layer")?;
+        self.cache
+            .put(&format!("{layer_key}.sha256"))
+            .body(digest)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .whatever("unable to upload layer digest")?;
+        // The files are uploaded last such that the layer is only found when complete.
+        self.cache
+            .put(&format!("{}.files", self.key))
+            .body(used_files)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .whatever("unable to upload used files")?;
+        Ok(())
+    }
+
+    /// Compute the key of the layer including the contents of the used files.
+    fn layer_key(&self, project: &ProjectRef, used_files: &str) -> BakeryResult<String> {
+        let mut hasher = Hasher::new();
+        hasher.push("key", &self.key);
+        hash_used_files(&mut hasher, project.dir(), used_files.lines())?;
+        Ok(hasher.finalize())
+    }
+}
+
+/// Hash the contents of the files used by the recipes of a layer.
+///
+/// The paths of the files are relative to the given project directory.
+pub(crate) fn hash_used_files<'f>(
+    hasher: &mut Hasher,
+    project_dir: &Path,
+    used_files: impl IntoIterator<Item = &'f str>,
+) -> BakeryResult<()> {
+    for line in used_files {
+        let path = project_dir.join(line);
+        let hash = if path.is_dir() {
+            hash_dir(&path)
+        } else {
+            sha256_file(&path)
+        }
+        .whatever_with(|_| format!("unable to hash used file `{line}`"))?;
+        hasher.push("file", line);
+        hasher.push("contents", hash);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn used_files_key(project_dir: &Path) -> String {
+        let mut hasher = Hasher::new();
+        hash_used_files(&mut hasher, project_dir, ["file.txt", "dir"]).unwrap();
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_hash_used_files() {
+        let project_dir = tempfile::tempdir().unwrap();
+        let project_dir = project_dir.path();
+        fs::write(project_dir.join("file.txt"), "a").unwrap();
+        fs::create_dir(project_dir.join("dir")).unwrap();
+        fs::write(project_dir.join("dir/nested.txt"), "b").unwrap();
+        let key = used_files_key(project_dir);
+        // Rewriting a file with the same contents changes its modification time only.
+        fs::write(project_dir.join("file.txt"), "a").unwrap();
+        assert_eq!(used_files_key(project_dir), key);
+        fs::write(project_dir.join("dir/nested.txt"), "c").unwrap();
+        assert_ne!(used_files_key(project_dir), key);
+    }
+}
@@ -0,0 +1,364 @@
+//! License reports of systems.
+//!
+//! The licenses of the installed packages are taken from the SBOM of the system while
+//! the licenses of software provided by recipes are declared in the recipe
+//! configurations. The consolidated report is written to [`LICENSES_FILE`] in the build
+//! directory of the system.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::load_json;
+use crate::config::systems::SystemConfig;
+use crate::oven::customize::recipe_schedule;
+use crate::project::ProjectRef;
+use crate::BakeryResult;
+
+/// Name of the license report in the build directory of a system.
+pub const LICENSES_FILE: &str = "licenses.json";
+
+/// Name of the SBOM in the build directory of a system.
+pub const SBOM_FILE: &str = "sbom.spdx.json";
+
+/// Placeholder used by SPDX for unknown licenses.
+const NO_ASSERTION: &str = "NOASSERTION";
+
+/// License report of a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseReport {
+    /// Components of the system with their licenses.
+    pub components: Vec<Component>,
+    /// Names of the components by license.
+    pub licenses: BTreeMap<String, Vec<String>>,
+    /// Names of the components whose licenses are denied.
+    pub violations: Vec<String>,
+}
+
+/// Software component of a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct Component {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Origin of the component.
+    pub source: ComponentSource,
+    /// SPDX license expression, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+/// Origin of a component.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentSource {
+    /// Package installed with the package manager.
+    Package,
+    /// Software provided by a recipe.
+    Recipe,
+}
+
+/// SPDX SBOM as generated by Syft.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    #[serde(default)]
+    packages: Vec<SpdxPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: String,
+    version_info: Option<String>,
+    license_declared: Option<String>,
+    license_concluded: Option<String>,
+}
+
+/// Write the license report of a system and check it against the denylist.
+pub fn write_report(
+    project: &ProjectRef,
+    config: &SystemConfig,
+    system_path: &Path,
+) -> BakeryResult<()> {
+    info!("writing license report");
+    let mut components = Vec::new();
+    let sbom_path = system_path.join(SBOM_FILE);
+    if sbom_path.exists() {
+        let sbom = load_json::<SpdxDocument>(&sbom_path)?;
+        for package in sbom.packages {
+            // Skip the package describing the scanned root filesystem itself.
+            if package.spdx_id.starts_with("SPDXRef-DocumentRoot") {
+                continue;
+            }
+            let license = [package.license_concluded, package.license_declared]
+                .into_iter()
+                .flatten()
+                .find(|license| !license.is_empty() && license != NO_ASSERTION);
+            components.push(Component {
+                name: package.name,
+                version: package.version_info,
+                source: ComponentSource::Package,
+                license,
+            });
+        }
+    } else {
+        warn!("no SBOM found, only including recipes in the license report");
+    }
+    let library = project.library()?;
+    let mut layer = library.lookup_layer(library.repositories.root_repository, &config.layer);
+    while let Some(layer_idx) = layer {
+        let layer_def = &library.layers[layer_idx];
+        let Some(layer_config) = layer_def.config(config.architecture) else {
+            break;
+        };
+        for job in recipe_schedule(layer_def.repo, layer_config, &library)? {
+            if let Some(license) = &job.recipe.config.license {
+                components.push(Component {
+                    name: job.recipe.name.to_string(),
+                    version: None,
+                    source: ComponentSource::Recipe,
+                    license: Some(license.clone()),
+                });
+            }
+        }
+        layer = layer_config
+            .parent
+            .as_ref()
+            .and_then(|parent| library.lookup_layer(layer_def.repo, parent));
+    }
+
+    let deny = config
+        .licenses
+        .as_ref()
+        .and_then(|licenses| licenses.deny.as_deref())
+        .unwrap_or_default();
+    let mut licenses = BTreeMap::<String, Vec<String>>::new();
+    let mut violations = Vec::new();
+    for component in &components {
+        let Some(license) = &component.license else {
+            licenses
+                .entry(NO_ASSERTION.to_owned())
+                .or_default()
+                .push(component.name.clone());
+            continue;
+        };
+        let expr = LicenseExpr::parse(license);
+        for id in expr.as_ref().map(LicenseExpr::ids).unwrap_or_default() {
+            licenses
+                .entry(id.to_owned())
+                .or_default()
+                .push(component.name.clone());
+        }
+        match expr {
+            Some(expr) => {
+                if !deny.is_empty() && !expr.is_allowed(deny) {
+                    violations.push(format!("{} ({license})", component.name));
+                }
+            }
+            None => warn!(
+                "unable to parse license expression {license:?} of `{}`",
+                component.name
+            ),
+        }
+    }
+    for names in licenses.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+    let report = LicenseReport {
+        components,
+        licenses,
+        violations,
+    };
+    fs::write(
+        system_path.join(LICENSES_FILE),
+        serde_json::to_string_pretty(&report).unwrap(),
+    )
+    .whatever("unable to write license report")?;
+    if !report.violations.is_empty() {
+        bail!(
+            "found components with denied licenses: {}",
+            report.violations.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Check whether a license identifier matches a pattern of the denylist.
+///
+/// Patterns ending with `*` match all identifiers with the given prefix.
+fn matches_pattern(id: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => id
+            .to_ascii_lowercase()
+            .starts_with(&prefix.to_ascii_lowercase()),
+        None => id.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// SPDX license expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LicenseExpr {
+    License(String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    /// Parse an SPDX license expression.
+    ///
+    /// License exceptions (`WITH`) are ignored.
+    fn parse(expr: &str) -> Option<Self> {
+        let expr = expr.replace('(', " ( ").replace(')', " ) ");
+        let mut tokens = expr.split_whitespace().peekable();
+        let parsed = Self::parse_or(&mut tokens)?;
+        tokens.peek().is_none().then_some(parsed)
+    }
+
+    fn parse_or<'t>(
+        tokens: &mut std::iter::Peekable<impl Iterator<Item = &'t str>>,
+    ) -> Option<Self> {
+        let mut terms = vec![Self::parse_and(tokens)?];
+        while tokens
+            .next_if(|token| token.eq_ignore_ascii_case("OR"))
+            .is_some()
+        {
+            terms.push(Self::parse_and(tokens)?);
+        }
+        Some(if terms.len() == 1 {
+            terms.pop().unwrap()
+        } else {
+            Self::Or(terms)
+        })
+    }
+
+    fn parse_and<'t>(
+        tokens: &mut std::iter::Peekable<impl Iterator<Item = &'t str>>,
+    ) -> Option<Self> {
+        let mut factors = vec![Self::parse_atom(tokens)?];
+        while tokens
+            .next_if(|token| token.eq_ignore_ascii_case("AND"))
+            .is_some()
+        {
+            factors.push(Self::parse_atom(tokens)?);
+        }
+        Some(if factors.len() == 1 {
+            factors.pop().unwrap()
+        } else {
+            Self::And(factors)
+        })
+    }
+
+    fn parse_atom<'t>(
+        tokens: &mut std::iter::Peekable<impl Iterator<Item = &'t str>>,
+    ) -> Option<Self> {
+        let token = tokens.next()?;
+        if token == "(" {
+            let expr = Self::parse_or(tokens)?;
+            return (tokens.next()? == ")").then_some(expr);
+        }
+        if token == ")" || token.eq_ignore_ascii_case("AND") || token.eq_ignore_ascii_case("OR") {
+            return None;
+        }
+        if tokens
+            .next_if(|token| token.eq_ignore_ascii_case("WITH"))
+            .is_some()
+        {
+            tokens.next()?;
+        }
+        Some(Self::License(token.to_owned()))
+    }
+
+    /// License identifiers of the expression.
+    fn ids(&self) -> Vec<&str> {
+        match self {
+            Self::License(id) => vec![id.as_str()],
+            Self::And(exprs) | Self::Or(exprs) => exprs.iter().flat_map(Self::ids).collect(),
+        }
+    }
+
+    /// Check whether the expression can be satisfied without any denied license.
+    fn is_allowed(&self, deny: &[String]) -> bool {
+        match self {
+            Self::License(id) => !deny.iter().any(|pattern| matches_pattern(id, pattern)),
+            Self::And(exprs) => exprs.iter().all(|expr| expr.is_allowed(deny)),
+            Self::Or(exprs) => exprs.iter().any(|expr| expr.is_allowed(deny)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(id: &str) -> LicenseExpr {
+        LicenseExpr::License(id.to_owned())
+    }
+
+    #[test]
+    fn test_parse_license_expr() {
+        assert_eq!(LicenseExpr::parse("MIT"), Some(license("MIT")));
+        assert_eq!(
+            LicenseExpr::parse("MIT OR Apache-2.0"),
+            Some(LicenseExpr::Or(vec![license("MIT"), license("Apache-2.0")]))
+        );
+        // `AND` binds stronger than `OR`.
+        assert_eq!(
+            LicenseExpr::parse("MIT AND BSD-3-Clause OR Apache-2.0"),
+            Some(LicenseExpr::Or(vec![
+                LicenseExpr::And(vec![license("MIT"), license("BSD-3-Clause")]),
+                license("Apache-2.0"),
+            ]))
+        );
+        assert_eq!(
+            LicenseExpr::parse("MIT AND (BSD-3-Clause OR Apache-2.0)"),
+            Some(LicenseExpr::And(vec![
+                license("MIT"),
+                LicenseExpr::Or(vec![license("BSD-3-Clause"), license("Apache-2.0")]),
+            ]))
+        );
+        assert_eq!(
+            LicenseExpr::parse("GPL-2.0-only WITH Linux-syscall-note or mit"),
+            Some(LicenseExpr::Or(vec![
+                license("GPL-2.0-only"),
+                license("mit")
+            ]))
+        );
+        assert_eq!(LicenseExpr::parse("((MIT))"), Some(license("MIT")));
+    }
+
+    #[test]
+    fn test_parse_invalid_license_expr() {
+        assert_eq!(LicenseExpr::parse(""), None);
+        assert_eq!(LicenseExpr::parse("MIT OR"), None);
+        assert_eq!(LicenseExpr::parse("AND MIT"), None);
+        assert_eq!(LicenseExpr::parse("(MIT"), None);
+        assert_eq!(LicenseExpr::parse("MIT)"), None);
+        assert_eq!(LicenseExpr::parse("MIT Apache-2.0"), None);
+        assert_eq!(LicenseExpr::parse("MIT WITH"), None);
+    }
+
+    #[test]
+    fn test_license_expr_is_allowed() {
+        let deny = vec!["GPL-3.0*".to_owned(), "AGPL-3.0-only".to_owned()];
+        let allowed = |expr: &str| LicenseExpr::parse(expr).unwrap().is_allowed(&deny);
+        assert!(allowed("MIT"));
+        assert!(!allowed("GPL-3.0-or-later"));
+        assert!(!allowed("agpl-3.0-only"));
+        assert!(allowed("GPL-3.0-only OR MIT"));
+        assert!(!allowed("GPL-3.0-only AND MIT"));
+        assert!(allowed("(GPL-3.0-only OR MIT) AND Apache-2.0"));
+        assert!(!allowed("GPL-3.0-only WITH GCC-exception-3.1"));
+        assert_eq!(
+            LicenseExpr::parse("MIT AND (ISC OR Zlib)").unwrap().ids(),
+            ["MIT", "ISC", "Zlib"]
+        );
+    }
+}
@@ -1,49 +1,162 @@
 //! Functionality for baking layers and images.
 
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Instant;
 
 use clap::Args;
 use layer::FrozenLayer;
+use layer_cache::{CachedLayer, LayerCache};
 use reportify::{bail, whatever, ResultExt};
 use rugix_bundle::manifest::{self, BundleManifest, ChunkerAlgorithm};
 use rugix_common::loop_dev::LoopDevice;
 use rugix_common::mount::Mounted;
-use system::ReleaseInfo;
+use system::{ReleaseInfo, TestAccess};
 use tempfile::tempdir;
 use tracing::info;
 use url::Url;
 use xscript::{run, Run};
 
-use crate::config::systems::{Architecture, Target};
+use crate::config::layers::LayerConfig;
+use crate::config::load_json;
+use crate::config::systems::{
+    Architecture, BundleCompression, SystemBundleConfig, SystemConfig, SystemProfileConfig, Target,
+};
+use crate::oven::report::{LayerReport, RecipeReport};
+use crate::project::layers::Layer;
 use crate::project::library::LayerIdx;
 use crate::project::ProjectRef;
-use crate::utils::caching::{download, Hasher};
+use crate::utils::caching::{download, record_layer_use, Hasher};
 use crate::BakeryResult;
 
+pub mod apt;
+pub mod artifacts;
+pub mod budget;
+pub mod ccache;
+pub mod checkpoints;
+pub mod cleanup;
+pub mod cloud_init;
+pub mod compression;
+pub mod convert;
 pub mod customize;
+pub mod delta;
+pub mod emulation;
+pub mod encryption;
+pub mod export;
+pub mod files;
+pub mod index;
 pub mod layer;
+pub mod layer_cache;
+pub mod licenses;
+pub mod mounts;
+pub mod network;
+pub mod packages;
+pub mod plan;
+pub mod profiles;
+pub mod provenance;
+pub mod report;
+pub mod rust;
+pub mod scan;
+pub mod secure_boot;
+pub mod signing;
 pub mod system;
 pub mod targets;
+pub mod templates;
+pub mod toolchain;
+pub mod users;
+pub mod verity;
+pub mod version;
 
 pub fn bake_system(
     project: &ProjectRef,
     release_info: &ReleaseInfo,
     system: &str,
+    profile: Option<&str>,
     output: &Path,
     source_date_epoch: u64,
 ) -> BakeryResult<()> {
-    let system_config = project
-        .config()
-        .get_system_config(system)
-        .ok_or_else(|| whatever!("unable to find image {system}"))?;
+    bake_test_system(
+        project,
+        release_info,
+        None,
+        system,
+        profile,
+        output,
+        source_date_epoch,
+    )
+}
+
+/// Bake a system with, optionally, SSH access for testing.
+///
+/// If a build profile is given, its settings are applied on top of the system.
+pub fn bake_test_system(
+    project: &ProjectRef,
+    release_info: &ReleaseInfo,
+    test_access: Option<&TestAccess>,
+    system: &str,
+    profile: Option<&str>,
+    output: &Path,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    let (system_config, profile) = resolve_system(project, system, profile)?;
     info!("baking image `{system}`");
     let layer_bakery = LayerBakery::new(project, system_config.architecture);
     let baked_layer = layer_bakery.bake_root(&system_config.layer, source_date_epoch)?;
     let frozen = FrozenLayer::new(system_config.layer.clone(), baked_layer);
-    system::make_system(
-        system_config,
+    assemble_system(
+        project,
+        &system_config,
+        profile,
+        release_info,
+        test_access,
+        system,
+        &frozen,
+        output,
+        source_date_epoch,
+    )
+}
+
+/// Bake a system from a prebuilt layer artifact instead of baking its layer.
+///
+/// The artifact is a layer exported with `bake layer --export layer`.
+pub fn bake_system_from_layer(
+    project: &ProjectRef,
+    release_info: &ReleaseInfo,
+    system: &str,
+    profile: Option<&str>,
+    layer: &Path,
+    output: &Path,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    let (system_config, profile) = resolve_system(project, system, profile)?;
+    if !layer.is_file() {
+        bail!("layer artifact {layer:?} does not exist");
+    }
+    let layer_info = export::read_layer_info(layer)?;
+    if layer_info.arch != system_config.architecture.as_str() {
+        bail!(
+            "layer artifact has been baked for `{}`, but the system requires `{}`",
+            layer_info.arch,
+            system_config.architecture
+        );
+    }
+    if layer_info.layer != system_config.layer {
+        bail!(
+            "layer artifact contains layer `{}`, but the system requires layer `{}`",
+            layer_info.layer,
+            system_config.layer
+        );
+    }
+    info!("baking image `{system}` from layer artifact {layer:?}");
+    let frozen = FrozenLayer::new(layer.to_string_lossy().into_owned(), layer.to_path_buf());
+    assemble_system(
+        project,
+        &system_config,
+        profile,
         release_info,
+        None,
         system,
         &frozen,
         output,
@@ -51,6 +164,67 @@ pub fn bake_system(
     )
 }
 
+/// Resolve the configuration of a system with the given build profile applied.
+fn resolve_system<'p>(
+    project: &'p ProjectRef,
+    system: &str,
+    profile: Option<&str>,
+) -> BakeryResult<(SystemConfig, Option<&'p SystemProfileConfig>)> {
+    let system_config = project
+        .config()
+        .get_system_config(system)
+        .ok_or_else(|| whatever!("unable to find image {system}"))?;
+    let profile = match profile {
+        Some(name) => {
+            info!("using profile `{name}`");
+            system_config.get_profile(name)?
+        }
+        None => None,
+    };
+    let mut system_config = system_config.clone();
+    if let Some(layer) = profile.and_then(|profile| profile.layer.as_ref()) {
+        system_config.layer = layer.clone();
+    }
+    Ok((system_config, profile))
+}
+
+/// Assemble the image of a system from its frozen layer.
+#[allow(clippy::too_many_arguments)]
+fn assemble_system(
+    project: &ProjectRef,
+    system_config: &SystemConfig,
+    profile: Option<&SystemProfileConfig>,
+    release_info: &ReleaseInfo,
+    test_access: Option<&TestAccess>,
+    system: &str,
+    frozen: &FrozenLayer,
+    output: &Path,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    let release_info = version::resolve_release(project, release_info)?;
+    report::phase("system image", || {
+        system::make_system(
+            project,
+            system_config,
+            &release_info,
+            test_access,
+            profile,
+            system,
+            frozen,
+            output,
+            source_date_epoch,
+        )
+    })?;
+    licenses::write_report(project, system_config, output)?;
+    if let Some(scan) = &system_config.scan {
+        let vulnerabilities = scan::scan_system(Some(scan), output)?;
+        if let Some(fail_on) = scan.fail_on {
+            scan::check_severity(&vulnerabilities, fail_on)?;
+        }
+    }
+    budget::check_partitions(system_config, output)
+}
+
 pub struct LayerBakery<'p> {
     project: &'p ProjectRef,
     arch: Architecture,
@@ -70,67 +244,221 @@ impl<'p> LayerBakery<'p> {
     }
 
     pub fn bake(&self, layer: LayerIdx, source_date_epoch: u64) -> BakeryResult<PathBuf> {
-        let repositories = &self.project.repositories()?.repositories;
         let library = self.project.library()?;
+        let layer_cache = LayerCache::from_project(self.project)?;
+        let cache_key = self.cache_key(layer)?;
+        let cached = layer_cache.as_ref().map(|cache| CachedLayer {
+            cache,
+            key: cache_key.clone(),
+        });
         let layer = &library.layers[layer];
         info!("baking layer `{}`", layer.name);
         let Some(config) = layer.config(self.arch) else {
             bail!("no layer configuration for architecture `{}`", self.arch);
         };
-        let mut layer_id = Hasher::new();
-        layer_id.push("layer", &layer.name);
-        layer_id.push("repository", repositories[layer.repo].source.id.as_str());
-        layer_id.push("arch", self.arch.as_str());
+        let layer_id = self.layer_id(layer, config)?;
         if let Some(url) = &config.url {
-            layer_id.push("url", url);
-            let layer_id = layer_id.finalize();
             let system_tar = self
                 .project
                 .dir()
                 .join(format!(".rugix/layers/{layer_id}/system.tar"));
-            if !system_tar.exists() {
+            let started = Instant::now();
+            let cached = system_tar.exists();
+            if !cached {
                 extract(self.project, url, &system_tar)?;
+                fs::write(
+                    system_tar.with_file_name(customize::CACHE_KEY_FILE),
+                    &cache_key,
+                )
+                .whatever("unable to record cache key")?;
             }
+            self.record(&layer.name, cached, started, Vec::new());
+            record_layer_use(
+                system_tar.parent().unwrap(),
+                &layer.name,
+                self.arch.as_str(),
+            )?;
             Ok(system_tar)
         } else if let Some(parent) = &config.parent {
-            layer_id.push("parent", parent);
             let Some(parent) = library.lookup_layer(layer.repo, parent) else {
                 bail!("unable to find layer `{parent}`");
             };
             let src = self.bake(parent, source_date_epoch)?;
-            let layer_id = layer_id.finalize();
             let layer_path = PathBuf::from(format!(".rugix/layers/{layer_id}"));
             let target = self.project.dir().join(&layer_path).join("system.tar");
             fs::create_dir_all(target.parent().unwrap()).ok();
-            customize::customize(
+            let started = Instant::now();
+            let recipes = customize::customize(
                 self.project,
                 self.arch,
                 layer,
                 Some(&src),
                 &target,
                 &layer_path,
+                &cache_key,
+                cached.as_ref(),
                 source_date_epoch,
             )?;
+            self.record(
+                &layer.name,
+                recipes.is_none(),
+                started,
+                recipes.unwrap_or_default(),
+            );
+            record_layer_use(target.parent().unwrap(), &layer.name, self.arch.as_str())?;
             Ok(target)
-        } else if config.root.unwrap_or(false) {
-            layer_id.push("bare", "true");
-            let layer_id = layer_id.finalize();
+        } else {
             let layer_path = PathBuf::from(format!(".rugix/layers/{layer_id}"));
             let target = self.project.dir().join(&layer_path).join("system.tar");
             fs::create_dir_all(target.parent().unwrap()).ok();
-            customize::customize(
+            let started = Instant::now();
+            let recipes = customize::customize(
                 self.project,
                 self.arch,
                 layer,
                 None,
                 &target,
                 &layer_path,
+                &cache_key,
+                cached.as_ref(),
                 source_date_epoch,
             )?;
+            self.record(
+                &layer.name,
+                recipes.is_none(),
+                started,
+                recipes.unwrap_or_default(),
+            );
+            record_layer_use(target.parent().unwrap(), &layer.name, self.arch.as_str())?;
             Ok(target)
+        }
+    }
+
+    /// Compute the identifier of a layer, which determines its path in the cache.
+    fn layer_id(&self, layer: &Layer, config: &LayerConfig) -> BakeryResult<String> {
+        let repositories = &self.project.repositories()?.repositories;
+        let mut layer_id = Hasher::new();
+        layer_id.push("layer", &layer.name);
+        layer_id.push("repository", repositories[layer.repo].source.id.as_str());
+        layer_id.push("arch", self.arch.as_str());
+        if let Some(url) = &config.url {
+            layer_id.push("url", url);
+        } else if let Some(parent) = &config.parent {
+            layer_id.push("parent", parent);
+        } else if config.root.unwrap_or(false) {
+            layer_id.push("bare", "true");
         } else {
             bail!("invalid layer configuration")
         }
+        Ok(layer_id.finalize())
+    }
+
+    /// Compute the key of the cached layer with the given identifier.
+    ///
+    /// Returns `None`, if no layer of the project has the given identifier.
+    pub fn cache_key_by_id(&self, layer_id: &str) -> BakeryResult<Option<String>> {
+        let library = self.project.library()?;
+        for (idx, layer) in library.layers.iter() {
+            let Some(config) = layer.config(self.arch) else {
+                continue;
+            };
+            if self.layer_id(layer, config)? == layer_id {
+                return self.cache_key(idx).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Compute the key of a layer in the shared layer cache.
+    ///
+    /// In contrast to the identifier, the key is derived from the contents of the recipes
+    /// of the layer and its parents such that it is the same on all machines.
+    fn cache_key(&self, layer: LayerIdx) -> BakeryResult<String> {
+        let library = self.project.library()?;
+        let layer = &library.layers[layer];
+        let Some(config) = layer.config(self.arch) else {
+            bail!("no layer configuration for architecture `{}`", self.arch);
+        };
+        let mut hasher = Hasher::new();
+        hasher.push("arch", self.arch.as_str());
+        if let Some(url) = &config.url {
+            hasher.push("url", url);
+            return Ok(hasher.finalize());
+        }
+        if let Some(parent) = &config.parent {
+            let Some(parent) = library.lookup_layer(layer.repo, parent) else {
+                bail!("unable to find layer `{parent}`");
+            };
+            hasher.push("parent", self.cache_key(parent)?);
+        }
+        layer_cache::hash_project_config(&mut hasher, self.project.config());
+        hasher.push(
+            "aggregate-packages",
+            config.aggregate_packages.unwrap_or(false).to_string(),
+        );
+        if let Some(cleanup) = &config.cleanup {
+            hasher.push("cleanup", serde_json::to_string(cleanup).unwrap());
+        }
+        let jobs = customize::recipe_schedule(layer.repo, config, &library)?;
+        layer_cache::hash_recipes(&mut hasher, &jobs)?;
+        for (name, digest) in templates::template_inputs(self.project, &jobs)? {
+            hasher.push("template-input", format!("{name}={digest}"));
+        }
+        Ok(hasher.finalize())
+    }
+
+    /// Record the report of a baked layer for the build report.
+    fn record(&self, name: &str, cached: bool, started: Instant, recipes: Vec<RecipeReport>) {
+        report::record_layer(LayerReport {
+            name: name.to_owned(),
+            arch: self.arch.to_string(),
+            cached,
+            duration: started.elapsed().as_secs_f64(),
+            recipes,
+        });
+    }
+
+    /// Spawn a shell or run a command in the customization environment of the given
+    /// layer.
+    ///
+    /// If a recipe is given, the shell is spawned right before applying it.
+    pub fn shell(
+        &self,
+        layer: &str,
+        recipe: Option<&str>,
+        command: Option<&[String]>,
+        source_date_epoch: u64,
+    ) -> BakeryResult<ExitStatus> {
+        let library = self.project.library()?;
+        let Some(layer) = library.lookup_layer(library.repositories.root_repository, layer) else {
+            bail!("unable to find layer {layer}");
+        };
+        let layer = &library.layers[layer];
+        let Some(config) = layer.config(self.arch) else {
+            bail!("no layer configuration for architecture `{}`", self.arch);
+        };
+        let src = if let Some(parent) = &config.parent {
+            let Some(parent) = library.lookup_layer(layer.repo, parent) else {
+                bail!("unable to find layer `{parent}`");
+            };
+            Some(self.bake(parent, source_date_epoch)?)
+        } else if config.root.unwrap_or(false) {
+            None
+        } else {
+            bail!("layer `{}` is not customized with recipes", layer.name);
+        };
+        let layer_path = PathBuf::from(format!(".rugix/shell/{}", layer.name));
+        fs::create_dir_all(&layer_path).whatever("unable to create shell directory")?;
+        customize::shell(
+            self.project,
+            self.arch,
+            layer,
+            src.as_deref(),
+            &layer_path,
+            recipe,
+            command,
+            source_date_epoch,
+        )
     }
 }
 
@@ -182,9 +510,15 @@ fn extract(project: &ProjectRef, image_url: &str, layer_path: &Path) -> BakeryRe
     } else {
         info!("creating `.tar` archive with system files");
         let loop_dev = LoopDevice::attach(image_path).whatever("unable to setup loop device")?;
-        let _mounted_root = Mounted::mount(loop_dev.partition(2), &system_dir)
+        let system_partition = loop_dev
+            .partition(2)
+            .whatever("unable to find system partition")?;
+        let boot_partition = loop_dev
+            .partition(1)
+            .whatever("unable to find boot partition")?;
+        let _mounted_root = Mounted::mount(system_partition, &system_dir)
             .whatever("unable to mount system partition")?;
-        let _mounted_boot = Mounted::mount(loop_dev.partition(1), temp_dir_path.join("roots/boot"))
+        let _mounted_boot = Mounted::mount(boot_partition, temp_dir_path.join("roots/boot"))
             .whatever("unable to mount boot partition")?;
         run!(["tar", "-c", "-f", &layer_path, "-C", temp_dir_path, "."])
             .whatever("unable to create layer tar file")?;
@@ -193,7 +527,7 @@ fn extract(project: &ProjectRef, image_url: &str, layer_path: &Path) -> BakeryRe
 }
 
 /// Bundle options.
-#[derive(Args, Clone, Debug)]
+#[derive(Args, Clone, Debug, Default)]
 pub struct BundleOpts {
     /// Disable compression of the bundle.
     #[clap(long)]
@@ -201,14 +535,9 @@ pub struct BundleOpts {
     /// Use a specific chunking algorithm.
     #[clap(long)]
     chunker: Option<ChunkerAlgorithm>,
-}
-
-impl BundleOpts {
-    pub fn chunker_algorithm(&self) -> ChunkerAlgorithm {
-        self.chunker.clone().unwrap_or(ChunkerAlgorithm::Casync {
-            avg_block_size_kib: 64,
-        })
-    }
+    /// Create a static delta bundle against the given image of a previous release.
+    #[clap(long)]
+    delta_from: Option<PathBuf>,
 }
 
 pub fn bake_bundle(
@@ -221,99 +550,252 @@ pub fn bake_bundle(
     let bundle_dir = tempdir().whatever("unable to create temporary directory")?;
     let bundle_dir = bundle_dir.path();
     let system_config = project.config().resolve_system_config(system)?;
-    let config = match system_config.target.clone().unwrap_or(Target::Unknown) {
-        Target::GenericGrubEfi => efi_bundle_config(opts),
-        Target::RpiTryboot => rpi_bundle_config(opts),
-        Target::RpiUboot => rpi_bundle_config(opts),
-        Target::Unknown => bail!("cannot bake bundles for unknown targets"),
-    };
+    let mut config = system_bundle_config(project, system, system_path, opts)?;
+    let filesystems_dir = system_path
+        .join("filesystems")
+        .canonicalize()
+        .whatever("unable to canonicalize filesystems directory")?;
+    let variants = bundle_variants(project, system)?;
+    let include_sbom = system_config
+        .bundle
+        .as_ref()
+        .and_then(|bundle| bundle.sbom)
+        .unwrap_or(false);
+    let payloads_dir = bundle_dir.join("payloads");
+    if !variants.is_empty() {
+        if opts.delta_from.is_some() {
+            bail!("delta updates are not supported for bundles with variants");
+        }
+        fs::create_dir_all(&payloads_dir).whatever("unable to create payloads directory")?;
+        let mut payloads = Vec::new();
+        let mut sbom_payloads = Vec::new();
+        let mut seen = HashSet::new();
+        for variant_system in std::iter::once(system).chain(variants.iter().map(String::as_str)) {
+            // Systems are baked next to each other into the build directory.
+            let variant_path = system_path.with_file_name(variant_system);
+            let variant = bundle_variant(project, variant_system)?;
+            if !seen.insert(variant.clone()) {
+                bail!("variant {variant:?} of system `{variant_system}` is not unique");
+            }
+            info!("adding payloads of system `{variant_system}` as variant {variant:?}");
+            let variant_config =
+                system_bundle_config(project, variant_system, &variant_path, opts)?;
+            let variant_filesystems_dir = variant_path
+                .join("filesystems")
+                .canonicalize()
+                .whatever("unable to canonicalize filesystems directory")?;
+            std::os::unix::fs::symlink(&variant_filesystems_dir, payloads_dir.join(&variant))
+                .whatever("unable to symlink filesystems")?;
+            for mut payload in variant_config.payloads {
+                payload.filename = format!("{variant}/{}", payload.filename);
+                payload.variant = Some(variant.clone());
+                payloads.push(payload);
+            }
+            if include_sbom {
+                sbom_payloads.push(sbom_payload(
+                    &variant_path,
+                    &payloads_dir,
+                    Some(variant.as_str()),
+                )?);
+            }
+        }
+        // Metadata payloads must come after the payloads of the slots.
+        payloads.extend(sbom_payloads);
+        config.payloads = payloads;
+    } else if let Some(old_image) = &opts.delta_from {
+        fs::create_dir_all(&payloads_dir).whatever("unable to create payloads directory")?;
+        delta::encode_payloads(
+            &mut config,
+            &filesystems_dir,
+            old_image,
+            &payloads_dir,
+            !opts.without_compression,
+        )?;
+        if include_sbom {
+            config
+                .payloads
+                .push(sbom_payload(system_path, &payloads_dir, None)?);
+        }
+    } else if include_sbom {
+        fs::create_dir_all(&payloads_dir).whatever("unable to create payloads directory")?;
+        for payload in &config.payloads {
+            std::os::unix::fs::symlink(
+                filesystems_dir.join(&payload.filename),
+                payloads_dir.join(&payload.filename),
+            )
+            .whatever("unable to symlink payload")?;
+        }
+        config
+            .payloads
+            .push(sbom_payload(system_path, &payloads_dir, None)?);
+    } else {
+        std::os::unix::fs::symlink(&filesystems_dir, &payloads_dir)
+            .whatever("unable to symlink filesystems")?;
+    }
     std::fs::write(
         bundle_dir.join("rugix-bundle.toml"),
         toml::to_string(&config).unwrap(),
     )
     .whatever("unable to write bundle config")?;
-    std::os::unix::fs::symlink(
-        system_path
-            .join("filesystems")
-            .canonicalize()
-            .whatever("unable to canonicalize filesystems directory")?,
-        bundle_dir.join("payloads"),
-    )
-    .whatever("unable to symlink filesystems")?;
     info!("Creating bundle, this may take a while...");
     if let Some(parent) = output.parent() {
         std::fs::create_dir_all(parent).ok();
     }
     rugix_bundle::builder::pack(bundle_dir, output).whatever("unable to create bundle")?;
+    budget::check_bundle(system_config, system_path, output)?;
+    if let Some(signing) = system_config
+        .bundle
+        .as_ref()
+        .and_then(|bundle| bundle.signing.as_ref())
+    {
+        signing::sign_bundle(signing, output)?;
+    }
     Ok(())
 }
 
-fn rpi_bundle_config(opts: &BundleOpts) -> BundleManifest {
-    let compression = if opts.without_compression {
-        None
-    } else {
-        Some(manifest::Compression::Xz(manifest::XzCompression::new()))
+/// Metadata payload with the SBOM of a system for its system slot.
+fn sbom_payload(
+    system_path: &Path,
+    payloads_dir: &Path,
+    variant: Option<&str>,
+) -> BakeryResult<manifest::Payload> {
+    let sbom_path = system_path
+        .join(licenses::SBOM_FILE)
+        .canonicalize()
+        .whatever("unable to find SBOM of the system")?;
+    let filename = match variant {
+        Some(variant) => format!("sbom-{variant}.spdx.json"),
+        None => licenses::SBOM_FILE.to_owned(),
     };
-    manifest::BundleManifest::new(
-        manifest::UpdateType::Full,
-        vec![
-            manifest::Payload::new(
-                manifest::DeliveryConfig::Slot(manifest::SlotDeliveryConfig {
-                    slot: "boot".to_owned(),
-                }),
-                "partition-2.img".to_owned(),
-            )
-            .with_block_encoding(Some(
-                manifest::BlockEncoding::new(opts.chunker_algorithm())
-                    .with_deduplicate(Some(true))
-                    .with_compression(compression.clone()),
-            )),
-            manifest::Payload::new(
-                manifest::DeliveryConfig::Slot(manifest::SlotDeliveryConfig {
-                    slot: "system".to_owned(),
-                }),
-                "partition-5.img".to_owned(),
-            )
-            .with_block_encoding(Some(
-                manifest::BlockEncoding::new(opts.chunker_algorithm())
-                    .with_deduplicate(Some(true))
-                    .with_compression(compression.clone()),
-            )),
-        ],
+    std::os::unix::fs::symlink(&sbom_path, payloads_dir.join(&filename))
+        .whatever("unable to symlink SBOM")?;
+    Ok(manifest::Payload::new(
+        manifest::DeliveryConfig::Metadata(manifest::MetadataDeliveryConfig::new(
+            "sbom".to_owned(),
+            "system".to_owned(),
+        )),
+        filename,
     )
+    .with_variant(variant.map(str::to_owned)))
 }
 
-fn efi_bundle_config(opts: &BundleOpts) -> BundleManifest {
-    let compression = if opts.without_compression {
-        None
-    } else {
-        Some(manifest::Compression::Xz(manifest::XzCompression::new()))
+/// Other systems whose payloads are included as variants in the bundle of a system.
+pub fn bundle_variants(project: &ProjectRef, system: &str) -> BakeryResult<Vec<String>> {
+    let system_config = project.config().resolve_system_config(system)?;
+    let variants = system_config
+        .bundle
+        .as_ref()
+        .and_then(|bundle| bundle.variants.clone())
+        .unwrap_or_default();
+    for variant in &variants {
+        project.config().resolve_system_config(variant)?;
+    }
+    Ok(variants)
+}
+
+/// Variant of the payloads of a system.
+fn bundle_variant(project: &ProjectRef, system: &str) -> BakeryResult<String> {
+    let system_config = project.config().resolve_system_config(system)?;
+    Ok(system_config
+        .bundle
+        .as_ref()
+        .and_then(|bundle| bundle.variant.clone())
+        .unwrap_or_else(|| system_config.architecture.to_string()))
+}
+
+/// Bundle manifest for the given system, based on its target.
+fn system_bundle_config(
+    project: &ProjectRef,
+    system: &str,
+    system_path: &Path,
+    opts: &BundleOpts,
+) -> BakeryResult<BundleManifest> {
+    let system_config = project.config().resolve_system_config(system)?;
+    let mut config = match system_config.target.clone().unwrap_or(Target::Unknown) {
+        Target::GenericGrubEfi => slot_bundle_config(system_config, "partition-4.img", opts)?,
+        Target::RpiTryboot => slot_bundle_config(system_config, "partition-5.img", opts)?,
+        Target::RpiUboot => slot_bundle_config(system_config, "partition-5.img", opts)?,
+        Target::Unknown => bail!("cannot bake bundles for unknown targets"),
     };
-    manifest::BundleManifest::new(
-        manifest::UpdateType::Full,
-        vec![
-            manifest::Payload::new(
-                manifest::DeliveryConfig::Slot(manifest::SlotDeliveryConfig {
-                    slot: "boot".to_owned(),
-                }),
-                "partition-2.img".to_owned(),
-            )
-            .with_block_encoding(Some(
-                manifest::BlockEncoding::new(opts.chunker_algorithm())
-                    .with_deduplicate(Some(true))
-                    .with_compression(compression.clone()),
-            )),
+    let verity_info_path = system_path.join(verity::VERITY_INFO_FILE);
+    if verity_info_path.exists() {
+        let verity_info = load_json::<verity::VerityInfo>(&verity_info_path)?;
+        for payload in &mut config.payloads {
+            payload.verity = verity_info.get(&payload.filename).cloned();
+        }
+    }
+    let system_info_path = system_path.join("system-build-info.json");
+    if system_info_path.exists() {
+        let system_info = load_json::<system::SystemInfo>(&system_info_path)?;
+        config.version = Some(system_info.release.version);
+    }
+    Ok(config)
+}
+
+/// Bundle manifest with a payload for the boot and system slot.
+fn slot_bundle_config(
+    system_config: &SystemConfig,
+    system_partition: &str,
+    opts: &BundleOpts,
+) -> BakeryResult<BundleManifest> {
+    let bundle_config = system_config.bundle.as_ref();
+    let block_encoding = block_encoding(bundle_config, opts)?;
+    let payloads = [("boot", "partition-2.img"), ("system", system_partition)]
+        .into_iter()
+        .map(|(slot, filename)| {
             manifest::Payload::new(
                 manifest::DeliveryConfig::Slot(manifest::SlotDeliveryConfig {
-                    slot: "system".to_owned(),
+                    slot: slot.to_owned(),
                 }),
-                "partition-4.img".to_owned(),
+                filename.to_owned(),
             )
-            .with_block_encoding(Some(
-                manifest::BlockEncoding::new(opts.chunker_algorithm())
-                    .with_deduplicate(Some(true))
-                    .with_compression(compression.clone()),
-            )),
-        ],
+            .with_block_encoding(block_encoding.clone())
+        })
+        .collect();
+    Ok(
+        manifest::BundleManifest::new(manifest::UpdateType::Full, payloads).with_hash_algorithm(
+            bundle_config.and_then(|bundle_config| bundle_config.hash_algorithm),
+        ),
     )
 }
+
+/// Block encoding of the payloads based on the bundle configuration.
+///
+/// Options provided on the command line take precedence over the configuration.
+fn block_encoding(
+    config: Option<&SystemBundleConfig>,
+    opts: &BundleOpts,
+) -> BakeryResult<Option<manifest::BlockEncoding>> {
+    let compression = config.and_then(|config| config.compression.as_ref());
+    if !config
+        .and_then(|config| config.block_encoding)
+        .unwrap_or(true)
+    {
+        if matches!(compression, Some(BundleCompression::Xz(_))) {
+            bail!("compression of bundle payloads requires block encoding");
+        }
+        return Ok(None);
+    }
+    let chunker = opts
+        .chunker
+        .clone()
+        .or_else(|| config.and_then(|config| config.chunker.clone()))
+        .unwrap_or(ChunkerAlgorithm::Casync {
+            avg_block_size_kib: 64,
+        });
+    let compression = match compression {
+        _ if opts.without_compression => None,
+        None => Some(manifest::Compression::Xz(manifest::XzCompression::new())),
+        Some(BundleCompression::None) => None,
+        Some(BundleCompression::Xz(xz)) => Some(manifest::Compression::Xz(
+            manifest::XzCompression::new().with_level(xz.level),
+        )),
+    };
+    Ok(Some(
+        manifest::BlockEncoding::new(chunker)
+            .with_deduplicate(Some(
+                config.and_then(|config| config.deduplicate).unwrap_or(true),
+            ))
+            .with_compression(compression),
+    ))
+}
@@ -0,0 +1,346 @@
+//! Generation of mounts for partitions of the image layout.
+//!
+//! Partitions with a mount point and swap partitions are either added to `/etc/fstab`
+//! of the system or turned into systemd mount and swap units. This keeps the mounts of
+//! the system in sync with the layout of the image.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use tracing::{info, warn};
+
+use rugix_common::disk::{DiskId, PartitionTable};
+use rugix_common::utils::ascii_numbers;
+
+use crate::config::images::{Filesystem, ImageLayout, MountGeneration};
+use crate::oven::system::filesystem_uuid;
+use crate::BakeryResult;
+
+/// Mount of a partition.
+#[derive(Debug, Clone)]
+struct Mount {
+    /// Source of the mount in `fstab` notation (e.g., `LABEL=data`).
+    source: String,
+    /// Mount point or `none` for swap partitions.
+    mountpoint: String,
+    /// Type of the filesystem.
+    fs_type: &'static str,
+    /// Mount options.
+    options: String,
+}
+
+impl Mount {
+    /// Indicates whether the mount is a swap partition.
+    fn is_swap(&self) -> bool {
+        self.fs_type == "swap"
+    }
+}
+
+/// Add mounts for the partitions of the layout to the system.
+pub fn write_mounts(
+    layout: &ImageLayout,
+    table: &PartitionTable,
+    system_name: &str,
+    system_dir: &Path,
+) -> BakeryResult<()> {
+    let mounts = layout_mounts(layout, table, system_name)?;
+    if mounts.is_empty() {
+        return Ok(());
+    }
+    match layout.mounts.unwrap_or(MountGeneration::Fstab) {
+        MountGeneration::Fstab => write_fstab(&mounts, system_dir),
+        MountGeneration::Systemd => write_units(&mounts, system_dir),
+    }
+}
+
+/// Compute the mounts for the partitions of the layout.
+fn layout_mounts(
+    layout: &ImageLayout,
+    table: &PartitionTable,
+    system_name: &str,
+) -> BakeryResult<Vec<Mount>> {
+    let Some(partitions) = &layout.partitions else {
+        return Ok(Vec::new());
+    };
+    let mut mounts = Vec::new();
+    for (layout_partition, image_partition) in partitions.iter().zip(table.partitions.iter()) {
+        // Swap partitions are always activated.
+        let is_swap = matches!(layout_partition.filesystem, Some(Filesystem::Swap));
+        let mountpoint = match &layout_partition.mountpoint {
+            Some(mountpoint) => mountpoint.as_str(),
+            None if is_swap => "none",
+            None => continue,
+        };
+        let Some(filesystem) = &layout_partition.filesystem else {
+            bail!("partition with mount point {mountpoint:?} must have a filesystem");
+        };
+        if layout_partition.encryption.is_some() {
+            bail!("mount points are not supported for encrypted partitions");
+        }
+        if !is_swap && !mountpoint.starts_with('/') {
+            bail!("mount point {mountpoint:?} must be an absolute path");
+        }
+        let fs_uuid = filesystem_uuid(
+            layout_partition,
+            filesystem,
+            system_name,
+            image_partition.number,
+        )?;
+        let source = match (
+            &layout_partition.label,
+            fs_uuid,
+            table.disk_id,
+            image_partition.gpt_id,
+        ) {
+            (Some(label), _, _, _) if !matches!(filesystem, Filesystem::Squashfs(_)) => {
+                format!("LABEL={label}")
+            }
+            (_, Some(fs_uuid), _, _) => format!("UUID={fs_uuid}"),
+            (_, _, DiskId::Mbr(disk_id), _) => {
+                format!(
+                    "PARTUUID={:08x}-{:02x}",
+                    disk_id.into_raw(),
+                    image_partition.number
+                )
+            }
+            (_, _, _, Some(gpt_id)) => {
+                format!("PARTUUID={}", gpt_id.to_hex_str(ascii_numbers::Case::Lower))
+            }
+            _ => bail!(
+                "unable to determine partition UUID of partition with mount point {mountpoint:?}"
+            ),
+        };
+        let (fs_type, default_options) = match filesystem {
+            Filesystem::Ext4(_) => ("ext4", "defaults"),
+            Filesystem::Fat32 => ("vfat", "defaults"),
+            Filesystem::Squashfs(_) => ("squashfs", "ro"),
+            Filesystem::F2fs(_) => ("f2fs", "defaults"),
+            Filesystem::Swap => ("swap", "sw"),
+        };
+        mounts.push(Mount {
+            source,
+            mountpoint: mountpoint.to_owned(),
+            fs_type,
+            options: layout_partition
+                .mount_options
+                .clone()
+                .unwrap_or_else(|| default_options.to_owned()),
+        });
+    }
+    Ok(mounts)
+}
+
+/// Add entries for the mounts to `/etc/fstab` of the system.
+///
+/// Existing entries for the same mount points are replaced.
+fn write_fstab(mounts: &[Mount], system_dir: &Path) -> BakeryResult<()> {
+    info!("Adding mount points to `/etc/fstab`.");
+    let fstab_path = system_dir.join("etc/fstab");
+    let existing = match fs::read_to_string(&fstab_path) {
+        Ok(existing) => existing,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error).whatever("unable to read `/etc/fstab`"),
+    };
+    let mut fstab = String::new();
+    for line in existing.lines() {
+        let mut fields = line.split_whitespace();
+        let source = fields.next().filter(|source| !source.starts_with('#'));
+        let mountpoint = fields.next();
+        let is_replaced = mounts.iter().any(|mount| {
+            if mount.is_swap() {
+                source == Some(mount.source.as_str())
+            } else {
+                mountpoint == Some(mount.mountpoint.as_str())
+            }
+        });
+        if source.is_some() && is_replaced {
+            warn!("replacing entry {line:?} of `/etc/fstab` with entry from layout");
+            continue;
+        }
+        fstab.push_str(line);
+        fstab.push('\n');
+    }
+    for mount in mounts {
+        let pass = if mount.is_swap() { 0 } else { 2 };
+        writeln!(
+            &mut fstab,
+            "{} {} {} {} 0 {pass}",
+            mount.source, mount.mountpoint, mount.fs_type, mount.options
+        )
+        .unwrap();
+    }
+    fs::create_dir_all(fstab_path.parent().unwrap()).whatever("unable to create `/etc`")?;
+    fs::write(&fstab_path, fstab).whatever("unable to write `/etc/fstab`")?;
+    Ok(())
+}
+
+/// Create and enable systemd units for the mounts.
+fn write_units(mounts: &[Mount], system_dir: &Path) -> BakeryResult<()> {
+    info!("Creating systemd units for mount points.");
+    let units_dir = system_dir.join("etc/systemd/system");
+    for mount in mounts {
+        let what = device_path(&mount.source);
+        let (unit_name, target, unit) = if mount.is_swap() {
+            (
+                format!("{}.swap", escape_path(&what)),
+                "swap.target",
+                format!(
+                    "[Unit]\nDescription=Swap {what}\n\n\
+                    [Swap]\nWhat={what}\nOptions={}\n\n\
+                    [Install]\nWantedBy=swap.target\n",
+                    mount.options
+                ),
+            )
+        } else {
+            (
+                format!("{}.mount", escape_path(&mount.mountpoint)),
+                "local-fs.target",
+                format!(
+                    "[Unit]\nDescription=Mount {mountpoint}\nBefore=local-fs.target\n\n\
+                    [Mount]\nWhat={what}\nWhere={mountpoint}\nType={}\nOptions={}\n\n\
+                    [Install]\nWantedBy=local-fs.target\n",
+                    mount.fs_type,
+                    mount.options,
+                    mountpoint = mount.mountpoint,
+                ),
+            )
+        };
+        let wants_dir = units_dir.join(format!("{target}.wants"));
+        fs::create_dir_all(&wants_dir).whatever("unable to create systemd units directory")?;
+        fs::write(units_dir.join(&unit_name), unit)
+            .whatever_with(|_| format!("unable to write unit {unit_name:?}"))?;
+        let link = wants_dir.join(&unit_name);
+        fs::remove_file(&link).ok();
+        std::os::unix::fs::symlink(Path::new("..").join(&unit_name), &link)
+            .whatever_with(|_| format!("unable to enable unit {unit_name:?}"))?;
+    }
+    Ok(())
+}
+
+/// Convert a source in `fstab` notation to the device path created by udev.
+fn device_path(source: &str) -> String {
+    let (directory, value) = match source.split_once('=') {
+        Some(("LABEL", label)) => ("by-label", label),
+        Some(("UUID", uuid)) => ("by-uuid", uuid),
+        Some(("PARTUUID", uuid)) => ("by-partuuid", uuid),
+        _ => return source.to_owned(),
+    };
+    let mut path = format!("/dev/disk/{directory}/");
+    // udev encodes unsafe characters of labels in its symlinks.
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"#+-.:=@_".contains(&byte) {
+            path.push(byte as char);
+        } else {
+            write!(&mut path, "\\x{byte:02x}").unwrap();
+        }
+    }
+    path
+}
+
+/// Escape a path for use in a systemd unit name (see `systemd-escape --path`).
+fn escape_path(path: &str) -> String {
+    let path = path
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        return "-".to_owned();
+    }
+    let mut escaped = String::new();
+    for (idx, byte) in path.bytes().enumerate() {
+        match byte {
+            b'/' => escaped.push('-'),
+            b'.' if idx == 0 => escaped.push_str("\\x2e"),
+            byte if byte.is_ascii_alphanumeric() || b":_.".contains(&byte) => {
+                escaped.push(byte as char)
+            }
+            byte => write!(&mut escaped, "\\x{byte:02x}").unwrap(),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(source: &str, mountpoint: &str, fs_type: &'static str, options: &str) -> Mount {
+        Mount {
+            source: source.to_owned(),
+            mountpoint: mountpoint.to_owned(),
+            fs_type,
+            options: options.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_escape_path() {
+        assert_eq!(escape_path("/"), "-");
+        assert_eq!(escape_path("/data"), "data");
+        assert_eq!(escape_path("//var/lib//data/"), "var-lib-data");
+        assert_eq!(escape_path("/.hidden/.dir"), "\\x2ehidden-.dir");
+        assert_eq!(escape_path("/my-data dir"), "my\\x2ddata\\x20dir");
+        assert_eq!(escape_path("/data\u{e4}"), "data\\xc3\\xa4");
+    }
+
+    #[test]
+    fn test_device_path() {
+        assert_eq!(device_path("LABEL=data"), "/dev/disk/by-label/data");
+        assert_eq!(
+            device_path("LABEL=my data/1"),
+            "/dev/disk/by-label/my\\x20data\\x2f1"
+        );
+        assert_eq!(
+            device_path("UUID=0b7c2b1e-6b0a-4b7e-9f4c-1d2e3f4a5b6c"),
+            "/dev/disk/by-uuid/0b7c2b1e-6b0a-4b7e-9f4c-1d2e3f4a5b6c"
+        );
+        assert_eq!(
+            device_path("PARTUUID=12345678-07"),
+            "/dev/disk/by-partuuid/12345678-07"
+        );
+        assert_eq!(device_path("/dev/sda1"), "/dev/sda1");
+    }
+
+    #[test]
+    fn test_write_fstab() {
+        let system_dir = tempfile::tempdir().unwrap();
+        let mounts = [
+            mount("LABEL=data", "/data", "ext4", "defaults"),
+            mount("LABEL=swap", "none", "swap", "sw"),
+        ];
+        // A missing `/etc/fstab` is created.
+        write_fstab(&mounts[..1], system_dir.path()).unwrap();
+        let fstab_path = system_dir.path().join("etc/fstab");
+        assert_eq!(
+            fs::read_to_string(&fstab_path).unwrap(),
+            "LABEL=data /data ext4 defaults 0 2\n"
+        );
+        fs::write(
+            &fstab_path,
+            "# <file system> <mount point> <type> <options> <dump> <pass>\n\
+            proc /proc proc defaults 0 0\n\
+            /dev/sda3 /data ext4 defaults 0 2\n\
+            LABEL=swap none swap sw 0 0\n",
+        )
+        .unwrap();
+        write_fstab(&mounts, system_dir.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&fstab_path).unwrap(),
+            "# <file system> <mount point> <type> <options> <dump> <pass>\n\
+            proc /proc proc defaults 0 0\n\
+            LABEL=data /data ext4 defaults 0 2\n\
+            LABEL=swap none swap sw 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_write_fstab_unreadable() {
+        let system_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(system_dir.path().join("etc/fstab")).unwrap();
+        let mounts = [mount("LABEL=data", "/data", "ext4", "defaults")];
+        assert!(write_fstab(&mounts, system_dir.path()).is_err());
+    }
+}
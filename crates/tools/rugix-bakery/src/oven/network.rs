@@ -0,0 +1,524 @@
+//! Generation of network configurations by `network` steps.
+//!
+//! The generated configuration is installed in `/usr/lib`, where NetworkManager and
+//! `systemd-networkd` pick it up with a lower precedence than configuration in `/etc`.
+//! Hence, the directories in `/etc` can be persisted with Rugix Ctrl's state management
+//! such that changes made on the device persist, while updates still ship changes to
+//! the generated configuration.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::net::IpAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use sha1::{Digest, Sha1};
+use tracing::info;
+
+use crate::config::recipes::{NetworkBackend, NetworkConfig, WifiConfig};
+use crate::BakeryResult;
+
+/// Directory of the generated NetworkManager profiles.
+const NM_PROFILES_DIR: &str = "usr/lib/NetworkManager/system-connections";
+
+/// Directory of NetworkManager profiles created or modified on the device.
+const NM_STATE_DIR: &str = "/etc/NetworkManager/system-connections";
+
+/// Directory of the generated `systemd-networkd` configuration files.
+const NETWORKD_DIR: &str = "usr/lib/systemd/network";
+
+/// Directory of `systemd-networkd` configuration files created on the device.
+const NETWORKD_STATE_DIR: &str = "/etc/systemd/network";
+
+/// State configuration persisting the network configuration.
+const STATE_CONFIG: &str = "etc/rugix/state/network.toml";
+
+/// Interface used for Wi-Fi networks by default.
+const DEFAULT_WIFI_INTERFACE: &str = "wlan0";
+
+/// Name of cellular connections by default.
+const DEFAULT_CELLULAR_NAME: &str = "cellular";
+
+/// Permissions of files containing secrets.
+const SECRET_MODE: u32 = 0o600;
+
+/// IP configuration of an interface.
+#[derive(Debug, PartialEq, Eq)]
+struct IpConfig<'c> {
+    addresses: &'c [String],
+    gateway: Option<&'c str>,
+    dns: &'c [String],
+}
+
+impl<'c> IpConfig<'c> {
+    /// Validate and create an IP configuration.
+    fn new(
+        addresses: &'c Option<Vec<String>>,
+        gateway: &'c Option<String>,
+        dns: &'c Option<Vec<String>>,
+    ) -> BakeryResult<Self> {
+        let addresses = addresses.as_deref().unwrap_or_default();
+        for address in addresses {
+            check_address(address)?;
+        }
+        if let Some(gateway) = gateway {
+            let is_ipv6 = check_ip("gateway", gateway)?.is_ipv6();
+            if !addresses
+                .iter()
+                .any(|address| is_ipv6_address(address) == is_ipv6)
+            {
+                bail!("gateway {gateway:?} requires a static address of the same IP version");
+            }
+        }
+        let dns = dns.as_deref().unwrap_or_default();
+        for server in dns {
+            check_ip("DNS server", server)?;
+        }
+        Ok(Self {
+            addresses,
+            gateway: gateway.as_deref(),
+            dns,
+        })
+    }
+}
+
+/// Generate the network configuration of a `network` step in the root filesystem.
+pub fn configure_network(config: &NetworkConfig, root_dir: &Path) -> BakeryResult<()> {
+    let backend = match &config.backend {
+        Some(backend) => backend.clone(),
+        None if root_dir.join("usr/sbin/NetworkManager").exists()
+            || root_dir.join("usr/bin/nmcli").exists() =>
+        {
+            NetworkBackend::NetworkManager
+        }
+        None => NetworkBackend::Networkd,
+    };
+    let state_dir = match backend {
+        NetworkBackend::NetworkManager => {
+            configure_network_manager(config, root_dir)?;
+            NM_STATE_DIR
+        }
+        NetworkBackend::Networkd => {
+            configure_networkd(config, root_dir)?;
+            NETWORKD_STATE_DIR
+        }
+    };
+    if config.persist.unwrap_or(true) {
+        let state_config = root_dir.join(STATE_CONFIG);
+        fs::create_dir_all(state_config.parent().unwrap())
+            .whatever("unable to create state configuration directory")?;
+        fs::write(
+            &state_config,
+            format!("[[persist]]\ndirectory = \"{state_dir}\"\n"),
+        )
+        .whatever("unable to write state configuration")?;
+    }
+    Ok(())
+}
+
+fn configure_network_manager(config: &NetworkConfig, root_dir: &Path) -> BakeryResult<()> {
+    let profiles_dir = root_dir.join(NM_PROFILES_DIR);
+    fs::create_dir_all(&profiles_dir).whatever("unable to create profiles directory")?;
+    for interface in config.interfaces.as_deref().unwrap_or_default() {
+        check_interface(&interface.name)?;
+        let ip = IpConfig::new(&interface.addresses, &interface.gateway, &interface.dns)?;
+        let mut profile = nm_connection(&interface.name, "ethernet", Some(&interface.name));
+        nm_ip_config(&mut profile, &ip);
+        write_profile(&profiles_dir, &interface.name, &profile)?;
+    }
+    for wifi in config.wifi.as_deref().unwrap_or_default() {
+        check_value("SSID", &wifi.ssid)?;
+        if let Some(interface) = &wifi.interface {
+            check_interface(interface)?;
+        }
+        let ip = IpConfig::new(&wifi.addresses, &wifi.gateway, &wifi.dns)?;
+        let mut profile = nm_connection(&wifi.ssid, "wifi", wifi.interface.as_deref());
+        writeln!(profile, "\n[wifi]\nmode=infrastructure\nssid={}", wifi.ssid).unwrap();
+        if wifi.hidden.unwrap_or(false) {
+            writeln!(profile, "hidden=true").unwrap();
+        }
+        if let Some(psk) = wifi_psk(wifi)? {
+            writeln!(profile, "\n[wifi-security]\nkey-mgmt=wpa-psk\npsk={psk}").unwrap();
+        }
+        nm_ip_config(&mut profile, &ip);
+        write_profile(&profiles_dir, &wifi.ssid, &profile)?;
+    }
+    for cellular in config.cellular.as_deref().unwrap_or_default() {
+        let name = cellular.name.as_deref().unwrap_or(DEFAULT_CELLULAR_NAME);
+        let mut profile = nm_connection(name, "gsm", None);
+        check_value("APN", &cellular.apn)?;
+        writeln!(profile, "\n[gsm]\napn={}", cellular.apn).unwrap();
+        if let Some(username) = &cellular.username {
+            check_value("user name", username)?;
+            writeln!(profile, "username={username}").unwrap();
+        }
+        if let Some(variable) = &cellular.password_env {
+            writeln!(profile, "password={}", secret(variable)?).unwrap();
+        }
+        nm_ip_config(
+            &mut profile,
+            &IpConfig {
+                addresses: &[],
+                gateway: None,
+                dns: &[],
+            },
+        );
+        write_profile(&profiles_dir, name, &profile)?;
+    }
+    Ok(())
+}
+
+/// Start a NetworkManager profile with the `connection` section.
+fn nm_connection(id: &str, kind: &str, interface: Option<&str>) -> String {
+    // Profiles need a stable UUID, which we derive from their id.
+    let mut hasher = Sha1::new();
+    hasher.update(format!("rugix-network:{kind}:{id}"));
+    let digest = hasher.finalize();
+    let uuid = uuid::Builder::from_sha1_bytes(digest[..16].try_into().unwrap()).into_uuid();
+    let mut profile = format!("[connection]\nid={id}\nuuid={uuid}\ntype={kind}\n");
+    if let Some(interface) = interface {
+        writeln!(profile, "interface-name={interface}").unwrap();
+    }
+    profile.push_str("autoconnect=true\n");
+    profile
+}
+
+/// Add the `ipv4` and `ipv6` sections to a NetworkManager profile.
+fn nm_ip_config(profile: &mut String, ip: &IpConfig) {
+    for (section, is_ipv6) in [("ipv4", false), ("ipv6", true)] {
+        let addresses = ip
+            .addresses
+            .iter()
+            .filter(|address| is_ipv6_address(address) == is_ipv6)
+            .collect::<Vec<_>>();
+        let method = if addresses.is_empty() {
+            "auto"
+        } else {
+            "manual"
+        };
+        writeln!(profile, "\n[{section}]\nmethod={method}").unwrap();
+        for (idx, address) in addresses.iter().enumerate() {
+            // The gateway is attached to the first address of the same IP version.
+            let gateway = ip
+                .gateway
+                .filter(|gateway| idx == 0 && is_ipv6_address(gateway) == is_ipv6)
+                .map(|gateway| format!(",{gateway}"))
+                .unwrap_or_default();
+            writeln!(profile, "address{}={address}{gateway}", idx + 1).unwrap();
+        }
+        let dns = ip
+            .dns
+            .iter()
+            .filter(|server| is_ipv6_address(server) == is_ipv6)
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if !dns.is_empty() {
+            writeln!(profile, "dns={};", dns.join(";")).unwrap();
+        }
+    }
+}
+
+/// Write a NetworkManager profile, which must only be readable by root.
+fn write_profile(profiles_dir: &Path, name: &str, profile: &str) -> BakeryResult<()> {
+    let path = profiles_dir.join(format!("{}.nmconnection", file_name(name)?));
+    info!("writing NetworkManager profile {path:?}");
+    write_secret_file(&path, profile)
+}
+
+fn configure_networkd(config: &NetworkConfig, root_dir: &Path) -> BakeryResult<()> {
+    if config
+        .cellular
+        .as_ref()
+        .is_some_and(|cellular| !cellular.is_empty())
+    {
+        bail!("cellular connections require NetworkManager");
+    }
+    let network_dir = root_dir.join(NETWORKD_DIR);
+    fs::create_dir_all(&network_dir).whatever("unable to create network directory")?;
+    for interface in config.interfaces.as_deref().unwrap_or_default() {
+        check_interface(&interface.name)?;
+        let ip = IpConfig::new(&interface.addresses, &interface.gateway, &interface.dns)?;
+        write_networkd_network(&network_dir, &interface.name, &ip)?;
+    }
+    // Wi-Fi networks are handled by `wpa_supplicant` with one configuration file per
+    // interface, containing all networks of the interface.
+    let mut wifi_interfaces = BTreeMap::<&str, Vec<&WifiConfig>>::new();
+    for wifi in config.wifi.as_deref().unwrap_or_default() {
+        let interface = wifi.interface.as_deref().unwrap_or(DEFAULT_WIFI_INTERFACE);
+        check_interface(interface)?;
+        wifi_interfaces.entry(interface).or_default().push(wifi);
+    }
+    for (interface, networks) in wifi_interfaces {
+        // The IP configuration belongs to the interface and not to the network.
+        let ip = IpConfig::new(
+            &networks[0].addresses,
+            &networks[0].gateway,
+            &networks[0].dns,
+        )?;
+        for wifi in &networks[1..] {
+            if IpConfig::new(&wifi.addresses, &wifi.gateway, &wifi.dns)? != ip {
+                bail!("Wi-Fi networks on {interface:?} must have the same IP configuration");
+            }
+        }
+        write_networkd_network(&network_dir, interface, &ip)?;
+        let mut supplicant = String::from("ctrl_interface=DIR=/run/wpa_supplicant\n");
+        for wifi in networks {
+            check_value("SSID", &wifi.ssid)?;
+            writeln!(supplicant, "\nnetwork={{\n    ssid=\"{}\"", wifi.ssid).unwrap();
+            match wifi_psk(wifi)? {
+                Some(psk) => writeln!(supplicant, "    psk=\"{psk}\"").unwrap(),
+                None => writeln!(supplicant, "    key_mgmt=NONE").unwrap(),
+            }
+            if wifi.hidden.unwrap_or(false) {
+                writeln!(supplicant, "    scan_ssid=1").unwrap();
+            }
+            supplicant.push_str("}\n");
+        }
+        let supplicant_dir = root_dir.join("etc/wpa_supplicant");
+        fs::create_dir_all(&supplicant_dir)
+            .whatever("unable to create `wpa_supplicant` directory")?;
+        let path = supplicant_dir.join(format!("wpa_supplicant-{}.conf", file_name(interface)?));
+        info!("writing `wpa_supplicant` configuration {path:?}");
+        write_secret_file(&path, &supplicant)?;
+    }
+    Ok(())
+}
+
+/// Write a `systemd-networkd` network file for the given interface.
+fn write_networkd_network(network_dir: &Path, interface: &str, ip: &IpConfig) -> BakeryResult<()> {
+    let mut network = format!("[Match]\nName={interface}\n\n[Network]\n");
+    if ip.addresses.is_empty() {
+        network.push_str("DHCP=yes\n");
+    }
+    for address in ip.addresses {
+        writeln!(network, "Address={address}").unwrap();
+    }
+    if let Some(gateway) = ip.gateway {
+        writeln!(network, "Gateway={gateway}").unwrap();
+    }
+    for dns in ip.dns {
+        writeln!(network, "DNS={dns}").unwrap();
+    }
+    let path = network_dir.join(format!("50-rugix-{}.network", file_name(interface)?));
+    info!("writing network configuration {path:?}");
+    fs::write(&path, network).whatever("unable to write network configuration")
+}
+
+/// Pre-shared key of a Wi-Fi network, if any.
+fn wifi_psk(wifi: &WifiConfig) -> BakeryResult<Option<String>> {
+    let Some(variable) = &wifi.psk_env else {
+        return Ok(None);
+    };
+    let psk = secret(variable)?;
+    if psk.len() < 8 || psk.len() > 63 {
+        bail!(
+            "pre-shared key of {:?} must have 8 to 63 characters",
+            wifi.ssid
+        );
+    }
+    Ok(Some(psk))
+}
+
+/// Read a secret from the given environment variable.
+fn secret(variable: &str) -> BakeryResult<String> {
+    let Ok(value) = std::env::var(variable) else {
+        bail!("environment variable `{variable}` with secret is not set");
+    };
+    check_value(variable, &value)?;
+    Ok(value)
+}
+
+/// Check that a value can be embedded into configuration files.
+fn check_value(what: &str, value: &str) -> BakeryResult<()> {
+    if value.contains(['\n', '"']) {
+        bail!("{what} must not contain newlines or quotes");
+    }
+    Ok(())
+}
+
+/// Check that a name is a valid name of a network interface.
+fn check_interface(name: &str) -> BakeryResult<()> {
+    // Linux limits interface names to 15 bytes.
+    if name.is_empty()
+        || name.len() > 15
+        || name == "."
+        || name == ".."
+        || name.contains(|c: char| c == '/' || c == ':' || c.is_whitespace() || c.is_control())
+    {
+        bail!("invalid network interface name {name:?}");
+    }
+    Ok(())
+}
+
+/// Check that an address is an IP address with prefix length, e.g., `192.168.1.10/24`.
+fn check_address(address: &str) -> BakeryResult<()> {
+    let is_valid = address.split_once('/').is_some_and(|(ip, prefix)| {
+        let max_prefix = match ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => 32,
+            Ok(IpAddr::V6(_)) => 128,
+            Err(_) => return false,
+        };
+        prefix
+            .parse::<u8>()
+            .is_ok_and(|prefix| prefix <= max_prefix)
+    });
+    if !is_valid {
+        bail!("invalid address {address:?}, expected an IP address with prefix length");
+    }
+    Ok(())
+}
+
+/// Check that a value is an IP address.
+fn check_ip(what: &str, value: &str) -> BakeryResult<IpAddr> {
+    let Ok(ip) = value.parse() else {
+        bail!("invalid {what} {value:?}, expected an IP address");
+    };
+    Ok(ip)
+}
+
+/// Indicates whether a validated address is an IPv6 address.
+fn is_ipv6_address(address: &str) -> bool {
+    address.contains(':')
+}
+
+/// Turn the name of a connection or interface into a file name.
+fn file_name(name: &str) -> BakeryResult<String> {
+    if name.is_empty() || name.contains('/') {
+        bail!("invalid name {name:?} of network configuration");
+    }
+    Ok(name.replace(' ', "_"))
+}
+
+/// Write a file containing secrets, which must only be readable by root.
+fn write_secret_file(path: &Path, content: &str) -> BakeryResult<()> {
+    fs::write(path, content).whatever_with(|_| format!("unable to write {path:?}"))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(SECRET_MODE))
+        .whatever_with(|_| format!("unable to set permissions of {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::recipes::NetworkInterfaceConfig;
+
+    fn strings(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn test_nm_ip_config() {
+        let addresses = strings(&["192.168.1.10/24", "fd00::10/64", "192.168.2.10/24"]);
+        let gateway = Some("192.168.1.1".to_owned());
+        let dns = strings(&["1.1.1.1", "2606:4700:4700::1111"]);
+        let ip = IpConfig::new(&addresses, &gateway, &dns).unwrap();
+        let mut profile = String::new();
+        nm_ip_config(&mut profile, &ip);
+        assert_eq!(
+            profile,
+            "\n[ipv4]\nmethod=manual\naddress1=192.168.1.10/24,192.168.1.1\n\
+            address2=192.168.2.10/24\ndns=1.1.1.1;\n\
+            \n[ipv6]\nmethod=manual\naddress1=fd00::10/64\ndns=2606:4700:4700::1111;\n"
+        );
+        let mut profile = String::new();
+        nm_ip_config(&mut profile, &IpConfig::new(&None, &None, &None).unwrap());
+        assert_eq!(profile, "\n[ipv4]\nmethod=auto\n\n[ipv6]\nmethod=auto\n");
+    }
+
+    #[test]
+    fn test_network_manager() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let config = NetworkConfig::new()
+            .with_backend(Some(NetworkBackend::NetworkManager))
+            .with_interfaces(Some(vec![NetworkInterfaceConfig::new("eth0".to_owned())]))
+            .with_wifi(Some(vec![WifiConfig::new("Home Network".to_owned())]));
+        configure_network(&config, root_dir.path()).unwrap();
+        let profiles_dir = root_dir.path().join(NM_PROFILES_DIR);
+        let ethernet = fs::read_to_string(profiles_dir.join("eth0.nmconnection")).unwrap();
+        assert!(ethernet.starts_with("[connection]\nid=eth0\nuuid="));
+        assert!(ethernet.contains("type=ethernet\ninterface-name=eth0\nautoconnect=true\n"));
+        let wifi_path = profiles_dir.join("Home_Network.nmconnection");
+        let wifi = fs::read_to_string(&wifi_path).unwrap();
+        assert!(wifi.contains("\n[wifi]\nmode=infrastructure\nssid=Home Network\n"));
+        assert!(!wifi.contains("[wifi-security]"));
+        let mode = fs::metadata(&wifi_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, SECRET_MODE);
+        assert_eq!(
+            fs::read_to_string(root_dir.path().join(STATE_CONFIG)).unwrap(),
+            format!("[[persist]]\ndirectory = \"{NM_STATE_DIR}\"\n")
+        );
+    }
+
+    #[test]
+    fn test_networkd() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let config = NetworkConfig::new()
+            .with_backend(Some(NetworkBackend::Networkd))
+            .with_persist(Some(false))
+            .with_interfaces(Some(vec![NetworkInterfaceConfig::new("eth0".to_owned())
+                .with_addresses(strings(&["192.168.1.10/24"]))
+                .with_gateway(Some("192.168.1.1".to_owned()))
+                .with_dns(strings(&["192.168.1.1"]))]))
+            .with_wifi(Some(vec![
+                WifiConfig::new("First".to_owned()),
+                WifiConfig::new("Second".to_owned()).with_hidden(Some(true)),
+            ]));
+        configure_network(&config, root_dir.path()).unwrap();
+        let network_dir = root_dir.path().join(NETWORKD_DIR);
+        assert_eq!(
+            fs::read_to_string(network_dir.join("50-rugix-eth0.network")).unwrap(),
+            "[Match]\nName=eth0\n\n[Network]\nAddress=192.168.1.10/24\n\
+            Gateway=192.168.1.1\nDNS=192.168.1.1\n"
+        );
+        assert_eq!(
+            fs::read_to_string(network_dir.join("50-rugix-wlan0.network")).unwrap(),
+            "[Match]\nName=wlan0\n\n[Network]\nDHCP=yes\n"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                root_dir
+                    .path()
+                    .join("etc/wpa_supplicant/wpa_supplicant-wlan0.conf")
+            )
+            .unwrap(),
+            "ctrl_interface=DIR=/run/wpa_supplicant\n\
+            \nnetwork={\n    ssid=\"First\"\n    key_mgmt=NONE\n}\n\
+            \nnetwork={\n    ssid=\"Second\"\n    key_mgmt=NONE\n    scan_ssid=1\n}\n"
+        );
+        assert!(!root_dir.path().join(STATE_CONFIG).exists());
+    }
+
+    #[test]
+    fn test_networkd_differing_wifi_ip_configs() {
+        let root_dir = tempfile::tempdir().unwrap();
+        let config = NetworkConfig::new()
+            .with_backend(Some(NetworkBackend::Networkd))
+            .with_wifi(Some(vec![
+                WifiConfig::new("First".to_owned()),
+                WifiConfig::new("Second".to_owned()).with_addresses(strings(&["10.0.0.2/8"])),
+            ]));
+        assert!(configure_network(&config, root_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        for name in ["", "eth 0", "../eth0", "eth0:1", "interface-too-long"] {
+            assert!(check_interface(name).is_err(), "{name:?}");
+        }
+        assert!(check_interface("enp0s31f6").is_ok());
+        for address in ["192.168.1.10", "192.168.1.10/33", "fd00::1/129", "host/24"] {
+            assert!(check_address(address).is_err(), "{address:?}");
+        }
+        assert!(check_address("fd00::1/64").is_ok());
+        assert!(IpConfig::new(&None, &Some("192.168.1.1".to_owned()), &None).is_err());
+        assert!(IpConfig::new(
+            &strings(&["fd00::10/64"]),
+            &Some("10.0.0.1".to_owned()),
+            &None
+        )
+        .is_err());
+        assert!(IpConfig::new(&None, &None, &strings(&["dns.example.com"])).is_err());
+    }
+}
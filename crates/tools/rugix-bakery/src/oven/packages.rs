@@ -0,0 +1,78 @@
+//! Packages installed in root filesystems.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Package installed with the package manager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    /// Version of the package.
+    pub version: String,
+    /// Installed size in bytes.
+    pub size: u64,
+}
+
+/// Read the packages installed in the given root filesystem by their name.
+///
+/// Supports the package databases of `dpkg` and `apk`.
+pub fn installed_packages(root: &Path) -> BTreeMap<String, Package> {
+    let mut packages = BTreeMap::new();
+    if let Ok(status) = fs::read_to_string(root.join("var/lib/dpkg/status")) {
+        for paragraph in status.split("\n\n") {
+            let mut name = None;
+            let mut version = None;
+            let mut size = None;
+            let mut installed = false;
+            for line in paragraph.lines() {
+                if let Some(value) = line.strip_prefix("Package:") {
+                    name = Some(value.trim());
+                } else if let Some(value) = line.strip_prefix("Version:") {
+                    version = Some(value.trim());
+                } else if let Some(value) = line.strip_prefix("Installed-Size:") {
+                    // The installed size is given in KiB.
+                    size = value.trim().parse::<u64>().ok().map(|size| size * 1024);
+                } else if let Some(value) = line.strip_prefix("Status:") {
+                    installed = value.trim().ends_with(" installed");
+                }
+            }
+            if let (Some(name), true) = (name, installed) {
+                packages.insert(
+                    name.to_owned(),
+                    Package {
+                        version: version.unwrap_or_default().to_owned(),
+                        size: size.unwrap_or_default(),
+                    },
+                );
+            }
+        }
+    }
+    if let Ok(installed) = fs::read_to_string(root.join("lib/apk/db/installed")) {
+        for paragraph in installed.split("\n\n") {
+            let mut name = None;
+            let mut version = None;
+            let mut size = None;
+            for line in paragraph.lines() {
+                if let Some(value) = line.strip_prefix("P:") {
+                    name = Some(value);
+                } else if let Some(value) = line.strip_prefix("V:") {
+                    version = Some(value);
+                } else if let Some(value) = line.strip_prefix("I:") {
+                    size = value.parse::<u64>().ok();
+                }
+            }
+            if let Some(name) = name {
+                packages.insert(
+                    name.to_owned(),
+                    Package {
+                        version: version.unwrap_or_default().to_owned(),
+                        size: size.unwrap_or_default(),
+                    },
+                );
+            }
+        }
+    }
+    packages
+}
@@ -0,0 +1,143 @@
+//! Planning of bakes without executing them.
+//!
+//! The plan resolves the layers, recipes, and parameters of a bake and determines, based
+//! on the state of the cache, which layers would be rebuilt and why.
+
+use std::path::PathBuf;
+
+use reportify::bail;
+
+use crate::oven::customize::{rebuild_reasons, recipe_schedule};
+use crate::oven::LayerBakery;
+use crate::project::library::LayerIdx;
+use crate::utils::caching::mtime;
+use crate::BakeryResult;
+
+/// Action taken for a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanAction {
+    /// The layer is taken from the cache.
+    Cached,
+    /// The layer is extracted from an image.
+    Download,
+    /// The layer is built by applying its recipes.
+    Build,
+}
+
+impl PlanAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PlanAction::Cached => "cached",
+            PlanAction::Download => "download",
+            PlanAction::Build => "build",
+        }
+    }
+}
+
+/// Plan of a layer.
+#[derive(Debug, Clone)]
+pub struct LayerPlan {
+    /// Name of the layer.
+    pub name: String,
+    /// Action taken for the layer.
+    pub action: PlanAction,
+    /// Reasons why the layer is rebuilt.
+    pub reasons: Vec<String>,
+    /// Recipes applied to the layer, including their parameters.
+    pub recipes: Vec<String>,
+    /// Path of the layer in the cache.
+    pub path: PathBuf,
+}
+
+impl LayerBakery<'_> {
+    /// Plan the bake of the given layer.
+    ///
+    /// Returns the plans of the layer and its ancestors in the order in which they would
+    /// be baked.
+    pub fn plan_root(&self, layer: &str) -> BakeryResult<Vec<LayerPlan>> {
+        let library = self.project.library()?;
+        let Some(layer) = library.lookup_layer(library.repositories.root_repository, layer) else {
+            bail!("unable to find layer {layer}");
+        };
+        let mut plans = Vec::new();
+        self.plan(layer, &mut plans)?;
+        Ok(plans)
+    }
+
+    fn plan(&self, layer: LayerIdx, plans: &mut Vec<LayerPlan>) -> BakeryResult<()> {
+        let library = self.project.library()?;
+        let layer = &library.layers[layer];
+        let Some(config) = layer.config(self.arch) else {
+            bail!("no layer configuration for architecture `{}`", self.arch);
+        };
+        let layer_path = PathBuf::from(format!(".rugix/layers/{}", self.layer_id(layer, config)?));
+        let path = self.project.dir().join(&layer_path).join("system.tar");
+        if let Some(url) = &config.url {
+            let (action, reasons) = if path.exists() {
+                (PlanAction::Cached, Vec::new())
+            } else {
+                (
+                    PlanAction::Download,
+                    vec![format!("image {url} has not been extracted yet")],
+                )
+            };
+            plans.push(LayerPlan {
+                name: layer.name.clone(),
+                action,
+                reasons,
+                recipes: Vec::new(),
+                path,
+            });
+            return Ok(());
+        }
+        let mut parent_rebuilt = None;
+        let mut src_modified = None;
+        if let Some(parent) = &config.parent {
+            let Some(parent_idx) = library.lookup_layer(layer.repo, parent) else {
+                bail!("unable to find layer `{parent}`");
+            };
+            self.plan(parent_idx, plans)?;
+            let parent_plan = plans.last().unwrap();
+            if parent_plan.action == PlanAction::Cached {
+                src_modified = mtime(&parent_plan.path).ok();
+            } else {
+                parent_rebuilt = Some(parent);
+            }
+        }
+        let jobs = recipe_schedule(layer.repo, config, &library)?;
+        let mut reasons =
+            rebuild_reasons(self.project, layer, &jobs, src_modified, &path, &layer_path)?;
+        if let Some(parent) = parent_rebuilt {
+            reasons.push(format!("parent layer `{parent}` is rebuilt"));
+        }
+        let recipes = jobs
+            .iter()
+            .map(|job| {
+                let mut parameters = job
+                    .parameters
+                    .iter()
+                    .map(|(name, value)| format!("{name}={value:?}"))
+                    .collect::<Vec<_>>();
+                parameters.sort();
+                if parameters.is_empty() {
+                    job.recipe.name.to_string()
+                } else {
+                    format!("{} ({})", job.recipe.name, parameters.join(", "))
+                }
+            })
+            .collect();
+        let action = if reasons.is_empty() {
+            PlanAction::Cached
+        } else {
+            PlanAction::Build
+        };
+        plans.push(LayerPlan {
+            name: layer.name.clone(),
+            action,
+            reasons,
+            recipes,
+            path,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! Build profiles of systems, e.g., a development profile with a writable root.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use reportify::ResultExt;
+use tracing::{info, warn};
+
+use crate::config::systems::{ProfileOverlay, ProfileSshConfig, SystemProfileConfig};
+use crate::BakeryResult;
+
+/// State configuration of Rugix Ctrl.
+const STATE_CONFIG: &str = "etc/rugix/state.toml";
+
+/// Configuration of the SSH server.
+const SSHD_CONFIG: &str = "etc/ssh/sshd_config";
+
+/// Drop-in with the SSH settings of the profile.
+const SSHD_PROFILE_CONFIG: &str = "etc/ssh/sshd_config.d/50-rugix-profile.conf";
+
+/// Apply the build profile to the root filesystem of a system.
+pub fn apply_profile(profile: &SystemProfileConfig, system_dir: &Path) -> BakeryResult<()> {
+    if let Some(overlay) = &profile.overlay {
+        set_overlay(system_dir, overlay)?;
+    }
+    if let Some(ssh) = &profile.ssh {
+        configure_ssh(system_dir, ssh)?;
+    }
+    Ok(())
+}
+
+fn set_overlay(system_dir: &Path, overlay: &ProfileOverlay) -> BakeryResult<()> {
+    let overlay = match overlay {
+        ProfileOverlay::Persist => "persist",
+        ProfileOverlay::Discard => "discard",
+        ProfileOverlay::InMemory => "in-memory",
+        ProfileOverlay::Disabled => "disabled",
+    };
+    info!("setting root overlay to {overlay:?}");
+    let config_path = system_dir.join(STATE_CONFIG);
+    // Keep any other state configuration of the layer.
+    let mut config = match fs::read_to_string(&config_path) {
+        Ok(config) => toml::from_str::<toml::Table>(&config)
+            .whatever("unable to parse `/etc/rugix/state.toml`")?,
+        Err(_) => toml::Table::new(),
+    };
+    config.insert(
+        "overlay".to_owned(),
+        toml::Value::String(overlay.to_owned()),
+    );
+    fs::create_dir_all(config_path.parent().unwrap()).whatever("unable to create `/etc/rugix`")?;
+    fs::write(&config_path, toml::to_string(&config).unwrap())
+        .whatever("unable to write `/etc/rugix/state.toml`")
+}
+
+fn configure_ssh(system_dir: &Path, ssh: &ProfileSshConfig) -> BakeryResult<()> {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let mut config = String::new();
+    if let Some(value) = ssh.permit_root_login {
+        writeln!(config, "PermitRootLogin {}", yes_no(value)).unwrap();
+    }
+    if let Some(value) = ssh.password_authentication {
+        writeln!(config, "PasswordAuthentication {}", yes_no(value)).unwrap();
+    }
+    if let Some(value) = ssh.permit_empty_passwords {
+        writeln!(config, "PermitEmptyPasswords {}", yes_no(value)).unwrap();
+    }
+    if config.is_empty() {
+        return Ok(());
+    }
+    let sshd_config = fs::read_to_string(system_dir.join(SSHD_CONFIG)).unwrap_or_default();
+    if !sshd_config.lines().any(|line| {
+        line.trim_start()
+            .starts_with("Include /etc/ssh/sshd_config.d/")
+    }) {
+        warn!("`/etc/ssh/sshd_config` does not include `/etc/ssh/sshd_config.d`");
+    }
+    info!("writing SSH settings of profile");
+    let config_path = system_dir.join(SSHD_PROFILE_CONFIG);
+    fs::create_dir_all(config_path.parent().unwrap())
+        .whatever("unable to create `/etc/ssh/sshd_config.d`")?;
+    fs::write(&config_path, config).whatever("unable to write SSH settings")
+}
@@ -0,0 +1,325 @@
+//! SLSA provenance attestations in the in-toto format.
+//!
+//! For each bake, an in-toto statement with a SLSA provenance predicate is emitted. The
+//! statement is wrapped in a DSSE envelope which is signed if a key is configured.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use base64::prelude::*;
+use reportify::{bail, ResultExt};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tempfile::tempdir;
+use tracing::info;
+use url::Url;
+use xscript::{cmd_os, ParentEnv, Run};
+
+use crate::config::repositories::SourceConfig;
+use crate::config::systems::ProvenanceConfig;
+use crate::oven::artifacts::{sha256_file, ArtifactManifest};
+use crate::oven::customize::recipe_schedule;
+use crate::oven::system::ReleaseInfo;
+use crate::oven::version::git_safe_directory;
+use crate::project::ProjectRef;
+use crate::utils::caching::cached_download;
+use crate::BakeryResult;
+
+/// Name of the provenance file in the build directory of a system.
+pub const PROVENANCE_FILE: &str = "provenance.intoto.jsonl";
+
+/// Payload type of in-toto statements in DSSE envelopes.
+const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Build type of Rugix Bakery builds.
+const BUILD_TYPE: &str = "https://oss.silitics.com/rugix/bakery/build-types/system/v1";
+
+/// Builder id of Rugix Bakery.
+const BUILDER_ID: &str = "https://github.com/silitics/rugix/bakery";
+
+/// Prefix of PKCS#11 URIs.
+const PKCS11_URI_PREFIX: &str = "pkcs11:";
+
+/// Resource descriptor of the in-toto attestation framework.
+#[derive(Debug, Clone, Default, Serialize)]
+struct ResourceDescriptor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    digest: BTreeMap<&'static str, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    annotations: BTreeMap<&'static str, serde_json::Value>,
+}
+
+/// Information about the bake for which provenance is recorded.
+pub struct BakeInfo<'i> {
+    /// Command used for the bake, e.g., `bake image`.
+    pub command: &'i str,
+    /// Name of the system.
+    pub system: &'i str,
+    /// Release information of the system.
+    pub release: &'i ReleaseInfo,
+    /// Source date epoch used for the bake.
+    pub source_date_epoch: u64,
+    /// Time at which the bake has been started.
+    pub started_on: jiff::Timestamp,
+}
+
+/// Write the provenance attestation for the artifacts of a system.
+pub fn write_provenance(
+    project: &ProjectRef,
+    bake: &BakeInfo,
+    system_path: &Path,
+    manifest: &ArtifactManifest,
+) -> BakeryResult<()> {
+    info!("writing provenance attestation");
+    let system_config = project.config().resolve_system_config(bake.system)?;
+    let subject = manifest
+        .artifacts
+        .iter()
+        .map(|artifact| ResourceDescriptor {
+            name: Some(artifact.path.to_string_lossy().into_owned()),
+            digest: BTreeMap::from([("sha256", artifact.sha256.clone())]),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+    let statement = json!({
+        "_type": "https://in-toto.io/Statement/v1",
+        "subject": subject,
+        "predicateType": "https://slsa.dev/provenance/v1",
+        "predicate": {
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "command": bake.command,
+                    "system": bake.system,
+                    "release": bake.release,
+                },
+                "internalParameters": {
+                    "architecture": system_config.architecture.as_str(),
+                    "sourceDateEpoch": bake.source_date_epoch,
+                },
+                "resolvedDependencies": resolved_dependencies(project, bake.system)?,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": BUILDER_ID,
+                    "version": {
+                        "rugix-bakery": rugix_version::RUGIX_GIT_VERSION,
+                    },
+                },
+                "metadata": {
+                    "startedOn": bake.started_on.to_string(),
+                    "finishedOn": jiff::Timestamp::now().to_string(),
+                },
+            },
+        },
+    });
+    let payload = serde_json::to_vec(&statement).unwrap();
+    let mut signatures = Vec::new();
+    if let Some(config) = &system_config.provenance {
+        let mut signature = json!({
+            "sig": BASE64_STANDARD.encode(sign(project, config, &payload)?),
+        });
+        if let Some(key_id) = &config.key_id {
+            signature["keyid"] = json!(key_id);
+        }
+        signatures.push(signature);
+    }
+    let envelope = json!({
+        "payloadType": PAYLOAD_TYPE,
+        "payload": BASE64_STANDARD.encode(&payload),
+        "signatures": signatures,
+    });
+    let mut envelope = serde_json::to_string(&envelope).unwrap();
+    envelope.push('\n');
+    fs::write(system_path.join(PROVENANCE_FILE), envelope)
+        .whatever("unable to write provenance attestation")?;
+    Ok(())
+}
+
+/// Collect the inputs of the build of a system.
+fn resolved_dependencies(
+    project: &ProjectRef,
+    system: &str,
+) -> BakeryResult<Vec<ResourceDescriptor>> {
+    let system_config = project.config().resolve_system_config(system)?;
+    let arch = system_config.architecture;
+    let library = project.library()?;
+    let repositories = &library.repositories;
+    let mut dependencies = vec![project_descriptor(project)];
+    for (idx, repository) in repositories.iter() {
+        if idx == repositories.root_repository {
+            continue;
+        }
+        let source = &repository.source;
+        let mut descriptor = ResourceDescriptor {
+            name: Some(format!("repository:{}", source.id.as_short_str())),
+            ..Default::default()
+        };
+        if let SourceConfig::Git(git) = &source.config {
+            descriptor.uri = Some(format!("git+{}", git.url));
+        }
+        if let Some(commit) = &source.commit {
+            descriptor.digest.insert("gitCommit", commit.clone());
+        }
+        dependencies.push(descriptor);
+    }
+    // Walk the layer hierarchy from the system's layer to the base layer.
+    let mut next_layer = library.lookup_layer(repositories.root_repository, &system_config.layer);
+    while let Some(layer_idx) = next_layer.take() {
+        let layer = &library.layers[layer_idx];
+        let Some(config) = layer.config(arch) else {
+            bail!("no layer configuration for architecture `{arch}`");
+        };
+        if let Some(url) = &config.url {
+            let mut descriptor = ResourceDescriptor {
+                name: Some(format!("layer:{}", layer.name)),
+                uri: Some(url.clone()),
+                ..Default::default()
+            };
+            let image_path = Url::parse(url).ok().and_then(|url| match url.scheme() {
+                "file" => Some(project.dir().join(url.path().trim_start_matches('/'))),
+                _ => cached_download(&url),
+            });
+            if let Some(image_path) = image_path {
+                descriptor.digest.insert(
+                    "sha256",
+                    sha256_file(&image_path).whatever("unable to hash base layer")?,
+                );
+            }
+            dependencies.push(descriptor);
+        } else {
+            for job in recipe_schedule(layer.repo, config, &library)? {
+                let recipe = &job.recipe;
+                let repository = &repositories.repositories[recipe.repository];
+                let mut descriptor = ResourceDescriptor {
+                    name: Some(format!(
+                        "recipe:{}/{}",
+                        repository.source.id.as_short_str(),
+                        recipe.name
+                    )),
+                    digest: BTreeMap::from([(
+                        "sha256",
+                        hash_dir(&recipe.path).whatever("unable to hash recipe")?,
+                    )]),
+                    ..Default::default()
+                };
+                descriptor
+                    .annotations
+                    .insert("layer", json!(layer.name.clone()));
+                dependencies.push(descriptor);
+            }
+        }
+        if let Some(parent) = &config.parent {
+            next_layer = library.lookup_layer(layer.repo, parent);
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Describe the project directory by its Git commit, if it is a Git repository.
+fn project_descriptor(project: &ProjectRef) -> ResourceDescriptor {
+    let dir = project.dir();
+    let mut descriptor = ResourceDescriptor {
+        name: Some("project".to_owned()),
+        ..Default::default()
+    };
+    let safe_directory = git_safe_directory(dir);
+    let git = |args: &[&str]| {
+        let mut cmd = cmd_os!("git", "-c", &safe_directory, "-C", dir);
+        cmd.extend_args(args);
+        ParentEnv.read_str(cmd)
+    };
+    if let Ok(commit) = git(&["rev-parse", "HEAD"]) {
+        descriptor
+            .digest
+            .insert("gitCommit", commit.trim().to_owned());
+        if let Ok(remote) = git(&["remote", "get-url", "origin"]) {
+            descriptor.uri = Some(format!("git+{}", remote.trim()));
+        }
+        if let Ok(status) = git(&["status", "--porcelain"]) {
+            descriptor
+                .annotations
+                .insert("dirty", json!(!status.trim().is_empty()));
+        }
+    }
+    descriptor
+}
+
+/// Compute a SHA-256 hash over the files of a directory.
+///
+/// The hash covers the relative paths and contents of all files in sorted order.
+pub(crate) fn hash_dir(dir: &Path) -> std::io::Result<String> {
+    fn collect(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                collect(&entry.path(), files)?;
+            } else {
+                files.push(entry.path());
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    collect(dir, &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap();
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(sha256_file(&file)?.as_bytes());
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Sign the DSSE pre-authentication encoding of the payload with `openssl`.
+///
+/// Key files are resolved relative to the project directory.
+fn sign(project: &ProjectRef, config: &ProvenanceConfig, payload: &[u8]) -> BakeryResult<Vec<u8>> {
+    let uses_pkcs11 = config.key.starts_with(PKCS11_URI_PREFIX);
+    let key = if uses_pkcs11 {
+        OsString::from(&config.key)
+    } else {
+        let path = project.dir().join(&config.key);
+        if !path.is_file() {
+            bail!("provenance signing key {path:?} does not exist");
+        }
+        path.into_os_string()
+    };
+    let mut pae = format!(
+        "DSSEv1 {} {PAYLOAD_TYPE} {} ",
+        PAYLOAD_TYPE.len(),
+        payload.len()
+    )
+    .into_bytes();
+    pae.extend_from_slice(payload);
+    let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+    let pae_path = temp_dir.path().join("pae");
+    let signature_path = temp_dir.path().join("signature");
+    fs::write(&pae_path, &pae).whatever("unable to write provenance payload")?;
+    let mut cmd = cmd_os!(
+        "openssl",
+        "dgst",
+        "-sha256",
+        "-sign",
+        &key,
+        "-out",
+        &signature_path
+    );
+    if uses_pkcs11 {
+        cmd.extend_args(["-provider", "pkcs11", "-provider", "default"]);
+    }
+    cmd.add_arg(&pae_path);
+    ParentEnv
+        .run(cmd)
+        .whatever("unable to sign provenance attestation")?;
+    fs::read(&signature_path).whatever("unable to read signature")
+}
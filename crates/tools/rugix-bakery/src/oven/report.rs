@@ -0,0 +1,231 @@
+//! Build reports breaking down the time spent per layer, recipe, and step.
+//!
+//! Timings are recorded while baking, if reporting has been enabled with [`enable`], and
+//! written as JSON and HTML with [`write_report`].
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use reportify::ResultExt;
+use serde::Serialize;
+use tracing::info;
+
+use crate::BakeryResult;
+
+/// Name of the JSON report file.
+pub const REPORT_JSON_FILE: &str = "report.json";
+
+/// Name of the HTML report file.
+pub const REPORT_HTML_FILE: &str = "report.html";
+
+/// Timings recorded during the current bake, if reporting is enabled.
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Recorder {
+    started: Instant,
+    layers: Vec<LayerReport>,
+    phases: Vec<PhaseReport>,
+}
+
+/// Build report of a bake.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildReport {
+    /// Command used for the bake, e.g., `bake image`.
+    pub command: String,
+    /// Name of the system or layer which has been baked.
+    pub target: String,
+    /// Total duration of the bake in seconds.
+    pub duration: f64,
+    /// Layers in the order in which they have been baked.
+    pub layers: Vec<LayerReport>,
+    /// Phases after baking the layers, e.g., creating the image.
+    pub phases: Vec<PhaseReport>,
+}
+
+/// Report of a layer.
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerReport {
+    /// Name of the layer.
+    pub name: String,
+    /// Architecture of the layer.
+    pub arch: String,
+    /// Indicates whether the layer has been taken from the cache.
+    pub cached: bool,
+    /// Duration in seconds, excluding the time spent on parent layers.
+    pub duration: f64,
+    /// Recipes applied to the layer.
+    pub recipes: Vec<RecipeReport>,
+}
+
+/// Report of a recipe applied to a layer.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeReport {
+    /// Name of the recipe.
+    pub name: String,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// Steps of the recipe.
+    pub steps: Vec<StepReport>,
+}
+
+/// Report of a recipe step.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    /// Filename of the step.
+    pub filename: String,
+    /// Duration in seconds.
+    pub duration: f64,
+}
+
+/// Report of a phase of the bake.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseReport {
+    /// Name of the phase.
+    pub name: String,
+    /// Duration in seconds.
+    pub duration: f64,
+}
+
+/// Enable recording of timings for the build report.
+pub fn enable() {
+    *RECORDER.lock().unwrap() = Some(Recorder {
+        started: Instant::now(),
+        layers: Vec::new(),
+        phases: Vec::new(),
+    });
+}
+
+/// Record the report of a layer.
+pub fn record_layer(layer: LayerReport) {
+    if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+        recorder.layers.push(layer);
+    }
+}
+
+/// Run a phase of the bake and record its duration.
+pub fn phase<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+        recorder.phases.push(PhaseReport {
+            name: name.to_owned(),
+            duration: started.elapsed().as_secs_f64(),
+        });
+    }
+    result
+}
+
+/// Write the build report to the given directory, if reporting is enabled.
+///
+/// The file names are prefixed with the given prefix.
+pub fn write_report(command: &str, target: &str, dir: &Path, prefix: &str) -> BakeryResult<()> {
+    let Some(recorder) = RECORDER.lock().unwrap().take() else {
+        return Ok(());
+    };
+    let report = BuildReport {
+        command: command.to_owned(),
+        target: target.to_owned(),
+        duration: recorder.started.elapsed().as_secs_f64(),
+        layers: recorder.layers,
+        phases: recorder.phases,
+    };
+    fs::create_dir_all(dir).whatever("unable to create report directory")?;
+    let json_path = dir.join(format!("{prefix}{REPORT_JSON_FILE}"));
+    let html_path = dir.join(format!("{prefix}{REPORT_HTML_FILE}"));
+    info!("writing build report to {html_path:?}");
+    fs::write(&json_path, serde_json::to_string_pretty(&report).unwrap())
+        .whatever("unable to write build report")?;
+    fs::write(&html_path, render_html(&report)).whatever("unable to write build report")?;
+    Ok(())
+}
+
+/// Render the report as a self-contained HTML page.
+fn render_html(report: &BuildReport) -> String {
+    let total = report.duration.max(f64::EPSILON);
+    let mut rows = String::new();
+    let mut row = |level: usize, name: &str, status: &str, duration: f64| {
+        let width = (duration / total * 100.0).clamp(0.0, 100.0);
+        writeln!(
+            rows,
+            "<tr class=\"level-{level}\"><td>{}</td><td class=\"{status}\">{status}</td>\
+            <td class=\"time\">{}</td><td class=\"bar\"><div style=\"width: {width:.2}%\"></div></td></tr>",
+            escape(name),
+            format_duration(duration),
+        )
+        .unwrap();
+    };
+    for layer in &report.layers {
+        let status = if layer.cached { "hit" } else { "miss" };
+        row(
+            0,
+            &format!("{} ({})", layer.name, layer.arch),
+            status,
+            layer.duration,
+        );
+        for recipe in &layer.recipes {
+            row(1, &recipe.name, "", recipe.duration);
+            for step in &recipe.steps {
+                row(2, &step.filename, "", step.duration);
+            }
+        }
+    }
+    for phase in &report.phases {
+        row(0, &phase.name, "", phase.duration);
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Build Report: {target}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+th, td {{ text-align: left; padding: 0.25em 0.5em; border-bottom: 1px solid #ddd; }}
+.level-0 td:first-child {{ font-weight: bold; }}
+.level-1 td:first-child {{ padding-left: 2em; }}
+.level-2 td:first-child {{ padding-left: 4em; color: #555; }}
+.time {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.hit {{ color: #2a7d2a; }}
+.miss {{ color: #b25b00; }}
+.bar {{ width: 40%; }}
+.bar div {{ height: 0.8em; background: #4a7fc1; }}
+</style>
+</head>
+<body>
+<h1>Build Report: {target}</h1>
+<p>Command: <code>{command}</code> &middot; Total: {total}</p>
+<table>
+<tr><th>Layer / Recipe / Step</th><th>Cache</th><th class="time">Time</th><th></th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        target = escape(&report.target),
+        command = escape(&report.command),
+        total = format_duration(report.duration),
+    )
+}
+
+/// Format a duration in seconds for humans.
+fn format_duration(seconds: f64) -> String {
+    if seconds < 60.0 {
+        format!("{seconds:.1}s")
+    } else {
+        let seconds = seconds.round() as u64;
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+/// Escape a string for inclusion in HTML.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
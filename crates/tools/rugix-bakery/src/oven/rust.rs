@@ -0,0 +1,324 @@
+//! Cross-compilation of Rust crates by `rust` steps.
+
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, ResultExt};
+use tempfile::tempdir;
+use tracing::info;
+use url::Url;
+use xscript::{cmd_os, Cmd, ParentEnv, Run, Vars};
+
+use crate::config::recipes::{RustConfig, ToolchainConfig, ToolchainKind};
+use crate::config::systems::Architecture;
+use crate::oven::artifacts::sha256_file;
+use crate::oven::toolchain;
+use crate::project::ProjectRef;
+use crate::utils::build_env;
+use crate::utils::caching::download;
+use crate::BakeryResult;
+
+/// Rust toolchain used by default.
+///
+/// The toolchain is pinned such that builds are reproducible.
+const DEFAULT_TOOLCHAIN: &str = "1.85.0";
+
+/// Directory to install the binaries into by default.
+const DEFAULT_INSTALL_DIR: &str = "/usr/bin";
+
+/// Rust target triple for the given architecture.
+fn rust_target(arch: Architecture, is_musl: bool) -> &'static str {
+    match (arch, is_musl) {
+        (Architecture::Amd64, false) => "x86_64-unknown-linux-gnu",
+        (Architecture::Amd64, true) => "x86_64-unknown-linux-musl",
+        (Architecture::Arm64, false) => "aarch64-unknown-linux-gnu",
+        (Architecture::Arm64, true) => "aarch64-unknown-linux-musl",
+        (Architecture::Armv7, false) => "armv7-unknown-linux-gnueabihf",
+        (Architecture::Armv7, true) => "armv7-unknown-linux-musleabihf",
+        (Architecture::Armhf, false) => "arm-unknown-linux-gnueabihf",
+        (Architecture::Armhf, true) => "arm-unknown-linux-musleabihf",
+        (Architecture::Arm, false) => "arm-unknown-linux-gnueabi",
+        (Architecture::Arm, true) => "arm-unknown-linux-musleabi",
+    }
+}
+
+/// Rust toolchain provisioned with `rustup` in the global cache.
+struct RustEnv {
+    rustup_home: PathBuf,
+    cargo_home: PathBuf,
+    toolchain: String,
+}
+
+impl RustEnv {
+    fn new(config: &RustConfig) -> Self {
+        let rust_dir = build_env::cache_dir().join("rust");
+        Self {
+            rustup_home: rust_dir.join("rustup"),
+            cargo_home: rust_dir.join("cargo"),
+            toolchain: config
+                .toolchain
+                .clone()
+                .unwrap_or_else(|| DEFAULT_TOOLCHAIN.to_owned()),
+        }
+    }
+
+    /// Environment variables for running `rustup` and `cargo`.
+    fn vars(&self, source_date_epoch: u64) -> Vars {
+        let mut path = OsString::from(self.cargo_home.join("bin"));
+        if let Some(parent_path) = std::env::var_os("PATH") {
+            path.push(":");
+            path.push(parent_path);
+        }
+        let mut vars = Vars::new();
+        vars.set("RUSTUP_HOME", &self.rustup_home);
+        vars.set("CARGO_HOME", &self.cargo_home);
+        vars.set("RUSTUP_TOOLCHAIN", &self.toolchain);
+        vars.set("PATH", path);
+        vars.set("SOURCE_DATE_EPOCH", source_date_epoch.to_string());
+        vars
+    }
+
+    /// Path of the `cargo` binary.
+    fn cargo(&self) -> PathBuf {
+        self.cargo_home.join("bin/cargo")
+    }
+
+    /// Install `rustup` and the toolchain with the given targets.
+    ///
+    /// When offline, the toolchain is expected to be installed already.
+    fn install(
+        &self,
+        targets: &[&str],
+        run: &mut impl FnMut(Cmd<OsString>) -> BakeryResult<()>,
+    ) -> BakeryResult<()> {
+        let rustup = self.cargo_home.join("bin/rustup");
+        if !rustup.exists() {
+            build_env::ensure_online("installing `rustup`")?;
+            info!("installing rustup");
+            let rustup_init = download_rustup_init()?;
+            fs::set_permissions(&rustup_init, fs::Permissions::from_mode(0o755))
+                .whatever("unable to make `rustup-init` executable")?;
+            run(cmd_os!(
+                &rustup_init,
+                "-y",
+                "--no-modify-path",
+                "--profile",
+                "minimal",
+                "--default-toolchain",
+                "none"
+            )
+            .with_vars(self.vars(0)))?;
+        } else if build_env::is_offline() {
+            return Ok(());
+        }
+        info!("installing Rust toolchain `{}`", self.toolchain);
+        let mut cmd = cmd_os!(
+            rustup,
+            "toolchain",
+            "install",
+            &self.toolchain,
+            "--profile",
+            "minimal"
+        );
+        for target in targets {
+            cmd.extend_args(["--target", target]);
+        }
+        run(cmd.with_vars(self.vars(0)))
+    }
+}
+
+/// Download `rustup-init` and verify it against its published SHA256 digest.
+fn download_rustup_init() -> BakeryResult<PathBuf> {
+    let host = std::env::consts::ARCH;
+    let url = Url::parse(&format!(
+        "https://static.rust-lang.org/rustup/dist/{host}-unknown-linux-gnu/rustup-init"
+    ))
+    .unwrap();
+    let digest = reqwest::blocking::get(format!("{url}.sha256"))
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .whatever("unable to retrieve digest of `rustup-init`")?;
+    let Some(expected) = digest.split_whitespace().next() else {
+        bail!("invalid digest of `rustup-init`: {digest:?}");
+    };
+    let mut rustup_init = download(&url)?;
+    if sha256_file(&rustup_init).whatever("unable to hash `rustup-init`")? != expected {
+        // The cached download may predate the latest release of `rustup`.
+        fs::remove_file(&rustup_init).whatever("unable to remove cached `rustup-init`")?;
+        rustup_init = download(&url)?;
+        if sha256_file(&rustup_init).whatever("unable to hash `rustup-init`")? != expected {
+            fs::remove_file(&rustup_init).ok();
+            bail!("digest of `rustup-init` does not match");
+        }
+    }
+    Ok(rustup_init)
+}
+
+/// Prefetch the toolchain and dependencies of a `rust` step for offline builds.
+///
+/// Dependencies are only prefetched for crates given by a path.
+pub fn prefetch(config: &RustConfig, arch: Architecture, recipe_dir: &Path) -> BakeryResult<()> {
+    let rust_env = RustEnv::new(config);
+    // The C library of the system is unknown without its root filesystem.
+    let targets = [rust_target(arch, false), rust_target(arch, true)];
+    let mut run = |cmd: Cmd<OsString>| -> BakeryResult<()> {
+        ParentEnv
+            .run(cmd)
+            .whatever("unable to prefetch Rust toolchain")?;
+        Ok(())
+    };
+    rust_env.install(&targets, &mut run)?;
+    if let Some(path) = &config.path {
+        info!("fetching dependencies of Rust crate {path:?}");
+        let mut cmd = cmd_os!(
+            rust_env.cargo(),
+            "fetch",
+            "--manifest-path",
+            recipe_dir.join(path).join("Cargo.toml")
+        );
+        if config.locked.unwrap_or(true) {
+            cmd.add_arg("--locked");
+        }
+        for target in targets {
+            cmd.extend_args(["--target", target]);
+        }
+        ParentEnv
+            .run(cmd.with_vars(rust_env.vars(0)))
+            .whatever("unable to fetch dependencies of Rust crate")?;
+    }
+    Ok(())
+}
+
+/// Build a Rust crate for the architecture of the layer and install its binaries.
+///
+/// The Rust toolchain is provisioned with `rustup` in the global cache, which also
+/// holds the Cargo registry. The C toolchain provisioned by a preceding `toolchain`
+/// step is used for linking. If there is none, GCC is used. Commands are executed
+/// with the provided `run` function.
+#[allow(clippy::too_many_arguments)]
+pub fn build_crate(
+    project: &ProjectRef,
+    config: &RustConfig,
+    arch: Architecture,
+    recipe_dir: &Path,
+    root_dir: &Path,
+    toolchain_vars: &[(&'static str, OsString)],
+    source_date_epoch: u64,
+    mut run: impl FnMut(Cmd<OsString>) -> BakeryResult<()>,
+) -> BakeryResult<()> {
+    let target = rust_target(arch, toolchain::is_musl(root_dir));
+    let rust_env = RustEnv::new(config);
+    rust_env.install(&[target], &mut run)?;
+
+    // Configure the C toolchain of the target for linking and for build scripts.
+    let gcc_vars;
+    let toolchain_vars = if toolchain_vars.is_empty() {
+        gcc_vars =
+            toolchain::toolchain_vars(&ToolchainConfig::new(ToolchainKind::Gcc), arch, root_dir)?;
+        &gcc_vars
+    } else {
+        toolchain_vars
+    };
+    let mut vars = rust_env.vars(source_date_epoch);
+    let target_var = target.replace('-', "_");
+    let wrapper_dir = tempdir().whatever("unable to create temporary directory")?;
+    for (name, value) in toolchain_vars {
+        // Only variables specific to the target are set, such that they do not interfere
+        // with the host, e.g., when compiling build scripts and their native dependencies.
+        // Other variables of the toolchain are not used by Cargo.
+        if matches!(
+            *name,
+            "CC" | "CXX" | "AR" | "PKG_CONFIG" | "PKG_CONFIG_SYSROOT_DIR" | "PKG_CONFIG_LIBDIR"
+        ) {
+            vars.set(format!("{name}_{target_var}"), value);
+        }
+        if *name == "CC" {
+            // Cargo does not support linkers with arguments, hence, we use a wrapper.
+            let linker = wrapper_dir.path().join("linker");
+            fs::write(
+                &linker,
+                format!("#!/bin/sh\nexec {} \"$@\"\n", value.to_string_lossy()),
+            )
+            .whatever("unable to write linker wrapper")?;
+            fs::set_permissions(&linker, fs::Permissions::from_mode(0o755))
+                .whatever("unable to make linker wrapper executable")?;
+            vars.set(
+                format!("CARGO_TARGET_{}_LINKER", target_var.to_uppercase()),
+                &linker,
+            );
+        }
+    }
+    vars.set(format!("PKG_CONFIG_ALLOW_CROSS_{target_var}"), "1");
+    vars.set("CARGO_TARGET_DIR", project.dir().join(".rugix/rust/target"));
+
+    let install_root = tempdir().whatever("unable to create temporary directory")?;
+    let mut cmd = cmd_os!(
+        rust_env.cargo(),
+        "install",
+        "--target",
+        target,
+        "--root",
+        install_root.path()
+    );
+    if config.locked.unwrap_or(true) {
+        cmd.add_arg("--locked");
+    }
+    if build_env::is_offline() {
+        cmd.add_arg("--offline");
+    }
+    match (&config.path, &config.git) {
+        (Some(path), None) => {
+            cmd.add_arg("--path");
+            cmd.add_arg(recipe_dir.join(path));
+        }
+        (None, Some(git)) => {
+            // Dependencies of crates built from Git are not prefetched.
+            build_env::ensure_online("building a Rust crate from Git")?;
+            cmd.add_arg("--git");
+            cmd.add_arg(git);
+            for (flag, value) in [
+                ("--rev", &config.rev),
+                ("--branch", &config.branch),
+                ("--tag", &config.tag),
+            ] {
+                if let Some(value) = value {
+                    cmd.add_arg(flag);
+                    cmd.add_arg(value);
+                }
+            }
+        }
+        _ => bail!("`rust` step requires exactly one of `path` and `git`"),
+    }
+    for bin in config.bins.as_deref().unwrap_or_default() {
+        cmd.add_arg("--bin");
+        cmd.add_arg(bin);
+    }
+    if let Some(features) = &config.features {
+        cmd.add_arg("--features");
+        cmd.add_arg(features.join(","));
+    }
+    if config.no_default_features.unwrap_or(false) {
+        cmd.add_arg("--no-default-features");
+    }
+    if let Some(package) = &config.package {
+        cmd.add_arg(package);
+    }
+    info!("building Rust crate for `{target}`");
+    run(cmd.with_vars(vars))?;
+
+    let install_dir = config.install_dir.as_deref().unwrap_or(DEFAULT_INSTALL_DIR);
+    let install_dir = root_dir.join(install_dir.trim_start_matches('/'));
+    fs::create_dir_all(&install_dir).whatever("unable to create install directory")?;
+    let bin_dir = install_root.path().join("bin");
+    for entry in fs::read_dir(&bin_dir).whatever("unable to read built binaries")? {
+        let entry = entry.whatever("unable to read built binaries")?;
+        let binary = install_dir.join(entry.file_name());
+        info!("installing {:?}", entry.file_name());
+        fs::copy(entry.path(), &binary).whatever("unable to install binary")?;
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755))
+            .whatever("unable to make binary executable")?;
+    }
+    Ok(())
+}
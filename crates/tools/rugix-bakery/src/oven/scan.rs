@@ -0,0 +1,170 @@
+//! Scanning of systems for known vulnerabilities.
+//!
+//! The SBOM of a system is scanned with [Grype](https://github.com/anchore/grype) and the
+//! found vulnerabilities are written to [`VULNERABILITIES_FILE`] in the build directory
+//! of the system. The vulnerability database is kept in the global cache.
+
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use xscript::{cmd, vars, ParentEnv, Run, Vars};
+
+use crate::config::systems::{ScanConfig, VulnerabilitySeverity};
+use crate::oven::licenses::SBOM_FILE;
+use crate::utils::build_env;
+use crate::BakeryResult;
+
+/// Name of the vulnerability report in the build directory of a system.
+pub const VULNERABILITIES_FILE: &str = "vulnerabilities.json";
+
+/// Vulnerability found in a system.
+#[derive(Debug, Clone, Serialize)]
+pub struct Vulnerability {
+    /// Identifier of the vulnerability, e.g., `CVE-2024-1234`.
+    pub id: String,
+    /// Severity of the vulnerability, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<VulnerabilitySeverity>,
+    /// Name of the affected package.
+    pub package: String,
+    /// Installed version of the affected package.
+    pub version: String,
+    /// Versions fixing the vulnerability.
+    pub fixed_in: Vec<String>,
+    /// Indicates whether the vulnerability is ignored by the configuration.
+    pub ignored: bool,
+}
+
+/// Output of `grype -o json`.
+#[derive(Debug, Deserialize)]
+struct GrypeOutput {
+    #[serde(default)]
+    matches: Vec<GrypeMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeMatch {
+    vulnerability: GrypeVulnerability,
+    artifact: GrypeArtifact,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeVulnerability {
+    id: String,
+    severity: Option<String>,
+    fix: Option<GrypeFix>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeFix {
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrypeArtifact {
+    name: String,
+    version: String,
+}
+
+/// Environment variables configuring Grype.
+fn grype_vars() -> Vars {
+    let mut vars = vars! {
+        GRYPE_DB_CACHE_DIR = build_env::cache_dir().join("grype").to_string_lossy().into_owned(),
+    };
+    if build_env::is_offline() {
+        vars.set("GRYPE_DB_AUTO_UPDATE", "false");
+        vars.set("GRYPE_CHECK_FOR_APP_UPDATE", "false");
+    }
+    vars
+}
+
+/// Download or update the vulnerability database.
+pub fn update_database() -> BakeryResult<()> {
+    build_env::ensure_online("updating the vulnerability database")?;
+    info!("updating vulnerability database");
+    ParentEnv
+        .run(cmd!("grype", "db", "update").with_vars(grype_vars()))
+        .whatever("unable to update vulnerability database")?;
+    Ok(())
+}
+
+/// Scan the system in the given build directory and write the vulnerability report.
+pub fn scan_system(
+    config: Option<&ScanConfig>,
+    system_path: &Path,
+) -> BakeryResult<Vec<Vulnerability>> {
+    let sbom_path = system_path.join(SBOM_FILE);
+    if !sbom_path.exists() {
+        bail!("no SBOM found in {system_path:?}, bake the system first");
+    }
+    info!("scanning system for vulnerabilities");
+    let sbom = format!("sbom:{}", sbom_path.display());
+    let output = ParentEnv
+        .read_str(cmd!("grype", sbom, "-o", "json").with_vars(grype_vars()))
+        .whatever("unable to scan system for vulnerabilities")?;
+    let output = serde_json::from_str::<GrypeOutput>(&output)
+        .whatever("unable to parse output of `grype`")?;
+    let ignore = config
+        .and_then(|config| config.ignore.as_deref())
+        .unwrap_or_default();
+    let mut vulnerabilities = output
+        .matches
+        .into_iter()
+        .map(|m| Vulnerability {
+            ignored: ignore.contains(&m.vulnerability.id),
+            id: m.vulnerability.id,
+            severity: m
+                .vulnerability
+                .severity
+                .and_then(|severity| severity.to_ascii_lowercase().parse().ok()),
+            package: m.artifact.name,
+            version: m.artifact.version,
+            fixed_in: m
+                .vulnerability
+                .fix
+                .map(|fix| fix.versions)
+                .unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    vulnerabilities.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.id.cmp(&b.id))
+            .then_with(|| a.package.cmp(&b.package))
+    });
+    vulnerabilities.dedup_by(|a, b| a.id == b.id && a.package == b.package);
+    fs::write(
+        system_path.join(VULNERABILITIES_FILE),
+        serde_json::to_string_pretty(&vulnerabilities).unwrap(),
+    )
+    .whatever("unable to write vulnerability report")?;
+    Ok(vulnerabilities)
+}
+
+/// Fail if there are vulnerabilities which are not ignored and whose severity is at least
+/// the given severity.
+pub fn check_severity(
+    vulnerabilities: &[Vulnerability],
+    fail_on: VulnerabilitySeverity,
+) -> BakeryResult<()> {
+    let failing = vulnerabilities
+        .iter()
+        .filter(|vulnerability| {
+            !vulnerability.ignored
+                && vulnerability
+                    .severity
+                    .is_some_and(|severity| severity >= fail_on)
+        })
+        .count();
+    if failing > 0 {
+        bail!(
+            "found {failing} vulnerabilities with severity `{}` or higher",
+            fail_on.as_str()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Signing of boot artifacts for UEFI Secure Boot.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+use xscript::{run, Run};
+
+use crate::config::systems::SecureBootConfig;
+use crate::BakeryResult;
+
+/// Sign the EFI binaries of the config and boot partitions with `sbsign`.
+pub fn sign_boot_artifacts(config: &SecureBootConfig, roots_dir: &Path) -> BakeryResult<()> {
+    for (name, path) in [("key", &config.key), ("certificate", &config.cert)] {
+        if !Path::new(path).is_file() {
+            bail!("Secure Boot {name} {path:?} does not exist");
+        }
+    }
+    let files = match &config.files {
+        Some(files) => files.iter().map(|file| roots_dir.join(file)).collect(),
+        None => {
+            let mut files = Vec::new();
+            for dir in ["config", "boot"] {
+                find_efi_binaries(&roots_dir.join(dir), &mut files)?;
+            }
+            files
+        }
+    };
+    if files.is_empty() {
+        bail!("no EFI binaries to sign found");
+    }
+    for file in files {
+        info!("signing {file:?} for Secure Boot");
+        run!([
+            "sbsign",
+            "--key",
+            &config.key,
+            "--cert",
+            &config.cert,
+            "--output",
+            &file,
+            &file
+        ])
+        .whatever("unable to sign EFI binary")
+        .with_info(|_| format!("file: {file:?}"))?;
+        run!(["sbverify", "--cert", &config.cert, &file])
+            .whatever("unable to verify signature of EFI binary")
+            .with_info(|_| format!("file: {file:?}"))?;
+    }
+    Ok(())
+}
+
+/// Recursively find EFI binaries and kernels in the given directory.
+fn find_efi_binaries(dir: &Path, files: &mut Vec<PathBuf>) -> BakeryResult<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir).whatever("unable to read directory")? {
+        let entry = entry.whatever("unable to read directory entry")?;
+        let file_type = entry.file_type().whatever("unable to read file type")?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            find_efi_binaries(&path, files)?;
+            continue;
+        }
+        // Symlinks are skipped such that their targets are not signed twice.
+        if !file_type.is_file() {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let is_candidate = name.to_ascii_lowercase().ends_with(".efi")
+            || name.starts_with("vmlinuz")
+            || name.starts_with("vmlinux");
+        if is_candidate && is_pe_binary(&path)? {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Check whether the given file is a PE binary, i.e., whether it can be signed.
+fn is_pe_binary(path: &Path) -> BakeryResult<bool> {
+    let mut magic = [0; 2];
+    let mut file = File::open(path).whatever("unable to open file")?;
+    Ok(file.read_exact(&mut magic).is_ok() && &magic == b"MZ")
+}
@@ -0,0 +1,72 @@
+//! Signing of update bundles.
+
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use rugix_bundle::{add_bundle_signature, signed_metadata};
+use tempfile::tempdir;
+use tracing::info;
+use xscript::{cmd_os, ParentEnv, Run};
+
+use crate::config::systems::BundleSigningConfig;
+use crate::BakeryResult;
+
+/// Prefix of PKCS#11 URIs.
+const PKCS11_URI_PREFIX: &str = "pkcs11:";
+
+/// Sign the given bundle in place with `openssl cms`.
+///
+/// If the key or certificate is a PKCS#11 URI, OpenSSL's `pkcs11` provider is used.
+pub fn sign_bundle(config: &BundleSigningConfig, bundle: &Path) -> BakeryResult<()> {
+    let intermediate_certs = config.intermediate_certs.as_deref().unwrap_or_default();
+    let mut uses_pkcs11 = false;
+    for (name, path) in [("key", &config.key), ("certificate", &config.cert)]
+        .into_iter()
+        .chain(intermediate_certs.iter().map(|cert| ("certificate", cert)))
+    {
+        if path.starts_with(PKCS11_URI_PREFIX) {
+            uses_pkcs11 = true;
+        } else if !Path::new(path).is_file() {
+            bail!("bundle signing {name} {path:?} does not exist");
+        }
+    }
+    info!("signing bundle {bundle:?}");
+    let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+    let signed_metadata_raw = temp_dir.path().join("signed-metadata.raw");
+    let signed_metadata_cms = temp_dir.path().join("signed-metadata.cms");
+    let metadata = signed_metadata(bundle).whatever("unable to compute bundle metadata")?;
+    fs::write(&signed_metadata_raw, metadata).whatever("unable to write bundle metadata")?;
+    let mut cmd = cmd_os!(
+        "openssl",
+        "cms",
+        "-sign",
+        "-in",
+        &signed_metadata_raw,
+        "-signer",
+        &config.cert,
+        "-inkey",
+        &config.key,
+        "-out",
+        &signed_metadata_cms,
+        "-outform",
+        "DER",
+        "-nosmimecap",
+        "-nodetach",
+        "-binary"
+    );
+    if uses_pkcs11 {
+        cmd.extend_args(["-provider", "pkcs11", "-provider", "default"]);
+    }
+    for cert in intermediate_certs {
+        cmd.add_arg("-certfile");
+        cmd.add_arg(cert);
+    }
+    ParentEnv.run(cmd).whatever("unable to sign bundle")?;
+    let signature = fs::read(&signed_metadata_cms).whatever("unable to read signature")?;
+    let signed_bundle = temp_dir.path().join("signed.rugixb");
+    add_bundle_signature(bundle, signature, &signed_bundle)
+        .whatever("unable to add signature to bundle")?;
+    fs::copy(&signed_bundle, bundle).whatever("unable to write signed bundle")?;
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use std::fmt::Debug;
 use std::fs::{self, File};
-use std::io::Seek;
-use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -10,24 +9,28 @@ use tracing::info;
 use reportify::{bail, whatever, ResultExt};
 use xscript::{cmd, cmd_os, run, vars, ParentEnv, Run};
 
-use rugix_common::disk::gpt::gpt_types;
-use rugix_common::disk::mbr::mbr_types;
+use rugix_common::disk::gpt::{gpt_types, GptAttributes, Guid};
+use rugix_common::disk::mbr::{self, mbr_types, HybridMbrEntry, MbrId};
 use rugix_common::disk::{
-    parse_size, DiskId, NumBlocks, Partition, PartitionTable, PartitionTableType,
+    parse_size, DiskId, NumBlocks, Partition, PartitionTable, PartitionTableType, PartitionType,
 };
-use rugix_common::fsutils::allocate_file;
+use rugix_common::fsutils::{allocate_file, copy_sparse, create_sparse_file, resolve_in_root};
 use rugix_common::utils::ascii_numbers;
 use rugix_common::utils::units::NumBytes;
 use rugix_common::{grub_patch_env, rpi_patch_boot};
 
-use crate::config::images::{Filesystem, ImageLayout};
+use crate::config::images::{DefaultLayoutConfig, Filesystem, ImageLayout, ImagePartition};
 use crate::config::load_json;
-use crate::config::systems::{SystemConfig, Target};
-use crate::oven::targets;
+use crate::config::systems::{SystemConfig, SystemProfileConfig, Target};
+use crate::oven::files::set_owner;
 use crate::oven::targets::generic_grub_efi::initialize_grub;
 use crate::oven::targets::rpi_tryboot::initialize_tryboot;
 use crate::oven::targets::rpi_uboot::initialize_uboot;
-use crate::utils::caching::mtime;
+use crate::oven::{
+    budget, cloud_init, encryption, index, licenses, mounts, profiles, secure_boot, targets, verity,
+};
+use crate::project::ProjectRef;
+use crate::utils::caching::{mtime, Hasher};
 use crate::BakeryResult;
 
 use super::layer::FrozenLayer;
@@ -38,11 +41,27 @@ pub struct ReleaseInfo {
     pub system_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub system_version: Option<String>,
+    /// Git commit the system has been built from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// SSH access injected into a system for testing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestAccess {
+    /// Public SSH key to authorize.
+    pub public_key: String,
+    /// Users to authorize the key for.
+    pub users: Vec<String>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn make_system(
+    project: &ProjectRef,
     config: &SystemConfig,
     release_info: &ReleaseInfo,
+    test_access: Option<&TestAccess>,
+    profile: Option<&SystemProfileConfig>,
     system_name: &str,
     frozen: &FrozenLayer,
     out: &Path,
@@ -50,13 +69,18 @@ pub fn make_system(
 ) -> BakeryResult<()> {
     let system_build_input = out.join("system-build-input.json");
     let system_build_info = out.join("system-build-info.json");
-    if system_build_info.exists() {
+    let profile_value = profile.map(|profile| serde_json::to_value(profile).unwrap());
+    if system_build_info.exists() && system_build_input.exists() {
         let system_mtime = mtime(&system_build_info).whatever("unable to get system mtime")?;
         let layer_mtime = frozen.last_modified()?;
         if layer_mtime < system_mtime {
             info!("system is newer than layer");
-            let system_info = load_json::<SystemBuildInput>(&system_build_info)?;
-            if &system_info.release == release_info {
+            let build_input = load_json::<SystemBuildInput>(&system_build_input)?;
+            if &build_input.release == release_info
+                && build_input.test_access.as_ref() == test_access
+                && build_input.profile == profile_value
+                && build_input.layer == frozen.name()
+            {
                 info!("release info has not changed, skipping build");
                 return Ok(());
             }
@@ -65,6 +89,7 @@ pub fn make_system(
         }
     }
 
+    let previous_sizes = budget::load_sizes(out);
     std::fs::remove_dir_all(out).ok();
     std::fs::create_dir_all(out).ok();
 
@@ -72,6 +97,9 @@ pub fn make_system(
         &system_build_input,
         serde_json::to_string_pretty(&SystemBuildInput {
             release: release_info.clone(),
+            test_access: test_access.cloned(),
+            profile: profile_value,
+            layer: frozen.name().to_owned(),
         })
         .unwrap(),
     )
@@ -114,6 +142,7 @@ pub fn make_system(
         release: SystemReleaseInfo {
             id: release_id,
             version: release_version.to_owned(),
+            commit: release_info.commit.clone(),
         },
     };
 
@@ -123,17 +152,32 @@ pub fn make_system(
     )
     .whatever("unable to write `/etc/rugix/system-build-info.json`")?;
 
+    super::version::write_os_release_version(&system_dir, release_version)?;
+
+    if let Some(test_access) = test_access {
+        for user in &test_access.users {
+            authorize_ssh_key(&system_dir, user, &test_access.public_key)?;
+        }
+    }
+
+    if let Some(profile) = profile {
+        profiles::apply_profile(profile, &system_dir)?;
+    }
+
     info!("Generating SBOM");
     run!([
         "syft",
-        system_dir,
+        &system_dir,
         "--source-name",
         system_name,
         "--source-version",
         release_version,
         "--quiet",
         "-o",
-        format!("spdx-json={}", out.join("sbom.spdx.json").to_string_lossy())
+        format!(
+            "spdx-json={}",
+            out.join(licenses::SBOM_FILE).to_string_lossy()
+        )
     ])
     .whatever("unable to generate SBOM")?;
 
@@ -167,46 +211,70 @@ pub fn make_system(
         }
     }
 
-    // At this point, everything is initialized and we can compute the partition table.
-    let layout = config
+    if let Some(cloud_init) = config
         .image
         .as_ref()
-        .and_then(|image| image.layout.clone())
-        .or_else(|| {
-            config.target.as_ref().and_then(|target| {
-                targets::get_default_layout(
-                    target,
-                    config
-                        .options
-                        .as_ref()
-                        .and_then(|options| options.use_squashfs.as_ref()),
-                )
-            })
-        })
-        .ok_or_else(|| whatever!("image layout needs to be specified"))?;
-
-    let image_file = out.join("system.img");
-
-    info!("Computing partition table.");
-    let table = compute_partition_table(&layout, &layer_path.join("roots"))?;
+        .and_then(|image| image.cloud_init.as_ref())
+    {
+        cloud_init::write_seed(
+            project,
+            cloud_init,
+            &[
+                ("RUGIX_SYSTEM_NAME", system_name),
+                ("RUGIX_RELEASE_ID", &system_info.release.id),
+                ("RUGIX_RELEASE_VERSION", &system_info.release.version),
+            ],
+            &config_dir,
+            &system_dir,
+        )?;
+    }
 
-    let size_bytes = table.blocks_to_bytes(table.disk_size);
+    if let Some(secure_boot) = &config.secure_boot {
+        info!("Signing boot artifacts for Secure Boot.");
+        secure_boot::sign_boot_artifacts(secure_boot, &layer_path.join("roots"))?;
+    }
 
-    info!("Allocating image file.");
-    if let Some(size) = &config.image.as_ref().and_then(|image| image.size) {
-        allocate_file(&image_file, size.raw)
-    } else {
-        allocate_file(&image_file, size_bytes.into_raw())
+    // At this point, everything is initialized and we can compute the partition table.
+    let image_size = config.image.as_ref().and_then(|image| image.size);
+    let layout = match config.image.as_ref().and_then(|image| image.layout.clone()) {
+        Some(layout) => Some(layout),
+        None => match &config.target {
+            Some(target) => targets::get_default_layout(
+                target,
+                config
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.use_squashfs.as_ref()),
+                config
+                    .image
+                    .as_ref()
+                    .and_then(|image| image.default_layout.as_ref()),
+                image_size,
+            )?,
+            None => None,
+        },
     }
-    .whatever("error allocating image file")?;
+    .ok_or_else(|| whatever!("image layout needs to be specified"))?;
 
-    info!("Writing image partition table.");
-    table
-        .write(&image_file)
-        .whatever("error writing image partition table")?;
+    // Only the default layout is also used by Rugix Ctrl for bootstrapping.
+    if let Some(default_layout) = config
+        .image
+        .as_ref()
+        .filter(|image| image.layout.is_none())
+        .and_then(|image| image.default_layout.as_ref())
+    {
+        write_bootstrapping_config(default_layout, &system_dir)?;
+    }
 
-    let table =
-        PartitionTable::read(&image_file).whatever("error reading image partition table")?;
+    let image_file = out.join("system.img");
+    let table = create_image(
+        &layout,
+        image_size,
+        &layer_path.join("roots"),
+        system_name,
+        &image_file,
+    )?;
+    check_encrypted_partitions(&layout, Some(&table))?;
 
     if let Some(target) = &config.target {
         if matches!(target, Target::RpiTryboot | Target::RpiUboot) {
@@ -214,12 +282,16 @@ pub fn make_system(
                 DiskId::Mbr(mbr_id) => mbr_id.into_raw(),
                 _ => bail!("unsupported GPT partition layout"),
             };
+            let root_part = system_partition(&layout, &table)?;
             info!("Patching boot configuration.");
-            rpi_patch_boot(&boot_dir, format!("PARTUUID={disk_id:08X}-05"))
-                .whatever("unable to patch boot configuration")?;
+            rpi_patch_boot(
+                &boot_dir,
+                format!("PARTUUID={disk_id:08X}-{:02x}", root_part.number),
+            )
+            .whatever("unable to patch boot configuration")?;
         }
         if matches!(target, Target::GenericGrubEfi) {
-            let root_part = &table.partitions[3];
+            let root_part = system_partition(&layout, &table)?;
             let part_uuid = root_part
                 .gpt_id
                 .unwrap()
@@ -229,11 +301,178 @@ pub fn make_system(
         }
     }
 
+    mounts::write_mounts(
+        &layout,
+        &table,
+        system_name,
+        &layer_path.join("roots/system"),
+    )?;
+
+    // Secondary disks are created before the filesystems such that their mount points
+    // end up in the system.
+    let disks_dir = out.join("disks");
+    // Images of disks which have been removed from the configuration must not be picked
+    // up, e.g., when running the system in a VM.
+    if disks_dir.exists() {
+        fs::remove_dir_all(&disks_dir).whatever("unable to remove old disk images")?;
+    }
+    let mut disks = Vec::new();
+    for (disk_name, disk) in config
+        .image
+        .as_ref()
+        .and_then(|image| image.disks.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        if disk_name.is_empty() || disk_name.contains(['/', '.']) {
+            bail!("invalid disk name {disk_name:?}");
+        }
+        check_encrypted_partitions(&disk.layout, None)?;
+        info!("Creating disk image {disk_name:?}.");
+        fs::create_dir_all(&disks_dir).whatever("unable to create disks directory")?;
+        let disk_file = disks_dir.join(format!("{disk_name}.img"));
+        // Identifiers of the disk must differ from the identifiers of the image.
+        let id_name = format!("{system_name}/{disk_name}");
+        let disk_table = create_image(
+            &disk.layout,
+            disk.size,
+            &layer_path.join("roots"),
+            &id_name,
+            &disk_file,
+        )?;
+        mounts::write_mounts(
+            &disk.layout,
+            &disk_table,
+            &id_name,
+            &layer_path.join("roots/system"),
+        )?;
+        disks.push((disk_name, disk, disk_table, disk_file, id_name));
+    }
+
+    let sizes =
+        budget::measure_partitions(&layout, &layer_path.join("roots"), previous_sizes.as_ref())?;
+    if let Err(error) = budget::check_sizes(config, &sizes) {
+        // Keep the sizes of the previous build as the baseline for the next build.
+        if let Some(previous_sizes) = &previous_sizes {
+            budget::write_sizes(previous_sizes, out)?;
+        }
+        return Err(error);
+    }
+    budget::write_sizes(&sizes, out)?;
+    index::write_index(&layout, &layer_path.join("roots"), out)?;
+
     let filesystems_dir = out.join("filesystems");
+    let verity_info = write_filesystems(
+        &layout,
+        &table,
+        &image_file,
+        &layer_path.join("roots"),
+        &filesystems_dir,
+        system_name,
+        source_date_epoch,
+    )?;
+    for (disk_name, disk, disk_table, disk_file, id_name) in &disks {
+        let disk_verity_info = write_filesystems(
+            &disk.layout,
+            disk_table,
+            disk_file,
+            &layer_path.join("roots"),
+            &filesystems_dir.join("disks").join(disk_name),
+            id_name,
+            source_date_epoch,
+        )?;
+        if !disk_verity_info.is_empty() {
+            bail!("dm-verity is not supported for partitions of disk {disk_name:?}");
+        }
+    }
 
-    std::fs::create_dir_all(&filesystems_dir).ok();
+    if !verity_info.is_empty() {
+        std::fs::write(
+            out.join(verity::VERITY_INFO_FILE),
+            serde_json::to_string_pretty(&verity_info).unwrap(),
+        )
+        .whatever("unable to write dm-verity parameters")?;
+    }
+
+    std::fs::write(
+        &system_build_info,
+        &serde_json::to_string_pretty(&system_info).unwrap(),
+    )
+    .whatever("unable to write `system-build-info.json`")?;
+
+    Ok(())
+}
+
+/// Create the image file with the partition table for the given layout.
+fn create_image(
+    layout: &ImageLayout,
+    image_size: Option<byte_calc::NumBytes>,
+    roots_dir: &Path,
+    system_name: &str,
+    image_file: &Path,
+) -> BakeryResult<PartitionTable> {
+    info!("Computing partition table.");
+    let mut table = compute_partition_table(layout, image_size, roots_dir)?;
+    assign_stable_ids(&mut table, system_name);
+
+    let size_bytes = table.blocks_to_bytes(table.disk_size);
+
+    info!("Allocating image file.");
+    // The image is sparse such that unused space does not need to be written.
+    let size = image_size.map_or(size_bytes.into_raw(), |size| size.raw);
+    create_sparse_file(image_file, size).whatever("error allocating image file")?;
+
+    info!("Writing image partition table.");
+    table
+        .write(image_file)
+        .whatever("error writing image partition table")?;
+
+    let table = PartitionTable::read(image_file).whatever("error reading image partition table")?;
+
+    write_hybrid_mbr(layout, &table, image_file)?;
+    Ok(table)
+}
+
+/// Check that only the data partition of the image is encrypted.
+///
+/// Rugix Ctrl only unlocks the data partition when booting, hence, other encrypted
+/// partitions would be unusable. Secondary disks are passed without a table.
+fn check_encrypted_partitions(
+    layout: &ImageLayout,
+    table: Option<&PartitionTable>,
+) -> BakeryResult<()> {
+    for (idx, partition) in layout.partitions.iter().flatten().enumerate() {
+        if partition.encryption.is_none() {
+            continue;
+        }
+        let Some(table) = table else {
+            bail!("encryption is only supported for the data partition of the image");
+        };
+        let data_partition = if table.is_mbr() { 7 } else { 6 };
+        let number = table.partitions[idx].number;
+        if number != data_partition {
+            bail!(
+                "encryption is only supported for the data partition (partition {data_partition}), not for partition {number}"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Create the filesystems of the layout and copy them into the image file.
+fn write_filesystems(
+    layout: &ImageLayout,
+    table: &PartitionTable,
+    image_file: &Path,
+    roots_dir: &Path,
+    filesystems_dir: &Path,
+    system_name: &str,
+    source_date_epoch: u64,
+) -> BakeryResult<verity::VerityInfo> {
+    std::fs::create_dir_all(filesystems_dir).ok();
 
     // Create filesystems.
+    let mut verity_info = verity::VerityInfo::new();
     if let Some(partitions) = &layout.partitions {
         for (partition, (layout_partition, image_partition)) in
             partitions.iter().zip(table.partitions.iter()).enumerate()
@@ -248,10 +487,30 @@ pub fn make_system(
                 image_partition.size.into_raw()
             );
             let fs_image = filesystems_dir.join(format!("partition-{}.img", partition + 1));
+            let partition_size = table.blocks_to_bytes(image_partition.size).into_raw();
+            let verity = layout_partition.verity.unwrap_or(false);
+            let fs_uuid = filesystem_uuid(
+                layout_partition,
+                filesystem,
+                system_name,
+                image_partition.number,
+            )?;
+            if verity && layout_partition.encryption.is_some() {
+                bail!("dm-verity and encryption cannot be combined");
+            }
+            let fs_size = if layout_partition.encryption.is_some() {
+                partition_size
+                    .checked_sub(encryption::LUKS_SIZE_REDUCTION)
+                    .ok_or_else(|| whatever!("partition too small for encryption"))?
+            } else if verity {
+                verity::max_data_size(partition_size)
+                    .ok_or_else(|| whatever!("partition too small for dm-verity"))?
+            } else {
+                partition_size
+            };
             match filesystem {
                 Filesystem::Ext4(options) => {
-                    let size = table.blocks_to_bytes(image_partition.size);
-                    allocate_file(&fs_image, size.into_raw())
+                    allocate_file(&fs_image, fs_size)
                         .whatever("unable to allocate filesystem file")?;
                     if let Some(path) = &layout_partition.root {
                         let tar_archive =
@@ -270,39 +529,50 @@ pub fn make_system(
                             "-cf",
                             &tar_archive,
                             "-C",
-                            layer_path.join("roots").join(path),
+                            roots_dir.join(path),
                             "."
                         ])
                         .whatever("unable to create root filesystem tar")?;
                         let mut cmd = cmd_os!("mkfs.ext4", "-F", "-d", &tar_archive, &fs_image);
+                        if let Some(label) = &layout_partition.label {
+                            cmd.extend_args(["-L", label.as_str()]);
+                        }
+                        if let Some(uuid) = &fs_uuid {
+                            cmd.extend_args(["-U", uuid.as_str()]);
+                        }
                         if let Some(additional_options) = &options.additional_options {
                             cmd.extend_args(additional_options);
                         }
                         ParentEnv.run(cmd)
                     } else {
-                        run!(["mkfs.ext4", &fs_image])
+                        let mut cmd = cmd_os!("mkfs.ext4", &fs_image);
+                        if let Some(label) = &layout_partition.label {
+                            cmd.extend_args(["-L", label.as_str()]);
+                        }
+                        if let Some(uuid) = &fs_uuid {
+                            cmd.extend_args(["-U", uuid.as_str()]);
+                        }
+                        ParentEnv.run(cmd)
                     }
                     .whatever("unable to create EXT4 filesystem")?;
-                    let mut src =
-                        File::open(&fs_image).whatever("unable to open filesystem image file")?;
-                    let mut dst = File::options()
-                        .write(true)
-                        .open(&image_file)
-                        .whatever("unable to open image file")?;
-                    dst.seek(std::io::SeekFrom::Start(
-                        table.blocks_to_bytes(image_partition.start).into_raw(),
-                    ))
-                    .whatever("unable to seek in image file")?;
-                    std::io::copy(&mut src, &mut dst)
-                        .whatever("error copying filesystem into image")?;
                 }
                 Filesystem::Fat32 => {
-                    let size = table.blocks_to_bytes(image_partition.size);
-                    allocate_file(&fs_image, size.into_raw())
+                    allocate_file(&fs_image, fs_size)
                         .whatever("error allocating filesystem image")?;
-                    run!(["mkfs.vfat", &fs_image]).whatever("error creating FAT32 filesystem")?;
+                    let mut cmd = cmd_os!("mkfs.vfat");
+                    if let Some(label) = &layout_partition.label {
+                        cmd.extend_args(["-n", label.as_str()]);
+                    }
+                    if let Some(uuid) = &fs_uuid {
+                        // The volume ID is passed without the dash.
+                        cmd.extend_args(["-i", uuid.replace('-', "").as_str()]);
+                    }
+                    cmd.add_arg(&fs_image);
+                    ParentEnv
+                        .run(cmd)
+                        .whatever("error creating FAT32 filesystem")?;
                     if let Some(path) = &layout_partition.root {
-                        let fs_path = layer_path.join("roots").join(path);
+                        let fs_path = roots_dir.join(path);
                         for entry in
                             fs::read_dir(&fs_path).whatever("error reading filesystem content")?
                         {
@@ -318,25 +588,12 @@ pub fn make_system(
                             .whatever("error copying files into image")?;
                         }
                     }
-                    let mut src =
-                        File::open(&fs_image).whatever("unable to open filesystem image file")?;
-                    let mut dst = File::options()
-                        .write(true)
-                        .open(&image_file)
-                        .whatever("unable to open image file")?;
-                    dst.seek(std::io::SeekFrom::Start(
-                        table.blocks_to_bytes(image_partition.start).into_raw(),
-                    ))
-                    .whatever("unable to seek in image file")?;
-                    std::io::copy(&mut src, &mut dst)
-                        .whatever("error copying filesystem into image")?;
                 }
                 Filesystem::Squashfs(squashfs_options) => {
                     let Some(path) = &layout_partition.root else {
                         bail!("Squashfs needs a root");
                     };
-                    let mut cmd =
-                        cmd!("mksquashfs", layer_path.join("roots").join(path), &fs_image);
+                    let mut cmd = cmd!("mksquashfs", roots_dir.join(path), &fs_image);
                     if squashfs_options.no_compression.unwrap_or(false) {
                         cmd.add_arg("-noI");
                         cmd.add_arg("-noD");
@@ -347,35 +604,156 @@ pub fn make_system(
                         SOURCE_DATE_EPOCH = source_date_epoch.to_string(),
                     });
                     ParentEnv.run(cmd).whatever("error creating filesystem")?;
-                    let mut src =
-                        File::open(&fs_image).whatever("unable to open filesystem image file")?;
-                    let mut dst = File::options()
-                        .write(true)
-                        .open(&image_file)
-                        .whatever("unable to open image file")?;
-                    dst.seek(std::io::SeekFrom::Start(
-                        table.blocks_to_bytes(image_partition.start).into_raw(),
-                    ))
-                    .whatever("unable to seek in image file")?;
-                    std::io::copy(&mut src, &mut dst)
-                        .whatever("error copying filesystem into image")?;
                 }
+                Filesystem::F2fs(options) => {
+                    allocate_file(&fs_image, fs_size)
+                        .whatever("unable to allocate filesystem file")?;
+                    let mut cmd = cmd_os!("mkfs.f2fs", "-f");
+                    if let Some(label) = &layout_partition.label {
+                        cmd.extend_args(["-l", label.as_str()]);
+                    }
+                    if let Some(uuid) = &fs_uuid {
+                        cmd.extend_args(["-U", uuid.as_str()]);
+                    }
+                    if let Some(additional_options) = &options.additional_options {
+                        cmd.extend_args(additional_options);
+                    }
+                    cmd.add_arg(&fs_image);
+                    ParentEnv
+                        .run(cmd)
+                        .whatever("unable to create F2FS filesystem")?;
+                    if let Some(path) = &layout_partition.root {
+                        run!([
+                            "sload.f2fs",
+                            "-f",
+                            roots_dir.join(path),
+                            "-T",
+                            source_date_epoch.to_string(),
+                            &fs_image
+                        ])
+                        .whatever("unable to copy files into F2FS filesystem")?;
+                    }
+                }
+                Filesystem::Swap => {
+                    if layout_partition.root.is_some() {
+                        bail!("swap partitions cannot have a root");
+                    }
+                    if verity {
+                        bail!("dm-verity is not supported for swap partitions");
+                    }
+                    allocate_file(&fs_image, fs_size)
+                        .whatever("unable to allocate swap space file")?;
+                    let mut cmd = cmd_os!("mkswap");
+                    if let Some(label) = &layout_partition.label {
+                        cmd.extend_args(["-L", label.as_str()]);
+                    }
+                    if let Some(uuid) = &fs_uuid {
+                        cmd.extend_args(["-U", uuid.as_str()]);
+                    }
+                    cmd.add_arg(&fs_image);
+                    ParentEnv.run(cmd).whatever("unable to create swap space")?;
+                }
+            }
+            if let Some(encryption) = &layout_partition.encryption {
+                encryption::encrypt_filesystem(&fs_image, partition_size, encryption)?;
             }
+            if verity {
+                let seed = format!("{system_name}:{}:{source_date_epoch}", partition + 1);
+                verity_info.insert(
+                    format!("partition-{}.img", partition + 1),
+                    verity::append_hash_tree(&fs_image, partition_size, &seed)?,
+                );
+            }
+            let mut src = File::open(&fs_image).whatever("unable to open filesystem image file")?;
+            let mut dst = File::options()
+                .write(true)
+                .open(&image_file)
+                .whatever("unable to open image file")?;
+            let src_size = src
+                .metadata()
+                .whatever("unable to read size of filesystem image file")?
+                .len();
+            copy_sparse(
+                &mut src,
+                &mut dst,
+                0,
+                table.blocks_to_bytes(image_partition.start).into_raw(),
+                src_size,
+            )
+            .whatever("error copying filesystem into image")?;
         }
     }
-
-    std::fs::write(
-        &system_build_info,
-        &serde_json::to_string_pretty(&system_info).unwrap(),
-    )
-    .whatever("unable to write `system-build-info.json`")?;
-
-    Ok(())
+    Ok(verity_info)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemBuildInput {
     pub release: ReleaseInfo,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_access: Option<TestAccess>,
+    /// Build profile of the system, which is applied when making the system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<serde_json::Value>,
+    /// Layer or layer artifact the system has been built from.
+    #[serde(default)]
+    pub layer: String,
+}
+
+/// Authorize an SSH key for the given user of the system.
+fn authorize_ssh_key(system_dir: &Path, user: &str, public_key: &str) -> BakeryResult<()> {
+    info!("authorizing SSH key for user {user:?}");
+    let passwd = fs::read_to_string(system_dir.join("etc/passwd"))
+        .whatever("unable to read `/etc/passwd`")?;
+    let Some(entry) = passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 6 && fields[0] == user)
+    else {
+        bail!("user {user:?} does not exist in the system");
+    };
+    let uid = entry[2]
+        .parse()
+        .whatever("invalid user id in `/etc/passwd`")?;
+    let gid = entry[3]
+        .parse()
+        .whatever("invalid group id in `/etc/passwd`")?;
+    let home = Path::new(entry[5]);
+    // Symbolic links must not lead outside of the root filesystem.
+    let home_dir =
+        resolve_in_root(system_dir, home).whatever("unable to resolve home directory")?;
+    // Creating a missing home directory here would leave it owned by root.
+    if !home_dir.is_dir() {
+        bail!("home directory {home:?} of user {user:?} does not exist in the system");
+    }
+    let ssh_dir = resolve_in_root(system_dir, &home.join(".ssh"))
+        .whatever("unable to resolve `.ssh` directory")?;
+    if !ssh_dir.is_dir() {
+        fs::create_dir(&ssh_dir).whatever("unable to create `.ssh` directory")?;
+    }
+    let authorized_keys = resolve_in_root(system_dir, &home.join(".ssh/authorized_keys"))
+        .whatever("unable to resolve `authorized_keys`")?;
+    let mut keys = match fs::read_to_string(&authorized_keys) {
+        Ok(keys) => keys,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error).whatever("unable to read `authorized_keys`"),
+    };
+    let public_key = public_key.trim();
+    if !keys.lines().any(|line| line.trim() == public_key) {
+        if !keys.is_empty() && !keys.ends_with('\n') {
+            keys.push('\n');
+        }
+        keys.push_str(public_key);
+        keys.push('\n');
+        fs::write(&authorized_keys, keys).whatever("unable to write `authorized_keys`")?;
+    }
+    fs::set_permissions(&ssh_dir, fs::Permissions::from_mode(0o700))
+        .whatever("unable to set permissions of `.ssh` directory")?;
+    fs::set_permissions(&authorized_keys, fs::Permissions::from_mode(0o600))
+        .whatever("unable to set permissions of `authorized_keys`")?;
+    for path in [&ssh_dir, &authorized_keys] {
+        set_owner(path, Some(uid), Some(gid))?;
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -388,21 +766,216 @@ pub struct SystemInfo {
 pub struct SystemReleaseInfo {
     pub id: String,
     pub version: String,
+    /// Git commit the system has been built from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// Write a hybrid MBR, if any partitions of the layout should be included in it.
+fn write_hybrid_mbr(
+    layout: &ImageLayout,
+    table: &PartitionTable,
+    image_file: &Path,
+) -> BakeryResult<()> {
+    let Some(partitions) = &layout.partitions else {
+        return Ok(());
+    };
+    let mut entries = Vec::new();
+    for (layout_partition, image_partition) in partitions.iter().zip(table.partitions.iter()) {
+        let Some(hybrid) = layout_partition.hybrid else {
+            continue;
+        };
+        let PartitionType::Mbr(ty) = hybrid else {
+            bail!("hybrid MBR partition type `{hybrid}` is not an MBR partition type");
+        };
+        entries.push(HybridMbrEntry {
+            number: image_partition.number,
+            ty,
+            bootable: false,
+        });
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+    info!("Writing hybrid MBR.");
+    mbr::write_hybrid_mbr(image_file, table, &entries).whatever("unable to write hybrid MBR")?;
+    Ok(())
+}
+
+/// Derive an identifier from the name of the system and the given purpose.
+///
+/// Deriving identifiers instead of generating random ones keeps the identifiers, and
+/// thereby `/etc/fstab` and boot configurations referring to them, stable across
+/// rebuilds of the system.
+fn derive_id(system_name: &str, purpose: &str) -> [u8; 16] {
+    let mut hasher = Hasher::new();
+    hasher.push("system", system_name);
+    hasher.push("purpose", purpose);
+    let hash = hex::decode(hasher.finalize()).expect("hash must be valid hex");
+    hash[..16]
+        .try_into()
+        .expect("hash must have at least 16 bytes")
+}
+
+/// Assign derived identifiers to the partition table and its partitions.
+fn assign_stable_ids(table: &mut PartitionTable, system_name: &str) {
+    let disk_id = derive_id(system_name, "disk");
+    match &mut table.disk_id {
+        DiskId::Mbr(id) => {
+            *id = MbrId::new(u32::from_le_bytes(disk_id[..4].try_into().unwrap()));
+        }
+        DiskId::Gpt(id) => {
+            *id = Guid::from_random_bytes(disk_id);
+            for partition in &mut table.partitions {
+                partition.gpt_id = Some(Guid::from_random_bytes(derive_id(
+                    system_name,
+                    &format!("partition-{}", partition.number),
+                )));
+            }
+        }
+        _ => { /* keep the random identifier */ }
+    }
+}
+
+/// UUID of the filesystem of the given partition as reported by `blkid`.
+///
+/// Returns `None` for filesystems without a UUID.
+pub fn filesystem_uuid(
+    layout_partition: &ImagePartition,
+    filesystem: &Filesystem,
+    system_name: &str,
+    number: u8,
+) -> BakeryResult<Option<String>> {
+    let derived = || derive_id(system_name, &format!("filesystem-{number}"));
+    match filesystem {
+        Filesystem::Squashfs(_) => {
+            if layout_partition.uuid.is_some() {
+                bail!("Squashfs filesystems do not have a UUID");
+            }
+            Ok(None)
+        }
+        Filesystem::Fat32 => {
+            // FAT32 filesystems have a 32-bit volume ID instead of a UUID.
+            let volume_id = match &layout_partition.uuid {
+                Some(uuid) => uuid.replace('-', "").to_uppercase(),
+                None => hex::encode_upper(&derived()[..4]),
+            };
+            if volume_id.len() != 8 || !volume_id.chars().all(|c| c.is_ascii_hexdigit()) {
+                bail!("invalid FAT32 volume ID {volume_id:?}, expected `XXXX-XXXX`");
+            }
+            Ok(Some(format!("{}-{}", &volume_id[..4], &volume_id[4..])))
+        }
+        _ => match &layout_partition.uuid {
+            Some(uuid) => {
+                let uuid = uuid.to_lowercase();
+                if Guid::from_hex_str(&uuid).is_err() {
+                    bail!("invalid filesystem UUID {uuid:?}");
+                }
+                Ok(Some(uuid))
+            }
+            None => Ok(Some(
+                Guid::from_random_bytes(derived())
+                    .to_hex_str(ascii_numbers::Case::Lower)
+                    .to_string(),
+            )),
+        },
+    }
+}
+
+/// Path of the bootstrapping configuration in the system.
+const BOOTSTRAPPING_CONFIG_PATH: &str = "etc/rugix/bootstrapping.toml";
+
+/// Write a bootstrapping configuration using the given default layout, unless the system
+/// already has a bootstrapping configuration.
+///
+/// This ensures that the partitions created by Rugix Ctrl on the first boot match the
+/// partitions of the image. Without a fixed size of the system partitions, nothing is
+/// written.
+fn write_bootstrapping_config(
+    default_layout: &DefaultLayoutConfig,
+    system_dir: &Path,
+) -> BakeryResult<()> {
+    let Some(config) = bootstrapping_config(default_layout) else {
+        return Ok(());
+    };
+    let config_path = system_dir.join(BOOTSTRAPPING_CONFIG_PATH);
+    if config_path.exists() {
+        info!("using existing `/{BOOTSTRAPPING_CONFIG_PATH}`");
+        return Ok(());
+    }
+    info!("writing `/{BOOTSTRAPPING_CONFIG_PATH}`");
+    fs::create_dir_all(config_path.parent().unwrap()).whatever("unable to create `/etc/rugix`")?;
+    fs::write(&config_path, config)
+        .whatever_with(|_| format!("unable to write `/{BOOTSTRAPPING_CONFIG_PATH}`"))?;
+    Ok(())
+}
+
+/// Bootstrapping configuration for the given default layout.
+///
+/// Returns `None`, if the default layout has no fixed size of the system partitions.
+fn bootstrapping_config(default_layout: &DefaultLayoutConfig) -> Option<String> {
+    let system_size = default_layout.system_size?;
+    let mut config = format!(
+        "[layout]\ntype = \"default\"\nsystem-size = {}\n",
+        system_size.raw
+    );
+    for (name, size) in [
+        ("config-size", default_layout.config_size),
+        ("boot-size", default_layout.boot_size),
+        ("alignment", default_layout.alignment),
+    ] {
+        if let Some(size) = size {
+            config.push_str(&format!("{name} = {}\n", size.raw));
+        }
+    }
+    Some(config)
+}
+
+/// Find the partition of the `A` system, i.e., the first partition with the `system`
+/// root.
+fn system_partition<'t>(
+    layout: &ImageLayout,
+    table: &'t PartitionTable,
+) -> BakeryResult<&'t Partition> {
+    let Some(idx) = layout
+        .partitions
+        .iter()
+        .flatten()
+        .position(|partition| partition.root.as_deref() == Some("system"))
+    else {
+        bail!("layout has no partition with the `system` root");
+    };
+    Ok(&table.partitions[idx])
 }
 
 /// We are calculating everything with a portable block size of 512 bytes.
 const BLOCK_SIZE: NumBytes = NumBytes::from_raw(512);
 
-/// We align everything to 2048 blocks, i.e., 1MiB.
+/// By default, we align everything to 2048 blocks, i.e., 1MiB.
 const ALIGNMENT: NumBlocks = NumBlocks::from_raw(2048);
 
+/// Alignment of the start of the partitions of the given layout.
+fn layout_alignment(layout: &ImageLayout) -> BakeryResult<NumBlocks> {
+    let Some(alignment) = layout.alignment else {
+        return Ok(ALIGNMENT);
+    };
+    if alignment.raw == 0 || alignment.raw % BLOCK_SIZE.into_raw() != 0 {
+        bail!("partition alignment {alignment} is not a multiple of 512 bytes");
+    }
+    Ok(NumBlocks::from_raw(alignment.raw / BLOCK_SIZE.into_raw()))
+}
+
 /// Convert number of bytes to number of blocks.
 fn bytes_to_blocks(bytes: NumBytes) -> NumBlocks {
     NumBlocks::from_raw(bytes.into_raw().div_ceil(BLOCK_SIZE.into_raw()))
 }
 
 /// Compute the partition table for an image based on the provided layout.
-fn compute_partition_table(layout: &ImageLayout, roots_dir: &Path) -> BakeryResult<PartitionTable> {
+fn compute_partition_table(
+    layout: &ImageLayout,
+    image_size: Option<byte_calc::NumBytes>,
+    roots_dir: &Path,
+) -> BakeryResult<PartitionTable> {
     let table_type = layout
         .ty
         .map(|ty| match ty {
@@ -410,12 +983,109 @@ fn compute_partition_table(layout: &ImageLayout, roots_dir: &Path) -> BakeryResu
             crate::config::images::PartitionTableType::Gpt => PartitionTableType::Gpt,
         })
         .unwrap_or(PartitionTableType::Mbr);
+    let layout_partitions = layout.partitions.as_deref().unwrap_or_default();
+    let alignment = layout_alignment(layout)?;
+    // Resolve the sizes of the partitions. The size of the partition taking up the rest
+    // of the image can only be determined once all other partitions have been placed.
+    let mut sizes = Vec::with_capacity(layout_partitions.len());
+    let mut rest = None;
+    for (idx, partition) in layout_partitions.iter().enumerate() {
+        let size = match &partition.size {
+            Some(size) if size.is_rest() => {
+                if image_size.is_none() {
+                    bail!("partitions using the rest of the image require an image size");
+                }
+                if rest.replace(idx).is_some() {
+                    bail!("only a single partition can use the rest of the image");
+                }
+                NumBlocks::from_raw(0)
+            }
+            Some(size) => {
+                let Some(size) = size.resolve(image_size, byte_calc::NumBytes::ZERO) else {
+                    bail!("partitions with a relative size require an image size");
+                };
+                bytes_to_blocks(size.raw.into())
+            }
+            None if partition.ty.is_some_and(|ty| ty.is_extended()) => {
+                // The size of the extended partition is computed when placing partitions.
+                NumBlocks::from_raw(0)
+            }
+            None if matches!(partition.filesystem, Some(Filesystem::Swap)) => {
+                bail!("swap partitions must have a fixed size");
+            }
+            None => {
+                let Some(path) = &partition.root else {
+                    bail!("partitions without a fixed size must have a root path");
+                };
+                let size = compute_fs_size(roots_dir.join(path))?;
+                if partition.encryption.is_some() {
+                    size + bytes_to_blocks(NumBytes::from_raw(encryption::LUKS_SIZE_REDUCTION))
+                } else if partition.verity.unwrap_or(false) {
+                    let data_size = size.into_raw() * BLOCK_SIZE.into_raw();
+                    size + bytes_to_blocks(NumBytes::from_raw(verity::hash_tree_size(data_size)))
+                } else {
+                    size
+                }
+            }
+        };
+        sizes.push(size);
+    }
+    let mut partitions = place_partitions(table_type, layout, &sizes, alignment)?;
+    if let (Some(idx), Some(image_size)) = (rest, image_size) {
+        let required = required_disk_size(&partitions);
+        let available = NumBlocks::from_raw(image_size.raw / BLOCK_SIZE.into_raw());
+        if required > available {
+            bail!("image size is too small, no space left for the rest of the image");
+        }
+        let remaining = (available - required).floor_align_to(alignment);
+        let remaining = layout_partitions[idx]
+            .size
+            .unwrap()
+            .resolve(
+                Some(image_size),
+                byte_calc::NumBytes::new(BLOCK_SIZE.into_raw() * remaining.into_raw()),
+            )
+            .unwrap();
+        sizes[idx] = NumBlocks::from_raw(remaining.raw / BLOCK_SIZE.into_raw());
+        partitions = place_partitions(table_type, layout, &sizes, alignment)?;
+    }
+    // Create and validate the partition table.
+    let disk_size = required_disk_size(&partitions);
+    if let Some(image_size) = image_size {
+        if image_size.raw < disk_size.into_raw() * BLOCK_SIZE.into_raw() {
+            bail!(
+                "image size of {image_size} is too small for the partitions ({} required)",
+                byte_calc::NumBytes::new(disk_size.into_raw() * BLOCK_SIZE.into_raw())
+            );
+        }
+    }
+    let table_id = match table_type {
+        PartitionTableType::Mbr => DiskId::random_mbr(),
+        PartitionTableType::Gpt => DiskId::random_gpt(),
+    };
+    let mut table = PartitionTable::new(table_id, disk_size);
+    table.partitions = partitions;
+    table
+        .validate()
+        .whatever("unable to validate image partitions")?;
+    Ok(table)
+}
+
+/// Place the partitions of the layout with the given sizes.
+fn place_partitions(
+    table_type: PartitionTableType,
+    layout: &ImageLayout,
+    sizes: &[NumBlocks],
+    alignment: NumBlocks,
+) -> BakeryResult<Vec<Partition>> {
     let mut partitions = Vec::new();
-    let mut next_usable = ALIGNMENT;
+    // The first partition never starts before 1MiB leaving space for the partition table
+    // and bootloaders.
+    let mut next_usable = ALIGNMENT.ceil_align_to(alignment);
     let mut next_number = 1;
     let mut in_extended = false;
     if let Some(layout_partitions) = &layout.partitions {
-        for partition in layout_partitions {
+        for (partition, size) in layout_partitions.iter().zip(sizes) {
             // Partitions are numbered based on their appearance in the layout.
             let number = next_number;
             next_number += 1;
@@ -424,16 +1094,28 @@ fn compute_partition_table(layout: &ImageLayout, roots_dir: &Path) -> BakeryResu
             }
             // Leave space for the EBR, if we are creating a logical MBR partition.
             if in_extended {
-                next_usable = (next_usable + NumBlocks::ONE).ceil_align_to(ALIGNMENT);
+                next_usable = (next_usable + NumBlocks::ONE).ceil_align_to(alignment);
             }
-            // By default, we create `LINUX` partitions.
-            let partition_type = partition.ty.unwrap_or(match table_type {
-                PartitionTableType::Mbr => mbr_types::LINUX,
-                PartitionTableType::Gpt => gpt_types::LINUX,
+            // By default, we create `LINUX` or `LINUX_SWAP` partitions.
+            let is_swap = matches!(partition.filesystem, Some(Filesystem::Swap));
+            let partition_type = partition.ty.unwrap_or(match (table_type, is_swap) {
+                (PartitionTableType::Mbr, false) => mbr_types::LINUX,
+                (PartitionTableType::Mbr, true) => mbr_types::LINUX_SWAP,
+                (PartitionTableType::Gpt, false) => gpt_types::LINUX,
+                (PartitionTableType::Gpt, true) => gpt_types::LINUX_SWAP,
             });
-            if layout.ty.unwrap() != partition_type.table_type() {
+            if table_type != partition_type.table_type() {
                 bail!("partition type `{partition_type}` does not match table type `{table_type}`",)
             }
+            let attributes = partition.attributes.unwrap_or_default();
+            if table_type.is_mbr() {
+                if partition.name.is_some() {
+                    bail!("partition names are not supported by MBR");
+                }
+                if !GptAttributes::LEGACY_BIOS_BOOTABLE.contains(attributes) {
+                    bail!("MBR partitions only support the `LegacyBIOSBootable` attribute");
+                }
+            }
             // The start of the partition is the next usable block.
             let start = next_usable;
             if partition_type.is_extended() {
@@ -448,29 +1130,23 @@ fn compute_partition_table(layout: &ImageLayout, roots_dir: &Path) -> BakeryResu
                     ty: partition_type,
                     name: None,
                     gpt_id: None,
+                    attributes,
                 });
                 in_extended = true;
                 next_number = 5;
                 // Space for the EBR is automatically added prior to the next partition.
             } else {
-                let size = match &partition.size {
-                    Some(size) => bytes_to_blocks(size.raw.into()),
-                    None => {
-                        let Some(path) = &partition.root else {
-                            bail!("partitions without a fixed size must have a root path");
-                        };
-                        compute_fs_size(roots_dir.join(path))?
-                    }
-                };
+                let size = *size;
                 partitions.push(Partition {
                     number,
                     start,
                     size,
                     ty: partition_type,
-                    name: None,
+                    name: partition.name.clone(),
                     gpt_id: None,
+                    attributes,
                 });
-                next_usable = (start + size).ceil_align_to(ALIGNMENT);
+                next_usable = (start + size).ceil_align_to(alignment);
             }
         }
     }
@@ -479,26 +1155,20 @@ fn compute_partition_table(layout: &ImageLayout, roots_dir: &Path) -> BakeryResu
         if !partition.ty.is_extended() {
             continue;
         }
-        partition.size = (next_usable - partition.start + NumBlocks::ONE).ceil_align_to(ALIGNMENT);
+        partition.size = (next_usable - partition.start + NumBlocks::ONE).ceil_align_to(alignment);
         break;
     }
-    // Create and validate the partition table.
-    let image_size = match partitions.last() {
+    Ok(partitions)
+}
+
+/// Compute the disk size required for the given partitions.
+fn required_disk_size(partitions: &[Partition]) -> NumBlocks {
+    match partitions.last() {
         Some(last_partition) => {
             (last_partition.start + last_partition.size).ceil_align_to(ALIGNMENT) + ALIGNMENT
         }
         None => ALIGNMENT * 32,
-    };
-    let table_id = match table_type {
-        PartitionTableType::Mbr => DiskId::random_mbr(),
-        PartitionTableType::Gpt => DiskId::random_gpt(),
-    };
-    let mut table = PartitionTable::new(table_id, image_size);
-    table.partitions = partitions;
-    table
-        .validate()
-        .whatever("unable to validate image partitions")?;
-    Ok(table)
+    }
 }
 
 /// Compute the required size for a filesystem based on the given root path.
@@ -521,3 +1191,56 @@ fn compute_fs_size(root: PathBuf) -> BakeryResult<NumBlocks> {
     size += NumBytes::from_raw(size.into_raw().div_ceil(5));
     Ok(bytes_to_blocks(size))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bootstrapping_config() {
+        assert_eq!(bootstrapping_config(&DefaultLayoutConfig::new()), None);
+        let default_layout = DefaultLayoutConfig::new()
+            .with_system_size(Some(byte_calc::NumBytes::gibibytes(4)))
+            .with_boot_size(Some(byte_calc::NumBytes::mebibytes(512)))
+            .with_alignment(Some(byte_calc::NumBytes::mebibytes(4)));
+        assert_eq!(
+            bootstrapping_config(&default_layout).unwrap(),
+            "[layout]\ntype = \"default\"\nsystem-size = 4294967296\nboot-size = 536870912\nalignment = 4194304\n"
+        );
+    }
+
+    #[test]
+    fn test_authorize_ssh_key() {
+        let system_dir = tempfile::tempdir().unwrap();
+        let metadata = fs::metadata(system_dir.path()).unwrap();
+        fs::create_dir_all(system_dir.path().join("etc")).unwrap();
+        fs::create_dir_all(system_dir.path().join("home/user")).unwrap();
+        fs::write(
+            system_dir.path().join("etc/passwd"),
+            format!(
+                "user:x:{}:{}::/home/user:/bin/sh\nnobody:x:65534:65534::/nonexistent:/bin/false\n",
+                metadata.uid(),
+                metadata.gid()
+            ),
+        )
+        .unwrap();
+        let key =
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIFvHXbZ3n3Pq4Yb1yJ4zGJ0s8bKjP3pJq6l4cYt0m5rW test";
+        authorize_ssh_key(system_dir.path(), "user", key).unwrap();
+        // Keys are only authorized once.
+        authorize_ssh_key(system_dir.path(), "user", &format!("{key}\n")).unwrap();
+        let ssh_dir = system_dir.path().join("home/user/.ssh");
+        assert_eq!(
+            fs::read_to_string(ssh_dir.join("authorized_keys")).unwrap(),
+            format!("{key}\n")
+        );
+        assert_eq!(
+            fs::metadata(&ssh_dir).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+        // Missing home directories are not created.
+        assert!(authorize_ssh_key(system_dir.path(), "nobody", key).is_err());
+        assert!(!system_dir.path().join("nonexistent").exists());
+        assert!(authorize_ssh_key(system_dir.path(), "unknown", key).is_err());
+    }
+}
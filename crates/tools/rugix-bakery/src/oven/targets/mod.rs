@@ -1,120 +1,150 @@
 use byte_calc::NumBytes;
 
+use reportify::bail;
 use rugix_common::disk::gpt::gpt_types;
 use rugix_common::disk::mbr::mbr_types;
 
 use crate::config::images::{
-    Ext4Options, Filesystem, ImageLayout, ImagePartition, PartitionTableType, SquashfsOptions,
+    DefaultLayoutConfig, Ext4Options, Filesystem, ImageLayout, ImagePartition, PartitionTableType,
+    SquashfsOptions,
 };
+use crate::config::partition_size::{PartitionSize, PartitionSizeBase};
 use crate::config::systems::Target;
+use crate::BakeryResult;
 
 pub mod generic_grub_efi;
 pub mod rpi_tryboot;
 pub mod rpi_uboot;
 
+/// Default size of the config partition.
+const DEFAULT_CONFIG_SIZE: NumBytes = NumBytes::mebibytes(256);
+
+/// Default size of the boot partitions of MBR layouts.
+const DEFAULT_MBR_BOOT_SIZE: NumBytes = NumBytes::mebibytes(128);
+
+/// Default size of the boot partitions of GPT layouts.
+const DEFAULT_GPT_BOOT_SIZE: NumBytes = NumBytes::mebibytes(256);
+
 /// Get the default image layout for the provided target.
 pub fn get_default_layout(
     target: &Target,
     squashfs_options: Option<&SquashfsOptions>,
-) -> Option<ImageLayout> {
-    match target {
-        Target::GenericGrubEfi => Some(default_gpt_layout(squashfs_options)),
-        Target::RpiTryboot => Some(default_mbr_layout(squashfs_options)),
-        Target::RpiUboot => Some(default_mbr_layout(squashfs_options)),
-        Target::Unknown => None,
+    config: Option<&DefaultLayoutConfig>,
+    image_size: Option<NumBytes>,
+) -> BakeryResult<Option<ImageLayout>> {
+    let table_type = match target {
+        Target::GenericGrubEfi => PartitionTableType::Gpt,
+        Target::RpiTryboot | Target::RpiUboot => PartitionTableType::Mbr,
+        Target::Unknown => return Ok(None),
+    };
+    let default_config = DefaultLayoutConfig::new();
+    let config = config.unwrap_or(&default_config);
+    let is_mbr = matches!(table_type, PartitionTableType::Mbr);
+    let (config_ty, boot_ty, linux_ty) = if is_mbr {
+        (mbr_types::FAT32_LBA, mbr_types::FAT32_LBA, mbr_types::LINUX)
+    } else {
+        (gpt_types::EFI, gpt_types::LINUX, gpt_types::LINUX)
+    };
+    let boot_size = config.boot_size.unwrap_or(if is_mbr {
+        DEFAULT_MBR_BOOT_SIZE
+    } else {
+        DEFAULT_GPT_BOOT_SIZE
+    });
+    let boot_filesystem = if is_mbr {
+        Filesystem::Fat32
+    } else {
+        Filesystem::Ext4(Ext4Options::new())
+    };
+    let mut partitions = vec![
+        // Config partition.
+        ImagePartition::new()
+            .with_size(Some(
+                config.config_size.unwrap_or(DEFAULT_CONFIG_SIZE).into(),
+            ))
+            .with_ty(Some(config_ty))
+            .with_filesystem(Some(Filesystem::Fat32))
+            .with_root(Some("config".to_owned())),
+        // `A` boot partition.
+        ImagePartition::new()
+            .with_size(Some(boot_size.into()))
+            .with_ty(Some(boot_ty))
+            .with_filesystem(Some(boot_filesystem))
+            .with_root(Some("boot".to_owned())),
+        // `B` boot partition.
+        ImagePartition::new()
+            .with_size(Some(boot_size.into()))
+            .with_ty(Some(boot_ty)),
+    ];
+    if is_mbr {
+        // MBR extended partition.
+        partitions.push(ImagePartition::new().with_ty(Some(mbr_types::EXTENDED)));
     }
-}
-
-fn default_mbr_layout(squashfs_options: Option<&SquashfsOptions>) -> ImageLayout {
-    ImageLayout::new()
-        .with_ty(Some(PartitionTableType::Mbr))
-        .with_partitions(Some(vec![
-            // Config partition.
-            ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(256)))
-                .with_ty(Some(mbr_types::FAT32_LBA))
-                .with_filesystem(Some(Filesystem::Fat32))
-                .with_root(Some("config".to_owned())),
-            // `A` boot partition.
+    // `A` system partition.
+    partitions.push(
+        ImagePartition::new()
+            .with_size(config.system_size.map(PartitionSize::from))
+            .with_ty(Some(linux_ty))
+            .with_filesystem(Some(system_filesystem(squashfs_options)))
+            .with_root(Some("system".to_owned())),
+    );
+    // With a fixed size, the `B` system partition is already included in the image.
+    if let Some(system_size) = config.system_size {
+        // `B` system partition.
+        partitions.push(
             ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(128)))
-                .with_ty(Some(mbr_types::FAT32_LBA))
-                .with_filesystem(Some(Filesystem::Fat32))
-                .with_root(Some("boot".to_owned())),
-            // `B` boot partition.
-            ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(128)))
-                .with_ty(Some(mbr_types::FAT32_LBA)),
-            // MBR extended partition.
-            ImagePartition::new().with_ty(Some(mbr_types::EXTENDED)),
-            // `A` system partition.
+                .with_size(Some(system_size.into()))
+                .with_ty(Some(linux_ty)),
+        );
+    }
+    if let Some(data) = &config.data {
+        // The data partition must come after the `B` system partition.
+        if config.system_size.is_none() {
+            bail!("the data partition of the default layout requires a system size");
+        }
+        let size = data.size.or_else(|| {
+            image_size.map(|_| PartitionSize {
+                base: PartitionSizeBase::Rest,
+                align: None,
+            })
+        });
+        if size.is_none() && data.root.is_none() {
+            bail!("the data partition of the default layout requires a size or an image size");
+        }
+        // Data partition.
+        partitions.push(
             ImagePartition::new()
-                .with_ty(Some(mbr_types::LINUX))
-                .with_filesystem(Some(
-                    squashfs_options
-                        .cloned()
-                        .map(Filesystem::Squashfs)
-                        .unwrap_or(Filesystem::Ext4(
-                            Ext4Options::new().with_additional_options(Some(
-                                [
-                                    "-O",
-                                    "^has_journal",
-                                    "-E",
-                                    "hash_seed=035cb65d-0a86-404a-bad7-19c88d05e400",
-                                    "-U",
-                                    "12341234-a4ec-4304-a70f-c549ea829da9",
-                                ]
-                                .map(str::to_owned)
-                                .into(),
-                            )),
-                        )),
-                ))
-                .with_root(Some("system".to_owned())),
-        ]))
+                .with_size(size)
+                .with_ty(Some(linux_ty))
+                .with_filesystem(Some(Filesystem::Ext4(Ext4Options::new())))
+                .with_root(data.root.clone())
+                .with_label(Some("data".to_owned())),
+        );
+    }
+    Ok(Some(
+        ImageLayout::new()
+            .with_ty(Some(table_type))
+            .with_alignment(config.alignment)
+            .with_partitions(Some(partitions)),
+    ))
 }
 
-fn default_gpt_layout(squashfs_options: Option<&SquashfsOptions>) -> ImageLayout {
-    ImageLayout::new()
-        .with_ty(Some(PartitionTableType::Gpt))
-        .with_partitions(Some(vec![
-            // Config partition.
-            ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(256)))
-                .with_ty(Some(gpt_types::EFI))
-                .with_filesystem(Some(Filesystem::Fat32))
-                .with_root(Some("config".to_owned())),
-            // `A` boot partition.
-            ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(256)))
-                .with_ty(Some(gpt_types::LINUX))
-                .with_filesystem(Some(Filesystem::Ext4(Ext4Options::new())))
-                .with_root(Some("boot".to_owned())),
-            // `B` boot partition.
-            ImagePartition::new()
-                .with_size(Some(NumBytes::mebibytes(256)))
-                .with_ty(Some(gpt_types::LINUX)),
-            // `A` system partition.
-            ImagePartition::new()
-                .with_ty(Some(gpt_types::LINUX))
-                .with_filesystem(Some(
-                    squashfs_options
-                        .cloned()
-                        .map(Filesystem::Squashfs)
-                        .unwrap_or(Filesystem::Ext4(
-                            Ext4Options::new().with_additional_options(Some(
-                                [
-                                    "-O",
-                                    "^has_journal",
-                                    "-E",
-                                    "hash_seed=035cb65d-0a86-404a-bad7-19c88d05e400",
-                                    "-U",
-                                    "12341234-a4ec-4304-a70f-c549ea829da9",
-                                ]
-                                .map(str::to_owned)
-                                .into(),
-                            )),
-                        )),
-                ))
-                .with_root(Some("system".to_owned())),
-        ]))
+/// Filesystem of the `A` system partition.
+fn system_filesystem(squashfs_options: Option<&SquashfsOptions>) -> Filesystem {
+    squashfs_options
+        .cloned()
+        .map(Filesystem::Squashfs)
+        .unwrap_or(Filesystem::Ext4(
+            Ext4Options::new().with_additional_options(Some(
+                [
+                    "-O",
+                    "^has_journal",
+                    "-E",
+                    "hash_seed=035cb65d-0a86-404a-bad7-19c88d05e400",
+                    "-U",
+                    "12341234-a4ec-4304-a70f-c549ea829da9",
+                ]
+                .map(str::to_owned)
+                .into(),
+            )),
+        ))
 }
@@ -0,0 +1,151 @@
+//! Rendering of template files by `templates` steps.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use minijinja::{path_loader, Environment, UndefinedBehavior};
+use reportify::{bail, ResultExt};
+use rugix_common::fsutils::resolve_in_root;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::config::recipes::TemplatesConfig;
+use crate::config::systems::Architecture;
+use crate::oven::customize::RecipeJob;
+use crate::oven::files::{parse_mode, resolve_ownership, set_owner};
+use crate::project::recipes::StepKind;
+use crate::project::ProjectRef;
+use crate::BakeryResult;
+
+/// Permissions of rendered files by default.
+const DEFAULT_MODE: u32 = 0o644;
+
+/// Information available to templates.
+pub struct TemplateContext<'c> {
+    /// Project the layer belongs to.
+    pub project: &'c ProjectRef,
+    /// Name of the layer.
+    pub layer: &'c str,
+    /// Name of the recipe.
+    pub recipe: &'c str,
+    /// Parameters of the recipe.
+    pub parameters: &'c HashMap<String, String>,
+    /// Architecture of the layer.
+    pub arch: Architecture,
+    /// Source date epoch of the build.
+    pub source_date_epoch: u64,
+}
+
+/// Render the templates of a recipe into the root filesystem.
+///
+/// Templates are loaded relative to the recipe directory, hence, they can include or
+/// extend other templates of the recipe.
+pub fn render_templates(
+    config: &TemplatesConfig,
+    recipe_dir: &Path,
+    root_dir: &Path,
+    ctx: &TemplateContext,
+) -> BakeryResult<()> {
+    let mut env = Environment::new();
+    env.set_loader(path_loader(recipe_dir));
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    let build_date = jiff::Timestamp::from_second(ctx.source_date_epoch as i64)
+        .map(|timestamp| timestamp.to_string())
+        .unwrap_or_default();
+    let values = json!({
+        "params": ctx.parameters,
+        "recipe": { "name": ctx.recipe },
+        "layer": { "name": ctx.layer },
+        "arch": ctx.arch.as_str(),
+        "build": {
+            "source_date_epoch": ctx.source_date_epoch,
+            "date": build_date,
+            "rugix_version": rugix_version::RUGIX_GIT_VERSION,
+        },
+        "systems": systems_value(ctx.project),
+        "env": template_env(config)?,
+    });
+    for template in &config.templates {
+        info!(
+            "rendering {:?} to {:?}",
+            template.source, template.destination
+        );
+        let rendered = env
+            .get_template(&template.source)
+            .and_then(|source| source.render(&values))
+            .whatever_with(|_| format!("unable to render template {:?}", template.source))?;
+        let mode = match &template.mode {
+            Some(mode) => parse_mode(mode)?,
+            None => DEFAULT_MODE,
+        };
+        // Symbolic links must not lead outside of the root filesystem.
+        let destination = resolve_in_root(root_dir, Path::new(&template.destination))
+            .whatever_with(|_| format!("unable to resolve {:?}", template.destination))?;
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).whatever("unable to create destination directory")?;
+        }
+        fs::write(&destination, rendered)
+            .whatever_with(|_| format!("unable to write {:?}", template.destination))?;
+        fs::set_permissions(&destination, fs::Permissions::from_mode(mode))
+            .whatever("unable to set permissions of rendered file")?;
+        let (uid, gid) = resolve_ownership(
+            root_dir,
+            Some(template.owner.as_deref().unwrap_or("root")),
+            Some(template.group.as_deref().unwrap_or("root")),
+        )?;
+        set_owner(&destination, uid, gid)?;
+    }
+    Ok(())
+}
+
+/// Values of the environment variables which the templates of a step may read.
+///
+/// Only explicitly listed variables are available such that secrets of the build
+/// environment, e.g., access tokens, do not end up in the root filesystem.
+fn template_env(config: &TemplatesConfig) -> BakeryResult<BTreeMap<String, String>> {
+    let mut env = BTreeMap::new();
+    for name in config.env.iter().flatten() {
+        let Ok(value) = std::env::var(name) else {
+            bail!("environment variable `{name}` for templates is not set");
+        };
+        env.insert(name.clone(), value);
+    }
+    Ok(env)
+}
+
+/// Configurations of the systems of the project.
+fn systems_value(project: &ProjectRef) -> serde_json::Value {
+    serde_json::to_value(project.config().systems.clone().unwrap_or_default()).unwrap()
+}
+
+/// Inputs of the templates of the given recipes beyond the recipes and their parameters.
+///
+/// The inputs are part of the cache key of a layer and are recorded to rebuild the layer
+/// when they change. They are digests such that the values of environment variables are
+/// not stored anywhere.
+pub fn template_inputs(
+    project: &ProjectRef,
+    jobs: &[RecipeJob],
+) -> BakeryResult<BTreeMap<String, String>> {
+    let digest = |value: &str| hex::encode(Sha256::digest(value.as_bytes()));
+    let mut inputs = BTreeMap::new();
+    for job in jobs {
+        for step in &job.recipe.steps {
+            let StepKind::Templates { config } = &step.kind else {
+                continue;
+            };
+            inputs.insert(
+                "systems".to_owned(),
+                digest(&systems_value(project).to_string()),
+            );
+            for (name, value) in template_env(config)? {
+                inputs.insert(format!("env.{name}"), digest(&value));
+            }
+        }
+    }
+    Ok(inputs)
+}
@@ -0,0 +1,213 @@
+//! Cross-compilation toolchains provisioned by `toolchain` steps.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reportify::{bail, ResultExt};
+use semver::Version;
+use tracing::info;
+use url::Url;
+use xscript::{read_str, run, Run};
+
+use crate::config::recipes::{ToolchainConfig, ToolchainKind};
+use crate::config::systems::Architecture;
+use crate::utils::caching::download;
+use crate::BakeryResult;
+
+/// Target of a cross-compilation toolchain.
+struct Target {
+    /// GNU target triple, also used as the Debian multiarch directory.
+    gnu_triple: &'static str,
+    /// Architecture name used by Zig.
+    zig_arch: &'static str,
+    /// ABI suffix used by Zig for the target.
+    zig_abi: &'static str,
+    /// Additional compiler flags for GCC.
+    gcc_flags: &'static str,
+    /// CPU model used by Zig.
+    zig_cpu: Option<&'static str>,
+}
+
+impl Target {
+    fn for_arch(arch: Architecture) -> Self {
+        match arch {
+            Architecture::Amd64 => Self {
+                gnu_triple: "x86_64-linux-gnu",
+                zig_arch: "x86_64",
+                zig_abi: "",
+                gcc_flags: "",
+                zig_cpu: None,
+            },
+            Architecture::Arm64 => Self {
+                gnu_triple: "aarch64-linux-gnu",
+                zig_arch: "aarch64",
+                zig_abi: "",
+                gcc_flags: "",
+                zig_cpu: None,
+            },
+            Architecture::Armv7 => Self {
+                gnu_triple: "arm-linux-gnueabihf",
+                zig_arch: "arm",
+                zig_abi: "eabihf",
+                gcc_flags: "",
+                zig_cpu: Some("generic+v7a"),
+            },
+            Architecture::Armhf => Self {
+                gnu_triple: "arm-linux-gnueabihf",
+                zig_arch: "arm",
+                zig_abi: "eabihf",
+                gcc_flags: "-marm -march=armv6+fp -mfloat-abi=hard",
+                zig_cpu: Some("arm1176jzf_s"),
+            },
+            Architecture::Arm => Self {
+                gnu_triple: "arm-linux-gnueabi",
+                zig_arch: "arm",
+                zig_abi: "eabi",
+                gcc_flags: "-marm -march=armv6",
+                zig_cpu: Some("arm1176jz_s"),
+            },
+        }
+    }
+}
+
+/// Provision the toolchain and compute the environment variables for subsequent steps.
+pub fn toolchain_vars(
+    config: &ToolchainConfig,
+    arch: Architecture,
+    root_dir: &Path,
+) -> BakeryResult<Vec<(&'static str, OsString)>> {
+    let target = Target::for_arch(arch);
+    let is_musl = is_musl(root_dir);
+    let root = root_dir.to_string_lossy();
+    let mut vars = match config.kind {
+        ToolchainKind::Gcc => {
+            if is_musl {
+                bail!("GCC toolchain does not support musl-based systems, use Zig instead");
+            }
+            let triple = target.gnu_triple;
+            if !is_on_path(&format!("{triple}-gcc")) {
+                bail!("GCC cross-compiler for `{triple}` is not installed");
+            }
+            let flags = format!("--sysroot={root} {}", target.gcc_flags);
+            let mut vars = vec![
+                ("CC", format!("{triple}-gcc {flags}")),
+                ("AR", format!("{triple}-ar")),
+                ("STRIP", format!("{triple}-strip")),
+                ("CROSS_COMPILE", format!("{triple}-")),
+                ("RUGIX_TARGET_TRIPLE", triple.to_owned()),
+            ];
+            // The C++ compiler is installed separately from the C compiler.
+            if is_on_path(&format!("{triple}-g++")) {
+                vars.push(("CXX", format!("{triple}-g++ {flags}")));
+            }
+            vars
+        }
+        ToolchainKind::Zig => {
+            let zig = provision_zig(config)?;
+            let zig = zig.to_string_lossy();
+            let libc = if is_musl { "musl" } else { "gnu" };
+            let triple = format!("{}-linux-{libc}{}", target.zig_arch, target.zig_abi);
+            let mut flags = format!("-target {triple}");
+            if let Some(cpu) = target.zig_cpu {
+                flags.push_str(&format!(" -mcpu={cpu}"));
+            }
+            vec![
+                ("CC", format!("{zig} cc {flags}")),
+                ("CXX", format!("{zig} c++ {flags}")),
+                ("AR", format!("{zig} ar")),
+                ("RUGIX_TARGET_TRIPLE", triple),
+            ]
+        }
+    };
+    let multiarch = target.gnu_triple;
+    vars.extend([
+        ("PKG_CONFIG", "pkg-config".to_owned()),
+        ("PKG_CONFIG_SYSROOT_DIR", root.to_string()),
+        (
+            "PKG_CONFIG_LIBDIR",
+            format!(
+                "{root}/usr/lib/{multiarch}/pkgconfig:{root}/usr/lib/pkgconfig:{root}/usr/share/pkgconfig"
+            ),
+        ),
+        ("RUGIX_SYSROOT", root.to_string()),
+    ]);
+    Ok(vars
+        .into_iter()
+        .map(|(name, value)| (name, value.into()))
+        .collect())
+}
+
+/// Check whether the system in the given root directory uses musl as its C library.
+pub(crate) fn is_musl(root_dir: &Path) -> bool {
+    fs::read_dir(root_dir.join("lib"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-"))
+        })
+        .unwrap_or(false)
+}
+
+/// Download and extract the pinned Zig release, returning the path of the `zig` binary.
+pub(crate) fn provision_zig(config: &ToolchainConfig) -> BakeryResult<PathBuf> {
+    let Some(version) = &config.version else {
+        bail!("Zig toolchain requires a version");
+    };
+    let Some(expected) = &config.sha256 else {
+        bail!("Zig toolchain requires the `sha256` hash of the release archive");
+    };
+    let expected = expected.trim().to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        bail!("invalid `sha256` hash {expected:?} of Zig toolchain");
+    }
+    let host = std::env::consts::ARCH;
+    let url = match &config.url {
+        Some(url) => url.clone(),
+        None => {
+            // The naming scheme of release archives changed with Zig 0.14.1.
+            let new_naming = Version::parse(version)
+                .ok()
+                .is_none_or(|version| version >= Version::new(0, 14, 1));
+            if new_naming {
+                format!("https://ziglang.org/download/{version}/zig-{host}-linux-{version}.tar.xz")
+            } else {
+                format!("https://ziglang.org/download/{version}/zig-linux-{host}-{version}.tar.xz")
+            }
+        }
+    };
+    // The hash identifies the archive, such that changing the URL or hash does not reuse
+    // a toolchain extracted from a different archive.
+    let toolchain_name = format!("zig-{version}-{host}-{}", &expected[..16]);
+    let toolchain_dir = Path::new(".rugix/toolchains").join(&toolchain_name);
+    if !toolchain_dir.exists() {
+        let archive = download(&Url::parse(&url).whatever("invalid toolchain URL")?)?;
+        let output = read_str!(["sha256sum", &archive])
+            .whatever("unable to compute hash of toolchain archive")?;
+        let actual = output.split_whitespace().next().unwrap_or_default();
+        if !actual.eq_ignore_ascii_case(&expected) {
+            bail!("hash mismatch for toolchain archive from `{url}` (got {actual})");
+        }
+        info!("extracting Zig {version}");
+        let temp_dir = toolchain_dir.with_file_name(format!("{toolchain_name}.partial"));
+        fs::remove_dir_all(&temp_dir).ok();
+        fs::create_dir_all(&temp_dir).whatever("unable to create toolchain directory")?;
+        run!([
+            "tar",
+            "-xf",
+            &archive,
+            "-C",
+            &temp_dir,
+            "--strip-components=1"
+        ])
+        .whatever("unable to extract toolchain archive")?;
+        fs::rename(&temp_dir, &toolchain_dir).whatever("unable to move toolchain directory")?;
+    }
+    std::path::absolute(toolchain_dir.join("zig")).whatever("unable to determine toolchain path")
+}
+
+/// Check whether the given program is on the `PATH`.
+fn is_on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).exists()))
+}
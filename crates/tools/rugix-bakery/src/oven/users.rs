@@ -0,0 +1,554 @@
+//! Provisioning of users and groups.
+//!
+//! Instead of running `useradd` and friends, which differ between distributions, the
+//! user and group databases of the root filesystem are edited directly.
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use reportify::{bail, ResultExt};
+use rugix_common::fsutils::resolve_in_root;
+use tracing::info;
+
+use crate::config::recipes::{GroupConfig, UserConfig, UsersConfig};
+use crate::oven::files::{copy_tree, set_owner};
+use crate::BakeryResult;
+
+/// Range of ids of system users and groups.
+const SYSTEM_IDS: (u32, u32) = (100, 999);
+
+/// Range of ids of regular users and groups.
+const REGULAR_IDS: (u32, u32) = (1000, 59999);
+
+/// Permissions of created home directories.
+const HOME_MODE: u32 = 0o700;
+
+/// Permissions of sudo policies.
+const SUDOERS_MODE: u32 = 0o440;
+
+/// Maximal length of user and group names.
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Check that the given user or group name is valid.
+///
+/// Like `useradd`, names must match `[a-z_][a-z0-9_-]*[$]?` and must not be longer than
+/// 32 characters.
+fn validate_name(kind: &str, name: &str) -> BakeryResult<()> {
+    let base = name.strip_suffix('$').unwrap_or(name);
+    let mut chars = base.chars();
+    let is_valid = name.len() <= MAX_NAME_LENGTH
+        && chars
+            .next()
+            .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !is_valid {
+        bail!("invalid {kind} name {name:?}");
+    }
+    Ok(())
+}
+
+/// Check that the given value can be stored in a field of a database.
+fn validate_field(field: &str, value: &str) -> BakeryResult<()> {
+    if value.contains([':', '\n']) {
+        bail!("{field} {value:?} must not contain `:` or newlines");
+    }
+    Ok(())
+}
+
+/// Database of the system with colon-separated entries, e.g., `/etc/passwd`.
+struct Database {
+    path: PathBuf,
+    entries: Vec<Vec<String>>,
+}
+
+impl Database {
+    /// Load the database with the given path relative to the root directory.
+    ///
+    /// Returns `None`, if the database does not exist.
+    fn load(root_dir: &Path, database: &str) -> BakeryResult<Option<Self>> {
+        let path = root_dir.join(database);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let entries = fs::read_to_string(&path)
+            .whatever_with(|_| format!("unable to read `/{database}`"))?
+            .lines()
+            .map(|line| line.split(':').map(str::to_owned).collect())
+            .collect();
+        Ok(Some(Self { path, entries }))
+    }
+
+    /// Load the database and fail, if it does not exist.
+    fn load_required(root_dir: &Path, database: &str) -> BakeryResult<Self> {
+        match Self::load(root_dir, database)? {
+            Some(database) => Ok(database),
+            None => bail!("`/{database}` does not exist in the system"),
+        }
+    }
+
+    /// Find the entry with the given name.
+    fn find(&self, name: &str) -> Option<&Vec<String>> {
+        self.entries.iter().find(|entry| entry[0] == name)
+    }
+
+    /// Find the entry with the given name for modification.
+    fn find_mut(&mut self, name: &str) -> Option<&mut Vec<String>> {
+        self.entries.iter_mut().find(|entry| entry[0] == name)
+    }
+
+    /// Numeric id of the entry with the given name.
+    fn id(&self, name: &str) -> Option<u32> {
+        self.find(name)
+            .and_then(|entry| entry.get(2))
+            .and_then(|id| id.parse().ok())
+    }
+
+    /// Numeric ids in use.
+    fn ids(&self) -> HashSet<u32> {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.get(2).and_then(|id| id.parse().ok()))
+            .collect()
+    }
+
+    /// Add a member to the group with the given name in the given field.
+    fn add_member(&mut self, group: &str, field: usize, member: &str) {
+        if let Some(entry) = self.find_mut(group) {
+            if entry.len() <= field {
+                entry.resize(field + 1, String::new());
+            }
+            let members = &mut entry[field];
+            if !members.split(',').any(|existing| existing == member) {
+                if !members.is_empty() {
+                    members.push(',');
+                }
+                members.push_str(member);
+            }
+        }
+    }
+
+    /// Write the database back to the root filesystem.
+    fn save(&self) -> BakeryResult<()> {
+        let mut content = String::new();
+        for entry in &self.entries {
+            content.push_str(&entry.join(":"));
+            content.push('\n');
+        }
+        // Writing to the existing file preserves its ownership and permissions.
+        fs::write(&self.path, content).whatever_with(|_| format!("unable to write {:?}", self.path))
+    }
+}
+
+/// User and group databases of the root filesystem.
+struct Databases {
+    passwd: Database,
+    shadow: Option<Database>,
+    group: Database,
+    gshadow: Option<Database>,
+}
+
+impl Databases {
+    fn load(root_dir: &Path) -> BakeryResult<Self> {
+        Ok(Self {
+            passwd: Database::load_required(root_dir, "etc/passwd")?,
+            shadow: Database::load(root_dir, "etc/shadow")?,
+            group: Database::load_required(root_dir, "etc/group")?,
+            gshadow: Database::load(root_dir, "etc/gshadow")?,
+        })
+    }
+
+    fn save(&self) -> BakeryResult<()> {
+        self.passwd.save()?;
+        self.group.save()?;
+        if let Some(shadow) = &self.shadow {
+            shadow.save()?;
+        }
+        if let Some(gshadow) = &self.gshadow {
+            gshadow.save()?;
+        }
+        Ok(())
+    }
+
+    /// Add a group with the given id.
+    fn add_group(&mut self, name: &str, gid: u32) {
+        self.group.entries.push(vec![
+            name.to_owned(),
+            "x".to_owned(),
+            gid.to_string(),
+            String::new(),
+        ]);
+        if let Some(gshadow) = &mut self.gshadow {
+            gshadow.entries.push(vec![
+                name.to_owned(),
+                "!".to_owned(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+    }
+
+    /// Add a user to the given supplementary group.
+    fn add_group_member(&mut self, group: &str, user: &str) -> BakeryResult<()> {
+        if self.group.find(group).is_none() {
+            bail!("group {group:?} does not exist");
+        }
+        self.group.add_member(group, 3, user);
+        if let Some(gshadow) = &mut self.gshadow {
+            gshadow.add_member(group, 3, user);
+        }
+        Ok(())
+    }
+}
+
+/// Find the first id in the given range which is not in use by any of the databases.
+fn free_id(system: bool, databases: &[&Database]) -> BakeryResult<u32> {
+    let (first, last) = if system { SYSTEM_IDS } else { REGULAR_IDS };
+    let used = databases
+        .iter()
+        .flat_map(|database| database.ids())
+        .collect::<HashSet<_>>();
+    match (first..=last).find(|id| !used.contains(id)) {
+        Some(id) => Ok(id),
+        None => bail!("no free id in the range {first} to {last}"),
+    }
+}
+
+/// Create the users and groups of a `users` step in the root filesystem.
+pub fn provision_users(
+    config: &UsersConfig,
+    root_dir: &Path,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    let mut databases = Databases::load(root_dir)?;
+    for group in config.groups.as_deref().unwrap_or_default() {
+        provision_group(&mut databases, group)?;
+    }
+    for user in config.users.as_deref().unwrap_or_default() {
+        provision_user(&mut databases, root_dir, user, source_date_epoch)?;
+    }
+    databases.save()
+}
+
+fn provision_group(databases: &mut Databases, config: &GroupConfig) -> BakeryResult<()> {
+    validate_name("group", &config.name)?;
+    if let Some(gid) = databases.group.id(&config.name) {
+        if config.gid.is_some_and(|expected| expected != gid) {
+            bail!("group {:?} already exists with gid {gid}", config.name);
+        }
+        return Ok(());
+    }
+    let gid = match config.gid {
+        Some(gid) => {
+            if databases.group.ids().contains(&gid) {
+                bail!("gid {gid} of group {:?} is already in use", config.name);
+            }
+            gid
+        }
+        None => free_id(config.system.unwrap_or(false), &[&databases.group])?,
+    };
+    info!("creating group {:?} with gid {gid}", config.name);
+    databases.add_group(&config.name, gid);
+    Ok(())
+}
+
+fn provision_user(
+    databases: &mut Databases,
+    root_dir: &Path,
+    config: &UserConfig,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    let name = &config.name;
+    validate_name("user", name)?;
+    for group in config.group.iter().chain(config.groups.iter().flatten()) {
+        validate_name("group", group)?;
+    }
+    for (field, value) in [
+        ("comment", &config.comment),
+        ("home directory", &config.home),
+        ("shell", &config.shell),
+        ("password hash", &config.password_hash),
+    ] {
+        if let Some(value) = value {
+            validate_field(field, value)?;
+        }
+    }
+    let system = config.system.unwrap_or(false);
+    let (uid, gid, home) = match databases.passwd.find(name) {
+        Some(entry) => {
+            if entry.len() < 7 {
+                bail!("invalid entry of user {name:?} in `/etc/passwd`");
+            }
+            let uid = entry[2].parse().whatever("invalid uid in `/etc/passwd`")?;
+            if config.uid.is_some_and(|expected| expected != uid) {
+                bail!("user {name:?} already exists with uid {uid}");
+            }
+            let gid = entry[3].parse().whatever("invalid gid in `/etc/passwd`")?;
+            (uid, gid, entry[5].clone())
+        }
+        None => {
+            let uid = match config.uid {
+                Some(uid) => {
+                    if databases.passwd.ids().contains(&uid) {
+                        bail!("uid {uid} of user {name:?} is already in use");
+                    }
+                    uid
+                }
+                // Ids which are free for users and groups are preferred, such that
+                // the group of the user can get the same id.
+                None if config.group.is_none() => {
+                    free_id(system, &[&databases.passwd, &databases.group])?
+                }
+                None => free_id(system, &[&databases.passwd])?,
+            };
+            let group = config.group.as_deref().unwrap_or(name);
+            let gid = match databases.group.id(group) {
+                Some(gid) => gid,
+                None if config.group.is_some() => bail!("group {group:?} does not exist"),
+                None => {
+                    let gid = if databases.group.ids().contains(&uid) {
+                        free_id(system, &[&databases.group])?
+                    } else {
+                        uid
+                    };
+                    info!("creating group {group:?} with gid {gid}");
+                    databases.add_group(group, gid);
+                    gid
+                }
+            };
+            let home = config
+                .home
+                .clone()
+                .unwrap_or_else(|| format!("/home/{name}"));
+            let shell = match &config.shell {
+                Some(shell) => shell.clone(),
+                None => default_shell(root_dir, system),
+            };
+            info!("creating user {name:?} with uid {uid}");
+            databases.passwd.entries.push(vec![
+                name.clone(),
+                "x".to_owned(),
+                uid.to_string(),
+                gid.to_string(),
+                config.comment.clone().unwrap_or_default(),
+                home.clone(),
+                shell,
+            ]);
+            if let Some(shadow) = &mut databases.shadow {
+                // Without a password hash, password logins are impossible. In contrast
+                // to `!`, `*` does not lock the account for logins with SSH keys.
+                let last_change = source_date_epoch / (24 * 60 * 60);
+                shadow.entries.push(vec![
+                    name.clone(),
+                    config
+                        .password_hash
+                        .clone()
+                        .unwrap_or_else(|| "*".to_owned()),
+                    last_change.to_string(),
+                    "0".to_owned(),
+                    "99999".to_owned(),
+                    "7".to_owned(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ]);
+            } else if config.password_hash.is_some() {
+                bail!("unable to set password of {name:?}, `/etc/shadow` does not exist");
+            }
+            (uid, gid, home)
+        }
+    };
+    for group in config.groups.as_deref().unwrap_or_default() {
+        databases.add_group_member(group, name)?;
+    }
+    // Symbolic links must not lead outside of the root filesystem.
+    let home_dir = resolve_in_root(root_dir, Path::new(&home))
+        .whatever_with(|_| format!("unable to resolve home directory {home:?}"))?;
+    if config.create_home.unwrap_or(!system) && !home_dir.exists() {
+        fs::create_dir_all(&home_dir).whatever("unable to create home directory")?;
+        let skel_dir = root_dir.join("etc/skel");
+        if skel_dir.is_dir() {
+            copy_tree(&skel_dir, &home_dir, Some(uid), Some(gid))?;
+        }
+        fs::set_permissions(&home_dir, fs::Permissions::from_mode(HOME_MODE))
+            .whatever("unable to set permissions of home directory")?;
+        set_owner(&home_dir, Some(uid), Some(gid))?;
+    }
+    if let Some(keys) = &config.authorized_keys {
+        if !home_dir.is_dir() {
+            bail!("unable to authorize SSH keys, home directory of {name:?} does not exist");
+        }
+        let ssh_dir = home_dir.join(".ssh");
+        if !ssh_dir.exists() {
+            fs::create_dir(&ssh_dir).whatever("unable to create `.ssh` directory")?;
+            fs::set_permissions(&ssh_dir, fs::Permissions::from_mode(0o700))
+                .whatever("unable to set permissions of `.ssh` directory")?;
+            set_owner(&ssh_dir, Some(uid), Some(gid))?;
+        }
+        let authorized_keys = ssh_dir.join("authorized_keys");
+        // Keys which have already been authorized, e.g., by a previous layer, are kept.
+        let mut content = if authorized_keys.exists() {
+            fs::read_to_string(&authorized_keys).whatever("unable to read authorized keys")?
+        } else {
+            String::new()
+        };
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for key in keys {
+            let key = key.trim();
+            if !content.lines().any(|line| line.trim() == key) {
+                content.push_str(key);
+                content.push('\n');
+            }
+        }
+        fs::write(&authorized_keys, content).whatever("unable to write authorized keys")?;
+        fs::set_permissions(&authorized_keys, fs::Permissions::from_mode(0o600))
+            .whatever("unable to set permissions of authorized keys")?;
+        set_owner(&authorized_keys, Some(uid), Some(gid))?;
+    }
+    if let Some(rule) = &config.sudo {
+        let sudoers_dir = root_dir.join("etc/sudoers.d");
+        fs::create_dir_all(&sudoers_dir).whatever("unable to create `/etc/sudoers.d`")?;
+        // Sudo ignores files in `/etc/sudoers.d` whose names contain a `.`.
+        let policy = sudoers_dir.join(name.replace('.', "_"));
+        fs::write(&policy, format!("{name} {rule}\n")).whatever("unable to write sudo policy")?;
+        fs::set_permissions(&policy, fs::Permissions::from_mode(SUDOERS_MODE))
+            .whatever("unable to set permissions of sudo policy")?;
+        // An invalid policy would break `sudo` for all users of the system.
+        let check = Command::new("visudo")
+            .args(["--check", "--quiet", "--file"])
+            .arg(&policy)
+            .stdin(Stdio::null())
+            .output()
+            .whatever("unable to run `visudo`")?;
+        if !check.status.success() {
+            fs::remove_file(&policy).ok();
+            bail!(
+                "invalid sudo rule {rule:?} for user {name:?}: {}",
+                String::from_utf8_lossy(&check.stderr).trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Default login shell of users.
+fn default_shell(root_dir: &Path, system: bool) -> String {
+    let candidates: &[&str] = if system {
+        &["/usr/sbin/nologin", "/sbin/nologin", "/bin/false"]
+    } else {
+        &["/bin/bash", "/bin/sh"]
+    };
+    candidates
+        .iter()
+        .find(|shell| root_dir.join(shell.trim_start_matches('/')).exists())
+        .unwrap_or(candidates.last().unwrap())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWD: &str = "root:x:0:0:root:/root:/bin/bash\n\
+        daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin\n\
+        messagebus:x:100:101::/nonexistent:/usr/sbin/nologin\n\
+        alice:x:1000:1000:Alice:/home/alice:/bin/bash\n";
+
+    const GROUP: &str = "root:x:0:\n\
+        daemon:x:1:\n\
+        messagebus:x:101:\n\
+        alice:x:1000:\n\
+        sudo:x:27:\n";
+
+    fn root_dir() -> tempfile::TempDir {
+        let root_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(root_dir.path().join("etc")).unwrap();
+        fs::write(root_dir.path().join("etc/passwd"), PASSWD).unwrap();
+        fs::write(root_dir.path().join("etc/group"), GROUP).unwrap();
+        root_dir
+    }
+
+    fn user(name: &str) -> UserConfig {
+        UserConfig::new(name.to_owned()).with_create_home(Some(false))
+    }
+
+    #[test]
+    fn test_database_roundtrip() {
+        let root_dir = root_dir();
+        let mut group = Database::load_required(root_dir.path(), "etc/group").unwrap();
+        assert_eq!(group.id("sudo"), Some(27));
+        assert_eq!(group.id("unknown"), None);
+        group.add_member("sudo", 3, "alice");
+        group.add_member("sudo", 3, "bob");
+        group.add_member("sudo", 3, "alice");
+        group.save().unwrap();
+        let content = fs::read_to_string(root_dir.path().join("etc/group")).unwrap();
+        assert_eq!(content, GROUP.replace("sudo:x:27:", "sudo:x:27:alice,bob"));
+        let passwd = Database::load_required(root_dir.path(), "etc/passwd").unwrap();
+        passwd.save().unwrap();
+        let content = fs::read_to_string(root_dir.path().join("etc/passwd")).unwrap();
+        assert_eq!(content, PASSWD);
+        assert!(Database::load(root_dir.path(), "etc/shadow")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_free_id() {
+        let root_dir = root_dir();
+        let passwd = Database::load_required(root_dir.path(), "etc/passwd").unwrap();
+        let group = Database::load_required(root_dir.path(), "etc/group").unwrap();
+        assert_eq!(free_id(true, &[&passwd]).unwrap(), 101);
+        assert_eq!(free_id(true, &[&passwd, &group]).unwrap(), 102);
+        assert_eq!(free_id(false, &[&passwd, &group]).unwrap(), 1001);
+    }
+
+    #[test]
+    fn test_provision_user() {
+        let root_dir = root_dir();
+        let mut databases = Databases::load(root_dir.path()).unwrap();
+        let config = user("bob").with_uid(Some(1500));
+        provision_user(&mut databases, root_dir.path(), &config, 0).unwrap();
+        assert_eq!(databases.passwd.id("bob"), Some(1500));
+        assert_eq!(databases.group.id("bob"), Some(1500));
+        // Provisioning an existing user again is fine, if the id matches.
+        provision_user(&mut databases, root_dir.path(), &config, 0).unwrap();
+        assert_eq!(databases.passwd.entries.len(), 5);
+    }
+
+    #[test]
+    fn test_provision_user_pinned_id() {
+        let root_dir = root_dir();
+        let mut databases = Databases::load(root_dir.path()).unwrap();
+        // The existing user has a different id.
+        let config = user("alice").with_uid(Some(1001));
+        assert!(provision_user(&mut databases, root_dir.path(), &config, 0).is_err());
+        // The id is already in use by another user.
+        let config = user("bob").with_uid(Some(1000));
+        assert!(provision_user(&mut databases, root_dir.path(), &config, 0).is_err());
+    }
+
+    #[test]
+    fn test_provision_user_invalid() {
+        let root_dir = root_dir();
+        let mut databases = Databases::load(root_dir.path()).unwrap();
+        for config in [
+            user("Bob"),
+            user("bob:x"),
+            user("1bob"),
+            user(&"b".repeat(33)),
+            user("bob").with_comment(Some("Bob:Builder".to_owned())),
+            user("bob").with_home(Some("/home/bob\n".to_owned())),
+            user("bob").with_shell(Some("/bin/sh:".to_owned())),
+            user("bob").with_groups(Some(vec!["sudo\nroot".to_owned()])),
+        ] {
+            assert!(provision_user(&mut databases, root_dir.path(), &config, 0).is_err());
+        }
+        assert!(databases.passwd.find("bob").is_none());
+        provision_user(&mut databases, root_dir.path(), &user("_bob-2$"), 0).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! Generation of dm-verity hash trees for filesystems.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use rugix_bundle::manifest::PayloadVerity;
+use tempfile::tempdir;
+use tracing::info;
+use xscript::{run, Run};
+
+use crate::utils::caching::Hasher;
+use crate::BakeryResult;
+
+/// Size of data and hash blocks.
+const BLOCK_SIZE: u64 = 4096;
+
+/// Number of SHA256 hashes fitting into a hash block.
+const HASHES_PER_BLOCK: u64 = BLOCK_SIZE / 32;
+
+/// Name of the file with the verity parameters in the system output directory.
+pub const VERITY_INFO_FILE: &str = "verity.json";
+
+/// Verity parameters of the filesystems of a system indexed by their file name.
+pub type VerityInfo = BTreeMap<String, PayloadVerity>;
+
+/// Size of the hash tree, including the superblock, for the given amount of data.
+pub fn hash_tree_size(data_size: u64) -> u64 {
+    let mut blocks = data_size.div_ceil(BLOCK_SIZE);
+    let mut size = BLOCK_SIZE;
+    loop {
+        blocks = blocks.div_ceil(HASHES_PER_BLOCK);
+        size += blocks * BLOCK_SIZE;
+        if blocks <= 1 {
+            break size;
+        }
+    }
+}
+
+/// Maximal size of a filesystem such that it fits into a partition with its hash tree.
+pub fn max_data_size(partition_size: u64) -> Option<u64> {
+    partition_size
+        .checked_sub(hash_tree_size(partition_size))
+        .map(|size| size / BLOCK_SIZE * BLOCK_SIZE)
+}
+
+/// Append a dm-verity hash tree to the given filesystem image.
+///
+/// The salt and UUID are derived from the given seed to keep builds reproducible.
+pub fn append_hash_tree(
+    fs_image: &Path,
+    partition_size: u64,
+    seed: &str,
+) -> BakeryResult<PayloadVerity> {
+    info!("generating dm-verity hash tree for {fs_image:?}");
+    let fs_size = fs::metadata(fs_image)
+        .whatever("unable to read filesystem image metadata")?
+        .len();
+    let data_size = fs_size.next_multiple_of(BLOCK_SIZE);
+    if data_size + hash_tree_size(data_size) > partition_size {
+        bail!("filesystem and hash tree do not fit into the partition");
+    }
+    File::options()
+        .write(true)
+        .open(fs_image)
+        .and_then(|file| file.set_len(data_size))
+        .whatever("unable to pad filesystem image")?;
+    let mut hasher = Hasher::new();
+    hasher.push("verity-salt", seed);
+    let salt = hasher.finalize();
+    let mut hasher = Hasher::new();
+    hasher.push("verity-uuid", seed);
+    let uuid = hasher.finalize();
+    let uuid = format!(
+        "{}-{}-{}-{}-{}",
+        &uuid[0..8],
+        &uuid[8..12],
+        &uuid[12..16],
+        &uuid[16..20],
+        &uuid[20..32]
+    );
+    let temp_dir = tempdir().whatever("unable to create temporary directory")?;
+    let root_hash_file = temp_dir.path().join("root-hash");
+    let data_blocks = data_size / BLOCK_SIZE;
+    run!([
+        "veritysetup",
+        "format",
+        "--hash",
+        "sha256",
+        "--data-block-size",
+        BLOCK_SIZE.to_string(),
+        "--hash-block-size",
+        BLOCK_SIZE.to_string(),
+        "--data-blocks",
+        data_blocks.to_string(),
+        "--hash-offset",
+        data_size.to_string(),
+        "--salt",
+        &salt,
+        "--uuid",
+        &uuid,
+        "--root-hash-file",
+        &root_hash_file,
+        fs_image,
+        fs_image
+    ])
+    .whatever("unable to generate dm-verity hash tree")?;
+    let root_hash = fs::read_to_string(&root_hash_file)
+        .whatever("unable to read dm-verity root hash")?
+        .trim()
+        .to_owned();
+    Ok(PayloadVerity::new(
+        root_hash,
+        salt,
+        "sha256".to_owned(),
+        BLOCK_SIZE as u32,
+        BLOCK_SIZE as u32,
+        data_blocks,
+        data_size,
+    ))
+}
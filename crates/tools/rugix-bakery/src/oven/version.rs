@@ -0,0 +1,103 @@
+//! Derivation of release versions from the project configuration and Git.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+use reportify::{bail, ResultExt};
+use tracing::{info, warn};
+use xscript::{cmd_os, read_str, ParentEnv, Run};
+
+use crate::oven::system::ReleaseInfo;
+use crate::project::ProjectRef;
+use crate::BakeryResult;
+
+/// Default arguments of `git describe`.
+const DEFAULT_DESCRIBE_ARGS: &[&str] = &["--tags", "--always", "--dirty"];
+
+/// Resolve the release version and commit of a system.
+///
+/// A version given on the command line takes precedence over the version configured for
+/// the project, which takes precedence over the version derived from Git. If there is no
+/// version, the version is derived from the build time when making the system.
+pub fn resolve_release(
+    project: &ProjectRef,
+    release_info: &ReleaseInfo,
+) -> BakeryResult<ReleaseInfo> {
+    let mut release_info = release_info.clone();
+    let Some(config) = &project.config().release else {
+        return Ok(release_info);
+    };
+    if release_info.system_version.is_none() {
+        release_info.system_version = config.version.clone();
+    }
+    if config.git.unwrap_or(false) {
+        let dir = project.dir();
+        let safe_directory = git_safe_directory(dir);
+        let commit = read_str!(["git", "-c", &safe_directory, "-C", dir, "rev-parse", "HEAD"])
+            .whatever("unable to determine Git commit of the project")?;
+        release_info.commit = Some(commit.trim().to_owned());
+        if release_info.system_version.is_none() {
+            let mut cmd = cmd_os!("git", "-c", &safe_directory, "-C", dir, "describe");
+            match &config.git_describe_args {
+                Some(args) => cmd.extend_args(args),
+                None => cmd.extend_args(DEFAULT_DESCRIBE_ARGS),
+            };
+            let version = ParentEnv
+                .read_str(cmd)
+                .whatever("unable to derive release version with `git describe`")?;
+            let version = version.trim();
+            if !is_valid_version(version) {
+                bail!("invalid release version {version:?} derived from Git");
+            }
+            info!("derived release version {version:?} from Git");
+            release_info.system_version = Some(version.to_owned());
+        }
+    }
+    Ok(release_info)
+}
+
+/// Configuration option marking the given directory as safe for Git.
+///
+/// Git refuses to operate on repositories owned by a different user, which is usually
+/// the case for the project directory mounted into the Bakery container.
+pub(crate) fn git_safe_directory(dir: &Path) -> OsString {
+    let mut option = OsString::from("safe.directory=");
+    option.push(dir);
+    option
+}
+
+/// Check whether the version can be used as is, e.g., in `os-release` and file names.
+fn is_valid_version(version: &str) -> bool {
+    !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+' | '~'))
+}
+
+/// Set the image version in the `os-release` file of the system.
+///
+/// The `os-release` file is usually a symlink to `/usr/lib/os-release`, which is
+/// resolved relative to the root filesystem of the system.
+pub fn write_os_release_version(system_dir: &Path, version: &str) -> BakeryResult<()> {
+    let mut path = system_dir.join("etc/os-release");
+    if let Ok(target) = fs::read_link(&path) {
+        path = if target.is_absolute() {
+            system_dir.join(target.strip_prefix("/").unwrap())
+        } else {
+            system_dir.join("etc").join(target)
+        };
+    }
+    let Ok(os_release) = fs::read_to_string(&path) else {
+        warn!("system does not have an `os-release` file, not setting image version");
+        return Ok(());
+    };
+    info!("setting `IMAGE_VERSION` in `os-release` to {version:?}");
+    let mut os_release = os_release
+        .lines()
+        .filter(|line| !line.starts_with("IMAGE_VERSION="))
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    os_release.push_str(&format!("IMAGE_VERSION=\"{version}\"\n"));
+    fs::write(&path, os_release).whatever("unable to write `os-release`")
+}
@@ -6,7 +6,7 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use reportify::{whatever, ResultExt};
+use reportify::{bail, whatever, ResultExt};
 
 use crate::config::load_config;
 use crate::config::systems::Architecture;
@@ -25,11 +25,16 @@ pub struct Library {
     pub layers: IdxVec<LayerIdx, Layer>,
     pub recipe_tables: IdxVec<RepositoryIdx, HashMap<String, RecipeIdx>>,
     pub layer_tables: IdxVec<RepositoryIdx, HashMap<String, LayerIdx>>,
+    /// Overrides of recipe parameters by recipe and parameter name.
+    pub parameter_overrides: HashMap<RecipeIdx, HashMap<String, String>>,
 }
 
 impl Library {
     #[allow(clippy::assigning_clones)]
-    pub fn load(repositories: Arc<ProjectRepositories>) -> BakeryResult<Self> {
+    pub fn load(
+        repositories: Arc<ProjectRepositories>,
+        parameter_overrides: &HashMap<String, HashMap<String, String>>,
+    ) -> BakeryResult<Self> {
         let mut recipes = IdxVec::new();
         let mut tables = IdxVec::<RepositoryIdx, _>::new();
         for (idx, repository) in repositories.iter() {
@@ -98,13 +103,27 @@ impl Library {
             }
             layer_tables.push(table);
         }
-        Ok(Self {
+        let mut library = Self {
             repositories,
             recipes,
             recipe_tables: tables,
             layers,
             layer_tables,
-        })
+            parameter_overrides: HashMap::new(),
+        };
+        for (name, parameters) in parameter_overrides {
+            let recipe_idx = library.try_lookup(library.repositories.root_repository, name)?;
+            let definitions = library.recipes[recipe_idx].config.parameters.as_ref();
+            for parameter in parameters.keys() {
+                if !definitions.is_some_and(|definitions| definitions.contains_key(parameter)) {
+                    bail!("unknown parameter `{parameter}` of recipe `{name}`");
+                }
+            }
+            library
+                .parameter_overrides
+                .insert(recipe_idx, parameters.clone());
+        }
+        Ok(library)
     }
 
     pub fn lookup(&self, repository: RepositoryIdx, name: &str) -> Option<RecipeIdx> {
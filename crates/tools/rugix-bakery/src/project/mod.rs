@@ -1,21 +1,24 @@
 //! In-memory project representation.
 
+use std::collections::HashMap;
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use library::Library;
 use reportify::ResultExt;
-use repositories::ProjectRepositories;
+use repositories::{LockMode, ProjectRepositories};
 use si_crypto_hashes::HashAlgorithm;
 
-use crate::config::load_config;
+use crate::config::load_project_config;
+use crate::config::overrides::Overrides;
 use crate::config::projects::ProjectConfig;
 use crate::BakeryResult;
 
 pub mod layers;
 pub mod library;
 pub mod recipes;
+pub mod registry;
 pub mod repositories;
 
 /// Shared reference to an in-memory project.
@@ -41,6 +44,11 @@ impl ProjectRef {
         &self.shared.config
     }
 
+    /// How to treat the lock file when loading the repositories.
+    pub fn lock_mode(&self) -> LockMode {
+        self.shared.lock_mode
+    }
+
     /// Retrieve the repositories of the project.
     ///
     /// This may load the repositories lazily.
@@ -62,7 +70,10 @@ impl ProjectRef {
         if let Some(library) = &*library {
             Ok(library.clone())
         } else {
-            *library = Some(Library::load(self.repositories()?).map(Arc::new)?);
+            *library = Some(
+                Library::load(self.repositories()?, &self.shared.parameter_overrides)
+                    .map(Arc::new)?,
+            );
             Ok(library.clone().unwrap())
         }
     }
@@ -77,6 +88,10 @@ struct ProjectShared {
     id: LocalProjectId,
     /// Project configuration.
     config: Arc<ProjectConfig>,
+    /// How to treat the lock file when loading the repositories.
+    lock_mode: LockMode,
+    /// Overrides of recipe parameters by recipe and parameter name.
+    parameter_overrides: HashMap<String, HashMap<String, String>>,
     /// Lazily-loaded project data.
     lazy: ProjectLazy,
 }
@@ -107,6 +122,10 @@ pub struct ProjectLoader {
     config_file: Option<PathBuf>,
     /// Local project id.
     local_id: LocalProjectId,
+    /// How to treat the lock file when loading the repositories.
+    lock_mode: LockMode,
+    /// Overrides of configuration values and recipe parameters.
+    overrides: Overrides,
 }
 
 impl ProjectLoader {
@@ -116,6 +135,8 @@ impl ProjectLoader {
             project_dir: project_dir.to_path_buf(),
             config_file: None,
             local_id: local_id_from_bytes(project_dir.as_os_str().as_bytes()),
+            lock_mode: LockMode::default(),
+            overrides: Overrides::default(),
         }
     }
 
@@ -137,6 +158,18 @@ impl ProjectLoader {
         self
     }
 
+    /// Set how to treat the lock file when loading the repositories.
+    pub fn with_lock_mode(mut self, lock_mode: LockMode) -> Self {
+        self.lock_mode = lock_mode;
+        self
+    }
+
+    /// Set overrides of configuration values and recipe parameters.
+    pub fn with_overrides(mut self, overrides: Overrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
     /// The full path to the configuration file.
     fn config_path(&self) -> PathBuf {
         self.project_dir.join(
@@ -148,12 +181,14 @@ impl ProjectLoader {
 
     /// Load the project.
     pub fn load(self) -> BakeryResult<ProjectRef> {
-        let config = load_config(&self.config_path())?;
+        let config = load_project_config(&self.config_path(), &self.overrides)?;
         Ok(ProjectRef {
             shared: Arc::new(ProjectShared {
                 dir: self.project_dir,
                 config,
+                parameter_overrides: self.overrides.parameters,
                 id: self.local_id,
+                lock_mode: self.lock_mode,
                 lazy: ProjectLazy::default(),
             }),
         })
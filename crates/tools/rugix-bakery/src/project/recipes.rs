@@ -10,7 +10,10 @@ use serde::{Deserialize, Serialize};
 use reportify::{bail, whatever, ResultExt};
 
 use crate::config::load_config;
-use crate::config::recipes::RecipeConfig;
+use crate::config::recipes::{
+    FilesConfig, NetworkConfig, RecipeConfig, RustConfig, TemplatesConfig, ToolchainConfig,
+    UsersConfig,
+};
 use crate::utils::caching::{mtime_recursive, ModificationTime};
 use crate::BakeryResult;
 
@@ -154,6 +157,33 @@ impl RecipeStep {
             }
             "install" => StepKind::Install,
             "run" => StepKind::Run,
+            "overlays" => {
+                let sources = fs::read_to_string(path)
+                    .whatever("unable to read overlays step")?
+                    .split_whitespace()
+                    .map(str::to_owned)
+                    .collect();
+                StepKind::Overlays { sources }
+            }
+            "first-boot" => StepKind::FirstBoot,
+            "toolchain" => StepKind::Toolchain {
+                config: load_config(path)?,
+            },
+            "rust" => StepKind::Rust {
+                config: load_config(path)?,
+            },
+            "templates" => StepKind::Templates {
+                config: load_config(path)?,
+            },
+            "files" => StepKind::Files {
+                config: load_config(path)?,
+            },
+            "users" => StepKind::Users {
+                config: load_config(path)?,
+            },
+            "network" => StepKind::Network {
+                config: load_config(path)?,
+            },
             _ => bail!("unknown step kind `{kind}`"),
         };
         Ok(Self {
@@ -176,6 +206,22 @@ pub enum StepKind {
     Install,
     /// Run a script on the host machine.
     Run,
+    /// Compile the given device tree sources into the boot partition.
+    Overlays { sources: Vec<String> },
+    /// Install a script to run once on the first boot of the system.
+    FirstBoot,
+    /// Provision a cross-compilation toolchain for subsequent `run` steps.
+    Toolchain { config: ToolchainConfig },
+    /// Cross-compile a Rust crate and install its binaries.
+    Rust { config: RustConfig },
+    /// Render template files into the root filesystem.
+    Templates { config: TemplatesConfig },
+    /// Copy files into the root filesystem with the given ownership and permissions.
+    Files { config: FilesConfig },
+    /// Create users and groups in the root filesystem.
+    Users { config: UsersConfig },
+    /// Generate the network configuration of the system.
+    Network { config: NetworkConfig },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -0,0 +1,167 @@
+//! Registry indices listing versioned repositories.
+//!
+//! A registry index is a static JSON file served over HTTPS. It lists repositories by
+//! their name together with the Git URL and the versions of each repository.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use reportify::ResultExt;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use tracing::info;
+
+use crate::config::repositories::GitSourceConfig;
+use crate::utils::build_env;
+use crate::BakeryResult;
+
+/// Registry index.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryIndex {
+    /// Repositories listed in the registry by their name.
+    #[serde(default)]
+    pub repositories: HashMap<String, RegistryRepository>,
+}
+
+impl RegistryIndex {
+    /// Fetch the index from the given URL.
+    ///
+    /// Fetched indices are cached in the project such that they are available offline.
+    pub fn fetch(url: &str) -> BakeryResult<Self> {
+        let cache_path = index_cache_path(url);
+        let index = if build_env::is_offline() && cache_path.exists() {
+            fs::read_to_string(&cache_path).whatever("unable to read cached registry index")?
+        } else {
+            build_env::ensure_online(&format!("fetching registry index from `{url}`"))?;
+            info!("fetching registry index from `{url}`");
+            let index = reqwest::blocking::get(url)
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.text())
+                .whatever_with(|_| format!("unable to fetch registry index from `{url}`"))?;
+            if let Some(parent) = cache_path.parent() {
+                fs::create_dir_all(parent).ok();
+            }
+            fs::write(&cache_path, &index).ok();
+            index
+        };
+        serde_json::from_str(&index)
+            .whatever_with(|_| format!("unable to parse registry index from `{url}`"))
+    }
+}
+
+/// Path where the index from the given URL is cached.
+fn index_cache_path(url: &str) -> PathBuf {
+    let mut hasher = Sha1::new();
+    hasher.update(url.as_bytes());
+    PathBuf::from(".rugix/registries").join(format!("{}.json", hex::encode(hasher.finalize())))
+}
+
+/// Repository listed in a registry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryRepository {
+    /// Description of the repository.
+    pub description: Option<String>,
+    /// URL of the Git repository.
+    pub git: String,
+    /// Subdirectory in which the repository is located.
+    pub dir: Option<String>,
+    /// Published versions of the repository.
+    #[serde(default)]
+    pub versions: Vec<RegistryVersion>,
+}
+
+impl RegistryRepository {
+    /// Versions satisfying the given requirement, the latest version first.
+    ///
+    /// Versions which are not valid semantic versions are ignored.
+    pub fn matching_versions(&self, req: &VersionReq) -> Vec<(Version, &RegistryVersion)> {
+        let mut versions = self
+            .versions
+            .iter()
+            .filter_map(|entry| Some((parse_version(&entry.version)?, entry)))
+            .filter(|(version, _)| req.matches(version))
+            .collect::<Vec<_>>();
+        versions.sort_by(|(a, _), (b, _)| b.cmp(a));
+        versions
+    }
+
+    /// Latest version of the repository.
+    pub fn latest_version(&self) -> Option<Version> {
+        self.versions
+            .iter()
+            .filter_map(|entry| parse_version(&entry.version))
+            .max()
+    }
+
+    /// Git source of the given version.
+    pub fn source_config(&self, version: &RegistryVersion) -> GitSourceConfig {
+        GitSourceConfig::new(self.git.clone())
+            .with_tag(version.tag.clone())
+            .with_rev(version.rev.clone())
+            .with_dir(self.dir.clone())
+    }
+}
+
+/// Published version of a repository.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegistryVersion {
+    /// Semantic version.
+    pub version: String,
+    /// Git tag of the version.
+    pub tag: Option<String>,
+    /// Git revision of the version.
+    pub rev: Option<String>,
+}
+
+/// Parse a version requirement, e.g., `^0.8`, `~1.2.3`, or `>=1.0, <2.0`.
+///
+/// The semantics follow Cargo's version requirements. Without an operator, a caret
+/// requirement is assumed.
+pub fn parse_version_req(req: &str) -> BakeryResult<VersionReq> {
+    VersionReq::parse(req).whatever_with(|_| format!("invalid version requirement `{req}`"))
+}
+
+/// Parse a published version, which may be prefixed with `v`.
+fn parse_version(version: &str) -> Option<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(versions: &[&str]) -> RegistryRepository {
+        RegistryRepository {
+            description: None,
+            git: "https://example.com/repository.git".to_owned(),
+            dir: None,
+            versions: versions
+                .iter()
+                .map(|version| RegistryVersion {
+                    version: version.to_string(),
+                    tag: Some(version.to_string()),
+                    rev: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_matching_versions() {
+        let repository = repository(&["v0.8.0", "0.8.3", "0.9.0", "1.0.0", "invalid"]);
+        let matching = |req: &str| {
+            repository
+                .matching_versions(&parse_version_req(req).unwrap())
+                .into_iter()
+                .map(|(_, entry)| entry.version.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matching("0.8"), ["0.8.3", "v0.8.0"]);
+        assert_eq!(matching(">=0.9, <2.0"), ["1.0.0", "0.9.0"]);
+        assert_eq!(matching("*").len(), 4);
+        assert_eq!(repository.latest_version(), Some(Version::new(1, 0, 0)));
+        assert!(parse_version_req("not a version").is_err());
+    }
+}
@@ -1,21 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
-use tracing::debug;
+use tracing::{debug, info};
 
-use xscript::{read_str, run, LocalEnv, Run};
+use xscript::{cmd_os, read_str, run, Cmd, LocalEnv, ParentEnv, Run};
 
 use reportify::{bail, ResultExt};
 
 use crate::config::repositories::{
-    GitSourceConfig, PathSourceConfig, RepositoryConfig, SourceConfig,
+    GitSourceConfig, PathSourceConfig, RegistrySourceConfig, RepositoryConfig, SourceConfig,
 };
+use crate::utils::build_env;
 use crate::utils::idx_vec::{new_idx_type, IdxVec};
 use crate::BakeryResult;
 
+use super::registry::{parse_version_req, RegistryIndex};
 use super::ProjectRef;
 
 #[derive(Debug)]
@@ -28,7 +32,23 @@ pub struct ProjectRepositories {
 
 impl ProjectRepositories {
     pub fn load(project: &ProjectRef) -> BakeryResult<Self> {
-        let mut repositories = RepositoriesLoader::new(project.dir());
+        let lock_path = project.dir().join(LOCK_FILE);
+        let lock = if lock_path.exists() && project.lock_mode() != LockMode::Update {
+            toml::from_str(
+                &std::fs::read_to_string(&lock_path).whatever("unable to read lock file")?,
+            )
+            .whatever("unable to parse lock file")?
+        } else if project.lock_mode() == LockMode::Locked {
+            bail!("lock file `{LOCK_FILE}` does not exist");
+        } else {
+            LockFile::default()
+        };
+        let mut repositories = RepositoriesLoader::new(
+            project.dir(),
+            project.lock_mode(),
+            lock,
+            project.config().registries.clone().unwrap_or_default(),
+        );
         let core = repositories.load_source(
             SourceConfig::Path(PathSourceConfig {
                 path: "/usr/share/rugix/repositories/core".into(),
@@ -39,6 +59,21 @@ impl ProjectRepositories {
             project.config().repositories.clone().unwrap_or_default(),
             true,
         )?;
+        if repositories.resolved != repositories.lock {
+            if project.lock_mode() == LockMode::Locked {
+                bail!("lock file `{LOCK_FILE}` needs to be updated but `--locked` was given");
+            }
+            info!("writing lock file `{LOCK_FILE}`");
+            let lock = toml::to_string_pretty(&repositories.resolved)
+                .whatever("unable to serialize lock file")?;
+            std::fs::write(
+                &lock_path,
+                format!(
+                    "# This file is generated by Rugix Bakery. Do not edit it manually.\n\n{lock}"
+                ),
+            )
+            .whatever("unable to write lock file")?;
+        }
         Ok(Self {
             repositories: repositories.repositories.map(|_, repo| repo.unwrap()),
             root_repository: root,
@@ -69,16 +104,118 @@ struct RepositoriesLoader {
     source_to_repository: HashMap<SourceId, RepositoryIdx>,
     /// Path to the project's root directory.
     root_dir: PathBuf,
+    /// How to treat the lock file.
+    lock_mode: LockMode,
+    /// Commits pinned by the existing lock file.
+    lock: LockFile,
+    /// Commits the repositories have been resolved to.
+    resolved: LockFile,
+    /// URLs of the registry indices by the name of the registry.
+    registries: HashMap<String, String>,
+    /// Registry indices which have already been fetched.
+    indices: HashMap<String, RegistryIndex>,
 }
 
 impl RepositoriesLoader {
     /// Create an empty collection of repositories.
-    pub fn new(root_dir: impl AsRef<Path>) -> Self {
+    pub fn new(
+        root_dir: impl AsRef<Path>,
+        lock_mode: LockMode,
+        lock: LockFile,
+        registries: HashMap<String, String>,
+    ) -> Self {
         let root_dir = root_dir.as_ref();
         Self {
             repositories: IdxVec::new(),
             source_to_repository: HashMap::new(),
             root_dir: root_dir.to_path_buf(),
+            lock_mode,
+            lock,
+            resolved: LockFile::default(),
+            registries,
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Resolve a registry source to the Git source of a matching version.
+    ///
+    /// If the lock file pins a version which still satisfies the requirement, this
+    /// version is used. Otherwise, the latest matching version is used.
+    fn resolve_registry_source(
+        &mut self,
+        config: &RegistrySourceConfig,
+    ) -> BakeryResult<GitSourceConfig> {
+        let registry = match &config.registry {
+            Some(registry) => registry.clone(),
+            None if self.registries.len() == 1 => self.registries.keys().next().unwrap().clone(),
+            None => bail!(
+                "registry of repository `{}` must be specified",
+                config.repository
+            ),
+        };
+        let Some(url) = self.registries.get(&registry).cloned() else {
+            bail!("unknown registry `{registry}`");
+        };
+        if !self.indices.contains_key(&registry) {
+            self.indices
+                .insert(registry.clone(), RegistryIndex::fetch(&url)?);
+        }
+        let Some(repository) = self.indices[&registry].repositories.get(&config.repository) else {
+            bail!(
+                "repository `{}` not found in registry `{registry}`",
+                config.repository
+            );
+        };
+        let req = parse_version_req(config.version.as_deref().unwrap_or("*"))?;
+        let versions = repository.matching_versions(&req);
+        if self.lock_mode != LockMode::Update {
+            for (_, version) in &versions {
+                let source = repository.source_config(version);
+                let source_id = compute_source_id(&SourceConfig::Git(source.clone()));
+                if self
+                    .lock
+                    .repositories
+                    .get(source_id.as_str())
+                    .is_some_and(|locked| locked.matches(&source))
+                {
+                    return Ok(source);
+                }
+            }
+        }
+        let Some((version, entry)) = versions.first() else {
+            bail!(
+                "no version of repository `{}` matches `{}`",
+                config.repository,
+                config.version.as_deref().unwrap_or("*")
+            );
+        };
+        info!(
+            "using version {version} of repository `{}` from registry `{registry}`",
+            config.repository
+        );
+        Ok(repository.source_config(entry))
+    }
+
+    /// Commit the given source is pinned to by the lock file.
+    fn locked_commit(&self, id: &SourceId, config: &SourceConfig) -> BakeryResult<Option<String>> {
+        let SourceConfig::Git(config) = config else {
+            return Ok(None);
+        };
+        let locked = self
+            .lock
+            .repositories
+            .get(id.as_str())
+            .filter(|locked| locked.matches(config));
+        match (self.lock_mode, locked) {
+            (LockMode::Update, _) => Ok(None),
+            (_, Some(locked)) => Ok(Some(locked.commit.clone())),
+            (LockMode::Locked, None) => {
+                bail!(
+                    "repository {} is not pinned by the lock file but `--locked` was given",
+                    config.url
+                );
+            }
+            (LockMode::Default, None) => Ok(None),
         }
     }
 
@@ -95,6 +232,7 @@ impl RepositoriesLoader {
                 SourceConfig::Path(PathSourceConfig { path: "".into() }),
                 &self.root_dir,
                 update,
+                None,
             )?,
             RepositoryConfig {
                 name: Some("root".to_owned()),
@@ -113,6 +251,12 @@ impl RepositoriesLoader {
         config: SourceConfig,
         update: bool,
     ) -> BakeryResult<RepositoryIdx> {
+        let config = match config {
+            SourceConfig::Registry(config) => {
+                SourceConfig::Git(self.resolve_registry_source(&config)?)
+            }
+            config => config,
+        };
         let source_id = compute_source_id(&config);
         if let Some(id) = self.source_to_repository.get(&source_id).cloned() {
             let Some(repository) = &self.repositories[id] else {
@@ -128,7 +272,26 @@ impl RepositoriesLoader {
                 );
             }
         } else {
-            let source = Source::materialize(config.clone(), &self.root_dir, update)?;
+            let locked_commit = self.locked_commit(&source_id, &config)?;
+            let source = Source::materialize(
+                config.clone(),
+                &self.root_dir,
+                update,
+                locked_commit.as_deref(),
+            )?;
+            if let (SourceConfig::Git(config), Some(commit)) = (&source.config, &source.commit) {
+                self.resolved.repositories.insert(
+                    source.id.as_str().to_owned(),
+                    LockedRepository {
+                        git: config.url.clone(),
+                        tag: config.tag.clone(),
+                        branch: config.branch.clone(),
+                        rev: config.rev.clone(),
+                        dir: config.dir.clone(),
+                        commit: commit.clone(),
+                    },
+                );
+            }
             let config_path = source.dir.join("rugix-repository.toml");
             let config =
                 toml::from_str(&std::fs::read_to_string(&config_path).whatever_with(|_| {
@@ -199,35 +362,105 @@ pub struct Source {
     pub config: SourceConfig,
     /// The directory where the source has been materialized.
     pub dir: PathBuf,
+    /// The commit which has been checked out (for Git sources).
+    pub commit: Option<String>,
 }
 
 impl Source {
     /// Materialize the source within the given project root directory.
     ///
-    /// The *update* flag indicates whether remote repositories should be updated.
-    pub fn materialize(config: SourceConfig, root_dir: &Path, update: bool) -> BakeryResult<Self> {
+    /// The *update* flag indicates whether remote repositories should be updated. If a
+    /// *locked commit* is given, it is checked out instead of the configured revision.
+    pub fn materialize(
+        config: SourceConfig,
+        root_dir: &Path,
+        update: bool,
+        locked_commit: Option<&str>,
+    ) -> BakeryResult<Self> {
         let id = compute_source_id(&config);
         debug!("materializing source {id}");
-        let path = match &config {
-            SourceConfig::Path(config) => root_dir.join(&config.path),
+        let (path, commit) = match &config {
+            SourceConfig::Path(config) => (root_dir.join(&config.path), None),
             SourceConfig::Git(config) => {
                 let mut path = root_dir.join(".rugix/repositories");
                 path.push(id.as_str());
-                check_out_git_source(config, &path, update)?;
+                let commit = check_out_git_source(config, root_dir, &path, update, locked_commit)?;
                 if let Some(repository_path) = &config.dir {
                     path.push(repository_path);
                 }
-                path
+                (path, Some(commit))
+            }
+            SourceConfig::Registry(config) => {
+                bail!(
+                    "registry source `{}` must be resolved before materializing it",
+                    config.repository
+                );
             }
         };
         Ok(Self {
             id,
             config,
             dir: path,
+            commit,
         })
     }
 }
 
+/// Name of the lock file pinning the repositories of a project.
+pub const LOCK_FILE: &str = "rugix-bakery.lock";
+
+/// How to treat the lock file when loading repositories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockMode {
+    /// Use the pinned commits and pin repositories which are not pinned yet.
+    #[default]
+    Default,
+    /// Require all repositories to be pinned and the lock file to be up to date.
+    Locked,
+    /// Ignore the pinned commits and pin the latest commits.
+    Update,
+}
+
+/// Lock file pinning the repositories of a project to exact commits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct LockFile {
+    /// Pinned repositories by their source id.
+    #[serde(default)]
+    repositories: BTreeMap<String, LockedRepository>,
+}
+
+/// Repository pinned to an exact commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LockedRepository {
+    /// URL of the Git repository.
+    git: String,
+    /// Configured tag of the repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+    /// Configured branch of the repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    /// Configured revision of the repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rev: Option<String>,
+    /// Subdirectory in which the repository is located.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dir: Option<String>,
+    /// Pinned commit.
+    commit: String,
+}
+
+impl LockedRepository {
+    /// Check whether the pinned commit has been resolved from the given configuration.
+    fn matches(&self, config: &GitSourceConfig) -> bool {
+        self.git == config.url
+            && self.tag == config.tag
+            && self.branch == config.branch
+            && self.rev == config.rev
+            && self.dir == config.dir
+    }
+}
+
 /// Globally unique id of a source.
 ///
 /// The id is computed by hashing the path or URL of a source.
@@ -267,39 +500,154 @@ fn compute_source_id(config: &SourceConfig) -> SourceId {
                 hasher.update(inner_path.as_bytes());
             }
         }
+        SourceConfig::Registry(registry_source) => {
+            hasher.update(b"registry");
+            if let Some(registry) = &registry_source.registry {
+                hasher.update(registry.as_bytes());
+            }
+            hasher.update(registry_source.repository.as_bytes());
+        }
     }
     SourceId(hex::encode(&hasher.finalize()[..]).into())
 }
 
-/// Check out the Git repository in the given directory.
+/// Check out the Git repository in the given directory and return the commit.
 ///
-/// The *fetch* flag indicates whether updates should be fetched from the remote.
-fn check_out_git_source(config: &GitSourceConfig, path: &Path, fetch: bool) -> BakeryResult<()> {
+/// The *fetch* flag indicates whether updates should be fetched from the remote. A
+/// *locked commit* is only fetched if it is not available locally.
+fn check_out_git_source(
+    config: &GitSourceConfig,
+    root_dir: &Path,
+    path: &Path,
+    fetch: bool,
+    locked_commit: Option<&str>,
+) -> BakeryResult<String> {
     if !path.exists() {
-        run!(["git", "clone", &config.url, path]).whatever("unable to clone repository")?;
+        build_env::ensure_online(&format!("cloning `{}`", config.url))?;
+        let mut cmd = git_cmd(config, root_dir)?;
+        cmd.add_arg("clone");
+        cmd.add_arg(&config.url);
+        cmd.add_arg(path);
+        ParentEnv.run(cmd).whatever("unable to clone repository")?;
     }
     let env = LocalEnv::new(path);
-    if fetch {
-        run!(env, ["git", "fetch", "--all"]).whatever("unable to fetch updates of repository")?;
-    }
+    let fetch_updates = || -> BakeryResult<()> {
+        build_env::ensure_online(&format!("fetching updates of `{}`", config.url))?;
+        let mut cmd = git_cmd(config, root_dir)?;
+        cmd.extend_args(["fetch", "--all"]);
+        env.run(cmd)
+            .whatever("unable to fetch updates of repository")?;
+        Ok(())
+    };
     macro_rules! rev_parse {
         ($rev:literal) => {
             read_str!(env, ["git", "rev-parse", "--verify", $rev]).whatever("unable to parse rev")
         };
     }
-    let mut commit = rev_parse!("refs/remotes/origin/HEAD^{{commit}}")?;
-    if let Some(tag) = &config.tag {
-        commit = rev_parse!("refs/tags/{tag}^{{commit}}")?;
+    let commit = if let Some(locked) = locked_commit {
+        if run!(env, ["git", "cat-file", "-e", "{locked}^{{commit}}"]).is_err() {
+            fetch_updates()?;
+        }
+        rev_parse!("{locked}^{{commit}}")?
+    } else {
+        if fetch {
+            fetch_updates()?;
+        }
+        let mut commit = rev_parse!("refs/remotes/origin/HEAD^{{commit}}")?;
+        if let Some(tag) = &config.tag {
+            commit = rev_parse!("refs/tags/{tag}^{{commit}}")?;
+        }
+        if let Some(branch) = &config.branch {
+            commit = rev_parse!("refs/remotes/origin/{branch}^{{commit}}")?;
+        }
+        if let Some(rev) = &config.rev {
+            commit = rev_parse!("{rev}^{{commit}}")?;
+        }
+        commit
+    };
+    let head = rev_parse!("HEAD^{{commit}}")?;
+    if head != commit {
+        run!(env, ["git", "checkout", &commit]).whatever("error checking out commit")?;
     }
-    if let Some(branch) = &config.branch {
-        commit = rev_parse!("refs/remotes/origin/{branch}^{{commit}}")?;
+    Ok(commit)
+}
+
+/// Convert a path such that it can be quoted in the SSH command.
+fn ssh_option_path<'p>(path: &'p Path, url: &str) -> BakeryResult<&'p str> {
+    match path.to_str().filter(|path| !path.contains('\'')) {
+        Some(path) => Ok(path),
+        None => bail!("invalid path {path:?} for SSH of repository {url}"),
     }
-    if let Some(rev) = &config.rev {
-        commit = rev_parse!("{rev}^{{commit}}")?;
+}
+
+/// Construct a Git command using the credentials configured for the source.
+fn git_cmd(config: &GitSourceConfig, root_dir: &Path) -> BakeryResult<Cmd<OsString>> {
+    let mut cmd = cmd_os!("git");
+    let mut ssh_command = match &config.ssh_known_hosts {
+        Some(known_hosts) => {
+            let known_hosts = root_dir.join(known_hosts);
+            if !known_hosts.exists() {
+                bail!(
+                    "SSH known hosts file {known_hosts:?} for repository {} does not exist",
+                    config.url
+                );
+            }
+            let known_hosts = ssh_option_path(&known_hosts, &config.url)?;
+            format!("ssh -o StrictHostKeyChecking=yes -o UserKnownHostsFile='{known_hosts}'")
+        }
+        None => {
+            // As there is no interactive prompt, we have to accept unknown host keys.
+            // They are remembered in the project such that changed keys are rejected
+            // by subsequent builds, even in fresh containers.
+            let rugix_dir = root_dir.join(".rugix");
+            std::fs::create_dir_all(&rugix_dir).whatever("unable to create `.rugix` directory")?;
+            let known_hosts = ssh_option_path(&rugix_dir.join("known_hosts"), &config.url)?;
+            format!("ssh -o StrictHostKeyChecking=accept-new -o UserKnownHostsFile='{known_hosts}'")
+        }
+    };
+    if let Some(ssh_key) = &config.ssh_key {
+        let ssh_key = root_dir.join(ssh_key);
+        if !ssh_key.exists() {
+            bail!(
+                "SSH key {ssh_key:?} for repository {} does not exist",
+                config.url
+            );
+        }
+        let ssh_key = ssh_option_path(&ssh_key, &config.url)?;
+        ssh_command.push_str(&format!(" -i '{ssh_key}' -o IdentitiesOnly=yes"));
     }
-    let head = rev_parse!("HEAD^{{commit}}")?;
-    if head != commit {
-        run!(env, ["git", "checkout", commit]).whatever("error checking out commit")?;
+    cmd.extend_args(["-c".to_owned(), format!("core.sshCommand={ssh_command}")]);
+    if let Some(token_env) = &config.token_env {
+        if token_env.is_empty()
+            || !token_env
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("invalid token environment variable `{token_env}`");
+        }
+        if std::env::var_os(token_env).is_none() {
+            bail!(
+                "environment variable `{token_env}` for repository {} is not set",
+                config.url
+            );
+        }
+        let token_user = config.token_user.as_deref().unwrap_or("git");
+        if token_user
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | '$' | '`' | ';'))
+        {
+            bail!("invalid token user `{token_user}`");
+        }
+        // The credential helper reads the token from the environment such that it does
+        // not show up in the command line or any configuration files.
+        cmd.extend_args([
+            "-c".to_owned(),
+            "credential.helper=".to_owned(),
+            "-c".to_owned(),
+            format!(
+                "credential.helper=!f() {{ echo username={token_user}; echo \"password=${token_env}\"; }}; f"
+            ),
+        ]);
     }
-    Ok(())
+    Ok(cmd)
 }
@@ -0,0 +1,122 @@
+//! SSH access to the systems under test.
+//!
+//! Rugix Bakery generates an SSH key pair for testing and authorizes the public key
+//! for the users of the tested systems when baking them. Tests can thus connect to the
+//! systems without baking a fixed public key into them.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use reportify::ResultExt;
+use tracing::info;
+use xscript::{run, Run};
+
+use crate::config::tests::{SshConfig, TestConfig, TestStep};
+use crate::oven::system::TestAccess;
+use crate::tester::update;
+use crate::BakeryResult;
+
+/// Path of the private key generated for testing.
+const PRIVATE_KEY_PATH: &str = ".rugix/ssh/id_ed25519";
+
+/// User to connect as, if no user is configured.
+const DEFAULT_USER: &str = "root";
+
+/// SSH key generated for testing and the users to authorize it for.
+pub struct TestKey {
+    /// Path of the private key.
+    private_key: PathBuf,
+    /// Public key in OpenSSH format.
+    public_key: String,
+    /// Users to authorize the key for by system.
+    users: HashMap<String, BTreeSet<String>>,
+}
+
+impl TestKey {
+    /// Load the test key, generating it if it does not exist yet.
+    pub fn load<'c>(configs: impl IntoIterator<Item = &'c TestConfig>) -> BakeryResult<Self> {
+        let private_key = PathBuf::from(PRIVATE_KEY_PATH);
+        let public_key_path = private_key.with_extension("pub");
+        if !private_key.exists() || !public_key_path.exists() {
+            info!("generating SSH key for testing");
+            fs::create_dir_all(private_key.parent().unwrap())
+                .whatever("unable to create SSH key directory")?;
+            fs::remove_file(&private_key).ok();
+            fs::remove_file(&public_key_path).ok();
+            run!([
+                "ssh-keygen",
+                "-q",
+                "-t",
+                "ed25519",
+                "-N",
+                "",
+                "-C",
+                "rugix-bakery-test",
+                "-f",
+                &private_key
+            ])
+            .whatever("unable to generate SSH key")?;
+        }
+        let public_key =
+            fs::read_to_string(&public_key_path).whatever("unable to read public SSH key")?;
+        let mut users = HashMap::<String, BTreeSet<String>>::new();
+        for config in configs {
+            for system in &config.systems {
+                let user = ssh_user(system.ssh.as_ref());
+                users
+                    .entry(system.system.clone())
+                    .or_default()
+                    .insert(user.to_owned());
+                // Updated systems are accessed with the same user.
+                for step in &config.steps {
+                    if let TestStep::Update(step) = step {
+                        let (update_system, _) = update::update_target(step, &system.system);
+                        users
+                            .entry(update_system.to_owned())
+                            .or_default()
+                            .insert(user.to_owned());
+                    }
+                }
+            }
+            for peer in config.peers.iter().flatten() {
+                users
+                    .entry(peer.system.clone())
+                    .or_default()
+                    .insert(ssh_user(peer.ssh.as_ref()).to_owned());
+            }
+        }
+        Ok(Self {
+            private_key,
+            public_key: public_key.trim().to_owned(),
+            users,
+        })
+    }
+
+    /// Access to inject into the given system when baking it.
+    pub fn access(&self, system: &str) -> TestAccess {
+        TestAccess {
+            public_key: self.public_key.clone(),
+            users: self
+                .users
+                .get(system)
+                .map(|users| users.iter().cloned().collect())
+                .unwrap_or_else(|| vec![DEFAULT_USER.to_owned()]),
+        }
+    }
+
+    /// SSH configuration using the test key, if no private key is configured.
+    pub fn ssh_config(&self, ssh: Option<&SshConfig>) -> SshConfig {
+        let mut ssh = ssh.cloned().unwrap_or_else(SshConfig::new);
+        if ssh.private_key.is_none() {
+            ssh.private_key = Some(self.private_key.to_string_lossy().into_owned());
+        }
+        ssh
+    }
+}
+
+/// User to connect as.
+fn ssh_user(ssh: Option<&SshConfig>) -> &str {
+    ssh.and_then(|ssh| ssh.user.as_deref())
+        .unwrap_or(DEFAULT_USER)
+}
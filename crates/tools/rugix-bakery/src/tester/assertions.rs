@@ -0,0 +1,139 @@
+//! Declarative assertions evaluated as shell scripts in the VM.
+
+use crate::config::tests::{AssertCommandStep, AssertFileStep, AssertPortStep, AssertServiceStep};
+
+/// Assertion to evaluate in the VM.
+pub struct Assertion {
+    /// Description of the assertion.
+    pub description: String,
+    /// Script checking the assertion, exiting with a non-zero code if it does not hold.
+    pub script: String,
+}
+
+impl Assertion {
+    fn new(description: &Option<String>, default: String, checks: &str) -> Self {
+        Self {
+            description: description.clone().unwrap_or(default),
+            script: format!("#!/bin/sh\n{checks}"),
+        }
+    }
+}
+
+/// Quote the given string for use in a shell script.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Assertion of an `assert-file` step.
+pub fn file(step: &AssertFileStep) -> Assertion {
+    let path = quote(&step.path);
+    let mut checks = format!(
+        "if [ ! -e {path} ]; then\n    echo \"file \"{path}\" does not exist\" >&2\n    exit 1\nfi\n"
+    );
+    let default = match &step.contains {
+        Some(text) => {
+            checks.push_str(&format!(
+                "if ! grep -qF -- {} {path}; then\n    echo \"file \"{path}\" does not contain the expected text\" >&2\n    exit 1\nfi\n",
+                quote(text)
+            ));
+            format!("file {:?} contains {text:?}", step.path)
+        }
+        None => format!("file {:?} exists", step.path),
+    };
+    Assertion::new(&step.description, default, &checks)
+}
+
+/// Assertion of an `assert-service` step.
+pub fn service(step: &AssertServiceStep) -> Assertion {
+    let service = quote(&step.service);
+    let checks = format!(
+        "if command -v systemctl >/dev/null 2>&1; then\n    systemctl is-active {service}\nelse\n    rc-service {service} status\nfi\n"
+    );
+    Assertion::new(
+        &step.description,
+        format!("service {:?} is active", step.service),
+        &checks,
+    )
+}
+
+/// Assertion of an `assert-port` step.
+pub fn port(step: &AssertPortStep) -> Assertion {
+    let protocol = step.protocol.as_deref().unwrap_or("tcp");
+    let flag = if protocol == "udp" { "u" } else { "t" };
+    let checks = format!(
+        "if ! {{ ss -ln{flag} 2>/dev/null || netstat -ln{flag}; }} | grep -Eq ':{port}[[:space:]]'; then\n    echo \"no socket listening on {protocol} port {port}\" >&2\n    exit 1\nfi\n",
+        port = step.port
+    );
+    Assertion::new(
+        &step.description,
+        format!("{protocol} port {} is listening", step.port),
+        &checks,
+    )
+}
+
+/// Assertion of an `assert-command` step.
+pub fn command(step: &AssertCommandStep) -> Assertion {
+    let exit_status = step.exit_status.unwrap_or(0);
+    let mut checks = format!(
+        "output=$(sh -c {} 2>&1)\nstatus=$?\nprintf '%s\\n' \"$output\"\nif [ \"$status\" -ne {exit_status} ]; then\n    echo \"expected exit status {exit_status} but got $status\" >&2\n    exit 1\nfi\n",
+        quote(&step.command)
+    );
+    if let Some(pattern) = &step.output_matches {
+        checks.push_str(&format!(
+            "if ! printf '%s\\n' \"$output\" | grep -Eq -- {}; then\n    echo \"output does not match the expected pattern\" >&2\n    exit 1\nfi\n",
+            quote(pattern)
+        ));
+    }
+    Assertion::new(
+        &step.description,
+        format!("command {:?} exits with {exit_status}", step.command),
+        &checks,
+    )
+}
+
+/// Assertion that the system booted into the given release version.
+pub fn booted_version(version: &str) -> Assertion {
+    let checks = format!(
+        "if ! grep -qF -- {} /etc/rugix/system-build-info.json; then\n    echo \"system did not boot into version \"{}\"\" >&2\n    exit 1\nfi\n",
+        quote(&format!("\"version\": \"{version}\"")),
+        quote(version)
+    );
+    Assertion::new(
+        &None,
+        format!("system booted into version {version:?}"),
+        &checks,
+    )
+}
+
+/// Assertion that the active boot group is the default boot group.
+pub fn booted_default_group() -> Assertion {
+    let checks = "info=$(rugix-ctrl system info 2>&1)\n\
+        active=$(printf '%s\\n' \"$info\" | sed -n 's/^Active Boot Group: //p')\n\
+        default=$(printf '%s\\n' \"$info\" | sed -n 's/^Default Boot Group: //p')\n\
+        if [ -z \"$active\" ] || [ \"$active\" != \"$default\" ]; then\n    \
+        echo \"active boot group $active is not the default boot group $default\" >&2\n    \
+        exit 1\nfi\n";
+    Assertion::new(
+        &None,
+        "active boot group is the default boot group".to_owned(),
+        checks,
+    )
+}
+
+/// Assertion that the active boot group is not the default boot group.
+///
+/// This is the case after rebooting into a freshly installed update which has not been
+/// committed yet.
+pub fn booted_spare_group() -> Assertion {
+    let checks = "info=$(rugix-ctrl system info 2>&1)\n\
+        active=$(printf '%s\\n' \"$info\" | sed -n 's/^Active Boot Group: //p')\n\
+        default=$(printf '%s\\n' \"$info\" | sed -n 's/^Default Boot Group: //p')\n\
+        if [ -z \"$active\" ] || [ \"$active\" = \"$default\" ]; then\n    \
+        echo \"system did not boot into the spare boot group\" >&2\n    \
+        exit 1\nfi\n";
+    Assertion::new(
+        &None,
+        "system booted into the spare boot group".to_owned(),
+        checks,
+    )
+}
@@ -0,0 +1,182 @@
+//! Hardware-in-the-loop testing on physical devices.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use reportify::{bail, ErrorExt, ResultExt};
+use tokio::process::Command;
+use tokio::{fs, time};
+use tracing::info;
+
+use crate::config::tests::{DeviceConfig, SystemConfig};
+use crate::tester::{assertions, check_assertion, qemu, update, TestCtx};
+use crate::BakeryResult;
+
+use super::qemu::Vm;
+
+/// Maximum time to wait for the device to reboot into the deployed system.
+const REBOOT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Interval in which to check whether the device has rebooted.
+const REBOOT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// File with a random identifier which changes on every boot.
+const BOOT_ID_FILE: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Flash the image onto the device, or deploy the bundle onto it, and wait for it to be
+/// ready.
+///
+/// The bundle is required if the system is deployed onto the device.
+pub async fn start(
+    image_file: &Path,
+    bundle_file: Option<&Path>,
+    device: &DeviceConfig,
+    config: &SystemConfig,
+    work_dir: &Path,
+    ctx: &TestCtx,
+) -> BakeryResult<Vm> {
+    fs::create_dir_all(work_dir)
+        .await
+        .whatever("unable to create device working directory")?;
+    let deploy = device.deploy.unwrap_or(false);
+    if !deploy {
+        let Some(flash) = &device.flash else {
+            bail!("device {:?} requires a `flash` command", device.host);
+        };
+        info!("flashing image onto device {:?}", device.host);
+        run_device_command("flash", flash, image_file, device, work_dir).await?;
+        if let Some(power_cycle) = &device.power_cycle {
+            info!("power-cycling device {:?}", device.host);
+            run_device_command("power-cycle", power_cycle, image_file, device, work_dir).await?;
+        }
+    }
+    if let Some(ready) = &device.ready {
+        info!("waiting for device {:?} to be ready", device.host);
+        run_device_command("ready", ready, image_file, device, work_dir).await?;
+    }
+    let vm = Vm::new(
+        Vec::new(),
+        config,
+        work_dir,
+        device.host.clone(),
+        device.port.unwrap_or(22),
+    )?;
+    if deploy {
+        let Some(bundle_file) = bundle_file else {
+            bail!("no bundle to deploy onto device {:?}", device.host);
+        };
+        deploy_bundle(&vm, ctx, bundle_file, &config.system).await?;
+    }
+    Ok(vm)
+}
+
+/// Install the bundle with Rugix Ctrl, reboot into the new slot, and commit it.
+///
+/// The bundle is streamed to Rugix Ctrl via SSH, so it does not need to fit into the
+/// device's temporary storage.
+async fn deploy_bundle(
+    vm: &Vm,
+    ctx: &TestCtx,
+    bundle_file: &Path,
+    system: &str,
+) -> BakeryResult<()> {
+    ctx.status
+        .set_description("deploy system onto device".to_owned());
+    vm.wait_for_ssh()
+        .await
+        .whatever("unable to connect to device via SSH")?;
+    let Some(boot_id) = read_boot_id(vm).await else {
+        bail!("unable to read boot id of device {:?}", vm.ssh_host());
+    };
+    info!(
+        "deploying system {system:?} onto device {:?}",
+        vm.ssh_host()
+    );
+    let script = "#!/bin/sh\nset -e\nrugix-ctrl update install --reboot yes -\n";
+    match vm.run_script(ctx, script, Some(bundle_file)).await {
+        // The connection may drop before the script exits because the device reboots.
+        Ok(()) => {}
+        Err(report) if matches!(report.error(), qemu::ExecError::Disconnected) => {}
+        Err(report) => return Err(report.whatever("unable to deploy system")),
+    }
+    info!("waiting for device {:?} to reboot", vm.ssh_host());
+    wait_for_reboot(vm, &boot_id).await?;
+    // If the device fell back to the old system, the active group is the default one.
+    check_assertion(vm, ctx, assertions::booted_spare_group()).await?;
+    check_assertion(
+        vm,
+        ctx,
+        assertions::booted_version(&update::baked_version(system)?),
+    )
+    .await?;
+    info!("committing deployed system");
+    vm.run_script(ctx, "#!/bin/sh\nrugix-ctrl system commit\n", None)
+        .await
+        .whatever("unable to commit deployed system")?;
+    check_assertion(vm, ctx, assertions::booted_default_group()).await
+}
+
+/// Wait until the device has rebooted, i.e., reports a different boot id.
+///
+/// Until the device shuts down, the connection may still be open and report the old
+/// boot id. Unresponsive connections are dropped and re-established.
+async fn wait_for_reboot(vm: &Vm, boot_id: &str) -> BakeryResult<()> {
+    time::timeout(REBOOT_TIMEOUT, async {
+        loop {
+            if vm.wait_for_ssh().await.is_ok() {
+                match read_boot_id(vm).await {
+                    Some(current) if current != boot_id => break,
+                    Some(_) => {}
+                    None => vm.disconnect().await,
+                }
+            }
+            time::sleep(REBOOT_POLL_INTERVAL).await;
+        }
+    })
+    .await
+    .whatever("device did not reboot into the deployed system in time")
+}
+
+/// Read the boot id of the device, if it is reachable.
+async fn read_boot_id(vm: &Vm) -> Option<String> {
+    let data = time::timeout(REBOOT_POLL_INTERVAL, vm.read_file(BOOT_ID_FILE))
+        .await
+        .ok()?
+        .ok()?;
+    Some(String::from_utf8_lossy(&data).trim().to_owned())
+}
+
+/// Run a device command with a shell on the host.
+///
+/// The output of the command is written to a log file in the working directory.
+async fn run_device_command(
+    name: &str,
+    command: &str,
+    image_file: &Path,
+    device: &DeviceConfig,
+    work_dir: &Path,
+) -> BakeryResult<()> {
+    let log_file = std::fs::File::create(work_dir.join(format!("{name}.log")))
+        .whatever("unable to create device command log file")?;
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("RUGIX_IMAGE", image_file)
+        .env("RUGIX_DEVICE_HOST", &device.host)
+        .stdin(Stdio::null())
+        .stdout(
+            log_file
+                .try_clone()
+                .whatever("unable to create device command log file")?,
+        )
+        .stderr(log_file)
+        .status()
+        .await
+        .whatever("unable to run device command")
+        .with_info(|_| format!("command: {name}"))?;
+    if !status.success() {
+        bail!("device command `{name}` failed with {status}");
+    }
+    Ok(())
+}
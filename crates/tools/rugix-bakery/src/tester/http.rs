@@ -0,0 +1,143 @@
+//! Minimal HTTP server serving update bundles to VMs.
+//!
+//! The server supports `GET` and `HEAD` requests as well as byte ranges, such that
+//! `rugix-ctrl` can skip blocks it already has when installing a bundle.
+
+use std::io::SeekFrom;
+use std::path::Path;
+use std::sync::Arc;
+
+use reportify::ResultExt;
+use rugix_common::http::parse_range;
+use tokio::fs::File;
+use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::BakeryResult;
+
+/// Maximal size of the request head.
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// HTTP server serving a single file.
+///
+/// The server is stopped when it is dropped.
+pub struct FileServer {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl FileServer {
+    /// Start serving the given file on a free port of the loopback interface.
+    pub async fn start(path: &Path) -> BakeryResult<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .whatever("unable to bind HTTP server")?;
+        let port = listener
+            .local_addr()
+            .whatever("unable to get address of HTTP server")?
+            .port();
+        let path = Arc::new(path.to_path_buf());
+        let task = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let path = path.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = serve(stream, &path).await {
+                        debug!("unable to serve HTTP request: {error}");
+                    }
+                });
+            }
+        });
+        Ok(Self { port, task })
+    }
+
+    /// Port the server is listening on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl Drop for FileServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Serve a single request on the given connection.
+async fn serve(stream: TcpStream, path: &Path) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut head = Vec::new();
+    loop {
+        let read = stream.read_until(b'\n', &mut head).await?;
+        if read == 0 || head.len() > MAX_HEAD_SIZE {
+            return Ok(());
+        }
+        if head.ends_with(b"\r\n\r\n") || head.ends_with(b"\n\n") {
+            break;
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let range = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("range")
+            .then(|| value.trim().to_owned())
+    });
+    let mut stream = stream.into_inner();
+    if method != "GET" && method != "HEAD" {
+        return respond(&mut stream, "405 Method Not Allowed", &[]).await;
+    }
+    let mut file = match File::open(path).await {
+        Ok(file) => file,
+        Err(_) => return respond(&mut stream, "404 Not Found", &[]).await,
+    };
+    let size = file.metadata().await?.len();
+    let (status, start, end) = match range.as_deref().map(|range| parse_range(range, size)) {
+        None => ("200 OK", 0, size),
+        Some(Some((start, end))) => ("206 Partial Content", start, end),
+        Some(None) => {
+            let content_range = format!("bytes */{size}");
+            return respond(
+                &mut stream,
+                "416 Range Not Satisfiable",
+                &[("Content-Range", content_range.as_str())],
+            )
+            .await;
+        }
+    };
+    let content_length = (end - start).to_string();
+    let content_range = format!("bytes {start}-{}/{size}", end.saturating_sub(1));
+    let mut headers = vec![
+        ("Content-Type", "application/octet-stream"),
+        ("Content-Length", content_length.as_str()),
+    ];
+    if range.is_some() {
+        headers.push(("Content-Range", content_range.as_str()));
+    }
+    respond(&mut stream, status, &headers).await?;
+    if method == "GET" {
+        file.seek(SeekFrom::Start(start)).await?;
+        io::copy(&mut file.take(end - start), &mut stream).await?;
+    }
+    stream.shutdown().await
+}
+
+/// Write the status line and headers of a response.
+async fn respond(stream: &mut TcpStream, status: &str, headers: &[(&str, &str)]) -> io::Result<()> {
+    let mut head = format!("HTTP/1.1 {status}\r\nAccept-Ranges: bytes\r\nConnection: close\r\n");
+    if !headers.iter().any(|(name, _)| *name == "Content-Length") {
+        head.push_str("Content-Length: 0\r\n");
+    }
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await
+}
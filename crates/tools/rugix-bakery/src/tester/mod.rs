@@ -1,142 +1,771 @@
-use std::collections::VecDeque;
-use std::path::Path;
-use std::sync::Mutex;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use rugix_tasks::block_on;
-use tracing::info;
+use tokio::sync::Semaphore;
+use tracing::{error, info};
 
-use reportify::{ErrorExt, ResultExt};
+use reportify::{bail, ErrorExt, ResultExt};
 
 use rugix_cli::style::{Style, Stylize};
 use rugix_cli::widgets::{Heading, ProgressBar, ProgressSpinner, Text, Widget};
 use rugix_cli::{StatusSegment, StatusSegmentRef, VisualHeight};
 
 use crate::config::load_config;
-use crate::config::tests::{RunStep, TestConfig, WaitStep};
+use crate::config::systems::Architecture;
+use crate::config::tests::{
+    PeerConfig, RunHostStep, RunStep, SystemConfig, TestConfig, TestStep, WaitStep,
+};
 use crate::oven::system::ReleaseInfo;
 use crate::project::ProjectRef;
+use crate::tester::report::{Outcome, ScenarioResult, StepResult};
 use crate::{oven, BakeryResult};
 
+pub mod access;
+pub mod assertions;
+pub mod hil;
+pub mod http;
 pub mod qemu;
+pub mod report;
+pub mod update;
 
-pub fn main(project: &ProjectRef, test_path: &Path) -> BakeryResult<()> {
-    let test_config = load_config::<TestConfig>(test_path)?;
-
-    let test_name = test_path
-        .file_stem()
-        .unwrap()
-        .to_string_lossy()
-        .into_owned();
+pub fn main(project: &ProjectRef, workflows: &[PathBuf], jobs: usize) -> BakeryResult<()> {
     let source_date_epoch = jiff::Timestamp::now().as_second() as u64;
-    for system in &test_config.systems {
-        let system_out = Path::new("build").join(&system.system);
-        oven::bake_system(
-            &project,
+    let mut scenarios = Vec::new();
+    let mut baked_systems = HashSet::new();
+    let mut snapshots = HashMap::<String, PathBuf>::new();
+    let mut baked_bundles = HashSet::<(String, String)>::new();
+    let mut deploy_bundles = HashMap::<String, PathBuf>::new();
+    // Tests on the same physical device must not run in parallel.
+    let mut device_locks = HashMap::<String, Arc<tokio::sync::Mutex<()>>>::new();
+    let test_configs = workflows
+        .iter()
+        .map(|test_path| Ok((test_path, Arc::new(load_config::<TestConfig>(test_path)?))))
+        .collect::<BakeryResult<Vec<_>>>()?;
+    let test_key = access::TestKey::load(test_configs.iter().map(|(_, config)| &**config))?;
+    for (test_path, test_config) in test_configs {
+        let test_name = test_path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let mut peers = Vec::<Peer>::new();
+        for peer in test_config.peers.iter().flatten() {
+            if peers.iter().any(|other| other.name == peer.name) {
+                bail!("duplicate peer {:?}", peer.name);
+            }
+            let (architecture, image) = prepare_system(
+                project,
+                &mut baked_systems,
+                &test_key,
+                &peer.system,
+                source_date_epoch,
+            )?;
+            let mut peer = Peer::new(peer, architecture, image);
+            peer.system.ssh = Some(test_key.ssh_config(peer.system.ssh.as_ref()));
+            peers.push(peer);
+        }
+        let peers = Arc::new(peers);
+        for system in &test_config.systems {
+            let system = &SystemConfig {
+                ssh: Some(test_key.ssh_config(system.ssh.as_ref())),
+                ..system.clone()
+            };
+            let (architecture, image) = prepare_system(
+                project,
+                &mut baked_systems,
+                &test_key,
+                &system.system,
+                source_date_epoch,
+            )?;
+            if !peers.is_empty() && system.device.is_some() {
+                bail!("peers are not supported for physical devices");
+            }
+            for step in &test_config.steps {
+                let TestStep::Update(step) = step else {
+                    continue;
+                };
+                if system.device.is_some() {
+                    bail!("update steps are not supported for physical devices");
+                }
+                let (update_system, version) = update::update_target(step, &system.system);
+                // Bundles are baked upfront, like the systems themselves.
+                if baked_bundles.insert((update_system.to_owned(), version.to_owned())) {
+                    update::bake_bundle(
+                        project,
+                        &test_key.access(update_system),
+                        update_system,
+                        version,
+                        source_date_epoch,
+                    )?;
+                }
+            }
+            let bundle = if system
+                .device
+                .as_ref()
+                .is_some_and(|device| device.deploy.unwrap_or(false))
+            {
+                // The bundle is baked once per system and shared by all tests.
+                if !deploy_bundles.contains_key(&system.system) {
+                    let system_out = system_out(&system.system);
+                    let bundle = system_out.join("system.rugixb");
+                    oven::bake_bundle(
+                        project,
+                        &system.system,
+                        &system_out,
+                        &bundle,
+                        &oven::BundleOpts::default(),
+                    )
+                    .whatever("error baking bundle to deploy")?;
+                    let bundle = bundle
+                        .canonicalize()
+                        .whatever("unable to canonicalize bundle path")?;
+                    deploy_bundles.insert(system.system.clone(), bundle);
+                }
+                deploy_bundles.get(&system.system).cloned()
+            } else {
+                None
+            };
+            let snapshot = if system.snapshot.unwrap_or(false) {
+                if system.device.is_some() {
+                    bail!("snapshots are not supported for physical devices");
+                }
+                if !peers.is_empty() {
+                    bail!("snapshots are not supported for tests with peers");
+                }
+                if system
+                    .qemu
+                    .as_ref()
+                    .and_then(|qemu| qemu.tpm)
+                    .unwrap_or(false)
+                {
+                    bail!("snapshots are not supported with an emulated TPM");
+                }
+                // The snapshot is created once per system and shared by all tests.
+                if !snapshots.contains_key(&system.system) {
+                    let snapshot = block_on(create_snapshot(architecture, &image, system))?;
+                    snapshots.insert(system.system.clone(), snapshot);
+                }
+                snapshots.get(&system.system).cloned()
+            } else {
+                None
+            };
+            scenarios.push(Scenario {
+                test_name: test_name.clone(),
+                test_config: test_config.clone(),
+                system: system.clone(),
+                architecture,
+                image,
+                bundle,
+                snapshot,
+                peers: peers.clone(),
+                device_lock: system
+                    .device
+                    .as_ref()
+                    .map(|device| device_locks.entry(device.host.clone()).or_default().clone()),
+            });
+        }
+    }
+
+    let limit = Arc::new(Semaphore::new(jobs.max(1)));
+    block_on(async {
+        let tasks = scenarios
+            .into_iter()
+            .map(|scenario| {
+                let limit = limit.clone();
+                rugix_tasks::spawn(async move {
+                    let _device_guard = match &scenario.device_lock {
+                        Some(lock) => Some(lock.clone().lock_owned().await),
+                        None => None,
+                    };
+                    let _permit = limit
+                        .acquire_owned()
+                        .await
+                        .expect("semaphore is never closed");
+                    run_scenario(scenario).await
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut results = Vec::new();
+        let mut result = Ok(());
+        for task in tasks {
+            let (scenario_result, scenario_outcome) = task.join().await;
+            results.push(scenario_result);
+            if let Err(report) = scenario_outcome {
+                if result.is_ok() {
+                    result = Err(report);
+                } else {
+                    error!("test failed:\n{report:?}");
+                }
+            }
+        }
+        let results_dir = Path::new("build/tests");
+        fs::create_dir_all(results_dir).whatever("unable to create test results directory")?;
+        report::write_json(&results, &results_dir.join("results.json"))?;
+        report::write_junit(&results, &results_dir.join("junit.xml"))?;
+        result
+    })
+}
+
+/// Output directory of the given system baked for testing.
+///
+/// As the systems contain the SSH key for testing, they are kept apart from the
+/// systems baked for production.
+fn system_out(system: &str) -> PathBuf {
+    Path::new("build/tests/systems").join(system)
+}
+
+/// Bake the system, if it has not been baked yet, and return its architecture and image.
+fn prepare_system(
+    project: &ProjectRef,
+    baked_systems: &mut HashSet<String>,
+    test_key: &access::TestKey,
+    system: &str,
+    source_date_epoch: u64,
+) -> BakeryResult<(Architecture, PathBuf)> {
+    let system_out = system_out(system);
+    // Systems are baked upfront as building them while tests run is not safe.
+    if baked_systems.insert(system.to_owned()) {
+        oven::bake_test_system(
+            project,
             &ReleaseInfo::default(),
-            &system.system,
+            Some(&test_key.access(system)),
+            system,
+            None,
             &system_out,
             source_date_epoch,
         )
         .whatever("error baking system")?;
+    }
+    let image_config = project.config().resolve_system_config(system)?;
+    let image = system_out
+        .join("system.img")
+        .canonicalize()
+        .whatever("unable to canonicalize image path")?;
+    Ok((image_config.architecture, image))
+}
+
+/// Boot the system in a VM and save a snapshot once it is reachable via SSH.
+async fn create_snapshot(
+    architecture: Architecture,
+    image: &Path,
+    system: &SystemConfig,
+) -> BakeryResult<PathBuf> {
+    info!("creating snapshot of booted system {:?}", system.system);
+    let work_dir = Path::new(".rugix/snapshots").join(&system.system);
+    if work_dir.exists() {
+        fs::remove_dir_all(&work_dir).whatever("unable to remove old snapshot")?;
+    }
+    let vm = qemu::start(
+        architecture,
+        &image.to_string_lossy(),
+        system,
+        &work_dir,
+        qemu::VmOptions::new(qemu::free_port()?),
+    )
+    .await?;
+    vm.wait_for_ssh()
+        .await
+        .whatever("unable to connect to VM via SSH")?;
+    vm.save_snapshot().await
+}
+
+/// Test scenario, i.e., a test workflow on a specific system.
+struct Scenario {
+    test_name: String,
+    test_config: Arc<TestConfig>,
+    system: SystemConfig,
+    architecture: Architecture,
+    image: PathBuf,
+    /// Bundle to deploy onto the physical device, if the system is deployed.
+    bundle: Option<PathBuf>,
+    /// Snapshot of the booted VM to restore.
+    snapshot: Option<PathBuf>,
+    /// Lock of the physical device, if the test runs on one.
+    device_lock: Option<Arc<tokio::sync::Mutex<()>>>,
+    /// Peers to start alongside the SUT.
+    peers: Arc<Vec<Peer>>,
+}
+
+/// Peer of a test scenario.
+struct Peer {
+    name: String,
+    system: SystemConfig,
+    architecture: Architecture,
+    image: PathBuf,
+}
+
+impl Peer {
+    fn new(config: &PeerConfig, architecture: Architecture, image: PathBuf) -> Self {
+        Self {
+            name: config.name.clone(),
+            system: SystemConfig {
+                system: config.system.clone(),
+                disk_size: config.disk_size.clone(),
+                ssh: config.ssh.clone(),
+                device: None,
+                qemu: config.qemu.clone(),
+                snapshot: None,
+            },
+            architecture,
+            image,
+        }
+    }
+}
+
+/// Running systems of a test scenario.
+struct Systems {
+    /// System under test.
+    sut: qemu::Vm,
+    /// Peers by name.
+    peers: Vec<(String, qemu::Vm)>,
+}
+
+impl Systems {
+    /// Get the SUT or the peer with the given name.
+    fn get(&self, target: Option<&str>) -> BakeryResult<&qemu::Vm> {
+        let Some(target) = target else {
+            return Ok(&self.sut);
+        };
+        match self.peers.iter().find(|(name, _)| name == target) {
+            Some((_, vm)) => Ok(vm),
+            None => bail!("unknown peer {target:?}"),
+        }
+    }
+}
+
+/// Run a test scenario in its own VM.
+async fn run_scenario(scenario: Scenario) -> (ScenarioResult, BakeryResult<()>) {
+    let Scenario {
+        test_name,
+        test_config,
+        system,
+        architecture,
+        image,
+        bundle,
+        snapshot,
+        device_lock: _,
+        peers,
+    } = scenario;
+
+    let started = Instant::now();
+    let mut scenario_result = ScenarioResult {
+        test: test_name.clone(),
+        system: system.system.clone(),
+        duration: 0.0,
+        outcome: Outcome::Passed,
+        error: None,
+        steps: Vec::new(),
+    };
+
+    let test_status = rugix_cli::add_status(TestCliStatus {
+        total_steps: test_config.steps.len() as u64,
+        state: Mutex::default(),
+        heading: format!("Test {test_name:?} on {:?}", system.system),
+    });
+
+    let ctx = TestCtx {
+        status: test_status.clone(),
+    };
+
+    // Each VM gets its own working directory and SSH port such that tests can run in
+    // parallel.
+    let work_dir = Path::new(".rugix/tests")
+        .join(&test_name)
+        .join(&system.system);
+    let systems = start_systems(
+        &ctx,
+        &system,
+        architecture,
+        &image,
+        bundle.as_deref(),
+        snapshot.as_deref(),
+        &peers,
+        &work_dir,
+    )
+    .await;
+    let systems = match systems {
+        Ok(systems) => systems,
+        Err(report) => {
+            scenario_result.duration = started.elapsed().as_secs_f64();
+            scenario_result.outcome = Outcome::Failed;
+            scenario_result.error = Some(format!("{report:?}"));
+            return (scenario_result, Err(report));
+        }
+    };
 
-        let test_status = rugix_cli::add_status(TestCliStatus {
-            total_steps: test_config.steps.len() as u64,
-            state: Mutex::default(),
-            heading: format!("Test {test_name:?}"),
+    info!("VM started");
+
+    let mut result = Ok(());
+    for (idx, step) in test_config.steps.iter().enumerate() {
+        let (action, description) = step_info(step);
+        if result.is_err() {
+            scenario_result.steps.push(StepResult {
+                action,
+                description,
+                duration: 0.0,
+                outcome: Outcome::Skipped,
+                error: None,
+                output: String::new(),
+            });
+            continue;
+        }
+        test_status.state.lock().unwrap().current_step = idx as u64 + 1;
+        rugix_cli::redraw();
+        let log_path = systems
+            .get(step_target(step))
+            .unwrap_or(&systems.sut)
+            .work_dir()
+            .join("test.log");
+        let log_offset = fs::metadata(&log_path).map(|m| m.len()).unwrap_or(0);
+        let step_started = Instant::now();
+        let step_result = run_step(&systems, &ctx, &system, step).await;
+        let duration = step_started.elapsed().as_secs_f64();
+        let output = fs::read(&log_path)
+            .ok()
+            .and_then(|log| {
+                log.get(log_offset as usize..)
+                    .map(|output| String::from_utf8_lossy(output).into_owned())
+            })
+            .unwrap_or_default();
+        let (outcome, error) = match &step_result {
+            Ok(()) => (Outcome::Passed, None),
+            Err(report) => (Outcome::Failed, Some(format!("{report:?}"))),
+        };
+        scenario_result.steps.push(StepResult {
+            action,
+            description,
+            duration,
+            outcome,
+            error,
+            output,
         });
+        result = step_result;
+    }
 
-        let image_config = project.config().resolve_system_config(&system.system)?;
+    if let Err(report) = &result {
+        scenario_result.outcome = Outcome::Failed;
+        scenario_result.error = Some(format!("{report:?}"));
+        let artifacts_dir = Path::new("build/tests")
+            .join(&test_name)
+            .join(&system.system)
+            .join("artifacts");
+        collect_artifacts(
+            &systems.sut,
+            test_config.artifacts.as_deref().unwrap_or_default(),
+            &artifacts_dir,
+        )
+        .await;
+        for (name, peer) in &systems.peers {
+            collect_artifacts(peer, &[], &artifacts_dir.join("peers").join(name)).await;
+        }
+    }
 
-        block_on(async {
-            let vm = qemu::start(
-                image_config.architecture,
-                &system_out
-                    .join("system.img")
-                    .canonicalize()
-                    .whatever("unable to canonicalize image path")?
-                    .to_string_lossy(),
-                &system,
-            )
-            .await?;
+    scenario_result.duration = started.elapsed().as_secs_f64();
+    (scenario_result, result)
+}
 
-            info!("VM started");
+/// Start the SUT and the peers of a test scenario.
+///
+/// If there are peers, all VMs are attached to a private network.
+#[allow(clippy::too_many_arguments)]
+async fn start_systems(
+    ctx: &TestCtx,
+    system: &SystemConfig,
+    architecture: Architecture,
+    image: &Path,
+    bundle: Option<&Path>,
+    snapshot: Option<&Path>,
+    peers: &[Peer],
+    work_dir: &Path,
+) -> BakeryResult<Systems> {
+    if let Some(device) = &system.device {
+        return Ok(Systems {
+            sut: hil::start(image, bundle, device, system, work_dir, ctx).await?,
+            peers: Vec::new(),
+        });
+    }
+    let network = if peers.is_empty() {
+        None
+    } else {
+        Some(qemu::PrivateNetwork::new()?)
+    };
+    let sut = qemu::start(
+        architecture,
+        &image.to_string_lossy(),
+        system,
+        work_dir,
+        qemu::VmOptions {
+            snapshot,
+            network: network.map(|network| (network, 0)),
+            ..qemu::VmOptions::new(qemu::free_port()?)
+        },
+    )
+    .await?;
+    let mut systems = Systems {
+        sut,
+        peers: Vec::new(),
+    };
+    for (idx, peer) in peers.iter().enumerate() {
+        info!("starting peer {:?}", peer.name);
+        let Ok(index) = u8::try_from(idx + 1) else {
+            bail!("too many peers");
+        };
+        let vm = qemu::start(
+            peer.architecture,
+            &peer.image.to_string_lossy(),
+            &peer.system,
+            &work_dir.join("peers").join(&peer.name),
+            qemu::VmOptions {
+                network: network.map(|network| (network, index)),
+                ..qemu::VmOptions::new(qemu::free_port()?)
+            },
+        )
+        .await?;
+        systems.peers.push((peer.name.clone(), vm));
+    }
+    Ok(systems)
+}
 
-            let ctx = TestCtx {
-                status: test_status.clone(),
-            };
+/// Peer targeted by a step, if any.
+fn step_target(step: &TestStep) -> Option<&str> {
+    match step {
+        TestStep::Run(step) => step.target.as_deref(),
+        TestStep::RunHost(_) | TestStep::Wait(_) => None,
+        TestStep::AssertFile(step) => step.target.as_deref(),
+        TestStep::AssertService(step) => step.target.as_deref(),
+        TestStep::AssertPort(step) => step.target.as_deref(),
+        TestStep::AssertCommand(step) => step.target.as_deref(),
+        TestStep::Update(_) => None,
+    }
+}
 
-            for (idx, step) in test_config.steps.iter().enumerate() {
-                test_status.state.lock().unwrap().current_step = idx as u64 + 1;
-                rugix_cli::redraw();
-                match step {
-                    crate::config::tests::TestStep::Run(RunStep {
-                        description,
-                        script,
-                        stdin_file,
-                        may_disconnect,
-                        may_fail,
-                    }) => {
-                        info!("running script");
-                        ctx.status
-                            .set_description(description.clone().unwrap_or_default());
-                        {
-                            let mut state = ctx.status.state.lock().unwrap();
-                            state.step_progress = Some(StepProgress {
-                                message: "waiting for SSH to connect",
-                                position: 0,
-                                length: None,
-                            });
-                        }
-                        vm.wait_for_ssh()
-                            .await
-                            .whatever("unable to connect to VM via SSH")?;
-                        {
-                            let mut state = ctx.status.state.lock().unwrap();
-                            state.step_progress = None;
+/// Action and description of a step for the test results.
+fn step_info(step: &TestStep) -> (&'static str, Option<String>) {
+    match step {
+        TestStep::Run(step) => ("run", step.description.clone()),
+        TestStep::RunHost(step) => ("run-host", step.description.clone()),
+        TestStep::Wait(step) => ("wait", step.description.clone()),
+        TestStep::AssertFile(step) => ("assert-file", step.description.clone()),
+        TestStep::AssertService(step) => ("assert-service", step.description.clone()),
+        TestStep::AssertPort(step) => ("assert-port", step.description.clone()),
+        TestStep::AssertCommand(step) => ("assert-command", step.description.clone()),
+        TestStep::Update(step) => ("update", step.description.clone()),
+    }
+}
+
+/// Run a single test step.
+async fn run_step(
+    systems: &Systems,
+    ctx: &TestCtx,
+    system: &SystemConfig,
+    step: &TestStep,
+) -> BakeryResult<()> {
+    let vm = systems.get(step_target(step))?;
+    match step {
+        TestStep::Run(RunStep {
+            description,
+            target: _,
+            script,
+            stdin_file,
+            may_disconnect,
+            may_fail,
+        }) => {
+            info!("running script");
+            ctx.status
+                .set_description(description.clone().unwrap_or_default());
+            {
+                let mut state = ctx.status.state.lock().unwrap();
+                state.step_progress = Some(StepProgress {
+                    message: "waiting for SSH to connect",
+                    position: 0,
+                    length: None,
+                });
+            }
+            vm.wait_for_ssh()
+                .await
+                .whatever("unable to connect to VM via SSH")?;
+            {
+                let mut state = ctx.status.state.lock().unwrap();
+                state.step_progress = None;
+            }
+            if let Err(report) = vm
+                .run_script(ctx, script, stdin_file.as_ref().map(|p| p.as_ref()))
+                .await
+            {
+                match report.error() {
+                    qemu::ExecError::Disconnected => {
+                        if !may_disconnect.unwrap_or(false) {
+                            return Err(report.whatever("script execution failed"));
                         }
-                        if let Err(report) = vm
-                            .run_script(&ctx, script, stdin_file.as_ref().map(|p| p.as_ref()))
-                            .await
-                        {
-                            match report.error() {
-                                qemu::ExecError::Disconnected => {
-                                    if !may_disconnect.unwrap_or(false) {
-                                        return Err(report.whatever("script execution failed"));
-                                    }
-                                }
-                                qemu::ExecError::Failed { code } => {
-                                    if *code != 0 && !may_fail.unwrap_or(false) {
-                                        return Err(report.whatever("script execution failed"));
-                                    }
-                                }
-                                qemu::ExecError::Other => {
-                                    return Err(report.whatever("script execution failed"));
-                                }
-                            }
+                    }
+                    qemu::ExecError::Failed { code } => {
+                        if *code != 0 && !may_fail.unwrap_or(false) {
+                            return Err(report.whatever("script execution failed"));
                         }
                     }
-                    crate::config::tests::TestStep::Wait(WaitStep {
-                        description,
-                        duration,
-                    }) => {
-                        ctx.status
-                            .set_description(description.clone().unwrap_or_else(|| {
-                                if *duration == 1.0 {
-                                    "wait for 1 second".to_owned()
-                                } else {
-                                    format!("wait for {duration:.1} seconds")
-                                }
-                            }));
-                        tokio::time::sleep(Duration::from_secs_f64(*duration)).await;
+                    qemu::ExecError::Other => {
+                        return Err(report.whatever("script execution failed"));
                     }
                 }
             }
+        }
+        TestStep::RunHost(step) => {
+            run_host_script(systems, ctx, step).await?;
+        }
+        TestStep::Wait(WaitStep {
+            description,
+            duration,
+        }) => {
+            ctx.status
+                .set_description(description.clone().unwrap_or_else(|| {
+                    if *duration == 1.0 {
+                        "wait for 1 second".to_owned()
+                    } else {
+                        format!("wait for {duration:.1} seconds")
+                    }
+                }));
+            tokio::time::sleep(Duration::from_secs_f64(*duration)).await;
+        }
+        TestStep::AssertFile(step) => {
+            check_assertion(vm, ctx, assertions::file(step)).await?;
+        }
+        TestStep::AssertService(step) => {
+            check_assertion(vm, ctx, assertions::service(step)).await?;
+        }
+        TestStep::AssertPort(step) => {
+            check_assertion(vm, ctx, assertions::port(step)).await?;
+        }
+        TestStep::AssertCommand(step) => {
+            check_assertion(vm, ctx, assertions::command(step)).await?;
+        }
+        TestStep::Update(step) => {
+            update::run(vm, ctx, step, &system.system).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a script on the host with the SSH connection details of the VMs.
+async fn run_host_script(systems: &Systems, ctx: &TestCtx, step: &RunHostStep) -> BakeryResult<()> {
+    info!("running host script");
+    let vm = &systems.sut;
+    ctx.status
+        .set_description(step.description.clone().unwrap_or_default());
+    let script_path = vm.work_dir().join("host-script");
+    fs::write(&script_path, &step.script).whatever("unable to write host script")?;
+    fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))
+        .whatever("unable to make host script executable")?;
+    let output_log = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(vm.work_dir().join("test.log"))
+        .whatever("unable to create test log file")?;
+    let mut command = tokio::process::Command::new(
+        script_path
+            .canonicalize()
+            .whatever("unable to canonicalize host script path")?,
+    );
+    command
+        .env("RUGIX_VM_SSH_HOST", vm.ssh_host())
+        .env("RUGIX_VM_SSH_PORT", vm.ssh_port().to_string())
+        .env("RUGIX_VM_SSH_USER", vm.ssh_user())
+        .stdin(Stdio::null())
+        .stdout(
+            output_log
+                .try_clone()
+                .whatever("unable to create test log file")?,
+        )
+        .stderr(output_log);
+    for (name, peer) in &systems.peers {
+        let prefix = format!("RUGIX_PEER_{}", name.to_uppercase().replace('-', "_"));
+        command
+            .env(format!("{prefix}_SSH_HOST"), peer.ssh_host())
+            .env(format!("{prefix}_SSH_PORT"), peer.ssh_port().to_string());
+    }
+    if let Some(private_key) = vm.ssh_private_key() {
+        command.env(
+            "RUGIX_VM_SSH_PRIVATE_KEY",
+            Path::new(private_key)
+                .canonicalize()
+                .whatever("unable to canonicalize private key path")?,
+        );
+    }
+    let status = command
+        .status()
+        .await
+        .whatever("unable to run host script")?;
+    if !status.success() && !step.may_fail.unwrap_or(false) {
+        bail!("host script failed with {status}");
+    }
+    Ok(())
+}
 
-            Ok(())
-        })?;
+/// Collect the logs of the VM and the given artifacts from the VM.
+///
+/// Errors are logged but otherwise ignored as this is done after a test has failed.
+async fn collect_artifacts(vm: &qemu::Vm, artifacts: &[String], artifacts_dir: &Path) {
+    info!("collecting test artifacts into {artifacts_dir:?}");
+    if let Err(error) = fs::create_dir_all(artifacts_dir) {
+        error!("unable to create artifacts directory: {error}");
+        return;
+    }
+    for log in [
+        "vm-stdout.log",
+        "vm-stderr.log",
+        "flash.log",
+        "power-cycle.log",
+        "ready.log",
+        "test.log",
+    ] {
+        let log_path = vm.work_dir().join(log);
+        if log_path.exists() {
+            if let Err(error) = fs::copy(&log_path, artifacts_dir.join(log)) {
+                error!("unable to copy {log_path:?}: {error}");
+            }
+        }
+    }
+    if artifacts.is_empty() {
+        return;
+    }
+    if let Err(report) = vm.wait_for_ssh().await {
+        error!("unable to collect artifacts from VM:\n{report:?}");
+        return;
     }
+    for artifact in artifacts {
+        let local = artifacts_dir
+            .join("vm")
+            .join(artifact.trim_start_matches('/'));
+        if let Err(report) = vm.download(artifact, &local).await {
+            error!("unable to collect artifact {artifact:?}:\n{report:?}");
+        }
+    }
+}
 
+/// Check an assertion in the VM.
+async fn check_assertion(
+    vm: &qemu::Vm,
+    ctx: &TestCtx,
+    assertion: assertions::Assertion,
+) -> BakeryResult<()> {
+    info!("checking assertion: {}", assertion.description);
+    ctx.status.set_description(assertion.description.clone());
+    vm.wait_for_ssh()
+        .await
+        .whatever("unable to connect to VM via SSH")?;
+    if let Err(report) = vm.run_script(ctx, &assertion.script, None).await {
+        return Err(match report.error() {
+            qemu::ExecError::Failed { .. } => {
+                report.whatever(format!("assertion failed: {}", assertion.description))
+            }
+            _ => report.whatever("unable to check assertion"),
+        });
+    }
     Ok(())
 }
 
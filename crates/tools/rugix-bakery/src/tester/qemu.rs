@@ -1,5 +1,6 @@
+use std::borrow::Cow;
 use std::os::unix::fs::MetadataExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
@@ -10,13 +11,14 @@ use async_trait::async_trait;
 use byte_calc::NumBytes;
 use reportify::{bail, whatever, ErrorExt, Report, ResultExt, Whatever};
 
-use russh::client::Handle;
+use russh::client::{Handle, KeyboardInteractiveAuthResponse};
 use russh::keys::key::PrivateKeyWithHashAlg;
 use russh::keys::{load_secret_key, ssh_key, PrivateKey};
-use russh::ChannelMsg;
+use russh::{ChannelMsg, Preferred};
 use russh_sftp::client::SftpSession;
 use thiserror::Error;
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
 use tokio::process::{Child, Command};
 use tokio::sync::{oneshot, Mutex};
 use tokio::{fs, time};
@@ -29,14 +31,90 @@ use crate::BakeryResult;
 
 use super::TestCtx;
 
+/// Default port on the host forwarded to the SSH port of the VM.
+pub const SSH_PORT: u16 = 2222;
+
+/// Tag of the internal snapshot of a booted VM.
+const SNAPSHOT_TAG: &str = "booted";
+
+/// Find a free port on the host to forward to the SSH port of a VM.
+pub fn free_port() -> BakeryResult<u16> {
+    let listener =
+        std::net::TcpListener::bind(("127.0.0.1", 0)).whatever("unable to find free port")?;
+    Ok(listener
+        .local_addr()
+        .whatever("unable to find free port")?
+        .port())
+}
+
+/// Multicast group of the private networks connecting the VMs of a test scenario.
+const PRIVATE_NETWORK_GROUP: &str = "230.0.0.1";
+
+/// Private network connecting the VMs of a test scenario.
+///
+/// The network is implemented as a UDP multicast group on the loopback interface of
+/// the host. Each private network uses its own port, isolating it from the others.
+#[derive(Debug, Clone, Copy)]
+pub struct PrivateNetwork {
+    port: u16,
+}
+
+impl PrivateNetwork {
+    /// Create a new private network on a free port.
+    pub fn new() -> BakeryResult<Self> {
+        let socket = std::net::UdpSocket::bind(("127.0.0.1", 0))
+            .whatever("unable to find free port for private network")?;
+        Ok(Self {
+            port: socket
+                .local_addr()
+                .whatever("unable to find free port for private network")?
+                .port(),
+        })
+    }
+}
+
+/// Options for starting a VM.
+#[derive(Debug, Clone, Copy)]
+pub struct VmOptions<'options> {
+    /// Port on the host forwarded to the SSH port of the VM.
+    pub ssh_port: u16,
+    /// Attach the serial console of the VM to the terminal.
+    pub interactive: bool,
+    /// Snapshot of the booted VM to restore.
+    pub snapshot: Option<&'options Path>,
+    /// Private network to attach the VM to and index of the VM on the network.
+    ///
+    /// The index determines the MAC address `52:54:00:12:35:<index>` of the interface.
+    pub network: Option<(PrivateNetwork, u8)>,
+}
+
+impl VmOptions<'_> {
+    /// Options for a VM with the given SSH port.
+    pub fn new(ssh_port: u16) -> Self {
+        Self {
+            ssh_port,
+            interactive: false,
+            snapshot: None,
+            network: None,
+        }
+    }
+}
+
+/// System under test, i.e., a VM or a physical device.
 pub struct Vm {
-    #[expect(dead_code, reason = "not currently used")]
-    child: Child,
+    /// Processes of the VM, e.g., `swtpm` and Qemu, killed on drop.
+    ///
+    /// If the system under test is a VM, Qemu is the last process.
+    children: Vec<Child>,
     ssh_session: Mutex<Option<Handle<SshHandler>>>,
     sftp_session: Mutex<Option<SftpSession>>,
-    #[expect(dead_code, reason = "not currently used")]
     vm_config: SystemConfig,
     private_key: Option<Arc<PrivateKey>>,
+    /// Expected host key of the system.
+    host_key: Option<ssh_key::PublicKey>,
+    work_dir: PathBuf,
+    ssh_host: String,
+    ssh_port: u16,
 }
 
 #[derive(Debug, Default)]
@@ -162,7 +240,7 @@ impl Vm {
         let mut output_log = tokio::fs::OpenOptions::new()
             .append(true)
             .create(true)
-            .open(".rugix/test.log")
+            .open(self.work_dir.join("test.log"))
             .await
             .whatever("unable to create test log file")?;
 
@@ -207,34 +285,245 @@ impl Vm {
             Err(ExecError::Disconnected.report())
         }
     }
+    /// Working directory of the VM with its image and logs.
+    pub fn work_dir(&self) -> &Path {
+        &self.work_dir
+    }
+
+    /// Create a system under test reachable via SSH at the given host and port.
+    pub fn new(
+        children: Vec<Child>,
+        config: &SystemConfig,
+        work_dir: &Path,
+        ssh_host: String,
+        ssh_port: u16,
+    ) -> BakeryResult<Self> {
+        let private_key = match config.ssh.as_ref().and_then(|ssh| ssh.private_key.as_ref()) {
+            Some(private_key) => Some(
+                load_secret_key(private_key, None)
+                    .whatever("unable to load private SSH key")
+                    .with_info(|_| format!("path: {private_key:?}"))?,
+            ),
+            None => None,
+        };
+        let host_key = match config.ssh.as_ref().and_then(|ssh| ssh.host_key.as_ref()) {
+            Some(host_key) => Some(
+                ssh_key::PublicKey::from_openssh(host_key.trim())
+                    .whatever("invalid SSH host key")
+                    .with_info(|_| format!("host key: {host_key:?}"))?,
+            ),
+            None => None,
+        };
+        Ok(Vm {
+            children,
+            ssh_session: Mutex::default(),
+            sftp_session: Mutex::default(),
+            vm_config: config.clone(),
+            private_key: private_key.map(Arc::new),
+            host_key,
+            work_dir: work_dir.to_path_buf(),
+            ssh_host,
+            ssh_port,
+        })
+    }
+
+    /// Run a command in the Qemu monitor and return its output.
+    pub async fn monitor(&self, command: &str) -> BakeryResult<String> {
+        let mut stream = UnixStream::connect(self.work_dir.join("monitor.sock"))
+            .await
+            .whatever("unable to connect to Qemu monitor")?;
+        read_monitor_output(&mut stream).await?;
+        stream
+            .write_all(format!("{command}\n").as_bytes())
+            .await
+            .whatever("unable to send command to Qemu monitor")?;
+        read_monitor_output(&mut stream).await
+    }
+
+    /// Save a snapshot of the running VM and terminate it.
+    ///
+    /// The snapshot is stored in the images in the working directory of the VM and can
+    /// be restored by passing the working directory to [`start`].
+    pub async fn save_snapshot(mut self) -> BakeryResult<PathBuf> {
+        let output = self.monitor(&format!("savevm {SNAPSHOT_TAG}")).await?;
+        if output.to_ascii_lowercase().contains("error") {
+            bail!("unable to save VM snapshot: {}", output.trim());
+        }
+        // Qemu exits without printing another prompt.
+        let _ = self.monitor("quit").await;
+        self.wait().await?;
+        Ok(self.work_dir.clone())
+    }
+
+    /// Cut the power of the VM by resetting it without shutting it down.
+    ///
+    /// Data which has not been written to the disk by the VM is lost.
+    pub async fn power_cycle(&self) -> BakeryResult<()> {
+        self.monitor("system_reset").await?;
+        // The connections are dead after the reset.
+        *self.sftp_session.lock().await = None;
+        *self.ssh_session.lock().await = None;
+        Ok(())
+    }
+
+    /// Wait for the VM to terminate.
+    pub async fn wait(&mut self) -> BakeryResult<()> {
+        if let Some(qemu) = self.children.last_mut() {
+            qemu.wait().await.whatever("unable to wait for Qemu")?;
+        }
+        Ok(())
+    }
+
+    /// Host to connect to via SSH.
+    pub fn ssh_host(&self) -> &str {
+        &self.ssh_host
+    }
+
+    /// Port to connect to via SSH.
+    pub fn ssh_port(&self) -> u16 {
+        self.ssh_port
+    }
+
+    /// Path of the private key used to connect to the VM via SSH.
+    pub fn ssh_private_key(&self) -> Option<&str> {
+        self.vm_config
+            .ssh
+            .as_ref()
+            .and_then(|ssh| ssh.private_key.as_deref())
+    }
+
+    /// User to connect as via SSH.
+    pub fn ssh_user(&self) -> &str {
+        self.vm_config
+            .ssh
+            .as_ref()
+            .and_then(|ssh| ssh.user.as_deref())
+            .unwrap_or("root")
+    }
+
+    /// Password used to connect to the VM via SSH.
+    fn ssh_password(&self) -> Option<&str> {
+        self.vm_config
+            .ssh
+            .as_ref()
+            .and_then(|ssh| ssh.password.as_deref())
+    }
+
+    /// Download a file or directory from the VM via SFTP.
+    pub async fn download(&self, remote: &str, local: &Path) -> Result<(), Report<SshError>> {
+        let Some(sftp_session) = &*self.sftp_session.lock().await else {
+            bail!("no SFTP session");
+        };
+        let mut pending = vec![(remote.to_owned(), local.to_path_buf())];
+        while let Some((remote, local)) = pending.pop() {
+            let metadata = sftp_session
+                .metadata(remote.as_str())
+                .await
+                .whatever("unable to read metadata of remote file")
+                .with_info(|_| format!("path: {remote:?}"))?;
+            if metadata.is_dir() {
+                fs::create_dir_all(&local)
+                    .await
+                    .whatever("unable to create directory")?;
+                let entries = sftp_session
+                    .read_dir(remote.as_str())
+                    .await
+                    .whatever("unable to read remote directory")
+                    .with_info(|_| format!("path: {remote:?}"))?;
+                for entry in entries {
+                    let name = entry.file_name();
+                    if name == "." || name == ".." {
+                        continue;
+                    }
+                    pending.push((
+                        format!("{}/{name}", remote.trim_end_matches('/')),
+                        local.join(&name),
+                    ));
+                }
+            } else {
+                let data = sftp_session
+                    .read(remote.as_str())
+                    .await
+                    .whatever("unable to read remote file")
+                    .with_info(|_| format!("path: {remote:?}"))?;
+                if let Some(parent) = local.parent() {
+                    fs::create_dir_all(parent)
+                        .await
+                        .whatever("unable to create directory")?;
+                }
+                fs::write(&local, data)
+                    .await
+                    .whatever("unable to write file")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a file from the VM via SFTP.
+    pub async fn read_file(&self, remote: &str) -> Result<Vec<u8>, Report<SshError>> {
+        let Some(sftp_session) = &*self.sftp_session.lock().await else {
+            bail!("no SFTP session");
+        };
+        sftp_session
+            .read(remote)
+            .await
+            .whatever("unable to read remote file")
+            .with_info(|_| format!("path: {remote:?}"))
+    }
+
+    /// Drop the SSH connection such that the next call to [`Vm::wait_for_ssh`]
+    /// reconnects.
+    pub async fn disconnect(&self) {
+        *self.sftp_session.lock().await = None;
+        *self.ssh_session.lock().await = None;
+    }
+
     pub async fn wait_for_ssh(&self) -> Result<(), Report<SshError>> {
         if let Some(ssh_session) = &*self.ssh_session.lock().await {
             if !ssh_session.is_closed() {
                 return Ok(());
             }
         }
-        let config = Arc::new(russh::client::Config::default());
-        let Some(private_key) = self.private_key.clone() else {
-            bail!("no private key");
-        };
-        let key = PrivateKeyWithHashAlg::new(private_key, Some(ssh_key::HashAlg::Sha512))
-            .whatever("unable to construct SSH key for SSH authentication")?;
+        let mut config = russh::client::Config::default();
+        if let Some(host_key) = &self.host_key {
+            // Make sure that the system presents the host key we expect.
+            let algorithms = if host_key.algorithm().is_rsa() {
+                vec![
+                    ssh_key::Algorithm::Rsa {
+                        hash: Some(ssh_key::HashAlg::Sha512),
+                    },
+                    ssh_key::Algorithm::Rsa {
+                        hash: Some(ssh_key::HashAlg::Sha256),
+                    },
+                ]
+            } else {
+                vec![host_key.algorithm()]
+            };
+            config.preferred = Preferred {
+                key: Cow::Owned(algorithms),
+                ..Preferred::default()
+            };
+        }
+        let config = Arc::new(config);
+        if self.private_key.is_none() && self.ssh_password().is_none() {
+            bail!("no private key or password");
+        }
         time::timeout(Duration::from_secs(120), async {
             loop {
                 debug!("trying to connect to VM via SSH");
                 if let Ok(Ok(mut ssh_session)) = time::timeout(
                     Duration::from_secs(5),
-                    russh::client::connect(config.clone(), ("127.0.0.1", 2222), SshHandler),
+                    russh::client::connect(
+                        config.clone(),
+                        (self.ssh_host.as_str(), self.ssh_port),
+                        SshHandler {
+                            host_key: self.host_key.clone(),
+                        },
+                    ),
                 )
                 .await
                 {
-                    if !ssh_session
-                        .authenticate_publickey("root", key)
-                        .await
-                        .whatever("unable to authenticate via SSH")?
-                    {
-                        bail!("unable to authenticate with the provided private key");
-                    }
+                    self.authenticate(&mut ssh_session).await?;
                     let channel = ssh_session.channel_open_session().await.unwrap();
                     channel.request_subsystem(true, "sftp").await.unwrap();
                     let sftp = SftpSession::new(channel.into_stream()).await.unwrap();
@@ -255,69 +544,254 @@ impl Vm {
         .map_err(|_| SshError::NotConnected.report())
         .and_then(|result| result)
     }
+
+    /// Authenticate with the private key and fall back to the password, if any.
+    async fn authenticate(
+        &self,
+        ssh_session: &mut Handle<SshHandler>,
+    ) -> Result<(), Report<SshError>> {
+        let user = self.ssh_user();
+        if let Some(private_key) = self.private_key.clone() {
+            let key = PrivateKeyWithHashAlg::new(private_key, Some(ssh_key::HashAlg::Sha512))
+                .whatever("unable to construct SSH key for SSH authentication")?;
+            if ssh_session
+                .authenticate_publickey(user, key)
+                .await
+                .whatever("unable to authenticate via SSH")?
+            {
+                return Ok(());
+            }
+        }
+        let Some(password) = self.ssh_password() else {
+            bail!("unable to authenticate with the provided private key");
+        };
+        debug!("falling back to keyboard-interactive authentication");
+        let mut response = ssh_session
+            .authenticate_keyboard_interactive_start(user, None::<String>)
+            .await
+            .whatever("unable to authenticate via SSH")?;
+        loop {
+            match response {
+                KeyboardInteractiveAuthResponse::Success => return Ok(()),
+                KeyboardInteractiveAuthResponse::Failure => break,
+                KeyboardInteractiveAuthResponse::InfoRequest { prompts, .. } => {
+                    // We assume that all prompts ask for the password.
+                    let answers = prompts.iter().map(|_| password.to_owned()).collect();
+                    response = ssh_session
+                        .authenticate_keyboard_interactive_respond(answers)
+                        .await
+                        .whatever("unable to authenticate via SSH")?;
+                }
+            }
+        }
+        debug!("falling back to password authentication");
+        if !ssh_session
+            .authenticate_password(user, password)
+            .await
+            .whatever("unable to authenticate via SSH")?
+        {
+            bail!("unable to authenticate with the provided private key or password");
+        }
+        Ok(())
+    }
 }
 
 pub async fn start(
     arch: Architecture,
     image_file: &str,
     config: &SystemConfig,
+    work_dir: &Path,
+    options: VmOptions<'_>,
 ) -> BakeryResult<Vm> {
-    let private_key = if let Some(ssh_config) = &config.ssh {
-        Some(
-            load_secret_key(&ssh_config.private_key, None)
-                .whatever("unable to load private SSH key")
-                .with_info(|_| format!("path: {:?}", ssh_config.private_key))?,
-        )
+    let VmOptions {
+        ssh_port,
+        interactive,
+        snapshot,
+        network,
+    } = options;
+    fs::create_dir_all(work_dir)
+        .await
+        .whatever("unable to create VM working directory")?;
+    let vm_image = work_dir.join("vm-image.img");
+    if let Some(snapshot) = snapshot {
+        // Internal snapshots are stored in the images, hence, we copy all images.
+        let mut read_dir = fs::read_dir(snapshot)
+            .await
+            .whatever("unable to read snapshot directory")?;
+        while let Some(entry) = read_dir
+            .next_entry()
+            .await
+            .whatever("unable to read snapshot directory")?
+        {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == "vm-image.img" || (name.starts_with("disk-") && name.ends_with(".qcow2")) {
+                fs::copy(entry.path(), work_dir.join(&*name))
+                    .await
+                    .whatever("unable to copy snapshot image")?;
+            }
+        }
     } else {
-        None
-    };
-    fs::create_dir_all(".rugix/")
+        run!([
+            "qemu-img",
+            "create",
+            "-f",
+            "qcow2",
+            "-F",
+            "raw",
+            "-o",
+            "backing_file={image_file}",
+            &vm_image,
+            config
+                .disk_size
+                .unwrap_or(NumBytes::gibibytes(40))
+                .raw
+                .to_string(),
+        ])
         .await
-        .whatever("unable to create .rugix directory")?;
-    run!([
-        "qemu-img",
-        "create",
-        "-f",
-        "qcow2",
-        "-F",
-        "raw",
-        "-o",
-        "backing_file={image_file}",
-        ".rugix/vm-image.img",
-        config
-            .disk_size
-            .unwrap_or(NumBytes::gibibytes(40))
-            .raw
-            .to_string(),
-    ])
-    .await
-    .whatever("unable to create VM image")?;
+        .whatever("unable to create VM image")?;
+    }
+    let qemu_config = config.qemu.as_ref();
+    let machine = qemu_config.and_then(|qemu| qemu.machine.as_deref());
+    let cpu = qemu_config.and_then(|qemu| qemu.cpu.as_deref());
     let mut command = match arch {
         Architecture::Amd64 => {
             let mut command = Command::new("qemu-system-x86_64");
-            command.args(&["-machine", "pc", "-m", "2G", "-smp", "cpus=2"]);
+            command.args(&["-machine", machine.unwrap_or("pc")]);
+            if let Some(cpu) = cpu {
+                command.args(&["-cpu", cpu]);
+            }
             command
         }
         Architecture::Arm64 => {
             let mut command = Command::new("qemu-system-aarch64");
             command.args(&[
                 "-machine",
-                "virt",
+                machine.unwrap_or("virt"),
                 "-cpu",
-                "cortex-a72",
-                "-m",
-                "2G",
-                "-smp",
-                "cpus=2",
+                cpu.unwrap_or("cortex-a72"),
             ]);
             command
         }
         _ => bail!("unsupported architecture {arch}"),
     };
+    command.args(&[
+        "-m",
+        qemu_config
+            .and_then(|qemu| qemu.memory.as_deref())
+            .unwrap_or("2G"),
+    ]);
+    command.arg("-smp");
+    command.arg(format!(
+        "cpus={}",
+        qemu_config.and_then(|qemu| qemu.cpus).unwrap_or(2)
+    ));
     command.arg("-drive");
-    command.arg("file=.rugix/vm-image.img,format=qcow2,if=virtio");
+    command.arg(format!(
+        "file={},format=qcow2,if=virtio",
+        vm_image.to_string_lossy()
+    ));
+    // Secondary disk images of the system are attached after the image.
+    for (name, disk_image) in find_disk_images(Path::new(image_file)).await? {
+        let disk_overlay = work_dir.join(format!("disk-image-{name}.qcow2"));
+        if snapshot.is_none() {
+            run!([
+                "qemu-img",
+                "create",
+                "-f",
+                "qcow2",
+                "-F",
+                "raw",
+                "-o",
+                format!("backing_file={}", disk_image.to_string_lossy()),
+                &disk_overlay,
+            ])
+            .await
+            .whatever("unable to create VM disk image")?;
+        }
+        command.arg("-drive");
+        command.arg(format!(
+            "file={},format=qcow2,if=virtio",
+            disk_overlay.to_string_lossy()
+        ));
+    }
+    let mut netdev = format!("user,id=net0,hostfwd=tcp:0.0.0.0:{ssh_port}-:22");
+    let mut children = Vec::new();
+    if let Some(qemu_config) = qemu_config {
+        for forward in qemu_config.port_forwards.iter().flatten() {
+            let protocol = forward.protocol.as_deref().unwrap_or("tcp");
+            if protocol != "tcp" && protocol != "udp" {
+                bail!("unsupported port forward protocol {protocol:?}");
+            }
+            netdev.push_str(&format!(
+                ",hostfwd={protocol}:0.0.0.0:{}-:{}",
+                forward.host, forward.guest
+            ));
+        }
+        for (idx, disk) in qemu_config.disks.iter().flatten().enumerate() {
+            let disk_image = work_dir.join(format!("disk-{idx}.qcow2"));
+            if snapshot.is_none() {
+                run!([
+                    "qemu-img",
+                    "create",
+                    "-f",
+                    "qcow2",
+                    &disk_image,
+                    disk.size.raw.to_string()
+                ])
+                .await
+                .whatever("unable to create disk image")?;
+            }
+            command.arg("-drive");
+            command.arg(format!(
+                "file={},format=qcow2,if=virtio",
+                disk_image.to_string_lossy()
+            ));
+        }
+        if let Some(usb_devices) = &qemu_config.usb_devices {
+            command.args(&["-device", "qemu-xhci,id=xhci"]);
+            for usb_device in usb_devices {
+                let Some((vendor, product)) = usb_device.split_once(':') else {
+                    bail!("invalid USB device {usb_device:?}, expected `<vendor>:<product>`");
+                };
+                command.arg("-device");
+                command.arg(format!(
+                    "usb-host,bus=xhci.0,vendorid=0x{vendor},productid=0x{product}"
+                ));
+            }
+        }
+        if qemu_config.tpm.unwrap_or(false) {
+            children.push(start_swtpm(work_dir).await?);
+            let tpm_device = match arch {
+                Architecture::Arm64 => "tpm-tis-device,tpmdev=tpm0",
+                _ => "tpm-tis,tpmdev=tpm0",
+            };
+            command.arg("-chardev");
+            command.arg(format!(
+                "socket,id=chrtpm,path={}",
+                work_dir.join("swtpm.sock").to_string_lossy()
+            ));
+            command.args(&[
+                "-tpmdev",
+                "emulator,id=tpm0,chardev=chrtpm",
+                "-device",
+                tpm_device,
+            ]);
+        }
+    }
     command.args(&["-device", "virtio-net-pci,netdev=net0", "-netdev"]);
-    command.arg("user,id=net0,hostfwd=tcp:0.0.0.0:2222-:22");
+    command.arg(netdev);
+    if let Some((network, index)) = network {
+        command.arg("-device");
+        command.arg(format!(
+            "virtio-net-pci,netdev=net1,mac=52:54:00:12:35:{index:02x}"
+        ));
+        command.arg("-netdev");
+        command.arg(format!(
+            "socket,id=net1,mcast={PRIVATE_NETWORK_GROUP}:{},localaddr=127.0.0.1",
+            network.port
+        ));
+    }
     let efi_code = match arch {
         Architecture::Amd64 => "/usr/share/OVMF/OVMF_CODE.fd",
         Architecture::Arm64 => "/usr/share/AAVMF/AAVMF_CODE.fd",
@@ -332,17 +806,37 @@ pub async fn start(
         "-serial",
         "mon:stdio",
     ]);
-    command
-        .kill_on_drop(true)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .stdin(Stdio::null());
+    command.arg("-monitor");
+    command.arg(format!(
+        "unix:{},server,nowait",
+        work_dir.join("monitor.sock").to_string_lossy()
+    ));
+    if snapshot.is_some() {
+        command.args(&["-loadvm", SNAPSHOT_TAG]);
+    }
+    // Raw arguments come last such that they can override the previous ones.
+    if let Some(args) = qemu_config.and_then(|qemu| qemu.args.as_ref()) {
+        command.args(args);
+    }
+    command.kill_on_drop(true);
+    if interactive {
+        // Qemu puts the terminal into raw mode and handles the escape sequences.
+        command
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .stdin(Stdio::inherit());
+    } else {
+        command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null());
+    }
     let mut child = command.spawn().whatever("unable to spawn Qemu")?;
-    if let Some(stdout) = Some(".rugix/vm-stdout.log") {
-        let mut stdout_log = fs::File::create(stdout)
+    if let Some(mut stdout) = child.stdout.take() {
+        let stdout_log_path = work_dir.join("vm-stdout.log");
+        let mut stdout_log = fs::File::create(stdout_log_path)
             .await
             .whatever("unable to create stdout log file")?;
-        let mut stdout = child.stdout.take().expect("we used Stdio::piped");
         tokio::spawn(async move {
             let log = rugix_cli::add_status(CliLog::new("VM".to_owned()));
             let mut line_buffer = Vec::new();
@@ -365,11 +859,10 @@ pub async fn start(
             // io::copy(&mut stdout, &mut stdout_log).await
         });
     }
-    if let Some(stderr) = Some(".rugix/vm-stderr.log") {
-        let mut stderr_log = fs::File::create(stderr)
+    if let Some(mut stderr) = child.stderr.take() {
+        let mut stderr_log = fs::File::create(work_dir.join("vm-stderr.log"))
             .await
             .whatever("unable to create stderr log file")?;
-        let mut stderr = child.stderr.take().expect("we used Stdio::piped");
         tokio::spawn(async move { io::copy(&mut stderr, &mut stderr_log).await });
     }
     // We give Qemu some time to start before checking it's exit status.
@@ -378,17 +871,92 @@ pub async fn start(
         Err(whatever!("unable to start qemu")
             .with_info(format!("status: {}", status.code().unwrap_or(1))))
     } else {
-        Ok(Vm {
-            child,
-            ssh_session: Mutex::default(),
-            sftp_session: Mutex::default(),
-            vm_config: config.clone(),
-            private_key: private_key.map(Arc::new),
-        })
+        children.push(child);
+        Vm::new(children, config, work_dir, "127.0.0.1".to_owned(), ssh_port)
     }
 }
 
-struct SshHandler;
+/// Find the secondary disk images built alongside the given image.
+///
+/// The images are ordered by their name.
+async fn find_disk_images(image_file: &Path) -> BakeryResult<Vec<(String, PathBuf)>> {
+    let Some(disks_dir) = image_file.parent().map(|parent| parent.join("disks")) else {
+        return Ok(Vec::new());
+    };
+    if !disks_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut disk_images = Vec::new();
+    let mut read_dir = fs::read_dir(&disks_dir)
+        .await
+        .whatever("unable to read disks directory")?;
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .whatever("unable to read disks directory")?
+    {
+        let path = entry.path();
+        if path.extension().is_some_and(|extension| extension == "img") {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let path = path
+                .canonicalize()
+                .whatever("unable to canonicalize disk image path")?;
+            disk_images.push((name, path));
+        }
+    }
+    disk_images.sort();
+    Ok(disk_images)
+}
+
+/// Start `swtpm` emulating a TPM 2.0 with its state in the working directory.
+async fn start_swtpm(work_dir: &Path) -> BakeryResult<Child> {
+    let state_dir = work_dir.join("tpm");
+    fs::create_dir_all(&state_dir)
+        .await
+        .whatever("unable to create TPM state directory")?;
+    let socket = work_dir.join("swtpm.sock");
+    let child = Command::new("swtpm")
+        .arg("socket")
+        .arg("--tpm2")
+        .arg("--tpmstate")
+        .arg(format!("dir={}", state_dir.to_string_lossy()))
+        .arg("--ctrl")
+        .arg(format!("type=unixio,path={}", socket.to_string_lossy()))
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .spawn()
+        .whatever("unable to spawn swtpm")?;
+    // Wait for `swtpm` to create the socket before starting Qemu.
+    for _ in 0..50 {
+        if socket.exists() {
+            return Ok(child);
+        }
+        time::sleep(Duration::from_millis(100)).await;
+    }
+    bail!("swtpm did not create its socket")
+}
+
+/// Read the output of the Qemu monitor up to the next prompt.
+async fn read_monitor_output(stream: &mut UnixStream) -> BakeryResult<String> {
+    let mut output = Vec::new();
+    let mut buffer = [0; 1024];
+    while !output.ends_with(b"(qemu) ") {
+        let read = stream
+            .read(&mut buffer)
+            .await
+            .whatever("unable to read from Qemu monitor")?;
+        if read == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+struct SshHandler {
+    /// Expected host key of the system, if any.
+    host_key: Option<ssh_key::PublicKey>,
+}
 
 #[derive(Debug, Error)]
 pub enum ExecError {
@@ -428,8 +996,22 @@ impl Whatever for SshError {
 impl russh::client::Handler for SshHandler {
     type Error = SshError;
 
-    async fn check_server_key(&mut self, _: &ssh_key::PublicKey) -> Result<bool, Self::Error> {
-        // We do not care about the identity of the server.
+    async fn check_server_key(
+        &mut self,
+        server_key: &ssh_key::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        let Some(host_key) = &self.host_key else {
+            // Systems under test usually generate their host keys on the first boot, so
+            // there is nothing to verify them against.
+            return Ok(true);
+        };
+        if host_key.key_data() != server_key.key_data() {
+            error!(
+                "host key {} of the system does not match the configured host key",
+                server_key.fingerprint(ssh_key::HashAlg::Sha256)
+            );
+            return Ok(false);
+        }
         Ok(true)
     }
 }
@@ -0,0 +1,164 @@
+//! Machine-readable test results.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use reportify::ResultExt;
+use serde::Serialize;
+
+use crate::BakeryResult;
+
+/// Result of a test workflow on a system.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScenarioResult {
+    /// Name of the test workflow.
+    pub test: String,
+    /// Name of the system.
+    pub system: String,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// Outcome of the test.
+    pub outcome: Outcome,
+    /// Error in case the test failed.
+    pub error: Option<String>,
+    /// Results of the steps.
+    pub steps: Vec<StepResult>,
+}
+
+/// Result of a test step.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StepResult {
+    /// Action of the step.
+    pub action: &'static str,
+    /// Description of the step.
+    pub description: Option<String>,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// Outcome of the step.
+    pub outcome: Outcome,
+    /// Error in case the step failed.
+    pub error: Option<String>,
+    /// Output produced by the step.
+    pub output: String,
+}
+
+/// Outcome of a test or step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Summary of all test results.
+#[derive(Debug, Serialize)]
+struct Summary<'results> {
+    passed: usize,
+    failed: usize,
+    tests: &'results [ScenarioResult],
+}
+
+/// Write the test results as JSON summary.
+pub fn write_json(results: &[ScenarioResult], path: &Path) -> BakeryResult<()> {
+    let count = |outcome| {
+        results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    };
+    let summary = Summary {
+        passed: count(Outcome::Passed),
+        failed: count(Outcome::Failed),
+        tests: results,
+    };
+    let json = serde_json::to_string_pretty(&summary).whatever("unable to serialize results")?;
+    fs::write(path, json).whatever("unable to write JSON test results")
+}
+
+/// Write the test results in JUnit XML format.
+///
+/// Each test workflow becomes a test suite with a test case per system.
+pub fn write_junit(results: &[ScenarioResult], path: &Path) -> BakeryResult<()> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    let mut tests = results
+        .iter()
+        .map(|result| &result.test)
+        .collect::<Vec<_>>();
+    tests.dedup();
+    for test in tests {
+        let cases = results
+            .iter()
+            .filter(|result| &result.test == test)
+            .collect::<Vec<_>>();
+        let failures = cases
+            .iter()
+            .filter(|case| case.outcome == Outcome::Failed)
+            .count();
+        let time = cases.iter().map(|case| case.duration).sum::<f64>();
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{time:.3}\">",
+            escape(test),
+            cases.len(),
+        )
+        .unwrap();
+        for case in cases {
+            writeln!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">",
+                escape(&case.system),
+                escape(test),
+                case.duration
+            )
+            .unwrap();
+            if let Some(error) = &case.error {
+                writeln!(
+                    xml,
+                    "      <failure message=\"test failed\">{}</failure>",
+                    escape(error)
+                )
+                .unwrap();
+            }
+            let mut output = String::new();
+            for (idx, step) in case.steps.iter().enumerate() {
+                writeln!(
+                    output,
+                    "--- step {} ({}): {:?} in {:.3}s",
+                    idx + 1,
+                    step.action,
+                    step.outcome,
+                    step.duration
+                )
+                .unwrap();
+                output.push_str(&step.output);
+            }
+            writeln!(xml, "      <system-out>{}</system-out>", escape(&output)).unwrap();
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    fs::write(path, xml).whatever("unable to write JUnit test results")
+}
+
+/// Escape a string for use in XML.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tabs and newlines are not allowed in XML.
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,199 @@
+//! Update steps installing a bundle of a newer release and rebooting into it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reportify::{bail, ErrorExt, ResultExt};
+use tracing::info;
+
+use crate::config::load_json;
+use crate::config::tests::{PowerLoss, UpdatePhase, UpdateStep};
+use crate::oven::system::{ReleaseInfo, SystemInfo, TestAccess};
+use crate::oven::{self, BundleOpts};
+use crate::project::ProjectRef;
+use crate::tester::http::FileServer;
+use crate::tester::{assertions, check_assertion, qemu, system_out, TestCtx};
+use crate::BakeryResult;
+
+/// Default release version of updates.
+const DEFAULT_VERSION: &str = "update";
+
+/// Address of the host as seen from a VM with QEMU's user networking.
+const HOST_ADDRESS: &str = "10.0.2.2";
+
+/// Time to give the system to shut down, if the SSH connection did not drop.
+const REBOOT_DELAY: Duration = Duration::from_secs(10);
+
+/// Hook blocking the installation once the point of the power loss has been reached.
+const POWER_LOSS_HOOK: &str = "99-rugix-test-power-loss";
+
+/// File created by the hook once the point of the power loss has been reached.
+const POWER_LOSS_MARKER: &str = "/run/rugix-test-power-loss";
+
+/// System and release version of the update of the given step.
+pub fn update_target<'s>(step: &'s UpdateStep, sut_system: &'s str) -> (&'s str, &'s str) {
+    (
+        step.system.as_deref().unwrap_or(sut_system),
+        step.version.as_deref().unwrap_or(DEFAULT_VERSION),
+    )
+}
+
+/// Path of the update bundle of the given system and release version.
+pub fn bundle_path(system: &str, version: &str) -> PathBuf {
+    Path::new("build/tests/updates")
+        .join(version)
+        .join(system)
+        .join("system.rugixb")
+}
+
+/// Bake the system with the given release version and its update bundle.
+pub fn bake_bundle(
+    project: &ProjectRef,
+    test_access: &TestAccess,
+    system: &str,
+    version: &str,
+    source_date_epoch: u64,
+) -> BakeryResult<()> {
+    if version.is_empty() || version.contains('/') {
+        bail!("invalid update version {version:?}");
+    }
+    let bundle = bundle_path(system, version);
+    let system_out = bundle.parent().unwrap();
+    info!("baking update of system {system:?} with version {version:?}");
+    oven::bake_test_system(
+        project,
+        &ReleaseInfo {
+            system_id: None,
+            system_version: Some(version.to_owned()),
+            commit: None,
+        },
+        Some(test_access),
+        system,
+        None,
+        system_out,
+        source_date_epoch,
+    )
+    .whatever("error baking update")?;
+    oven::bake_bundle(project, system, system_out, &bundle, &BundleOpts::default())
+        .whatever("error baking update bundle")
+}
+
+/// Install the update bundle, reboot into it, and, optionally, commit it.
+pub async fn run(
+    vm: &qemu::Vm,
+    ctx: &TestCtx,
+    step: &UpdateStep,
+    sut_system: &str,
+) -> BakeryResult<()> {
+    let (system, version) = update_target(step, sut_system);
+    ctx.status.set_description(
+        step.description
+            .clone()
+            .unwrap_or_else(|| format!("update to version {version:?}")),
+    );
+    let server = FileServer::start(&bundle_path(system, version)).await?;
+    let url = format!("http://{HOST_ADDRESS}:{}/system.rugixb", server.port());
+    if let Some(power_loss) = &step.power_loss {
+        cut_power(vm, ctx, power_loss, &url, sut_system).await?;
+    }
+    vm.wait_for_ssh()
+        .await
+        .whatever("unable to connect to VM via SSH")?;
+    info!("installing update {version:?} of system {system:?}");
+    let script = format!("#!/bin/sh\nset -e\nrugix-ctrl update install --reboot yes {url}\n");
+    match vm.run_script(ctx, &script, None).await {
+        Ok(()) => {
+            // The reboot has been initiated but the connection has not dropped yet.
+            tokio::time::sleep(REBOOT_DELAY).await;
+        }
+        Err(report) if matches!(report.error(), qemu::ExecError::Disconnected) => {
+            // The connection dropped because the system is rebooting.
+        }
+        Err(report) => return Err(report.whatever("unable to install update")),
+    }
+    drop(server);
+    check_assertion(vm, ctx, assertions::booted_version(version)).await?;
+    if step.commit.unwrap_or(true) {
+        info!("committing update");
+        vm.run_script(ctx, "#!/bin/sh\nrugix-ctrl system commit\n", None)
+            .await
+            .whatever("unable to commit update")?;
+        check_assertion(vm, ctx, assertions::booted_default_group()).await?;
+    }
+    Ok(())
+}
+
+/// Cut the power during the installation and check that the old system boots.
+async fn cut_power(
+    vm: &qemu::Vm,
+    ctx: &TestCtx,
+    power_loss: &PowerLoss,
+    url: &str,
+    sut_system: &str,
+) -> BakeryResult<()> {
+    let phase = power_loss.phase.unwrap_or(UpdatePhase::Progress);
+    let (stage, condition) = match phase {
+        UpdatePhase::PreUpdate => ("pre-update", String::new()),
+        UpdatePhase::Progress => {
+            let progress = power_loss.progress.unwrap_or(50.0);
+            (
+                "progress",
+                format!(
+                    "awk -v progress=\"$RUGIX_UPDATE_PROGRESS\" 'BEGIN {{ exit !(progress >= {progress}) }}' || exit 0\n"
+                ),
+            )
+        }
+        UpdatePhase::PostUpdate => ("post-update", String::new()),
+    };
+    let hook_dir = format!("/etc/rugix/hooks/update-install/{stage}");
+    // The hook blocks the installation such that the power is cut at a defined point.
+    let script = format!(
+        "#!/bin/sh\n\
+        set -e\n\
+        rm -f {POWER_LOSS_MARKER}\n\
+        mkdir -p {hook_dir}\n\
+        cat >{hook_dir}/{POWER_LOSS_HOOK} <<'EOF'\n\
+        #!/bin/sh\n\
+        {condition}\
+        touch {POWER_LOSS_MARKER}\n\
+        sleep 3600\n\
+        EOF\n\
+        chmod +x {hook_dir}/{POWER_LOSS_HOOK}\n\
+        rugix-ctrl update install --reboot yes {url} >/tmp/rugix-test-update.log 2>&1 &\n\
+        pid=$!\n\
+        while [ ! -e {POWER_LOSS_MARKER} ]; do\n    \
+            if ! kill -0 \"$pid\" 2>/dev/null; then\n        \
+                cat /tmp/rugix-test-update.log\n        \
+                echo \"update finished before the power loss\" >&2\n        \
+                exit 1\n    \
+            fi\n    \
+            sleep 0.1\n\
+        done\n"
+    );
+    vm.wait_for_ssh()
+        .await
+        .whatever("unable to connect to VM via SSH")?;
+    info!("installing update until power loss");
+    vm.run_script(ctx, &script, None)
+        .await
+        .whatever("unable to reach point of power loss")?;
+    info!("cutting power of VM");
+    vm.power_cycle().await?;
+    let old_version = baked_version(sut_system)?;
+    check_assertion(vm, ctx, assertions::booted_version(&old_version)).await?;
+    check_assertion(vm, ctx, assertions::booted_default_group()).await?;
+    // The hook may survive the reboot, e.g., if the overlay is persistent.
+    vm.run_script(
+        ctx,
+        &format!("#!/bin/sh\nrm -f {hook_dir}/{POWER_LOSS_HOOK}\n"),
+        None,
+    )
+    .await
+    .whatever("unable to remove power loss hook")
+}
+
+/// Release version of the system baked for testing.
+pub fn baked_version(system: &str) -> BakeryResult<String> {
+    let info = load_json::<SystemInfo>(&system_out(system).join("system-build-info.json"))?;
+    Ok(info.release.version)
+}
@@ -0,0 +1,76 @@
+//! Build environment Rugix Bakery runs in.
+//!
+//! Usually, Rugix Bakery runs in a privileged container set up by `run-bakery`. When
+//! running natively on the host, the paths of the build environment and the isolation
+//! used for customizing layers are configured with environment variables.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use reportify::bail;
+
+use crate::BakeryResult;
+
+/// Directory of the build environment (defaults to `/run/rugix/bakery`).
+pub fn run_dir() -> PathBuf {
+    std::env::var_os("RUGIX_BAKERY_RUN_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/run/rugix/bakery"))
+}
+
+/// Context directory (defaults to the `context` directory of the build environment).
+pub fn context_dir() -> PathBuf {
+    std::env::var_os("RUGIX_BAKERY_CONTEXT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| run_dir().join("context"))
+}
+
+/// Global cache directory shared among projects.
+pub fn cache_dir() -> PathBuf {
+    run_dir().join("cache")
+}
+
+/// Indicates whether network access is forbidden.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Forbid network access, requiring all sources to be prefetched.
+pub fn set_offline() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+/// Check whether network access is forbidden.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail if network access is forbidden.
+///
+/// The given description says what would require network access.
+pub fn ensure_online(what: &str) -> BakeryResult<()> {
+    if is_offline() {
+        bail!("{what} requires network access, run `rugix-bakery fetch` before baking offline");
+    }
+    Ok(())
+}
+
+/// Isolation used for executing commands in the root filesystem of a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    /// Mount pseudo filesystems into the root filesystem and use `chroot`.
+    ///
+    /// This requires root privileges.
+    Chroot,
+    /// Use Bubblewrap to set up an unprivileged user namespace.
+    Bwrap,
+}
+
+impl Isolation {
+    /// Isolation configured with `RUGIX_BAKERY_ISOLATION` (defaults to `chroot`).
+    pub fn from_env() -> BakeryResult<Self> {
+        match std::env::var("RUGIX_BAKERY_ISOLATION").as_deref() {
+            Err(_) | Ok("chroot") => Ok(Self::Chroot),
+            Ok("bwrap") => Ok(Self::Bwrap),
+            Ok(other) => bail!("unknown isolation `{other}`, expected `chroot` or `bwrap`"),
+        }
+    }
+}
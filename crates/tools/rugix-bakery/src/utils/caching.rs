@@ -12,23 +12,13 @@ use sha1::{Digest, Sha1};
 use tracing::info;
 use url::Url;
 
+use crate::utils::build_env;
 use crate::BakeryResult;
 
 pub fn download(url: &Url) -> BakeryResult<PathBuf> {
-    let Some(file_name) = url.path_segments().and_then(|segments| segments.last()) else {
-        bail!("unable to obtain file name from URL");
-    };
-    let file_extension = file_name.split_once('.').map(|(_, extension)| extension);
-    let mut url_hasher = Sha1::new();
-    url_hasher.update(url.as_str().as_bytes());
-    let url_hash = url_hasher.finalize();
-    let mut cache_file_name = hex::encode(url_hash);
-    if let Some(extension) = file_extension {
-        cache_file_name.push('.');
-        cache_file_name.push_str(extension);
-    }
-    let cache_file_path = Path::new(".rugix/cache").join(cache_file_name);
+    let cache_file_path = download_cache_path(url)?;
     if !cache_file_path.exists() {
+        build_env::ensure_online(&format!("downloading `{url}`"))?;
         info!("downloading `{url}`");
         std::fs::create_dir_all(".rugix/cache").whatever("error creating cache directory")?;
         let mut response = reqwest::blocking::get(url.clone()).whatever("error retrieving URL")?;
@@ -60,10 +50,39 @@ pub fn download(url: &Url) -> BakeryResult<PathBuf> {
         } else {
             bail!("error downloading file: {}", response.status());
         }
+    } else {
+        // The modification time of downloaded files is their last use.
+        fs::File::options()
+            .append(true)
+            .open(&cache_file_path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .ok();
     }
     Ok(cache_file_path)
 }
 
+/// Path of a previously downloaded file, if it is still in the cache.
+pub fn cached_download(url: &Url) -> Option<PathBuf> {
+    download_cache_path(url).ok().filter(|path| path.exists())
+}
+
+/// Path where the file of the given URL is cached.
+fn download_cache_path(url: &Url) -> BakeryResult<PathBuf> {
+    let Some(file_name) = url.path_segments().and_then(|segments| segments.last()) else {
+        bail!("unable to obtain file name from URL");
+    };
+    let file_extension = file_name.split_once('.').map(|(_, extension)| extension);
+    let mut url_hasher = Sha1::new();
+    url_hasher.update(url.as_str().as_bytes());
+    let url_hash = url_hasher.finalize();
+    let mut cache_file_name = hex::encode(url_hash);
+    if let Some(extension) = file_extension {
+        cache_file_name.push('.');
+        cache_file_name.push_str(extension);
+    }
+    Ok(Path::new(".rugix/cache").join(cache_file_name))
+}
+
 #[derive(Debug, Default)]
 pub struct Hasher {
     hasher: Sha1,
@@ -124,3 +143,133 @@ pub fn mtime_recursive(path: &Path) -> Result<ModificationTime, io::Error> {
     }
     Ok(time)
 }
+
+/// Directory of the local cache.
+const CACHE_DIR: &str = ".rugix";
+
+/// Name of the file recording the last use of a cached layer.
+const LAYER_USE_FILE: &str = "last-use.json";
+
+/// Last use of a cached layer.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LayerUse {
+    layer: String,
+    arch: String,
+    timestamp: u64,
+}
+
+/// Record that the cached layer in the given directory has been used.
+pub fn record_layer_use(layer_dir: &Path, layer: &str, arch: &str) -> BakeryResult<()> {
+    let layer_use = LayerUse {
+        layer: layer.to_owned(),
+        arch: arch.to_owned(),
+        timestamp: jiff::Timestamp::now().as_second() as u64,
+    };
+    fs::write(
+        layer_dir.join(LAYER_USE_FILE),
+        serde_json::to_string(&layer_use).unwrap(),
+    )
+    .whatever("unable to record layer use")
+}
+
+/// Architecture of the cached layer in the given directory, if known.
+pub fn layer_arch(layer_dir: &Path) -> Option<String> {
+    fs::read_to_string(layer_dir.join(LAYER_USE_FILE))
+        .ok()
+        .and_then(|layer_use| serde_json::from_str::<LayerUse>(&layer_use).ok())
+        .map(|layer_use| layer_use.arch)
+}
+
+/// Kind of a cache entry.
+#[derive(Debug, Clone)]
+pub enum CacheEntryKind {
+    /// Cached layer.
+    Layer {
+        /// Name of the layer, if known.
+        name: Option<String>,
+        /// Architecture of the layer, if known.
+        arch: Option<String>,
+    },
+    /// Downloaded file.
+    Download,
+}
+
+/// Entry of the local cache.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    /// Path of the entry.
+    pub path: PathBuf,
+    /// Kind of the entry.
+    pub kind: CacheEntryKind,
+    /// Size of the entry in bytes.
+    pub size: u64,
+    /// Last use in seconds since the UNIX epoch.
+    pub last_used: u64,
+}
+
+impl CacheEntry {
+    /// Remove the entry from the cache.
+    pub fn remove(&self) -> BakeryResult<()> {
+        if self.path.is_dir() {
+            fs::remove_dir_all(&self.path)
+        } else {
+            fs::remove_file(&self.path)
+        }
+        .whatever_with(|_| format!("unable to remove cache entry {:?}", self.path))
+    }
+}
+
+/// List the cached layers and downloaded files of the local cache.
+pub fn cache_entries() -> BakeryResult<Vec<CacheEntry>> {
+    let mut entries = Vec::new();
+    let layers_dir = Path::new(CACHE_DIR).join("layers");
+    if layers_dir.is_dir() {
+        for entry in fs::read_dir(&layers_dir).whatever("unable to read layer cache")? {
+            let path = entry.whatever("unable to read layer cache")?.path();
+            let layer_use = fs::read_to_string(path.join(LAYER_USE_FILE))
+                .ok()
+                .and_then(|layer_use| serde_json::from_str::<LayerUse>(&layer_use).ok());
+            let last_used = match &layer_use {
+                Some(layer_use) => layer_use.timestamp,
+                // Layers baked by older versions do not record their use.
+                None => mtime_recursive(&path).map(|mtime| mtime.0).unwrap_or(0),
+            };
+            entries.push(CacheEntry {
+                size: disk_usage(&path).whatever("unable to determine size of cached layer")?,
+                kind: CacheEntryKind::Layer {
+                    name: layer_use.as_ref().map(|layer_use| layer_use.layer.clone()),
+                    arch: layer_use.map(|layer_use| layer_use.arch),
+                },
+                last_used,
+                path,
+            });
+        }
+    }
+    let downloads_dir = Path::new(CACHE_DIR).join("cache");
+    if downloads_dir.is_dir() {
+        for entry in fs::read_dir(&downloads_dir).whatever("unable to read download cache")? {
+            let path = entry.whatever("unable to read download cache")?.path();
+            entries.push(CacheEntry {
+                size: disk_usage(&path).whatever("unable to determine size of download")?,
+                kind: CacheEntryKind::Download,
+                last_used: mtime(&path).map(|mtime| mtime.0).unwrap_or(0),
+                path,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Recursively compute the size of the files in the given path.
+fn disk_usage(path: &Path) -> Result<u64, io::Error> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += disk_usage(&entry?.path())?;
+    }
+    Ok(size)
+}
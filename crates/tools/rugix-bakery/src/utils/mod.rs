@@ -1,5 +1,6 @@
 //! Various utilities.
 
+pub mod build_env;
 pub mod caching;
 pub mod idx_vec;
 pub mod once_cell_ext;
@@ -0,0 +1,135 @@
+//! HTTP basic authentication.
+
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reportify::{bail, ResultExt};
+
+use crate::ServerResult;
+
+/// Credentials of a user allowed to access the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Name of the user.
+    pub user: String,
+    /// Password of the user.
+    pub password: String,
+}
+
+impl Credentials {
+    /// Parse credentials of the form `<user>:<password>`.
+    pub fn parse(credentials: &str) -> Option<Self> {
+        let (user, password) = credentials.split_once(':')?;
+        Some(Self {
+            user: user.to_owned(),
+            password: password.to_owned(),
+        })
+    }
+}
+
+/// Load credentials from a file with one `<user>:<password>` pair per line.
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn load_credentials(path: &Path) -> ServerResult<Vec<Credentials>> {
+    let contents = std::fs::read_to_string(path).whatever("unable to read credentials file")?;
+    let mut credentials = Vec::new();
+    for (idx, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(entry) = Credentials::parse(line) else {
+            bail!(
+                "invalid credentials in line {}, expected `<user>:<password>`",
+                idx + 1
+            );
+        };
+        credentials.push(entry);
+    }
+    Ok(credentials)
+}
+
+/// Check the value of an `Authorization` header against the given credentials.
+pub fn is_authorized(header: Option<&str>, credentials: &[Credentials]) -> bool {
+    let Some(encoded) = header.and_then(|header| header.strip_prefix("Basic ")) else {
+        return false;
+    };
+    let Some(provided) = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|decoded| Credentials::parse(&decoded))
+    else {
+        return false;
+    };
+    credentials.iter().any(|credentials| {
+        credentials.user == provided.user
+            && constant_time_eq(
+                credentials.password.as_bytes(),
+                provided.password.as_bytes(),
+            )
+    })
+}
+
+/// Compare two byte strings in time independent of the position of the first mismatch.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basic(credentials: &str) -> String {
+        format!("Basic {}", STANDARD.encode(credentials))
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let credentials = vec![
+            Credentials::parse("alice:secret").unwrap(),
+            Credentials::parse("bob:pass:word").unwrap(),
+        ];
+        assert!(is_authorized(Some(&basic("alice:secret")), &credentials));
+        // Passwords may contain colons.
+        assert!(is_authorized(Some(&basic("bob:pass:word")), &credentials));
+        // Wrong passwords and users.
+        assert!(!is_authorized(Some(&basic("alice:secrets")), &credentials));
+        assert!(!is_authorized(Some(&basic("alice:")), &credentials));
+        assert!(!is_authorized(Some(&basic("bob:secret")), &credentials));
+        assert!(!is_authorized(Some(&basic("eve:secret")), &credentials));
+        // Missing or malformed headers.
+        assert!(!is_authorized(None, &credentials));
+        assert!(!is_authorized(Some("Basic"), &credentials));
+        assert!(!is_authorized(Some("Basic !!!"), &credentials));
+        assert!(!is_authorized(Some(&basic("alice")), &credentials));
+        assert!(!is_authorized(
+            Some(&format!("Bearer {}", STANDARD.encode("alice:secret"))),
+            &credentials
+        ));
+        // Without credentials, nobody is authorized.
+        assert!(!is_authorized(Some(&basic("alice:secret")), &[]));
+    }
+
+    #[test]
+    fn test_load_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("credentials");
+        std::fs::write(&path, "# Users\nalice:secret\n\n  bob:password  \n").unwrap();
+        assert_eq!(
+            load_credentials(&path).unwrap(),
+            vec![
+                Credentials::parse("alice:secret").unwrap(),
+                Credentials::parse("bob:password").unwrap(),
+            ]
+        );
+        std::fs::write(&path, "alice:secret\nbob\n").unwrap();
+        assert!(load_credentials(&path).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Delta negotiation against block indices reported by clients.
+//!
+//! Clients report the block indices of the data they already have, e.g., the indices of
+//! their slots stored by Rugix Ctrl. For payloads with a matching block encoding, the
+//! blocks already present on the client do not need to be transferred. The server
+//! responds with the byte ranges of the bundle the client still needs to download, which
+//! the client can then fetch with range requests.
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+
+use byte_calc::NumBytes;
+use reportify::{bail, ResultExt};
+use rugix_bundle::format::decode::{Decode, Decoder};
+use rugix_bundle::format::stlv::read_atom_head;
+use rugix_bundle::format::{tags, BlockEncoding, BlockIndex};
+use rugix_bundle::manifest::ChunkerAlgorithm;
+use rugix_bundle::reader::BundleReader;
+use rugix_bundle::source::{self, BundleSource, FileSource};
+use rugix_bundle::BundleResult;
+use rugix_compression::{ByteProcessor, CompressionFormat, XzDecoder};
+use serde::Serialize;
+
+use crate::ServerResult;
+
+/// Byte ranges of a bundle a client needs to download.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeltaPlan {
+    /// Size of the bundle.
+    pub size: u64,
+    /// Number of bytes the client needs to download.
+    pub download: u64,
+    /// Byte ranges to download in the syntax of HTTP `Range` headers.
+    pub ranges: Vec<String>,
+}
+
+/// Decode block indices concatenated in the given bytes.
+pub fn decode_indices(bytes: &[u8]) -> ServerResult<Vec<BlockIndex>> {
+    let mut source = source::from_slice(&bytes);
+    let mut indices = Vec::new();
+    while let Some(atom) = read_atom_head(&mut source).whatever("unable to read block index")? {
+        if !atom.is_start() || atom.tag() != tags::BLOCK_INDEX {
+            bail!("expected block index, found {atom:?}");
+        }
+        let mut decoder = Decoder::new(&mut source, 16, NumBytes::new(u64::MAX));
+        indices.push(BlockIndex::decode(&mut decoder, atom).whatever("invalid block index")?);
+    }
+    Ok(indices)
+}
+
+/// Compute the byte ranges of the bundle a client with the given block indices needs.
+pub fn plan(bundle: &Path, indices: &[BlockIndex]) -> ServerResult<DeltaPlan> {
+    let file = File::open(bundle).whatever("unable to open bundle")?;
+    let size = file.metadata().whatever("unable to get bundle size")?.len();
+    let position = Rc::new(Cell::new(0));
+    let source = CountingSource {
+        source: FileSource::from_unbuffered(file),
+        position: position.clone(),
+    };
+    let mut reader = BundleReader::start(source, None).whatever("unable to read bundle")?;
+    // Ranges of the bundle containing blocks the client already has.
+    let mut present = Vec::<Range<u64>>::new();
+    while let Some(payload) = reader.next_payload().whatever("unable to read payload")? {
+        if let Some(encoding) = &payload.header().block_encoding {
+            let client_hashes = client_hashes(encoding, indices);
+            if !client_hashes.is_empty() {
+                let data_start = position.get();
+                let blocks = encoded_blocks(encoding, payload.remaining_data().raw)
+                    .whatever("invalid block encoding")?;
+                for (hash, range) in blocks {
+                    if client_hashes.contains(hash.as_slice()) {
+                        let range = data_start + range.start..data_start + range.end;
+                        match present.last_mut() {
+                            Some(last) if last.end == range.start => last.end = range.end,
+                            _ => present.push(range),
+                        }
+                    }
+                }
+            }
+        }
+        payload.skip().whatever("unable to skip payload")?;
+    }
+    let mut ranges = Vec::new();
+    let mut download = 0;
+    let mut offset = 0;
+    for range in present.into_iter().chain([size..size]) {
+        if offset < range.start {
+            ranges.push(format!("{offset}-{}", range.start - 1));
+            download += range.start - offset;
+        }
+        offset = range.end;
+    }
+    Ok(DeltaPlan {
+        size,
+        download,
+        ranges,
+    })
+}
+
+/// Hashes of the blocks reported by the client which are compatible with the encoding.
+fn client_hashes<'i>(encoding: &BlockEncoding, indices: &'i [BlockIndex]) -> HashSet<&'i [u8]> {
+    let hash_size = encoding.hash_algorithm.hash_size();
+    indices
+        .iter()
+        .filter(|index| {
+            index.hash_algorithm == encoding.hash_algorithm && index.chunker == encoding.chunker
+        })
+        .flat_map(|index| index.block_hashes.raw.chunks_exact(hash_size))
+        .collect()
+}
+
+/// Hashes and ranges of the blocks stored in the payload data.
+///
+/// Deduplicated blocks are stored only once and ranges are relative to the start of
+/// the payload data.
+fn encoded_blocks(
+    encoding: &BlockEncoding,
+    data_size: u64,
+) -> BundleResult<Vec<(Vec<u8>, Range<u64>)>> {
+    let hashes = uncompress(encoding.compression, &encoding.block_hashes.raw)?;
+    let sizes = match &encoding.block_sizes {
+        Some(sizes) => Some(uncompress(encoding.compression, &sizes.raw)?),
+        None => None,
+    };
+    let fixed_size = match encoding.chunker {
+        ChunkerAlgorithm::Fixed { block_size_kib } => Some(u64::from(block_size_kib) * 1024),
+        ChunkerAlgorithm::Casync { .. } => None,
+    };
+    if fixed_size.is_none() && sizes.is_none() {
+        bail!("variable-size index needs block sizes");
+    }
+    let mut sizes = sizes
+        .iter()
+        .flat_map(|sizes| sizes.chunks_exact(4))
+        .map(|size| u64::from(u32::from_be_bytes(size.try_into().unwrap())));
+    let mut seen = HashSet::new();
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    for hash in hashes.chunks_exact(encoding.hash_algorithm.hash_size()) {
+        let is_fresh = seen.insert(hash);
+        if !is_fresh && encoding.deduplicated {
+            continue;
+        }
+        let Some(size) = sizes.next().or(fixed_size) else {
+            bail!("missing block size");
+        };
+        let size = size.min(data_size.saturating_sub(offset));
+        blocks.push((hash.to_vec(), offset..offset + size));
+        offset += size;
+    }
+    Ok(blocks)
+}
+
+/// Uncompress the given bytes of the block encoding.
+fn uncompress(format: Option<CompressionFormat>, bytes: &[u8]) -> BundleResult<Vec<u8>> {
+    match format {
+        None => Ok(bytes.to_vec()),
+        Some(CompressionFormat::Xz) => {
+            let mut decoder = XzDecoder::new();
+            let mut output = Vec::new();
+            decoder
+                .process(bytes, &mut output)
+                .whatever("unable to uncompress block encoding")?;
+            decoder
+                .finalize(&mut output)
+                .whatever("unable to uncompress block encoding")?;
+            Ok(output)
+        }
+    }
+}
+
+/// Bundle source keeping track of the current position in the bundle.
+struct CountingSource<S> {
+    /// Underlying source.
+    source: S,
+    /// Current position in the bundle.
+    position: Rc<Cell<u64>>,
+}
+
+impl<S: BundleSource> BundleSource for CountingSource<S> {
+    fn read(&mut self, slice: &mut [u8]) -> BundleResult<usize> {
+        let read = self.source.read(slice)?;
+        self.position.set(self.position.get() + read as u64);
+        Ok(read)
+    }
+
+    fn skip(&mut self, length: NumBytes) -> BundleResult<()> {
+        self.source.skip(length)?;
+        self.position.set(self.position.get() + length.raw);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rugix_bundle::block_encoding::block_index::{compute_block_index, BlockIndexConfig};
+    use rugix_bundle::manifest::HashAlgorithm;
+
+    use super::*;
+
+    /// Size of the payload of the test bundle.
+    const PAYLOAD_SIZE: usize = 64 * 1024;
+
+    /// Create a bundle with a single payload encoded in blocks of 4 KiB.
+    fn create_bundle(dir: &Path, payload: &[u8]) -> PathBuf {
+        let bundle_dir = dir.join("bundle");
+        std::fs::create_dir_all(bundle_dir.join("payloads")).unwrap();
+        std::fs::write(
+            bundle_dir.join("rugix-bundle.toml"),
+            "update-type = \"full\"\n\n[[payloads]]\nfilename = \"system.img\"\n\
+            [payloads.delivery]\ntype = \"slot\"\nslot = \"system\"\n\
+            [payloads.block-encoding]\nchunker = \"fixed-4\"\n",
+        )
+        .unwrap();
+        std::fs::write(bundle_dir.join("payloads/system.img"), payload).unwrap();
+        let bundle = dir.join("bundle.rugixb");
+        rugix_bundle::builder::pack(&bundle_dir, &bundle).unwrap();
+        bundle
+    }
+
+    /// Compute the block index of the given data as reported by a client.
+    fn client_index(dir: &Path, data: &[u8]) -> Vec<BlockIndex> {
+        let path = dir.join("client.img");
+        std::fs::write(&path, data).unwrap();
+        let config = BlockIndexConfig {
+            hash_algorithm: HashAlgorithm::Sha512_256,
+            chunker: "fixed-4".parse().unwrap(),
+        };
+        decode_indices(&compute_block_index(config, &path).unwrap().encode()).unwrap()
+    }
+
+    #[test]
+    fn test_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        // Blocks with distinct contents, such that no blocks are shared.
+        let payload = (0..PAYLOAD_SIZE)
+            .map(|idx| (idx / 4096 * 7 + idx % 251) as u8)
+            .collect::<Vec<_>>();
+        let bundle = create_bundle(dir.path(), &payload);
+        let size = std::fs::metadata(&bundle).unwrap().len();
+
+        // Without indices, the entire bundle is needed.
+        let delta = plan(&bundle, &[]).unwrap();
+        assert_eq!(delta.size, size);
+        assert_eq!(delta.download, size);
+        assert_eq!(delta.ranges, vec![format!("0-{}", size - 1)]);
+
+        // With all blocks present, only the rest of the bundle is needed.
+        let delta = plan(&bundle, &client_index(dir.path(), &payload)).unwrap();
+        assert_eq!(delta.download, size - PAYLOAD_SIZE as u64);
+
+        // With the first half of the blocks present, the second half is needed.
+        let half = &payload[..PAYLOAD_SIZE / 2];
+        let delta = plan(&bundle, &client_index(dir.path(), half)).unwrap();
+        assert_eq!(delta.download, size - PAYLOAD_SIZE as u64 / 2);
+        assert_eq!(delta.ranges.len(), 2);
+
+        // Indices with an incompatible chunker are ignored.
+        let mut indices = client_index(dir.path(), &payload);
+        indices[0].chunker = "fixed-8".parse().unwrap();
+        assert_eq!(plan(&bundle, &indices).unwrap().download, size);
+    }
+}
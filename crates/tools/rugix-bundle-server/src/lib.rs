@@ -0,0 +1,175 @@
+#![forbid(unsafe_code)]
+
+//! Lightweight HTTP server for self-hosting Rugix update bundles.
+//!
+//! The server exposes the bundles of a directory under `/bundles/<name>` with support
+//! for range requests, such that Rugix Ctrl can skip blocks it already has. In addition,
+//! clients can post their block indices to `/bundles/<name>/delta` to obtain the byte
+//! ranges they need to download (see [`delta`]). Access can be restricted with HTTP
+//! basic authentication.
+
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use reportify::Report;
+use rugix_common::http::parse_range;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use tracing::error;
+
+use crate::auth::Credentials;
+
+pub mod auth;
+pub mod delta;
+
+reportify::new_whatever_type! {
+    /// Error serving bundles.
+    BundleServerError
+}
+
+/// Result with [`BundleServerError`] as error type.
+pub type ServerResult<T> = Result<T, Report<BundleServerError>>;
+
+/// Maximal size of the block indices posted by a client.
+const MAX_INDICES_SIZE: usize = 128 * 1024 * 1024;
+
+/// Configuration of the server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Directory containing the bundles to serve.
+    pub bundles_dir: PathBuf,
+    /// Credentials of the users allowed to access the server.
+    ///
+    /// If empty, no authentication is required.
+    pub credentials: Vec<Credentials>,
+}
+
+/// Create the router of the server.
+pub fn router(config: ServerConfig) -> Router {
+    let config = Arc::new(config);
+    Router::new()
+        .route("/bundles/:name", get(get_bundle))
+        .route("/bundles/:name/delta", post(post_delta))
+        .layer(DefaultBodyLimit::max(MAX_INDICES_SIZE))
+        .route_layer(middleware::from_fn_with_state(config.clone(), basic_auth))
+        .with_state(config)
+}
+
+/// Reject requests without valid credentials, if authentication is required.
+async fn basic_auth(
+    State(config): State<Arc<ServerConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !config.credentials.is_empty() {
+        let authorization = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        if !auth::is_authorized(authorization, &config.credentials) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Basic realm=\"Rugix\"")],
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Serve a bundle, optionally restricted to a single byte range.
+async fn get_bundle(
+    State(config): State<Arc<ServerConfig>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(path) = bundle_path(&config, &name) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(mut file) = tokio::fs::File::open(&path).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let size = match file.metadata().await {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        _ => return StatusCode::NOT_FOUND.into_response(),
+    };
+    // Requests for multiple ranges are answered with the entire bundle.
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|range| !range.contains(','));
+    let (status, start, end) = match range.map(|range| parse_range(range, size)) {
+        None => (StatusCode::OK, 0, size),
+        Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(None) => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{size}"))],
+            )
+                .into_response();
+        }
+    };
+    if let Err(error) = file.seek(SeekFrom::Start(start)).await {
+        error!("unable to seek in bundle {name:?}: {error}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, end - start);
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {start}-{}/{size}", end - 1),
+        );
+    }
+    let body = Body::from_stream(ReaderStream::new(file.take(end - start)));
+    response.body(body).unwrap()
+}
+
+/// Compute the byte ranges of a bundle a client needs based on its block indices.
+async fn post_delta(
+    State(config): State<Arc<ServerConfig>>,
+    Path(name): Path<String>,
+    body: Bytes,
+) -> Response {
+    let Some(path) = bundle_path(&config, &name).filter(|path| path.is_file()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let result = tokio::task::spawn_blocking(move || {
+        let indices =
+            delta::decode_indices(&body).map_err(|report| (StatusCode::BAD_REQUEST, report))?;
+        delta::plan(&path, &indices).map_err(|report| (StatusCode::INTERNAL_SERVER_ERROR, report))
+    })
+    .await;
+    match result {
+        Ok(Ok(plan)) => Json(plan).into_response(),
+        Ok(Err((status, report))) => {
+            error!("unable to compute delta for bundle {name:?}:\n{report:?}");
+            status.into_response()
+        }
+        Err(error) => {
+            error!("unable to compute delta for bundle {name:?}: {error}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Path of the bundle with the given name.
+///
+/// Returns [`None`] for names which could escape the bundles directory.
+fn bundle_path(config: &ServerConfig, name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return None;
+    }
+    Some(config.bundles_dir.join(name))
+}
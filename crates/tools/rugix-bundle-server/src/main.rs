@@ -0,0 +1,52 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use clap::Parser;
+use reportify::ResultExt;
+use rugix_bundle_server::auth::load_credentials;
+use rugix_bundle_server::{router, ServerConfig, ServerResult};
+use tracing::{info, warn};
+
+#[derive(Debug, Parser)]
+#[clap(version = rugix_version::RUGIX_GIT_VERSION)]
+pub struct Args {
+    /// Directory containing the bundles to serve.
+    bundles: PathBuf,
+    /// Address to listen on.
+    #[clap(long, default_value = "0.0.0.0:8080")]
+    address: SocketAddr,
+    /// File with `<user>:<password>` pairs to require basic authentication.
+    #[clap(long)]
+    credentials: Option<PathBuf>,
+    #[clap(flatten)]
+    logging: si_observability::clap4::LoggingArgs,
+}
+
+#[tokio::main]
+async fn main() -> ServerResult<()> {
+    let args = Args::parse();
+    let _guard = si_observability::Initializer::new("RUGIX")
+        .apply(&args.logging)
+        .init();
+    let credentials = match &args.credentials {
+        Some(path) => load_credentials(path)?,
+        None => {
+            warn!("serving bundles without authentication");
+            Vec::new()
+        }
+    };
+    info!(
+        "serving bundles from {:?} on {}",
+        args.bundles, args.address
+    );
+    let app = router(ServerConfig {
+        bundles_dir: args.bundles,
+        credentials,
+    });
+    let listener = tokio::net::TcpListener::bind(args.address)
+        .await
+        .whatever("unable to bind server")?;
+    axum::serve(listener, app)
+        .await
+        .whatever("unable to serve bundles")
+}
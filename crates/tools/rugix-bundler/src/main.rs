@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -14,15 +15,20 @@ use rugix_bundle::manifest::{
     DeltaEncodingInput, HashAlgorithm, XzCompression,
 };
 use rugix_bundle::reader::BundleReader;
-use rugix_bundle::source::FileSource;
+use rugix_bundle::sigstore::SigstoreTrust;
+use rugix_bundle::source::{BundleSource, FileSource};
 use rugix_bundle::xdelta::xdelta_compress;
-use rugix_bundle::{add_bundle_signature, bundle_hash, format, signed_metadata, BundleResult};
+use rugix_bundle::{
+    add_bundle_signature, add_bundle_sigstore_bundle, bundle_hash, format, signed_metadata,
+    sigstore, verify_signatures, BundleResult,
+};
 use rugix_chunker::ChunkerAlgorithm;
 use si_crypto_hashes::HashDigest;
-use tracing::{info, Level};
-use xscript::{cmd_os, run, ParentEnv, Run};
+use tracing::{info, warn, Level};
+use xscript::{cmd_os, ParentEnv, Run};
 
 mod simulation;
+mod verify;
 
 #[derive(Debug, Parser)]
 #[clap(version = rugix_version::RUGIX_GIT_VERSION)]
@@ -47,6 +53,8 @@ pub enum Cmd {
     Delta(DeltaCmd),
     /// Inspect an update bundle.
     Inspect(InspectCmd),
+    /// Verify a bundle and print a detailed trust report.
+    Verify(verify::VerifyCmd),
     /// Manipulate and inspect signatures.
     #[clap(subcommand)]
     Signatures(SignaturesCmd),
@@ -95,6 +103,27 @@ pub enum SignaturesCmd {
         /// Output path.
         out: PathBuf,
     },
+    /// Sign a bundle with Sigstore using `cosign`.
+    ///
+    /// Without a key, keyless signing based on an OIDC identity is used.
+    SigstoreSign {
+        /// Private key to sign with (also accepts `cosign` key references).
+        #[clap(long)]
+        key: Option<PathBuf>,
+        /// Bundle to sign.
+        bundle: PathBuf,
+        /// Output path.
+        out: PathBuf,
+    },
+    /// Add a Sigstore bundle to a bundle.
+    AddSigstore {
+        /// Bundle to add the Sigstore bundle to.
+        bundle: PathBuf,
+        /// Sigstore bundle created by signing the bundle metadata.
+        sigstore_bundle: PathBuf,
+        /// Output bundle.
+        out: PathBuf,
+    },
     /// Verify that the bundle has been signed using the given certificate.
     Verify {
         /// Bundle to verify.
@@ -149,6 +178,15 @@ pub struct DeltaCmd {
 
 #[derive(Debug, Parser)]
 pub struct UnpackCmd {
+    /// Verify the bundle header against the given hash.
+    #[clap(long)]
+    verify_bundle: Option<HashDigest>,
+    /// Verify that the bundle has been signed using the given root certificate.
+    #[clap(long)]
+    verify_signature: Option<PathBuf>,
+    /// Extract the payloads to files named after their target slots.
+    #[clap(long)]
+    slots: bool,
     /// Path to the bundle.
     src: PathBuf,
     /// Output directory.
@@ -177,7 +215,22 @@ fn main() -> BundleResult<()> {
             rugix_bundle::builder::pack(&create_cmd.src, &create_cmd.dst)?;
         }
         Cmd::Unpack(cmd) => {
-            unpack(&cmd.src, &cmd.out)?;
+            let source = FileSource::from_unbuffered(
+                File::open(&cmd.src).whatever("unable to open bundle")?,
+            );
+            let reader = BundleReader::start(source, cmd.verify_bundle)?;
+            if let Some(cert) = &cmd.verify_signature {
+                verify_signatures(
+                    &reader,
+                    std::slice::from_ref(cert),
+                    &SigstoreTrust::default(),
+                )?;
+            }
+            if cmd.slots {
+                unpack_slots(reader, &cmd.out)?;
+            } else {
+                unpack_reader(reader, &cmd.out)?;
+            }
         }
         Cmd::Extract(unpack_cmd) => {
             let source = FileSource::from_unbuffered(File::open(&unpack_cmd.bundle).unwrap());
@@ -237,6 +290,17 @@ fn main() -> BundleResult<()> {
                         )
                     );
                 }
+                if let Some(metadata_type) = &entry.type_metadata {
+                    println!(
+                        "  {idx}: metadata={:?} slot={:?} file={}",
+                        metadata_type.name,
+                        metadata_type.slot,
+                        HashDigest::new_unchecked(
+                            reader.header().hash_algorithm,
+                            &entry.file_hash.raw
+                        )
+                    );
+                }
             }
         }
         Cmd::Delta(cmd) => {
@@ -327,6 +391,9 @@ fn main() -> BundleResult<()> {
             .unwrap();
             rugix_bundle::builder::pack(new_dir.path(), &cmd.out)?;
         }
+        Cmd::Verify(cmd) => {
+            verify::run(&cmd)?;
+        }
         Cmd::Simulator(cmd) => {
             simulation::run(&cmd);
         }
@@ -369,6 +436,13 @@ fn main() -> BundleResult<()> {
                     for (idx, signature) in signatures.cms_signatures.iter().enumerate() {
                         println!("CMS Signature {} (length={})", idx, signature.raw.len());
                     }
+                    for (idx, sigstore_bundle) in signatures.sigstore_bundles.iter().enumerate() {
+                        println!(
+                            "Sigstore Bundle {} (length={})",
+                            idx,
+                            sigstore_bundle.raw.len()
+                        );
+                    }
                 } else {
                     println!("No signatures found");
                 }
@@ -419,59 +493,24 @@ fn main() -> BundleResult<()> {
                     std::fs::read(&signed_metadata_cms).whatever("unable to read signature")?;
                 add_bundle_signature(&bundle, signature, &out)?;
             }
+            SignaturesCmd::SigstoreSign { key, bundle, out } => {
+                let metadata = signed_metadata(&bundle)?;
+                let sigstore_bundle = sigstore::sign(&metadata, key.as_deref())?;
+                add_bundle_sigstore_bundle(&bundle, sigstore_bundle, &out)?;
+            }
+            SignaturesCmd::AddSigstore {
+                bundle,
+                sigstore_bundle,
+                out,
+            } => {
+                let sigstore_bundle =
+                    std::fs::read(sigstore_bundle).whatever("unable to read Sigstore bundle")?;
+                add_bundle_sigstore_bundle(&bundle, sigstore_bundle, &out)?;
+            }
             SignaturesCmd::Verify { bundle, cert } => {
                 let source = FileSource::from_unbuffered(File::open(&bundle).unwrap());
                 let reader = BundleReader::start(source, None)?;
-                let Some(signatures) = reader.signatures() else {
-                    bail!("no signatures found");
-                };
-                let mut found_valid_signature = false;
-                for signature in signatures.cms_signatures.iter() {
-                    let tempdir =
-                        tempfile::tempdir().whatever("unable to create temporary directory")?;
-                    let tempdir_path = tempdir.path();
-                    let signed_metadata_raw = tempdir_path.join("signed-metadata.raw");
-                    let signed_metadata_cms = tempdir_path.join("signed-metadata.cms");
-                    std::fs::write(&signed_metadata_cms, &signature.raw)
-                        .whatever("unable to write CMS signature")?;
-                    if let Err(error) = run!([
-                        "openssl",
-                        "cms",
-                        "-verify",
-                        "-in",
-                        &signed_metadata_cms,
-                        "-inform",
-                        "DER",
-                        "-out",
-                        &signed_metadata_raw,
-                        // Do not load OS default certificates.
-                        "-no-CAfile",
-                        "-no-CApath",
-                        "-no-CAstore",
-                        // Non-zero exit code on verification failure.
-                        "-verify_retcode",
-                        // Load provided certificate.
-                        "-CAfile",
-                        &cert,
-                    ]) {
-                        println!("{error}");
-                        continue;
-                    }
-                    let signed_metadata = std::fs::read(&signed_metadata_raw)
-                        .whatever("unable to read signed metadata")?;
-                    let signed_metadata = decode_slice::<format::SignedMetadata>(&signed_metadata)
-                        .whatever("unable to decode signed metadata")?;
-                    if signed_metadata.header_hash
-                        == reader.header_hash(signed_metadata.header_hash.algorithm())
-                    {
-                        found_valid_signature = true;
-                        println!("Found valid signature!");
-                        break;
-                    }
-                }
-                if !found_valid_signature {
-                    bail!("no valid signature found");
-                }
+                verify_signatures(&reader, &[cert], &SigstoreTrust::default())?;
             }
         },
     }
@@ -479,9 +518,13 @@ fn main() -> BundleResult<()> {
 }
 
 pub fn unpack(src: &Path, dst: &Path) -> BundleResult<()> {
-    std::fs::create_dir_all(dst).unwrap();
     let source = FileSource::from_unbuffered(File::open(&src).unwrap());
-    let mut reader = BundleReader::start(source, None)?;
+    unpack_reader(BundleReader::start(source, None)?, dst)
+}
+
+/// Unpack a bundle into a bundle directory.
+fn unpack_reader<S: BundleSource>(mut reader: BundleReader<S>, dst: &Path) -> BundleResult<()> {
+    std::fs::create_dir_all(dst).unwrap();
     let Some(manifest) = &reader.header().manifest else {
         panic!("unpacking requires a manifest");
     };
@@ -496,18 +539,85 @@ pub fn unpack(src: &Path, dst: &Path) -> BundleResult<()> {
     while let Some(payload_reader) = reader.next_payload()? {
         let filename = &manifest.payloads[payload_reader.idx()].filename;
         info!(%filename, "unpacking bundle payload");
+        let payload_path = payload_dir.join(filename);
+        if let Some(parent) = payload_path.parent() {
+            std::fs::create_dir_all(parent).whatever("unable to create payload directory")?;
+        }
         let target = std::fs::OpenOptions::new()
             .create(true)
             .truncate(true)
             .read(true)
             .write(true)
-            .open(payload_dir.join(filename))
+            .open(payload_path)
             .whatever("unable to open payload target")?;
         payload_reader.decode_into(target, None, &mut |_| {})?;
     }
     Ok(())
 }
 
+/// Extract the payloads of a bundle to files named after their target slots.
+///
+/// Payloads are verified against their hashes while being extracted.
+fn unpack_slots<S: BundleSource>(mut reader: BundleReader<S>, dst: &Path) -> BundleResult<()> {
+    let filenames = reader
+        .header()
+        .payload_index
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| slot_payload_filename(idx, entry))
+        .collect::<Vec<_>>();
+    // Check for conflicts before extracting anything.
+    let mut seen = HashSet::new();
+    for filename in &filenames {
+        if !seen.insert(filename) {
+            bail!("multiple payloads target {filename:?} without distinct variants");
+        }
+    }
+    std::fs::create_dir_all(dst).whatever("unable to create output directory")?;
+    while let Some(payload_reader) = reader.next_payload()? {
+        let idx = payload_reader.idx();
+        let filename = &filenames[idx];
+        if payload_reader.entry().delta_encoding.is_some() {
+            // The original payload requires the inputs of the delta encoding.
+            warn!(%filename, "payload is delta-encoded, extracting the patch");
+        }
+        info!(idx, %filename, "extracting bundle payload");
+        let target = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(dst.join(filename))
+            .whatever("unable to open payload target")?;
+        let decoded = payload_reader.decode_into(target, None, &mut |_| {})?;
+        println!("{filename}: {} ({})", decoded.size, decoded.hash);
+    }
+    Ok(())
+}
+
+/// Name of the file to extract a payload to, based on its target slot and variant.
+fn slot_payload_filename(idx: usize, entry: &format::PayloadEntry) -> String {
+    let mut filename = match (&entry.type_slot, &entry.type_metadata) {
+        (Some(slot_type), _) => slot_type.slot.replace('/', "_"),
+        (_, Some(metadata_type)) => format!(
+            "{}-{}",
+            metadata_type.slot.replace('/', "_"),
+            metadata_type.name.replace('/', "_")
+        ),
+        _ => format!("payload-{idx}"),
+    };
+    if let Some(variant) = &entry.variant {
+        filename.push('-');
+        filename.push_str(&variant.replace('/', "_"));
+    }
+    if entry.delta_encoding.is_some() {
+        filename.push_str(".xdelta");
+    } else if entry.type_slot.is_some() {
+        filename.push_str(".img");
+    }
+    filename
+}
+
 #[tracing::instrument(level = Level::DEBUG)]
 pub fn hash_file(algorithm: HashAlgorithm, path: &Path) -> HashDigest {
     let mut file = std::fs::File::open(&path).unwrap();
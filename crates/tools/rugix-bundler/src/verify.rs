@@ -0,0 +1,299 @@
+//! Verification of bundles with a detailed trust report.
+//!
+//! The checks mirror the checks performed by Rugix Ctrl when installing a bundle with
+//! `--verify-bundle` and `--verify-signature`, such that bundles can be checked before
+//! rolling them out to devices.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use clap::Parser;
+use cms::cert::x509::der::oid::db::rfc5911::ID_SIGNED_DATA;
+use cms::cert::x509::der::Decode;
+use cms::cert::CertificateChoices;
+use reportify::{bail, ResultExt};
+use rugix_bundle::format::stlv::{read_atom_head, AtomHead, Tag};
+use rugix_bundle::format::{tags, Bytes};
+use rugix_bundle::reader::BundleReader;
+use rugix_bundle::sigstore::{self, SigstoreTrust};
+use rugix_bundle::source::{BundleSource, FileSource};
+use rugix_bundle::BundleResult;
+use si_crypto_hashes::HashDigest;
+
+#[derive(Debug, Parser)]
+pub struct VerifyCmd {
+    /// Expected hash of the bundle header.
+    #[clap(long)]
+    verify_bundle: Option<HashDigest>,
+    /// Root certificate or directory of root certificates to verify signatures with.
+    #[clap(long = "root-cert")]
+    root_certs: Vec<PathBuf>,
+    /// Public key to verify Sigstore signatures with.
+    #[clap(long)]
+    sigstore_key: Option<PathBuf>,
+    /// Identity of the signer to verify keyless Sigstore signatures with.
+    #[clap(long)]
+    sigstore_identity: Option<String>,
+    /// OIDC issuer of the identity of the signer of keyless Sigstore signatures.
+    #[clap(long)]
+    sigstore_oidc_issuer: Option<String>,
+    /// Sigstore trusted root to verify keyless Sigstore signatures with.
+    #[clap(long)]
+    sigstore_trusted_root: Option<PathBuf>,
+    /// Do not verify the payloads, i.e., only verify the header and signatures.
+    #[clap(long)]
+    skip_payloads: bool,
+    /// Bundle to verify.
+    bundle: PathBuf,
+}
+
+/// Verify a bundle and print a report of the verification.
+pub fn run(cmd: &VerifyCmd) -> BundleResult<()> {
+    // Reasons why Rugix Ctrl would reject the bundle.
+    let mut problems = Vec::<String>::new();
+
+    println!("Bundle: {}", cmd.bundle.display());
+    let unknown_tags = scan_unknown_tags(&cmd.bundle)?;
+    if unknown_tags.is_empty() {
+        println!("Unknown Tags: none");
+    } else {
+        println!("Unknown Tags:");
+        for tag in &unknown_tags {
+            if tags::is_required(*tag) {
+                println!("  {tag} (required)");
+                problems.push(format!("unknown required tag {tag}"));
+            } else {
+                println!("  {tag} (optional, ignored)");
+            }
+        }
+    }
+
+    let source =
+        FileSource::from_unbuffered(File::open(&cmd.bundle).whatever("unable to open bundle")?);
+    let mut reader = match BundleReader::start(source, None) {
+        Ok(reader) => reader,
+        Err(report) => {
+            println!("Header: invalid ({report})");
+            bail!("bundle rejected: invalid header");
+        }
+    };
+    let header = reader.header();
+    let hash_algorithm = header.hash_algorithm;
+    println!("Hash Algorithm: {hash_algorithm:?}");
+    println!("Header Hash: {}", reader.header_hash(hash_algorithm));
+    println!(
+        "Update Type: {}",
+        if header.is_incremental {
+            "incremental"
+        } else {
+            "full"
+        }
+    );
+    match &cmd.verify_bundle {
+        Some(expected) if reader.header_hash(expected.algorithm()) == *expected => {
+            println!("Header Check: hash matches {expected}");
+        }
+        Some(expected) => {
+            println!("Header Check: hash does not match {expected}");
+            problems.push("header hash mismatch".to_owned());
+        }
+        None => println!("Header Check: not requested"),
+    }
+
+    let sigstore_trust = SigstoreTrust {
+        key: cmd.sigstore_key.clone(),
+        certificate_identity: cmd.sigstore_identity.clone(),
+        certificate_oidc_issuer: cmd.sigstore_oidc_issuer.clone(),
+        trusted_root: cmd.sigstore_trusted_root.clone(),
+    };
+    check_signatures(&reader, &cmd.root_certs, &sigstore_trust, &mut problems)?;
+
+    println!("Payloads:");
+    while let Some(payload) = reader.next_payload()? {
+        let entry = payload.entry();
+        let mut description = match (&entry.type_slot, &entry.type_execute) {
+            (Some(slot_type), _) => format!("slot={:?}", slot_type.slot),
+            (_, Some(execute_type)) => format!("execute={:?}", execute_type.handler.join(" ")),
+            _ => match &entry.type_metadata {
+                Some(metadata_type) => format!(
+                    "metadata={:?} slot={:?}",
+                    metadata_type.name, metadata_type.slot
+                ),
+                None => "unknown delivery".to_owned(),
+            },
+        };
+        if let Some(variant) = &entry.variant {
+            description.push_str(&format!(" variant={variant:?}"));
+        }
+        if entry.delta_encoding.is_some() {
+            description.push_str(" delta-encoded");
+        }
+        let file_hash = HashDigest::new_unchecked(hash_algorithm, &entry.file_hash.raw);
+        println!("  {}: {description}", payload.idx());
+        println!("    Hash: {file_hash}");
+        if cmd.skip_payloads {
+            println!("    Status: not verified");
+            payload.skip()?;
+            continue;
+        }
+        let idx = payload.idx();
+        // Deduplicated blocks are read back from the target, so we need a real file.
+        let target = tempfile::tempfile().whatever("unable to create temporary file")?;
+        match payload.decode_into(target, None, &mut |_| {}) {
+            Ok(decoded) => println!("    Status: valid ({})", decoded.size),
+            Err(report) => {
+                println!("    Status: invalid ({report})");
+                problems.push(format!("invalid payload {idx}"));
+                // The position in the bundle is unknown after an error.
+                break;
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        println!("Result: accepted");
+        Ok(())
+    } else {
+        println!("Result: rejected");
+        bail!("bundle rejected: {}", problems.join(", "));
+    }
+}
+
+/// Check the signatures of the bundle and print the results.
+fn check_signatures<S: BundleSource>(
+    reader: &BundleReader<S>,
+    root_certs: &[PathBuf],
+    sigstore_trust: &SigstoreTrust,
+    problems: &mut Vec<String>,
+) -> BundleResult<()> {
+    let is_trust_configured = !root_certs.is_empty() || sigstore_trust.is_configured();
+    let Some(signatures) = reader.signatures() else {
+        println!("Signatures: none");
+        if is_trust_configured {
+            problems.push("no signatures found".to_owned());
+        }
+        return Ok(());
+    };
+    println!("Signatures:");
+    if root_certs.len() > 1 {
+        println!("  Note: Rugix Ctrl does not yet support multiple root certificates");
+        problems.push("multiple root certificates".to_owned());
+    }
+    let mut found_valid_signature = false;
+    for (idx, signature) in signatures.cms_signatures.iter().enumerate() {
+        println!("  {idx}: CMS signature ({} bytes)", signature.raw.len());
+        print_certificates(signature);
+        if root_certs.is_empty() {
+            println!("    Status: not verified (no root certificates given)");
+            continue;
+        }
+        match rugix_bundle::cms::verify(reader, &signature.raw, root_certs)? {
+            Ok(signer_pem) => {
+                found_valid_signature = true;
+                println!("    Signer: {}", signer_subject(&signer_pem));
+                println!("    Status: valid");
+            }
+            Err(reason) => println!("    Status: invalid ({reason})"),
+        }
+    }
+    let signed_metadata =
+        rugix_bundle::encode_signed_metadata(reader.header_hash(reader.header().hash_algorithm));
+    for (idx, sigstore_bundle) in signatures.sigstore_bundles.iter().enumerate() {
+        println!(
+            "  {idx}: Sigstore bundle ({} bytes)",
+            sigstore_bundle.raw.len()
+        );
+        if !sigstore_trust.is_configured() {
+            println!("    Status: not verified (no Sigstore key or identity given)");
+            continue;
+        }
+        match sigstore::verify(sigstore_trust, &signed_metadata, &sigstore_bundle.raw) {
+            Ok(()) => {
+                found_valid_signature = true;
+                println!("    Status: valid");
+            }
+            Err(report) => println!("    Status: {report}"),
+        }
+    }
+    if is_trust_configured && !found_valid_signature {
+        problems.push("no valid signature found".to_owned());
+    }
+    Ok(())
+}
+
+/// Print the certificates embedded into a CMS signature.
+fn print_certificates(signature: &Bytes) {
+    let signed_data = cms::content_info::ContentInfo::from_der(&signature.raw)
+        .ok()
+        .filter(|content_info| content_info.content_type == ID_SIGNED_DATA)
+        .and_then(|content_info| {
+            content_info
+                .content
+                .decode_as::<cms::signed_data::SignedData>()
+                .ok()
+        });
+    let Some(signed_data) = signed_data else {
+        println!("    Certificates: unable to decode signature");
+        return;
+    };
+    let Some(certificates) = &signed_data.certificates else {
+        println!("    Certificates: none embedded");
+        return;
+    };
+    println!("    Certificates:");
+    for certificate in certificates.0.iter() {
+        if let CertificateChoices::Certificate(certificate) = certificate {
+            println!(
+                "      {} (issued by {})",
+                certificate.tbs_certificate.subject, certificate.tbs_certificate.issuer
+            );
+        }
+    }
+}
+
+/// Subject of the given PEM-encoded signer certificate.
+fn signer_subject(signer_pem: &[u8]) -> String {
+    Command::new("openssl")
+        .args(["x509", "-noout", "-subject", "-issuer", "-enddate"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .expect("stdin should be piped")
+                .write_all(signer_pem)?;
+            child.wait_with_output()
+        })
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+/// Scan the structure of the bundle for tags unknown to this version.
+fn scan_unknown_tags(bundle: &Path) -> BundleResult<Vec<Tag>> {
+    let mut source =
+        FileSource::from_unbuffered(File::open(bundle).whatever("unable to open bundle")?);
+    let mut unknown = Vec::new();
+    while let Some(head) = read_atom_head(&mut source)? {
+        let tag = head.tag();
+        if !tags::is_know(tag) && !unknown.contains(&tag) {
+            unknown.push(tag);
+        }
+        if let AtomHead::Value { length, .. } = head {
+            source.skip(length)?;
+        }
+    }
+    Ok(unknown)
+}
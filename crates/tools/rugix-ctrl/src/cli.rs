@@ -5,13 +5,15 @@ use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Child;
 
-use rugix_bundle::format::decode::decode_slice;
+use byte_calc::NumBytes;
+use indexmap::IndexMap;
 use rugix_bundle::manifest::ChunkerAlgorithm;
 use rugix_bundle::reader::block_provider::StoredBlockProvider;
 use rugix_bundle::reader::{DecodedPayloadInfo, PayloadTarget};
+use rugix_bundle::sigstore::SigstoreTrust;
 use rugix_bundle::source::{BundleSource, ReaderSource, SkipRead};
 use rugix_bundle::xdelta::xdelta_decompress;
-use rugix_bundle::{format, BUNDLE_MAGIC};
+use rugix_bundle::BUNDLE_MAGIC;
 use rugix_common::pipe::{buffered_pipe, PipeWriter};
 use rugix_common::slots::SlotState;
 use rugix_hooks::{HooksLoader, RunOptions};
@@ -23,16 +25,22 @@ use crate::system::slots::SlotKind;
 use crate::system::{System, SystemResult};
 use clap::{Parser, ValueEnum};
 use reportify::{bail, whatever, ErrorExt, ResultExt};
+use rugix_common::disk::gpt;
 use rugix_common::disk::stream::ImgStream;
+use rugix_common::image_writer::ImageWriter;
 use rugix_common::maybe_compressed::{MaybeCompressed, PeekReader};
 use rugix_common::stream_hasher::StreamHasher;
-use xscript::{cmd_os, vars, ParentEnv, Run, Vars};
+use xscript::{vars, Vars};
 
+use crate::config::output::{StateStatusOutput, UsageOutput};
+use crate::config::state::OverlayConfig;
 use crate::http_source::HttpSource;
 use crate::overlay::overlay_dir;
 use crate::slot_db::{self, BlockProvider};
-use crate::system_state;
+use crate::state::load_state_config;
+use crate::swupdate_progress::{ProgressServer, UpdateSource};
 use crate::utils::{clear_flag, reboot, set_flag, DEFERRED_SPARE_REBOOT_FLAG};
+use crate::{first_boot, flash, init, system_state, usage};
 
 fn create_rugix_state_directory() -> SystemResult<()> {
     fs::create_dir_all("/run/rugix/state/.rugix")
@@ -63,6 +71,17 @@ pub fn main() -> SystemResult<()> {
     rugix_cli::CliBuilder::new().init();
 
     let args = Args::parse();
+    // Flashing does not require a Rugix-managed system.
+    if let Command::Flash {
+        image,
+        device,
+        verify,
+        sync_interval,
+        force,
+    } = &args.command
+    {
+        return flash::flash(image, device, *verify, *sync_interval, *force);
+    }
     let system = System::initialize()?;
     match &args.command {
         Command::State(state_cmd) => match state_cmd {
@@ -78,6 +97,48 @@ pub fn main() -> SystemResult<()> {
                 set_rugix_state_flag("reset-state")?;
                 reboot()?;
             }
+            StateCommand::Status { json } => {
+                let config = load_state_config()?;
+                let thresholds = usage::Thresholds::from_config(config.usage.as_ref());
+                let overlay = match config.overlay.as_ref().unwrap_or(&OverlayConfig::Discard) {
+                    OverlayConfig::Persist => "persist",
+                    OverlayConfig::Discard => "discard",
+                    OverlayConfig::InMemory => "in-memory",
+                    OverlayConfig::Disabled => "disabled",
+                };
+                eprintln!("Overlay: {overlay}");
+                let mut output = StateStatusOutput::new(overlay.to_owned(), IndexMap::new());
+                for usage in usage::measure(&config)? {
+                    let level = thresholds.level(&usage);
+                    eprintln!(
+                        "Usage of {}: {}% ({} of {} used, {} available), {}",
+                        usage.name,
+                        usage.percent(),
+                        NumBytes::new(usage.used),
+                        NumBytes::new(usage.used + usage.available),
+                        NumBytes::new(usage.available),
+                        level.as_str(),
+                    );
+                    output.usage.insert(
+                        usage.name.to_owned(),
+                        UsageOutput::new(
+                            usage.path.to_owned(),
+                            usage.size,
+                            usage.used,
+                            usage.available,
+                            usage.percent(),
+                            level.as_str().to_owned(),
+                        ),
+                    );
+                }
+                if rugix_cli::stdout_is_piped() || *json {
+                    serde_json::to_writer(std::io::stdout(), &output)
+                        .whatever("unable to write state status to stdout")?;
+                }
+            }
+            StateCommand::Monitor => {
+                usage::monitor(&load_state_config()?)?;
+            }
             StateCommand::Overlay(overlay_cmd) => match overlay_cmd {
                 OverlayCommand::ForcePersist { persist } => match persist {
                     Boolean::True => {
@@ -101,6 +162,12 @@ pub fn main() -> SystemResult<()> {
                     boot_group,
                     verify_signature,
                     root_cert,
+                    sigstore_key,
+                    sigstore_identity,
+                    sigstore_oidc_issuer,
+                    sigstore_trusted_root,
+                    verify_write,
+                    sync_interval,
                 } => {
                     let check_hash = check_hash.as_deref()
                             .map(|encoded_hash| -> SystemResult<ImageHash> {
@@ -165,7 +232,21 @@ pub fn main() -> SystemResult<()> {
                         }
                     }
 
-                    let should_reboot = install_update_stream(
+                    let progress_server = system
+                        .config()
+                        .swupdate_progress
+                        .as_ref()
+                        .map(|config| {
+                            let source = if image.starts_with("http") {
+                                UpdateSource::Downloader
+                            } else {
+                                UpdateSource::Local
+                            };
+                            ProgressServer::start(config, source)
+                        })
+                        .transpose()?;
+
+                    let install_result = install_update_stream(
                         &system,
                         image,
                         check_hash,
@@ -173,7 +254,25 @@ pub fn main() -> SystemResult<()> {
                         boot_group.as_ref(),
                         *verify_signature,
                         root_cert,
-                    )?;
+                        &SigstoreTrust {
+                            key: sigstore_key.clone(),
+                            certificate_identity: sigstore_identity.clone(),
+                            certificate_oidc_issuer: sigstore_oidc_issuer.clone(),
+                            trusted_root: sigstore_trusted_root.clone(),
+                        },
+                        &WriteOptions {
+                            verify: *verify_write,
+                            sync_interval: *sync_interval,
+                        },
+                        progress_server.as_ref(),
+                    );
+                    if let Some(progress_server) = &progress_server {
+                        progress_server.finish(match &install_result {
+                            Ok(_) => Ok(()),
+                            Err(error) => Err(format!("{error:?}")),
+                        });
+                    }
+                    let should_reboot = install_result?;
 
                     hooks
                         .run_hooks("post-update", hook_vars.clone(), &Default::default())
@@ -230,6 +329,24 @@ pub fn main() -> SystemResult<()> {
                         }
                     );
                 }
+                if let Some(root) = system
+                    .root()
+                    .as_ref()
+                    .filter(|root| root.table.as_ref().is_some_and(|table| table.is_gpt()))
+                {
+                    match gpt::check_backup_header(root.device.path()) {
+                        Ok(status) if status.needs_repair() => {
+                            warn!(
+                                "backup GPT header of {:?} is {status}, repair it with `rugix-ctrl system repair-gpt`",
+                                root.device.path()
+                            );
+                        }
+                        Ok(_) => { /* nothing to do */ }
+                        Err(error) => {
+                            warn!("unable to check backup GPT header: {error:?}");
+                        }
+                    }
+                }
                 if rugix_cli::stdout_is_piped() || *json {
                     serde_json::to_writer(std::io::stdout(), &output)
                         .whatever("unable to write system info to stdout")?;
@@ -262,6 +379,70 @@ pub fn main() -> SystemResult<()> {
                 }
                 reboot()?;
             }
+            SystemCommand::FirstBoot => {
+                first_boot::run_first_boot_scripts()?;
+            }
+            SystemCommand::Sbom => {
+                let Some(active_entry) = system.active_boot_entry() else {
+                    bail!("unable to determine active boot group");
+                };
+                let Some(slot) = system.boot_entries()[active_entry].get_slot("system") else {
+                    bail!("active boot group does not have a system slot");
+                };
+                let slot = &system.slots()[slot];
+                let sbom_path = slot_db::metadata_path(slot.name(), "sbom")?;
+                let mut sbom = match File::open(&sbom_path) {
+                    Ok(sbom) => sbom,
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                        bail!(
+                            "no SBOM stored for slot {:?}, the slot has not been installed from a bundle with an SBOM",
+                            slot.name()
+                        );
+                    }
+                    Err(error) => {
+                        return Err(error.whatever("unable to open SBOM"));
+                    }
+                };
+                io::copy(&mut sbom, &mut io::stdout())
+                    .whatever("unable to write SBOM to stdout")?;
+            }
+            SystemCommand::RepairGpt { check } => {
+                let Some(root) = system.root() else {
+                    bail!("unable to determine root device");
+                };
+                if !root.table.as_ref().is_some_and(|table| table.is_gpt()) {
+                    bail!("root device {:?} does not have a GPT", root.device.path());
+                }
+                let status = gpt::check_backup_header(root.device.path())
+                    .whatever("unable to check backup GPT header")?;
+                eprintln!("Backup GPT header of {:?} is {status}.", root.device.path());
+                if status.needs_repair() {
+                    if *check {
+                        bail!("backup GPT header needs to be repaired");
+                    }
+                    gpt::repair_backup_header(root.device.path())
+                        .whatever("unable to repair backup GPT header")?;
+                    eprintln!("Repaired backup GPT header.");
+                }
+            }
+            SystemCommand::CheckLayout { config } => {
+                let Some(root) = system.root() else {
+                    bail!("unable to determine root device");
+                };
+                let config = match config {
+                    Some(path) => {
+                        // Only the implicit default path falls back to the default layout.
+                        if !path.exists() {
+                            bail!("bootstrapping configuration {path:?} does not exist");
+                        }
+                        init::load_bootstrap_config(path)?
+                    }
+                    None => init::load_bootstrap_config(Path::new(init::BOOTSTRAP_CONFIG_PATH))?,
+                };
+                if !init::check_layout(&config, root, &system.disks)? {
+                    bail!("partition layout does not match the bootstrapping configuration");
+                }
+            }
         },
         Command::Unstable(command) => match command {
             UnstableCommand::SetDeferredSpareReboot { value } => match value {
@@ -273,6 +454,8 @@ pub fn main() -> SystemResult<()> {
                 println!("{:#?}", system.config());
                 println!("Root:");
                 println!("{:#?}", system.root());
+                println!("Disks:");
+                println!("{:#?}", system.disks);
                 println!("Slots:");
                 for (_, slot) in system.slots().iter() {
                     println!("{:#?}", slot)
@@ -281,6 +464,7 @@ pub fn main() -> SystemResult<()> {
                 println!("{:#?}", system.boot_entries());
             }
         },
+        Command::Flash { .. } => unreachable!("flash command is handled before"),
         Command::Slots(slots_command) => match slots_command {
             SlotsCommand::Inspect { slot } => {
                 let indices = slot_db::get_stored_indices(slot)?;
@@ -461,6 +645,9 @@ fn install_update_stream(
     boot_group: Option<&(BootGroupIdx, &BootGroup)>,
     verify_signature: bool,
     root_cert: &[PathBuf],
+    sigstore_trust: &SigstoreTrust,
+    write_options: &WriteOptions,
+    progress_server: Option<&ProgressServer>,
 ) -> SystemResult<UpdateRebootType> {
     if image.starts_with("http") {
         if check_hash.is_some() {
@@ -474,6 +661,9 @@ fn install_update_stream(
             boot_group,
             verify_signature,
             root_cert,
+            sigstore_trust,
+            write_options,
+            progress_server,
         )?;
         let stats = bundle_source.get_download_stats();
         info!(
@@ -514,6 +704,9 @@ fn install_update_stream(
             boot_group,
             verify_signature,
             root_cert,
+            sigstore_trust,
+            write_options,
+            progress_server,
         );
     }
     if verify_bundle.is_some() {
@@ -573,20 +766,16 @@ fn install_update_stream(
         );
         match partition_idx {
             1 => {
-                io::copy(
-                    &mut partition,
-                    &mut fs::File::create(raw_boot_slot.device())
-                        .whatever("error opening boot partition file")?,
-                )
-                .whatever("error copying boot partition")?;
+                let _writable = raw_boot_slot.make_writable()?;
+                let mut target = write_options.writer(raw_boot_slot.open_writable()?)?;
+                io::copy(&mut partition, &mut target).whatever("error copying boot partition")?;
+                target.finish().whatever("error copying boot partition")?;
             }
             3 => {
-                io::copy(
-                    &mut partition,
-                    &mut fs::File::create(raw_system_slot.device())
-                        .whatever("error opening system partition file")?,
-                )
-                .whatever("error copying system partition")?;
+                let _writable = raw_system_slot.make_writable()?;
+                let mut target = write_options.writer(raw_system_slot.open_writable()?)?;
+                io::copy(&mut partition, &mut target).whatever("error copying system partition")?;
+                target.finish().whatever("error copying system partition")?;
             }
             _ => { /* Nothing to do! */ }
         }
@@ -647,75 +836,45 @@ fn install_update_bundle<R: BundleSource>(
     boot_group: Option<&(BootGroupIdx, &BootGroup)>,
     verify_signature: bool,
     root_certs: &[PathBuf],
+    sigstore_trust: &SigstoreTrust,
+    write_options: &WriteOptions,
+    progress_server: Option<&ProgressServer>,
 ) -> SystemResult<UpdateRebootType> {
     let mut bundle_reader =
         rugix_bundle::reader::BundleReader::start(bundle_source, verify_bundle.clone())
             .whatever("unable to read bundle")?;
 
     if verify_signature {
-        let Some(signatures) = bundle_reader.signatures() else {
-            bail!("no signatures found in bundle");
-        };
-        if root_certs.is_empty() {
-            bail!("no root certificates provided for signature verification");
-        }
-        if root_certs.len() > 1 {
-            bail!("multiple root certificates are not yet supported");
-        }
-        let mut found_valid_signature = false;
-        info!("checking bundle signatures");
-        for signature in signatures.cms_signatures.iter() {
-            let tempdir = tempfile::tempdir().whatever("unable to create temporary directory")?;
-            let tempdir_path = tempdir.path();
-            let signed_metadata_raw = tempdir_path.join("signed-metadata.raw");
-            let signed_metadata_cms = tempdir_path.join("signed-metadata.cms");
-            std::fs::write(&signed_metadata_cms, &signature.raw)
-                .whatever("unable to write CMS signature")?;
-            let mut cmd = cmd_os!(
-                "openssl",
-                "cms",
-                "-verify",
-                "-in",
-                &signed_metadata_cms,
-                "-inform",
-                "DER",
-                "-out",
-                &signed_metadata_raw,
-                // Do not load OS default certificates.
-                "-no-CAfile",
-                "-no-CApath",
-                "-no-CAstore",
-                // Non-zero exit code on verification failure.
-                "-verify_retcode",
-            );
-            for cert in root_certs {
-                if cert.is_dir() {
-                    cmd.add_arg("-CApath");
-                    cmd.add_arg(cert);
-                } else {
-                    cmd.add_arg("-CAfile");
-                    cmd.add_arg(cert);
-                }
+        rugix_bundle::verify_signatures(&bundle_reader, root_certs, sigstore_trust)
+            .whatever("unable to verify bundle signatures")?;
+    }
+
+    let mut variants = bundle_reader
+        .header()
+        .payload_index
+        .iter()
+        .filter_map(|entry| entry.variant.as_deref())
+        .collect::<Vec<_>>();
+    if !variants.is_empty() {
+        variants.sort_unstable();
+        variants.dedup();
+        match system.variant() {
+            Some(variant) if variants.contains(&variant) => {
+                info!("installing payloads for variant {variant:?}");
             }
-            if let Err(error) = ParentEnv.run(cmd) {
-                println!("{error}");
-                continue;
+            Some(variant) => {
+                bail!(
+                    "bundle does not contain payloads for variant {variant:?}, available variants: {}",
+                    variants.join(", ")
+                );
             }
-            let signed_metadata =
-                std::fs::read(&signed_metadata_raw).whatever("unable to read signed metadata")?;
-            let signed_metadata = decode_slice::<format::SignedMetadata>(&signed_metadata)
-                .whatever("unable to decode signed metadata")?;
-            if signed_metadata.header_hash
-                == bundle_reader.header_hash(signed_metadata.header_hash.algorithm())
-            {
-                found_valid_signature = true;
-                info!("found valid signature");
-                break;
+            None => {
+                bail!(
+                    "unable to determine system variant, set `variant` in the system configuration, available variants: {}",
+                    variants.join(", ")
+                );
             }
         }
-        if !found_valid_signature {
-            bail!("no valid signature found");
-        }
     }
 
     if !bundle_reader.header().is_incremental {
@@ -735,6 +894,12 @@ fn install_update_bundle<R: BundleSource>(
 
         let mut last_progress = 0.0;
         move |source: &R| {
+            if let Some(progress_server) = progress_server {
+                progress_server.update(
+                    source.bytes_read().map(|bytes| bytes.raw),
+                    source.bytes_total().map(|bytes| bytes.raw),
+                );
+            }
             let Some(bytes_total) = source.bytes_total() else {
                 return;
             };
@@ -758,11 +923,57 @@ fn install_update_bundle<R: BundleSource>(
         }
     };
 
+    if let Some(progress_server) = progress_server {
+        let nsteps = bundle_reader
+            .header()
+            .payload_index
+            .iter()
+            .filter(|entry| {
+                entry
+                    .variant
+                    .as_deref()
+                    .is_none_or(|variant| Some(variant) == system.variant())
+            })
+            .count();
+        let source = bundle_reader.source();
+        progress_server.set_steps(nsteps, source.bytes_total().map(|bytes| bytes.raw));
+    }
+
     while let Some(payload) = bundle_reader
         .next_payload()
         .whatever("unable to read payload")?
     {
         let payload_entry = payload.entry();
+        if payload_entry
+            .variant
+            .as_deref()
+            .is_some_and(|variant| Some(variant) != system.variant())
+        {
+            payload.skip().whatever("unable to skip payload")?;
+            continue;
+        }
+        if let Some(progress_server) = progress_server {
+            let (image, handler) = if let Some(slot_type) = &payload_entry.type_slot {
+                (slot_type.slot.clone(), "slot")
+            } else if let Some(metadata_type) = &payload_entry.type_metadata {
+                (
+                    format!("{}/{}", metadata_type.slot, metadata_type.name),
+                    "metadata",
+                )
+            } else {
+                (format!("payload {}", payload.idx()), "execute")
+            };
+            progress_server.start_step(
+                &image,
+                handler,
+                payload
+                    .reader()
+                    .source()
+                    .bytes_read()
+                    .map(|bytes| bytes.raw),
+                payload.remaining_data().raw,
+            );
+        }
         if let Some(slot_type) = &payload_entry.type_slot {
             let slot = boot_group
                 .and_then(|(_, entry)| entry.get_slot(&slot_type.slot))
@@ -843,12 +1054,12 @@ fn install_update_bundle<R: BundleSource>(
                             bail!("source slot must not be a custom slot");
                         }
                     };
+                    let _writable = match slot.kind() {
+                        SlotKind::Block(block_slot) => Some(block_slot.make_writable()?),
+                        _ => None,
+                    };
                     let target = match slot.kind() {
-                        SlotKind::Block(block_slot) => std::fs::OpenOptions::new()
-                            .read(true)
-                            .write(true)
-                            .open(block_slot.device())
-                            .whatever("unable to open payload target")?,
+                        SlotKind::Block(block_slot) => block_slot.open_writable()?,
                         SlotKind::File { path } => std::fs::OpenOptions::new()
                             .read(true)
                             .write(true)
@@ -901,14 +1112,13 @@ fn install_update_bundle<R: BundleSource>(
                 } else {
                     match slot.kind() {
                         SlotKind::Block(block_slot) => {
-                            let target = std::fs::OpenOptions::new()
-                                .read(true)
-                                .write(true)
-                                .open(block_slot.device())
-                                .whatever("unable to open payload target")?;
+                            let _writable = block_slot.make_writable()?;
+                            let target = block_slot.open_writable()?;
                             payload
                                 .decode_into(
-                                    target,
+                                    WriterTarget {
+                                        writer: write_options.writer(target)?,
+                                    },
                                     block_provider
                                         .as_ref()
                                         .map(|p| p as &dyn StoredBlockProvider),
@@ -926,7 +1136,9 @@ fn install_update_bundle<R: BundleSource>(
                                 .whatever("unable to open payload target")?;
                             payload
                                 .decode_into(
-                                    target,
+                                    WriterTarget {
+                                        writer: write_options.writer(target)?,
+                                    },
                                     block_provider
                                         .as_ref()
                                         .map(|p| p as &dyn StoredBlockProvider),
@@ -980,6 +1192,44 @@ fn install_update_bundle<R: BundleSource>(
                     payload.idx()
                 );
             }
+        } else if let Some(metadata_type) = &payload_entry.type_metadata {
+            let slot = boot_group
+                .and_then(|(_, entry)| entry.get_slot(&metadata_type.slot))
+                .or_else(|| {
+                    system
+                        .slots()
+                        .find_by_name(&metadata_type.slot)
+                        .map(|e| e.0)
+                });
+            if let Some(slot) = slot {
+                let slot = &system.slots()[slot];
+                eprintln!(
+                    "Storing {} metadata of slot {} from bundle payload {}",
+                    metadata_type.name,
+                    slot.name(),
+                    payload.idx()
+                );
+                // The metadata is erased together with the slot's state, hence, metadata
+                // payloads must come after the payload installing the slot.
+                let metadata_path = slot_db::metadata_path(slot.name(), &metadata_type.name)?;
+                fs::create_dir_all(metadata_path.parent().unwrap())
+                    .whatever("unable to create metadata directory")?;
+                let metadata_path_tmp = metadata_path.with_extension("tmp");
+                let target =
+                    File::create(&metadata_path_tmp).whatever("unable to create metadata file")?;
+                payload
+                    .decode_into(target, None, &mut progress)
+                    .whatever("unable to decode payload")?;
+                fs::rename(&metadata_path_tmp, &metadata_path)
+                    .whatever("unable to store metadata")?;
+                continue;
+            } else {
+                warn!(
+                    "slot {:?} for metadata payload {} not found",
+                    metadata_type.slot,
+                    payload.idx()
+                );
+            }
         } else if let Some(type_execute) = &payload_entry.type_execute {
             eprintln!("executing update payload {}", payload.idx(),);
             let target = CustomTarget::new(type_execute.handler.iter().map(|arg| arg.as_str()))?;
@@ -1051,6 +1301,61 @@ impl PayloadTarget for BufferedPipeTarget {
     }
 }
 
+/// Options for writing to slots.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Verify the written data by reading it back.
+    pub verify: bool,
+    /// Sync the written data to disk after the given number of bytes.
+    pub sync_interval: Option<NumBytes>,
+}
+
+impl WriteOptions {
+    /// Create a writer for the given target.
+    pub fn writer(&self, target: File) -> SystemResult<ImageWriter<'static>> {
+        Ok(ImageWriter::new(target)
+            .whatever("unable to create writer")?
+            .with_verification(self.verify)
+            .with_sync_interval(self.sync_interval)
+            .with_progress(|progress| {
+                debug!("written {} ({}/s)", progress.written, progress.throughput())
+            }))
+    }
+}
+
+/// Payload target writing to a slot.
+pub struct WriterTarget {
+    writer: ImageWriter<'static>,
+}
+
+impl PayloadTarget for WriterTarget {
+    fn write(&mut self, bytes: &[u8]) -> rugix_bundle::BundleResult<()> {
+        self.writer
+            .write(bytes)
+            .whatever("unable to write to target")
+    }
+
+    fn read_block(
+        &mut self,
+        offset: NumBytes,
+        size: NumBytes,
+        buffer: &mut Vec<u8>,
+    ) -> rugix_bundle::BundleResult<()> {
+        buffer.resize(size.unwrap_usize(), 0);
+        self.writer
+            .read_exact_at(buffer, offset.raw)
+            .whatever("unable to read block from target")
+    }
+
+    fn finalize(self) -> rugix_bundle::BundleResult<()> {
+        let summary = self.writer.finish().whatever("unable to write to target")?;
+        if let Some(hash) = summary.verified_hash {
+            info!("verified written data ({} bytes, {hash})", summary.size.raw);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct CustomTarget {
     child: Child,
@@ -1132,12 +1437,36 @@ pub enum Command {
     /// Unstable experimental commands.
     #[clap(subcommand)]
     Unstable(UnstableCommand),
+    /// Flash an image to a block device.
+    Flash {
+        /// Path to the image (`-` to read from stdin).
+        image: PathBuf,
+        /// Block device to flash the image to.
+        device: PathBuf,
+        /// Verify the written data by reading it back.
+        #[clap(long)]
+        verify: bool,
+        /// Sync the written data to disk after the given number of bytes.
+        #[clap(long)]
+        sync_interval: Option<NumBytes>,
+        /// Flash the image even if the device is mounted.
+        #[clap(long)]
+        force: bool,
+    },
 }
 
 #[derive(Debug, Parser)]
 pub enum StateCommand {
     /// Perform a factory reset of the system.
     Reset,
+    /// Show the overlay configuration and the usage of the overlay and data partition.
+    Status {
+        /// Output the status as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Monitor the usage of the overlay and data partition.
+    Monitor,
     /// Configure the root filesystem overlay.
     #[clap(subcommand)]
     Overlay(OverlayCommand),
@@ -1178,6 +1507,18 @@ pub enum UpdateCommand {
         /// Root certificate to use for signature verification.
         #[clap(long = "root-cert")]
         root_cert: Vec<PathBuf>,
+        /// Public key to verify Sigstore signatures with.
+        #[clap(long)]
+        sigstore_key: Option<PathBuf>,
+        /// Identity of the signer to verify keyless Sigstore signatures with.
+        #[clap(long)]
+        sigstore_identity: Option<String>,
+        /// OIDC issuer of the identity of the signer of keyless Sigstore signatures.
+        #[clap(long)]
+        sigstore_oidc_issuer: Option<String>,
+        /// Sigstore trusted root to verify keyless Sigstore signatures with.
+        #[clap(long)]
+        sigstore_trusted_root: Option<PathBuf>,
         /// Verify a bundle based on the provided hash.
         #[clap(long)]
         verify_bundle: Option<HashDigest>,
@@ -1190,6 +1531,12 @@ pub enum UpdateCommand {
         /// Boot group to install the update to.
         #[clap(long)]
         boot_group: Option<String>,
+        /// Verify the data written to slots by reading it back.
+        #[clap(long)]
+        verify_write: bool,
+        /// Sync the data written to slots to disk after the given number of bytes.
+        #[clap(long)]
+        sync_interval: Option<NumBytes>,
     },
 }
 
@@ -1215,6 +1562,22 @@ pub enum SystemCommand {
         #[clap(long)]
         spare: bool,
     },
+    /// Run pending first-boot scripts.
+    FirstBoot,
+    /// Print the SBOM of the system slot of the active boot group.
+    Sbom,
+    /// Check and repair the backup GPT header of the root device.
+    RepairGpt {
+        /// Only check the backup GPT header without repairing it.
+        #[clap(long)]
+        check: bool,
+    },
+    /// Check the partition tables against the layouts of the bootstrapping configuration.
+    CheckLayout {
+        /// Path to the bootstrapping configuration.
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Parser)]
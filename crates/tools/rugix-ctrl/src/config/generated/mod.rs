@@ -12,6 +12,8 @@ pub mod bootstrapping {
     pub type NumBytes = byte_calc::NumBytes;
     #[doc = ""]
     pub type PartitionType = rugix_common::disk::PartitionType;
+    #[doc = ""]
+    pub type GptAttributes = rugix_common::disk::gpt::GptAttributes;
     #[doc = "Bootstrapping configuration.\n"]
     #[derive(Clone, Debug)]
     pub struct BootstrappingConfig {
@@ -19,6 +21,9 @@ pub mod bootstrapping {
         pub disabled: ::std::option::Option<bool>,
         #[doc = "System layout configuration to use for bootstrapping.\n"]
         pub layout: ::std::option::Option<SystemLayoutConfig>,
+        #[doc = "Layouts of additional disks configured in the system configuration.\n"]
+        pub disks:
+            ::std::option::Option<indexmap::IndexMap<::std::string::String, SystemLayoutConfig>>,
     }
     impl BootstrappingConfig {
         #[doc = "Creates a new [`BootstrappingConfig`]."]
@@ -26,6 +31,7 @@ pub mod bootstrapping {
             Self {
                 disabled: ::std::default::Default::default(),
                 layout: ::std::default::Default::default(),
+                disks: ::std::default::Default::default(),
             }
         }
         #[doc = "Sets the value of `disabled`."]
@@ -51,6 +57,26 @@ pub mod bootstrapping {
             self.layout = layout;
             self
         }
+        #[doc = "Sets the value of `disks`."]
+        pub fn set_disks(
+            &mut self,
+            disks: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, SystemLayoutConfig>,
+            >,
+        ) -> &mut Self {
+            self.disks = disks;
+            self
+        }
+        #[doc = "Sets the value of `disks`."]
+        pub fn with_disks(
+            mut self,
+            disks: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, SystemLayoutConfig>,
+            >,
+        ) -> Self {
+            self.disks = disks;
+            self
+        }
     }
     impl ::std::default::Default for BootstrappingConfig {
         fn default() -> Self {
@@ -66,7 +92,7 @@ pub mod bootstrapping {
             let mut __record = __sidex_serde::ser::RecordSerializer::new(
                 __serializer,
                 "BootstrappingConfig",
-                2usize,
+                3usize,
             )?;
             __record.serialize_optional_field(
                 "disabled",
@@ -74,6 +100,8 @@ pub mod bootstrapping {
             )?;
             __record
                 .serialize_optional_field("layout", ::core::option::Option::as_ref(&self.layout))?;
+            __record
+                .serialize_optional_field("disks", ::core::option::Option::as_ref(&self.disks))?;
             __record.end()
         }
     }
@@ -109,7 +137,7 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 3 fields"),
                             );
                         }
                     };
@@ -120,13 +148,27 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, SystemLayoutConfig>,
+                        >,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 3 fields"),
                             );
                         }
                     };
                     ::core::result::Result::Ok(BootstrappingConfig {
                         disabled: __field0,
                         layout: __field1,
+                        disks: __field2,
                     })
                 }
                 #[inline]
@@ -138,15 +180,16 @@ pub mod bootstrapping {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["disabled", "layout"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["disabled", "layout", "disks"];
                     #[doc(hidden)]
                     const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"disabled\", \"layout\"]";
+                        "an identifier in [\"disabled\", \"layout\", \"disks\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
+                        __Identifier2,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -169,6 +212,7 @@ pub mod bootstrapping {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -184,6 +228,7 @@ pub mod bootstrapping {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
                                 "layout" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "disks" => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -201,6 +246,7 @@ pub mod bootstrapping {
                                 b"layout" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                b"disks" => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -224,6 +270,11 @@ pub mod bootstrapping {
                     let mut __field1: ::core::option::Option<
                         ::std::option::Option<SystemLayoutConfig>,
                     > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, SystemLayoutConfig>,
+                        >,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -256,6 +307,25 @@ pub mod bootstrapping {
                                     >(&mut __map)?,
                                 );
                             }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "disks",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<
+                                            indexmap::IndexMap<
+                                                ::std::string::String,
+                                                SystemLayoutConfig,
+                                            >,
+                                        >,
+                                    >(&mut __map)?,
+                                );
+                            }
                             _ => {
                                 __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
                                     &mut __map,
@@ -271,14 +341,19 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
                     ::core::result::Result::Ok(BootstrappingConfig {
                         disabled: __field0,
                         layout: __field1,
+                        disks: __field2,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["disabled", "layout"];
+            const __FIELDS: &'static [&'static str] = &["disabled", "layout", "disks"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
                 "BootstrappingConfig",
@@ -755,8 +830,14 @@ pub mod bootstrapping {
         pub size: ::std::option::Option<NumBytes>,
         #[doc = "Type of the partition (one byte hex or GUID).\n"]
         pub ty: ::std::option::Option<PartitionType>,
+        #[doc = "Attribute flags of the partition (e.g., `RequiredPartition,GUID:60`).\n"]
+        pub attributes: ::std::option::Option<GptAttributes>,
+        #[doc = "Grow the partition and its filesystem into the available space.\n"]
+        pub grow: ::std::option::Option<bool>,
         #[doc = "Filesystem of the partition.\n"]
         pub filesystem: ::std::option::Option<Filesystem>,
+        #[doc = "Encryption of the partition.\n"]
+        pub encryption: ::std::option::Option<PartitionEncryption>,
     }
     impl LayoutPartitionConfig {
         #[doc = "Creates a new [`LayoutPartitionConfig`]."]
@@ -765,7 +846,10 @@ pub mod bootstrapping {
                 name: ::std::default::Default::default(),
                 size: ::std::default::Default::default(),
                 ty: ::std::default::Default::default(),
+                attributes: ::std::default::Default::default(),
+                grow: ::std::default::Default::default(),
                 filesystem: ::std::default::Default::default(),
+                encryption: ::std::default::Default::default(),
             }
         }
         #[doc = "Sets the value of `name`."]
@@ -801,6 +885,29 @@ pub mod bootstrapping {
             self.ty = ty;
             self
         }
+        #[doc = "Sets the value of `attributes`."]
+        pub fn set_attributes(
+            &mut self,
+            attributes: ::std::option::Option<GptAttributes>,
+        ) -> &mut Self {
+            self.attributes = attributes;
+            self
+        }
+        #[doc = "Sets the value of `attributes`."]
+        pub fn with_attributes(mut self, attributes: ::std::option::Option<GptAttributes>) -> Self {
+            self.attributes = attributes;
+            self
+        }
+        #[doc = "Sets the value of `grow`."]
+        pub fn set_grow(&mut self, grow: ::std::option::Option<bool>) -> &mut Self {
+            self.grow = grow;
+            self
+        }
+        #[doc = "Sets the value of `grow`."]
+        pub fn with_grow(mut self, grow: ::std::option::Option<bool>) -> Self {
+            self.grow = grow;
+            self
+        }
         #[doc = "Sets the value of `filesystem`."]
         pub fn set_filesystem(
             &mut self,
@@ -814,6 +921,22 @@ pub mod bootstrapping {
             self.filesystem = filesystem;
             self
         }
+        #[doc = "Sets the value of `encryption`."]
+        pub fn set_encryption(
+            &mut self,
+            encryption: ::std::option::Option<PartitionEncryption>,
+        ) -> &mut Self {
+            self.encryption = encryption;
+            self
+        }
+        #[doc = "Sets the value of `encryption`."]
+        pub fn with_encryption(
+            mut self,
+            encryption: ::std::option::Option<PartitionEncryption>,
+        ) -> Self {
+            self.encryption = encryption;
+            self
+        }
     }
     impl ::std::default::Default for LayoutPartitionConfig {
         fn default() -> Self {
@@ -829,17 +952,27 @@ pub mod bootstrapping {
             let mut __record = __sidex_serde::ser::RecordSerializer::new(
                 __serializer,
                 "LayoutPartitionConfig",
-                4usize,
+                7usize,
             )?;
             __record
                 .serialize_optional_field("name", ::core::option::Option::as_ref(&self.name))?;
             __record
                 .serialize_optional_field("size", ::core::option::Option::as_ref(&self.size))?;
             __record.serialize_optional_field("type", ::core::option::Option::as_ref(&self.ty))?;
+            __record.serialize_optional_field(
+                "attributes",
+                ::core::option::Option::as_ref(&self.attributes),
+            )?;
+            __record
+                .serialize_optional_field("grow", ::core::option::Option::as_ref(&self.grow))?;
             __record.serialize_optional_field(
                 "filesystem",
                 ::core::option::Option::as_ref(&self.filesystem),
             )?;
+            __record.serialize_optional_field(
+                "encryption",
+                ::core::option::Option::as_ref(&self.encryption),
+            )?;
             __record.end()
         }
     }
@@ -875,7 +1008,7 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 7 fields"),
                             );
                         }
                     };
@@ -886,7 +1019,7 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 7 fields"),
                             );
                         }
                     };
@@ -897,18 +1030,51 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(2usize, &"record with 7 fields"),
                             );
                         }
                     };
                     let __field3 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<GptAttributes>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<bool>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
                         ::std::option::Option<Filesystem>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(5usize, &"record with 7 fields"),
+                            );
+                        }
+                    };
+                    let __field6 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<PartitionEncryption>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(6usize, &"record with 7 fields"),
                             );
                         }
                     };
@@ -916,7 +1082,10 @@ pub mod bootstrapping {
                         name: __field0,
                         size: __field1,
                         ty: __field2,
-                        filesystem: __field3,
+                        attributes: __field3,
+                        grow: __field4,
+                        filesystem: __field5,
+                        encryption: __field6,
                     })
                 }
                 #[inline]
@@ -928,11 +1097,17 @@ pub mod bootstrapping {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] =
-                        &["name", "size", "type", "filesystem"];
+                    const __IDENTIFIERS: &'static [&'static str] = &[
+                        "name",
+                        "size",
+                        "type",
+                        "attributes",
+                        "grow",
+                        "filesystem",
+                        "encryption",
+                    ];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"name\", \"size\", \"type\", \"filesystem\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"name\", \"size\", \"type\", \"attributes\", \"grow\", \"filesystem\", \"encryption\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -940,6 +1115,9 @@ pub mod bootstrapping {
                         __Identifier1,
                         __Identifier2,
                         __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
+                        __Identifier6,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -964,6 +1142,9 @@ pub mod bootstrapping {
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                6u64 => ::core::result::Result::Ok(__Identifier::__Identifier6),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -978,9 +1159,16 @@ pub mod bootstrapping {
                                 "name" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 "size" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 "type" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                "filesystem" => {
+                                "attributes" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
+                                "grow" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                "filesystem" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                "encryption" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -995,9 +1183,16 @@ pub mod bootstrapping {
                                 b"name" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 b"size" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 b"type" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                b"filesystem" => {
+                                b"attributes" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
+                                b"grow" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                b"filesystem" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                b"encryption" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1023,8 +1218,15 @@ pub mod bootstrapping {
                         ::core::option::Option::None;
                     let mut __field2: ::core::option::Option<::std::option::Option<PartitionType>> =
                         ::core::option::Option::None;
-                    let mut __field3: ::core::option::Option<::std::option::Option<Filesystem>> =
+                    let mut __field3: ::core::option::Option<::std::option::Option<GptAttributes>> =
+                        ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<::std::option::Option<bool>> =
+                        ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<::std::option::Option<Filesystem>> =
                         ::core::option::Option::None;
+                    let mut __field6: ::core::option::Option<
+                        ::std::option::Option<PartitionEncryption>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -1069,16 +1271,56 @@ pub mod bootstrapping {
                                 if ::core::option::Option::is_some(&__field3) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "filesystem",
+                                            "attributes",
                                         ),
                                     );
                                 }
                                 __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<GptAttributes>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("grow"),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<bool>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "filesystem",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
                                         ::std::option::Option<Filesystem>,
                                     >(&mut __map)?,
                                 );
                             }
+                            __Identifier::__Identifier6 => {
+                                if ::core::option::Option::is_some(&__field6) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "encryption",
+                                        ),
+                                    );
+                                }
+                                __field6 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<PartitionEncryption>,
+                                    >(&mut __map)?,
+                                );
+                            }
                             _ => {
                                 __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
                                     &mut __map,
@@ -1102,16 +1344,39 @@ pub mod bootstrapping {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
+                    let __field4 = match __field4 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field6 = match __field6 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
                     ::core::result::Result::Ok(LayoutPartitionConfig {
                         name: __field0,
                         size: __field1,
                         ty: __field2,
-                        filesystem: __field3,
+                        attributes: __field3,
+                        grow: __field4,
+                        filesystem: __field5,
+                        encryption: __field6,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["name", "size", "type", "filesystem"];
+            const __FIELDS: &'static [&'static str] = &[
+                "name",
+                "size",
+                "type",
+                "attributes",
+                "grow",
+                "filesystem",
+                "encryption",
+            ];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
                 "LayoutPartitionConfig",
@@ -1122,85 +1387,312 @@ pub mod bootstrapping {
             )
         }
     }
-    #[doc = ""]
+    #[doc = "Encryption of a partition.\n"]
     #[derive(Clone, Debug)]
-    pub enum Filesystem {
-        #[doc = ""]
-        Ext4(Ext4Filesystem),
+    pub struct PartitionEncryption {
+        #[doc = "Path to the key file used for the initial keyslot of the LUKS container.\n"]
+        pub key_file: ::std::string::String,
+    }
+    impl PartitionEncryption {
+        #[doc = "Creates a new [`PartitionEncryption`]."]
+        pub fn new(key_file: ::std::string::String) -> Self {
+            Self { key_file }
+        }
+        #[doc = "Sets the value of `key_file`."]
+        pub fn set_key_file(&mut self, key_file: ::std::string::String) -> &mut Self {
+            self.key_file = key_file;
+            self
+        }
+        #[doc = "Sets the value of `key_file`."]
+        pub fn with_key_file(mut self, key_file: ::std::string::String) -> Self {
+            self.key_file = key_file;
+            self
+        }
     }
     #[automatically_derived]
-    impl __serde::Serialize for Filesystem {
+    impl __serde::Serialize for PartitionEncryption {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let __serializer =
-                __sidex_serde::ser::VariantSerializer::new(__serializer, "Filesystem");
-            match self {
-                Self::Ext4(__value) => {
-                    __serializer.serialize_internally_tagged("type", "ext4", 0u32, __value)
-                }
-            }
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "PartitionEncryption",
+                1usize,
+            )?;
+            __record.serialize_field("key-file", &self.key_file)?;
+            __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for Filesystem {
+    impl<'de> __serde::Deserialize<'de> for PartitionEncryption {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
             #[doc(hidden)]
-            const __IDENTIFIERS: &'static [&'static str] = &["ext4"];
-            #[doc(hidden)]
-            const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"ext4\"]";
-            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
-            #[doc(hidden)]
-            enum __Identifier {
-                __Identifier0,
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
-            #[doc(hidden)]
-            struct __IdentifierVisitor;
-            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
-                type Value = __Identifier;
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = PartitionEncryption;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                    ::core::fmt::Formatter::write_str(__formatter, "record PartitionEncryption")
                 }
-                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __E: __serde::de::Error,
+                    __A: __serde::de::SeqAccess<'de>,
                 {
-                    match __value {
-                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        __variant => {
-                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
-                                __serde::de::Unexpected::Unsigned(__variant),
-                                &__EXPECTING_IDENTIFIERS,
-                            ))
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 1 fields"),
+                            );
                         }
-                    }
-                }
-                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        "ext4" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        __variant => ::core::result::Result::Err(
-                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
-                        ),
-                    }
+                    };
+                    ::core::result::Result::Ok(PartitionEncryption { key_file: __field0 })
                 }
-                fn visit_bytes<__E>(
+                #[inline]
+                fn visit_map<__A>(
                     self,
-                    __value: &[u8],
-                ) -> ::core::result::Result<Self::Value, __E>
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __E: __serde::de::Error,
+                    __A: __serde::de::MapAccess<'de>,
                 {
-                    match __value {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["key-file"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"key-file\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "key-file" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"key-file" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "key-file",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("key-file"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PartitionEncryption { key_file: __field0 })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["key-file"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "PartitionEncryption",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = ""]
+    #[derive(Clone, Debug)]
+    pub enum Filesystem {
+        #[doc = ""]
+        Ext4(Ext4Filesystem),
+        #[doc = ""]
+        F2fs(F2fsFilesystem),
+        #[doc = ""]
+        Btrfs(BtrfsFilesystem),
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for Filesystem {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let __serializer =
+                __sidex_serde::ser::VariantSerializer::new(__serializer, "Filesystem");
+            match self {
+                Self::Ext4(__value) => {
+                    __serializer.serialize_internally_tagged("type", "ext4", 0u32, __value)
+                }
+                Self::F2fs(__value) => {
+                    __serializer.serialize_internally_tagged("type", "f2fs", 1u32, __value)
+                }
+                Self::Btrfs(__value) => {
+                    __serializer.serialize_internally_tagged("type", "btrfs", 2u32, __value)
+                }
+            }
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for Filesystem {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            const __IDENTIFIERS: &'static [&'static str] = &["ext4", "f2fs", "btrfs"];
+            #[doc(hidden)]
+            const __EXPECTING_IDENTIFIERS: &'static str =
+                "an identifier in [\"ext4\", \"f2fs\", \"btrfs\"]";
+            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+            #[doc(hidden)]
+            enum __Identifier {
+                __Identifier0,
+                __Identifier1,
+                __Identifier2,
+            }
+            #[doc(hidden)]
+            struct __IdentifierVisitor;
+            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                type Value = __Identifier;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                }
+                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                        __variant => {
+                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
+                                __serde::de::Unexpected::Unsigned(__variant),
+                                &__EXPECTING_IDENTIFIERS,
+                            ))
+                        }
+                    }
+                }
+                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        "ext4" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        "f2fs" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        "btrfs" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                        __variant => ::core::result::Result::Err(
+                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
+                        ),
+                    }
+                }
+                fn visit_bytes<__E>(
+                    self,
+                    __value: &[u8],
+                ) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
                         b"ext4" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        b"f2fs" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        b"btrfs" => ::core::result::Result::Ok(__Identifier::__Identifier2),
                         __variant => {
                             ::core::result::Result::Err(__serde::de::Error::invalid_value(
                                 __serde::de::Unexpected::Bytes(__variant),
@@ -1223,7 +1715,7 @@ pub mod bootstrapping {
                 }
             }
             #[doc(hidden)]
-            const __VARIANTS: &'static [&'static str] = &["ext4"];
+            const __VARIANTS: &'static [&'static str] = &["ext4", "f2fs", "btrfs"];
             if __serde::Deserializer::is_human_readable(&__deserializer) {
                 let __tagged = __sidex_serde::de::tagged::deserialize_tagged_variant::<
                     __Identifier,
@@ -1233,6 +1725,12 @@ pub mod bootstrapping {
                     __Identifier::__Identifier0 => ::core::result::Result::Ok(Filesystem::Ext4(
                         __tagged.deserialize_internally_tagged::<Ext4Filesystem, __D::Error>()?,
                     )),
+                    __Identifier::__Identifier1 => ::core::result::Result::Ok(Filesystem::F2fs(
+                        __tagged.deserialize_internally_tagged::<F2fsFilesystem, __D::Error>()?,
+                    )),
+                    __Identifier::__Identifier2 => ::core::result::Result::Ok(Filesystem::Btrfs(
+                        __tagged.deserialize_internally_tagged::<BtrfsFilesystem, __D::Error>()?,
+                    )),
                 }
             } else {
                 #[doc(hidden)]
@@ -1279,6 +1777,18 @@ pub mod bootstrapping {
                                 >(__variant)?;
                                 ::core::result::Result::Ok(Filesystem::Ext4(__value))
                             }
+                            (__Identifier::__Identifier1, __variant) => {
+                                let __value = __serde::de::VariantAccess::newtype_variant::<
+                                    F2fsFilesystem,
+                                >(__variant)?;
+                                ::core::result::Result::Ok(Filesystem::F2fs(__value))
+                            }
+                            (__Identifier::__Identifier2, __variant) => {
+                                let __value = __serde::de::VariantAccess::newtype_variant::<
+                                    BtrfsFilesystem,
+                                >(__variant)?;
+                                ::core::result::Result::Ok(Filesystem::Btrfs(__value))
+                            }
                         }
                     }
                 }
@@ -1502,45 +2012,53 @@ pub mod bootstrapping {
             )
         }
     }
-    #[doc = "Default layout configuration.\n"]
+    #[doc = ""]
     #[derive(Clone, Debug)]
-    pub struct DefaultLayoutConfig {
-        #[doc = "Size of the system partitions.\n"]
-        pub system_size: NumBytes,
+    pub struct F2fsFilesystem {
+        #[doc = ""]
+        pub label: ::std::option::Option<::std::string::String>,
     }
-    impl DefaultLayoutConfig {
-        #[doc = "Creates a new [`DefaultLayoutConfig`]."]
-        pub fn new(system_size: NumBytes) -> Self {
-            Self { system_size }
+    impl F2fsFilesystem {
+        #[doc = "Creates a new [`F2fsFilesystem`]."]
+        pub fn new() -> Self {
+            Self {
+                label: ::std::default::Default::default(),
+            }
         }
-        #[doc = "Sets the value of `system_size`."]
-        pub fn set_system_size(&mut self, system_size: NumBytes) -> &mut Self {
-            self.system_size = system_size;
+        #[doc = "Sets the value of `label`."]
+        pub fn set_label(
+            &mut self,
+            label: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.label = label;
             self
         }
-        #[doc = "Sets the value of `system_size`."]
-        pub fn with_system_size(mut self, system_size: NumBytes) -> Self {
-            self.system_size = system_size;
+        #[doc = "Sets the value of `label`."]
+        pub fn with_label(mut self, label: ::std::option::Option<::std::string::String>) -> Self {
+            self.label = label;
             self
         }
     }
+    impl ::std::default::Default for F2fsFilesystem {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
     #[automatically_derived]
-    impl __serde::Serialize for DefaultLayoutConfig {
+    impl __serde::Serialize for F2fsFilesystem {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record = __sidex_serde::ser::RecordSerializer::new(
-                __serializer,
-                "DefaultLayoutConfig",
-                1usize,
-            )?;
-            __record.serialize_field("system-size", &self.system_size)?;
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "F2fsFilesystem", 1usize)?;
+            __record
+                .serialize_optional_field("label", ::core::option::Option::as_ref(&self.label))?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for DefaultLayoutConfig {
+    impl<'de> __serde::Deserialize<'de> for F2fsFilesystem {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -1549,12 +2067,12 @@ pub mod bootstrapping {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = DefaultLayoutConfig;
+                type Value = F2fsFilesystem;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record DefaultLayoutConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record F2fsFilesystem")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -1564,21 +2082,18 @@ pub mod bootstrapping {
                 where
                     __A: __serde::de::SeqAccess<'de>,
                 {
-                    let __field0 =
-                        match __serde::de::SeqAccess::next_element::<NumBytes>(&mut __seq)? {
-                            ::core::option::Option::Some(__value) => __value,
-                            ::core::option::Option::None => {
-                                return ::core::result::Result::Err(
-                                    __serde::de::Error::invalid_length(
-                                        0usize,
-                                        &"record with 1 fields",
-                                    ),
-                                );
-                            }
-                        };
-                    ::core::result::Result::Ok(DefaultLayoutConfig {
-                        system_size: __field0,
-                    })
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 1 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(F2fsFilesystem { label: __field0 })
                 }
                 #[inline]
                 fn visit_map<__A>(
@@ -1589,10 +2104,9 @@ pub mod bootstrapping {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["system-size"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["label"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"system-size\"]";
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"label\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -1629,9 +2143,7 @@ pub mod bootstrapping {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "system-size" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
+                                "label" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1643,9 +2155,7 @@ pub mod bootstrapping {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"system-size" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
+                                b"label" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1664,8 +2174,9 @@ pub mod bootstrapping {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<NumBytes> =
-                        ::core::option::Option::None;
+                    let mut __field0: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -1674,12 +2185,14 @@ pub mod bootstrapping {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "system-size",
+                                            "label",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<NumBytes>(&mut __map)?,
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
                                 );
                             }
                             _ => {
@@ -1691,22 +2204,16 @@ pub mod bootstrapping {
                     }
                     let __field0 = match __field0 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("system-size"),
-                            );
-                        }
+                        ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(DefaultLayoutConfig {
-                        system_size: __field0,
-                    })
+                    ::core::result::Result::Ok(F2fsFilesystem { label: __field0 })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["system-size"];
+            const __FIELDS: &'static [&'static str] = &["label"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "DefaultLayoutConfig",
+                "F2fsFilesystem",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -1714,75 +2221,53 @@ pub mod bootstrapping {
             )
         }
     }
-}
-pub mod output {
-    #![doc = ""]
-    #[allow(unused)]
-    use :: serde as __serde;
-    #[allow(unused)]
-    use :: sidex_serde as __sidex_serde;
     #[doc = ""]
     #[derive(Clone, Debug)]
-    pub struct SystemStateOutput {
-        #[doc = ""]
-        pub slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
+    pub struct BtrfsFilesystem {
         #[doc = ""]
-        pub boot: ::std::option::Option<BootStateOutput>,
+        pub label: ::std::option::Option<::std::string::String>,
     }
-    impl SystemStateOutput {
-        #[doc = "Creates a new [`SystemStateOutput`]."]
-        pub fn new(slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>) -> Self {
+    impl BtrfsFilesystem {
+        #[doc = "Creates a new [`BtrfsFilesystem`]."]
+        pub fn new() -> Self {
             Self {
-                slots,
-                boot: ::std::default::Default::default(),
+                label: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `slots`."]
-        pub fn set_slots(
+        #[doc = "Sets the value of `label`."]
+        pub fn set_label(
             &mut self,
-            slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
+            label: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.slots = slots;
-            self
-        }
-        #[doc = "Sets the value of `slots`."]
-        pub fn with_slots(
-            mut self,
-            slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
-        ) -> Self {
-            self.slots = slots;
+            self.label = label;
             self
         }
-        #[doc = "Sets the value of `boot`."]
-        pub fn set_boot(&mut self, boot: ::std::option::Option<BootStateOutput>) -> &mut Self {
-            self.boot = boot;
+        #[doc = "Sets the value of `label`."]
+        pub fn with_label(mut self, label: ::std::option::Option<::std::string::String>) -> Self {
+            self.label = label;
             self
         }
-        #[doc = "Sets the value of `boot`."]
-        pub fn with_boot(mut self, boot: ::std::option::Option<BootStateOutput>) -> Self {
-            self.boot = boot;
-            self
+    }
+    impl ::std::default::Default for BtrfsFilesystem {
+        fn default() -> Self {
+            Self::new()
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for SystemStateOutput {
+    impl __serde::Serialize for BtrfsFilesystem {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record = __sidex_serde::ser::RecordSerializer::new(
-                __serializer,
-                "SystemStateOutput",
-                2usize,
-            )?;
-            __record.serialize_field("slots", &self.slots)?;
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "BtrfsFilesystem", 1usize)?;
             __record
-                .serialize_optional_field("boot", ::core::option::Option::as_ref(&self.boot))?;
+                .serialize_optional_field("label", ::core::option::Option::as_ref(&self.label))?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for SystemStateOutput {
+    impl<'de> __serde::Deserialize<'de> for BtrfsFilesystem {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -1791,12 +2276,12 @@ pub mod output {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = SystemStateOutput;
+                type Value = BtrfsFilesystem;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record SystemStateOutput")
+                    ::core::fmt::Formatter::write_str(__formatter, "record BtrfsFilesystem")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -1807,31 +2292,17 @@ pub mod output {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
-                            );
-                        }
-                    };
-                    let __field1 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<BootStateOutput>,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 1 fields"),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(SystemStateOutput {
-                        slots: __field0,
-                        boot: __field1,
-                    })
+                    ::core::result::Result::Ok(BtrfsFilesystem { label: __field0 })
                 }
                 #[inline]
                 fn visit_map<__A>(
@@ -1842,15 +2313,13 @@ pub mod output {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["slots", "boot"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["label"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"slots\", \"boot\"]";
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"label\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
-                        __Identifier1,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -1872,7 +2341,6 @@ pub mod output {
                         {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1884,8 +2352,7 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "slots" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                "boot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "label" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1897,8 +2364,7 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"slots" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                b"boot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                b"label" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -1918,10 +2384,7 @@ pub mod output {
                         }
                     }
                     let mut __field0: ::core::option::Option<
-                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
-                    > = ::core::option::Option::None;
-                    let mut __field1: ::core::option::Option<
-                        ::std::option::Option<BootStateOutput>,
+                        ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -1931,25 +2394,13 @@ pub mod output {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "slots",
+                                            "label",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
-                                    >(&mut __map)?,
-                                );
-                            }
-                            __Identifier::__Identifier1 => {
-                                if ::core::option::Option::is_some(&__field1) {
-                                    return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field("boot"),
-                                    );
-                                }
-                                __field1 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<BootStateOutput>,
+                                        ::std::option::Option<::std::string::String>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -1961,28 +2412,17 @@ pub mod output {
                         }
                     }
                     let __field0 = match __field0 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("slots"),
-                            );
-                        }
-                    };
-                    let __field1 = match __field1 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(SystemStateOutput {
-                        slots: __field0,
-                        boot: __field1,
-                    })
+                    ::core::result::Result::Ok(BtrfsFilesystem { label: __field0 })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["slots", "boot"];
+            const __FIELDS: &'static [&'static str] = &["label"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "SystemStateOutput",
+                "BtrfsFilesystem",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -1990,114 +2430,101 @@ pub mod output {
             )
         }
     }
-    #[doc = ""]
+    #[doc = "Default layout configuration.\n"]
     #[derive(Clone, Debug)]
-    pub struct SlotStateOutput {
-        #[doc = ""]
-        pub active: ::std::option::Option<bool>,
-        #[doc = ""]
-        pub hashes:
-            ::std::option::Option<indexmap::IndexMap<::std::string::String, ::std::string::String>>,
-        #[doc = ""]
-        pub size: ::std::option::Option<u64>,
-        #[doc = ""]
-        pub updated_at: ::std::option::Option<::std::string::String>,
+    pub struct DefaultLayoutConfig {
+        #[doc = "Size of the config partition (defaults to 256MiB).\n"]
+        pub config_size: ::std::option::Option<NumBytes>,
+        #[doc = "Size of the boot partitions (defaults to 128MiB for MBR and 256MiB for GPT).\n"]
+        pub boot_size: ::std::option::Option<NumBytes>,
+        #[doc = "Size of the system partitions.\n"]
+        pub system_size: NumBytes,
+        #[doc = "Alignment of the start of the partitions (defaults to 1MiB).\n"]
+        pub alignment: ::std::option::Option<NumBytes>,
     }
-    impl SlotStateOutput {
-        #[doc = "Creates a new [`SlotStateOutput`]."]
-        pub fn new() -> Self {
+    impl DefaultLayoutConfig {
+        #[doc = "Creates a new [`DefaultLayoutConfig`]."]
+        pub fn new(system_size: NumBytes) -> Self {
             Self {
-                active: ::std::default::Default::default(),
-                hashes: ::std::default::Default::default(),
-                size: ::std::default::Default::default(),
-                updated_at: ::std::default::Default::default(),
+                system_size,
+                config_size: ::std::default::Default::default(),
+                boot_size: ::std::default::Default::default(),
+                alignment: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `active`."]
-        pub fn set_active(&mut self, active: ::std::option::Option<bool>) -> &mut Self {
-            self.active = active;
+        #[doc = "Sets the value of `config_size`."]
+        pub fn set_config_size(
+            &mut self,
+            config_size: ::std::option::Option<NumBytes>,
+        ) -> &mut Self {
+            self.config_size = config_size;
             self
         }
-        #[doc = "Sets the value of `active`."]
-        pub fn with_active(mut self, active: ::std::option::Option<bool>) -> Self {
-            self.active = active;
+        #[doc = "Sets the value of `config_size`."]
+        pub fn with_config_size(mut self, config_size: ::std::option::Option<NumBytes>) -> Self {
+            self.config_size = config_size;
             self
         }
-        #[doc = "Sets the value of `hashes`."]
-        pub fn set_hashes(
-            &mut self,
-            hashes: ::std::option::Option<
-                indexmap::IndexMap<::std::string::String, ::std::string::String>,
-            >,
-        ) -> &mut Self {
-            self.hashes = hashes;
+        #[doc = "Sets the value of `boot_size`."]
+        pub fn set_boot_size(&mut self, boot_size: ::std::option::Option<NumBytes>) -> &mut Self {
+            self.boot_size = boot_size;
             self
         }
-        #[doc = "Sets the value of `hashes`."]
-        pub fn with_hashes(
-            mut self,
-            hashes: ::std::option::Option<
-                indexmap::IndexMap<::std::string::String, ::std::string::String>,
-            >,
-        ) -> Self {
-            self.hashes = hashes;
+        #[doc = "Sets the value of `boot_size`."]
+        pub fn with_boot_size(mut self, boot_size: ::std::option::Option<NumBytes>) -> Self {
+            self.boot_size = boot_size;
             self
         }
-        #[doc = "Sets the value of `size`."]
-        pub fn set_size(&mut self, size: ::std::option::Option<u64>) -> &mut Self {
-            self.size = size;
+        #[doc = "Sets the value of `system_size`."]
+        pub fn set_system_size(&mut self, system_size: NumBytes) -> &mut Self {
+            self.system_size = system_size;
             self
         }
-        #[doc = "Sets the value of `size`."]
-        pub fn with_size(mut self, size: ::std::option::Option<u64>) -> Self {
-            self.size = size;
+        #[doc = "Sets the value of `system_size`."]
+        pub fn with_system_size(mut self, system_size: NumBytes) -> Self {
+            self.system_size = system_size;
             self
         }
-        #[doc = "Sets the value of `updated_at`."]
-        pub fn set_updated_at(
-            &mut self,
-            updated_at: ::std::option::Option<::std::string::String>,
-        ) -> &mut Self {
-            self.updated_at = updated_at;
+        #[doc = "Sets the value of `alignment`."]
+        pub fn set_alignment(&mut self, alignment: ::std::option::Option<NumBytes>) -> &mut Self {
+            self.alignment = alignment;
             self
         }
-        #[doc = "Sets the value of `updated_at`."]
-        pub fn with_updated_at(
-            mut self,
-            updated_at: ::std::option::Option<::std::string::String>,
-        ) -> Self {
-            self.updated_at = updated_at;
+        #[doc = "Sets the value of `alignment`."]
+        pub fn with_alignment(mut self, alignment: ::std::option::Option<NumBytes>) -> Self {
+            self.alignment = alignment;
             self
         }
     }
-    impl ::std::default::Default for SlotStateOutput {
-        fn default() -> Self {
-            Self::new()
-        }
-    }
     #[automatically_derived]
-    impl __serde::Serialize for SlotStateOutput {
+    impl __serde::Serialize for DefaultLayoutConfig {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "SlotStateOutput", 4usize)?;
-            __record
-                .serialize_optional_field("active", ::core::option::Option::as_ref(&self.active))?;
-            __record
-                .serialize_optional_field("hashes", ::core::option::Option::as_ref(&self.hashes))?;
-            __record
-                .serialize_optional_field("size", ::core::option::Option::as_ref(&self.size))?;
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "DefaultLayoutConfig",
+                4usize,
+            )?;
             __record.serialize_optional_field(
-                "updatedAt",
-                ::core::option::Option::as_ref(&self.updated_at),
+                "config-size",
+                ::core::option::Option::as_ref(&self.config_size),
+            )?;
+            __record.serialize_optional_field(
+                "boot-size",
+                ::core::option::Option::as_ref(&self.boot_size),
+            )?;
+            __record.serialize_field("system-size", &self.system_size)?;
+            __record.serialize_optional_field(
+                "alignment",
+                ::core::option::Option::as_ref(&self.alignment),
             )?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for SlotStateOutput {
+    impl<'de> __serde::Deserialize<'de> for DefaultLayoutConfig {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -2106,12 +2533,12 @@ pub mod output {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = SlotStateOutput;
+                type Value = DefaultLayoutConfig;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record SlotStateOutput")
+                    ::core::fmt::Formatter::write_str(__formatter, "record DefaultLayoutConfig")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -2122,7 +2549,7 @@ pub mod output {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<bool>,
+                        ::std::option::Option<NumBytes>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
@@ -2133,9 +2560,7 @@ pub mod output {
                         }
                     };
                     let __field1 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, ::std::string::String>,
-                        >,
+                        ::std::option::Option<NumBytes>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
@@ -2145,19 +2570,20 @@ pub mod output {
                             );
                         }
                     };
-                    let __field2 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<u64>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
-                            );
-                        }
-                    };
+                    let __field2 =
+                        match __serde::de::SeqAccess::next_element::<NumBytes>(&mut __seq)? {
+                            ::core::option::Option::Some(__value) => __value,
+                            ::core::option::Option::None => {
+                                return ::core::result::Result::Err(
+                                    __serde::de::Error::invalid_length(
+                                        2usize,
+                                        &"record with 4 fields",
+                                    ),
+                                );
+                            }
+                        };
                     let __field3 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<::std::string::String>,
+                        ::std::option::Option<NumBytes>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
@@ -2167,11 +2593,11 @@ pub mod output {
                             );
                         }
                     };
-                    ::core::result::Result::Ok(SlotStateOutput {
-                        active: __field0,
-                        hashes: __field1,
-                        size: __field2,
-                        updated_at: __field3,
+                    ::core::result::Result::Ok(DefaultLayoutConfig {
+                        config_size: __field0,
+                        boot_size: __field1,
+                        system_size: __field2,
+                        alignment: __field3,
                     })
                 }
                 #[inline]
@@ -2184,10 +2610,9 @@ pub mod output {
                 {
                     #[doc(hidden)]
                     const __IDENTIFIERS: &'static [&'static str] =
-                        &["active", "hashes", "size", "updatedAt"];
+                        &["config-size", "boot-size", "system-size", "alignment"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"active\", \"hashes\", \"size\", \"updatedAt\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"config-size\", \"boot-size\", \"system-size\", \"alignment\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -2230,10 +2655,16 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "active" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                "hashes" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                                "size" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                "updatedAt" => {
+                                "config-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "boot-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                "system-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                "alignment" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
@@ -2247,14 +2678,16 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"active" => {
+                                b"config-size" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                b"hashes" => {
+                                b"boot-size" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
-                                b"size" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                b"updatedAt" => {
+                                b"system-size" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                b"alignment" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier3)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
@@ -2275,18 +2708,14 @@ pub mod output {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::option::Option<bool>> =
+                    let mut __field0: ::core::option::Option<::std::option::Option<NumBytes>> =
                         ::core::option::Option::None;
-                    let mut __field1: ::core::option::Option<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, ::std::string::String>,
-                        >,
-                    > = ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<::std::option::Option<u64>> =
+                    let mut __field1: ::core::option::Option<::std::option::Option<NumBytes>> =
+                        ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<NumBytes> =
+                        ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<::std::option::Option<NumBytes>> =
                         ::core::option::Option::None;
-                    let mut __field3: ::core::option::Option<
-                        ::std::option::Option<::std::string::String>,
-                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -2295,13 +2724,13 @@ pub mod output {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "active",
+                                            "config-size",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<bool>,
+                                        ::std::option::Option<NumBytes>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -2309,44 +2738,39 @@ pub mod output {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "hashes",
+                                            "boot-size",
                                         ),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<
-                                            indexmap::IndexMap<
-                                                ::std::string::String,
-                                                ::std::string::String,
-                                            >,
-                                        >,
+                                        ::std::option::Option<NumBytes>,
                                     >(&mut __map)?,
                                 );
                             }
                             __Identifier::__Identifier2 => {
                                 if ::core::option::Option::is_some(&__field2) {
                                     return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field("size"),
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "system-size",
+                                        ),
                                     );
                                 }
                                 __field2 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<::std::option::Option<u64>>(
-                                        &mut __map,
-                                    )?,
+                                    __serde::de::MapAccess::next_value::<NumBytes>(&mut __map)?,
                                 );
                             }
                             __Identifier::__Identifier3 => {
                                 if ::core::option::Option::is_some(&__field3) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "updatedAt",
+                                            "alignment",
                                         ),
                                     );
                                 }
                                 __field3 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<::std::string::String>,
+                                        ::std::option::Option<NumBytes>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -2367,25 +2791,30 @@ pub mod output {
                     };
                     let __field2 = match __field2 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("system-size"),
+                            );
+                        }
                     };
                     let __field3 = match __field3 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(SlotStateOutput {
-                        active: __field0,
-                        hashes: __field1,
-                        size: __field2,
-                        updated_at: __field3,
+                    ::core::result::Result::Ok(DefaultLayoutConfig {
+                        config_size: __field0,
+                        boot_size: __field1,
+                        system_size: __field2,
+                        alignment: __field3,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["active", "hashes", "size", "updatedAt"];
+            const __FIELDS: &'static [&'static str] =
+                &["config-size", "boot-size", "system-size", "alignment"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "SlotStateOutput",
+                "DefaultLayoutConfig",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -2393,113 +2822,75 @@ pub mod output {
             )
         }
     }
+}
+pub mod output {
+    #![doc = ""]
+    #[allow(unused)]
+    use :: serde as __serde;
+    #[allow(unused)]
+    use :: sidex_serde as __sidex_serde;
     #[doc = ""]
     #[derive(Clone, Debug)]
-    pub struct BootStateOutput {
-        #[doc = ""]
-        pub boot_flow: ::std::string::String,
-        #[doc = ""]
-        pub active_group: ::std::option::Option<::std::string::String>,
+    pub struct SystemStateOutput {
         #[doc = ""]
-        pub default_group: ::std::option::Option<::std::string::String>,
+        pub slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
         #[doc = ""]
-        pub groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+        pub boot: ::std::option::Option<BootStateOutput>,
     }
-    impl BootStateOutput {
-        #[doc = "Creates a new [`BootStateOutput`]."]
-        pub fn new(
-            boot_flow: ::std::string::String,
-            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
-        ) -> Self {
+    impl SystemStateOutput {
+        #[doc = "Creates a new [`SystemStateOutput`]."]
+        pub fn new(slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>) -> Self {
             Self {
-                boot_flow,
-                groups,
-                active_group: ::std::default::Default::default(),
-                default_group: ::std::default::Default::default(),
+                slots,
+                boot: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `boot_flow`."]
-        pub fn set_boot_flow(&mut self, boot_flow: ::std::string::String) -> &mut Self {
-            self.boot_flow = boot_flow;
-            self
-        }
-        #[doc = "Sets the value of `boot_flow`."]
-        pub fn with_boot_flow(mut self, boot_flow: ::std::string::String) -> Self {
-            self.boot_flow = boot_flow;
-            self
-        }
-        #[doc = "Sets the value of `active_group`."]
-        pub fn set_active_group(
-            &mut self,
-            active_group: ::std::option::Option<::std::string::String>,
-        ) -> &mut Self {
-            self.active_group = active_group;
-            self
-        }
-        #[doc = "Sets the value of `active_group`."]
-        pub fn with_active_group(
-            mut self,
-            active_group: ::std::option::Option<::std::string::String>,
-        ) -> Self {
-            self.active_group = active_group;
-            self
-        }
-        #[doc = "Sets the value of `default_group`."]
-        pub fn set_default_group(
+        #[doc = "Sets the value of `slots`."]
+        pub fn set_slots(
             &mut self,
-            default_group: ::std::option::Option<::std::string::String>,
+            slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
         ) -> &mut Self {
-            self.default_group = default_group;
+            self.slots = slots;
             self
         }
-        #[doc = "Sets the value of `default_group`."]
-        pub fn with_default_group(
+        #[doc = "Sets the value of `slots`."]
+        pub fn with_slots(
             mut self,
-            default_group: ::std::option::Option<::std::string::String>,
+            slots: indexmap::IndexMap<::std::string::String, SlotStateOutput>,
         ) -> Self {
-            self.default_group = default_group;
+            self.slots = slots;
             self
         }
-        #[doc = "Sets the value of `groups`."]
-        pub fn set_groups(
-            &mut self,
-            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
-        ) -> &mut Self {
-            self.groups = groups;
+        #[doc = "Sets the value of `boot`."]
+        pub fn set_boot(&mut self, boot: ::std::option::Option<BootStateOutput>) -> &mut Self {
+            self.boot = boot;
             self
         }
-        #[doc = "Sets the value of `groups`."]
-        pub fn with_groups(
-            mut self,
-            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
-        ) -> Self {
-            self.groups = groups;
+        #[doc = "Sets the value of `boot`."]
+        pub fn with_boot(mut self, boot: ::std::option::Option<BootStateOutput>) -> Self {
+            self.boot = boot;
             self
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for BootStateOutput {
+    impl __serde::Serialize for SystemStateOutput {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "BootStateOutput", 4usize)?;
-            __record.serialize_field("bootFlow", &self.boot_flow)?;
-            __record.serialize_optional_field(
-                "activeGroup",
-                ::core::option::Option::as_ref(&self.active_group),
-            )?;
-            __record.serialize_optional_field(
-                "defaultGroup",
-                ::core::option::Option::as_ref(&self.default_group),
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "SystemStateOutput",
+                2usize,
             )?;
-            __record.serialize_field("groups", &self.groups)?;
+            __record.serialize_field("slots", &self.slots)?;
+            __record
+                .serialize_optional_field("boot", ::core::option::Option::as_ref(&self.boot))?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for BootStateOutput {
+    impl<'de> __serde::Deserialize<'de> for SystemStateOutput {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -2508,12 +2899,12 @@ pub mod output {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = BootStateOutput;
+                type Value = SystemStateOutput;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record BootStateOutput")
+                    ::core::fmt::Formatter::write_str(__formatter, "record SystemStateOutput")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -2524,54 +2915,30 @@ pub mod output {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::string::String,
+                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
                             );
                         }
                     };
                     let __field1 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<::std::string::String>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
-                            );
-                        }
-                    };
-                    let __field2 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<::std::string::String>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
-                            );
-                        }
-                    };
-                    let __field3 = match __serde::de::SeqAccess::next_element::<
-                        indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+                        ::std::option::Option<BootStateOutput>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(BootStateOutput {
-                        boot_flow: __field0,
-                        active_group: __field1,
-                        default_group: __field2,
-                        groups: __field3,
+                    ::core::result::Result::Ok(SystemStateOutput {
+                        slots: __field0,
+                        boot: __field1,
                     })
                 }
                 #[inline]
@@ -2583,17 +2950,15 @@ pub mod output {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] =
-                        &["bootFlow", "activeGroup", "defaultGroup", "groups"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["slots", "boot"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"bootFlow\", \"activeGroup\", \"defaultGroup\", \"groups\"]" ;
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"slots\", \"boot\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
-                        __Identifier2,
-                        __Identifier3,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -2616,8 +2981,6 @@ pub mod output {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2629,16 +2992,8 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "bootFlow" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
-                                "activeGroup" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
-                                }
-                                "defaultGroup" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
-                                }
-                                "groups" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                "slots" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "boot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2650,18 +3005,8 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"bootFlow" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
-                                b"activeGroup" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
-                                }
-                                b"defaultGroup" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
-                                }
-                                b"groups" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
-                                }
+                                b"slots" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                b"boot" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2680,16 +3025,11 @@ pub mod output {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::string::String> =
-                        ::core::option::Option::None;
-                    let mut __field1: ::core::option::Option<
-                        ::std::option::Option<::std::string::String>,
-                    > = ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<
-                        ::std::option::Option<::std::string::String>,
+                    let mut __field0: ::core::option::Option<
+                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
                     > = ::core::option::Option::None;
-                    let mut __field3: ::core::option::Option<
-                        indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+                    let mut __field1: ::core::option::Option<
+                        ::std::option::Option<BootStateOutput>,
                     > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -2699,58 +3039,25 @@ pub mod output {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "bootFlow",
+                                            "slots",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<::std::string::String>(
-                                        &mut __map,
-                                    )?,
+                                    __serde::de::MapAccess::next_value::<
+                                        indexmap::IndexMap<::std::string::String, SlotStateOutput>,
+                                    >(&mut __map)?,
                                 );
                             }
                             __Identifier::__Identifier1 => {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "activeGroup",
-                                        ),
+                                        <__A::Error as __serde::de::Error>::duplicate_field("boot"),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<::std::string::String>,
-                                    >(&mut __map)?,
-                                );
-                            }
-                            __Identifier::__Identifier2 => {
-                                if ::core::option::Option::is_some(&__field2) {
-                                    return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "defaultGroup",
-                                        ),
-                                    );
-                                }
-                                __field2 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<::std::string::String>,
-                                    >(&mut __map)?,
-                                );
-                            }
-                            __Identifier::__Identifier3 => {
-                                if ::core::option::Option::is_some(&__field3) {
-                                    return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "groups",
-                                        ),
-                                    );
-                                }
-                                __field3 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        indexmap::IndexMap<
-                                            ::std::string::String,
-                                            BootGroupStateOutput,
-                                        >,
+                                        ::std::option::Option<BootStateOutput>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -2765,7 +3072,7 @@ pub mod output {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("bootFlow"),
+                                <__A::Error as __serde::de::Error>::missing_field("slots"),
                             );
                         }
                     };
@@ -2773,32 +3080,17 @@ pub mod output {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    let __field2 = match __field2 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
-                    };
-                    let __field3 = match __field3 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("groups"),
-                            );
-                        }
-                    };
-                    ::core::result::Result::Ok(BootStateOutput {
-                        boot_flow: __field0,
-                        active_group: __field1,
-                        default_group: __field2,
-                        groups: __field3,
+                    ::core::result::Result::Ok(SystemStateOutput {
+                        slots: __field0,
+                        boot: __field1,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] =
-                &["bootFlow", "activeGroup", "defaultGroup", "groups"];
+            const __FIELDS: &'static [&'static str] = &["slots", "boot"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "BootStateOutput",
+                "SystemStateOutput",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -2808,48 +3100,126 @@ pub mod output {
     }
     #[doc = ""]
     #[derive(Clone, Debug)]
-    pub struct BootGroupStateOutput {}
-    impl BootGroupStateOutput {
-        #[doc = "Creates a new [`BootGroupStateOutput`]."]
+    pub struct SlotStateOutput {
+        #[doc = ""]
+        pub active: ::std::option::Option<bool>,
+        #[doc = ""]
+        pub hashes:
+            ::std::option::Option<indexmap::IndexMap<::std::string::String, ::std::string::String>>,
+        #[doc = ""]
+        pub size: ::std::option::Option<u64>,
+        #[doc = ""]
+        pub updated_at: ::std::option::Option<::std::string::String>,
+    }
+    impl SlotStateOutput {
+        #[doc = "Creates a new [`SlotStateOutput`]."]
         pub fn new() -> Self {
-            Self {}
+            Self {
+                active: ::std::default::Default::default(),
+                hashes: ::std::default::Default::default(),
+                size: ::std::default::Default::default(),
+                updated_at: ::std::default::Default::default(),
+            }
+        }
+        #[doc = "Sets the value of `active`."]
+        pub fn set_active(&mut self, active: ::std::option::Option<bool>) -> &mut Self {
+            self.active = active;
+            self
+        }
+        #[doc = "Sets the value of `active`."]
+        pub fn with_active(mut self, active: ::std::option::Option<bool>) -> Self {
+            self.active = active;
+            self
+        }
+        #[doc = "Sets the value of `hashes`."]
+        pub fn set_hashes(
+            &mut self,
+            hashes: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, ::std::string::String>,
+            >,
+        ) -> &mut Self {
+            self.hashes = hashes;
+            self
+        }
+        #[doc = "Sets the value of `hashes`."]
+        pub fn with_hashes(
+            mut self,
+            hashes: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, ::std::string::String>,
+            >,
+        ) -> Self {
+            self.hashes = hashes;
+            self
+        }
+        #[doc = "Sets the value of `size`."]
+        pub fn set_size(&mut self, size: ::std::option::Option<u64>) -> &mut Self {
+            self.size = size;
+            self
+        }
+        #[doc = "Sets the value of `size`."]
+        pub fn with_size(mut self, size: ::std::option::Option<u64>) -> Self {
+            self.size = size;
+            self
+        }
+        #[doc = "Sets the value of `updated_at`."]
+        pub fn set_updated_at(
+            &mut self,
+            updated_at: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.updated_at = updated_at;
+            self
+        }
+        #[doc = "Sets the value of `updated_at`."]
+        pub fn with_updated_at(
+            mut self,
+            updated_at: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.updated_at = updated_at;
+            self
         }
     }
-    impl ::std::default::Default for BootGroupStateOutput {
+    impl ::std::default::Default for SlotStateOutput {
         fn default() -> Self {
             Self::new()
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for BootGroupStateOutput {
+    impl __serde::Serialize for SlotStateOutput {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record = __sidex_serde::ser::RecordSerializer::new(
-                __serializer,
-                "BootGroupStateOutput",
-                0usize,
-            )?;
-            __record.end()
-        }
-    }
-    #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for BootGroupStateOutput {
-        fn deserialize<__D: __serde::Deserializer<'de>>(
-            __deserializer: __D,
-        ) -> ::std::result::Result<Self, __D::Error> {
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "SlotStateOutput", 4usize)?;
+            __record
+                .serialize_optional_field("active", ::core::option::Option::as_ref(&self.active))?;
+            __record
+                .serialize_optional_field("hashes", ::core::option::Option::as_ref(&self.hashes))?;
+            __record
+                .serialize_optional_field("size", ::core::option::Option::as_ref(&self.size))?;
+            __record.serialize_optional_field(
+                "updatedAt",
+                ::core::option::Option::as_ref(&self.updated_at),
+            )?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for SlotStateOutput {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
             #[doc(hidden)]
             struct __Visitor {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = BootGroupStateOutput;
+                type Value = SlotStateOutput;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record BootGroupStateOutput")
+                    ::core::fmt::Formatter::write_str(__formatter, "record SlotStateOutput")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -2859,7 +3229,58 @@ pub mod output {
                 where
                     __A: __serde::de::SeqAccess<'de>,
                 {
-                    ::core::result::Result::Ok(BootGroupStateOutput {})
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<bool>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, ::std::string::String>,
+                        >,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<u64>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(SlotStateOutput {
+                        active: __field0,
+                        hashes: __field1,
+                        size: __field2,
+                        updated_at: __field3,
+                    })
                 }
                 #[inline]
                 fn visit_map<__A>(
@@ -2870,12 +3291,18 @@ pub mod output {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &[];
+                    const __IDENTIFIERS: &'static [&'static str] =
+                        &["active", "hashes", "size", "updatedAt"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in []";
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"active\", \"hashes\", \"size\", \"updatedAt\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Identifier2,
+                        __Identifier3,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -2896,6 +3323,10 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2907,6 +3338,12 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
+                                "active" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "hashes" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "size" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                "updatedAt" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2918,6 +3355,16 @@ pub mod output {
                             __E: __serde::de::Error,
                         {
                             match __value {
+                                b"active" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"hashes" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                b"size" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                b"updatedAt" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -2936,10 +3383,81 @@ pub mod output {
                             )
                         }
                     }
+                    let mut __field0: ::core::option::Option<::std::option::Option<bool>> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, ::std::string::String>,
+                        >,
+                    > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<::std::option::Option<u64>> =
+                        ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
                         match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "active",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<bool>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "hashes",
+                                        ),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<
+                                            indexmap::IndexMap<
+                                                ::std::string::String,
+                                                ::std::string::String,
+                                            >,
+                                        >,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("size"),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::option::Option<u64>>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "updatedAt",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
                             _ => {
                                 __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
                                     &mut __map,
@@ -2947,14 +3465,35 @@ pub mod output {
                             }
                         }
                     }
-                    ::core::result::Result::Ok(BootGroupStateOutput {})
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(SlotStateOutput {
+                        active: __field0,
+                        hashes: __field1,
+                        size: __field2,
+                        updated_at: __field3,
+                    })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &[];
+            const __FIELDS: &'static [&'static str] = &["active", "hashes", "size", "updatedAt"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "BootGroupStateOutput",
+                "SlotStateOutput",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -2962,82 +3501,113 @@ pub mod output {
             )
         }
     }
-}
-pub mod state {
-    #![doc = "State management configuration.\n"]
-    #[allow(unused)]
-    use :: serde as __serde;
-    #[allow(unused)]
-    use :: sidex_serde as __sidex_serde;
-    #[doc = "State management configuration.\n"]
+    #[doc = ""]
     #[derive(Clone, Debug)]
-    pub struct StateConfig {
-        #[doc = "Configuration of the root overlay.\n"]
-        pub overlay: ::std::option::Option<OverlayConfig>,
-        #[doc = "Files and directories to persist.\n"]
-        pub persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+    pub struct BootStateOutput {
+        #[doc = ""]
+        pub boot_flow: ::std::string::String,
+        #[doc = ""]
+        pub active_group: ::std::option::Option<::std::string::String>,
+        #[doc = ""]
+        pub default_group: ::std::option::Option<::std::string::String>,
+        #[doc = ""]
+        pub groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
     }
-    impl StateConfig {
-        #[doc = "Creates a new [`StateConfig`]."]
-        pub fn new() -> Self {
+    impl BootStateOutput {
+        #[doc = "Creates a new [`BootStateOutput`]."]
+        pub fn new(
+            boot_flow: ::std::string::String,
+            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+        ) -> Self {
             Self {
-                overlay: ::std::default::Default::default(),
-                persist: ::std::default::Default::default(),
+                boot_flow,
+                groups,
+                active_group: ::std::default::Default::default(),
+                default_group: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `overlay`."]
-        pub fn set_overlay(&mut self, overlay: ::std::option::Option<OverlayConfig>) -> &mut Self {
-            self.overlay = overlay;
+        #[doc = "Sets the value of `boot_flow`."]
+        pub fn set_boot_flow(&mut self, boot_flow: ::std::string::String) -> &mut Self {
+            self.boot_flow = boot_flow;
             self
         }
-        #[doc = "Sets the value of `overlay`."]
-        pub fn with_overlay(mut self, overlay: ::std::option::Option<OverlayConfig>) -> Self {
-            self.overlay = overlay;
+        #[doc = "Sets the value of `boot_flow`."]
+        pub fn with_boot_flow(mut self, boot_flow: ::std::string::String) -> Self {
+            self.boot_flow = boot_flow;
             self
         }
-        #[doc = "Sets the value of `persist`."]
-        pub fn set_persist(
+        #[doc = "Sets the value of `active_group`."]
+        pub fn set_active_group(
             &mut self,
-            persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+            active_group: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.persist = persist;
+            self.active_group = active_group;
             self
         }
-        #[doc = "Sets the value of `persist`."]
-        pub fn with_persist(
+        #[doc = "Sets the value of `active_group`."]
+        pub fn with_active_group(
             mut self,
-            persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+            active_group: ::std::option::Option<::std::string::String>,
         ) -> Self {
-            self.persist = persist;
+            self.active_group = active_group;
             self
         }
-    }
-    impl ::std::default::Default for StateConfig {
-        fn default() -> Self {
-            Self::new()
+        #[doc = "Sets the value of `default_group`."]
+        pub fn set_default_group(
+            &mut self,
+            default_group: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.default_group = default_group;
+            self
+        }
+        #[doc = "Sets the value of `default_group`."]
+        pub fn with_default_group(
+            mut self,
+            default_group: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.default_group = default_group;
+            self
+        }
+        #[doc = "Sets the value of `groups`."]
+        pub fn set_groups(
+            &mut self,
+            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+        ) -> &mut Self {
+            self.groups = groups;
+            self
+        }
+        #[doc = "Sets the value of `groups`."]
+        pub fn with_groups(
+            mut self,
+            groups: indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+        ) -> Self {
+            self.groups = groups;
+            self
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for StateConfig {
+    impl __serde::Serialize for BootStateOutput {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "StateConfig", 2usize)?;
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "BootStateOutput", 4usize)?;
+            __record.serialize_field("bootFlow", &self.boot_flow)?;
             __record.serialize_optional_field(
-                "overlay",
-                ::core::option::Option::as_ref(&self.overlay),
+                "activeGroup",
+                ::core::option::Option::as_ref(&self.active_group),
             )?;
             __record.serialize_optional_field(
-                "persist",
-                ::core::option::Option::as_ref(&self.persist),
+                "defaultGroup",
+                ::core::option::Option::as_ref(&self.default_group),
             )?;
+            __record.serialize_field("groups", &self.groups)?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for StateConfig {
+    impl<'de> __serde::Deserialize<'de> for BootStateOutput {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -3046,12 +3616,12 @@ pub mod state {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = StateConfig;
+                type Value = BootStateOutput;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record StateConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record BootStateOutput")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -3062,30 +3632,54 @@ pub mod state {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<OverlayConfig>,
+                        ::std::string::String,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 4 fields"),
                             );
                         }
                     };
                     let __field1 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 4 fields"),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(StateConfig {
-                        overlay: __field0,
-                        persist: __field1,
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<
+                        indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 4 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(BootStateOutput {
+                        boot_flow: __field0,
+                        active_group: __field1,
+                        default_group: __field2,
+                        groups: __field3,
                     })
                 }
                 #[inline]
@@ -3097,15 +3691,17 @@ pub mod state {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["overlay", "persist"];
+                    const __IDENTIFIERS: &'static [&'static str] =
+                        &["bootFlow", "activeGroup", "defaultGroup", "groups"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"overlay\", \"persist\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"bootFlow\", \"activeGroup\", \"defaultGroup\", \"groups\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
+                        __Identifier2,
+                        __Identifier3,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -3128,6 +3724,8 @@ pub mod state {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3139,12 +3737,16 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "overlay" => {
+                                "bootFlow" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                "persist" => {
+                                "activeGroup" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                "defaultGroup" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                "groups" => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3156,12 +3758,18 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"overlay" => {
+                                b"bootFlow" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                b"persist" => {
+                                b"activeGroup" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                b"defaultGroup" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                b"groups" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3180,10 +3788,16 @@ pub mod state {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::option::Option<OverlayConfig>> =
+                    let mut __field0: ::core::option::Option<::std::string::String> =
                         ::core::option::Option::None;
                     let mut __field1: ::core::option::Option<
-                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<
+                        indexmap::IndexMap<::std::string::String, BootGroupStateOutput>,
                     > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -3193,27 +3807,58 @@ pub mod state {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "overlay",
+                                            "bootFlow",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<OverlayConfig>,
-                                    >(&mut __map)?,
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
                                 );
                             }
                             __Identifier::__Identifier1 => {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "persist",
+                                            "activeGroup",
                                         ),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "defaultGroup",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "groups",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        indexmap::IndexMap<
+                                            ::std::string::String,
+                                            BootGroupStateOutput,
+                                        >,
                                     >(&mut __map)?,
                                 );
                             }
@@ -3226,23 +3871,42 @@ pub mod state {
                     }
                     let __field0 = match __field0 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("bootFlow"),
+                            );
+                        }
                     };
                     let __field1 = match __field1 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(StateConfig {
-                        overlay: __field0,
-                        persist: __field1,
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("groups"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(BootStateOutput {
+                        boot_flow: __field0,
+                        active_group: __field1,
+                        default_group: __field2,
+                        groups: __field3,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["overlay", "persist"];
+            const __FIELDS: &'static [&'static str] =
+                &["bootFlow", "activeGroup", "defaultGroup", "groups"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "StateConfig",
+                "BootStateOutput",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -3250,469 +3914,2697 @@ pub mod state {
             )
         }
     }
-    #[doc = "Configuration of the root overlay.\n"]
+    #[doc = ""]
     #[derive(Clone, Debug)]
-    pub enum OverlayConfig {
-        #[doc = "Put the overlay on the data partition and persist it across boots.\n"]
-        Persist,
-        #[doc = "Put the overlay on the data partition and discard it on each boot.\n"]
-        Discard,
-        #[doc = "Put the overlay in a temporary, in-memory filesystem.\n"]
-        InMemory,
-        #[doc = "Disable the overlay.\n"]
-        Disabled,
+    pub struct BootGroupStateOutput {}
+    impl BootGroupStateOutput {
+        #[doc = "Creates a new [`BootGroupStateOutput`]."]
+        pub fn new() -> Self {
+            Self {}
+        }
+    }
+    impl ::std::default::Default for BootGroupStateOutput {
+        fn default() -> Self {
+            Self::new()
+        }
     }
     #[automatically_derived]
-    impl __serde::Serialize for OverlayConfig {
+    impl __serde::Serialize for BootGroupStateOutput {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let __serializer =
-                __sidex_serde::ser::VariantSerializer::new(__serializer, "OverlayConfig");
-            match self {
-                Self::Persist => __serializer.serialize_tag("persist", 0u32),
-                Self::Discard => __serializer.serialize_tag("discard", 1u32),
-                Self::InMemory => __serializer.serialize_tag("in-memory", 2u32),
-                Self::Disabled => __serializer.serialize_tag("disabled", 3u32),
-            }
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "BootGroupStateOutput",
+                0usize,
+            )?;
+            __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for OverlayConfig {
+    impl<'de> __serde::Deserialize<'de> for BootGroupStateOutput {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
             #[doc(hidden)]
-            const __IDENTIFIERS: &'static [&'static str] =
-                &["persist", "discard", "in-memory", "disabled"];
-            #[doc(hidden)]
-            const __EXPECTING_IDENTIFIERS: &'static str =
-                "an identifier in [\"persist\", \"discard\", \"in-memory\", \"disabled\"]";
-            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
-            #[doc(hidden)]
-            enum __Identifier {
-                __Identifier0,
-                __Identifier1,
-                __Identifier2,
-                __Identifier3,
-            }
-            #[doc(hidden)]
-            struct __IdentifierVisitor;
-            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
-                type Value = __Identifier;
-                fn expecting(
-                    &self,
-                    __formatter: &mut ::core::fmt::Formatter,
-                ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
-                }
-                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                        3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                        __variant => {
-                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
-                                __serde::de::Unexpected::Unsigned(__variant),
-                                &__EXPECTING_IDENTIFIERS,
-                            ))
-                        }
-                    }
-                }
-                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        "persist" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        "discard" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        "in-memory" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                        "disabled" => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                        __variant => ::core::result::Result::Err(
-                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
-                        ),
-                    }
-                }
-                fn visit_bytes<__E>(
-                    self,
-                    __value: &[u8],
-                ) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        b"persist" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        b"discard" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        b"in-memory" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                        b"disabled" => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                        __variant => {
-                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
-                                __serde::de::Unexpected::Bytes(__variant),
-                                &__EXPECTING_IDENTIFIERS,
-                            ))
-                        }
-                    }
-                }
-            }
-            impl<'de> __serde::Deserialize<'de> for __Identifier {
-                #[inline]
-                fn deserialize<__D>(__deserializer: __D) -> ::core::result::Result<Self, __D::Error>
-                where
-                    __D: __serde::Deserializer<'de>,
-                {
-                    __serde::Deserializer::deserialize_identifier(
-                        __deserializer,
-                        __IdentifierVisitor,
-                    )
-                }
-            }
-            #[doc(hidden)]
-            const __VARIANTS: &'static [&'static str] =
-                &["persist", "discard", "in-memory", "disabled"];
-            #[doc(hidden)]
             struct __Visitor {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = OverlayConfig;
+                type Value = BootGroupStateOutput;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "enum OverlayConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record BootGroupStateOutput")
                 }
                 #[inline]
-                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __E: __serde::de::Error,
+                    __A: __serde::de::SeqAccess<'de>,
                 {
-                    let __identifier = __IdentifierVisitor.visit_str(__value)?;
-                    #[allow(unreachable_patterns)]
-                    match __identifier {
-                        __Identifier::__Identifier0 => {
-                            ::core::result::Result::Ok(OverlayConfig::Persist)
-                        }
-                        __Identifier::__Identifier1 => {
-                            ::core::result::Result::Ok(OverlayConfig::Discard)
-                        }
-                        __Identifier::__Identifier2 => {
-                            ::core::result::Result::Ok(OverlayConfig::InMemory)
-                        }
-                        __Identifier::__Identifier3 => {
-                            ::core::result::Result::Ok(OverlayConfig::Disabled)
-                        }
-                        _ => Err(__E::invalid_value(
-                            __serde::de::Unexpected::Str(__value),
-                            &self,
-                        )),
-                    }
+                    ::core::result::Result::Ok(BootGroupStateOutput {})
                 }
                 #[inline]
-                fn visit_enum<__A>(
+                fn visit_map<__A>(
                     self,
-                    __data: __A,
+                    mut __map: __A,
                 ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __A: __serde::de::EnumAccess<'de>,
+                    __A: __serde::de::MapAccess<'de>,
                 {
-                    match __serde::de::EnumAccess::variant::<__Identifier>(__data)? {
-                        (__Identifier::__Identifier0, __variant) => {
-                            __serde::de::VariantAccess::unit_variant(__variant)?;
-                            ::core::result::Result::Ok(OverlayConfig::Persist)
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &[];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in []";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
                         }
-                        (__Identifier::__Identifier1, __variant) => {
-                            __serde::de::VariantAccess::unit_variant(__variant)?;
-                            ::core::result::Result::Ok(OverlayConfig::Discard)
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
                         }
-                        (__Identifier::__Identifier2, __variant) => {
-                            __serde::de::VariantAccess::unit_variant(__variant)?;
-                            ::core::result::Result::Ok(OverlayConfig::InMemory)
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
                         }
-                        (__Identifier::__Identifier3, __variant) => {
-                            __serde::de::VariantAccess::unit_variant(__variant)?;
-                            ::core::result::Result::Ok(OverlayConfig::Disabled)
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
                         }
                     }
+                    ::core::result::Result::Ok(BootGroupStateOutput {})
                 }
             }
-            __serde::Deserializer::deserialize_enum(
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &[];
+            __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "OverlayConfig",
-                __VARIANTS,
+                "BootGroupStateOutput",
+                __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
                 },
             )
         }
     }
-    #[doc = "Configuration to persist a file or directory.\n"]
+    #[doc = ""]
     #[derive(Clone, Debug)]
-    pub enum PersistConfig {
-        #[doc = "Configuration to persist a file.\n"]
-        File(PersistFileConfig),
-        #[doc = "Configuration to persist a directory.\n"]
-        Directory(PersistDirectoryConfig),
+    pub struct StateStatusOutput {
+        #[doc = ""]
+        pub overlay: ::std::string::String,
+        #[doc = ""]
+        pub usage: indexmap::IndexMap<::std::string::String, UsageOutput>,
+    }
+    impl StateStatusOutput {
+        #[doc = "Creates a new [`StateStatusOutput`]."]
+        pub fn new(
+            overlay: ::std::string::String,
+            usage: indexmap::IndexMap<::std::string::String, UsageOutput>,
+        ) -> Self {
+            Self { overlay, usage }
+        }
+        #[doc = "Sets the value of `overlay`."]
+        pub fn set_overlay(&mut self, overlay: ::std::string::String) -> &mut Self {
+            self.overlay = overlay;
+            self
+        }
+        #[doc = "Sets the value of `overlay`."]
+        pub fn with_overlay(mut self, overlay: ::std::string::String) -> Self {
+            self.overlay = overlay;
+            self
+        }
+        #[doc = "Sets the value of `usage`."]
+        pub fn set_usage(
+            &mut self,
+            usage: indexmap::IndexMap<::std::string::String, UsageOutput>,
+        ) -> &mut Self {
+            self.usage = usage;
+            self
+        }
+        #[doc = "Sets the value of `usage`."]
+        pub fn with_usage(
+            mut self,
+            usage: indexmap::IndexMap<::std::string::String, UsageOutput>,
+        ) -> Self {
+            self.usage = usage;
+            self
+        }
     }
     #[automatically_derived]
-    impl __serde::Serialize for PersistConfig {
+    impl __serde::Serialize for StateStatusOutput {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let __serializer =
-                __sidex_serde::ser::VariantSerializer::new(__serializer, "PersistConfig");
-            match self {
-                Self::File(__value) => {
-                    __serializer.serialize_implicitly_tagged("File", 0u32, __value)
-                }
-                Self::Directory(__value) => {
-                    __serializer.serialize_implicitly_tagged("Directory", 1u32, __value)
-                }
-            }
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "StateStatusOutput",
+                2usize,
+            )?;
+            __record.serialize_field("overlay", &self.overlay)?;
+            __record.serialize_field("usage", &self.usage)?;
+            __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for PersistConfig {
+    impl<'de> __serde::Deserialize<'de> for StateStatusOutput {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
             #[doc(hidden)]
-            const __IDENTIFIERS: &'static [&'static str] = &["File", "Directory"];
-            #[doc(hidden)]
-            const __EXPECTING_IDENTIFIERS: &'static str =
-                "an identifier in [\"File\", \"Directory\"]";
-            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
-            #[doc(hidden)]
-            enum __Identifier {
-                __Identifier0,
-                __Identifier1,
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
-            #[doc(hidden)]
-            struct __IdentifierVisitor;
-            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
-                type Value = __Identifier;
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = StateStatusOutput;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
-                }
-                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        __variant => {
-                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
-                                __serde::de::Unexpected::Unsigned(__variant),
-                                &__EXPECTING_IDENTIFIERS,
-                            ))
-                        }
-                    }
-                }
-                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
-                where
-                    __E: __serde::de::Error,
-                {
-                    match __value {
-                        "File" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        "Directory" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        __variant => ::core::result::Result::Err(
-                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
-                        ),
-                    }
+                    ::core::fmt::Formatter::write_str(__formatter, "record StateStatusOutput")
                 }
-                fn visit_bytes<__E>(
+                #[inline]
+                fn visit_seq<__A>(
                     self,
-                    __value: &[u8],
-                ) -> ::core::result::Result<Self::Value, __E>
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __E: __serde::de::Error,
+                    __A: __serde::de::SeqAccess<'de>,
                 {
-                    match __value {
-                        b"File" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                        b"Directory" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                        __variant => {
-                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
-                                __serde::de::Unexpected::Bytes(__variant),
-                                &__EXPECTING_IDENTIFIERS,
-                            ))
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                            );
                         }
-                    }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        indexmap::IndexMap<::std::string::String, UsageOutput>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(StateStatusOutput {
+                        overlay: __field0,
+                        usage: __field1,
+                    })
                 }
-            }
-            impl<'de> __serde::Deserialize<'de> for __Identifier {
                 #[inline]
-                fn deserialize<__D>(__deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
                 where
-                    __D: __serde::Deserializer<'de>,
-                {
-                    __serde::Deserializer::deserialize_identifier(
-                        __deserializer,
-                        __IdentifierVisitor,
-                    )
-                }
-            }
-            #[doc(hidden)]
-            const __VARIANTS: &'static [&'static str] = &["File", "Directory"];
-            if __serde::Deserializer::is_human_readable(&__deserializer) {
-                let __content =
-                    __sidex_serde::de::content::deserialize_into_content(__deserializer)?;
-                match __sidex_serde::de::content::deserialize_content_ref::<
-                    PersistFileConfig,
-                    __D::Error,
-                >(&__content)
-                {
-                    Ok(__value) => return Ok(PersistConfig::File(__value)),
-                    Err(_) => {}
-                };
-                match __sidex_serde::de::content::deserialize_content_ref::<
-                    PersistDirectoryConfig,
-                    __D::Error,
-                >(&__content)
+                    __A: __serde::de::MapAccess<'de>,
                 {
-                    Ok(__value) => return Ok(PersistConfig::Directory(__value)),
-                    Err(_) => {}
-                };
-                Err(<__D::Error as __serde::de::Error>::custom(
-                    "no matching variant found",
-                ))
-            } else {
-                #[doc(hidden)]
-                struct __Visitor {
-                    __phantom_vars: ::core::marker::PhantomData<fn(&())>,
-                }
-                impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                    type Value = PersistConfig;
-                    fn expecting(
-                        &self,
-                        __formatter: &mut ::core::fmt::Formatter,
-                    ) -> ::core::fmt::Result {
-                        ::core::fmt::Formatter::write_str(__formatter, "enum PersistConfig")
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["overlay", "usage"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"overlay\", \"usage\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Unknown,
                     }
-                    #[inline]
-                    fn visit_str<__E>(
-                        self,
-                        __value: &str,
-                    ) -> ::core::result::Result<Self::Value, __E>
-                    where
-                        __E: __serde::de::Error,
-                    {
-                        let __identifier = __IdentifierVisitor.visit_str(__value)?;
-                        #[allow(unreachable_patterns)]
-                        match __identifier {
-                            _ => Err(__E::invalid_value(
-                                __serde::de::Unexpected::Str(__value),
-                                &self,
-                            )),
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
                         }
-                    }
-                    #[inline]
-                    fn visit_enum<__A>(
-                        self,
-                        __data: __A,
-                    ) -> ::core::result::Result<Self::Value, __A::Error>
-                    where
-                        __A: __serde::de::EnumAccess<'de>,
-                    {
-                        match __serde::de::EnumAccess::variant::<__Identifier>(__data)? {
-                            (__Identifier::__Identifier0, __variant) => {
-                                let __value = __serde::de::VariantAccess::newtype_variant::<
-                                    PersistFileConfig,
-                                >(__variant)?;
-                                ::core::result::Result::Ok(PersistConfig::File(__value))
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
-                            (__Identifier::__Identifier1, __variant) => {
-                                let __value = __serde::de::VariantAccess::newtype_variant::<
-                                    PersistDirectoryConfig,
-                                >(__variant)?;
-                                ::core::result::Result::Ok(PersistConfig::Directory(__value))
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "overlay" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "usage" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"overlay" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"usage" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
                     }
-                }
-                __serde::Deserializer::deserialize_enum(
-                    __deserializer,
-                    "PersistConfig",
-                    __VARIANTS,
-                    __Visitor {
-                        __phantom_vars: ::core::marker::PhantomData,
-                    },
-                )
-            }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<
+                        indexmap::IndexMap<::std::string::String, UsageOutput>,
+                    > = ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "overlay",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "usage",
+                                        ),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        indexmap::IndexMap<::std::string::String, UsageOutput>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("overlay"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("usage"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(StateStatusOutput {
+                        overlay: __field0,
+                        usage: __field1,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["overlay", "usage"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "StateStatusOutput",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = ""]
+    #[derive(Clone, Debug)]
+    pub struct UsageOutput {
+        #[doc = ""]
+        pub path: ::std::string::String,
+        #[doc = ""]
+        pub size: u64,
+        #[doc = ""]
+        pub used: u64,
+        #[doc = ""]
+        pub available: u64,
+        #[doc = ""]
+        pub percent: u32,
+        #[doc = ""]
+        pub level: ::std::string::String,
+    }
+    impl UsageOutput {
+        #[doc = "Creates a new [`UsageOutput`]."]
+        pub fn new(
+            path: ::std::string::String,
+            size: u64,
+            used: u64,
+            available: u64,
+            percent: u32,
+            level: ::std::string::String,
+        ) -> Self {
+            Self {
+                path,
+                size,
+                used,
+                available,
+                percent,
+                level,
+            }
+        }
+        #[doc = "Sets the value of `path`."]
+        pub fn set_path(&mut self, path: ::std::string::String) -> &mut Self {
+            self.path = path;
+            self
+        }
+        #[doc = "Sets the value of `path`."]
+        pub fn with_path(mut self, path: ::std::string::String) -> Self {
+            self.path = path;
+            self
+        }
+        #[doc = "Sets the value of `size`."]
+        pub fn set_size(&mut self, size: u64) -> &mut Self {
+            self.size = size;
+            self
+        }
+        #[doc = "Sets the value of `size`."]
+        pub fn with_size(mut self, size: u64) -> Self {
+            self.size = size;
+            self
+        }
+        #[doc = "Sets the value of `used`."]
+        pub fn set_used(&mut self, used: u64) -> &mut Self {
+            self.used = used;
+            self
+        }
+        #[doc = "Sets the value of `used`."]
+        pub fn with_used(mut self, used: u64) -> Self {
+            self.used = used;
+            self
+        }
+        #[doc = "Sets the value of `available`."]
+        pub fn set_available(&mut self, available: u64) -> &mut Self {
+            self.available = available;
+            self
+        }
+        #[doc = "Sets the value of `available`."]
+        pub fn with_available(mut self, available: u64) -> Self {
+            self.available = available;
+            self
+        }
+        #[doc = "Sets the value of `percent`."]
+        pub fn set_percent(&mut self, percent: u32) -> &mut Self {
+            self.percent = percent;
+            self
+        }
+        #[doc = "Sets the value of `percent`."]
+        pub fn with_percent(mut self, percent: u32) -> Self {
+            self.percent = percent;
+            self
+        }
+        #[doc = "Sets the value of `level`."]
+        pub fn set_level(&mut self, level: ::std::string::String) -> &mut Self {
+            self.level = level;
+            self
+        }
+        #[doc = "Sets the value of `level`."]
+        pub fn with_level(mut self, level: ::std::string::String) -> Self {
+            self.level = level;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for UsageOutput {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "UsageOutput", 6usize)?;
+            __record.serialize_field("path", &self.path)?;
+            __record.serialize_field("size", &self.size)?;
+            __record.serialize_field("used", &self.used)?;
+            __record.serialize_field("available", &self.available)?;
+            __record.serialize_field("percent", &self.percent)?;
+            __record.serialize_field("level", &self.level)?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for UsageOutput {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = UsageOutput;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record UsageOutput")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<u64>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<u64>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<u64>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<u32>(&mut __seq)? {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(5usize, &"record with 6 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(UsageOutput {
+                        path: __field0,
+                        size: __field1,
+                        used: __field2,
+                        available: __field3,
+                        percent: __field4,
+                        level: __field5,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] =
+                        &["path", "size", "used", "available", "percent", "level"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"path\", \"size\", \"used\", \"available\", \"percent\", \"level\"]" ;
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Identifier2,
+                        __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "path" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "size" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "used" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                "available" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
+                                "percent" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                "level" => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"path" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                b"size" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                b"used" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                b"available" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                }
+                                b"percent" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                b"level" => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<u64> = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<u64> = ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<u64> = ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<u32> = ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("path"),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("size"),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u64>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("used"),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u64>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "available",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u64>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "percent",
+                                        ),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<u32>(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "level",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("path"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("size"),
+                            );
+                        }
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("used"),
+                            );
+                        }
+                    };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("available"),
+                            );
+                        }
+                    };
+                    let __field4 = match __field4 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("percent"),
+                            );
+                        }
+                    };
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("level"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(UsageOutput {
+                        path: __field0,
+                        size: __field1,
+                        used: __field2,
+                        available: __field3,
+                        percent: __field4,
+                        level: __field5,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] =
+                &["path", "size", "used", "available", "percent", "level"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "UsageOutput",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+}
+pub mod state {
+    #![doc = "State management configuration.\n"]
+    #[allow(unused)]
+    use :: serde as __serde;
+    #[allow(unused)]
+    use :: sidex_serde as __sidex_serde;
+    #[doc = "State management configuration.\n"]
+    #[derive(Clone, Debug)]
+    pub struct StateConfig {
+        #[doc = "Configuration of the root overlay.\n"]
+        pub overlay: ::std::option::Option<OverlayConfig>,
+        #[doc = "Files and directories to persist.\n"]
+        pub persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+        #[doc = "Usage monitoring of the overlay and data partition.\n"]
+        pub usage: ::std::option::Option<UsageConfig>,
+    }
+    impl StateConfig {
+        #[doc = "Creates a new [`StateConfig`]."]
+        pub fn new() -> Self {
+            Self {
+                overlay: ::std::default::Default::default(),
+                persist: ::std::default::Default::default(),
+                usage: ::std::default::Default::default(),
+            }
+        }
+        #[doc = "Sets the value of `overlay`."]
+        pub fn set_overlay(&mut self, overlay: ::std::option::Option<OverlayConfig>) -> &mut Self {
+            self.overlay = overlay;
+            self
+        }
+        #[doc = "Sets the value of `overlay`."]
+        pub fn with_overlay(mut self, overlay: ::std::option::Option<OverlayConfig>) -> Self {
+            self.overlay = overlay;
+            self
+        }
+        #[doc = "Sets the value of `persist`."]
+        pub fn set_persist(
+            &mut self,
+            persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+        ) -> &mut Self {
+            self.persist = persist;
+            self
+        }
+        #[doc = "Sets the value of `persist`."]
+        pub fn with_persist(
+            mut self,
+            persist: ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+        ) -> Self {
+            self.persist = persist;
+            self
+        }
+        #[doc = "Sets the value of `usage`."]
+        pub fn set_usage(&mut self, usage: ::std::option::Option<UsageConfig>) -> &mut Self {
+            self.usage = usage;
+            self
+        }
+        #[doc = "Sets the value of `usage`."]
+        pub fn with_usage(mut self, usage: ::std::option::Option<UsageConfig>) -> Self {
+            self.usage = usage;
+            self
+        }
+    }
+    impl ::std::default::Default for StateConfig {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for StateConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "StateConfig", 3usize)?;
+            __record.serialize_optional_field(
+                "overlay",
+                ::core::option::Option::as_ref(&self.overlay),
+            )?;
+            __record.serialize_optional_field(
+                "persist",
+                ::core::option::Option::as_ref(&self.persist),
+            )?;
+            __record
+                .serialize_optional_field("usage", ::core::option::Option::as_ref(&self.usage))?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for StateConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = StateConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record StateConfig")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<OverlayConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<UsageConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(StateConfig {
+                        overlay: __field0,
+                        persist: __field1,
+                        usage: __field2,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["overlay", "persist", "usage"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"overlay\", \"persist\", \"usage\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Identifier2,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "overlay" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "persist" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                "usage" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"overlay" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"persist" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                b"usage" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::option::Option<OverlayConfig>> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<
+                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                    > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<::std::option::Option<UsageConfig>> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "overlay",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<OverlayConfig>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "persist",
+                                        ),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::vec::Vec<PersistConfig>>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "usage",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<UsageConfig>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(StateConfig {
+                        overlay: __field0,
+                        persist: __field1,
+                        usage: __field2,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["overlay", "persist", "usage"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "StateConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Configuration of the root overlay.\n"]
+    #[derive(Clone, Debug)]
+    pub enum OverlayConfig {
+        #[doc = "Put the overlay on the data partition and persist it across boots.\n"]
+        Persist,
+        #[doc = "Put the overlay on the data partition and discard it on each boot.\n"]
+        Discard,
+        #[doc = "Put the overlay in a temporary, in-memory filesystem.\n"]
+        InMemory,
+        #[doc = "Disable the overlay.\n"]
+        Disabled,
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for OverlayConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let __serializer =
+                __sidex_serde::ser::VariantSerializer::new(__serializer, "OverlayConfig");
+            match self {
+                Self::Persist => __serializer.serialize_tag("persist", 0u32),
+                Self::Discard => __serializer.serialize_tag("discard", 1u32),
+                Self::InMemory => __serializer.serialize_tag("in-memory", 2u32),
+                Self::Disabled => __serializer.serialize_tag("disabled", 3u32),
+            }
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for OverlayConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            const __IDENTIFIERS: &'static [&'static str] =
+                &["persist", "discard", "in-memory", "disabled"];
+            #[doc(hidden)]
+            const __EXPECTING_IDENTIFIERS: &'static str =
+                "an identifier in [\"persist\", \"discard\", \"in-memory\", \"disabled\"]";
+            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+            #[doc(hidden)]
+            enum __Identifier {
+                __Identifier0,
+                __Identifier1,
+                __Identifier2,
+                __Identifier3,
+            }
+            #[doc(hidden)]
+            struct __IdentifierVisitor;
+            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                type Value = __Identifier;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                }
+                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                        3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                        __variant => {
+                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
+                                __serde::de::Unexpected::Unsigned(__variant),
+                                &__EXPECTING_IDENTIFIERS,
+                            ))
+                        }
+                    }
+                }
+                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        "persist" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        "discard" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        "in-memory" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                        "disabled" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                        __variant => ::core::result::Result::Err(
+                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
+                        ),
+                    }
+                }
+                fn visit_bytes<__E>(
+                    self,
+                    __value: &[u8],
+                ) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        b"persist" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        b"discard" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        b"in-memory" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                        b"disabled" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                        __variant => {
+                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
+                                __serde::de::Unexpected::Bytes(__variant),
+                                &__EXPECTING_IDENTIFIERS,
+                            ))
+                        }
+                    }
+                }
+            }
+            impl<'de> __serde::Deserialize<'de> for __Identifier {
+                #[inline]
+                fn deserialize<__D>(__deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: __serde::Deserializer<'de>,
+                {
+                    __serde::Deserializer::deserialize_identifier(
+                        __deserializer,
+                        __IdentifierVisitor,
+                    )
+                }
+            }
+            #[doc(hidden)]
+            const __VARIANTS: &'static [&'static str] =
+                &["persist", "discard", "in-memory", "disabled"];
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = OverlayConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "enum OverlayConfig")
+                }
+                #[inline]
+                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    let __identifier = __IdentifierVisitor.visit_str(__value)?;
+                    #[allow(unreachable_patterns)]
+                    match __identifier {
+                        __Identifier::__Identifier0 => {
+                            ::core::result::Result::Ok(OverlayConfig::Persist)
+                        }
+                        __Identifier::__Identifier1 => {
+                            ::core::result::Result::Ok(OverlayConfig::Discard)
+                        }
+                        __Identifier::__Identifier2 => {
+                            ::core::result::Result::Ok(OverlayConfig::InMemory)
+                        }
+                        __Identifier::__Identifier3 => {
+                            ::core::result::Result::Ok(OverlayConfig::Disabled)
+                        }
+                        _ => Err(__E::invalid_value(
+                            __serde::de::Unexpected::Str(__value),
+                            &self,
+                        )),
+                    }
+                }
+                #[inline]
+                fn visit_enum<__A>(
+                    self,
+                    __data: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::EnumAccess<'de>,
+                {
+                    match __serde::de::EnumAccess::variant::<__Identifier>(__data)? {
+                        (__Identifier::__Identifier0, __variant) => {
+                            __serde::de::VariantAccess::unit_variant(__variant)?;
+                            ::core::result::Result::Ok(OverlayConfig::Persist)
+                        }
+                        (__Identifier::__Identifier1, __variant) => {
+                            __serde::de::VariantAccess::unit_variant(__variant)?;
+                            ::core::result::Result::Ok(OverlayConfig::Discard)
+                        }
+                        (__Identifier::__Identifier2, __variant) => {
+                            __serde::de::VariantAccess::unit_variant(__variant)?;
+                            ::core::result::Result::Ok(OverlayConfig::InMemory)
+                        }
+                        (__Identifier::__Identifier3, __variant) => {
+                            __serde::de::VariantAccess::unit_variant(__variant)?;
+                            ::core::result::Result::Ok(OverlayConfig::Disabled)
+                        }
+                    }
+                }
+            }
+            __serde::Deserializer::deserialize_enum(
+                __deserializer,
+                "OverlayConfig",
+                __VARIANTS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Usage monitoring configuration.\n"]
+    #[derive(Clone, Debug)]
+    pub struct UsageConfig {
+        #[doc = "Usage in percent above which a warning is emitted.\n\nDefaults to 80.\n"]
+        pub warning_threshold: ::std::option::Option<u32>,
+        #[doc = "Usage in percent above which an error is emitted.\n\nDefaults to 95.\n"]
+        pub critical_threshold: ::std::option::Option<u32>,
+        #[doc = "Interval in seconds between the checks of the monitor.\n\nDefaults to 60.\n"]
+        pub interval: ::std::option::Option<u32>,
+    }
+    impl UsageConfig {
+        #[doc = "Creates a new [`UsageConfig`]."]
+        pub fn new() -> Self {
+            Self {
+                warning_threshold: ::std::default::Default::default(),
+                critical_threshold: ::std::default::Default::default(),
+                interval: ::std::default::Default::default(),
+            }
+        }
+        #[doc = "Sets the value of `warning_threshold`."]
+        pub fn set_warning_threshold(
+            &mut self,
+            warning_threshold: ::std::option::Option<u32>,
+        ) -> &mut Self {
+            self.warning_threshold = warning_threshold;
+            self
+        }
+        #[doc = "Sets the value of `warning_threshold`."]
+        pub fn with_warning_threshold(
+            mut self,
+            warning_threshold: ::std::option::Option<u32>,
+        ) -> Self {
+            self.warning_threshold = warning_threshold;
+            self
+        }
+        #[doc = "Sets the value of `critical_threshold`."]
+        pub fn set_critical_threshold(
+            &mut self,
+            critical_threshold: ::std::option::Option<u32>,
+        ) -> &mut Self {
+            self.critical_threshold = critical_threshold;
+            self
+        }
+        #[doc = "Sets the value of `critical_threshold`."]
+        pub fn with_critical_threshold(
+            mut self,
+            critical_threshold: ::std::option::Option<u32>,
+        ) -> Self {
+            self.critical_threshold = critical_threshold;
+            self
+        }
+        #[doc = "Sets the value of `interval`."]
+        pub fn set_interval(&mut self, interval: ::std::option::Option<u32>) -> &mut Self {
+            self.interval = interval;
+            self
+        }
+        #[doc = "Sets the value of `interval`."]
+        pub fn with_interval(mut self, interval: ::std::option::Option<u32>) -> Self {
+            self.interval = interval;
+            self
+        }
+    }
+    impl ::std::default::Default for UsageConfig {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for UsageConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "UsageConfig", 3usize)?;
+            __record.serialize_optional_field(
+                "warning-threshold",
+                ::core::option::Option::as_ref(&self.warning_threshold),
+            )?;
+            __record.serialize_optional_field(
+                "critical-threshold",
+                ::core::option::Option::as_ref(&self.critical_threshold),
+            )?;
+            __record.serialize_optional_field(
+                "interval",
+                ::core::option::Option::as_ref(&self.interval),
+            )?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for UsageConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = UsageConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record UsageConfig")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<u32>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<u32>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<u32>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 3 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(UsageConfig {
+                        warning_threshold: __field0,
+                        critical_threshold: __field1,
+                        interval: __field2,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] =
+                        &["warning-threshold", "critical-threshold", "interval"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"warning-threshold\", \"critical-threshold\", \"interval\"]" ;
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Identifier2,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "warning-threshold" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "critical-threshold" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                "interval" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"warning-threshold" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"critical-threshold" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                b"interval" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::option::Option<u32>> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<::std::option::Option<u32>> =
+                        ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<::std::option::Option<u32>> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "warning-threshold",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::option::Option<u32>>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "critical-threshold",
+                                        ),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::option::Option<u32>>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "interval",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::option::Option<u32>>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(UsageConfig {
+                        warning_threshold: __field0,
+                        critical_threshold: __field1,
+                        interval: __field2,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] =
+                &["warning-threshold", "critical-threshold", "interval"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "UsageConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Configuration to persist a file or directory.\n"]
+    #[derive(Clone, Debug)]
+    pub enum PersistConfig {
+        #[doc = "Configuration to persist a file.\n"]
+        File(PersistFileConfig),
+        #[doc = "Configuration to persist a directory.\n"]
+        Directory(PersistDirectoryConfig),
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for PersistConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let __serializer =
+                __sidex_serde::ser::VariantSerializer::new(__serializer, "PersistConfig");
+            match self {
+                Self::File(__value) => {
+                    __serializer.serialize_implicitly_tagged("File", 0u32, __value)
+                }
+                Self::Directory(__value) => {
+                    __serializer.serialize_implicitly_tagged("Directory", 1u32, __value)
+                }
+            }
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for PersistConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            const __IDENTIFIERS: &'static [&'static str] = &["File", "Directory"];
+            #[doc(hidden)]
+            const __EXPECTING_IDENTIFIERS: &'static str =
+                "an identifier in [\"File\", \"Directory\"]";
+            #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+            #[doc(hidden)]
+            enum __Identifier {
+                __Identifier0,
+                __Identifier1,
+            }
+            #[doc(hidden)]
+            struct __IdentifierVisitor;
+            impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                type Value = __Identifier;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                }
+                fn visit_u64<__E>(self, __value: u64) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        __variant => {
+                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
+                                __serde::de::Unexpected::Unsigned(__variant),
+                                &__EXPECTING_IDENTIFIERS,
+                            ))
+                        }
+                    }
+                }
+                fn visit_str<__E>(self, __value: &str) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        "File" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        "Directory" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        __variant => ::core::result::Result::Err(
+                            __serde::de::Error::unknown_variant(__variant, __IDENTIFIERS),
+                        ),
+                    }
+                }
+                fn visit_bytes<__E>(
+                    self,
+                    __value: &[u8],
+                ) -> ::core::result::Result<Self::Value, __E>
+                where
+                    __E: __serde::de::Error,
+                {
+                    match __value {
+                        b"File" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                        b"Directory" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                        __variant => {
+                            ::core::result::Result::Err(__serde::de::Error::invalid_value(
+                                __serde::de::Unexpected::Bytes(__variant),
+                                &__EXPECTING_IDENTIFIERS,
+                            ))
+                        }
+                    }
+                }
+            }
+            impl<'de> __serde::Deserialize<'de> for __Identifier {
+                #[inline]
+                fn deserialize<__D>(__deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                where
+                    __D: __serde::Deserializer<'de>,
+                {
+                    __serde::Deserializer::deserialize_identifier(
+                        __deserializer,
+                        __IdentifierVisitor,
+                    )
+                }
+            }
+            #[doc(hidden)]
+            const __VARIANTS: &'static [&'static str] = &["File", "Directory"];
+            if __serde::Deserializer::is_human_readable(&__deserializer) {
+                let __content =
+                    __sidex_serde::de::content::deserialize_into_content(__deserializer)?;
+                match __sidex_serde::de::content::deserialize_content_ref::<
+                    PersistFileConfig,
+                    __D::Error,
+                >(&__content)
+                {
+                    Ok(__value) => return Ok(PersistConfig::File(__value)),
+                    Err(_) => {}
+                };
+                match __sidex_serde::de::content::deserialize_content_ref::<
+                    PersistDirectoryConfig,
+                    __D::Error,
+                >(&__content)
+                {
+                    Ok(__value) => return Ok(PersistConfig::Directory(__value)),
+                    Err(_) => {}
+                };
+                Err(<__D::Error as __serde::de::Error>::custom(
+                    "no matching variant found",
+                ))
+            } else {
+                #[doc(hidden)]
+                struct __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+                }
+                impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                    type Value = PersistConfig;
+                    fn expecting(
+                        &self,
+                        __formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        ::core::fmt::Formatter::write_str(__formatter, "enum PersistConfig")
+                    }
+                    #[inline]
+                    fn visit_str<__E>(
+                        self,
+                        __value: &str,
+                    ) -> ::core::result::Result<Self::Value, __E>
+                    where
+                        __E: __serde::de::Error,
+                    {
+                        let __identifier = __IdentifierVisitor.visit_str(__value)?;
+                        #[allow(unreachable_patterns)]
+                        match __identifier {
+                            _ => Err(__E::invalid_value(
+                                __serde::de::Unexpected::Str(__value),
+                                &self,
+                            )),
+                        }
+                    }
+                    #[inline]
+                    fn visit_enum<__A>(
+                        self,
+                        __data: __A,
+                    ) -> ::core::result::Result<Self::Value, __A::Error>
+                    where
+                        __A: __serde::de::EnumAccess<'de>,
+                    {
+                        match __serde::de::EnumAccess::variant::<__Identifier>(__data)? {
+                            (__Identifier::__Identifier0, __variant) => {
+                                let __value = __serde::de::VariantAccess::newtype_variant::<
+                                    PersistFileConfig,
+                                >(__variant)?;
+                                ::core::result::Result::Ok(PersistConfig::File(__value))
+                            }
+                            (__Identifier::__Identifier1, __variant) => {
+                                let __value = __serde::de::VariantAccess::newtype_variant::<
+                                    PersistDirectoryConfig,
+                                >(__variant)?;
+                                ::core::result::Result::Ok(PersistConfig::Directory(__value))
+                            }
+                        }
+                    }
+                }
+                __serde::Deserializer::deserialize_enum(
+                    __deserializer,
+                    "PersistConfig",
+                    __VARIANTS,
+                    __Visitor {
+                        __phantom_vars: ::core::marker::PhantomData,
+                    },
+                )
+            }
+        }
+    }
+    #[doc = "Configuration to persist a file.\n"]
+    #[derive(Clone, Debug)]
+    pub struct PersistFileConfig {
+        #[doc = "Path of the file to persist.\n"]
+        pub file: ::std::string::String,
+        #[doc = "Default contents to initialize the file with if it does not exist.\n"]
+        pub default: ::std::option::Option<::std::string::String>,
+    }
+    impl PersistFileConfig {
+        #[doc = "Creates a new [`PersistFileConfig`]."]
+        pub fn new(file: ::std::string::String) -> Self {
+            Self {
+                file,
+                default: ::std::default::Default::default(),
+            }
+        }
+        #[doc = "Sets the value of `file`."]
+        pub fn set_file(&mut self, file: ::std::string::String) -> &mut Self {
+            self.file = file;
+            self
+        }
+        #[doc = "Sets the value of `file`."]
+        pub fn with_file(mut self, file: ::std::string::String) -> Self {
+            self.file = file;
+            self
+        }
+        #[doc = "Sets the value of `default`."]
+        pub fn set_default(
+            &mut self,
+            default: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.default = default;
+            self
+        }
+        #[doc = "Sets the value of `default`."]
+        pub fn with_default(
+            mut self,
+            default: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.default = default;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for PersistFileConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "PersistFileConfig",
+                2usize,
+            )?;
+            __record.serialize_field("file", &self.file)?;
+            __record.serialize_optional_field(
+                "default",
+                ::core::option::Option::as_ref(&self.default),
+            )?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for PersistFileConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = PersistFileConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record PersistFileConfig")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                            );
+                        }
+                    };
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PersistFileConfig {
+                        file: __field0,
+                        default: __field1,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["file", "default"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"file\", \"default\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Identifier1,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "file" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "default" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"file" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                b"default" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    let mut __field1: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("file"),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            __Identifier::__Identifier1 => {
+                                if ::core::option::Option::is_some(&__field1) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "default",
+                                        ),
+                                    );
+                                }
+                                __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("file"),
+                            );
+                        }
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(PersistFileConfig {
+                        file: __field0,
+                        default: __field1,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["file", "default"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "PersistFileConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Configuration to persist a directory.\n"]
+    #[derive(Clone, Debug)]
+    pub struct PersistDirectoryConfig {
+        #[doc = "Path of the directory to persist.\n"]
+        pub directory: ::std::string::String,
+    }
+    impl PersistDirectoryConfig {
+        #[doc = "Creates a new [`PersistDirectoryConfig`]."]
+        pub fn new(directory: ::std::string::String) -> Self {
+            Self { directory }
+        }
+        #[doc = "Sets the value of `directory`."]
+        pub fn set_directory(&mut self, directory: ::std::string::String) -> &mut Self {
+            self.directory = directory;
+            self
+        }
+        #[doc = "Sets the value of `directory`."]
+        pub fn with_directory(mut self, directory: ::std::string::String) -> Self {
+            self.directory = directory;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for PersistDirectoryConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "PersistDirectoryConfig",
+                1usize,
+            )?;
+            __record.serialize_field("directory", &self.directory)?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for PersistDirectoryConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = PersistDirectoryConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(__formatter, "record PersistDirectoryConfig")
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 1 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PersistDirectoryConfig {
+                        directory: __field0,
+                    })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["directory"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"directory\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "directory" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"directory" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "directory",
+                                        ),
+                                    );
+                                }
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
+                                );
+                            }
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("directory"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PersistDirectoryConfig {
+                        directory: __field0,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["directory"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "PersistDirectoryConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+}
+pub mod system {
+    #![doc = ""]
+    #[allow(unused)]
+    use :: serde as __serde;
+    #[allow(unused)]
+    use :: sidex_serde as __sidex_serde;
+    #[doc = "System configuration.\n"]
+    #[derive(Clone, Debug)]
+    pub struct SystemConfig {
+        #[doc = "Config partition configuration.\n"]
+        pub config_partition: ::std::option::Option<PartitionConfig>,
+        #[doc = "Data partition configuration.\n"]
+        pub data_partition: ::std::option::Option<PartitionConfig>,
+        #[doc = "Additional disks of the system.\n"]
+        pub disks: ::std::option::Option<indexmap::IndexMap<::std::string::String, DiskConfig>>,
+        #[doc = "System slots.\n"]
+        pub slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
+        #[doc = "System boot groups.\n"]
+        pub boot_groups:
+            ::std::option::Option<indexmap::IndexMap<::std::string::String, BootGroupConfig>>,
+        #[doc = "Boot flow configuration.\n"]
+        pub boot_flow: ::std::option::Option<BootFlowConfig>,
+        #[doc = "Variant of the system used to select the payloads of update bundles.\n\nDefaults to the architecture of the system, if it can be determined.\n"]
+        pub variant: ::std::option::Option<::std::string::String>,
+        #[doc = "SWUpdate-compatible progress interface for update installations.\n"]
+        pub swupdate_progress: ::std::option::Option<SwupdateProgressConfig>,
+    }
+    impl SystemConfig {
+        #[doc = "Creates a new [`SystemConfig`]."]
+        pub fn new() -> Self {
+            Self {
+                config_partition: ::std::default::Default::default(),
+                data_partition: ::std::default::Default::default(),
+                disks: ::std::default::Default::default(),
+                slots: ::std::default::Default::default(),
+                boot_groups: ::std::default::Default::default(),
+                boot_flow: ::std::default::Default::default(),
+                variant: ::std::default::Default::default(),
+                swupdate_progress: ::std::default::Default::default(),
+            }
+        }
+        #[doc = "Sets the value of `config_partition`."]
+        pub fn set_config_partition(
+            &mut self,
+            config_partition: ::std::option::Option<PartitionConfig>,
+        ) -> &mut Self {
+            self.config_partition = config_partition;
+            self
+        }
+        #[doc = "Sets the value of `config_partition`."]
+        pub fn with_config_partition(
+            mut self,
+            config_partition: ::std::option::Option<PartitionConfig>,
+        ) -> Self {
+            self.config_partition = config_partition;
+            self
+        }
+        #[doc = "Sets the value of `data_partition`."]
+        pub fn set_data_partition(
+            &mut self,
+            data_partition: ::std::option::Option<PartitionConfig>,
+        ) -> &mut Self {
+            self.data_partition = data_partition;
+            self
+        }
+        #[doc = "Sets the value of `data_partition`."]
+        pub fn with_data_partition(
+            mut self,
+            data_partition: ::std::option::Option<PartitionConfig>,
+        ) -> Self {
+            self.data_partition = data_partition;
+            self
         }
-    }
-    #[doc = "Configuration to persist a file.\n"]
-    #[derive(Clone, Debug)]
-    pub struct PersistFileConfig {
-        #[doc = "Path of the file to persist.\n"]
-        pub file: ::std::string::String,
-        #[doc = "Default contents to initialize the file with if it does not exist.\n"]
-        pub default: ::std::option::Option<::std::string::String>,
-    }
-    impl PersistFileConfig {
-        #[doc = "Creates a new [`PersistFileConfig`]."]
-        pub fn new(file: ::std::string::String) -> Self {
-            Self {
-                file,
-                default: ::std::default::Default::default(),
-            }
+        #[doc = "Sets the value of `disks`."]
+        pub fn set_disks(
+            &mut self,
+            disks: ::std::option::Option<indexmap::IndexMap<::std::string::String, DiskConfig>>,
+        ) -> &mut Self {
+            self.disks = disks;
+            self
         }
-        #[doc = "Sets the value of `file`."]
-        pub fn set_file(&mut self, file: ::std::string::String) -> &mut Self {
-            self.file = file;
+        #[doc = "Sets the value of `disks`."]
+        pub fn with_disks(
+            mut self,
+            disks: ::std::option::Option<indexmap::IndexMap<::std::string::String, DiskConfig>>,
+        ) -> Self {
+            self.disks = disks;
             self
         }
-        #[doc = "Sets the value of `file`."]
-        pub fn with_file(mut self, file: ::std::string::String) -> Self {
-            self.file = file;
+        #[doc = "Sets the value of `slots`."]
+        pub fn set_slots(
+            &mut self,
+            slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
+        ) -> &mut Self {
+            self.slots = slots;
             self
         }
-        #[doc = "Sets the value of `default`."]
-        pub fn set_default(
+        #[doc = "Sets the value of `slots`."]
+        pub fn with_slots(
+            mut self,
+            slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
+        ) -> Self {
+            self.slots = slots;
+            self
+        }
+        #[doc = "Sets the value of `boot_groups`."]
+        pub fn set_boot_groups(
             &mut self,
-            default: ::std::option::Option<::std::string::String>,
+            boot_groups: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, BootGroupConfig>,
+            >,
         ) -> &mut Self {
-            self.default = default;
+            self.boot_groups = boot_groups;
             self
         }
-        #[doc = "Sets the value of `default`."]
-        pub fn with_default(
+        #[doc = "Sets the value of `boot_groups`."]
+        pub fn with_boot_groups(
             mut self,
-            default: ::std::option::Option<::std::string::String>,
+            boot_groups: ::std::option::Option<
+                indexmap::IndexMap<::std::string::String, BootGroupConfig>,
+            >,
         ) -> Self {
-            self.default = default;
+            self.boot_groups = boot_groups;
+            self
+        }
+        #[doc = "Sets the value of `boot_flow`."]
+        pub fn set_boot_flow(
+            &mut self,
+            boot_flow: ::std::option::Option<BootFlowConfig>,
+        ) -> &mut Self {
+            self.boot_flow = boot_flow;
+            self
+        }
+        #[doc = "Sets the value of `boot_flow`."]
+        pub fn with_boot_flow(mut self, boot_flow: ::std::option::Option<BootFlowConfig>) -> Self {
+            self.boot_flow = boot_flow;
+            self
+        }
+        #[doc = "Sets the value of `variant`."]
+        pub fn set_variant(
+            &mut self,
+            variant: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.variant = variant;
+            self
+        }
+        #[doc = "Sets the value of `variant`."]
+        pub fn with_variant(
+            mut self,
+            variant: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.variant = variant;
+            self
+        }
+        #[doc = "Sets the value of `swupdate_progress`."]
+        pub fn set_swupdate_progress(
+            &mut self,
+            swupdate_progress: ::std::option::Option<SwupdateProgressConfig>,
+        ) -> &mut Self {
+            self.swupdate_progress = swupdate_progress;
+            self
+        }
+        #[doc = "Sets the value of `swupdate_progress`."]
+        pub fn with_swupdate_progress(
+            mut self,
+            swupdate_progress: ::std::option::Option<SwupdateProgressConfig>,
+        ) -> Self {
+            self.swupdate_progress = swupdate_progress;
             self
         }
     }
+    impl ::std::default::Default for SystemConfig {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
     #[automatically_derived]
-    impl __serde::Serialize for PersistFileConfig {
+    impl __serde::Serialize for SystemConfig {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record = __sidex_serde::ser::RecordSerializer::new(
-                __serializer,
-                "PersistFileConfig",
-                2usize,
+            let mut __record =
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "SystemConfig", 8usize)?;
+            __record.serialize_optional_field(
+                "config-partition",
+                ::core::option::Option::as_ref(&self.config_partition),
             )?;
-            __record.serialize_field("file", &self.file)?;
             __record.serialize_optional_field(
-                "default",
-                ::core::option::Option::as_ref(&self.default),
+                "data-partition",
+                ::core::option::Option::as_ref(&self.data_partition),
+            )?;
+            __record
+                .serialize_optional_field("disks", ::core::option::Option::as_ref(&self.disks))?;
+            __record
+                .serialize_optional_field("slots", ::core::option::Option::as_ref(&self.slots))?;
+            __record.serialize_optional_field(
+                "boot-groups",
+                ::core::option::Option::as_ref(&self.boot_groups),
+            )?;
+            __record.serialize_optional_field(
+                "boot-flow",
+                ::core::option::Option::as_ref(&self.boot_flow),
+            )?;
+            __record.serialize_optional_field(
+                "variant",
+                ::core::option::Option::as_ref(&self.variant),
+            )?;
+            __record.serialize_optional_field(
+                "swupdate-progress",
+                ::core::option::Option::as_ref(&self.swupdate_progress),
             )?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for PersistFileConfig {
+    impl<'de> __serde::Deserialize<'de> for SystemConfig {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -3721,12 +6613,12 @@ pub mod state {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = PersistFileConfig;
+                type Value = SystemConfig;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record PersistFileConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record SystemConfig")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -3737,30 +6629,108 @@ pub mod state {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::string::String,
+                        ::std::option::Option<PartitionConfig>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 8 fields"),
                             );
                         }
                     };
                     let __field1 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<PartitionConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(1usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, DiskConfig>,
+                        >,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, SlotConfig>,
+                        >,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, BootGroupConfig>,
+                        >,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<BootFlowConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(5usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field6 = match __serde::de::SeqAccess::next_element::<
                         ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
+                                __serde::de::Error::invalid_length(6usize, &"record with 8 fields"),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(PersistFileConfig {
-                        file: __field0,
-                        default: __field1,
+                    let __field7 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<SwupdateProgressConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(7usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(SystemConfig {
+                        config_partition: __field0,
+                        data_partition: __field1,
+                        disks: __field2,
+                        slots: __field3,
+                        boot_groups: __field4,
+                        boot_flow: __field5,
+                        variant: __field6,
+                        swupdate_progress: __field7,
                     })
                 }
                 #[inline]
@@ -3772,15 +6742,29 @@ pub mod state {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["file", "default"];
+                    const __IDENTIFIERS: &'static [&'static str] = &[
+                        "config-partition",
+                        "data-partition",
+                        "disks",
+                        "slots",
+                        "boot-groups",
+                        "boot-flow",
+                        "variant",
+                        "swupdate-progress",
+                    ];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"file\", \"default\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"config-partition\", \"data-partition\", \"disks\", \"slots\", \"boot-groups\", \"boot-flow\", \"variant\", \"swupdate-progress\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
+                        __Identifier2,
+                        __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
+                        __Identifier6,
+                        __Identifier7,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -3803,6 +6787,12 @@ pub mod state {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                6u64 => ::core::result::Result::Ok(__Identifier::__Identifier6),
+                                7u64 => ::core::result::Result::Ok(__Identifier::__Identifier7),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3814,10 +6804,26 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "file" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                "default" => {
+                                "config-partition" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                "data-partition" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                "disks" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                "slots" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                "boot-groups" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                "boot-flow" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                "variant" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
+                                "swupdate-progress" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3829,10 +6835,26 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"file" => ::core::result::Result::Ok(__Identifier::__Identifier0),
-                                b"default" => {
+                                b"config-partition" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
+                                }
+                                b"data-partition" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                b"disks" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                b"slots" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                b"boot-groups" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                }
+                                b"boot-flow" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                b"variant" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
+                                b"swupdate-progress" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
+                                }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -3851,11 +6873,36 @@ pub mod state {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::string::String> =
-                        ::core::option::Option::None;
+                    let mut __field0: ::core::option::Option<
+                        ::std::option::Option<PartitionConfig>,
+                    > = ::core::option::Option::None;
                     let mut __field1: ::core::option::Option<
+                        ::std::option::Option<PartitionConfig>,
+                    > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, DiskConfig>,
+                        >,
+                    > = ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, SlotConfig>,
+                        >,
+                    > = ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<
+                        ::std::option::Option<
+                            indexmap::IndexMap<::std::string::String, BootGroupConfig>,
+                        >,
+                    > = ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<
+                        ::std::option::Option<BootFlowConfig>,
+                    > = ::core::option::Option::None;
+                    let mut __field6: ::core::option::Option<
                         ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
+                    let mut __field7: ::core::option::Option<
+                        ::std::option::Option<SwupdateProgressConfig>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -3863,29 +6910,124 @@ pub mod state {
                             __Identifier::__Identifier0 => {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field("file"),
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "config-partition",
+                                        ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<::std::string::String>(
-                                        &mut __map,
-                                    )?,
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<PartitionConfig>,
+                                    >(&mut __map)?,
                                 );
                             }
                             __Identifier::__Identifier1 => {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "default",
+                                            "data-partition",
                                         ),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<PartitionConfig>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier2 => {
+                                if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "disks",
+                                        ),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<
+                                            indexmap::IndexMap<::std::string::String, DiskConfig>,
+                                        >,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "slots",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<
+                                            indexmap::IndexMap<::std::string::String, SlotConfig>,
+                                        >,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "boot-groups",
+                                        ),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<
+                                            indexmap::IndexMap<
+                                                ::std::string::String,
+                                                BootGroupConfig,
+                                            >,
+                                        >,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "boot-flow",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<BootFlowConfig>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier6 => {
+                                if ::core::option::Option::is_some(&__field6) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "variant",
+                                        ),
+                                    );
+                                }
+                                __field6 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
                                         ::std::option::Option<::std::string::String>,
                                     >(&mut __map)?,
                                 );
                             }
+                            __Identifier::__Identifier7 => {
+                                if ::core::option::Option::is_some(&__field7) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "swupdate-progress",
+                                        ),
+                                    );
+                                }
+                                __field7 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<SwupdateProgressConfig>,
+                                    >(&mut __map)?,
+                                );
+                            }
                             _ => {
                                 __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
                                     &mut __map,
@@ -3895,27 +7037,62 @@ pub mod state {
                     }
                     let __field0 = match __field0 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("file"),
-                            );
-                        }
+                        ::core::option::Option::None => ::core::option::Option::None,
                     };
                     let __field1 = match __field1 {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(PersistFileConfig {
-                        file: __field0,
-                        default: __field1,
+                    let __field2 = match __field2 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field4 = match __field4 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field6 = match __field6 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field7 = match __field7 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(SystemConfig {
+                        config_partition: __field0,
+                        data_partition: __field1,
+                        disks: __field2,
+                        slots: __field3,
+                        boot_groups: __field4,
+                        boot_flow: __field5,
+                        variant: __field6,
+                        swupdate_progress: __field7,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["file", "default"];
+            const __FIELDS: &'static [&'static str] = &[
+                "config-partition",
+                "data-partition",
+                "disks",
+                "slots",
+                "boot-groups",
+                "boot-flow",
+                "variant",
+                "swupdate-progress",
+            ];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "PersistFileConfig",
+                "SystemConfig",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -3923,45 +7100,56 @@ pub mod state {
             )
         }
     }
-    #[doc = "Configuration to persist a directory.\n"]
+    #[doc = "Configuration of the SWUpdate-compatible progress interface.\n"]
     #[derive(Clone, Debug)]
-    pub struct PersistDirectoryConfig {
-        #[doc = "Path of the directory to persist.\n"]
-        pub directory: ::std::string::String,
+    pub struct SwupdateProgressConfig {
+        #[doc = "Path of the progress socket.\n\nDefaults to `/tmp/swupdateprog`.\n"]
+        pub socket: ::std::option::Option<::std::string::String>,
     }
-    impl PersistDirectoryConfig {
-        #[doc = "Creates a new [`PersistDirectoryConfig`]."]
-        pub fn new(directory: ::std::string::String) -> Self {
-            Self { directory }
+    impl SwupdateProgressConfig {
+        #[doc = "Creates a new [`SwupdateProgressConfig`]."]
+        pub fn new() -> Self {
+            Self {
+                socket: ::std::default::Default::default(),
+            }
         }
-        #[doc = "Sets the value of `directory`."]
-        pub fn set_directory(&mut self, directory: ::std::string::String) -> &mut Self {
-            self.directory = directory;
+        #[doc = "Sets the value of `socket`."]
+        pub fn set_socket(
+            &mut self,
+            socket: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.socket = socket;
             self
         }
-        #[doc = "Sets the value of `directory`."]
-        pub fn with_directory(mut self, directory: ::std::string::String) -> Self {
-            self.directory = directory;
+        #[doc = "Sets the value of `socket`."]
+        pub fn with_socket(mut self, socket: ::std::option::Option<::std::string::String>) -> Self {
+            self.socket = socket;
             self
         }
     }
+    impl ::std::default::Default for SwupdateProgressConfig {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
     #[automatically_derived]
-    impl __serde::Serialize for PersistDirectoryConfig {
+    impl __serde::Serialize for SwupdateProgressConfig {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record = __sidex_serde::ser::RecordSerializer::new(
                 __serializer,
-                "PersistDirectoryConfig",
+                "SwupdateProgressConfig",
                 1usize,
             )?;
-            __record.serialize_field("directory", &self.directory)?;
+            __record
+                .serialize_optional_field("socket", ::core::option::Option::as_ref(&self.socket))?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for PersistDirectoryConfig {
+    impl<'de> __serde::Deserialize<'de> for SwupdateProgressConfig {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -3970,12 +7158,12 @@ pub mod state {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = PersistDirectoryConfig;
+                type Value = SwupdateProgressConfig;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record PersistDirectoryConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record SwupdateProgressConfig")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -3986,7 +7174,7 @@ pub mod state {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::string::String,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
@@ -3996,9 +7184,7 @@ pub mod state {
                             );
                         }
                     };
-                    ::core::result::Result::Ok(PersistDirectoryConfig {
-                        directory: __field0,
-                    })
+                    ::core::result::Result::Ok(SwupdateProgressConfig { socket: __field0 })
                 }
                 #[inline]
                 fn visit_map<__A>(
@@ -4009,10 +7195,9 @@ pub mod state {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] = &["directory"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["socket"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"directory\"]";
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"socket\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -4049,9 +7234,7 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "directory" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
+                                "socket" => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -4063,7 +7246,7 @@ pub mod state {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"directory" => {
+                                b"socket" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
@@ -4084,8 +7267,9 @@ pub mod state {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::string::String> =
-                        ::core::option::Option::None;
+                    let mut __field0: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -4094,14 +7278,14 @@ pub mod state {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "directory",
+                                            "socket",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<::std::string::String>(
-                                        &mut __map,
-                                    )?,
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
                                 );
                             }
                             _ => {
@@ -4113,22 +7297,16 @@ pub mod state {
                     }
                     let __field0 = match __field0 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                <__A::Error as __serde::de::Error>::missing_field("directory"),
-                            );
-                        }
+                        ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(PersistDirectoryConfig {
-                        directory: __field0,
-                    })
+                    ::core::result::Result::Ok(SwupdateProgressConfig { socket: __field0 })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["directory"];
+            const __FIELDS: &'static [&'static str] = &["socket"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "PersistDirectoryConfig",
+                "SwupdateProgressConfig",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -4136,157 +7314,248 @@ pub mod state {
             )
         }
     }
-}
-pub mod system {
-    #![doc = ""]
-    #[allow(unused)]
-    use :: serde as __serde;
-    #[allow(unused)]
-    use :: sidex_serde as __sidex_serde;
-    #[doc = "System configuration.\n"]
+    #[doc = "Partition configuration.\n"]
     #[derive(Clone, Debug)]
-    pub struct SystemConfig {
-        #[doc = "Config partition configuration.\n"]
-        pub config_partition: ::std::option::Option<PartitionConfig>,
-        #[doc = "Data partition configuration.\n"]
-        pub data_partition: ::std::option::Option<PartitionConfig>,
-        #[doc = "System slots.\n"]
-        pub slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
-        #[doc = "System boot groups.\n"]
-        pub boot_groups:
-            ::std::option::Option<indexmap::IndexMap<::std::string::String, BootGroupConfig>>,
-        #[doc = "Boot flow configuration.\n"]
-        pub boot_flow: ::std::option::Option<BootFlowConfig>,
+    pub struct PartitionConfig {
+        #[doc = ""]
+        pub disabled: ::std::option::Option<bool>,
+        #[doc = "Path to the partition block device.\n"]
+        pub device: ::std::option::Option<::std::string::String>,
+        #[doc = "Partition number of the root device or the given disk.\n"]
+        pub partition: ::std::option::Option<u32>,
+        #[doc = "Disk of the partition, if it is not on the root device.\n"]
+        pub disk: ::std::option::Option<::std::string::String>,
+        #[doc = "Label of the filesystem of the partition.\n"]
+        pub label: ::std::option::Option<::std::string::String>,
+        #[doc = "UUID of the filesystem of the partition.\n"]
+        pub uuid: ::std::option::Option<::std::string::String>,
+        #[doc = "GPT partition label (`PARTLABEL`) of the partition.\n"]
+        pub partlabel: ::std::option::Option<::std::string::String>,
+        #[doc = "Partition UUID (`PARTUUID`) of the partition.\n"]
+        pub partuuid: ::std::option::Option<::std::string::String>,
+        #[doc = "Path where the partition is or should be mounted.\n"]
+        pub path: ::std::option::Option<::std::string::String>,
+        #[doc = "Indicates whether the partition is write-protected.\n"]
+        pub protected: ::std::option::Option<bool>,
+        #[doc = "Encryption of the partition.\n"]
+        pub encryption: ::std::option::Option<PartitionEncryptionConfig>,
     }
-    impl SystemConfig {
-        #[doc = "Creates a new [`SystemConfig`]."]
+    impl PartitionConfig {
+        #[doc = "Creates a new [`PartitionConfig`]."]
         pub fn new() -> Self {
             Self {
-                config_partition: ::std::default::Default::default(),
-                data_partition: ::std::default::Default::default(),
-                slots: ::std::default::Default::default(),
-                boot_groups: ::std::default::Default::default(),
-                boot_flow: ::std::default::Default::default(),
+                disabled: ::std::default::Default::default(),
+                device: ::std::default::Default::default(),
+                partition: ::std::default::Default::default(),
+                disk: ::std::default::Default::default(),
+                label: ::std::default::Default::default(),
+                uuid: ::std::default::Default::default(),
+                partlabel: ::std::default::Default::default(),
+                partuuid: ::std::default::Default::default(),
+                path: ::std::default::Default::default(),
+                protected: ::std::default::Default::default(),
+                encryption: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `config_partition`."]
-        pub fn set_config_partition(
+        #[doc = "Sets the value of `disabled`."]
+        pub fn set_disabled(&mut self, disabled: ::std::option::Option<bool>) -> &mut Self {
+            self.disabled = disabled;
+            self
+        }
+        #[doc = "Sets the value of `disabled`."]
+        pub fn with_disabled(mut self, disabled: ::std::option::Option<bool>) -> Self {
+            self.disabled = disabled;
+            self
+        }
+        #[doc = "Sets the value of `device`."]
+        pub fn set_device(
             &mut self,
-            config_partition: ::std::option::Option<PartitionConfig>,
+            device: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.config_partition = config_partition;
+            self.device = device;
             self
         }
-        #[doc = "Sets the value of `config_partition`."]
-        pub fn with_config_partition(
-            mut self,
-            config_partition: ::std::option::Option<PartitionConfig>,
-        ) -> Self {
-            self.config_partition = config_partition;
+        #[doc = "Sets the value of `device`."]
+        pub fn with_device(mut self, device: ::std::option::Option<::std::string::String>) -> Self {
+            self.device = device;
             self
         }
-        #[doc = "Sets the value of `data_partition`."]
-        pub fn set_data_partition(
+        #[doc = "Sets the value of `partition`."]
+        pub fn set_partition(&mut self, partition: ::std::option::Option<u32>) -> &mut Self {
+            self.partition = partition;
+            self
+        }
+        #[doc = "Sets the value of `partition`."]
+        pub fn with_partition(mut self, partition: ::std::option::Option<u32>) -> Self {
+            self.partition = partition;
+            self
+        }
+        #[doc = "Sets the value of `disk`."]
+        pub fn set_disk(
             &mut self,
-            data_partition: ::std::option::Option<PartitionConfig>,
+            disk: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.data_partition = data_partition;
+            self.disk = disk;
             self
         }
-        #[doc = "Sets the value of `data_partition`."]
-        pub fn with_data_partition(
-            mut self,
-            data_partition: ::std::option::Option<PartitionConfig>,
-        ) -> Self {
-            self.data_partition = data_partition;
+        #[doc = "Sets the value of `disk`."]
+        pub fn with_disk(mut self, disk: ::std::option::Option<::std::string::String>) -> Self {
+            self.disk = disk;
             self
         }
-        #[doc = "Sets the value of `slots`."]
-        pub fn set_slots(
+        #[doc = "Sets the value of `label`."]
+        pub fn set_label(
             &mut self,
-            slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
+            label: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.slots = slots;
+            self.label = label;
             self
         }
-        #[doc = "Sets the value of `slots`."]
-        pub fn with_slots(
+        #[doc = "Sets the value of `label`."]
+        pub fn with_label(mut self, label: ::std::option::Option<::std::string::String>) -> Self {
+            self.label = label;
+            self
+        }
+        #[doc = "Sets the value of `uuid`."]
+        pub fn set_uuid(
+            &mut self,
+            uuid: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.uuid = uuid;
+            self
+        }
+        #[doc = "Sets the value of `uuid`."]
+        pub fn with_uuid(mut self, uuid: ::std::option::Option<::std::string::String>) -> Self {
+            self.uuid = uuid;
+            self
+        }
+        #[doc = "Sets the value of `partlabel`."]
+        pub fn set_partlabel(
+            &mut self,
+            partlabel: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.partlabel = partlabel;
+            self
+        }
+        #[doc = "Sets the value of `partlabel`."]
+        pub fn with_partlabel(
             mut self,
-            slots: ::std::option::Option<indexmap::IndexMap<::std::string::String, SlotConfig>>,
+            partlabel: ::std::option::Option<::std::string::String>,
         ) -> Self {
-            self.slots = slots;
+            self.partlabel = partlabel;
             self
         }
-        #[doc = "Sets the value of `boot_groups`."]
-        pub fn set_boot_groups(
+        #[doc = "Sets the value of `partuuid`."]
+        pub fn set_partuuid(
             &mut self,
-            boot_groups: ::std::option::Option<
-                indexmap::IndexMap<::std::string::String, BootGroupConfig>,
-            >,
+            partuuid: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.boot_groups = boot_groups;
+            self.partuuid = partuuid;
             self
         }
-        #[doc = "Sets the value of `boot_groups`."]
-        pub fn with_boot_groups(
+        #[doc = "Sets the value of `partuuid`."]
+        pub fn with_partuuid(
             mut self,
-            boot_groups: ::std::option::Option<
-                indexmap::IndexMap<::std::string::String, BootGroupConfig>,
-            >,
+            partuuid: ::std::option::Option<::std::string::String>,
         ) -> Self {
-            self.boot_groups = boot_groups;
+            self.partuuid = partuuid;
             self
         }
-        #[doc = "Sets the value of `boot_flow`."]
-        pub fn set_boot_flow(
+        #[doc = "Sets the value of `path`."]
+        pub fn set_path(
             &mut self,
-            boot_flow: ::std::option::Option<BootFlowConfig>,
+            path: ::std::option::Option<::std::string::String>,
         ) -> &mut Self {
-            self.boot_flow = boot_flow;
+            self.path = path;
             self
         }
-        #[doc = "Sets the value of `boot_flow`."]
-        pub fn with_boot_flow(mut self, boot_flow: ::std::option::Option<BootFlowConfig>) -> Self {
-            self.boot_flow = boot_flow;
+        #[doc = "Sets the value of `path`."]
+        pub fn with_path(mut self, path: ::std::option::Option<::std::string::String>) -> Self {
+            self.path = path;
+            self
+        }
+        #[doc = "Sets the value of `protected`."]
+        pub fn set_protected(&mut self, protected: ::std::option::Option<bool>) -> &mut Self {
+            self.protected = protected;
+            self
+        }
+        #[doc = "Sets the value of `protected`."]
+        pub fn with_protected(mut self, protected: ::std::option::Option<bool>) -> Self {
+            self.protected = protected;
+            self
+        }
+        #[doc = "Sets the value of `encryption`."]
+        pub fn set_encryption(
+            &mut self,
+            encryption: ::std::option::Option<PartitionEncryptionConfig>,
+        ) -> &mut Self {
+            self.encryption = encryption;
+            self
+        }
+        #[doc = "Sets the value of `encryption`."]
+        pub fn with_encryption(
+            mut self,
+            encryption: ::std::option::Option<PartitionEncryptionConfig>,
+        ) -> Self {
+            self.encryption = encryption;
             self
         }
     }
-    impl ::std::default::Default for SystemConfig {
+    impl ::std::default::Default for PartitionConfig {
         fn default() -> Self {
             Self::new()
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for SystemConfig {
+    impl __serde::Serialize for PartitionConfig {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
-            let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "SystemConfig", 5usize)?;
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "PartitionConfig",
+                11usize,
+            )?;
             __record.serialize_optional_field(
-                "config-partition",
-                ::core::option::Option::as_ref(&self.config_partition),
+                "disabled",
+                ::core::option::Option::as_ref(&self.disabled),
             )?;
+            __record
+                .serialize_optional_field("device", ::core::option::Option::as_ref(&self.device))?;
             __record.serialize_optional_field(
-                "data-partition",
-                ::core::option::Option::as_ref(&self.data_partition),
+                "partition",
+                ::core::option::Option::as_ref(&self.partition),
             )?;
             __record
-                .serialize_optional_field("slots", ::core::option::Option::as_ref(&self.slots))?;
+                .serialize_optional_field("disk", ::core::option::Option::as_ref(&self.disk))?;
+            __record
+                .serialize_optional_field("label", ::core::option::Option::as_ref(&self.label))?;
+            __record
+                .serialize_optional_field("uuid", ::core::option::Option::as_ref(&self.uuid))?;
             __record.serialize_optional_field(
-                "boot-groups",
-                ::core::option::Option::as_ref(&self.boot_groups),
+                "partlabel",
+                ::core::option::Option::as_ref(&self.partlabel),
             )?;
             __record.serialize_optional_field(
-                "boot-flow",
-                ::core::option::Option::as_ref(&self.boot_flow),
+                "partuuid",
+                ::core::option::Option::as_ref(&self.partuuid),
+            )?;
+            __record
+                .serialize_optional_field("path", ::core::option::Option::as_ref(&self.path))?;
+            __record.serialize_optional_field(
+                "protected",
+                ::core::option::Option::as_ref(&self.protected),
+            )?;
+            __record.serialize_optional_field(
+                "encryption",
+                ::core::option::Option::as_ref(&self.encryption),
             )?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for SystemConfig {
+    impl<'de> __serde::Deserialize<'de> for PartitionConfig {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -4295,12 +7564,12 @@ pub mod system {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = SystemConfig;
+                type Value = PartitionConfig;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record SystemConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record PartitionConfig")
                 }
                 #[inline]
                 fn visit_seq<__A>(
@@ -4311,70 +7580,171 @@ pub mod system {
                     __A: __serde::de::SeqAccess<'de>,
                 {
                     let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<PartitionConfig>,
+                        ::std::option::Option<bool>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(
+                                    0usize,
+                                    &"record with 11 fields",
+                                ),
                             );
                         }
                     };
                     let __field1 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<PartitionConfig>,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(
+                                    1usize,
+                                    &"record with 11 fields",
+                                ),
                             );
                         }
                     };
                     let __field2 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, SlotConfig>,
-                        >,
+                        ::std::option::Option<u32>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(
+                                    2usize,
+                                    &"record with 11 fields",
+                                ),
                             );
                         }
                     };
                     let __field3 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, BootGroupConfig>,
-                        >,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(3usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(
+                                    3usize,
+                                    &"record with 11 fields",
+                                ),
                             );
                         }
                     };
                     let __field4 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<BootFlowConfig>,
+                        ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(4usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(
+                                    4usize,
+                                    &"record with 11 fields",
+                                ),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(SystemConfig {
-                        config_partition: __field0,
-                        data_partition: __field1,
-                        slots: __field2,
-                        boot_groups: __field3,
-                        boot_flow: __field4,
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    5usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    let __field6 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    6usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    let __field7 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    7usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    let __field8 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    8usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    let __field9 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<bool>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    9usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    let __field10 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<PartitionEncryptionConfig>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(
+                                    10usize,
+                                    &"record with 11 fields",
+                                ),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PartitionConfig {
+                        disabled: __field0,
+                        device: __field1,
+                        partition: __field2,
+                        disk: __field3,
+                        label: __field4,
+                        uuid: __field5,
+                        partlabel: __field6,
+                        partuuid: __field7,
+                        path: __field8,
+                        protected: __field9,
+                        encryption: __field10,
                     })
                 }
                 #[inline]
@@ -4387,14 +7757,20 @@ pub mod system {
                 {
                     #[doc(hidden)]
                     const __IDENTIFIERS: &'static [&'static str] = &[
-                        "config-partition",
-                        "data-partition",
-                        "slots",
-                        "boot-groups",
-                        "boot-flow",
+                        "disabled",
+                        "device",
+                        "partition",
+                        "disk",
+                        "label",
+                        "uuid",
+                        "partlabel",
+                        "partuuid",
+                        "path",
+                        "protected",
+                        "encryption",
                     ];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"config-partition\", \"data-partition\", \"slots\", \"boot-groups\", \"boot-flow\"]" ;
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"disabled\", \"device\", \"partition\", \"disk\", \"label\", \"uuid\", \"partlabel\", \"partuuid\", \"path\", \"protected\", \"encryption\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
@@ -4403,6 +7779,12 @@ pub mod system {
                         __Identifier2,
                         __Identifier3,
                         __Identifier4,
+                        __Identifier5,
+                        __Identifier6,
+                        __Identifier7,
+                        __Identifier8,
+                        __Identifier9,
+                        __Identifier10,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -4428,6 +7810,12 @@ pub mod system {
                                 2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
                                 3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
                                 4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                6u64 => ::core::result::Result::Ok(__Identifier::__Identifier6),
+                                7u64 => ::core::result::Result::Ok(__Identifier::__Identifier7),
+                                8u64 => ::core::result::Result::Ok(__Identifier::__Identifier8),
+                                9u64 => ::core::result::Result::Ok(__Identifier::__Identifier9),
+                                10u64 => ::core::result::Result::Ok(__Identifier::__Identifier10),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -4439,18 +7827,28 @@ pub mod system {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "config-partition" => {
+                                "disabled" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                "data-partition" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
+                                "device" => ::core::result::Result::Ok(__Identifier::__Identifier1),
+                                "partition" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
                                 }
-                                "slots" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                "boot-groups" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                "disk" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                "label" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                "uuid" => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                "partlabel" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
                                 }
-                                "boot-flow" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                "partuuid" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
+                                }
+                                "path" => ::core::result::Result::Ok(__Identifier::__Identifier8),
+                                "protected" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier9)
+                                }
+                                "encryption" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier10)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
@@ -4463,18 +7861,30 @@ pub mod system {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"config-partition" => {
+                                b"disabled" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                b"data-partition" => {
+                                b"device" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
-                                b"slots" => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                b"boot-groups" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier3)
+                                b"partition" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
                                 }
-                                b"boot-flow" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                b"disk" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                b"label" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                b"uuid" => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                b"partlabel" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
+                                b"partuuid" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
+                                }
+                                b"path" => ::core::result::Result::Ok(__Identifier::__Identifier8),
+                                b"protected" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier9)
+                                }
+                                b"encryption" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier10)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
@@ -4494,24 +7904,35 @@ pub mod system {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<
-                        ::std::option::Option<PartitionConfig>,
-                    > = ::core::option::Option::None;
+                    let mut __field0: ::core::option::Option<::std::option::Option<bool>> =
+                        ::core::option::Option::None;
                     let mut __field1: ::core::option::Option<
-                        ::std::option::Option<PartitionConfig>,
-                    > = ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, SlotConfig>,
-                        >,
+                        ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
+                    let mut __field2: ::core::option::Option<::std::option::Option<u32>> =
+                        ::core::option::Option::None;
                     let mut __field3: ::core::option::Option<
-                        ::std::option::Option<
-                            indexmap::IndexMap<::std::string::String, BootGroupConfig>,
-                        >,
+                        ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
                     let mut __field4: ::core::option::Option<
-                        ::std::option::Option<BootFlowConfig>,
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field6: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field7: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field8: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field9: ::core::option::Option<::std::option::Option<bool>> =
+                        ::core::option::Option::None;
+                    let mut __field10: ::core::option::Option<
+                        ::std::option::Option<PartitionEncryptionConfig>,
                     > = ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -4521,13 +7942,13 @@ pub mod system {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "config-partition",
+                                            "disabled",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<PartitionConfig>,
+                                        ::std::option::Option<bool>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -4535,13 +7956,13 @@ pub mod system {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "data-partition",
+                                            "device",
                                         ),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<PartitionConfig>,
+                                        ::std::option::Option<::std::string::String>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -4549,34 +7970,25 @@ pub mod system {
                                 if ::core::option::Option::is_some(&__field2) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "slots",
+                                            "partition",
                                         ),
                                     );
                                 }
                                 __field2 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<
-                                            indexmap::IndexMap<::std::string::String, SlotConfig>,
-                                        >,
-                                    >(&mut __map)?,
+                                    __serde::de::MapAccess::next_value::<::std::option::Option<u32>>(
+                                        &mut __map,
+                                    )?,
                                 );
                             }
                             __Identifier::__Identifier3 => {
                                 if ::core::option::Option::is_some(&__field3) {
                                     return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "boot-groups",
-                                        ),
+                                        <__A::Error as __serde::de::Error>::duplicate_field("disk"),
                                     );
                                 }
                                 __field3 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<
-                                            indexmap::IndexMap<
-                                                ::std::string::String,
-                                                BootGroupConfig,
-                                            >,
-                                        >,
+                                        ::std::option::Option<::std::string::String>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -4584,13 +7996,93 @@ pub mod system {
                                 if ::core::option::Option::is_some(&__field4) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "boot-flow",
+                                            "label",
                                         ),
                                     );
                                 }
                                 __field4 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<BootFlowConfig>,
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("uuid"),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier6 => {
+                                if ::core::option::Option::is_some(&__field6) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "partlabel",
+                                        ),
+                                    );
+                                }
+                                __field6 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier7 => {
+                                if ::core::option::Option::is_some(&__field7) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "partuuid",
+                                        ),
+                                    );
+                                }
+                                __field7 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier8 => {
+                                if ::core::option::Option::is_some(&__field8) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("path"),
+                                    );
+                                }
+                                __field8 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier9 => {
+                                if ::core::option::Option::is_some(&__field9) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "protected",
+                                        ),
+                                    );
+                                }
+                                __field9 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<bool>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier10 => {
+                                if ::core::option::Option::is_some(&__field10) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "encryption",
+                                        ),
+                                    );
+                                }
+                                __field10 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<PartitionEncryptionConfig>,
                                     >(&mut __map)?,
                                 );
                             }
@@ -4621,26 +8113,62 @@ pub mod system {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
-                    ::core::result::Result::Ok(SystemConfig {
-                        config_partition: __field0,
-                        data_partition: __field1,
-                        slots: __field2,
-                        boot_groups: __field3,
-                        boot_flow: __field4,
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field6 = match __field6 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field7 = match __field7 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field8 = match __field8 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field9 = match __field9 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field10 = match __field10 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(PartitionConfig {
+                        disabled: __field0,
+                        device: __field1,
+                        partition: __field2,
+                        disk: __field3,
+                        label: __field4,
+                        uuid: __field5,
+                        partlabel: __field6,
+                        partuuid: __field7,
+                        path: __field8,
+                        protected: __field9,
+                        encryption: __field10,
                     })
                 }
             }
             #[doc(hidden)]
             const __FIELDS: &'static [&'static str] = &[
-                "config-partition",
-                "data-partition",
-                "slots",
-                "boot-groups",
-                "boot-flow",
+                "disabled",
+                "device",
+                "partition",
+                "disk",
+                "label",
+                "uuid",
+                "partlabel",
+                "partuuid",
+                "path",
+                "protected",
+                "encryption",
             ];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "SystemConfig",
+                "PartitionConfig",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -4648,41 +8176,22 @@ pub mod system {
             )
         }
     }
-    #[doc = "Partition configuration.\n"]
+    #[doc = "Disk configuration.\n"]
     #[derive(Clone, Debug)]
-    pub struct PartitionConfig {
-        #[doc = ""]
-        pub disabled: ::std::option::Option<bool>,
-        #[doc = "Path to the partition block device.\n"]
+    pub struct DiskConfig {
+        #[doc = "Path to the block device of the disk.\n"]
         pub device: ::std::option::Option<::std::string::String>,
-        #[doc = "Partition number of the root device.\n"]
-        pub partition: ::std::option::Option<u32>,
-        #[doc = "Path where the partition is or should be mounted.\n"]
-        pub path: ::std::option::Option<::std::string::String>,
-        #[doc = "Indicates whether the partition is write-protected.\n"]
-        pub protected: ::std::option::Option<bool>,
+        #[doc = "Identifier of the disk (GPT disk GUID or MBR disk identifier).\n"]
+        pub id: ::std::option::Option<::std::string::String>,
     }
-    impl PartitionConfig {
-        #[doc = "Creates a new [`PartitionConfig`]."]
+    impl DiskConfig {
+        #[doc = "Creates a new [`DiskConfig`]."]
         pub fn new() -> Self {
             Self {
-                disabled: ::std::default::Default::default(),
                 device: ::std::default::Default::default(),
-                partition: ::std::default::Default::default(),
-                path: ::std::default::Default::default(),
-                protected: ::std::default::Default::default(),
+                id: ::std::default::Default::default(),
             }
         }
-        #[doc = "Sets the value of `disabled`."]
-        pub fn set_disabled(&mut self, disabled: ::std::option::Option<bool>) -> &mut Self {
-            self.disabled = disabled;
-            self
-        }
-        #[doc = "Sets the value of `disabled`."]
-        pub fn with_disabled(mut self, disabled: ::std::option::Option<bool>) -> Self {
-            self.disabled = disabled;
-            self
-        }
         #[doc = "Sets the value of `device`."]
         pub fn set_device(
             &mut self,
@@ -4696,74 +8205,38 @@ pub mod system {
             self.device = device;
             self
         }
-        #[doc = "Sets the value of `partition`."]
-        pub fn set_partition(&mut self, partition: ::std::option::Option<u32>) -> &mut Self {
-            self.partition = partition;
-            self
-        }
-        #[doc = "Sets the value of `partition`."]
-        pub fn with_partition(mut self, partition: ::std::option::Option<u32>) -> Self {
-            self.partition = partition;
-            self
-        }
-        #[doc = "Sets the value of `path`."]
-        pub fn set_path(
-            &mut self,
-            path: ::std::option::Option<::std::string::String>,
-        ) -> &mut Self {
-            self.path = path;
-            self
-        }
-        #[doc = "Sets the value of `path`."]
-        pub fn with_path(mut self, path: ::std::option::Option<::std::string::String>) -> Self {
-            self.path = path;
-            self
-        }
-        #[doc = "Sets the value of `protected`."]
-        pub fn set_protected(&mut self, protected: ::std::option::Option<bool>) -> &mut Self {
-            self.protected = protected;
+        #[doc = "Sets the value of `id`."]
+        pub fn set_id(&mut self, id: ::std::option::Option<::std::string::String>) -> &mut Self {
+            self.id = id;
             self
         }
-        #[doc = "Sets the value of `protected`."]
-        pub fn with_protected(mut self, protected: ::std::option::Option<bool>) -> Self {
-            self.protected = protected;
+        #[doc = "Sets the value of `id`."]
+        pub fn with_id(mut self, id: ::std::option::Option<::std::string::String>) -> Self {
+            self.id = id;
             self
         }
     }
-    impl ::std::default::Default for PartitionConfig {
+    impl ::std::default::Default for DiskConfig {
         fn default() -> Self {
             Self::new()
         }
     }
     #[automatically_derived]
-    impl __serde::Serialize for PartitionConfig {
+    impl __serde::Serialize for DiskConfig {
         fn serialize<__S: __serde::Serializer>(
             &self,
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "PartitionConfig", 5usize)?;
-            __record.serialize_optional_field(
-                "disabled",
-                ::core::option::Option::as_ref(&self.disabled),
-            )?;
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "DiskConfig", 2usize)?;
             __record
                 .serialize_optional_field("device", ::core::option::Option::as_ref(&self.device))?;
-            __record.serialize_optional_field(
-                "partition",
-                ::core::option::Option::as_ref(&self.partition),
-            )?;
-            __record
-                .serialize_optional_field("path", ::core::option::Option::as_ref(&self.path))?;
-            __record.serialize_optional_field(
-                "protected",
-                ::core::option::Option::as_ref(&self.protected),
-            )?;
+            __record.serialize_optional_field("id", ::core::option::Option::as_ref(&self.id))?;
             __record.end()
         }
     }
     #[automatically_derived]
-    impl<'de> __serde::Deserialize<'de> for PartitionConfig {
+    impl<'de> __serde::Deserialize<'de> for DiskConfig {
         fn deserialize<__D: __serde::Deserializer<'de>>(
             __deserializer: __D,
         ) -> ::std::result::Result<Self, __D::Error> {
@@ -4772,82 +8245,46 @@ pub mod system {
                 __phantom_vars: ::core::marker::PhantomData<fn(&())>,
             }
             impl<'de> __serde::de::Visitor<'de> for __Visitor {
-                type Value = PartitionConfig;
+                type Value = DiskConfig;
                 fn expecting(
                     &self,
                     __formatter: &mut ::core::fmt::Formatter,
                 ) -> ::core::fmt::Result {
-                    ::core::fmt::Formatter::write_str(__formatter, "record PartitionConfig")
+                    ::core::fmt::Formatter::write_str(__formatter, "record DiskConfig")
                 }
                 #[inline]
                 fn visit_seq<__A>(
                     self,
                     mut __seq: __A,
-                ) -> ::core::result::Result<Self::Value, __A::Error>
-                where
-                    __A: __serde::de::SeqAccess<'de>,
-                {
-                    let __field0 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<bool>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 5 fields"),
-                            );
-                        }
-                    };
-                    let __field1 = match __serde::de::SeqAccess::next_element::<
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
                         ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 5 fields"),
-                            );
-                        }
-                    };
-                    let __field2 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<u32>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 2 fields"),
                             );
                         }
                     };
-                    let __field3 = match __serde::de::SeqAccess::next_element::<
+                    let __field1 = match __serde::de::SeqAccess::next_element::<
                         ::std::option::Option<::std::string::String>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(3usize, &"record with 5 fields"),
-                            );
-                        }
-                    };
-                    let __field4 = match __serde::de::SeqAccess::next_element::<
-                        ::std::option::Option<bool>,
-                    >(&mut __seq)?
-                    {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => {
-                            return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(4usize, &"record with 5 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 2 fields"),
                             );
                         }
                     };
-                    ::core::result::Result::Ok(PartitionConfig {
-                        disabled: __field0,
-                        device: __field1,
-                        partition: __field2,
-                        path: __field3,
-                        protected: __field4,
+                    ::core::result::Result::Ok(DiskConfig {
+                        device: __field0,
+                        id: __field1,
                     })
                 }
                 #[inline]
@@ -4859,18 +8296,15 @@ pub mod system {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] =
-                        &["disabled", "device", "partition", "path", "protected"];
+                    const __IDENTIFIERS: &'static [&'static str] = &["device", "id"];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"disabled\", \"device\", \"partition\", \"path\", \"protected\"]" ;
+                    const __EXPECTING_IDENTIFIERS: &'static str =
+                        "an identifier in [\"device\", \"id\"]";
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
-                        __Identifier2,
-                        __Identifier3,
-                        __Identifier4,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -4893,9 +8327,6 @@ pub mod system {
                             match __value {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                                2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
-                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -4907,17 +8338,8 @@ pub mod system {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                "disabled" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
-                                "device" => ::core::result::Result::Ok(__Identifier::__Identifier1),
-                                "partition" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
-                                }
-                                "path" => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                                "protected" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
-                                }
+                                "device" => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                "id" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -4929,19 +8351,10 @@ pub mod system {
                             __E: __serde::de::Error,
                         {
                             match __value {
-                                b"disabled" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
-                                }
                                 b"device" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier1)
-                                }
-                                b"partition" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
-                                }
-                                b"path" => ::core::result::Result::Ok(__Identifier::__Identifier3),
-                                b"protected" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier4)
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
+                                b"id" => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -4960,18 +8373,12 @@ pub mod system {
                             )
                         }
                     }
-                    let mut __field0: ::core::option::Option<::std::option::Option<bool>> =
-                        ::core::option::Option::None;
-                    let mut __field1: ::core::option::Option<
+                    let mut __field0: ::core::option::Option<
                         ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<::std::option::Option<u32>> =
-                        ::core::option::Option::None;
-                    let mut __field3: ::core::option::Option<
+                    let mut __field1: ::core::option::Option<
                         ::std::option::Option<::std::string::String>,
                     > = ::core::option::Option::None;
-                    let mut __field4: ::core::option::Option<::std::option::Option<bool>> =
-                        ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
                     {
@@ -4980,22 +8387,20 @@ pub mod system {
                                 if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "disabled",
+                                            "device",
                                         ),
                                     );
                                 }
                                 __field0 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<bool>,
+                                        ::std::option::Option<::std::string::String>,
                                     >(&mut __map)?,
                                 );
                             }
                             __Identifier::__Identifier1 => {
                                 if ::core::option::Option::is_some(&__field1) {
                                     return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "device",
-                                        ),
+                                        <__A::Error as __serde::de::Error>::duplicate_field("id"),
                                     );
                                 }
                                 __field1 = ::core::option::Option::Some(
@@ -5004,44 +8409,218 @@ pub mod system {
                                     >(&mut __map)?,
                                 );
                             }
-                            __Identifier::__Identifier2 => {
-                                if ::core::option::Option::is_some(&__field2) {
-                                    return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "partition",
-                                        ),
-                                    );
+                            _ => {
+                                __serde::de::MapAccess::next_value::<__serde::de::IgnoredAny>(
+                                    &mut __map,
+                                )?;
+                            }
+                        }
+                    }
+                    let __field0 = match __field0 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field1 = match __field1 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    ::core::result::Result::Ok(DiskConfig {
+                        device: __field0,
+                        id: __field1,
+                    })
+                }
+            }
+            #[doc(hidden)]
+            const __FIELDS: &'static [&'static str] = &["device", "id"];
+            __serde::Deserializer::deserialize_struct(
+                __deserializer,
+                "DiskConfig",
+                __FIELDS,
+                __Visitor {
+                    __phantom_vars: ::core::marker::PhantomData,
+                },
+            )
+        }
+    }
+    #[doc = "Partition encryption configuration.\n"]
+    #[derive(Clone, Debug)]
+    pub struct PartitionEncryptionConfig {
+        #[doc = "Path to the key file used to unlock the LUKS container.\n"]
+        pub key_file: ::std::string::String,
+    }
+    impl PartitionEncryptionConfig {
+        #[doc = "Creates a new [`PartitionEncryptionConfig`]."]
+        pub fn new(key_file: ::std::string::String) -> Self {
+            Self { key_file }
+        }
+        #[doc = "Sets the value of `key_file`."]
+        pub fn set_key_file(&mut self, key_file: ::std::string::String) -> &mut Self {
+            self.key_file = key_file;
+            self
+        }
+        #[doc = "Sets the value of `key_file`."]
+        pub fn with_key_file(mut self, key_file: ::std::string::String) -> Self {
+            self.key_file = key_file;
+            self
+        }
+    }
+    #[automatically_derived]
+    impl __serde::Serialize for PartitionEncryptionConfig {
+        fn serialize<__S: __serde::Serializer>(
+            &self,
+            __serializer: __S,
+        ) -> ::std::result::Result<__S::Ok, __S::Error> {
+            let mut __record = __sidex_serde::ser::RecordSerializer::new(
+                __serializer,
+                "PartitionEncryptionConfig",
+                1usize,
+            )?;
+            __record.serialize_field("key-file", &self.key_file)?;
+            __record.end()
+        }
+    }
+    #[automatically_derived]
+    impl<'de> __serde::Deserialize<'de> for PartitionEncryptionConfig {
+        fn deserialize<__D: __serde::Deserializer<'de>>(
+            __deserializer: __D,
+        ) -> ::std::result::Result<Self, __D::Error> {
+            #[doc(hidden)]
+            struct __Visitor {
+                __phantom_vars: ::core::marker::PhantomData<fn(&())>,
+            }
+            impl<'de> __serde::de::Visitor<'de> for __Visitor {
+                type Value = PartitionEncryptionConfig;
+                fn expecting(
+                    &self,
+                    __formatter: &mut ::core::fmt::Formatter,
+                ) -> ::core::fmt::Result {
+                    ::core::fmt::Formatter::write_str(
+                        __formatter,
+                        "record PartitionEncryptionConfig",
+                    )
+                }
+                #[inline]
+                fn visit_seq<__A>(
+                    self,
+                    mut __seq: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::SeqAccess<'de>,
+                {
+                    let __field0 = match __serde::de::SeqAccess::next_element::<
+                        ::std::string::String,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(0usize, &"record with 1 fields"),
+                            );
+                        }
+                    };
+                    ::core::result::Result::Ok(PartitionEncryptionConfig { key_file: __field0 })
+                }
+                #[inline]
+                fn visit_map<__A>(
+                    self,
+                    mut __map: __A,
+                ) -> ::core::result::Result<Self::Value, __A::Error>
+                where
+                    __A: __serde::de::MapAccess<'de>,
+                {
+                    #[doc(hidden)]
+                    const __IDENTIFIERS: &'static [&'static str] = &["key-file"];
+                    #[doc(hidden)]
+                    const __EXPECTING_IDENTIFIERS: &'static str = "an identifier in [\"key-file\"]";
+                    #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
+                    #[doc(hidden)]
+                    enum __Identifier {
+                        __Identifier0,
+                        __Unknown,
+                    }
+                    #[doc(hidden)]
+                    struct __IdentifierVisitor;
+                    impl<'de> __serde::de::Visitor<'de> for __IdentifierVisitor {
+                        type Value = __Identifier;
+                        fn expecting(
+                            &self,
+                            __formatter: &mut ::core::fmt::Formatter,
+                        ) -> ::core::fmt::Result {
+                            ::core::fmt::Formatter::write_str(__formatter, __EXPECTING_IDENTIFIERS)
+                        }
+                        fn visit_u64<__E>(
+                            self,
+                            __value: u64,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
+                            }
+                        }
+                        fn visit_str<__E>(
+                            self,
+                            __value: &str,
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                "key-file" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                __field2 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<::std::option::Option<u32>>(
-                                        &mut __map,
-                                    )?,
-                                );
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
-                            __Identifier::__Identifier3 => {
-                                if ::core::option::Option::is_some(&__field3) {
-                                    return ::core::result::Result::Err(
-                                        <__A::Error as __serde::de::Error>::duplicate_field("path"),
-                                    );
+                        }
+                        fn visit_bytes<__E>(
+                            self,
+                            __value: &[u8],
+                        ) -> ::core::result::Result<Self::Value, __E>
+                        where
+                            __E: __serde::de::Error,
+                        {
+                            match __value {
+                                b"key-file" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier0)
                                 }
-                                __field3 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<::std::string::String>,
-                                    >(&mut __map)?,
-                                );
+                                _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
-                            __Identifier::__Identifier4 => {
-                                if ::core::option::Option::is_some(&__field4) {
+                        }
+                    }
+                    impl<'de> __serde::Deserialize<'de> for __Identifier {
+                        #[inline]
+                        fn deserialize<__D>(
+                            __deserializer: __D,
+                        ) -> ::core::result::Result<Self, __D::Error>
+                        where
+                            __D: __serde::Deserializer<'de>,
+                        {
+                            __serde::Deserializer::deserialize_identifier(
+                                __deserializer,
+                                __IdentifierVisitor,
+                            )
+                        }
+                    }
+                    let mut __field0: ::core::option::Option<::std::string::String> =
+                        ::core::option::Option::None;
+                    while let ::core::option::Option::Some(__key) =
+                        __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
+                    {
+                        match __key {
+                            __Identifier::__Identifier0 => {
+                                if ::core::option::Option::is_some(&__field0) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
-                                            "protected",
+                                            "key-file",
                                         ),
                                     );
                                 }
-                                __field4 = ::core::option::Option::Some(
-                                    __serde::de::MapAccess::next_value::<
-                                        ::std::option::Option<bool>,
-                                    >(&mut __map)?,
+                                __field0 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<::std::string::String>(
+                                        &mut __map,
+                                    )?,
                                 );
                             }
                             _ => {
@@ -5053,39 +8632,20 @@ pub mod system {
                     }
                     let __field0 = match __field0 {
                         ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
-                    };
-                    let __field1 = match __field1 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
-                    };
-                    let __field2 = match __field2 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
-                    };
-                    let __field3 = match __field3 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
-                    };
-                    let __field4 = match __field4 {
-                        ::core::option::Option::Some(__value) => __value,
-                        ::core::option::Option::None => ::core::option::Option::None,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                <__A::Error as __serde::de::Error>::missing_field("key-file"),
+                            );
+                        }
                     };
-                    ::core::result::Result::Ok(PartitionConfig {
-                        disabled: __field0,
-                        device: __field1,
-                        partition: __field2,
-                        path: __field3,
-                        protected: __field4,
-                    })
+                    ::core::result::Result::Ok(PartitionEncryptionConfig { key_file: __field0 })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] =
-                &["disabled", "device", "partition", "path", "protected"];
+            const __FIELDS: &'static [&'static str] = &["key-file"];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
-                "PartitionConfig",
+                "PartitionEncryptionConfig",
                 __FIELDS,
                 __Visitor {
                     __phantom_vars: ::core::marker::PhantomData,
@@ -5306,8 +8866,18 @@ pub mod system {
     pub struct BlockSlotConfig {
         #[doc = "Path to the block device.\n"]
         pub device: ::std::option::Option<::std::string::String>,
-        #[doc = "Partition number of the block device.\n"]
+        #[doc = "Partition number of the root device or the given disk.\n"]
         pub partition: ::std::option::Option<u32>,
+        #[doc = "Disk of the slot's partition, if it is not on the root device.\n"]
+        pub disk: ::std::option::Option<::std::string::String>,
+        #[doc = "Label of the filesystem of the slot.\n\nAs updates replace the filesystem of the slot, the label must still identify\nthe slot after installing an update.\n"]
+        pub label: ::std::option::Option<::std::string::String>,
+        #[doc = "UUID of the filesystem of the slot.\n\nAs updates replace the filesystem of the slot, the UUID must still identify\nthe slot after installing an update.\n"]
+        pub uuid: ::std::option::Option<::std::string::String>,
+        #[doc = "GPT partition label (`PARTLABEL`) of the slot.\n"]
+        pub partlabel: ::std::option::Option<::std::string::String>,
+        #[doc = "Partition UUID (`PARTUUID`) of the slot.\n\nFor MBR partition tables, this has the form `<disk id>-<partition number>`.\n"]
+        pub partuuid: ::std::option::Option<::std::string::String>,
         #[doc = ""]
         pub immutable: ::std::option::Option<bool>,
     }
@@ -5317,6 +8887,11 @@ pub mod system {
             Self {
                 device: ::std::default::Default::default(),
                 partition: ::std::default::Default::default(),
+                disk: ::std::default::Default::default(),
+                label: ::std::default::Default::default(),
+                uuid: ::std::default::Default::default(),
+                partlabel: ::std::default::Default::default(),
+                partuuid: ::std::default::Default::default(),
                 immutable: ::std::default::Default::default(),
             }
         }
@@ -5343,6 +8918,77 @@ pub mod system {
             self.partition = partition;
             self
         }
+        #[doc = "Sets the value of `disk`."]
+        pub fn set_disk(
+            &mut self,
+            disk: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.disk = disk;
+            self
+        }
+        #[doc = "Sets the value of `disk`."]
+        pub fn with_disk(mut self, disk: ::std::option::Option<::std::string::String>) -> Self {
+            self.disk = disk;
+            self
+        }
+        #[doc = "Sets the value of `label`."]
+        pub fn set_label(
+            &mut self,
+            label: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.label = label;
+            self
+        }
+        #[doc = "Sets the value of `label`."]
+        pub fn with_label(mut self, label: ::std::option::Option<::std::string::String>) -> Self {
+            self.label = label;
+            self
+        }
+        #[doc = "Sets the value of `uuid`."]
+        pub fn set_uuid(
+            &mut self,
+            uuid: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.uuid = uuid;
+            self
+        }
+        #[doc = "Sets the value of `uuid`."]
+        pub fn with_uuid(mut self, uuid: ::std::option::Option<::std::string::String>) -> Self {
+            self.uuid = uuid;
+            self
+        }
+        #[doc = "Sets the value of `partlabel`."]
+        pub fn set_partlabel(
+            &mut self,
+            partlabel: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.partlabel = partlabel;
+            self
+        }
+        #[doc = "Sets the value of `partlabel`."]
+        pub fn with_partlabel(
+            mut self,
+            partlabel: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.partlabel = partlabel;
+            self
+        }
+        #[doc = "Sets the value of `partuuid`."]
+        pub fn set_partuuid(
+            &mut self,
+            partuuid: ::std::option::Option<::std::string::String>,
+        ) -> &mut Self {
+            self.partuuid = partuuid;
+            self
+        }
+        #[doc = "Sets the value of `partuuid`."]
+        pub fn with_partuuid(
+            mut self,
+            partuuid: ::std::option::Option<::std::string::String>,
+        ) -> Self {
+            self.partuuid = partuuid;
+            self
+        }
         #[doc = "Sets the value of `immutable`."]
         pub fn set_immutable(&mut self, immutable: ::std::option::Option<bool>) -> &mut Self {
             self.immutable = immutable;
@@ -5366,13 +9012,27 @@ pub mod system {
             __serializer: __S,
         ) -> ::std::result::Result<__S::Ok, __S::Error> {
             let mut __record =
-                __sidex_serde::ser::RecordSerializer::new(__serializer, "BlockSlotConfig", 3usize)?;
+                __sidex_serde::ser::RecordSerializer::new(__serializer, "BlockSlotConfig", 8usize)?;
             __record
                 .serialize_optional_field("device", ::core::option::Option::as_ref(&self.device))?;
             __record.serialize_optional_field(
                 "partition",
                 ::core::option::Option::as_ref(&self.partition),
             )?;
+            __record
+                .serialize_optional_field("disk", ::core::option::Option::as_ref(&self.disk))?;
+            __record
+                .serialize_optional_field("label", ::core::option::Option::as_ref(&self.label))?;
+            __record
+                .serialize_optional_field("uuid", ::core::option::Option::as_ref(&self.uuid))?;
+            __record.serialize_optional_field(
+                "partlabel",
+                ::core::option::Option::as_ref(&self.partlabel),
+            )?;
+            __record.serialize_optional_field(
+                "partuuid",
+                ::core::option::Option::as_ref(&self.partuuid),
+            )?;
             __record.serialize_optional_field(
                 "immutable",
                 ::core::option::Option::as_ref(&self.immutable),
@@ -5412,7 +9072,7 @@ pub mod system {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(0usize, &"record with 3 fields"),
+                                __serde::de::Error::invalid_length(0usize, &"record with 8 fields"),
                             );
                         }
                     };
@@ -5423,25 +9083,85 @@ pub mod system {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(1usize, &"record with 3 fields"),
+                                __serde::de::Error::invalid_length(1usize, &"record with 8 fields"),
                             );
                         }
                     };
                     let __field2 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(2usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field3 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(3usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field4 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(4usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field5 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(5usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field6 = match __serde::de::SeqAccess::next_element::<
+                        ::std::option::Option<::std::string::String>,
+                    >(&mut __seq)?
+                    {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(
+                                __serde::de::Error::invalid_length(6usize, &"record with 8 fields"),
+                            );
+                        }
+                    };
+                    let __field7 = match __serde::de::SeqAccess::next_element::<
                         ::std::option::Option<bool>,
                     >(&mut __seq)?
                     {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => {
                             return ::core::result::Result::Err(
-                                __serde::de::Error::invalid_length(2usize, &"record with 3 fields"),
+                                __serde::de::Error::invalid_length(7usize, &"record with 8 fields"),
                             );
                         }
                     };
                     ::core::result::Result::Ok(BlockSlotConfig {
                         device: __field0,
                         partition: __field1,
-                        immutable: __field2,
+                        disk: __field2,
+                        label: __field3,
+                        uuid: __field4,
+                        partlabel: __field5,
+                        partuuid: __field6,
+                        immutable: __field7,
                     })
                 }
                 #[inline]
@@ -5453,17 +9173,29 @@ pub mod system {
                     __A: __serde::de::MapAccess<'de>,
                 {
                     #[doc(hidden)]
-                    const __IDENTIFIERS: &'static [&'static str] =
-                        &["device", "partition", "immutable"];
+                    const __IDENTIFIERS: &'static [&'static str] = &[
+                        "device",
+                        "partition",
+                        "disk",
+                        "label",
+                        "uuid",
+                        "partlabel",
+                        "partuuid",
+                        "immutable",
+                    ];
                     #[doc(hidden)]
-                    const __EXPECTING_IDENTIFIERS: &'static str =
-                        "an identifier in [\"device\", \"partition\", \"immutable\"]";
+                    const __EXPECTING_IDENTIFIERS : & 'static str = "an identifier in [\"device\", \"partition\", \"disk\", \"label\", \"uuid\", \"partlabel\", \"partuuid\", \"immutable\"]" ;
                     #[derive(:: core :: clone :: Clone, :: core :: marker :: Copy)]
                     #[doc(hidden)]
                     enum __Identifier {
                         __Identifier0,
                         __Identifier1,
                         __Identifier2,
+                        __Identifier3,
+                        __Identifier4,
+                        __Identifier5,
+                        __Identifier6,
+                        __Identifier7,
                         __Unknown,
                     }
                     #[doc(hidden)]
@@ -5487,6 +9219,11 @@ pub mod system {
                                 0u64 => ::core::result::Result::Ok(__Identifier::__Identifier0),
                                 1u64 => ::core::result::Result::Ok(__Identifier::__Identifier1),
                                 2u64 => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                3u64 => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                4u64 => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                5u64 => ::core::result::Result::Ok(__Identifier::__Identifier5),
+                                6u64 => ::core::result::Result::Ok(__Identifier::__Identifier6),
+                                7u64 => ::core::result::Result::Ok(__Identifier::__Identifier7),
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
                         }
@@ -5502,8 +9239,17 @@ pub mod system {
                                 "partition" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                "disk" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                "label" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                "uuid" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                "partlabel" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                "partuuid" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
                                 "immutable" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
@@ -5522,8 +9268,17 @@ pub mod system {
                                 b"partition" => {
                                     ::core::result::Result::Ok(__Identifier::__Identifier1)
                                 }
+                                b"disk" => ::core::result::Result::Ok(__Identifier::__Identifier2),
+                                b"label" => ::core::result::Result::Ok(__Identifier::__Identifier3),
+                                b"uuid" => ::core::result::Result::Ok(__Identifier::__Identifier4),
+                                b"partlabel" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier5)
+                                }
+                                b"partuuid" => {
+                                    ::core::result::Result::Ok(__Identifier::__Identifier6)
+                                }
                                 b"immutable" => {
-                                    ::core::result::Result::Ok(__Identifier::__Identifier2)
+                                    ::core::result::Result::Ok(__Identifier::__Identifier7)
                                 }
                                 _ => ::core::result::Result::Ok(__Identifier::__Unknown),
                             }
@@ -5548,7 +9303,22 @@ pub mod system {
                     > = ::core::option::Option::None;
                     let mut __field1: ::core::option::Option<::std::option::Option<u32>> =
                         ::core::option::Option::None;
-                    let mut __field2: ::core::option::Option<::std::option::Option<bool>> =
+                    let mut __field2: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field3: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field4: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field5: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field6: ::core::option::Option<
+                        ::std::option::Option<::std::string::String>,
+                    > = ::core::option::Option::None;
+                    let mut __field7: ::core::option::Option<::std::option::Option<bool>> =
                         ::core::option::Option::None;
                     while let ::core::option::Option::Some(__key) =
                         __serde::de::MapAccess::next_key::<__Identifier>(&mut __map)?
@@ -5584,13 +9354,79 @@ pub mod system {
                             }
                             __Identifier::__Identifier2 => {
                                 if ::core::option::Option::is_some(&__field2) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("disk"),
+                                    );
+                                }
+                                __field2 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier3 => {
+                                if ::core::option::Option::is_some(&__field3) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "label",
+                                        ),
+                                    );
+                                }
+                                __field3 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier4 => {
+                                if ::core::option::Option::is_some(&__field4) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field("uuid"),
+                                    );
+                                }
+                                __field4 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier5 => {
+                                if ::core::option::Option::is_some(&__field5) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "partlabel",
+                                        ),
+                                    );
+                                }
+                                __field5 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier6 => {
+                                if ::core::option::Option::is_some(&__field6) {
+                                    return ::core::result::Result::Err(
+                                        <__A::Error as __serde::de::Error>::duplicate_field(
+                                            "partuuid",
+                                        ),
+                                    );
+                                }
+                                __field6 = ::core::option::Option::Some(
+                                    __serde::de::MapAccess::next_value::<
+                                        ::std::option::Option<::std::string::String>,
+                                    >(&mut __map)?,
+                                );
+                            }
+                            __Identifier::__Identifier7 => {
+                                if ::core::option::Option::is_some(&__field7) {
                                     return ::core::result::Result::Err(
                                         <__A::Error as __serde::de::Error>::duplicate_field(
                                             "immutable",
                                         ),
                                     );
                                 }
-                                __field2 = ::core::option::Option::Some(
+                                __field7 = ::core::option::Option::Some(
                                     __serde::de::MapAccess::next_value::<
                                         ::std::option::Option<bool>,
                                     >(&mut __map)?,
@@ -5615,15 +9451,49 @@ pub mod system {
                         ::core::option::Option::Some(__value) => __value,
                         ::core::option::Option::None => ::core::option::Option::None,
                     };
+                    let __field3 = match __field3 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field4 = match __field4 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field5 = match __field5 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field6 = match __field6 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
+                    let __field7 = match __field7 {
+                        ::core::option::Option::Some(__value) => __value,
+                        ::core::option::Option::None => ::core::option::Option::None,
+                    };
                     ::core::result::Result::Ok(BlockSlotConfig {
                         device: __field0,
                         partition: __field1,
-                        immutable: __field2,
+                        disk: __field2,
+                        label: __field3,
+                        uuid: __field4,
+                        partlabel: __field5,
+                        partuuid: __field6,
+                        immutable: __field7,
                     })
                 }
             }
             #[doc(hidden)]
-            const __FIELDS: &'static [&'static str] = &["device", "partition", "immutable"];
+            const __FIELDS: &'static [&'static str] = &[
+                "device",
+                "partition",
+                "disk",
+                "label",
+                "uuid",
+                "partlabel",
+                "partuuid",
+                "immutable",
+            ];
             __serde::Deserializer::deserialize_struct(
                 __deserializer,
                 "BlockSlotConfig",
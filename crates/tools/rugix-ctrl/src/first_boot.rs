@@ -0,0 +1,66 @@
+//! Execution of run-once first-boot scripts.
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use reportify::{bail, ResultExt};
+use tracing::info;
+
+use crate::system::SystemResult;
+
+/// Directory with the first-boot scripts installed by Rugix Bakery.
+pub const FIRST_BOOT_SCRIPTS_DIR: &str = "/usr/lib/rugix/first-boot";
+
+/// Directory with the completion markers and logs of the first-boot scripts.
+///
+/// The directory is part of the persistent state and is, thus, cleared by a factory
+/// reset, causing the scripts to run again.
+pub const FIRST_BOOT_STATE_DIR: &str = "/run/rugix/state/.rugix/first-boot";
+
+/// Run all first-boot scripts that have not been completed yet.
+///
+/// Scripts are executed in the order of their names. If a script fails, the remaining
+/// scripts are not executed and all pending scripts are retried on the next boot.
+pub fn run_first_boot_scripts() -> SystemResult<()> {
+    let scripts_dir = Path::new(FIRST_BOOT_SCRIPTS_DIR);
+    if !scripts_dir.is_dir() {
+        info!("no first-boot scripts found");
+        return Ok(());
+    }
+    let mut scripts = Vec::new();
+    for entry in fs::read_dir(scripts_dir).whatever("unable to read first-boot scripts")? {
+        let entry = entry.whatever("unable to read first-boot scripts")?;
+        if entry.path().is_file() {
+            scripts.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    scripts.sort();
+    let state_dir = Path::new(FIRST_BOOT_STATE_DIR);
+    fs::create_dir_all(state_dir).whatever("unable to create first-boot state directory")?;
+    for name in scripts {
+        let marker = state_dir.join(format!("{name}.done"));
+        if marker.exists() {
+            continue;
+        }
+        info!("running first-boot script {name:?}");
+        let log_file = File::create(state_dir.join(format!("{name}.log")))
+            .whatever("unable to create first-boot script log")?;
+        let status = Command::new(scripts_dir.join(&name))
+            .stdin(Stdio::null())
+            .stdout(
+                log_file
+                    .try_clone()
+                    .whatever("unable to create first-boot script log")?,
+            )
+            .stderr(log_file)
+            .status()
+            .whatever("unable to execute first-boot script")
+            .with_info(|_| format!("script: {name:?}"))?;
+        if !status.success() {
+            bail!("first-boot script {name:?} failed with {status}");
+        }
+        fs::write(&marker, "").whatever("unable to write first-boot completion marker")?;
+    }
+    Ok(())
+}
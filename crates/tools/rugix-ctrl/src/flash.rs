@@ -0,0 +1,109 @@
+//! Flashing of images to block devices.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use byte_calc::NumBytes;
+use reportify::{bail, ResultExt};
+use rugix_common::disk::blkdev::BlockDevice;
+use rugix_common::image_writer::ImageWriter;
+use rugix_common::maybe_compressed::MaybeCompressed;
+use tracing::{info, warn};
+
+use crate::system::SystemResult;
+
+/// Flash an image to a block device.
+///
+/// The image may be compressed with XZ. If `image` is `-`, the image is read from stdin.
+pub fn flash(
+    image: &Path,
+    device: &Path,
+    verify: bool,
+    sync_interval: Option<NumBytes>,
+    force: bool,
+) -> SystemResult<()> {
+    let device = BlockDevice::new(device).whatever("target is not a block device")?;
+    let device_size = NumBytes::new(device.size().whatever("unable to get device size")?);
+    if let Some(mount_point) = find_mount_point(&device)? {
+        if force {
+            warn!("{:?} is mounted at {mount_point:?}", device.path());
+        } else {
+            bail!(
+                "{:?} is mounted at {mount_point:?}, use --force to flash anyway",
+                device.path()
+            );
+        }
+    }
+    let (reader, image_size): (Box<dyn Read>, _) = if image == Path::new("-") {
+        (Box::new(io::stdin()), None)
+    } else {
+        let file = File::open(image).whatever("unable to open image")?;
+        let size = file.metadata().whatever("unable to get image size")?.len();
+        (Box::new(file), Some(NumBytes::new(size)))
+    };
+    let mut reader = MaybeCompressed::new(reader).whatever("unable to read image")?;
+    // The size of the decompressed image is not known upfront.
+    let total = image_size.filter(|_| !reader.is_compressed());
+    if let Some(total) = total {
+        if total > device_size {
+            bail!(
+                "image ({total}) does not fit onto {:?} ({device_size})",
+                device.path()
+            );
+        }
+    }
+    let target = File::options()
+        .read(true)
+        .write(true)
+        .open(device.path())
+        .whatever("unable to open device")?;
+    info!("flashing image to {:?}", device.path());
+    let mut writer = ImageWriter::new(target)
+        .whatever("unable to create writer")?
+        .with_total(total)
+        .with_verification(verify)
+        .with_sync_interval(sync_interval)
+        .with_progress(|progress| match progress.percentage() {
+            Some(percentage) => info!(
+                "written {} ({percentage:.1}%, {}/s)",
+                progress.written,
+                progress.throughput()
+            ),
+            None => info!("written {} ({}/s)", progress.written, progress.throughput()),
+        });
+    io::copy(&mut reader, &mut writer).whatever("unable to flash image")?;
+    let summary = writer.finish().whatever("unable to flash image")?;
+    eprintln!(
+        "Flashed {} to {:?} in {:.1}s.",
+        summary.size,
+        device.path(),
+        summary.elapsed.as_secs_f64()
+    );
+    if let Some(hash) = summary.verified_hash {
+        eprintln!("Verified: {hash}");
+    }
+    Ok(())
+}
+
+/// Find a mount point of the device or any of its partitions.
+fn find_mount_point(device: &BlockDevice) -> SystemResult<Option<String>> {
+    let mounts = fs::read_to_string("/proc/self/mounts").whatever("unable to read mounts")?;
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(source), Some(mount_point)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if !source.starts_with("/dev/") {
+            continue;
+        }
+        let Ok(mounted) = BlockDevice::new(source) else {
+            continue;
+        };
+        let parent = mounted.find_parent().ok().flatten();
+        if &mounted == device || parent.as_ref() == Some(device) {
+            return Ok(Some(mount_point.to_owned()));
+        }
+    }
+    Ok(None)
+}
@@ -10,7 +10,9 @@ use rugix_common::disk::blkdev::BlockDevice;
 
 use tracing::{info, warn};
 
-use crate::config::bootstrapping::{BootstrappingConfig, DefaultLayoutConfig, SystemLayoutConfig};
+use crate::config::bootstrapping::{
+    BootstrappingConfig, DefaultLayoutConfig, Filesystem, PartitionLayoutConfig, SystemLayoutConfig,
+};
 use crate::config::state::{
     OverlayConfig, PersistConfig, PersistDirectoryConfig, PersistFileConfig, StateConfig,
 };
@@ -19,15 +21,20 @@ use crate::state::load_state_config;
 use crate::system::config::load_system_config;
 use crate::system::partitions::resolve_data_partition;
 use crate::system::paths::{MOUNT_POINT_CONFIG, MOUNT_POINT_DATA, MOUNT_POINT_SYSTEM};
-use crate::system::root::{find_system_device, SystemRoot};
+use crate::system::root::{find_system_device, SystemDisks, SystemRoot};
 use crate::system::{System, SystemError, SystemResult};
 use rugix_common::disk::blkpg::update_kernel_partitions;
 use rugix_common::disk::repart::{
-    generic_efi_partition_schema, generic_mbr_partition_schema, repart, PartitionSchema,
-    SchemaPartition,
+    self, generic_efi_partition_schema, generic_mbr_partition_schema, repart, DefaultLayout,
+    PartitionSchema, SchemaPartition,
+};
+use rugix_common::disk::{
+    mbr, DiskId, NumBlocks, PartitionTable, PartitionTableType, DEFAULT_BLOCK_SIZE,
+};
+use rugix_common::partitions::{
+    filesystem_type, luks_close, luks_format, luks_open, luks_resize, mkfs_btrfs, mkfs_ext4,
+    mkfs_f2fs, resize_ext4, resize_filesystem,
 };
-use rugix_common::disk::PartitionTable;
-use rugix_common::partitions::mkfs_ext4;
 use rugix_hooks::HooksLoader;
 use xscript::{run, Run, Vars};
 
@@ -62,6 +69,9 @@ const SYNC: &str = "/usr/bin/sync";
 
 const DEFAULT_STATE_DIR: &str = "/run/rugix/mounts/data/state/default";
 
+/// Device mapper name of the unlocked data partition.
+const DATA_MAPPER_NAME: &str = "rugix-data";
+
 fn init() -> SystemResult<()> {
     println!(include_str!("../assets/BANNER.txt"));
 
@@ -77,6 +87,7 @@ fn init() -> SystemResult<()> {
     let Some(root) = SystemRoot::from_system_device(&system_device) else {
         bail!("unable to determine system root");
     };
+    let disks = SystemDisks::from_config(system_config.disks.as_ref());
 
     let Some(config_partition) = (match system_config.config_partition {
         Some(partition) => {
@@ -107,7 +118,7 @@ fn init() -> SystemResult<()> {
         .join(".rugix/bootstrap")
         .exists()
     {
-        bootstrap(&root)?;
+        bootstrap(&root, &disks)?;
         run!([MOUNT, "-o", "remount,rw", MOUNT_POINT_CONFIG])
             .whatever("unable to mount config partition as read-write")?;
         std::fs::remove_file(Path::new(MOUNT_POINT_CONFIG).join(".rugix/bootstrap"))
@@ -117,29 +128,34 @@ fn init() -> SystemResult<()> {
         info!("Done bootstrapping")
     }
 
-    let Some(data_partition) = resolve_data_partition(
-        Some(&root),
-        system_config
-            .data_partition
-            .as_ref()
-            .unwrap_or(&PartitionConfig::new()),
-    ) else {
+    let data_partition_config = system_config
+        .data_partition
+        .clone()
+        .unwrap_or_else(PartitionConfig::new);
+    let Some(data_partition) = resolve_data_partition(Some(&root), &disks, &data_partition_config)
+    else {
         bail!("Rugix pre-init requires a data partition");
     };
 
-    // 3️⃣ Check and mount the data partition.
-    if let Err(error) = run!([FSCK, "-p", data_partition.path()]) {
+    // 3️⃣ Unlock, check, and mount the data partition.
+    let data_device = match &data_partition_config.encryption {
+        Some(encryption) => {
+            info!("Unlocking encrypted data partition.");
+            luks_open(
+                data_partition.path(),
+                DATA_MAPPER_NAME,
+                &encryption.key_file,
+            )
+            .whatever("unable to unlock data partition")?
+        }
+        None => data_partition.path().to_path_buf(),
+    };
+    if let Err(error) = run!([FSCK, "-p", &data_device]) {
         println!("fsck reported: {error}")
     }
     fs::create_dir_all(MOUNT_POINT_DATA).ok();
-    run!([
-        MOUNT,
-        "-o",
-        "noatime",
-        data_partition.path(),
-        MOUNT_POINT_DATA
-    ])
-    .whatever("unable to mount data partition")?;
+    run!([MOUNT, "-o", "noatime", &data_device, MOUNT_POINT_DATA])
+        .whatever("unable to mount data partition")?;
 
     let state_config = load_state_config()?;
 
@@ -196,13 +212,17 @@ pub fn state_dir() -> &'static Path {
     Path::new(STATE_DIR)
 }
 
-const BOOTSTRAP_CONFIG_PATH: &str = "/etc/rugix/bootstrapping.toml";
+pub const BOOTSTRAP_CONFIG_PATH: &str = "/etc/rugix/bootstrapping.toml";
 
-fn load_bootstrap_config() -> SystemResult<BootstrappingConfig> {
-    Ok(if Path::new(BOOTSTRAP_CONFIG_PATH).exists() {
+/// Device mapper name used for encrypted partitions during bootstrapping.
+const BOOTSTRAP_MAPPER_NAME: &str = "rugix-bootstrap";
+
+/// Loads the bootstrapping configuration, falling back to the defaults if it does not
+/// exist.
+pub fn load_bootstrap_config(path: &Path) -> SystemResult<BootstrappingConfig> {
+    Ok(if path.exists() {
         toml::from_str(
-            &fs::read_to_string(BOOTSTRAP_CONFIG_PATH)
-                .whatever("unable to read system configuration file")?,
+            &fs::read_to_string(path).whatever("unable to read system configuration file")?,
         )
         .whatever("unable to parse system configuration file")?
     } else {
@@ -210,7 +230,7 @@ fn load_bootstrap_config() -> SystemResult<BootstrappingConfig> {
     })
 }
 
-fn bootstrap(root: &SystemRoot) -> SystemResult<()> {
+fn bootstrap(root: &SystemRoot, disks: &SystemDisks) -> SystemResult<()> {
     let bootstrap_hooks = HooksLoader::default()
         .load_hooks("bootstrap")
         .whatever("unable to load bootstrap hooks")?;
@@ -219,7 +239,7 @@ fn bootstrap(root: &SystemRoot) -> SystemResult<()> {
         .run_hooks("prepare", Vars::new(), &Default::default())
         .whatever("unable to run `bootstrap/prepare` hooks")?;
 
-    let bootstrap_config = load_bootstrap_config()?;
+    let bootstrap_config = load_bootstrap_config(Path::new(BOOTSTRAP_CONFIG_PATH))?;
 
     if bootstrap_config.disabled.unwrap_or(false) {
         warn!("Found bootstrapping marker but bootstrapping is disabled. Skip bootstrapping");
@@ -227,75 +247,50 @@ fn bootstrap(root: &SystemRoot) -> SystemResult<()> {
     }
 
     info!("Found bootstrapping marker. Begin bootstrapping");
-    let layout = bootstrap_config.layout.unwrap_or_else(|| {
-        SystemLayoutConfig::Default(DefaultLayoutConfig::new(NumBytes::gibibytes(4)))
-    });
-
-    let ty = root.table.as_ref().unwrap().ty();
+    let layout = root_layout(&bootstrap_config);
 
-    let schema = match &layout {
-        SystemLayoutConfig::Mbr(partition_layout_config)
-        | SystemLayoutConfig::Gpt(partition_layout_config) => Some(PartitionSchema {
-            ty,
-            partitions: partition_layout_config
-                .partitions
-                .iter()
-                .map(|part| SchemaPartition {
-                    number: None,
-                    name: part.name.clone(),
-                    size: part.size.map(|s| s.raw.into()),
-                    ty: part.ty,
-                })
-                .collect(),
-        }),
-        SystemLayoutConfig::Default(default_layout_config) => match ty {
-            rugix_common::disk::PartitionTableType::Gpt => Some(generic_efi_partition_schema(
-                default_layout_config.system_size.raw.into(),
-            )),
-            rugix_common::disk::PartitionTableType::Mbr => Some(generic_mbr_partition_schema(
-                default_layout_config.system_size.raw.into(),
-            )),
-        },
-        SystemLayoutConfig::None => None,
+    let Some(table) = &root.table else {
+        bail!("unable to bootstrap root device without partition table");
     };
+    let ty = table.ty();
+
+    let schema = root_schema(&layout, ty);
 
     if let Some(schema) = schema {
         bootstrap_hooks
             .run_hooks("pre-layout", Vars::new(), &Default::default())
             .whatever("unable to run `bootstrap/pre-layout` hooks")?;
-        if let Some((old_table, _)) = bootstrap_partitions(&schema, root)? {
+        if let Some((old_table, new_table)) = bootstrap_partitions(&schema, root)? {
             // Partition is new, let's see whether we need to create a filesystem.
             match &layout {
                 SystemLayoutConfig::Mbr(partition_layout_config)
                 | SystemLayoutConfig::Gpt(partition_layout_config) => {
-                    for (idx, config) in partition_layout_config.partitions.iter().enumerate() {
-                        let Some(filesystem) = &config.filesystem else {
-                            continue;
-                        };
-                        if idx < old_table.partitions.len() {
-                            warn!(
-                                "refuse to create filesystems on already existing partition {}",
-                                idx + 1
-                            );
-                            continue;
-                        }
-                        let block_device = root.resolve_partition((idx + 1) as u32).unwrap();
-                        match filesystem {
-                            crate::config::bootstrapping::Filesystem::Ext4(ext4_filesystem) => {
-                                mkfs_ext4(
-                                    block_device,
-                                    ext4_filesystem.label.as_deref().unwrap_or(""),
-                                )
-                                .whatever("unable to create filesystem on data partition")?;
-                            }
-                        }
-                    }
+                    bootstrap_filesystems(root, partition_layout_config, &old_table, &new_table)?;
                 }
                 SystemLayoutConfig::Default(_) => {
-                    let data_partition_idx = if ty.is_mbr() { 7 } else { 6 };
-                    if data_partition_idx as usize >= old_table.partitions.len() {
+                    let number = if ty.is_mbr() { 7 } else { 6 };
+                    let Some(data_partition) = root.resolve_partition(number) else {
+                        bail!("unable to resolve data partition {number}: partition not found");
+                    };
+                    let find_partition = |table: &PartitionTable| {
+                        table
+                            .partitions
+                            .iter()
+                            .find(|partition| u32::from(partition.number) == number)
+                            .cloned()
+                    };
+                    if let Some(old_partition) = find_partition(&old_table) {
+                        // The data partition has been included in the image.
+                        let has_grown = find_partition(&new_table)
+                            .is_some_and(|new_partition| new_partition.size > old_partition.size);
+                        if has_grown {
+                            info!("growing filesystem on data partition");
+                            resize_ext4(data_partition.path())
+                                .whatever("unable to grow filesystem on data partition")?;
+                        }
+                    } else {
                         // Create Ext4 filesystem on data partition.
-                        mkfs_ext4(root.resolve_partition(data_partition_idx).unwrap(), "data")
+                        mkfs_ext4(data_partition, "data")
                             .whatever("unable to create filesystem on data partition")?;
                     }
                 }
@@ -307,6 +302,263 @@ fn bootstrap(root: &SystemRoot) -> SystemResult<()> {
             .whatever("unable to run `bootstrap/post-layout` hooks")?;
     }
 
+    for (name, layout) in bootstrap_config.disks.iter().flatten() {
+        info!("Bootstrapping disk {name:?}");
+        let disk = disks
+            .get(Some(root), Some(name))
+            .whatever_with(|_| format!("unable to bootstrap disk {name:?}"))?;
+        bootstrap_disk(name, disk, layout)?;
+    }
+
+    Ok(())
+}
+
+/// Creates or grows the filesystems of the partitions of a layout.
+fn bootstrap_filesystems(
+    disk: &SystemRoot,
+    partition_layout_config: &PartitionLayoutConfig,
+    old_table: &PartitionTable,
+    new_table: &PartitionTable,
+) -> SystemResult<()> {
+    for (idx, config) in partition_layout_config.partitions.iter().enumerate() {
+        let grow = config.grow.unwrap_or(false);
+        if config.filesystem.is_none() && !grow {
+            continue;
+        }
+        // Logical MBR partitions are not numbered by their index, hence, partitions of
+        // the old and new table are matched up by their number.
+        let Some(new_partition) = new_table.partitions.get(idx) else {
+            bail!(
+                "partition {} of the layout is missing in the partition table",
+                idx + 1
+            );
+        };
+        let number = new_partition.number;
+        let Some(block_device) = disk.resolve_partition(u32::from(number)) else {
+            bail!("unable to resolve partition {number}: partition not found");
+        };
+        let old_partition = old_table
+            .partitions
+            .iter()
+            .find(|partition| partition.number == number);
+        if let Some(old_partition) = old_partition {
+            let has_grown = new_partition.size > old_partition.size;
+            if !has_grown {
+                if !grow {
+                    warn!("refuse to create filesystems on already existing partition {number}");
+                }
+                continue;
+            }
+            // The partition has been grown, so we grow its filesystem.
+            info!("growing filesystem on partition {number}");
+            let device = match &config.encryption {
+                Some(encryption) => {
+                    let device =
+                        luks_open(block_device, BOOTSTRAP_MAPPER_NAME, &encryption.key_file)
+                            .whatever("unable to unlock partition")?;
+                    luks_resize(BOOTSTRAP_MAPPER_NAME, &encryption.key_file)
+                        .whatever("unable to grow encrypted partition")?;
+                    device
+                }
+                None => block_device.path().to_path_buf(),
+            };
+            // Without a configured filesystem, we detect it.
+            let filesystem_type = match &config.filesystem {
+                Some(Filesystem::Ext4(_)) => "ext4".to_owned(),
+                Some(Filesystem::F2fs(_)) => "f2fs".to_owned(),
+                Some(Filesystem::Btrfs(_)) => "btrfs".to_owned(),
+                None => {
+                    filesystem_type(&device).whatever("unable to determine filesystem to grow")?
+                }
+            };
+            resize_filesystem(&device, &filesystem_type).whatever("unable to grow filesystem")?;
+            if config.encryption.is_some() {
+                luks_close(BOOTSTRAP_MAPPER_NAME).whatever("unable to lock partition")?;
+            }
+            continue;
+        }
+        let Some(filesystem) = &config.filesystem else {
+            continue;
+        };
+        let device = match &config.encryption {
+            Some(encryption) => {
+                info!("encrypting partition {number}");
+                luks_format(&block_device, &encryption.key_file)
+                    .whatever("unable to encrypt partition")?;
+                luks_open(block_device, BOOTSTRAP_MAPPER_NAME, &encryption.key_file)
+                    .whatever("unable to unlock partition")?
+            }
+            None => block_device.path().to_path_buf(),
+        };
+        match filesystem {
+            Filesystem::Ext4(ext4_filesystem) => {
+                mkfs_ext4(&device, ext4_filesystem.label.as_deref().unwrap_or(""))
+                    .whatever("unable to create filesystem on data partition")?;
+            }
+            Filesystem::F2fs(f2fs_filesystem) => {
+                mkfs_f2fs(&device, f2fs_filesystem.label.as_deref().unwrap_or(""))
+                    .whatever("unable to create filesystem on data partition")?;
+            }
+            Filesystem::Btrfs(btrfs_filesystem) => {
+                mkfs_btrfs(&device, btrfs_filesystem.label.as_deref().unwrap_or(""))
+                    .whatever("unable to create filesystem on data partition")?;
+            }
+        }
+        if config.encryption.is_some() {
+            luks_close(BOOTSTRAP_MAPPER_NAME).whatever("unable to lock partition")?;
+        }
+    }
+    Ok(())
+}
+
+/// Layout of the root device, defaulting to the default layout.
+fn root_layout(config: &BootstrappingConfig) -> SystemLayoutConfig {
+    config.layout.clone().unwrap_or_else(|| {
+        SystemLayoutConfig::Default(DefaultLayoutConfig::new(NumBytes::gibibytes(4)))
+    })
+}
+
+/// Builds the partition schema of the root device for the given layout.
+///
+/// Returns `None` if no partitions should be created.
+fn root_schema(layout: &SystemLayoutConfig, ty: PartitionTableType) -> Option<PartitionSchema> {
+    match layout {
+        SystemLayoutConfig::Mbr(partition_layout_config)
+        | SystemLayoutConfig::Gpt(partition_layout_config) => {
+            Some(partition_schema(ty, partition_layout_config))
+        }
+        SystemLayoutConfig::Default(default_layout_config) => {
+            let default_layout = DefaultLayout {
+                config_size: default_layout_config.config_size.map(|s| s.raw.into()),
+                boot_size: default_layout_config.boot_size.map(|s| s.raw.into()),
+                system_size: default_layout_config.system_size.raw.into(),
+                alignment: default_layout_config.alignment.map(|s| s.raw.into()),
+            };
+            match ty {
+                PartitionTableType::Gpt => Some(generic_efi_partition_schema(&default_layout)),
+                PartitionTableType::Mbr => Some(generic_mbr_partition_schema(&default_layout)),
+            }
+        }
+        SystemLayoutConfig::None => None,
+    }
+}
+
+/// Checks the partition tables of the root device and additional disks against the
+/// layouts of the bootstrapping configuration.
+///
+/// All mismatches are printed. Returns whether all partition tables match.
+pub fn check_layout(
+    config: &BootstrappingConfig,
+    root: &SystemRoot,
+    disks: &SystemDisks,
+) -> SystemResult<bool> {
+    let mut is_match = true;
+    let Some(table) = &root.table else {
+        bail!(
+            "root device {:?} has no partition table",
+            root.device.path()
+        );
+    };
+    if let Some(schema) = root_schema(&root_layout(config), table.ty()) {
+        is_match &= check_disk_layout(root, table, &schema);
+    }
+    for (name, layout) in config.disks.iter().flatten() {
+        let (ty, partition_layout_config) = match layout {
+            SystemLayoutConfig::Mbr(partition_layout_config) => {
+                (PartitionTableType::Mbr, partition_layout_config)
+            }
+            SystemLayoutConfig::Gpt(partition_layout_config) => {
+                (PartitionTableType::Gpt, partition_layout_config)
+            }
+            SystemLayoutConfig::Default(_) | SystemLayoutConfig::None => continue,
+        };
+        let disk = disks
+            .get(Some(root), Some(name))
+            .whatever_with(|_| format!("unable to check disk {name:?}"))?;
+        let Some(table) = &disk.table else {
+            eprintln!("Disk {name:?} has no partition table.");
+            is_match = false;
+            continue;
+        };
+        is_match &= check_disk_layout(disk, table, &partition_schema(ty, partition_layout_config));
+    }
+    Ok(is_match)
+}
+
+/// Checks the partition table of a disk against a schema and prints all mismatches.
+fn check_disk_layout(disk: &SystemRoot, table: &PartitionTable, schema: &PartitionSchema) -> bool {
+    let mismatches = repart::check_layout(table, schema);
+    if mismatches.is_empty() {
+        eprintln!("Partition layout of {:?} matches.", disk.device.path());
+        return true;
+    }
+    eprintln!(
+        "Partition layout of {:?} does not match:",
+        disk.device.path()
+    );
+    for mismatch in mismatches {
+        eprintln!("  - {mismatch}");
+    }
+    false
+}
+
+/// Builds the partition schema for the given layout.
+fn partition_schema(
+    ty: PartitionTableType,
+    partition_layout_config: &PartitionLayoutConfig,
+) -> PartitionSchema {
+    PartitionSchema {
+        ty,
+        alignment: None,
+        partitions: partition_layout_config
+            .partitions
+            .iter()
+            .map(|part| SchemaPartition {
+                number: None,
+                name: part.name.clone(),
+                size: part.size.map(|s| s.raw.into()),
+                ty: part.ty,
+                attributes: part.attributes,
+                grow: part.grow.unwrap_or(false),
+            })
+            .collect(),
+    }
+}
+
+/// Bootstraps the layout of an additional disk.
+///
+/// If the disk has not been partitioned yet, an empty partition table is created first.
+fn bootstrap_disk(name: &str, disk: &SystemRoot, layout: &SystemLayoutConfig) -> SystemResult<()> {
+    let (ty, partition_layout_config) = match layout {
+        SystemLayoutConfig::Mbr(partition_layout_config) => {
+            (PartitionTableType::Mbr, partition_layout_config)
+        }
+        SystemLayoutConfig::Gpt(partition_layout_config) => {
+            (PartitionTableType::Gpt, partition_layout_config)
+        }
+        SystemLayoutConfig::Default(_) => {
+            bail!("default layout is not supported for disk {name:?}")
+        }
+        SystemLayoutConfig::None => return Ok(()),
+    };
+    if disk.table.is_none() {
+        info!("creating {ty} partition table on disk {name:?}");
+        let size = disk
+            .device
+            .size()
+            .whatever("unable to determine disk size")?;
+        let id = match ty {
+            PartitionTableType::Mbr => DiskId::random_mbr(),
+            PartitionTableType::Gpt => DiskId::random_gpt(),
+        };
+        PartitionTable::new(id, NumBlocks::from_raw(size / DEFAULT_BLOCK_SIZE))
+            .write(disk.device.path())
+            .whatever("unable to create partition table")?;
+    }
+    let schema = partition_schema(ty, partition_layout_config);
+    if let Some((old_table, new_table)) = bootstrap_partitions(&schema, disk)? {
+        bootstrap_filesystems(disk, partition_layout_config, &old_table, &new_table)?;
+    }
     Ok(())
 }
 
@@ -342,10 +594,21 @@ fn bootstrap_partitions(
     if let Some(new_table) =
         repart(&old_table, schema).whatever("unable to compute new partition table")?
     {
+        // Writing the partition table replaces a hybrid MBR with a protective MBR.
+        let hybrid_mbr = if old_table.is_gpt() {
+            mbr::read_hybrid_mbr(root.device.path()).whatever("unable to read hybrid MBR")?
+        } else {
+            Vec::new()
+        };
         // Write new partition table to disk.
         new_table
             .write(root.device.path())
             .whatever("unable to write new partition table")?;
+        if !hybrid_mbr.is_empty() {
+            info!("restoring hybrid MBR");
+            mbr::write_hybrid_mbr(root.device.path(), &new_table, &hybrid_mbr)
+                .whatever("unable to restore hybrid MBR")?;
+        }
         run!([SYNC]).whatever("unable to synchronize file systems")?;
         // Inform the kernel about new partitions.
         update_kernel_partitions(root.device.path(), &old_table, &new_table)
@@ -1,13 +1,17 @@
 pub mod boot;
 pub mod cli;
 pub mod config;
+pub mod first_boot;
+pub mod flash;
 pub mod http_source;
 pub mod init;
 pub mod overlay;
 pub mod slot_db;
 pub mod state;
+pub mod swupdate_progress;
 pub mod system;
 pub mod system_state;
+pub mod usage;
 pub mod utils;
 
 pub fn main() {
@@ -7,7 +7,7 @@ use std::path::{Path, PathBuf};
 use crate::system::SystemResult;
 use byte_calc::NumBytes;
 use hashbrown::{DefaultHashBuilder, HashTable};
-use reportify::{whatever, ResultExt};
+use reportify::{bail, whatever, ResultExt};
 use rugix_bundle::block_encoding::block_index::{compute_block_index, BlockIndexConfig};
 use rugix_bundle::format::decode::{Decode, Decoder};
 use rugix_bundle::format::{self, BlockIndex};
@@ -225,6 +225,14 @@ pub fn save_slot_state(slot: &str, state: &SlotState) -> SystemResult<()> {
     Ok(())
 }
 
+/// Path of the metadata with the given name stored for a slot, e.g., its SBOM.
+pub fn metadata_path(slot: &str, name: &str) -> SystemResult<PathBuf> {
+    if name.is_empty() || name.starts_with('.') || name.contains('/') {
+        bail!("invalid metadata name {name:?}");
+    }
+    Ok(db_dir().join(slot).join("metadata").join(name))
+}
+
 /// Directory with the slot database.
 pub fn db_dir() -> &'static Path {
     const DATA_PATH: &str = "/run/rugix/mounts/data/rugix/slots";
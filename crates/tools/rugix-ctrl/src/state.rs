@@ -48,4 +48,9 @@ fn merge(target: &mut StateConfig, other: StateConfig) {
     } else {
         target.persist = other.persist;
     }
+    if target.usage.is_none() {
+        target.usage = other.usage;
+    } else if other.usage.is_some() {
+        warn!("Conflicting usage options. Will use {:?}", target.usage);
+    }
 }
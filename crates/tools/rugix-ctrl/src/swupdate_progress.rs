@@ -0,0 +1,343 @@
+//! Progress interface compatible with SWUpdate's progress socket.
+//!
+//! SWUpdate reports the progress of an update to UIs connected to a Unix socket by
+//! sending a `struct progress_msg` whenever the progress changes. When configured, Rugix
+//! Ctrl provides the same interface while installing an update, such that UIs built for
+//! SWUpdate can display the progress of Rugix updates without modification.
+
+use std::io::Write;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fs, io, mem, thread};
+
+use reportify::{ErrorExt, ResultExt};
+use tracing::{debug, warn};
+
+use crate::config::system::SwupdateProgressConfig;
+use crate::system::SystemResult;
+
+/// Default path of the progress socket.
+const DEFAULT_SOCKET: &str = "/tmp/swupdateprog";
+
+/// Version of the progress API implemented by SWUpdate (2.0.0).
+const API_VERSION: u32 = 2 << 16;
+
+/// Size of the `cur_image` field of a message.
+const CUR_IMAGE_SIZE: usize = 256;
+
+/// Size of the `hnd_name` field of a message.
+const HND_NAME_SIZE: usize = 64;
+
+/// Size of the `info` field of a message.
+const INFO_SIZE: usize = 2048;
+
+/// Timeout for sending a message to a client.
+///
+/// Clients which do not read their messages must not stall the update.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Status of an update (`RECOVERY_STATUS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Start = 1,
+    Run = 2,
+    Success = 3,
+    Failure = 4,
+    Done = 6,
+}
+
+/// Interface that triggered an update (`sourcetype`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateSource {
+    /// Update downloaded from a URL.
+    Downloader = 3,
+    /// Update from a local file or the standard input.
+    Local = 4,
+}
+
+/// Progress message as sent by SWUpdate.
+#[derive(Debug, Clone)]
+struct ProgressMsg {
+    status: Status,
+    /// Percentage of the update which has been downloaded.
+    dwl_percent: u32,
+    /// Total size of the update in bytes, if it is downloaded.
+    dwl_bytes: u64,
+    nsteps: u32,
+    /// Current step, starting with `1`.
+    cur_step: u32,
+    /// Percentage of the current step which has been completed.
+    cur_percent: u32,
+    cur_image: String,
+    hnd_name: String,
+    source: UpdateSource,
+    info: String,
+}
+
+impl ProgressMsg {
+    /// Encode the message as `struct progress_msg` with the native layout.
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(&API_VERSION.to_ne_bytes());
+        buffer.extend_from_slice(&(self.status as u32).to_ne_bytes());
+        buffer.extend_from_slice(&self.dwl_percent.to_ne_bytes());
+        pad(&mut buffer, mem::align_of::<u64>());
+        buffer.extend_from_slice(&self.dwl_bytes.to_ne_bytes());
+        buffer.extend_from_slice(&self.nsteps.to_ne_bytes());
+        buffer.extend_from_slice(&self.cur_step.to_ne_bytes());
+        buffer.extend_from_slice(&self.cur_percent.to_ne_bytes());
+        put_str(&mut buffer, &self.cur_image, CUR_IMAGE_SIZE);
+        put_str(&mut buffer, &self.hnd_name, HND_NAME_SIZE);
+        buffer.extend_from_slice(&(self.source as u32).to_ne_bytes());
+        let info_len = self.info.len().min(INFO_SIZE - 1) as u32;
+        buffer.extend_from_slice(&info_len.to_ne_bytes());
+        put_str(&mut buffer, &self.info, INFO_SIZE);
+        pad(&mut buffer, mem::align_of::<u64>());
+        buffer
+    }
+}
+
+/// Append a null-terminated string to a fixed-size field, truncating it if necessary.
+fn put_str(buffer: &mut Vec<u8>, value: &str, size: usize) {
+    let bytes = &value.as_bytes()[..value.len().min(size - 1)];
+    buffer.extend_from_slice(bytes);
+    buffer.resize(buffer.len() + size - bytes.len(), 0);
+}
+
+/// Pad the buffer to the given alignment.
+fn pad(buffer: &mut Vec<u8>, align: usize) {
+    buffer.resize(buffer.len().next_multiple_of(align), 0);
+}
+
+/// State of the progress reporting.
+#[derive(Debug)]
+struct ProgressState {
+    msg: ProgressMsg,
+    /// Bytes of the update read when the current step started.
+    step_start: u64,
+    /// Size of the current step in bytes.
+    step_size: u64,
+}
+
+/// Server sending progress messages to the clients connected to the progress socket.
+#[derive(Debug)]
+pub struct ProgressServer {
+    path: PathBuf,
+    clients: Arc<Mutex<Vec<UnixStream>>>,
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl ProgressServer {
+    /// Start listening on the progress socket.
+    ///
+    /// Clients are accepted in the background for the remaining lifetime of the process.
+    /// Upon connecting, clients receive the current progress.
+    pub fn start(config: &SwupdateProgressConfig, source: UpdateSource) -> SystemResult<Self> {
+        let path = PathBuf::from(config.socket.as_deref().unwrap_or(DEFAULT_SOCKET));
+        // Remove a stale socket, e.g., of a previous update that has been interrupted.
+        if let Err(error) = fs::remove_file(&path) {
+            if error.kind() != io::ErrorKind::NotFound {
+                return Err(error.whatever("unable to remove stale progress socket"));
+            }
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).whatever("unable to create progress socket directory")?;
+        }
+        let listener = UnixListener::bind(&path)
+            .whatever_with(|_| format!("unable to bind progress socket {path:?}"))?;
+        let state = ProgressState {
+            msg: ProgressMsg {
+                status: Status::Start,
+                dwl_percent: 0,
+                dwl_bytes: 0,
+                nsteps: 0,
+                cur_step: 0,
+                cur_percent: 0,
+                cur_image: String::new(),
+                hnd_name: String::new(),
+                source,
+                info: String::new(),
+            },
+            step_start: 0,
+            step_size: 0,
+        };
+        let server = Self {
+            path,
+            clients: Arc::new(Mutex::new(Vec::new())),
+            state: Arc::new(Mutex::new(state)),
+        };
+        let clients = server.clients.clone();
+        let state = server.state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!("unable to accept progress client: {error}");
+                        continue;
+                    }
+                };
+                debug!("progress client connected");
+                if let Err(error) = stream.set_write_timeout(Some(WRITE_TIMEOUT)) {
+                    warn!("unable to set write timeout of progress client: {error}");
+                    continue;
+                }
+                // Clients connecting during an update receive the current progress.
+                let current = state.lock().unwrap();
+                if stream.write_all(&current.msg.encode()).is_ok() {
+                    clients.lock().unwrap().push(stream);
+                }
+            }
+        });
+        Ok(server)
+    }
+
+    /// Set the number of steps of the update.
+    pub fn set_steps(&self, nsteps: usize, bytes_total: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        state.msg.status = Status::Run;
+        state.msg.nsteps = nsteps as u32;
+        state.msg.dwl_bytes = bytes_total.unwrap_or_default();
+        self.send(&state.msg);
+    }
+
+    /// Start the next step installing the given image with the given handler.
+    ///
+    /// The size of the step is used to compute the progress of the step. The previous
+    /// step, if any, is reported as completed.
+    pub fn start_step(&self, image: &str, handler: &str, bytes_read: Option<u64>, size: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.msg.cur_step > 0 && state.msg.cur_percent != 100 {
+            state.msg.cur_percent = 100;
+            self.send(&state.msg);
+        }
+        state.msg.status = Status::Run;
+        state.msg.cur_step += 1;
+        state.msg.cur_percent = 0;
+        state.msg.cur_image = image.to_owned();
+        state.msg.hnd_name = handler.to_owned();
+        state.step_start = bytes_read.unwrap_or_default();
+        state.step_size = size;
+        self.send(&state.msg);
+    }
+
+    /// Update the progress based on the bytes of the update read so far.
+    ///
+    /// Messages are only sent if the percentages change.
+    pub fn update(&self, bytes_read: Option<u64>, bytes_total: Option<u64>) {
+        let Some(bytes_read) = bytes_read else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        let cur_percent = percent(bytes_read.saturating_sub(state.step_start), state.step_size);
+        let dwl_percent = bytes_total.map_or(0, |total| percent(bytes_read, total));
+        if cur_percent != state.msg.cur_percent || dwl_percent != state.msg.dwl_percent {
+            state.msg.cur_percent = cur_percent;
+            state.msg.dwl_percent = dwl_percent;
+            self.send(&state.msg);
+        }
+    }
+
+    /// Report the result of the update.
+    pub fn finish(&self, result: Result<(), String>) {
+        let mut state = self.state.lock().unwrap();
+        match result {
+            Ok(()) => {
+                state.msg.status = Status::Success;
+                state.msg.cur_percent = 100;
+                if state.msg.dwl_bytes > 0 {
+                    state.msg.dwl_percent = 100;
+                }
+            }
+            Err(info) => {
+                state.msg.status = Status::Failure;
+                state.msg.info = info;
+            }
+        }
+        self.send(&state.msg);
+        state.msg.status = Status::Done;
+        self.send(&state.msg);
+    }
+
+    /// Send a message to all clients, dropping clients which cannot receive it.
+    fn send(&self, msg: &ProgressMsg) {
+        let encoded = msg.encode();
+        self.clients.lock().unwrap().retain_mut(|client| {
+            if let Err(error) = client.write_all(&encoded) {
+                debug!("dropping progress client: {error}");
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl Drop for ProgressServer {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Compute the percentage of `value` relative to `total`.
+fn percent(value: u64, total: u64) -> u32 {
+    if total == 0 {
+        return 0;
+    }
+    (value.min(total) * 100 / total) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_layout() {
+        let msg = ProgressMsg {
+            status: Status::Run,
+            dwl_percent: 42,
+            dwl_bytes: 1 << 32,
+            nsteps: 3,
+            cur_step: 2,
+            cur_percent: 50,
+            cur_image: "system".to_owned(),
+            hnd_name: "block".to_owned(),
+            source: UpdateSource::Local,
+            info: "x".repeat(INFO_SIZE + 10),
+        };
+        let encoded = msg.encode();
+        let dwl_bytes_offset = 12usize.next_multiple_of(mem::align_of::<u64>());
+        let source_offset = dwl_bytes_offset + 20 + CUR_IMAGE_SIZE + HND_NAME_SIZE;
+        let size = (source_offset + 8 + INFO_SIZE).next_multiple_of(mem::align_of::<u64>());
+        assert_eq!(encoded.len(), size);
+        let read_u32 =
+            |offset: usize| u32::from_ne_bytes(encoded[offset..offset + 4].try_into().unwrap());
+        assert_eq!(read_u32(0), 0x0002_0000);
+        assert_eq!(read_u32(4), 2);
+        assert_eq!(read_u32(8), 42);
+        assert_eq!(
+            u64::from_ne_bytes(
+                encoded[dwl_bytes_offset..dwl_bytes_offset + 8]
+                    .try_into()
+                    .unwrap()
+            ),
+            1 << 32
+        );
+        assert_eq!(read_u32(dwl_bytes_offset + 12), 2);
+        let image_offset = dwl_bytes_offset + 20;
+        assert_eq!(&encoded[image_offset..image_offset + 7], b"system\0");
+        assert_eq!(read_u32(source_offset), 4);
+        assert_eq!(read_u32(source_offset + 4), INFO_SIZE as u32 - 1);
+        // The info must be null-terminated.
+        assert_eq!(encoded[source_offset + 8 + INFO_SIZE - 1], 0);
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(0, 0), 0);
+        assert_eq!(percent(50, 200), 25);
+        assert_eq!(percent(300, 200), 100);
+    }
+}
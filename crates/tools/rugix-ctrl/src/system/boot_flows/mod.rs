@@ -416,7 +416,7 @@ fn tryboot_uboot_post_install(
         bail!("unknown entry");
     };
     let boot_slot = &system.slots()[boot_slot];
-    let _system_slot = &system.slots()[system_slot];
+    let system_slot = &system.slots()[system_slot];
     let SlotKind::Block(boot_raw) = boot_slot.kind() else {
         bail!("boot slot must be of type `block`")
     };
@@ -428,19 +428,31 @@ fn tryboot_uboot_post_install(
     let Some(table) = &root.table else {
         bail!("no partition table");
     };
+    let SlotKind::Block(system_raw) = system_slot.kind() else {
+        bail!("system slot must be of type `block`")
+    };
+    let Some(number) = system_raw
+        .device()
+        .is_partition()
+        .whatever("unable to determine partition number of system slot")?
+    else {
+        bail!("system slot must be a partition of the root device");
+    };
     let root = if table.is_mbr() {
         let disk_id = get_disk_id(&root.device).whatever("unable to get root device disk id")?;
-        if entry == inner.entry_a {
-            format!("PARTUUID={disk_id}-05")
-        } else {
-            format!("PARTUUID={disk_id}-06")
-        }
+        format!("PARTUUID={disk_id}-{number:02x}")
     } else {
         let table =
             PartitionTable::read(&root.device).whatever("unable to read partition table")?;
-        // Use partitions 4 (index 3) and 5 (index 4).
-        let partition = &table.partitions[if entry == inner.entry_a { 3 } else { 4 }];
-        format!("PARTUUID={}", partition.gpt_id.unwrap())
+        let Some(gpt_id) = table
+            .partitions
+            .iter()
+            .find(|partition| u32::from(partition.number) == number)
+            .and_then(|partition| partition.gpt_id)
+        else {
+            bail!("unable to determine partition UUID of system slot");
+        };
+        format!("PARTUUID={gpt_id}")
     };
     rpi_patch_boot(temp_dir_spare, root).whatever("unable to patch boot partition")?;
     Ok(())
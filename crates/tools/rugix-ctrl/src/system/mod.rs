@@ -3,7 +3,7 @@ use boot_groups::{BootGroup, BootGroupIdx, BootGroups};
 use config::load_system_config;
 use partitions::ConfigPartition;
 use reportify::{bail, whatever, Report, ResultExt};
-use root::{find_system_device, SystemRoot};
+use root::{find_system_device, SystemDisks, SystemRoot};
 use slots::{SlotKind, SystemSlots};
 use tracing::warn;
 
@@ -29,6 +29,7 @@ pub struct System {
     pub config: SystemConfig,
     pub device: Option<BlockDevice>,
     pub root: Option<SystemRoot>,
+    pub disks: SystemDisks,
 
     slots: SystemSlots,
     boot_entries: BootGroups,
@@ -54,7 +55,9 @@ impl System {
         let Some(config_partition) = config_partition else {
             bail!("config partition cannot currently be disabled");
         };
-        let slots = SystemSlots::from_config(system_root.as_ref(), system_config.slots.as_ref())?;
+        let disks = SystemDisks::from_config(system_config.disks.as_ref());
+        let slots =
+            SystemSlots::from_config(system_root.as_ref(), &disks, system_config.slots.as_ref())?;
         let boot_entries = BootGroups::from_config(&slots, system_config.boot_groups.as_ref())?;
         // Mark boot entries and slots active.
         let mut active_boot_entry = None;
@@ -107,6 +110,7 @@ impl System {
             config: system_config,
             device: system_device,
             root: system_root,
+            disks,
             slots,
             boot_entries,
             active_boot_entry,
@@ -123,6 +127,22 @@ impl System {
         &self.config
     }
 
+    /// Variant of the system used to select the payloads of update bundles.
+    pub fn variant(&self) -> Option<&str> {
+        if let Some(variant) = &self.config.variant {
+            return Some(variant);
+        }
+        // Rugix Ctrl for all 32-bit ARM architectures is built for `armhf`, hence, we
+        // cannot tell them apart.
+        if cfg!(target_arch = "x86_64") {
+            Some("amd64")
+        } else if cfg!(target_arch = "aarch64") {
+            Some("arm64")
+        } else {
+            None
+        }
+    }
+
     pub fn slots(&self) -> &SystemSlots {
         &self.slots
     }
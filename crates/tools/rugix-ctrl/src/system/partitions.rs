@@ -9,16 +9,18 @@ use xscript::{run, Run};
 
 use crate::config::system::PartitionConfig;
 
-use super::root::SystemRoot;
+use super::root::{SystemDisks, SystemRoot};
 use super::{paths, SystemResult};
 use rugix_common::disk::blkdev::BlockDevice;
+use rugix_common::partitions::{find_filesystem, find_partition};
 
 /// Resolve the data partition block device.
 pub fn resolve_data_partition(
     root: Option<&SystemRoot>,
+    disks: &SystemDisks,
     config: &PartitionConfig,
 ) -> Option<BlockDevice> {
-    resolve_partition(root, config, || {
+    resolve_partition(root, disks, config, || {
         match root.and_then(|root| root.table.as_ref()) {
             Some(table) => Ok(if table.is_mbr() { 7 } else { 6 }),
             None => {
@@ -34,9 +36,10 @@ pub fn resolve_data_partition(
 /// Resolve the config partition block device.
 pub fn resolve_config_partition(
     root: Option<&SystemRoot>,
+    disks: &SystemDisks,
     config: &PartitionConfig,
 ) -> Option<BlockDevice> {
-    resolve_partition(root, config, || Ok(1))
+    resolve_partition(root, disks, config, || Ok(1))
         .inspect_err(|error| error!("error resolving config partition: {error:?}"))
         .ok()
         .flatten()
@@ -45,6 +48,7 @@ pub fn resolve_config_partition(
 /// Resolve a partition block device based on the given config and default.
 fn resolve_partition(
     root: Option<&SystemRoot>,
+    disks: &SystemDisks,
     config: &PartitionConfig,
     default: impl FnOnce() -> SystemResult<u32>,
 ) -> SystemResult<Option<BlockDevice>> {
@@ -58,23 +62,123 @@ fn resolve_partition(
         BlockDevice::new(device)
             .whatever("partition is not a block device")
             .with_info(|_| format!("device: {device:?}"))?
+    } else if config.partlabel.is_some() || config.partuuid.is_some() {
+        if config.partition.is_some() {
+            warn!("ignoring `partition` because `partlabel` or `partuuid` is set");
+        }
+        match find_partition_device(
+            root,
+            config.partlabel.as_deref(),
+            config.partuuid.as_deref(),
+        )? {
+            Some(device) => device,
+            None => bail!("unable to resolve partition: partition not found"),
+        }
+    } else if config.label.is_some() || config.uuid.is_some() {
+        if config.partition.is_some() {
+            warn!("ignoring `partition` because `label` or `uuid` is set");
+        }
+        match find_filesystem_device(root, config.label.as_deref(), config.uuid.as_deref())? {
+            Some(device) => device,
+            None => bail!("unable to resolve partition: filesystem not found"),
+        }
     } else {
         let partition = match config.partition {
             Some(partition) => partition,
             None => default()?,
         };
-        if let Some(root) = root {
-            match root.resolve_partition(partition) {
-                Some(device) => device,
-                None => bail!("unable to resolve partition {partition}: partition not found"),
-            }
-        } else {
-            bail!("unable to resolve partition {partition}: no root device")
+        let disk = disks
+            .get(root, config.disk.as_deref())
+            .whatever_with(|_| format!("unable to resolve partition {partition}"))?;
+        match disk.resolve_partition(partition) {
+            Some(device) => device,
+            None => bail!("unable to resolve partition {partition}: partition not found"),
         }
     };
     Ok(Some(device))
 }
 
+/// Find the block device with a filesystem with the given label or UUID.
+///
+/// If both are given, the UUID takes precedence. See [`select_device`] for how multiple
+/// matching devices are handled.
+pub fn find_filesystem_device(
+    root: Option<&SystemRoot>,
+    label: Option<&str>,
+    uuid: Option<&str>,
+) -> SystemResult<Option<BlockDevice>> {
+    let (tag, value) = match (label, uuid) {
+        (_, Some(uuid)) => ("UUID", uuid),
+        (Some(label), None) => ("LABEL", label),
+        (None, None) => return Ok(None),
+    };
+    let mut devices = Vec::new();
+    for path in find_filesystem(tag, value) {
+        devices.push(
+            BlockDevice::new(&path)
+                .whatever("filesystem is not on a block device")
+                .with_info(|_| format!("device: {path:?}"))?,
+        );
+    }
+    select_device(root, tag, value, devices)
+}
+
+/// Find the partition block device with the given GPT partition label or UUID.
+///
+/// If both are given, the UUID takes precedence. See [`select_device`] for how multiple
+/// matching devices are handled.
+pub fn find_partition_device(
+    root: Option<&SystemRoot>,
+    partlabel: Option<&str>,
+    partuuid: Option<&str>,
+) -> SystemResult<Option<BlockDevice>> {
+    let (tag, value) = match (partlabel, partuuid) {
+        (_, Some(partuuid)) => ("PARTUUID", partuuid),
+        (Some(partlabel), None) => ("PARTLABEL", partlabel),
+        (None, None) => return Ok(None),
+    };
+    let mut devices = Vec::new();
+    for path in find_partition(tag, value) {
+        devices.push(
+            BlockDevice::new(&path)
+                .whatever("partition is not a block device")
+                .with_info(|_| format!("device: {path:?}"))?,
+        );
+    }
+    select_device(root, tag, value, devices)
+}
+
+/// Select the device with the given tag among the matching devices.
+///
+/// Tags are not necessarily unique, e.g., when a USB stick with the same image is
+/// attached. If multiple devices match, only devices on the root device are considered.
+/// If this does not yield a unique device, an error is returned instead of picking an
+/// arbitrary device, which may belong to a different disk.
+fn select_device(
+    root: Option<&SystemRoot>,
+    tag: &str,
+    value: &str,
+    mut devices: Vec<BlockDevice>,
+) -> SystemResult<Option<BlockDevice>> {
+    if devices.len() <= 1 {
+        return Ok(devices.pop());
+    }
+    if let Some(root) = root {
+        devices.retain(|device| {
+            device == &root.device
+                || device
+                    .find_parent()
+                    .ok()
+                    .flatten()
+                    .is_some_and(|parent| parent == root.device)
+        });
+        if devices.len() == 1 {
+            return Ok(devices.pop());
+        }
+    }
+    bail!("multiple devices with {tag}={value:?}, unable to select a unique device");
+}
+
 /// Config partition of the system.
 #[derive(Debug)]
 pub struct ConfigPartition {
@@ -2,19 +2,30 @@
 
 use std::path::Path;
 
-use tracing::error;
+use indexmap::IndexMap;
+use reportify::{bail, ResultExt};
+use tracing::{error, warn};
 
-use super::paths;
-use rugix_common::disk::blkdev::{find_block_device, BlockDevice};
-use rugix_common::disk::PartitionTable;
+use super::{paths, SystemResult};
+use crate::config::system::DiskConfig;
+use rugix_common::disk::blkdev::{find_block_device, list_disks, BlockDevice};
+use rugix_common::disk::{DiskId, PartitionTable};
 
 /// Find the system block device.
+///
+/// If the system is mounted from a device-mapper device, e.g., for an encrypted or
+/// dm-verity protected system partition, the underlying device is returned.
 pub fn find_system_device() -> Option<BlockDevice> {
     find_block_device(if Path::new(paths::MOUNT_POINT_SYSTEM).exists() {
         paths::MOUNT_POINT_SYSTEM
     } else {
         "/"
     })
+    .and_then(|device| {
+        device
+            .map(|device| device.resolve_device_mapper())
+            .transpose()
+    })
     .inspect_err(|error| error!("error determining system block device: {error}"))
     .ok()
     .flatten()
@@ -50,6 +61,18 @@ impl SystemRoot {
             })
     }
 
+    /// Obtain a disk from the provided block device.
+    ///
+    /// The partition table is `None`, if the disk has not been partitioned yet. Errors
+    /// reading an existing partition table are returned such that the disk is never
+    /// mistaken for an unpartitioned disk.
+    pub fn from_device(device: BlockDevice) -> SystemResult<Self> {
+        let table = PartitionTable::read_optional(&device)
+            .whatever("unable to read partition table")
+            .with_info(|_| format!("device: {:?}", device.path()))?;
+        Ok(SystemRoot { device, table })
+    }
+
     /// Resolve a partition.
     pub fn resolve_partition(&self, partition: u32) -> Option<BlockDevice> {
         self.device
@@ -61,3 +84,92 @@ impl SystemRoot {
             .flatten()
     }
 }
+
+/// Additional disks of the system.
+#[derive(Debug, Clone, Default)]
+pub struct SystemDisks {
+    /// Disks by their name, `None` if the disk could not be found.
+    disks: IndexMap<String, Option<SystemRoot>>,
+}
+
+impl SystemDisks {
+    /// Resolve the disks of the given configuration.
+    ///
+    /// Errors are logged and the respective disks are marked as not found.
+    pub fn from_config(config: Option<&IndexMap<String, DiskConfig>>) -> Self {
+        let mut disks = IndexMap::new();
+        for (name, config) in config.into_iter().flatten() {
+            let disk = resolve_disk(config)
+                .inspect_err(|error| error!("error resolving disk {name:?}: {error:?}"))
+                .ok()
+                .flatten();
+            disks.insert(name.clone(), disk);
+        }
+        Self { disks }
+    }
+
+    /// Get the disk with the given name or the root device, if no name is given.
+    pub fn get<'d>(
+        &'d self,
+        root: Option<&'d SystemRoot>,
+        disk: Option<&str>,
+    ) -> SystemResult<&'d SystemRoot> {
+        match disk {
+            None => match root {
+                Some(root) => Ok(root),
+                None => bail!("no system root"),
+            },
+            Some(name) => match self.disks.get(name) {
+                Some(Some(disk)) => Ok(disk),
+                Some(None) => bail!("disk {name:?} not found"),
+                None => bail!("disk {name:?} is not configured"),
+            },
+        }
+    }
+
+    /// Iterator over the disks.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&SystemRoot>)> {
+        self.disks
+            .iter()
+            .map(|(name, disk)| (name.as_str(), disk.as_ref()))
+    }
+}
+
+/// Resolve a disk based on the given config.
+fn resolve_disk(config: &DiskConfig) -> SystemResult<Option<SystemRoot>> {
+    if let Some(device) = &config.device {
+        if config.id.is_some() {
+            warn!("ignoring `id` because `device` is set");
+        }
+        let device = BlockDevice::new(device)
+            .whatever("disk is not a block device")
+            .with_info(|_| format!("device: {device:?}"))?;
+        return SystemRoot::from_device(device).map(Some);
+    }
+    let Some(id) = &config.id else {
+        bail!("invalid configuration: no device or id for disk");
+    };
+    for device in list_disks().whatever("unable to list disks")? {
+        let Ok(table) = PartitionTable::read(&device) else {
+            continue;
+        };
+        if disk_id_matches(table.disk_id, id) {
+            return Ok(Some(SystemRoot {
+                device,
+                table: Some(table),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Check whether the disk ID matches the configured identifier.
+///
+/// MBR disk identifiers may be given with or without `0x` prefix.
+fn disk_id_matches(disk_id: DiskId, id: &str) -> bool {
+    let id = id.trim_start_matches("0x");
+    disk_id
+        .to_string()
+        .trim_start_matches("0x")
+        .eq_ignore_ascii_case(id)
+}
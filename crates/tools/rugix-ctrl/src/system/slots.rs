@@ -1,13 +1,16 @@
+use std::fs::File;
 use std::ops::Index;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
 use indexmap::IndexMap;
 use reportify::{bail, ResultExt};
+use tracing::error;
 
 use crate::config::system::{BlockSlotConfig, SlotConfig};
 
-use super::root::SystemRoot;
+use super::partitions::{find_filesystem_device, find_partition_device};
+use super::root::{SystemDisks, SystemRoot};
 use super::SystemResult;
 use rugix_common::disk::blkdev::BlockDevice;
 
@@ -25,7 +28,11 @@ pub struct SystemSlots {
 }
 
 impl SystemSlots {
-    fn from_iter<'i, I>(root: Option<&SystemRoot>, iter: I) -> SystemResult<Self>
+    fn from_iter<'i, I>(
+        root: Option<&SystemRoot>,
+        disks: &SystemDisks,
+        iter: I,
+    ) -> SystemResult<Self>
     where
         I: Iterator<Item = (&'i str, &'i SlotConfig)>,
     {
@@ -37,16 +44,41 @@ impl SystemSlots {
                         BlockDevice::new(device)
                             .whatever("slot device is not a block device")
                             .with_info(|_| format!("device: {device:?}"))?
-                    } else if let Some(partition) = &block_slot_config.partition {
-                        let Some(root) = root else {
-                            bail!("no system root")
+                    } else if block_slot_config.partlabel.is_some()
+                        || block_slot_config.partuuid.is_some()
+                    {
+                        let Some(device) = find_partition_device(
+                            root,
+                            block_slot_config.partlabel.as_deref(),
+                            block_slot_config.partuuid.as_deref(),
+                        )?
+                        else {
+                            bail!("partition for slot {name:?} not found");
                         };
-                        let Some(device) = root.resolve_partition(*partition) else {
+                        device
+                    } else if block_slot_config.label.is_some() || block_slot_config.uuid.is_some()
+                    {
+                        let Some(device) = find_filesystem_device(
+                            root,
+                            block_slot_config.label.as_deref(),
+                            block_slot_config.uuid.as_deref(),
+                        )?
+                        else {
+                            bail!("filesystem for slot {name:?} not found");
+                        };
+                        device
+                    } else if let Some(partition) = &block_slot_config.partition {
+                        let disk = disks
+                            .get(root, block_slot_config.disk.as_deref())
+                            .whatever_with(|_| format!("unable to resolve slot {name:?}"))?;
+                        let Some(device) = disk.resolve_partition(*partition) else {
                             bail!("partition {partition} for slot {name:?} not found");
                         };
                         device
                     } else {
-                        bail!("invalid configuration: no device and partition for {name}");
+                        bail!(
+                            "invalid configuration: no device, filesystem, or partition for {name}"
+                        );
                     };
                     SlotKind::Block(BlockSlot { device })
                 }
@@ -64,11 +96,13 @@ impl SystemSlots {
 
     pub fn from_config(
         root: Option<&SystemRoot>,
+        disks: &SystemDisks,
         config: Option<&IndexMap<String, SlotConfig>>,
     ) -> SystemResult<Self> {
         match config {
             Some(config) => Self::from_iter(
                 root,
+                disks,
                 config.iter().map(|(name, config)| (name.as_str(), config)),
             ),
             None => {
@@ -85,6 +119,7 @@ impl SystemSlots {
                 };
                 Self::from_iter(
                     Some(root),
+                    disks,
                     default_slots.iter().map(|(name, config)| (*name, config)),
                 )
             }
@@ -189,6 +224,59 @@ impl BlockSlot {
     pub fn device(&self) -> &BlockDevice {
         &self.device
     }
+
+    /// Make the device of the slot writable while the returned guard is alive.
+    ///
+    /// eMMC boot partitions are read-only by default. When the guard is dropped, they
+    /// are made read-only again, if they have been read-only before.
+    pub fn make_writable(&self) -> SystemResult<WritableGuard<'_>> {
+        let mut restore_read_only = false;
+        if self.device.is_emmc_boot_partition() {
+            restore_read_only = self
+                .device
+                .is_force_read_only()
+                .whatever("unable to determine whether eMMC boot partition is read-only")?;
+            if restore_read_only {
+                self.device
+                    .set_force_read_only(false)
+                    .whatever("unable to make eMMC boot partition writable")?;
+            }
+        }
+        Ok(WritableGuard {
+            device: &self.device,
+            restore_read_only,
+        })
+    }
+
+    /// Open the device of the slot for writing.
+    ///
+    /// The device must be made writable with [`BlockSlot::make_writable`] first.
+    pub fn open_writable(&self) -> SystemResult<File> {
+        File::options()
+            .read(true)
+            .write(true)
+            .open(&self.device)
+            .whatever("unable to open slot device")
+    }
+}
+
+/// Guard keeping the device of a block slot writable.
+#[derive(Debug)]
+#[must_use]
+pub struct WritableGuard<'s> {
+    device: &'s BlockDevice,
+    /// Indicates whether the device must be made read-only again.
+    restore_read_only: bool,
+}
+
+impl Drop for WritableGuard<'_> {
+    fn drop(&mut self) {
+        if self.restore_read_only {
+            if let Err(error) = self.device.set_force_read_only(true) {
+                error!("unable to make eMMC boot partition read-only again: {error}");
+            }
+        }
+    }
 }
 
 /// Default slots of an MBR-partitioned root device.
@@ -212,6 +300,11 @@ const fn default_slot_config(partition: u32, immutable: bool) -> SlotConfig {
     SlotConfig::Block(BlockSlotConfig {
         device: None,
         partition: Some(partition),
+        disk: None,
+        label: None,
+        uuid: None,
+        partlabel: None,
+        partuuid: None,
         immutable: Some(immutable),
     })
 }
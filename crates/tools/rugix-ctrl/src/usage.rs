@@ -0,0 +1,208 @@
+//! Monitoring of the overlay and data partition usage.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::{env, fs, thread};
+
+use byte_calc::NumBytes;
+use reportify::ResultExt;
+use tracing::error;
+
+use crate::config::state::{OverlayConfig, StateConfig, UsageConfig};
+use crate::system::paths::MOUNT_POINT_DATA;
+use crate::system::SystemResult;
+
+/// Default usage in percent above which a warning is emitted.
+const DEFAULT_WARNING_THRESHOLD: u32 = 80;
+
+/// Default usage in percent above which an error is emitted.
+const DEFAULT_CRITICAL_THRESHOLD: u32 = 95;
+
+/// Default interval in seconds between the checks of the monitor.
+const DEFAULT_INTERVAL: u32 = 60;
+
+/// Level of the usage of a filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsageLevel {
+    /// Usage is below the warning threshold.
+    Ok,
+    /// Usage is above the warning threshold.
+    Warning,
+    /// Usage is above the critical threshold.
+    Critical,
+}
+
+impl UsageLevel {
+    /// Name of the level.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UsageLevel::Ok => "ok",
+            UsageLevel::Warning => "warning",
+            UsageLevel::Critical => "critical",
+        }
+    }
+
+    /// Syslog priority of the level.
+    fn priority(self) -> u8 {
+        match self {
+            UsageLevel::Ok => 6,
+            UsageLevel::Warning => 4,
+            UsageLevel::Critical => 3,
+        }
+    }
+}
+
+/// Usage of a monitored filesystem.
+#[derive(Debug, Clone)]
+pub struct Usage {
+    /// Name of the filesystem, i.e., `overlay` or `data`.
+    pub name: &'static str,
+    /// Path where the filesystem is mounted.
+    pub path: &'static str,
+    /// Size of the filesystem in bytes.
+    pub size: u64,
+    /// Used bytes of the filesystem.
+    pub used: u64,
+    /// Bytes available to unprivileged users.
+    pub available: u64,
+}
+
+impl Usage {
+    /// Usage in percent.
+    ///
+    /// Just like `df`, space reserved for the superuser is not counted as usable and the
+    /// percentage is rounded up.
+    pub fn percent(&self) -> u32 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            return 0;
+        }
+        (self.used * 100).div_ceil(usable) as u32
+    }
+}
+
+/// Thresholds of the usage levels.
+#[derive(Debug, Clone)]
+pub struct Thresholds {
+    /// Usage in percent above which a warning is emitted.
+    warning: u32,
+    /// Usage in percent above which an error is emitted.
+    critical: u32,
+    /// Interval between the checks of the monitor.
+    pub interval: Duration,
+}
+
+impl Thresholds {
+    /// Thresholds of the given configuration.
+    pub fn from_config(config: Option<&UsageConfig>) -> Self {
+        Self {
+            warning: config
+                .and_then(|config| config.warning_threshold)
+                .unwrap_or(DEFAULT_WARNING_THRESHOLD),
+            critical: config
+                .and_then(|config| config.critical_threshold)
+                .unwrap_or(DEFAULT_CRITICAL_THRESHOLD),
+            interval: Duration::from_secs(
+                config
+                    .and_then(|config| config.interval)
+                    .unwrap_or(DEFAULT_INTERVAL)
+                    .max(1)
+                    .into(),
+            ),
+        }
+    }
+
+    /// Level of the given usage.
+    pub fn level(&self, usage: &Usage) -> UsageLevel {
+        let percent = usage.percent();
+        if percent >= self.critical {
+            UsageLevel::Critical
+        } else if percent >= self.warning {
+            UsageLevel::Warning
+        } else {
+            UsageLevel::Ok
+        }
+    }
+}
+
+/// Measure the usage of the overlay and the data partition.
+///
+/// Filesystems which are not mounted are skipped. The usage of the overlay is the usage
+/// of the filesystem its upper directory resides on, which may be the data partition.
+pub fn measure(config: &StateConfig) -> SystemResult<Vec<Usage>> {
+    let mounts = fs::read_to_string("/proc/self/mounts").whatever("unable to read mounts")?;
+    let is_mounted = |path: &str, fs_type: Option<&str>| {
+        mounts.lines().any(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            fields.next() == Some(path) && fs_type.is_none_or(|ty| fields.next() == Some(ty))
+        })
+    };
+    let mut usages = Vec::new();
+    if !matches!(config.overlay, Some(OverlayConfig::Disabled)) && is_mounted("/", Some("overlay"))
+    {
+        usages.push(statvfs_usage("overlay", "/")?);
+    }
+    if is_mounted(MOUNT_POINT_DATA, None) {
+        usages.push(statvfs_usage("data", MOUNT_POINT_DATA)?);
+    }
+    Ok(usages)
+}
+
+/// Query the usage of the filesystem mounted at the given path.
+fn statvfs_usage(name: &'static str, path: &'static str) -> SystemResult<Usage> {
+    let stat = nix::sys::statvfs::statvfs(path)
+        .whatever_with(|_| format!("unable to query filesystem of {name}"))?;
+    let fragment_size = stat.fragment_size() as u64;
+    Ok(Usage {
+        name,
+        path,
+        size: stat.blocks() as u64 * fragment_size,
+        used: (stat.blocks() - stat.blocks_free()) as u64 * fragment_size,
+        available: stat.blocks_available() as u64 * fragment_size,
+    })
+}
+
+/// Monitor the usage and report whenever the level of a filesystem changes.
+///
+/// This function never returns.
+pub fn monitor(config: &StateConfig) -> SystemResult<()> {
+    let thresholds = Thresholds::from_config(config.usage.as_ref());
+    let mut levels = HashMap::new();
+    loop {
+        match measure(config) {
+            Ok(usages) => {
+                for usage in usages {
+                    let level = thresholds.level(&usage);
+                    let previous = levels.insert(usage.name, level).unwrap_or(UsageLevel::Ok);
+                    if level != previous {
+                        report(&usage, level);
+                    }
+                }
+            }
+            Err(error) => {
+                error!("unable to measure usage: {error:?}");
+            }
+        }
+        thread::sleep(thresholds.interval);
+    }
+}
+
+/// Report a change of the usage level.
+///
+/// When connected to the journal, the message is prefixed with the syslog priority of
+/// the level such that warnings and errors show up as such.
+fn report(usage: &Usage, level: UsageLevel) {
+    let message = format!(
+        "{} usage is {} at {}% ({} of {} used)",
+        usage.name,
+        level.as_str(),
+        usage.percent(),
+        NumBytes::new(usage.used),
+        NumBytes::new(usage.used + usage.available),
+    );
+    if env::var_os("JOURNAL_STREAM").is_some() {
+        eprintln!("<{}>{message}", level.priority());
+    } else {
+        eprintln!("{message}");
+    }
+}